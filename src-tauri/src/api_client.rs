@@ -20,8 +20,13 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use crate::certificate_pinning;
+use crate::connection_manager::{self, ConnectionProfile};
 use crate::error::AppError;
-use reqwest::Client;
+use crate::message_feedback::MessageFeedback;
+use crate::metrics;
+use crate::shell_approval::ApprovalDecision;
+use crate::slash_commands::ServerCommand;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -29,7 +34,7 @@ use tokio::sync::RwLock;
 use url::Url;
 
 /// Configuration for a specific model provider
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ModelConfig {
     pub provider_id: String,
     pub model_id: String,
@@ -69,21 +74,21 @@ pub struct ProviderConfig {
 
 /// API client for OpenCode server communication
 pub struct ApiClient {
-    client: Client,
     server_url: Arc<RwLock<Option<String>>>,
     api_key: Arc<RwLock<Option<String>>>,
 }
 
+/// Default time allowed for a request, overridden per request where needed
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
 impl ApiClient {
-    /// Create a new API client
+    /// Create a new API client. Requests go through the shared pooled HTTP
+    /// client, so every `ApiClient` reuses the same connections.
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
+        certificate_pinning::shared_client()
             .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
         Ok(Self {
-            client,
             server_url: Arc::new(RwLock::new(None)),
             api_key: Arc::new(RwLock::new(None)),
         })
@@ -103,6 +108,11 @@ impl ApiClient {
         *self.api_key.write().await = Some(api_key);
     }
 
+    /// Stop sending an API key
+    pub async fn clear_api_key(&self) {
+        *self.api_key.write().await = None;
+    }
+
     /// Get the current server URL (public getter for other modules)
     pub async fn get_server_url(&self) -> Option<String> {
         self.server_url.read().await.clone()
@@ -124,6 +134,21 @@ impl ApiClient {
         self.api_key.read().await.clone()
     }
 
+    /// Profile of the connection this client talks to
+    pub async fn profile(&self) -> ConnectionProfile {
+        match self.get_server_url().await {
+            Some(url) => connection_manager::profile_for_url(&url),
+            None => ConnectionProfile::default(),
+        }
+    }
+
+    /// Add the connection profile's headers and credentials to a request
+    /// built outside this client
+    pub async fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let api_key = self.get_api_key().await;
+        self.profile().await.authorize(request, api_key.as_deref())
+    }
+
     /// Build a request with authentication headers
     async fn build_request(
         &self,
@@ -137,14 +162,15 @@ impl ApiClient {
             path.trim_start_matches('/')
         );
 
-        let mut request = self.client.request(method, &url);
-
-        // Add API key if available
-        if let Some(api_key) = self.get_api_key().await {
-            request = request.header("Authorization", format!("Bearer {}", api_key));
-        }
+        metrics::record_request(&method);
+        let profile = connection_manager::profile_for_url(&base_url);
+        let request = certificate_pinning::shared_client()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?
+            .request(method, &url)
+            .timeout(profile.request_timeout().unwrap_or(REQUEST_TIMEOUT));
 
-        Ok(request)
+        let api_key = self.get_api_key().await;
+        Ok(profile.authorize(request, api_key.as_deref()))
     }
 
     /// Get available models from the server
@@ -227,6 +253,521 @@ impl ApiClient {
         }
     }
 
+    /// Sessions on the server, as it lists them
+    pub async fn list_sessions(
+        &self,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+        match self
+            .send_json(reqwest::Method::GET, "session", None)
+            .await?
+        {
+            serde_json::Value::Array(sessions) => Ok(sessions),
+            other => Err(AppError::ParseError {
+                message: "Failed to parse sessions".to_string(),
+                details: Some(format!("Expected a list, got {}", other)),
+            }
+            .into()),
+        }
+    }
+
+    /// Start a session; the server picks a title when none is given
+    pub async fn create_session(
+        &self,
+        title: Option<&str>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let body = match title {
+            Some(title) => serde_json::json!({ "title": title }),
+            None => serde_json::json!({}),
+        };
+        self.send_json(reqwest::Method::POST, "session", Some(body))
+            .await
+    }
+
+    /// Send a text prompt and wait for the reply, an `{ info, parts }` message
+    pub async fn send_session_message(
+        &self,
+        session_id: &str,
+        content: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let body = message_body(content, &self.profile().await);
+        self.send_json(
+            reqwest::Method::POST,
+            &format!("session/{}/message", session_id),
+            Some(body),
+        )
+        .await
+    }
+
+    pub async fn rename_session(
+        &self,
+        session_id: &str,
+        title: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::PATCH,
+            &format!("session/{}", session_id),
+            Some(serde_json::json!({ "title": title })),
+        )
+        .await
+    }
+
+    pub async fn delete_session(&self, session_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::DELETE,
+            &format!("session/{}", session_id),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// A session's messages as the server sends them, each an `{ info,
+    /// parts }` object including tool calls
+    pub async fn get_session_message_parts(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+        let request = self
+            .build_request(
+                reqwest::Method::GET,
+                &format!("session/{}/message", session_id),
+            )
+            .await?;
+
+        let response = request.send().await.map_err(|e| AppError::NetworkError {
+            message: "Failed to fetch session messages".to_string(),
+            details: e.to_string(),
+            retry_after: Some(2),
+        })?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServerError {
+                status_code: response.status().as_u16(),
+                message: format!("Server responded with status: {}", response.status()),
+                details: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        let messages = response.json().await.map_err(|e| AppError::ParseError {
+            message: "Failed to parse session messages".to_string(),
+            details: Some(e.to_string()),
+        })?;
+        Ok(messages)
+    }
+
+    /// Ask the server to open `directory` as a project. Returns the
+    /// directory the server resolved, which differs when it cannot see it.
+    pub async fn open_project_directory(
+        &self,
+        directory: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let request = self
+            .build_request(reqwest::Method::GET, "path")
+            .await?
+            .query(&[("directory", directory)]);
+
+        let response = request.send().await.map_err(|e| AppError::NetworkError {
+            message: "Failed to open project directory".to_string(),
+            details: e.to_string(),
+            retry_after: Some(2),
+        })?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServerError {
+                status_code: response.status().as_u16(),
+                message: format!("Server responded with status: {}", response.status()),
+                details: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        let paths: serde_json::Value = response.json().await.map_err(|e| AppError::ParseError {
+            message: "Failed to parse project path".to_string(),
+            details: Some(e.to_string()),
+        })?;
+        Ok(paths
+            .get("directory")
+            .and_then(|directory| directory.as_str())
+            .map(str::to_string))
+    }
+
+    /// The server's provider list as sent, including each model's limits
+    pub async fn get_provider_config(
+        &self,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(reqwest::Method::GET, "config/providers", None)
+            .await
+    }
+
+    /// The server's resolved configuration, after merging its config files
+    pub async fn get_config(&self) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(reqwest::Method::GET, "config", None).await
+    }
+
+    /// Every provider the server knows, with the ids it has credentials for
+    pub async fn list_provider_auth(
+        &self,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(reqwest::Method::GET, "provider", None).await
+    }
+
+    /// Store credentials for a provider on the server
+    pub async fn set_provider_auth(
+        &self,
+        provider_id: &str,
+        credentials: serde_json::Value,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::PUT,
+            &format!("auth/{}", provider_id),
+            Some(credentials),
+        )
+        .await
+    }
+
+    /// Entries of a directory in the server's workspace, `""` for its root
+    pub async fn list_files(
+        &self,
+        path: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::GET,
+            &format!("file?path={}", urlencoding::encode(path)),
+            None,
+        )
+        .await
+    }
+
+    /// Contents of a file in the server's workspace, with its diff if changed
+    pub async fn read_file(
+        &self,
+        path: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::GET,
+            &format!("file/content?path={}", urlencoding::encode(path)),
+            None,
+        )
+        .await
+    }
+
+    /// Workspace files whose names match `query`
+    pub async fn find_files(
+        &self,
+        query: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::GET,
+            &format!("find/file?query={}", urlencoding::encode(query)),
+            None,
+        )
+        .await
+    }
+
+    /// Lines in the workspace matching `pattern`
+    pub async fn find_text(
+        &self,
+        pattern: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::GET,
+            &format!("find?pattern={}", urlencoding::encode(pattern)),
+            None,
+        )
+        .await
+    }
+
+    /// Commands the server defines, such as those in `.opencode/command`
+    pub async fn list_commands(&self) -> Result<Vec<ServerCommand>, Box<dyn std::error::Error>> {
+        let response = self
+            .send_json(reqwest::Method::GET, "command", None)
+            .await?;
+        let commands = serde_json::from_value(response).map_err(|e| AppError::ParseError {
+            message: "Failed to parse command list".to_string(),
+            details: Some(e.to_string()),
+        })?;
+        Ok(commands)
+    }
+
+    /// Agents the server can run a session as
+    pub async fn list_agents(&self) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(reqwest::Method::GET, "agent", None).await
+    }
+
+    /// Run a server-defined command in a session
+    pub async fn run_session_command(
+        &self,
+        session_id: &str,
+        command: &str,
+        arguments: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::POST,
+            &format!("session/{}/command", session_id),
+            Some(serde_json::json!({ "command": command, "arguments": arguments })),
+        )
+        .await
+    }
+
+    /// Revert a session to before `message_id`, undoing its file changes
+    pub async fn revert_message(
+        &self,
+        session_id: &str,
+        message_id: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::POST,
+            &format!("session/{}/revert", session_id),
+            Some(serde_json::json!({ "messageID": message_id })),
+        )
+        .await
+    }
+
+    /// A session's info as the server keeps it, including any pending revert
+    pub async fn get_session_info(
+        &self,
+        session_id: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::GET,
+            &format!("session/{}", session_id),
+            None,
+        )
+        .await
+    }
+
+    /// Restore everything the last revert removed
+    pub async fn unrevert_session(
+        &self,
+        session_id: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::POST,
+            &format!("session/{}/unrevert", session_id),
+            None,
+        )
+        .await
+    }
+
+    /// Summarize a session with the given model to free up context
+    pub async fn summarize_session(
+        &self,
+        session_id: &str,
+        model: &ModelConfig,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::POST,
+            &format!("session/{}/summarize", session_id),
+            Some(serde_json::json!({
+                "providerID": model.provider_id,
+                "modelID": model.model_id,
+            })),
+        )
+        .await
+    }
+
+    /// Share a session, or stop sharing it
+    pub async fn set_session_shared(
+        &self,
+        session_id: &str,
+        shared: bool,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let method = if shared {
+            reqwest::Method::POST
+        } else {
+            reqwest::Method::DELETE
+        };
+        self.send_json(method, &format!("session/{}/share", session_id), None)
+            .await
+    }
+
+    /// Stop the response a session is generating
+    pub async fn abort_session(
+        &self,
+        session_id: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.send_json(
+            reqwest::Method::POST,
+            &format!("session/{}/abort", session_id),
+            None,
+        )
+        .await
+    }
+
+    /// Pass a message rating on to the server. Returns `false` when the
+    /// server has no feedback endpoint.
+    pub async fn send_message_feedback(
+        &self,
+        feedback: &MessageFeedback,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let request = self
+            .build_request(
+                reqwest::Method::POST,
+                &format!(
+                    "session/{}/message/{}/feedback",
+                    feedback.session_id, feedback.message_id
+                ),
+            )
+            .await?
+            .json(&serde_json::json!({
+                "rating": feedback.rating,
+                "comment": feedback.comment,
+            }));
+
+        let response = request.send().await.map_err(|e| AppError::NetworkError {
+            message: "Failed to send message feedback".to_string(),
+            details: e.to_string(),
+            retry_after: Some(2),
+        })?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND
+            | reqwest::StatusCode::METHOD_NOT_ALLOWED
+            | reqwest::StatusCode::NOT_IMPLEMENTED => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => Err(AppError::ServerError {
+                status_code: status.as_u16(),
+                message: format!("Server responded with status: {}", status),
+                details: response.text().await.unwrap_or_default(),
+            }
+            .into()),
+        }
+    }
+
+    /// Send a request with an optional JSON body and return the JSON
+    /// response, or `null` for an empty one
+    async fn send_json(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let mut request = self.build_request(method, path).await?;
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await.map_err(|e| AppError::NetworkError {
+            message: format!("Failed to call {}", path),
+            details: e.to_string(),
+            retry_after: Some(2),
+        })?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServerError {
+                status_code: response.status().as_u16(),
+                message: format!("Server responded with status: {}", response.status()),
+                details: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        let text = response.text().await.map_err(|e| AppError::NetworkError {
+            message: format!("Failed to read response from {}", path),
+            details: e.to_string(),
+            retry_after: None,
+        })?;
+        if text.trim().is_empty() {
+            return Ok(serde_json::Value::Null);
+        }
+        Ok(
+            serde_json::from_str(&text).map_err(|e| AppError::ParseError {
+                message: format!("Failed to parse response from {}", path),
+                details: Some(e.to_string()),
+            })?,
+        )
+    }
+
+    /// Answer a permission the agent is waiting on
+    pub async fn respond_to_permission(
+        &self,
+        session_id: &str,
+        permission_id: &str,
+        response: ApprovalDecision,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let request = self
+            .build_request(
+                reqwest::Method::POST,
+                &format!("session/{}/permissions/{}", session_id, permission_id),
+            )
+            .await?
+            .json(&serde_json::json!({ "response": response }));
+
+        let response = request.send().await.map_err(|e| AppError::NetworkError {
+            message: "Failed to answer permission request".to_string(),
+            details: e.to_string(),
+            retry_after: Some(2),
+        })?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServerError {
+                status_code: response.status().as_u16(),
+                message: format!("Server responded with status: {}", response.status()),
+                details: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Post a message body to a session in chunks, calling `on_progress`
+    /// with the bytes sent so far as each chunk goes out
+    pub async fn post_session_message_streamed<F>(
+        &self,
+        session_id: &str,
+        body: Vec<u8>,
+        mut on_progress: F,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>>
+    where
+        F: FnMut(u64) + Send + Sync + 'static,
+    {
+        let total = body.len();
+        let chunks: Vec<Vec<u8>> = body
+            .chunks(crate::context_upload::UPLOAD_CHUNK_BYTES)
+            .map(<[u8]>::to_vec)
+            .collect();
+        let mut sent = 0u64;
+        let stream = futures_util::stream::iter(chunks.into_iter().map(move |chunk| {
+            sent += chunk.len() as u64;
+            on_progress(sent);
+            Ok::<_, std::io::Error>(chunk)
+        }));
+
+        let request = self
+            .build_request(
+                reqwest::Method::POST,
+                &format!("session/{}/message", session_id),
+            )
+            .await?
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::CONTENT_LENGTH, total)
+            // Large files take longer than the default request timeout
+            .timeout(std::time::Duration::from_secs(300))
+            .body(reqwest::Body::wrap_stream(stream));
+
+        let response = request.send().await.map_err(|e| AppError::NetworkError {
+            message: "Failed to upload to session".to_string(),
+            details: e.to_string(),
+            retry_after: Some(2),
+        })?;
+
+        if !response.status().is_success() {
+            return Err(AppError::ServerError {
+                status_code: response.status().as_u16(),
+                message: format!("Server responded with status: {}", response.status()),
+                details: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        let message = response.json().await.map_err(|e| AppError::ParseError {
+            message: "Failed to parse upload response".to_string(),
+            details: Some(e.to_string()),
+        })?;
+        Ok(message)
+    }
+
     /// Get server information
     pub async fn get_server_info(&self) -> Result<ServerInfo, Box<dyn std::error::Error>> {
         let request = self.build_request(reqwest::Method::GET, "info").await?;
@@ -279,6 +820,20 @@ impl Default for ApiClient {
     }
 }
 
+/// Body of a text message, with the model and system prompt the
+/// connection's profile defaults to
+fn message_body(content: &str, profile: &ConnectionProfile) -> serde_json::Value {
+    let mut body = serde_json::json!({ "parts": [{ "type": "text", "text": content }] });
+    if let Some(model) = &profile.default_model {
+        body["providerID"] = serde_json::json!(model.provider_id);
+        body["modelID"] = serde_json::json!(model.model_id);
+    }
+    if let Some(system) = &profile.system_prompt {
+        body["system"] = serde_json::json!(system);
+    }
+    body
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,6 +906,9 @@ mod tests {
         let api_key = client.get_api_key().await;
         assert!(api_key.is_some(), "Should have API key after setting");
         assert_eq!(api_key.unwrap(), "test-key-123");
+
+        client.clear_api_key().await;
+        assert!(client.get_api_key().await.is_none());
     }
 
     #[tokio::test]
@@ -396,4 +954,27 @@ mod tests {
         assert!(info.description.is_some());
         assert!(info.features.as_ref().unwrap().len() == 2);
     }
+
+    #[test]
+    fn test_message_body_uses_profile_defaults() {
+        let body = message_body("hi", &ConnectionProfile::default());
+        assert_eq!(
+            body,
+            serde_json::json!({ "parts": [{ "type": "text", "text": "hi" }] })
+        );
+
+        let profile = ConnectionProfile {
+            default_model: Some(ModelConfig {
+                provider_id: "anthropic".to_string(),
+                model_id: "claude".to_string(),
+            }),
+            system_prompt: Some("Be brief".to_string()),
+            ..Default::default()
+        };
+        let body = message_body("hi", &profile);
+        assert_eq!(body["providerID"], "anthropic");
+        assert_eq!(body["modelID"], "claude");
+        assert_eq!(body["system"], "Be brief");
+        assert_eq!(body["parts"][0]["text"], "hi");
+    }
 }
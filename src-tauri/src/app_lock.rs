@@ -0,0 +1,509 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use crate::settings::AppLockSettings;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Tauri event emitted when the app locks itself after inactivity
+pub const APP_LOCKED_EVENT: &str = "app-locked";
+
+/// Shortest PIN accepted
+pub const MIN_PIN_LEN: usize = 4;
+
+/// Failed unlock attempts allowed before unlocking is paused
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+
+/// How long unlocking is paused after too many failures
+const LOCKOUT: Duration = Duration::from_secs(30);
+
+/// Commands the lock screen and tray may still call while the app is
+/// locked. None of them expose chat history, prompts, files or secrets.
+const ALLOWED_WHILE_LOCKED: &[&str] = &[
+    "greet",
+    "get_connection_status",
+    "get_active_connections",
+    "get_server_features",
+    "get_app_resource_usage",
+    "get_app_status",
+    "log_frontend_error",
+    "get_subsystem_status",
+    "get_startup_report",
+    "get_profile_encryption_status",
+    "unlock_profile",
+    "get_app_lock_status",
+    "report_user_activity",
+    "lock_app",
+    "unlock_app",
+    "get_settings",
+    "get_locale",
+    "get_tray_status",
+    "check_for_updates",
+    "get_setup_state",
+];
+
+/// Commands deliberately refused while the app is locked. Anything not
+/// allowed is refused anyway; this list only exists so a test can check that
+/// every command in `generate_handler!` was classified on purpose.
+#[cfg(test)]
+const PROTECTED_COMMANDS: &[&str] = &[
+    "connect_to_server",
+    "test_server_connection",
+    "get_current_connection",
+    "disconnect_from_server",
+    "set_primary_connection",
+    "get_saved_connections",
+    "test_all_saved_connections",
+    "save_connection",
+    "get_last_used_connection",
+    "list_sessions",
+    "create_session",
+    "send_message",
+    "get_recovered_work",
+    "resend_recovered_prompt",
+    "discard_recovered_work",
+    "get_outbox",
+    "retry_outbox",
+    "remove_outbox_item",
+    "get_session_messages",
+    "get_message_count",
+    "get_message_range",
+    "get_session_file_changes",
+    "get_context_usage",
+    "get_budget_status",
+    "set_session_budget",
+    "override_budget",
+    "get_session_diagnostics",
+    "get_server_config",
+    "get_provider_auth_status",
+    "authenticate_provider",
+    "list_checkpoints",
+    "revert_to_checkpoint",
+    "list_slash_commands",
+    "rate_message",
+    "clear_message_rating",
+    "list_message_feedback",
+    "export_rated_exchanges",
+    "run_slash_command",
+    "extract_code_blocks",
+    "apply_code_block_to_file",
+    "list_server_files",
+    "read_server_file",
+    "search_server_files",
+    "list_agents",
+    "list_pending_shell_approvals",
+    "open_terminal",
+    "write_terminal",
+    "resize_terminal",
+    "close_terminal",
+    "list_terminals",
+    "respond_to_shell_approval",
+    "subscribe_to_chat_events",
+    "delete_session",
+    "update_session_title",
+    "get_session_stats",
+    "get_available_models",
+    "get_model_preferences",
+    "set_model_preferences",
+    "set_default_model",
+    "start_message_stream",
+    "stop_message_stream",
+    "get_active_streams",
+    "get_application_logs",
+    "query_logs",
+    "subscribe_to_logs",
+    "unsubscribe_from_logs",
+    "clear_application_logs",
+    "export_support_bundle",
+    "get_log_level",
+    "set_log_level",
+    "get_log_targets",
+    "get_remote_logging",
+    "set_remote_logging",
+    "get_error_reporting_settings",
+    "set_error_reporting_enabled",
+    "set_log_target_level",
+    "get_retry_policy",
+    "set_retry_policy",
+    "get_error_summary",
+    "get_connection_health_history",
+    "get_connection_uptime_stats",
+    "report_problem",
+    "enable_profile_encryption",
+    "disable_profile_encryption",
+    "enable_app_lock",
+    "disable_app_lock",
+    "list_secrets",
+    "delete_secret",
+    "store_connection_secret",
+    "get_connection_secret",
+    "rotate_connection_credentials",
+    "set_connection_certificate_pin",
+    "get_connection_profile",
+    "update_connection_profile",
+    "store_proxy_password",
+    "test_proxy_settings",
+    "get_server_certificate_fingerprint",
+    "get_security_audit_log",
+    "set_secret_scanning",
+    "scan_outgoing_content",
+    "validate_attachment",
+    "upload_context_file",
+    "transcribe_audio",
+    "set_transcription_api_key",
+    "scan_project_directory",
+    "attach_project_directory",
+    "extract_attachment_archive",
+    "update_settings",
+    "download_update",
+    "install_update_and_restart",
+    "get_notification_settings",
+    "set_notification_settings",
+    "set_focus_mode",
+    "open_quick_prompt",
+    "get_quick_prompt_settings",
+    "set_quick_prompt_shortcut",
+    "submit_quick_prompt",
+    "list_plugins",
+    "reload_plugins",
+    "enable_plugin",
+    "disable_plugin",
+    "invoke_plugin_command",
+    "set_locale",
+    "get_collected_telemetry",
+    "set_telemetry_enabled",
+    "clear_collected_telemetry",
+    "create_backup",
+    "export_config_profile",
+    "export_connections",
+    "import_connections",
+    "restore_backup",
+    "open_session_window",
+    "close_session_window",
+    "set_window_session",
+    "list_session_windows",
+    "complete_setup",
+    "list_workspaces",
+    "get_active_workspace",
+    "create_workspace",
+    "open_workspace",
+    "delete_workspace",
+    "add_session_to_workspace",
+    "remove_session_from_workspace",
+    "list_prompts",
+    "create_prompt",
+    "update_prompt",
+    "delete_prompt",
+    "render_prompt",
+    "send_prompt",
+    "export_prompts",
+    "import_prompts",
+    "import_conversations",
+];
+
+/// Whether a command must be refused while the app is locked
+pub fn is_protected(command: &str) -> bool {
+    !ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+/// Hash a PIN for storage in settings
+pub fn hash_pin(pin: &str) -> Result<String, AppError> {
+    if pin.chars().count() < MIN_PIN_LEN {
+        return Err(AppError::ValidationError {
+            field: "pin".to_string(),
+            message: format!("must be at least {} characters", MIN_PIN_LEN),
+        });
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Other {
+            message: format!("Failed to hash PIN: {}", e),
+        })
+}
+
+/// Check a PIN against a stored hash
+pub fn verify_pin(pin: &str, pin_hash: &str) -> bool {
+    PasswordHash::new(pin_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(pin.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Current lock state for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub idle_timeout_minutes: Option<u32>,
+}
+
+struct LockState {
+    settings: Option<AppLockSettings>,
+    last_activity: Instant,
+    locked: bool,
+    failed_attempts: u32,
+    paused_until: Option<Instant>,
+}
+
+/// Idle lock shared by the command guard, the idle timer and the commands
+#[derive(Clone)]
+pub struct AppLock {
+    state: Arc<Mutex<LockState>>,
+}
+
+impl AppLock {
+    /// Create the lock; an enabled lock starts locked so a restart needs the PIN
+    pub fn new(settings: Option<AppLockSettings>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LockState {
+                locked: settings.is_some(),
+                settings,
+                last_activity: Instant::now(),
+                failed_attempts: 0,
+                paused_until: None,
+            })),
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, LockState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => {
+                eprintln!("[ERROR] AppLock: state mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Replace the lock settings after they were changed; stays unlocked
+    pub fn configure(&self, settings: Option<AppLockSettings>) {
+        let mut state = self.lock_state();
+        state.settings = settings;
+        state.locked = false;
+        state.last_activity = Instant::now();
+    }
+
+    /// Note user activity reported by the frontend
+    pub fn record_activity(&self) {
+        let mut state = self.lock_state();
+        if !state.locked {
+            state.last_activity = Instant::now();
+        }
+    }
+
+    /// Lock if the idle timeout elapsed by `now`. Returns true only when this
+    /// call engaged the lock.
+    pub fn check_idle_at(&self, now: Instant) -> bool {
+        let mut state = self.lock_state();
+        let Some(timeout_minutes) = state.settings.as_ref().map(|s| s.idle_timeout_minutes) else {
+            return false;
+        };
+        if state.locked {
+            return false;
+        }
+        let timeout = Duration::from_secs(u64::from(timeout_minutes) * 60);
+        if now.saturating_duration_since(state.last_activity) >= timeout {
+            state.locked = true;
+            return true;
+        }
+        false
+    }
+
+    pub fn check_idle(&self) -> bool {
+        self.check_idle_at(Instant::now())
+    }
+
+    /// Whether protected commands are currently refused
+    pub fn is_locked(&self) -> bool {
+        self.check_idle();
+        self.lock_state().locked
+    }
+
+    /// Engage the lock immediately
+    pub fn lock(&self) {
+        let mut state = self.lock_state();
+        if state.settings.is_some() {
+            state.locked = true;
+        }
+    }
+
+    /// Verify the PIN and release the lock. Unlocking pauses after repeated failures.
+    pub fn unlock(&self, pin: &str) -> Result<(), AppError> {
+        let mut state = self.lock_state();
+        let Some(settings) = state.settings.clone() else {
+            return Ok(());
+        };
+        if let Some(paused_until) = state.paused_until {
+            let remaining = paused_until.saturating_duration_since(Instant::now());
+            if !remaining.is_zero() {
+                return Err(AppError::AuthError {
+                    message: format!(
+                        "Too many failed attempts. Try again in {} seconds.",
+                        remaining.as_secs().max(1)
+                    ),
+                    details: "App lock paused".to_string(),
+                });
+            }
+            state.paused_until = None;
+        }
+
+        if !verify_pin(pin, &settings.pin_hash) {
+            state.failed_attempts += 1;
+            if state.failed_attempts >= MAX_FAILED_ATTEMPTS {
+                state.failed_attempts = 0;
+                state.paused_until = Some(Instant::now() + LOCKOUT);
+            }
+            return Err(AppError::AuthError {
+                message: "Incorrect PIN".to_string(),
+                details: "App lock verification failed".to_string(),
+            });
+        }
+
+        state.locked = false;
+        state.failed_attempts = 0;
+        state.last_activity = Instant::now();
+        Ok(())
+    }
+
+    pub fn status(&self) -> AppLockStatus {
+        self.check_idle();
+        let state = self.lock_state();
+        AppLockStatus {
+            enabled: state.settings.is_some(),
+            locked: state.locked,
+            idle_timeout_minutes: state.settings.as_ref().map(|s| s.idle_timeout_minutes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_lock(idle_timeout_minutes: u32) -> AppLock {
+        let lock = AppLock::new(Some(AppLockSettings {
+            idle_timeout_minutes,
+            pin_hash: hash_pin("4321").expect("Should hash PIN"),
+        }));
+        lock.unlock("4321").expect("Should unlock");
+        lock
+    }
+
+    #[test]
+    fn test_disabled_lock_never_locks() {
+        let lock = AppLock::new(None);
+        lock.lock();
+        assert!(!lock.check_idle_at(Instant::now() + Duration::from_secs(86_400)));
+        assert!(!lock.is_locked());
+    }
+
+    #[test]
+    fn test_locks_after_idle_timeout() {
+        let lock = create_test_lock(5);
+        let now = Instant::now();
+
+        assert!(!lock.check_idle_at(now + Duration::from_secs(60)));
+        assert!(lock.check_idle_at(now + Duration::from_secs(5 * 60 + 1)));
+        // Only the transition is reported
+        assert!(!lock.check_idle_at(now + Duration::from_secs(10 * 60)));
+        assert!(lock.is_locked());
+    }
+
+    #[test]
+    fn test_unlock_requires_correct_pin() {
+        let lock = create_test_lock(5);
+        lock.lock();
+
+        assert!(lock.unlock("0000").is_err());
+        assert!(lock.is_locked());
+        lock.unlock("4321")
+            .expect("Should unlock with the right PIN");
+        assert!(!lock.is_locked());
+    }
+
+    #[test]
+    fn test_repeated_failures_pause_unlocking() {
+        let lock = create_test_lock(5);
+        lock.lock();
+
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            assert!(lock.unlock("0000").is_err());
+        }
+        // Even the right PIN is refused during the pause
+        assert!(lock.unlock("4321").is_err());
+        assert!(lock.is_locked());
+    }
+
+    #[test]
+    fn test_protected_commands() {
+        assert!(is_protected("get_session_messages"));
+        assert!(!is_protected("unlock_app"));
+        assert!(!is_protected("get_connection_status"));
+        assert!(is_protected("read_server_file"));
+        assert!(is_protected("some_future_command"));
+    }
+
+    #[test]
+    fn test_every_command_is_classified() {
+        let source = include_str!("lib.rs");
+        let start = source
+            .find("generate_handler![")
+            .expect("lib.rs should register commands")
+            + "generate_handler![".len();
+        let end = start
+            + source[start..]
+                .find(']')
+                .expect("handler list should close");
+        let commands: Vec<&str> = source[start..end]
+            .lines()
+            .map(|line| line.split("//").next().unwrap_or_default())
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .collect();
+
+        for command in &commands {
+            let allowed = ALLOWED_WHILE_LOCKED.contains(command);
+            let protected = PROTECTED_COMMANDS.contains(command);
+            assert!(
+                allowed != protected,
+                "{command} must be on exactly one app lock list"
+            );
+        }
+        for command in ALLOWED_WHILE_LOCKED.iter().chain(PROTECTED_COMMANDS) {
+            assert!(
+                commands.contains(command),
+                "{command} is not a registered command"
+            );
+        }
+    }
+}
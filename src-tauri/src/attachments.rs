@@ -0,0 +1,440 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::error::AppError;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+
+/// Largest file accepted as an attachment
+pub const MAX_ATTACHMENT_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Most entries an archive may contain
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// Most bytes an archive may expand to
+const MAX_UNPACKED_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Highest uncompressed-to-compressed ratio before an archive is treated
+/// as a decompression bomb
+const MAX_COMPRESSION_RATIO: u64 = 100;
+
+/// Bytes inspected to decide whether a file is text
+const SNIFF_LEN: usize = 8192;
+
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "json", "yaml", "yml", "toml", "csv", "log", "xml", "html", "css",
+    "js", "jsx", "ts", "tsx", "rs", "py", "go", "java", "c", "h", "cpp", "hpp", "rb", "sh", "sql",
+    "svg",
+];
+
+/// A file that passed validation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidatedAttachment {
+    pub name: String,
+    pub size: u64,
+    /// MIME type detected from the contents
+    pub mime_type: String,
+    /// Entry count and expanded size for archives
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchiveSummary {
+    pub entries: usize,
+    pub unpacked_size: u64,
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::ValidationError {
+        field: "attachment".to_string(),
+        message: message.into(),
+    }
+}
+
+/// MIME type from magic bytes, `None` when nothing matched
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"PK\x05\x06", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"MZ", "application/x-msdownload"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+        (b"\xca\xfe\xba\xbe", "application/x-mach-binary"),
+    ];
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
+fn looks_like_text(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    !sample.contains(&0)
+        && match std::str::from_utf8(sample) {
+            Ok(_) => true,
+            // A multi-byte character may be cut off at the sample boundary
+            Err(e) => e.error_len().is_none(),
+        }
+}
+
+/// MIME types a file with this extension may contain
+fn expected_mimes(extension: &str) -> Option<&'static [&'static str]> {
+    Some(match extension {
+        "png" => &["image/png"],
+        "jpg" | "jpeg" => &["image/jpeg"],
+        "gif" => &["image/gif"],
+        "webp" => &["image/webp"],
+        "pdf" => &["application/pdf"],
+        "zip" | "docx" | "xlsx" | "pptx" | "jar" => &["application/zip"],
+        "gz" | "tgz" => &["application/gzip"],
+        "exe" | "dll" => &["application/x-msdownload"],
+        extension if TEXT_EXTENSIONS.contains(&extension) => &["text/plain"],
+        _ => return None,
+    })
+}
+
+fn is_executable(mime: &str) -> bool {
+    matches!(
+        mime,
+        "application/x-msdownload" | "application/x-executable" | "application/x-mach-binary"
+    )
+}
+
+/// Entry count and expanded size of a zip, rejecting decompression bombs
+fn inspect_zip(bytes: &[u8]) -> Result<ArchiveSummary, AppError> {
+    let archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| invalid(format!("Corrupt zip archive: {}", e)))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err(invalid(format!(
+            "Archive has {} entries (limit {})",
+            archive.len(),
+            MAX_ARCHIVE_ENTRIES
+        )));
+    }
+
+    let mut archive = archive;
+    let mut unpacked_size: u64 = 0;
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index_raw(index)
+            .map_err(|e| invalid(format!("Corrupt zip entry: {}", e)))?;
+        if entry.enclosed_name().is_none() {
+            return Err(invalid(format!(
+                "Archive entry escapes the extraction directory: {}",
+                entry.name()
+            )));
+        }
+        unpacked_size = unpacked_size.saturating_add(entry.size());
+    }
+    check_expansion(bytes.len() as u64, unpacked_size)?;
+    Ok(ArchiveSummary {
+        entries: archive.len(),
+        unpacked_size,
+    })
+}
+
+/// Expanded size of a gzip stream, decompressing at most one byte past the
+/// limit so a bomb is never fully inflated
+fn inspect_gzip(bytes: &[u8]) -> Result<ArchiveSummary, AppError> {
+    let mut decoder = GzDecoder::new(bytes).take(MAX_UNPACKED_BYTES + 1);
+    let unpacked_size = std::io::copy(&mut decoder, &mut std::io::sink())
+        .map_err(|e| invalid(format!("Corrupt gzip data: {}", e)))?;
+    check_expansion(bytes.len() as u64, unpacked_size)?;
+    Ok(ArchiveSummary {
+        entries: 1,
+        unpacked_size,
+    })
+}
+
+fn check_expansion(packed_size: u64, unpacked_size: u64) -> Result<(), AppError> {
+    if unpacked_size > MAX_UNPACKED_BYTES {
+        return Err(invalid(format!(
+            "Archive expands beyond {} MB",
+            MAX_UNPACKED_BYTES / (1024 * 1024)
+        )));
+    }
+    if unpacked_size / packed_size.max(1) > MAX_COMPRESSION_RATIO {
+        return Err(invalid(
+            "Archive compression ratio suggests a decompression bomb",
+        ));
+    }
+    Ok(())
+}
+
+/// Check an in-memory attachment: size, contents against extension,
+/// executables and archive safety
+pub fn validate_bytes(name: &str, bytes: &[u8]) -> Result<ValidatedAttachment, AppError> {
+    let size = bytes.len() as u64;
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(invalid(format!(
+            "File is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let extension = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mime_type = match sniff_mime(bytes) {
+        Some(mime) => mime,
+        None if looks_like_text(bytes) => "text/plain",
+        None => "application/octet-stream",
+    };
+
+    if is_executable(mime_type) {
+        return Err(invalid("Executable files cannot be attached"));
+    }
+    if let Some(expected) = expected_mimes(&extension) {
+        if !expected.contains(&mime_type) {
+            return Err(invalid(format!(
+                "Contents ({}) do not match the .{} extension",
+                mime_type, extension
+            )));
+        }
+    }
+
+    let archive = match mime_type {
+        "application/zip" => Some(inspect_zip(bytes)?),
+        "application/gzip" => Some(inspect_gzip(bytes)?),
+        _ => None,
+    };
+
+    Ok(ValidatedAttachment {
+        name: name.to_string(),
+        size,
+        mime_type: mime_type.to_string(),
+        archive,
+    })
+}
+
+/// Validate a file on disk without following symlinks or reading more
+/// than the size limit
+pub fn validate_file(path: &Path) -> Result<(ValidatedAttachment, Vec<u8>), AppError> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_file() {
+        return Err(invalid("Only regular files can be attached"));
+    }
+    if metadata.len() > MAX_ATTACHMENT_BYTES {
+        return Err(invalid(format!(
+            "File is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let bytes = std::fs::read(path)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let attachment = validate_bytes(&name, &bytes)?;
+    Ok((attachment, bytes))
+}
+
+/// Resolve an archive entry name under `destination`, rejecting absolute
+/// paths and `..` components
+fn safe_entry_path(destination: &Path, entry_name: &str) -> Result<PathBuf, AppError> {
+    let relative = Path::new(entry_name);
+    let escapes = relative.components().any(|component| {
+        matches!(
+            component,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    });
+    if escapes || entry_name.contains('\\') {
+        return Err(invalid(format!(
+            "Archive entry escapes the extraction directory: {}",
+            entry_name
+        )));
+    }
+    Ok(destination.join(relative))
+}
+
+/// Extract a validated zip into `destination`. Refuses destinations inside
+/// any of `protected_dirs` (such as the config directory), symlink entries
+/// and entries that would land outside `destination`.
+pub fn extract_zip(
+    bytes: &[u8],
+    destination: &Path,
+    protected_dirs: &[PathBuf],
+) -> Result<Vec<PathBuf>, AppError> {
+    inspect_zip(bytes)?;
+
+    std::fs::create_dir_all(destination)?;
+    let destination = destination.canonicalize()?;
+    for protected in protected_dirs {
+        let protected = protected
+            .canonicalize()
+            .unwrap_or_else(|_| protected.clone());
+        if destination.starts_with(&protected) {
+            return Err(invalid(format!(
+                "Cannot extract into {}",
+                protected.display()
+            )));
+        }
+    }
+
+    let mut archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| invalid(format!("Corrupt zip archive: {}", e)))?;
+    let mut extracted = Vec::new();
+    let mut written: u64 = 0;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| invalid(format!("Corrupt zip entry: {}", e)))?;
+        if entry.is_symlink() {
+            return Err(invalid(format!(
+                "Archive contains a symbolic link: {}",
+                entry.name()
+            )));
+        }
+        let path = safe_entry_path(&destination, entry.name())?;
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Sizes in the central directory can lie, so cap what is written
+        let remaining = MAX_UNPACKED_BYTES.saturating_sub(written);
+        let mut contents = Vec::new();
+        (&mut entry)
+            .take(remaining + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| invalid(format!("Failed to extract {}: {}", entry.name(), e)))?;
+        written += contents.len() as u64;
+        if written > MAX_UNPACKED_BYTES {
+            return Err(invalid("Archive expands beyond the extraction limit"));
+        }
+        std::fs::write(&path, contents)?;
+        extracted.push(path);
+    }
+    Ok(extracted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn create_test_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_accepts_matching_files() {
+        let png = validate_bytes("shot.png", b"\x89PNG\r\n\x1a\n....").unwrap();
+        assert_eq!(png.mime_type, "image/png");
+        let text = validate_bytes("notes.md", "# Notes — ok".as_bytes()).unwrap();
+        assert_eq!(text.mime_type, "text/plain");
+        let unknown = validate_bytes("data.bin", &[1, 0, 2, 0]).unwrap();
+        assert_eq!(unknown.mime_type, "application/octet-stream");
+    }
+
+    #[test]
+    fn test_rejects_mismatch_and_executables() {
+        assert!(validate_bytes("photo.png", b"%PDF-1.7").is_err());
+        assert!(validate_bytes("readme.txt", b"MZ\x90\x00").is_err());
+        assert!(validate_bytes("tool.dat", b"\x7fELF\x02").is_err());
+        assert!(validate_bytes("script.sh", &[0, 159, 146, 150]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_file() {
+        let bytes = vec![b'a'; MAX_ATTACHMENT_BYTES as usize + 1];
+        assert!(validate_bytes("big.txt", &bytes).is_err());
+    }
+
+    #[test]
+    fn test_detects_compression_bomb() {
+        let zeros = vec![0u8; 4 * 1024 * 1024];
+        let zip_with = |method| {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            let options = SimpleFileOptions::default().compression_method(method);
+            writer.start_file("zeros.bin", options).unwrap();
+            writer.write_all(&zeros).unwrap();
+            writer.finish().unwrap().into_inner()
+        };
+
+        let stored = zip_with(zip::CompressionMethod::Stored);
+        assert!(validate_bytes("stored.zip", &stored).is_ok());
+        let deflated = zip_with(zip::CompressionMethod::Deflated);
+        assert!(validate_bytes("bomb.zip", &deflated).is_err());
+    }
+
+    #[test]
+    fn test_extract_rejects_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_test_zip(&[("../escape.txt", b"bad")]);
+        assert!(validate_bytes("evil.zip", &archive).is_err());
+        assert!(extract_zip(&archive, temp_dir.path(), &[]).is_err());
+        assert!(!temp_dir
+            .path()
+            .parent()
+            .unwrap()
+            .join("escape.txt")
+            .exists());
+    }
+
+    #[test]
+    fn test_extract_into_destination_but_not_protected_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_test_zip(&[("docs/readme.md", b"hello"), ("a.txt", b"a")]);
+
+        let config_dir = temp_dir.path().join("config");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let protected = vec![config_dir.clone()];
+        assert!(extract_zip(&archive, &config_dir.join("sub"), &protected).is_err());
+
+        let destination = temp_dir.path().join("out");
+        let extracted = extract_zip(&archive, &destination, &protected).unwrap();
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(destination.join("docs/readme.md")).unwrap(),
+            "hello"
+        );
+    }
+}
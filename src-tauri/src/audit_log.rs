@@ -0,0 +1,412 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+
+/// Append-only log of security-sensitive operations, one JSON entry per line
+pub const AUDIT_LOG_FILE_NAME: &str = "security_audit.jsonl";
+
+/// `prev_hash` of the first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Log used by `record`, installed once at startup
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+/// Kind of operation recorded in the audit log
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    CredentialStored,
+    CredentialUsed,
+    CredentialRotated,
+    CredentialDeleted,
+    ConnectionChanged,
+    DataExported,
+    DataImported,
+    SettingsChanged,
+    ShellCommandApproved,
+    TerminalOpened,
+}
+
+/// One hash-chained audit record. `hash` covers every other field, and
+/// `prev_hash` links it to the entry before, so editing or removing a line
+/// breaks the chain from that point on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    /// What the operation touched, e.g. a secret name or connection
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> String {
+        let unhashed = serde_json::json!({
+            "sequence": self.sequence,
+            "timestamp": self.timestamp,
+            "action": self.action,
+            "target": self.target,
+            "details": self.details,
+            "prev_hash": self.prev_hash,
+        });
+        digest(&SHA256, unhashed.to_string().as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Audit entries plus the result of checking their hash chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogReport {
+    pub entries: Vec<AuditEntry>,
+    /// Whether every entry's hash and link verified
+    pub verified: bool,
+    /// Line number (1-based) of the first entry that failed verification
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_invalid_line: Option<usize>,
+}
+
+/// Where the next entry chains on, as of the last read or write
+#[derive(Debug, Clone)]
+struct Tail {
+    sequence: u64,
+    hash: String,
+    /// File length this was taken at; any other length means the file was
+    /// changed behind our back and must be read again
+    file_len: u64,
+    /// The file ends in a partial line that the next entry must not extend
+    needs_newline: bool,
+}
+
+pub struct AuditLog {
+    config_dir: PathBuf,
+    /// Serializes appends so two writers never chain off the same entry,
+    /// and caches the tail so appends don't re-read the whole log
+    tail: Mutex<Option<Tail>>,
+}
+
+impl AuditLog {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            tail: Mutex::new(None),
+        }
+    }
+
+    fn get_log_file_path(&self) -> PathBuf {
+        self.config_dir.join(AUDIT_LOG_FILE_NAME)
+    }
+
+    fn read_contents(&self) -> Result<String, AppError> {
+        let path = self.get_log_file_path();
+        if !path.exists() {
+            return Ok(String::new());
+        }
+        std::fs::read_to_string(&path).map_err(|e| AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: "Failed to read security audit log".to_string(),
+            details: e.to_string(),
+        })
+    }
+
+    fn read_lines(&self) -> Result<Vec<String>, AppError> {
+        Ok(self
+            .read_contents()?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Find the last complete entry on disk. A line cut short by a crash is
+    /// skipped, so the chain continues from the entry before it.
+    fn read_tail(&self) -> Result<Tail, AppError> {
+        let contents = self.read_contents()?;
+        let last = contents
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str::<AuditEntry>(line).ok());
+        Ok(Tail {
+            sequence: last.as_ref().map_or(0, |entry| entry.sequence + 1),
+            hash: last.map_or_else(|| GENESIS_HASH.to_string(), |entry| entry.hash),
+            file_len: contents.len() as u64,
+            needs_newline: !contents.is_empty() && !contents.ends_with('\n'),
+        })
+    }
+
+    /// Append an entry chained to the last one on disk
+    pub fn append(
+        &self,
+        action: AuditAction,
+        target: &str,
+        details: Option<String>,
+    ) -> Result<AuditEntry, AppError> {
+        let mut cached = match self.tail.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                eprintln!("[ERROR] AuditLog append: tail mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        };
+
+        let path = self.get_log_file_path();
+        let file_len = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        let tail = match cached.take() {
+            Some(tail) if tail.file_len == file_len => tail,
+            _ => self.read_tail()?,
+        };
+
+        let mut entry = AuditEntry {
+            sequence: tail.sequence,
+            timestamp: Utc::now(),
+            action,
+            target: target.to_string(),
+            details,
+            prev_hash: tail.hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let fs_error = |e: std::io::Error| AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: "Failed to append to security audit log".to_string(),
+            details: e.to_string(),
+        };
+        std::fs::create_dir_all(&self.config_dir).map_err(fs_error)?;
+        let mut line = if tail.needs_newline {
+            "\n".to_string()
+        } else {
+            String::new()
+        };
+        line.push_str(&serde_json::to_string(&entry)?);
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(fs_error)?;
+
+        *cached = Some(Tail {
+            sequence: entry.sequence + 1,
+            hash: entry.hash.clone(),
+            file_len: file_len + line.len() as u64,
+            needs_newline: false,
+        });
+        Ok(entry)
+    }
+
+    /// Read the log and verify its hash chain. `limit` keeps only the most
+    /// recent entries in the report; verification always covers all of them.
+    pub fn report(&self, limit: Option<usize>) -> Result<AuditLogReport, AppError> {
+        let mut entries = Vec::new();
+        let mut first_invalid_line = None;
+        let mut prev_hash = GENESIS_HASH.to_string();
+
+        for (index, line) in self.read_lines()?.iter().enumerate() {
+            let valid = match serde_json::from_str::<AuditEntry>(line) {
+                Ok(entry) => {
+                    let valid = entry.prev_hash == prev_hash
+                        && entry.sequence == entries.len() as u64
+                        && entry.hash == entry.compute_hash();
+                    prev_hash = entry.hash.clone();
+                    entries.push(entry);
+                    valid
+                }
+                Err(_) => false,
+            };
+            if !valid && first_invalid_line.is_none() {
+                first_invalid_line = Some(index + 1);
+            }
+        }
+
+        if let Some(limit) = limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        Ok(AuditLogReport {
+            entries,
+            verified: first_invalid_line.is_none(),
+            first_invalid_line,
+        })
+    }
+}
+
+/// Make `log` the target of `record`
+pub fn install(log: AuditLog) {
+    let _ = AUDIT_LOG.set(log);
+}
+
+/// Record a security-sensitive operation; a no-op until `install` has been
+/// called. Never pass secret values as `target` or `details`.
+pub fn record(action: AuditAction, target: &str, details: Option<String>) {
+    if let Some(log) = AUDIT_LOG.get() {
+        if let Err(e) = log.append(action, target, details) {
+            warn!(target: "security", "Failed to write security audit entry: {}", e);
+        }
+    }
+}
+
+/// Report for `get_security_audit_log`
+pub fn report(limit: Option<usize>) -> Result<AuditLogReport, AppError> {
+    match AUDIT_LOG.get() {
+        Some(log) => log.report(limit),
+        None => Ok(AuditLogReport {
+            entries: Vec::new(),
+            verified: true,
+            first_invalid_line: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_log() -> (AuditLog, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path().to_path_buf());
+        (log, temp_dir)
+    }
+
+    #[test]
+    fn test_entries_are_chained() {
+        let (log, _temp_dir) = create_test_log();
+        let first = log
+            .append(
+                AuditAction::CredentialStored,
+                "connection/home/api_key",
+                None,
+            )
+            .unwrap();
+        let second = log
+            .append(
+                AuditAction::SettingsChanged,
+                "settings",
+                Some("retry".to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(second.sequence, 1);
+
+        let report = log.report(None).unwrap();
+        assert!(report.verified);
+        assert_eq!(report.entries, vec![first, second.clone()]);
+        assert_eq!(log.report(Some(1)).unwrap().entries, vec![second]);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let (log, temp_dir) = create_test_log();
+        for target in ["a", "b", "c"] {
+            log.append(AuditAction::ConnectionChanged, target, None)
+                .unwrap();
+        }
+
+        let path = temp_dir.path().join(AUDIT_LOG_FILE_NAME);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replacen("\"b\"", "\"x\"", 1)).unwrap();
+        let report = log.report(None).unwrap();
+        assert!(!report.verified);
+        assert_eq!(report.first_invalid_line, Some(2));
+
+        let lines: Vec<&str> = contents.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let report = log.report(None).unwrap();
+        assert_eq!(report.first_invalid_line, Some(2));
+    }
+
+    #[test]
+    fn test_concurrent_appends_stay_chained() {
+        let (log, _temp_dir) = create_test_log();
+        let log = std::sync::Arc::new(log);
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let log = log.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        log.append(AuditAction::CredentialUsed, &writer.to_string(), None)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let report = log.report(None).unwrap();
+        assert!(report.verified);
+        assert_eq!(report.entries.len(), 200);
+    }
+
+    #[test]
+    fn test_append_after_truncated_last_line() {
+        let (log, temp_dir) = create_test_log();
+        let first = log
+            .append(AuditAction::ConnectionChanged, "a", None)
+            .unwrap();
+        log.append(AuditAction::ConnectionChanged, "b", None)
+            .unwrap();
+
+        // Simulate a crash part way through writing the second entry
+        let path = temp_dir.path().join(AUDIT_LOG_FILE_NAME);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &contents[..contents.len() - 20]).unwrap();
+
+        let reopened = AuditLog::new(temp_dir.path().to_path_buf());
+        let next = reopened
+            .append(AuditAction::ConnectionChanged, "c", None)
+            .unwrap();
+        assert_eq!(next.sequence, 1);
+        assert_eq!(next.prev_hash, first.hash);
+
+        // Only the cut-off line fails; the new entry starts a line of its own
+        let report = reopened.report(None).unwrap();
+        assert_eq!(report.first_invalid_line, Some(2));
+        assert_eq!(report.entries, vec![first, next]);
+
+        // The same log notices the file changing under its cached tail
+        std::fs::remove_file(&path).unwrap();
+        let restarted = reopened
+            .append(AuditAction::ConnectionChanged, "d", None)
+            .unwrap();
+        assert_eq!(restarted.sequence, 0);
+        assert_eq!(restarted.prev_hash, GENESIS_HASH);
+    }
+}
@@ -0,0 +1,485 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Full profile backups for moving to another machine. A backup is a zip
+//! holding `manifest.json`, the settings, the saved connections and the
+//! cached sessions. The manifest records a SHA-256 for every entry so a
+//! truncated or edited archive is rejected before anything is restored.
+//! Secrets are only included on request, encrypted with a passphrase.
+
+use crate::error::AppError;
+use crate::message_journal::{self, JOURNAL_DIR, JOURNAL_EXTENSION};
+use crate::profile_vault::ProfileKey;
+use crate::settings::{AppSettings, EncryptedProfileSettings, SecuritySettings};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Identifies the archive as a backup rather than any other zip
+pub const BACKUP_FORMAT: &str = "opencode-nexus-backup";
+
+/// Bumped when the archive layout changes; version 2 added session
+/// journals
+pub const BACKUP_VERSION: u32 = 2;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const SECRETS_ENTRY: &str = "secrets.enc";
+
+/// Profile files copied as they are, along with every session journal
+pub const DATA_FILES: [&str; 2] = ["server_connections.json", "chat_sessions.json"];
+
+/// Largest entry read back from an archive
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// One archived file and its checksum
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupEntry {
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub entries: Vec<BackupEntry>,
+    /// Key parameters for `secrets.enc`, present when secrets were included
+    #[serde(default)]
+    pub secrets: Option<EncryptedProfileSettings>,
+}
+
+/// Data read back from a verified backup
+#[derive(Debug, Clone)]
+pub struct BackupContents {
+    pub manifest: BackupManifest,
+    pub settings: AppSettings,
+    /// `DATA_FILES` and journals present in the archive, by name
+    pub files: BTreeMap<String, Vec<u8>>,
+    /// Decrypted secrets; empty when none were included or no passphrase
+    /// was given
+    pub secrets: BTreeMap<String, String>,
+}
+
+/// What a backup or restore covered, for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSummary {
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<String>,
+    pub secrets: usize,
+    /// The archive has secrets that were not restored for lack of a
+    /// passphrase
+    pub secrets_skipped: bool,
+}
+
+impl BackupSummary {
+    pub fn new(path: &Path, manifest: &BackupManifest, secrets: usize) -> Self {
+        Self {
+            path: path.display().to_string(),
+            created_at: manifest.created_at,
+            files: manifest
+                .entries
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect(),
+            secrets,
+            secrets_skipped: manifest.secrets.is_some() && secrets == 0,
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::DataError {
+        message: "Invalid backup".to_string(),
+        details: message.into(),
+    }
+}
+
+/// Whether an archive entry is a profile file to restore
+fn is_data_entry(name: &str) -> bool {
+    DATA_FILES.contains(&name) || message_journal::is_journal_entry(name)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Write a backup of `config_dir` to `output`. Settings are stored without
+/// the security section, since a PIN or profile key from one machine must
+/// not lock the other. `secrets` carries the passphrase and values to
+/// include.
+pub fn write_backup(
+    config_dir: &Path,
+    settings: &AppSettings,
+    secrets: Option<(&str, &BTreeMap<String, String>)>,
+    output: &Path,
+) -> Result<BackupManifest, AppError> {
+    let mut contents: Vec<(String, Vec<u8>)> = Vec::new();
+
+    let portable = AppSettings {
+        security: SecuritySettings::default(),
+        ..settings.clone()
+    };
+    let settings_json = serde_json::to_vec_pretty(&portable).map_err(|e| AppError::DataError {
+        message: "Failed to serialize settings".to_string(),
+        details: e.to_string(),
+    })?;
+    contents.push((SETTINGS_ENTRY.to_string(), settings_json));
+
+    let mut journals: Vec<String> = std::fs::read_dir(config_dir.join(JOURNAL_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == JOURNAL_EXTENSION))
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_str()?;
+            Some(format!("{}/{}", JOURNAL_DIR, file_name))
+        })
+        .collect();
+    journals.sort();
+    for name in DATA_FILES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(journals)
+    {
+        let path = config_dir.join(&name);
+        if path.exists() {
+            let bytes = std::fs::read(&path).map_err(|e| AppError::FileSystemError {
+                path: path.display().to_string(),
+                message: "Failed to read profile file".to_string(),
+                details: e.to_string(),
+            })?;
+            contents.push((name, bytes));
+        }
+    }
+
+    let secrets_params = match secrets {
+        Some((passphrase, values)) => {
+            let (key, params) = ProfileKey::create(passphrase)?;
+            let plaintext = zeroize::Zeroizing::new(serde_json::to_vec(values).map_err(|e| {
+                AppError::DataError {
+                    message: "Failed to serialize secrets".to_string(),
+                    details: e.to_string(),
+                }
+            })?);
+            contents.push((SECRETS_ENTRY.to_string(), key.encrypt(&plaintext)?));
+            Some(params)
+        }
+        None => None,
+    };
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        entries: contents
+            .iter()
+            .map(|(name, bytes)| BackupEntry {
+                name: name.clone(),
+                size: bytes.len() as u64,
+                sha256: sha256_hex(bytes),
+            })
+            .collect(),
+        secrets: secrets_params,
+    };
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = File::create(output).map_err(|e| AppError::FileSystemError {
+        path: output.display().to_string(),
+        message: "Failed to create backup file".to_string(),
+        details: e.to_string(),
+    })?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    let zip_error = |e: zip::result::ZipError| AppError::IoError {
+        message: "Failed to write backup".to_string(),
+        details: Some(e.to_string()),
+    };
+
+    zip.start_file(MANIFEST_ENTRY, options).map_err(zip_error)?;
+    zip.write_all(
+        &serde_json::to_vec_pretty(&manifest).map_err(|e| AppError::DataError {
+            message: "Failed to serialize backup manifest".to_string(),
+            details: e.to_string(),
+        })?,
+    )?;
+    for (name, bytes) in &contents {
+        zip.start_file(name.as_str(), options).map_err(zip_error)?;
+        zip.write_all(bytes)?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(manifest)
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, AppError> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| invalid(format!("{} is missing", name)))?;
+    let mut bytes = Vec::new();
+    entry.take(MAX_ENTRY_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(invalid(format!("{} is too large", name)));
+    }
+    Ok(bytes)
+}
+
+/// Open and verify a backup. Secrets are decrypted only when a passphrase
+/// is given; a wrong passphrase is an error rather than a silent skip.
+pub fn read_backup(path: &Path, passphrase: Option<&str>) -> Result<BackupContents, AppError> {
+    let file = File::open(path).map_err(|e| AppError::FileSystemError {
+        path: path.display().to_string(),
+        message: "Failed to open backup".to_string(),
+        details: e.to_string(),
+    })?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| invalid(format!("Not a zip archive: {}", e)))?;
+
+    let manifest: BackupManifest =
+        serde_json::from_slice(&read_entry(&mut archive, MANIFEST_ENTRY)?)
+            .map_err(|e| invalid(format!("Unreadable manifest: {}", e)))?;
+    if manifest.format != BACKUP_FORMAT {
+        return Err(invalid("Not an OpenCode Nexus backup"));
+    }
+    if manifest.version > BACKUP_VERSION {
+        return Err(invalid(format!(
+            "Backup version {} is newer than this app supports",
+            manifest.version
+        )));
+    }
+
+    let mut entries = BTreeMap::new();
+    for entry in &manifest.entries {
+        let known = entry.name == SETTINGS_ENTRY
+            || entry.name == SECRETS_ENTRY
+            || is_data_entry(&entry.name);
+        if !known {
+            return Err(invalid(format!("Unexpected entry {}", entry.name)));
+        }
+        let bytes = read_entry(&mut archive, &entry.name)?;
+        if bytes.len() as u64 != entry.size || sha256_hex(&bytes) != entry.sha256 {
+            return Err(invalid(format!(
+                "{} failed its integrity check",
+                entry.name
+            )));
+        }
+        entries.insert(entry.name.clone(), bytes);
+    }
+
+    let settings: AppSettings = serde_json::from_slice(
+        entries
+            .get(SETTINGS_ENTRY)
+            .ok_or_else(|| invalid("settings.json is missing"))?,
+    )
+    .map_err(|e| invalid(format!("Unreadable settings: {}", e)))?;
+
+    let secrets = match (&manifest.secrets, passphrase, entries.get(SECRETS_ENTRY)) {
+        (Some(params), Some(passphrase), Some(sealed)) => {
+            let key = ProfileKey::unlock(passphrase, params)?;
+            let plaintext = zeroize::Zeroizing::new(key.decrypt(sealed)?);
+            serde_json::from_slice(&plaintext)
+                .map_err(|e| invalid(format!("Unreadable secrets: {}", e)))?
+        }
+        (Some(_), Some(_), None) => return Err(invalid("secrets.enc is missing")),
+        _ => BTreeMap::new(),
+    };
+
+    let files = entries
+        .into_iter()
+        .filter(|(name, _)| is_data_entry(name))
+        .collect();
+
+    Ok(BackupContents {
+        manifest,
+        settings,
+        files,
+        secrets,
+    })
+}
+
+/// Replace profile files with those from a backup. Each file is written
+/// next to its target and renamed over it, so a failure leaves either the
+/// old or the new file.
+pub fn restore_files(
+    config_dir: &Path,
+    files: &BTreeMap<String, Vec<u8>>,
+) -> Result<Vec<PathBuf>, AppError> {
+    std::fs::create_dir_all(config_dir)?;
+    let mut written = Vec::new();
+    for (name, bytes) in files {
+        if !is_data_entry(name) {
+            continue;
+        }
+        let target = config_dir.join(name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut temp = target.clone().into_os_string();
+        temp.push(".restore");
+        let temp = PathBuf::from(temp);
+        std::fs::write(&temp, bytes)
+            .and_then(|_| std::fs::rename(&temp, &target))
+            .map_err(|e| AppError::FileSystemError {
+                path: target.display().to_string(),
+                message: "Failed to restore profile file".to_string(),
+                details: e.to_string(),
+            })?;
+        written.push(target);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn profile() -> (TempDir, AppSettings) {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("server_connections.json"),
+            r#"{"home":{"hostname":"nas"}}"#,
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("chat_sessions.json"), "[]").unwrap();
+        std::fs::create_dir(temp.path().join(JOURNAL_DIR)).unwrap();
+        std::fs::write(temp.path().join("sessions/ses_1.jsonl"), "{}\n").unwrap();
+        let mut settings = AppSettings {
+            locale: "fr".to_string(),
+            ..AppSettings::default()
+        };
+        settings.security.app_lock = Some(crate::settings::AppLockSettings {
+            idle_timeout_minutes: 5,
+            pin_hash: "$argon2id$secret".to_string(),
+        });
+        (temp, settings)
+    }
+
+    #[test]
+    fn test_round_trip_without_secrets() {
+        let (source, settings) = profile();
+        let output = source.path().join("backup.zip");
+        let manifest = write_backup(source.path(), &settings, None, &output).unwrap();
+        assert_eq!(manifest.entries.len(), 4);
+        assert!(manifest.secrets.is_none());
+
+        let contents = read_backup(&output, None).unwrap();
+        assert_eq!(contents.settings.locale, "fr");
+        assert!(contents.settings.security.app_lock.is_none());
+        assert!(contents.secrets.is_empty());
+
+        let target = TempDir::new().unwrap();
+        restore_files(target.path(), &contents.files).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target.path().join("server_connections.json")).unwrap(),
+            r#"{"home":{"hostname":"nas"}}"#
+        );
+        assert!(target.path().join("chat_sessions.json").exists());
+        assert!(target.path().join("sessions/ses_1.jsonl").exists());
+    }
+
+    #[test]
+    fn test_secrets_need_the_passphrase() {
+        let (source, settings) = profile();
+        let output = source.path().join("backup.zip");
+        let mut secrets = BTreeMap::new();
+        secrets.insert(
+            "connection/home/api_key".to_string(),
+            "sk-test-value".to_string(),
+        );
+        write_backup(
+            source.path(),
+            &settings,
+            Some(("correct horse battery", &secrets)),
+            &output,
+        )
+        .unwrap();
+
+        let raw = std::fs::read(&output).unwrap();
+        assert!(!raw.windows(13).any(|window| window == b"sk-test-value"));
+
+        let without = read_backup(&output, None).unwrap();
+        assert!(without.secrets.is_empty());
+        assert!(BackupSummary::new(&output, &without.manifest, 0).secrets_skipped);
+
+        assert!(read_backup(&output, Some("wrong passphrase")).is_err());
+        let with = read_backup(&output, Some("correct horse battery")).unwrap();
+        assert_eq!(with.secrets, secrets);
+    }
+
+    #[test]
+    fn test_tampered_entry_is_rejected() {
+        let (source, settings) = profile();
+        let output = source.path().join("backup.zip");
+        let manifest = write_backup(source.path(), &settings, None, &output).unwrap();
+
+        // Rebuild the archive with the original manifest but edited data
+        let tampered = source.path().join("tampered.zip");
+        let mut zip = ZipWriter::new(File::create(&tampered).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(MANIFEST_ENTRY, options).unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap())
+            .unwrap();
+        let mut original = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        for entry in &manifest.entries {
+            let mut bytes = read_entry(&mut original, &entry.name).unwrap();
+            if entry.name == "chat_sessions.json" {
+                bytes = b"[{}]".to_vec();
+            }
+            zip.start_file(entry.name.as_str(), options).unwrap();
+            zip.write_all(&bytes).unwrap();
+        }
+        zip.finish().unwrap();
+
+        assert!(read_backup(&tampered, None).is_err());
+    }
+
+    #[test]
+    fn test_rejects_other_archives() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("other.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("readme.txt", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        assert!(read_backup(&path, None).is_err());
+    }
+}
@@ -0,0 +1,688 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Spending budgets. The cost the server reports for each assistant reply
+//! is recorded locally and compared against daily, monthly and per-session
+//! limits, raising alerts at thresholds and optionally refusing new sends
+//! once a limit is reached.
+
+use crate::error::AppError;
+use chrono::{DateTime, Datelike, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Tauri event with a `BudgetAlert` when spending crosses a threshold
+pub const BUDGET_EVENT: &str = "budget-alert";
+
+/// Spending older than this no longer counts towards anything
+const RETENTION_DAYS: i64 = 400;
+
+/// Limits in the currency the server reports costs in (USD)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BudgetSettings {
+    pub daily_limit: Option<f64>,
+    pub monthly_limit: Option<f64>,
+    /// For sessions without a limit of their own
+    pub session_limit: Option<f64>,
+    /// Percentages of a limit at which to alert
+    pub warn_at: Vec<u8>,
+    /// Refuse new messages once a limit is reached, until overridden
+    pub hard_stop: bool,
+}
+
+impl Default for BudgetSettings {
+    fn default() -> Self {
+        Self {
+            daily_limit: None,
+            monthly_limit: None,
+            session_limit: None,
+            warn_at: vec![80, 100],
+            hard_stop: false,
+        }
+    }
+}
+
+impl BudgetSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        for (field, limit) in [
+            ("budgets.daily_limit", self.daily_limit),
+            ("budgets.monthly_limit", self.monthly_limit),
+            ("budgets.session_limit", self.session_limit),
+        ] {
+            validate_limit(field, limit)?;
+        }
+        if self
+            .warn_at
+            .iter()
+            .any(|percent| !(1..=100).contains(percent))
+        {
+            return Err(AppError::ValidationError {
+                field: "budgets.warn_at".to_string(),
+                message: "Alert thresholds must be between 1 and 100 percent".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn validate_limit(field: &str, limit: Option<f64>) -> Result<(), AppError> {
+    match limit {
+        Some(limit) if !limit.is_finite() || limit <= 0.0 => Err(AppError::ValidationError {
+            field: field.to_string(),
+            message: "Budget limits must be positive amounts".to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Daily,
+    Monthly,
+    Session,
+}
+
+impl BudgetScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BudgetScope::Daily => "daily",
+            BudgetScope::Monthly => "monthly",
+            BudgetScope::Session => "session",
+        }
+    }
+}
+
+/// Spending against one limit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetUsage {
+    pub scope: BudgetScope,
+    pub spent: f64,
+    pub limit: f64,
+    pub percent: f64,
+    /// The user chose to keep sending past this limit for its period
+    pub overridden: bool,
+}
+
+impl BudgetUsage {
+    pub fn exceeded(&self) -> bool {
+        self.spent >= self.limit
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub spent_today: f64,
+    pub spent_this_month: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spent_in_session: Option<f64>,
+    /// Configured limits only
+    pub usage: Vec<BudgetUsage>,
+    /// New messages are refused
+    pub blocked: bool,
+}
+
+impl BudgetStatus {
+    /// The limit holding back new messages, if any
+    pub fn blocking(&self) -> Option<&BudgetUsage> {
+        if !self.blocked {
+            return None;
+        }
+        self.usage
+            .iter()
+            .find(|usage| usage.exceeded() && !usage.overridden)
+    }
+}
+
+/// Spending crossed `threshold` percent of a limit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetAlert {
+    pub scope: BudgetScope,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub threshold: u8,
+    pub spent: f64,
+    pub limit: f64,
+}
+
+/// Cost of one assistant reply
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CostEntry {
+    pub session_id: String,
+    pub message_id: String,
+    pub cost: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Something that happens once per scope and period, e.g. an alert
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct PeriodMark {
+    scope: BudgetScope,
+    /// `2026-10-15`, `2026-10` or the session id
+    period: String,
+    #[serde(default)]
+    threshold: u8,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BudgetFile {
+    #[serde(default)]
+    entries: Vec<CostEntry>,
+    #[serde(default)]
+    session_limits: HashMap<String, f64>,
+    #[serde(default)]
+    alerts_sent: HashSet<PeriodMark>,
+    #[serde(default)]
+    overrides: HashSet<PeriodMark>,
+}
+
+fn period(scope: BudgetScope, session_id: Option<&str>, now: DateTime<Local>) -> Option<String> {
+    match scope {
+        BudgetScope::Daily => Some(now.format("%Y-%m-%d").to_string()),
+        BudgetScope::Monthly => Some(now.format("%Y-%m").to_string()),
+        BudgetScope::Session => session_id.map(str::to_string),
+    }
+}
+
+/// Costs of completed assistant replies in a session's messages (the
+/// server's `{ info, parts }` objects), with when they completed
+pub fn reply_costs(messages: &[Value]) -> Vec<(String, f64, DateTime<Utc>)> {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let info = message.get("info")?;
+            if info.get("role")?.as_str()? != "assistant" {
+                return None;
+            }
+            let cost = info.get("cost")?.as_f64().filter(|cost| *cost > 0.0)?;
+            let completed = info.pointer("/time/completed")?.as_i64()?;
+            Some((
+                info.get("id")?.as_str()?.to_string(),
+                cost,
+                Utc.timestamp_millis_opt(completed).single()?,
+            ))
+        })
+        .collect()
+}
+
+/// Recorded spending, per-session limits and alert state
+#[derive(Clone)]
+pub struct BudgetTracker {
+    config_dir: PathBuf,
+    data: Arc<Mutex<BudgetFile>>,
+}
+
+impl BudgetTracker {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            data: Arc::new(Mutex::new(BudgetFile::default())),
+        }
+    }
+
+    fn get_budget_file_path(&self) -> PathBuf {
+        self.config_dir.join("budgets.json")
+    }
+
+    fn lock_data(&self) -> MutexGuard<'_, BudgetFile> {
+        self.data.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Budget tracker lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let budget_file = self.get_budget_file_path();
+        if !budget_file.exists() {
+            return Ok(());
+        }
+
+        let budget_json =
+            std::fs::read_to_string(&budget_file).map_err(|e| AppError::FileSystemError {
+                path: budget_file.to_string_lossy().to_string(),
+                message: "Failed to read budget file".to_string(),
+                details: e.to_string(),
+            })?;
+        let loaded: BudgetFile =
+            serde_json::from_str(&budget_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse budget file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        *self.lock_data() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, data: &BudgetFile) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let budget_json = serde_json::to_string_pretty(data)?;
+        std::fs::write(self.get_budget_file_path(), budget_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_budget_file_path().to_string_lossy().to_string(),
+                message: "Failed to write budget file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Record the costs of replies not seen before; returns how many were new
+    pub fn record(
+        &self,
+        session_id: &str,
+        messages: &[Value],
+        now: DateTime<Local>,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let costs = reply_costs(messages);
+        self.modify(|data| {
+            let known: HashSet<String> = data
+                .entries
+                .iter()
+                .map(|entry| entry.message_id.clone())
+                .collect();
+            let before = data.entries.len();
+            data.entries.extend(
+                costs
+                    .into_iter()
+                    .filter(|(message_id, _, _)| !known.contains(message_id))
+                    .map(|(message_id, cost, recorded_at)| CostEntry {
+                        session_id: session_id.to_string(),
+                        message_id,
+                        cost,
+                        recorded_at,
+                    }),
+            );
+            let added = data.entries.len() - before;
+
+            let cutoff = now.with_timezone(&Utc) - chrono::Duration::days(RETENTION_DAYS);
+            data.entries.retain(|entry| entry.recorded_at >= cutoff);
+            let current = |mark: &PeriodMark| {
+                mark.scope == BudgetScope::Session
+                    || period(mark.scope, None, now).as_deref() == Some(mark.period.as_str())
+            };
+            data.alerts_sent.retain(current);
+            data.overrides.retain(current);
+            added
+        })
+    }
+
+    /// Give a session its own limit, or `None` to use the default
+    pub fn set_session_limit(
+        &self,
+        session_id: &str,
+        limit: Option<f64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        validate_limit("limit", limit)?;
+        self.modify(|data| match limit {
+            Some(limit) => {
+                data.session_limits.insert(session_id.to_string(), limit);
+            }
+            None => {
+                data.session_limits.remove(session_id);
+            }
+        })
+    }
+
+    pub fn status(
+        &self,
+        settings: &BudgetSettings,
+        session_id: Option<&str>,
+        now: DateTime<Local>,
+    ) -> BudgetStatus {
+        let data = self.lock_data();
+        let spent = |include: &dyn Fn(&CostEntry) -> bool| -> f64 {
+            data.entries
+                .iter()
+                .filter(|entry| include(entry))
+                .map(|entry| entry.cost)
+                .sum()
+        };
+        let today = now.date_naive();
+        let spent_today =
+            spent(&|entry| entry.recorded_at.with_timezone(&Local).date_naive() == today);
+        let spent_this_month = spent(&|entry| {
+            let local = entry.recorded_at.with_timezone(&Local);
+            (local.year(), local.month()) == (now.year(), now.month())
+        });
+        let spent_in_session =
+            session_id.map(|session_id| spent(&|entry| entry.session_id == session_id));
+        let session_limit = session_id
+            .and_then(|session_id| data.session_limits.get(session_id).copied())
+            .or(settings.session_limit);
+
+        let usage: Vec<BudgetUsage> = [
+            (BudgetScope::Daily, Some(spent_today), settings.daily_limit),
+            (
+                BudgetScope::Monthly,
+                Some(spent_this_month),
+                settings.monthly_limit,
+            ),
+            (BudgetScope::Session, spent_in_session, session_limit),
+        ]
+        .into_iter()
+        .filter_map(|(scope, spent, limit)| {
+            let (spent, limit) = (spent?, limit?);
+            let overridden = period(scope, session_id, now).is_some_and(|period| {
+                data.overrides.contains(&PeriodMark {
+                    scope,
+                    period,
+                    threshold: 0,
+                })
+            });
+            Some(BudgetUsage {
+                scope,
+                spent,
+                limit,
+                percent: spent / limit * 100.0,
+                overridden,
+            })
+        })
+        .collect();
+        let blocked = settings.hard_stop
+            && usage
+                .iter()
+                .any(|usage| usage.exceeded() && !usage.overridden);
+
+        BudgetStatus {
+            session_id: session_id.map(str::to_string),
+            spent_today,
+            spent_this_month,
+            spent_in_session,
+            usage,
+            blocked,
+        }
+    }
+
+    /// Alerts for thresholds crossed since the last call, each raised once
+    /// per period
+    pub fn take_alerts(
+        &self,
+        settings: &BudgetSettings,
+        session_id: &str,
+        now: DateTime<Local>,
+    ) -> Result<Vec<BudgetAlert>, Box<dyn std::error::Error>> {
+        let status = self.status(settings, Some(session_id), now);
+        self.modify(|data| {
+            let mut alerts = Vec::new();
+            for usage in &status.usage {
+                let Some(period) = period(usage.scope, Some(session_id), now) else {
+                    continue;
+                };
+                // Only the highest threshold crossed is worth telling
+                let crossed = settings
+                    .warn_at
+                    .iter()
+                    .copied()
+                    .filter(|threshold| usage.percent >= f64::from(*threshold))
+                    .max();
+                let Some(threshold) = crossed else {
+                    continue;
+                };
+                let mark = PeriodMark {
+                    scope: usage.scope,
+                    period,
+                    threshold,
+                };
+                if data.alerts_sent.insert(mark) {
+                    alerts.push(BudgetAlert {
+                        scope: usage.scope,
+                        session_id: (usage.scope == BudgetScope::Session)
+                            .then(|| session_id.to_string()),
+                        threshold,
+                        spent: usage.spent,
+                        limit: usage.limit,
+                    });
+                }
+            }
+            alerts
+        })
+    }
+
+    /// Keep sending past a limit for the rest of its period (the day, the
+    /// month, or the session's lifetime)
+    pub fn grant_override(
+        &self,
+        scope: BudgetScope,
+        session_id: Option<&str>,
+        now: DateTime<Local>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let period = period(scope, session_id, now).ok_or_else(|| AppError::ValidationError {
+            field: "session_id".to_string(),
+            message: "A session is required to override its budget".to_string(),
+        })?;
+        self.modify(|data| {
+            data.overrides.insert(PeriodMark {
+                scope,
+                period,
+                threshold: 0,
+            });
+        })
+    }
+
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut BudgetFile) -> T,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        let mut updated = data.clone();
+        let result = f(&mut updated);
+        self.save(&updated)?;
+        *data = updated;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn reply(id: &str, cost: f64, completed: DateTime<Local>) -> Value {
+        json!({
+            "info": {
+                "id": id,
+                "role": "assistant",
+                "cost": cost,
+                "time": { "created": 0, "completed": completed.timestamp_millis() }
+            },
+            "parts": []
+        })
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap()
+    }
+
+    fn limits() -> BudgetSettings {
+        BudgetSettings {
+            daily_limit: Some(1.0),
+            monthly_limit: Some(10.0),
+            session_limit: Some(5.0),
+            warn_at: vec![50, 100],
+            hard_stop: true,
+        }
+    }
+
+    #[test]
+    fn test_reply_costs_skip_unfinished_and_free_replies() {
+        let now = at(15, 12);
+        let mut in_progress = reply("msg_3", 0.2, now);
+        in_progress["info"]["time"]
+            .as_object_mut()
+            .unwrap()
+            .remove("completed");
+        let messages = vec![
+            json!({ "info": { "id": "msg_1", "role": "user" } }),
+            reply("msg_2", 0.25, now),
+            in_progress,
+            reply("msg_4", 0.0, now),
+        ];
+        let costs = reply_costs(&messages);
+        assert_eq!(costs.len(), 1);
+        assert_eq!(costs[0].0, "msg_2");
+        assert_eq!(costs[0].1, 0.25);
+    }
+
+    #[test]
+    fn test_record_is_idempotent_and_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = BudgetTracker::new(temp_dir.path().to_path_buf());
+        let now = at(15, 12);
+        let messages = vec![
+            reply("msg_1", 0.4, at(15, 9)),
+            reply("msg_2", 0.3, at(14, 9)),
+        ];
+
+        assert_eq!(tracker.record("ses_1", &messages, now).unwrap(), 2);
+        assert_eq!(tracker.record("ses_1", &messages, now).unwrap(), 0);
+
+        let reloaded = BudgetTracker::new(temp_dir.path().to_path_buf());
+        reloaded.load().unwrap();
+        let status = reloaded.status(&limits(), Some("ses_1"), now);
+        assert!((status.spent_today - 0.4).abs() < 1e-9);
+        assert!((status.spent_this_month - 0.7).abs() < 1e-9);
+        assert!((status.spent_in_session.unwrap() - 0.7).abs() < 1e-9);
+        assert!(!status.blocked);
+    }
+
+    #[test]
+    fn test_alerts_fire_once_per_threshold() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = BudgetTracker::new(temp_dir.path().to_path_buf());
+        let now = at(15, 12);
+
+        tracker
+            .record("ses_1", &[reply("msg_1", 0.6, now)], now)
+            .unwrap();
+        let alerts = tracker.take_alerts(&limits(), "ses_1", now).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].scope, BudgetScope::Daily);
+        assert_eq!(alerts[0].threshold, 50);
+        assert!(tracker
+            .take_alerts(&limits(), "ses_1", now)
+            .unwrap()
+            .is_empty());
+
+        tracker
+            .record("ses_1", &[reply("msg_2", 0.5, now)], now)
+            .unwrap();
+        let alerts = tracker.take_alerts(&limits(), "ses_1", now).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].threshold, 100);
+
+        // A new day starts over
+        let tomorrow = at(16, 9);
+        tracker
+            .record("ses_1", &[reply("msg_3", 0.6, tomorrow)], tomorrow)
+            .unwrap();
+        let alerts = tracker.take_alerts(&limits(), "ses_1", tomorrow).unwrap();
+        assert_eq!(alerts[0].threshold, 50);
+    }
+
+    #[test]
+    fn test_hard_stop_and_override() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = BudgetTracker::new(temp_dir.path().to_path_buf());
+        let now = at(15, 12);
+        tracker
+            .record("ses_1", &[reply("msg_1", 1.5, now)], now)
+            .unwrap();
+
+        let status = tracker.status(&limits(), Some("ses_1"), now);
+        assert!(status.blocked);
+        assert_eq!(status.blocking().unwrap().scope, BudgetScope::Daily);
+
+        let soft = BudgetSettings {
+            hard_stop: false,
+            ..limits()
+        };
+        assert!(tracker
+            .status(&soft, Some("ses_1"), now)
+            .blocking()
+            .is_none());
+
+        tracker
+            .grant_override(BudgetScope::Daily, None, now)
+            .unwrap();
+        assert!(!tracker.status(&limits(), Some("ses_1"), now).blocked);
+        assert!(tracker
+            .grant_override(BudgetScope::Session, None, now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_session_limit_overrides_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = BudgetTracker::new(temp_dir.path().to_path_buf());
+        let now = at(15, 12);
+        tracker
+            .record("ses_1", &[reply("msg_1", 0.3, now)], now)
+            .unwrap();
+        tracker.set_session_limit("ses_1", Some(0.2)).unwrap();
+
+        let settings = BudgetSettings {
+            daily_limit: None,
+            monthly_limit: None,
+            ..limits()
+        };
+        let status = tracker.status(&settings, Some("ses_1"), now);
+        assert_eq!(status.usage.len(), 1);
+        assert_eq!(status.usage[0].limit, 0.2);
+        assert!(status.blocked);
+
+        assert!(tracker.set_session_limit("ses_1", Some(-1.0)).is_err());
+        tracker.set_session_limit("ses_1", None).unwrap();
+        assert_eq!(
+            tracker.status(&settings, Some("ses_1"), now).usage[0].limit,
+            5.0
+        );
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(BudgetSettings::default().validate().is_ok());
+        assert!(BudgetSettings {
+            daily_limit: Some(0.0),
+            ..BudgetSettings::default()
+        }
+        .validate()
+        .is_err());
+        assert!(BudgetSettings {
+            warn_at: vec![120],
+            ..BudgetSettings::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
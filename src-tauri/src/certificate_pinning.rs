@@ -0,0 +1,387 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::error::AppError;
+use ring::digest::{digest, SHA256};
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Tauri event emitted when a pinned server presents a different certificate
+pub const CERTIFICATE_MISMATCH_EVENT: &str = "certificate-mismatch";
+
+/// Pinned SHA-256 fingerprints by hostname, consulted during every TLS
+/// handshake made by clients from `client_builder`
+static PINS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+/// Failed pin checks waiting to be turned into errors
+static MISMATCHES: Mutex<BTreeMap<String, CertificateMismatch>> = Mutex::new(BTreeMap::new());
+
+/// Last fingerprint seen for each host, used to offer the current
+/// certificate for pinning
+static OBSERVED: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// See `shared_client`
+static SHARED_CLIENT: RwLock<Option<reqwest::Client>> = RwLock::new(None);
+
+/// How long an idle pooled connection is kept for the next request
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Keep-alive probes stop NATs and tunnels dropping idle connections
+const TCP_KEEPALIVE: Duration = Duration::from_secs(30);
+const HTTP2_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+const HTTP2_KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A pinned server presented a certificate with a different fingerprint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificateMismatch {
+    pub hostname: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl From<CertificateMismatch> for AppError {
+    fn from(mismatch: CertificateMismatch) -> Self {
+        AppError::CertificateMismatch {
+            hostname: mismatch.hostname,
+            expected: mismatch.expected,
+            actual: mismatch.actual,
+        }
+    }
+}
+
+/// SHA-256 fingerprint of a DER certificate as `AB:CD:...`
+pub fn fingerprint(der: &[u8]) -> String {
+    digest(&SHA256, der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Accept a fingerprint with or without separators in either case and
+/// return it in the `AB:CD:...` form
+pub fn normalize_fingerprint(input: &str) -> Result<String, AppError> {
+    let hex: String = input
+        .trim()
+        .trim_start_matches("sha256/")
+        .chars()
+        .filter(|c| !matches!(c, ':' | ' ' | '-'))
+        .collect();
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::ValidationError {
+            field: "certificate_fingerprint".to_string(),
+            message: "Expected a SHA-256 fingerprint of 64 hex digits".to_string(),
+        });
+    }
+    Ok(hex
+        .to_ascii_uppercase()
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| String::from_utf8_lossy(pair).into_owned())
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+/// Replace the active pins with those of the saved connections. Recorded
+/// failures for hosts whose pin changed are cleared since they were judged
+/// against the old pin.
+pub fn set_pins(pins: impl IntoIterator<Item = (String, String)>) {
+    let pins: BTreeMap<String, String> = pins
+        .into_iter()
+        .map(|(hostname, fingerprint)| (hostname.to_ascii_lowercase(), fingerprint))
+        .collect();
+    let previous = match PINS.write() {
+        Ok(mut guard) => std::mem::replace(&mut *guard, pins.clone()),
+        Err(poisoned) => {
+            eprintln!("[ERROR] Certificate pins lock poisoned, recovering...");
+            std::mem::replace(&mut *poisoned.into_inner(), pins.clone())
+        }
+    };
+
+    let mut mismatches = match MISMATCHES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    mismatches.retain(|hostname, _| pins.get(hostname) == previous.get(hostname));
+    drop(mismatches);
+
+    // Pooled connections were verified against the old pins
+    if pins != previous {
+        reset_shared_client();
+    }
+}
+
+/// Drop the shared client so the next request builds one with the current
+/// pins and proxies
+pub fn reset_shared_client() {
+    match SHARED_CLIENT.write() {
+        Ok(mut client) => *client = None,
+        Err(poisoned) => *poisoned.into_inner() = None,
+    }
+}
+
+fn pin_for(hostname: &str) -> Option<String> {
+    let pins = match PINS.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    pins.get(&hostname.to_ascii_lowercase()).cloned()
+}
+
+/// The pin failure recorded for a host, if its last handshake failed one
+pub fn mismatch_for(hostname: &str) -> Option<CertificateMismatch> {
+    let mismatches = match MISMATCHES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    mismatches.get(&hostname.to_ascii_lowercase()).cloned()
+}
+
+/// Fingerprint of the certificate a host presented most recently
+pub fn observed_fingerprint(hostname: &str) -> Option<String> {
+    let observed = match OBSERVED.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    observed.get(&hostname.to_ascii_lowercase()).cloned()
+}
+
+/// Compare a presented certificate fingerprint against the host's pin.
+/// Returns whether a pin applied, or the mismatch when it did not match.
+fn check_pin(hostname: &str, pin: Option<&str>, actual: &str) -> Result<bool, CertificateMismatch> {
+    match pin {
+        None => Ok(false),
+        Some(expected) if expected == actual => Ok(true),
+        Some(expected) => Err(CertificateMismatch {
+            hostname: hostname.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }),
+    }
+}
+
+/// Remember what a host presented and whether it failed its pin
+fn record_handshake(hostname: &str, actual: &str, result: &Result<bool, CertificateMismatch>) {
+    match OBSERVED.lock() {
+        Ok(mut observed) => observed.insert(hostname.to_string(), actual.to_string()),
+        Err(poisoned) => poisoned
+            .into_inner()
+            .insert(hostname.to_string(), actual.to_string()),
+    };
+
+    let mut mismatches = match MISMATCHES.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match result {
+        Err(mismatch) => mismatches.insert(hostname.to_string(), mismatch.clone()),
+        Ok(_) => mismatches.remove(hostname),
+    };
+}
+
+/// Trusts a pinned host only by its fingerprint, which also allows
+/// self-signed certificates, and verifies every other host against the
+/// bundled web PKI roots
+struct PinningVerifier {
+    webpki: WebPkiVerifier,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let hostname = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_string(),
+            ServerName::IpAddress(address) => address.to_string(),
+            _ => String::new(),
+        };
+
+        let hostname = hostname.to_ascii_lowercase();
+        let actual = fingerprint(&end_entity.0);
+        let result = check_pin(&hostname, pin_for(&hostname).as_deref(), &actual);
+        record_handshake(&hostname, &actual, &result);
+
+        match result {
+            Ok(true) => Ok(ServerCertVerified::assertion()),
+            Ok(false) => self.webpki.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                scts,
+                ocsp_response,
+                now,
+            ),
+            Err(mismatch) => {
+                tracing::error!(
+                    target: "security",
+                    host = %mismatch.hostname,
+                    expected = %mismatch.expected,
+                    actual = %mismatch.actual,
+                    "Server certificate does not match pinned fingerprint"
+                );
+                Err(rustls::Error::General(
+                    "certificate fingerprint mismatch".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+fn tls_config() -> ClientConfig {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+
+    let mut config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(PinningVerifier {
+            webpki: WebPkiVerifier::new(roots, None),
+        }))
+        .with_no_client_auth();
+    // reqwest leaves ALPN to a preconfigured TLS config, so offer HTTP/2 here
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    config
+}
+
+/// HTTP client builder that enforces the pinned certificate fingerprints,
+/// with keep-alive tuned for remote and tunneled servers
+pub fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .use_preconfigured_tls(tls_config())
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(TCP_KEEPALIVE)
+        .tcp_nodelay(true)
+        .http2_keep_alive_interval(HTTP2_KEEPALIVE_INTERVAL)
+        .http2_keep_alive_timeout(HTTP2_KEEPALIVE_TIMEOUT)
+        .http2_keep_alive_while_idle(true)
+        .http2_adaptive_window(true)
+}
+
+/// The pooled client for talking to the server, shared so health checks,
+/// model fetches and messages reuse connections. It has no overall timeout;
+/// callers set one per request. Rebuilt when the pins or proxies change.
+pub fn shared_client() -> Result<reqwest::Client, reqwest::Error> {
+    let cached = match SHARED_CLIENT.read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    if let Some(client) = cached {
+        return Ok(client);
+    }
+
+    let mut guard = match SHARED_CLIENT.write() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(client) = guard.as_ref() {
+        return Ok(client.clone());
+    }
+    let client = crate::proxy::apply(client_builder()).build()?;
+    *guard = Some(client.clone());
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_fingerprint_accepts_common_formats() {
+        let plain = "ab".repeat(32);
+        let expected = vec!["AB"; 32].join(":");
+        assert_eq!(normalize_fingerprint(&plain).unwrap(), expected);
+        assert_eq!(
+            normalize_fingerprint(&expected.to_lowercase()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            normalize_fingerprint(&format!("sha256/{}", plain)).unwrap(),
+            expected
+        );
+        assert!(normalize_fingerprint("AB:CD").is_err());
+        assert!(normalize_fingerprint(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        let value = fingerprint(b"certificate");
+        assert_eq!(value.len(), 32 * 3 - 1);
+        assert_eq!(normalize_fingerprint(&value).unwrap(), value);
+    }
+
+    #[test]
+    fn test_check_pin() {
+        let pinned = fingerprint(b"pinned-certificate");
+        let changed = fingerprint(b"changed-certificate");
+
+        assert_eq!(check_pin("host", None, &changed), Ok(false));
+        assert_eq!(check_pin("host", Some(&pinned), &pinned), Ok(true));
+        assert_eq!(
+            check_pin("host", Some(&pinned), &changed),
+            Err(CertificateMismatch {
+                hostname: "host".to_string(),
+                expected: pinned,
+                actual: changed,
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_handshake_tracks_mismatch_until_success() {
+        let hostname = "recorded.example";
+        let mismatch = CertificateMismatch {
+            hostname: hostname.to_string(),
+            expected: fingerprint(b"expected"),
+            actual: fingerprint(b"actual"),
+        };
+
+        record_handshake(hostname, &mismatch.actual, &Err(mismatch.clone()));
+        assert_eq!(mismatch_for("Recorded.Example"), Some(mismatch.clone()));
+        assert_eq!(observed_fingerprint(hostname), Some(mismatch.actual));
+
+        record_handshake(hostname, &mismatch.expected, &Ok(true));
+        assert!(mismatch_for(hostname).is_none());
+    }
+
+    #[test]
+    fn test_tls_config_offers_http2() {
+        assert_eq!(
+            tls_config().alpn_protocols,
+            vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+        );
+    }
+}
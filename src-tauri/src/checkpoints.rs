@@ -0,0 +1,209 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Checkpoints a session can be reverted to. Every user message is one: the
+//! server can roll the conversation and the agent's file changes back to
+//! just before it, and restore them again until the next message is sent.
+
+use crate::file_changes;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Characters of the prompt shown for a checkpoint
+const PROMPT_PREVIEW_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Checkpoint {
+    /// Id of the user message the checkpoint precedes
+    pub id: String,
+    pub session_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Start of the prompt, to recognise the checkpoint by
+    pub prompt: String,
+    /// Files the agent changed in response to the prompt
+    pub files_changed: usize,
+    /// The session is currently reverted to this checkpoint or an earlier one
+    pub reverted: bool,
+}
+
+/// Checkpoints in a session's messages (the server's `{ info, parts }`
+/// objects), oldest first. `session` is the server's session info, whose
+/// `revert` names the message the session is reverted to.
+pub fn list(session_id: &str, messages: &[Value], session: &Value) -> Vec<Checkpoint> {
+    let reverted_from = session.pointer("/revert/messageID").and_then(Value::as_str);
+    let user_indices: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| {
+            message.pointer("/info/role").and_then(Value::as_str) == Some("user")
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut reverted = false;
+    user_indices
+        .iter()
+        .enumerate()
+        .filter_map(|(position, &index)| {
+            let message = &messages[index];
+            let id = message.pointer("/info/id").and_then(Value::as_str)?;
+            reverted |= Some(id) == reverted_from;
+            let next = user_indices
+                .get(position + 1)
+                .copied()
+                .unwrap_or(messages.len());
+            let created_at = message
+                .pointer("/info/time/created")
+                .and_then(Value::as_i64)
+                .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+                .unwrap_or_else(Utc::now);
+
+            Some(Checkpoint {
+                id: id.to_string(),
+                session_id: session_id.to_string(),
+                created_at,
+                prompt: prompt_preview(message),
+                files_changed: file_changes::changes_from_messages(&messages[index + 1..next])
+                    .len(),
+                reverted,
+            })
+        })
+        .collect()
+}
+
+/// First line of a message's text, shortened
+fn prompt_preview(message: &Value) -> String {
+    let text = message
+        .get("parts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+        .filter_map(|part| part.get("text").and_then(Value::as_str))
+        .next()
+        .unwrap_or_default();
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > PROMPT_PREVIEW_CHARS {
+        let preview: String = line.chars().take(PROMPT_PREVIEW_CHARS).collect();
+        format!("{}…", preview.trim_end())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Text of the marker message left in local history after a revert
+pub fn marker_text(checkpoint: &Checkpoint) -> String {
+    match checkpoint.files_changed {
+        0 => format!("Reverted to before \"{}\"", checkpoint.prompt),
+        1 => format!(
+            "Reverted to before \"{}\" and undid 1 file change",
+            checkpoint.prompt
+        ),
+        files => format!(
+            "Reverted to before \"{}\" and undid {} file changes",
+            checkpoint.prompt, files
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user(id: &str, created: i64, text: &str) -> Value {
+        json!({
+            "info": { "id": id, "role": "user", "time": { "created": created } },
+            "parts": [{ "type": "text", "text": text }]
+        })
+    }
+
+    fn assistant(id: &str, edits: usize) -> Value {
+        let parts: Vec<Value> = (0..edits)
+            .map(|i| {
+                json!({
+                    "type": "tool",
+                    "tool": "write",
+                    "state": {
+                        "status": "completed",
+                        "input": { "filePath": format!("/repo/f{}.rs", i), "content": "x" }
+                    }
+                })
+            })
+            .collect();
+        json!({ "info": { "id": id, "role": "assistant" }, "parts": parts })
+    }
+
+    #[test]
+    fn test_list_checkpoints() {
+        let messages = vec![
+            user("msg_1", 1_700_000_000_000, "Add a parser\nwith tests"),
+            assistant("msg_2", 2),
+            user("msg_3", 1_700_000_100_000, "Now document it"),
+            assistant("msg_4", 0),
+        ];
+        let checkpoints = list("ses_1", &messages, &json!({ "id": "ses_1" }));
+
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].id, "msg_1");
+        assert_eq!(checkpoints[0].prompt, "Add a parser");
+        assert_eq!(checkpoints[0].files_changed, 2);
+        assert_eq!(checkpoints[0].created_at.timestamp(), 1_700_000_000);
+        assert_eq!(checkpoints[1].files_changed, 0);
+        assert!(checkpoints.iter().all(|checkpoint| !checkpoint.reverted));
+    }
+
+    #[test]
+    fn test_reverted_checkpoints() {
+        let messages = vec![
+            user("msg_1", 1, "one"),
+            user("msg_2", 2, "two"),
+            user("msg_3", 3, "three"),
+        ];
+        let session = json!({ "revert": { "messageID": "msg_2" } });
+        let reverted: Vec<bool> = list("ses_1", &messages, &session)
+            .iter()
+            .map(|checkpoint| checkpoint.reverted)
+            .collect();
+        assert_eq!(reverted, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_prompt_preview_is_shortened() {
+        let long = "word ".repeat(40);
+        let checkpoints = list("ses_1", &[user("msg_1", 1, &long)], &Value::Null);
+        assert!(checkpoints[0].prompt.ends_with('…'));
+        assert!(checkpoints[0].prompt.chars().count() <= PROMPT_PREVIEW_CHARS + 1);
+    }
+
+    #[test]
+    fn test_marker_text() {
+        let mut checkpoint = list("ses_1", &[user("msg_1", 1, "Refactor")], &Value::Null).remove(0);
+        assert_eq!(marker_text(&checkpoint), "Reverted to before \"Refactor\"");
+        checkpoint.files_changed = 3;
+        assert_eq!(
+            marker_text(&checkpoint),
+            "Reverted to before \"Refactor\" and undid 3 file changes"
+        );
+    }
+}
@@ -0,0 +1,421 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Fenced code in assistant messages, and writing it to local files.
+
+use crate::error::AppError;
+use crate::file_changes::{self, DiffLineKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Blocks remembered for `apply_code_block_to_file`
+const MAX_CACHED_BLOCKS: usize = 500;
+
+/// How far a patch hunk may have drifted from its stated line
+const PATCH_FUZZ_LINES: usize = 50;
+
+/// A fenced code block from a message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeBlock {
+    /// `<message_id>:<index>`
+    pub id: String,
+    pub message_id: String,
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// File named in the fence, e.g. ```` ```rust title="src/main.rs" ````
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_hint: Option<String>,
+    pub content: String,
+}
+
+/// How a block is written to its target
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyMode {
+    /// Only if the file does not exist yet
+    Create,
+    Overwrite,
+    Append,
+    /// The block is a unified diff applied to the file
+    Patch,
+}
+
+/// Language and file name from a fence's info string
+fn parse_info(info: &str) -> (Option<String>, Option<String>) {
+    let mut words = info.split_whitespace();
+    let Some(first) = words.next() else {
+        return (None, None);
+    };
+    // ```rust:src/main.rs
+    let (language, mut file_hint) = match first.split_once(':') {
+        Some((language, path)) if !path.is_empty() => {
+            (language.to_string(), Some(path.to_string()))
+        }
+        _ => (first.to_string(), None),
+    };
+    for word in words {
+        if file_hint.is_some() {
+            break;
+        }
+        let value = ["title=", "file=", "filename=", "path="]
+            .iter()
+            .find_map(|key| word.strip_prefix(key));
+        file_hint = match value {
+            Some(value) => Some(value.trim_matches(|c| c == '"' || c == '\'').to_string()),
+            None if word.contains('/') || word.contains('.') => Some(word.to_string()),
+            None => None,
+        };
+    }
+    let language = (!language.is_empty()).then_some(language);
+    (language, file_hint.filter(|hint| !hint.is_empty()))
+}
+
+/// Fenced blocks in `text`, in order. An unclosed fence runs to the end.
+pub fn extract(message_id: &str, text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        let Some(fence_char) = ['`', '~']
+            .into_iter()
+            .find(|c| trimmed.starts_with(&c.to_string().repeat(3)))
+        else {
+            continue;
+        };
+        let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+        let (language, file_hint) = parse_info(&trimmed[fence_len..]);
+
+        let mut content = Vec::new();
+        for line in lines.by_ref() {
+            let closing = line.trim();
+            if closing.len() >= fence_len && closing.chars().all(|c| c == fence_char) {
+                break;
+            }
+            content.push(line);
+        }
+
+        let index = blocks.len();
+        blocks.push(CodeBlock {
+            id: format!("{}:{}", message_id, index),
+            message_id: message_id.to_string(),
+            index,
+            language,
+            file_hint,
+            content: content.join("\n") + "\n",
+        });
+    }
+    blocks
+}
+
+/// New contents of a file after applying `block` with `mode`
+pub fn apply(
+    block: &CodeBlock,
+    current: Option<&str>,
+    mode: ApplyMode,
+) -> Result<String, AppError> {
+    match (mode, current) {
+        (ApplyMode::Create, Some(_)) => Err(AppError::ValidationError {
+            field: "path".to_string(),
+            message: "File already exists".to_string(),
+        }),
+        (ApplyMode::Create | ApplyMode::Overwrite, _) => Ok(block.content.clone()),
+        (ApplyMode::Append, current) => {
+            let mut contents = current.unwrap_or_default().to_string();
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&block.content);
+            Ok(contents)
+        }
+        (ApplyMode::Patch, current) => apply_patch(current.unwrap_or_default(), &block.content),
+    }
+}
+
+/// Apply a single-file unified diff, tolerating hunks that moved by up to
+/// `PATCH_FUZZ_LINES` lines
+pub fn apply_patch(current: &str, patch: &str) -> Result<String, AppError> {
+    let failed = |message: String| AppError::ValidationError {
+        field: "patch".to_string(),
+        message,
+    };
+    let files = file_changes::parse_unified_diff(patch);
+    let diff = match files.as_slice() {
+        [diff] => diff,
+        [] => return Err(failed("Code block is not a unified diff".to_string())),
+        _ => return Err(failed("Patch changes more than one file".to_string())),
+    };
+
+    let lines: Vec<&str> = current.lines().collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut position = 0usize;
+    for (number, hunk) in diff.hunks.iter().enumerate() {
+        let expected: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter(|line| line.kind != DiffLineKind::Added)
+            .map(|line| line.content.as_str())
+            .collect();
+        let stated = (hunk.old_start as usize).saturating_sub(1).max(position);
+        let start = (0..=PATCH_FUZZ_LINES)
+            .flat_map(|offset| [stated.checked_add(offset), stated.checked_sub(offset)])
+            .flatten()
+            .filter(|start| *start >= position)
+            .find(|start| lines.get(*start..*start + expected.len()) == Some(&expected[..]))
+            .ok_or_else(|| failed(format!("Hunk {} does not match the file", number + 1)))?;
+
+        output.extend(lines[position..start].iter().map(|line| line.to_string()));
+        output.extend(
+            hunk.lines
+                .iter()
+                .filter(|line| line.kind != DiffLineKind::Removed)
+                .map(|line| line.content.clone()),
+        );
+        position = start + expected.len();
+    }
+    output.extend(lines[position..].iter().map(|line| line.to_string()));
+
+    let mut patched = output.join("\n");
+    if current.is_empty() || current.ends_with('\n') {
+        patched.push('\n');
+    }
+    Ok(patched)
+}
+
+fn invalid_path(message: String) -> AppError {
+    AppError::ValidationError {
+        field: "path".to_string(),
+        message,
+    }
+}
+
+/// Check that `path` is an absolute file path whose directory exists and
+/// is outside every protected directory (such as the config directory)
+pub fn check_target(path: &Path, protected_dirs: &[PathBuf]) -> Result<PathBuf, AppError> {
+    if !path.is_absolute() {
+        return Err(invalid_path(format!(
+            "{} is not an absolute path",
+            path.display()
+        )));
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(invalid_path(format!(
+            "{} is not a file path",
+            path.display()
+        )));
+    };
+    let parent = parent
+        .canonicalize()
+        .map_err(|_| invalid_path(format!("{} does not exist", parent.display())))?;
+    for protected in protected_dirs {
+        let protected = protected
+            .canonicalize()
+            .unwrap_or_else(|_| protected.clone());
+        if parent.starts_with(&protected) {
+            return Err(invalid_path(format!(
+                "Cannot write into {}",
+                protected.display()
+            )));
+        }
+    }
+    let target = parent.join(name);
+    if target.is_dir() {
+        return Err(invalid_path(format!("{} is a directory", target.display())));
+    }
+    Ok(target)
+}
+
+/// Write `contents` to `target`, first copying any existing file into
+/// `backup_dir`. Returns the backup's path.
+pub fn write_with_backup(
+    target: &Path,
+    contents: &str,
+    backup_dir: &Path,
+) -> Result<Option<PathBuf>, AppError> {
+    let backup = if target.exists() {
+        std::fs::create_dir_all(backup_dir).map_err(|e| AppError::FileSystemError {
+            path: backup_dir.display().to_string(),
+            message: "Failed to create backup directory".to_string(),
+            details: e.to_string(),
+        })?;
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let backup = backup_dir.join(format!(
+            "{}-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+            name
+        ));
+        std::fs::copy(target, &backup).map_err(|e| AppError::FileSystemError {
+            path: backup.display().to_string(),
+            message: "Failed to back up file".to_string(),
+            details: e.to_string(),
+        })?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    let mut temp = target.as_os_str().to_owned();
+    temp.push(".nexus-tmp");
+    let temp = PathBuf::from(temp);
+    std::fs::write(&temp, contents)
+        .and_then(|_| std::fs::rename(&temp, target))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&temp);
+            AppError::FileSystemError {
+                path: target.display().to_string(),
+                message: "Failed to write file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+    Ok(backup)
+}
+
+/// Blocks by id, plus insertion order for eviction
+type CachedBlocks = (HashMap<String, CodeBlock>, VecDeque<String>);
+
+/// Recently extracted blocks, so a block can be applied by id
+#[derive(Clone, Default)]
+pub struct CodeBlockCache {
+    blocks: Arc<Mutex<CachedBlocks>>,
+}
+
+impl CodeBlockCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock_blocks(&self) -> MutexGuard<'_, CachedBlocks> {
+        self.blocks.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Code block cache lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    pub fn insert(&self, blocks: &[CodeBlock]) {
+        let mut guard = self.lock_blocks();
+        let (by_id, order) = &mut *guard;
+        for block in blocks {
+            if by_id.insert(block.id.clone(), block.clone()).is_none() {
+                order.push_back(block.id.clone());
+            }
+        }
+        while order.len() > MAX_CACHED_BLOCKS {
+            if let Some(oldest) = order.pop_front() {
+                by_id.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, block_id: &str) -> Option<CodeBlock> {
+        self.lock_blocks().0.get(block_id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "Here you go:\n\n```rust title=\"src/main.rs\"\nfn main() {}\n```\n\nAnd a patch:\n\n~~~diff\n--- a.txt\n+++ a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+TWO\n~~~\n\n```\nplain\n";
+
+    #[test]
+    fn test_extract_blocks_with_hints() {
+        let blocks = extract("msg_1", MESSAGE);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].id, "msg_1:0");
+        assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+        assert_eq!(blocks[0].file_hint.as_deref(), Some("src/main.rs"));
+        assert_eq!(blocks[0].content, "fn main() {}\n");
+        assert_eq!(blocks[1].language.as_deref(), Some("diff"));
+        assert_eq!(blocks[2].language, None);
+        assert_eq!(blocks[2].content, "plain\n");
+
+        assert_eq!(
+            parse_info("ts:web/app.ts"),
+            (Some("ts".to_string()), Some("web/app.ts".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_apply_modes() {
+        let block = &extract("m", "```\nnew\n```")[0];
+        assert!(apply(block, Some("old\n"), ApplyMode::Create).is_err());
+        assert_eq!(apply(block, None, ApplyMode::Create).unwrap(), "new\n");
+        assert_eq!(
+            apply(block, Some("old"), ApplyMode::Append).unwrap(),
+            "old\nnew\n"
+        );
+        assert_eq!(
+            apply(block, Some("old\n"), ApplyMode::Overwrite).unwrap(),
+            "new\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_with_drift() {
+        let patch = &extract("m", MESSAGE)[1];
+        assert_eq!(
+            apply(patch, Some("zero\none\ntwo\nthree\n"), ApplyMode::Patch).unwrap(),
+            "zero\none\nTWO\nthree\n"
+        );
+        assert!(apply(patch, Some("unrelated\n"), ApplyMode::Patch).is_err());
+        assert!(apply_patch("x\n", "not a diff").is_err());
+    }
+
+    #[test]
+    fn test_write_backs_up_and_respects_protected_dirs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config = temp.path().join("config");
+        std::fs::create_dir(&config).unwrap();
+        let protected = vec![config.clone()];
+
+        assert!(check_target(Path::new("relative.txt"), &protected).is_err());
+        assert!(check_target(&config.join("settings.json"), &protected).is_err());
+        assert!(check_target(&temp.path().join("missing/a.txt"), &protected).is_err());
+
+        let target = check_target(&temp.path().join("a.txt"), &protected).unwrap();
+        assert_eq!(
+            write_with_backup(&target, "one\n", &config.join("bak")).unwrap(),
+            None
+        );
+        let backup = write_with_backup(&target, "two\n", &config.join("bak"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "two\n");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), "one\n");
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let cache = CodeBlockCache::new();
+        let blocks: Vec<CodeBlock> = (0..MAX_CACHED_BLOCKS + 1)
+            .map(|i| extract(&format!("m{}", i), "```\nx\n```").remove(0))
+            .collect();
+        cache.insert(&blocks);
+        assert!(cache.get("m0:0").is_none());
+        assert!(cache.get(&format!("m{}:0", MAX_CACHED_BLOCKS)).is_some());
+    }
+}
@@ -0,0 +1,319 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! What the connected server supports. The feature matrix is built from
+//! the version, and the feature list when the server sends one, reported
+//! right after connecting. Gated commands check it before calling the
+//! server, so an older server gets "needs ≥ vX.Y" instead of a bare 404.
+//!
+//! A server that doesn't report a version is assumed to support
+//! everything. If one of its routes turns out to be missing, that feature
+//! is marked unsupported for the rest of the connection.
+
+use crate::error::AppError;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Server functionality that not every release has
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerFeature {
+    /// Streamed responses over the event stream
+    Streaming,
+    /// Public share links for sessions
+    Share,
+    /// Listing the agents a session can run as
+    Agents,
+    /// Browsing and searching the server's workspace
+    FileApi,
+    /// Answering the agent's permission requests
+    Permissions,
+}
+
+impl ServerFeature {
+    pub const ALL: [ServerFeature; 5] = [
+        Self::Streaming,
+        Self::Share,
+        Self::Agents,
+        Self::FileApi,
+        Self::Permissions,
+    ];
+
+    /// Name in the server's `features` list and in error messages
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Streaming => "streaming",
+            Self::Share => "share",
+            Self::Agents => "agents",
+            Self::FileApi => "file_api",
+            Self::Permissions => "permissions",
+        }
+    }
+
+    /// First server release with the feature's endpoints
+    pub fn min_version(self) -> Version {
+        let (major, minor) = match self {
+            Self::Streaming => (0, 1),
+            Self::FileApi => (0, 2),
+            Self::Share => (0, 3),
+            Self::Permissions => (0, 5),
+            Self::Agents => (0, 6),
+        };
+        Version::new(major, minor, 0)
+    }
+}
+
+/// One row of the matrix, as shown to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureSupport {
+    pub feature: ServerFeature,
+    pub supported: bool,
+    /// Oldest server version with the feature, as `X.Y`
+    pub min_version: String,
+}
+
+/// Which features the connected server supports
+#[derive(Debug, Clone, Default)]
+pub struct FeatureMatrix {
+    server_version: Option<Version>,
+    /// Features the server listed itself, which overrides the version check
+    advertised: Option<BTreeSet<String>>,
+    /// Features whose routes turned out to be missing
+    missing: BTreeSet<ServerFeature>,
+}
+
+impl FeatureMatrix {
+    pub fn new(version: Option<&str>, advertised: Option<&[String]>) -> Self {
+        Self {
+            server_version: version.and_then(parse_version),
+            advertised: advertised
+                .map(|features| features.iter().map(|f| f.to_ascii_lowercase()).collect()),
+            missing: BTreeSet::new(),
+        }
+    }
+
+    pub fn server_version(&self) -> Option<&Version> {
+        self.server_version.as_ref()
+    }
+
+    pub fn supports(&self, feature: ServerFeature) -> bool {
+        if self.missing.contains(&feature) {
+            return false;
+        }
+        if let Some(advertised) = &self.advertised {
+            return advertised.contains(feature.name());
+        }
+        // Pre-releases of the minimum version already have the feature
+        self.server_version.as_ref().is_none_or(|version| {
+            let min = feature.min_version();
+            (version.major, version.minor, version.patch) >= (min.major, min.minor, min.patch)
+        })
+    }
+
+    pub fn require(&self, feature: ServerFeature) -> Result<(), AppError> {
+        if self.supports(feature) {
+            Ok(())
+        } else {
+            Err(self.unsupported(feature))
+        }
+    }
+
+    pub fn unsupported(&self, feature: ServerFeature) -> AppError {
+        AppError::Unsupported {
+            feature: feature.name().to_string(),
+            required_version: display_version(&feature.min_version()),
+            server_version: self.server_version.as_ref().map(Version::to_string),
+        }
+    }
+
+    pub fn summary(&self) -> Vec<FeatureSupport> {
+        ServerFeature::ALL
+            .into_iter()
+            .map(|feature| FeatureSupport {
+                feature,
+                supported: self.supports(feature),
+                min_version: display_version(&feature.min_version()),
+            })
+            .collect()
+    }
+}
+
+/// Server versions are semver, but some builds report `v0.5` or `0.5`
+fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    Version::parse(version).ok().or_else(|| {
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+        parts
+            .next()
+            .is_none()
+            .then(|| Version::new(major, minor, 0))
+    })
+}
+
+fn display_version(version: &Version) -> String {
+    format!("{}.{}", version.major, version.minor)
+}
+
+/// Whether a 404 means the server has no such route, rather than no such
+/// session or file. The server describes missing resources in a JSON body;
+/// unknown routes get a plain-text one.
+pub fn is_missing_route(error: &AppError) -> bool {
+    match error {
+        AppError::ServerError {
+            status_code: 404,
+            details,
+            ..
+        } => serde_json::from_str::<serde_json::Value>(details).is_err(),
+        _ => false,
+    }
+}
+
+/// The matrix for the current connection, shared by command handlers
+#[derive(Clone, Default)]
+pub struct Compatibility(Arc<Mutex<FeatureMatrix>>);
+
+impl Compatibility {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FeatureMatrix> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set(&self, matrix: FeatureMatrix) {
+        *self.lock() = matrix;
+    }
+
+    /// Forget the server, assuming everything is supported again
+    pub fn clear(&self) {
+        self.set(FeatureMatrix::default());
+    }
+
+    pub fn matrix(&self) -> FeatureMatrix {
+        self.lock().clone()
+    }
+
+    pub fn require(&self, feature: ServerFeature) -> Result<(), AppError> {
+        self.lock().require(feature)
+    }
+
+    /// Pass through the result of a call to `feature`'s endpoints, turning
+    /// a missing route into the unsupported error and remembering it
+    pub fn check<T>(
+        &self,
+        feature: ServerFeature,
+        result: Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        result.map_err(|error| match error.downcast_ref::<AppError>() {
+            Some(app_error) if is_missing_route(app_error) => {
+                let mut matrix = self.lock();
+                matrix.missing.insert(feature);
+                matrix.unsupported(feature).into()
+            }
+            _ => error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_found(details: &str) -> Box<dyn std::error::Error> {
+        AppError::ServerError {
+            status_code: 404,
+            message: "Not Found".to_string(),
+            details: details.to_string(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_version_gates_features() {
+        let matrix = FeatureMatrix::new(Some("v0.3.2"), None);
+        assert!(matrix.supports(ServerFeature::Streaming));
+        assert!(matrix.supports(ServerFeature::Share));
+        assert!(!matrix.supports(ServerFeature::Permissions));
+
+        match matrix.require(ServerFeature::Agents) {
+            Err(AppError::Unsupported {
+                feature,
+                required_version,
+                server_version,
+            }) => {
+                assert_eq!(feature, "agents");
+                assert_eq!(required_version, "0.6");
+                assert_eq!(server_version.as_deref(), Some("0.3.2"));
+            }
+            other => panic!("expected unsupported, got {:?}", other),
+        }
+
+        assert!(FeatureMatrix::new(Some("0.6.0-beta.1"), None).supports(ServerFeature::Agents));
+        assert!(FeatureMatrix::new(Some("0.5"), None).supports(ServerFeature::Permissions));
+    }
+
+    #[test]
+    fn test_unknown_version_supports_everything() {
+        for version in [None, Some("dev")] {
+            let matrix = FeatureMatrix::new(version, None);
+            assert!(ServerFeature::ALL.iter().all(|f| matrix.supports(*f)));
+        }
+    }
+
+    #[test]
+    fn test_advertised_features_override_version() {
+        let advertised = vec!["Streaming".to_string(), "agents".to_string()];
+        let matrix = FeatureMatrix::new(Some("0.1.0"), Some(&advertised));
+        assert!(matrix.supports(ServerFeature::Agents));
+        assert!(!matrix.supports(ServerFeature::FileApi));
+    }
+
+    #[test]
+    fn test_missing_route_marks_feature_unsupported() {
+        let compatibility = Compatibility::new();
+
+        let result: Result<(), _> = compatibility.check(
+            ServerFeature::FileApi,
+            Err(not_found(r#"{"error":"no file"}"#)),
+        );
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<AppError>(),
+            Some(AppError::ServerError { .. })
+        ));
+        assert!(compatibility.require(ServerFeature::FileApi).is_ok());
+
+        let result: Result<(), _> =
+            compatibility.check(ServerFeature::FileApi, Err(not_found("404 Not Found")));
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<AppError>(),
+            Some(AppError::Unsupported { .. })
+        ));
+        assert!(compatibility.require(ServerFeature::FileApi).is_err());
+
+        compatibility.clear();
+        assert!(compatibility.require(ServerFeature::FileApi).is_ok());
+    }
+}
@@ -20,32 +20,40 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::error::{retry_with_backoff, AppError, RetryConfig};
-use reqwest::Client;
+use crate::api_client::ModelConfig;
+use crate::certificate_pinning::{self, CERTIFICATE_MISMATCH_EVENT};
+use crate::error::{retry_with_backoff, AppError, ErrorCode, RetryConfig};
+use crate::health_history::{self, HealthStatus};
+use crate::i18n::t;
+use crate::proxy::{self, ProxySettings};
+use crate::settings::RetryOperation;
+use futures_util::StreamExt;
+use reqwest_eventsource::{Event, EventSource};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 use tauri::Emitter;
 use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
 
-// Enhanced logging macros that log to both console and file
-macro_rules! log_info {
-    ($($arg:tt)*) => {
-        let message = format!("[INFO] {}", format!($($arg)*));
-        println!("{}", message);
-        crate::log_to_file(&message);
-    };
-}
+/// Time allowed for a connection test or health check
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
-macro_rules! log_warn {
-    ($($arg:tt)*) => {
-        let message = format!("[WARN] {}", format!($($arg)*));
-        println!("{}", message);
-        crate::log_to_file(&message);
-    };
-}
+/// Time allowed for each server when probing all saved connections
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Saved servers probed at the same time
+const PROBE_CONCURRENCY: usize = 4;
+
+/// Longest request timeout a profile may set
+const MAX_PROFILE_TIMEOUT_SECS: u64 = 3600;
+
+/// Profiles of the saved connections by connection id, read by the HTTP
+/// clients when they build a request
+static PROFILES: RwLock<BTreeMap<String, ConnectionProfile>> = RwLock::new(BTreeMap::new());
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Copy)]
 pub enum ConnectionStatus {
@@ -80,6 +88,52 @@ pub enum ConnectionEventType {
     Disconnected,
     Error,
     HealthCheck,
+    /// A failed connection is about to be retried after `delay_ms`
+    Reconnecting {
+        server_url: String,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+    },
+    /// A failed connection passed a health check again
+    Reconnected {
+        server_url: String,
+        attempts: u32,
+    },
+}
+
+/// A global event from the server's `/event` feed, such as a session changed
+/// from the TUI or a permission ask
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub properties: serde_json::Value,
+    /// Connection whose feed the event came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+}
+
+impl ServerEvent {
+    /// Parse the data of one SSE message; heartbeats and malformed data yield None
+    pub fn parse(data: &str) -> Option<Self> {
+        serde_json::from_str(data).ok()
+    }
+}
+
+/// Whether a saved server answered a probe, and how quickly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reachability {
+    pub name: String,
+    pub url: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +143,193 @@ pub struct ServerConnection {
     pub port: u16,
     pub secure: bool,
     pub last_connected: Option<String>,
+    /// Pinned SHA-256 fingerprint of the server certificate (HTTPS only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_fingerprint: Option<String>,
+    /// Request settings for this server
+    #[serde(default)]
+    pub profile: ConnectionProfile,
+}
+
+/// How requests authenticate with the API key stored for a connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthMethod {
+    /// `Authorization: Bearer <key>`
+    #[default]
+    Bearer,
+    /// HTTP basic auth with the key as password
+    Basic { username: String },
+    /// No credentials, e.g. when a custom header carries them
+    None,
+}
+
+/// Per-server settings applied to every request sent to a connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ConnectionProfile {
+    /// Model for prompts that don't pick one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<ModelConfig>,
+    /// System prompt for prompts that don't set one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Replaces the default timeout of non-streaming requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+    /// Sent with every request, e.g. for a proxy in front of the server
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    pub auth_method: AuthMethod,
+    /// How the connection is retried after a failed health check
+    pub reconnect: ReconnectPolicy,
+    /// Replaces the global proxy for this server; a disabled one connects
+    /// directly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxySettings>,
+}
+
+/// Backoff used to reconnect after a failed health check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    pub enabled: bool,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub max_attempts: u32,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_delay_ms: 1000,
+            max_delay_ms: 60_000,
+            max_attempts: 10,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Largest `max_attempts` accepted from the frontend
+    pub const MAX_ATTEMPTS: u32 = 100;
+
+    /// Longest delay accepted between attempts
+    pub const MAX_DELAY_MS: u64 = 3_600_000;
+
+    /// Check that the policy is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 || self.max_attempts > Self::MAX_ATTEMPTS {
+            return Err(format!(
+                "Reconnect attempts must be between 1 and {}",
+                Self::MAX_ATTEMPTS
+            ));
+        }
+        if self.initial_delay_ms == 0 || self.initial_delay_ms > self.max_delay_ms {
+            return Err(
+                "Initial reconnect delay must be positive and at most the max delay".to_string(),
+            );
+        }
+        if self.max_delay_ms > Self::MAX_DELAY_MS {
+            return Err(format!(
+                "Max reconnect delay must be at most {} ms",
+                Self::MAX_DELAY_MS
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err("Reconnect jitter must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl From<&ReconnectPolicy> for RetryConfig {
+    fn from(policy: &ReconnectPolicy) -> Self {
+        Self {
+            max_retries: policy.max_attempts,
+            initial_delay_ms: policy.initial_delay_ms,
+            max_delay_ms: policy.max_delay_ms,
+            backoff_multiplier: 2.0,
+            jitter: policy.jitter,
+        }
+    }
+}
+
+impl ConnectionProfile {
+    /// Reject settings that would make every request to the server fail
+    pub fn validate(&self) -> Result<(), String> {
+        self.reconnect.validate()?;
+        if let Some(proxy) = &self.proxy {
+            proxy.validate()?;
+        }
+        if let Some(model) = &self.default_model {
+            if model.provider_id.trim().is_empty() || model.model_id.trim().is_empty() {
+                return Err("Default model needs a provider and a model id".to_string());
+            }
+        }
+        if let Some(secs) = self.request_timeout_secs {
+            if !(1..=MAX_PROFILE_TIMEOUT_SECS).contains(&secs) {
+                return Err(format!(
+                    "Request timeout must be between 1 and {} seconds",
+                    MAX_PROFILE_TIMEOUT_SECS
+                ));
+            }
+        }
+        if let AuthMethod::Basic { username } = &self.auth_method {
+            if username.is_empty() || username.contains(':') {
+                return Err("Basic auth needs a username without ':'".to_string());
+            }
+        }
+        for (name, value) in &self.headers {
+            let header = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name: {}", name))?;
+            reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for header {}", name))?;
+            if header == reqwest::header::AUTHORIZATION && self.auth_method != AuthMethod::None {
+                return Err(
+                    "Set the auth method to none to send a custom Authorization header".to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Timeout for non-streaming requests, when the profile overrides it
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Add the profile's headers and credentials to a request
+    pub fn authorize(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        api_key: Option<&str>,
+    ) -> reqwest::RequestBuilder {
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        match (&self.auth_method, api_key) {
+            (AuthMethod::Bearer, Some(key)) => request.bearer_auth(key),
+            (AuthMethod::Basic { username }, key) => request.basic_auth(username, key),
+            _ => request,
+        }
+    }
+}
+
+/// Profile of the saved connection serving `server_url`, or the defaults
+/// when it has none
+pub fn profile_for_url(server_url: &str) -> ConnectionProfile {
+    let Some(connection_id) = connection_id_for_url(server_url) else {
+        return ConnectionProfile::default();
+    };
+    let profiles = match PROFILES.read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    profiles.get(&connection_id).cloned().unwrap_or_default()
 }
 
 impl ServerConnection {
@@ -96,155 +337,234 @@ impl ServerConnection {
         let protocol = if self.secure { "https" } else { "http" };
         format!("{}://{}:{}", protocol, self.hostname, self.port)
     }
+
+    /// Id the connection's API key is stored under
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.hostname, self.port)
+    }
+
+    /// Check that the connection can be saved
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Connection name cannot be empty".to_string());
+        }
+        if self.hostname.trim().is_empty() {
+            return Err("Hostname cannot be empty".to_string());
+        }
+        if self.port == 0 {
+            return Err("Port must be between 1 and 65535".to_string());
+        }
+        if let Some(fingerprint) = &self.certificate_fingerprint {
+            if !self.secure {
+                return Err("Certificate pinning requires an HTTPS connection".to_string());
+            }
+            certificate_pinning::normalize_fingerprint(fingerprint)
+                .map_err(|e| e.user_message())?;
+        }
+        self.profile.validate()
+    }
+}
+
+/// Id of the connection serving `server_url`, as [`ServerConnection::id`]
+pub fn connection_id_for_url(server_url: &str) -> Option<String> {
+    let url = url::Url::parse(server_url).ok()?;
+    Some(format!(
+        "{}:{}",
+        url.host_str()?,
+        url.port_or_known_default()?
+    ))
+}
+
+/// A server the manager is connected, or connecting, to
+#[derive(Debug, Clone)]
+struct ActiveConnection {
+    server_url: String,
+    status: ConnectionStatus,
+    /// The `/event` subscription allowed to run for it
+    event_generation: u64,
 }
 
+/// An active connection, as listed for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActiveConnectionInfo {
+    pub connection_id: String,
+    pub server_url: String,
+    pub status: ConnectionStatus,
+    /// Commands that don't name a connection use this one
+    pub primary: bool,
+}
+
+/// Clones share the same connections, status and event channel
+#[derive(Clone)]
 pub struct ConnectionManager {
     config_dir: PathBuf,
-    client: Client,
-    server_url: Arc<Mutex<Option<String>>>,
-    connection_status: Arc<Mutex<ConnectionStatus>>,
+    /// Servers connected to at the same time, by connection id
+    active: Arc<Mutex<HashMap<String, ActiveConnection>>>,
+    /// The connection commands use unless they name another
+    primary: Arc<Mutex<Option<String>>>,
     event_sender: broadcast::Sender<ConnectionEvent>,
     app_handle: Option<tauri::AppHandle>,
     connections: Arc<Mutex<HashMap<String, ServerConnection>>>,
-    current_connection: Arc<Mutex<Option<String>>>,
+    server_event_sender: broadcast::Sender<ServerEvent>,
+    /// Bumped for every new `/event` subscription so a stale one stops
+    event_generation: Arc<AtomicU64>,
 }
 
 impl ConnectionManager {
     pub fn new(config_dir: PathBuf, app_handle: Option<tauri::AppHandle>) -> Result<Self, String> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        certificate_pinning::shared_client()
             .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
         let (event_sender, _) = broadcast::channel(100);
+        let (server_event_sender, _) = broadcast::channel(100);
 
         Ok(Self {
             config_dir,
-            client,
-            server_url: Arc::new(Mutex::new(None)),
-            connection_status: Arc::new(Mutex::new(ConnectionStatus::Disconnected)),
+            active: Arc::new(Mutex::new(HashMap::new())),
+            primary: Arc::new(Mutex::new(None)),
             event_sender,
             app_handle,
             connections: Arc::new(Mutex::new(HashMap::new())),
-            current_connection: Arc::new(Mutex::new(None)),
+            server_event_sender,
+            event_generation: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    fn active(&self) -> MutexGuard<'_, HashMap<String, ActiveConnection>> {
+        self.active
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn primary_id(&self) -> Option<String> {
+        self.primary
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn set_primary_id(&self, connection_id: Option<String>) {
+        *self
+            .primary
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = connection_id;
+    }
+
+    /// Connect to a server, alongside any already connected, and make it
+    /// the primary connection
     pub async fn connect_to_server(
         &mut self,
         hostname: &str,
         port: u16,
         secure: bool,
     ) -> Result<(), String> {
-        // Check if already connected
-        let current_status = match self.connection_status.lock() {
-            Ok(status) => *status,
-            Err(poisoned) => {
-                eprintln!(
-                    "[ERROR] ConnectionManager connect: status mutex poisoned, recovering..."
-                );
-                *poisoned.into_inner()
-            }
-        };
-        if matches!(
-            current_status,
-            ConnectionStatus::Connected | ConnectionStatus::Connecting
-        ) {
-            return Err("Already connected to a server".to_string());
-        }
+        let connection_id = format!("{}:{}", hostname, port);
+        let server_url = format!(
+            "{}://{}:{}",
+            if secure { "https" } else { "http" },
+            hostname,
+            port
+        );
 
-        // Update status to connecting
-        match self.connection_status.lock() {
-            Ok(mut status) => *status = ConnectionStatus::Connecting,
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager connect: failed to set connecting status, mutex poisoned");
-                return Err("Internal error: connection state corrupted".to_string());
+        {
+            let mut active = self.active();
+            if active.get(&connection_id).is_some_and(|connection| {
+                matches!(
+                    connection.status,
+                    ConnectionStatus::Connected | ConnectionStatus::Connecting
+                )
+            }) {
+                return Err("Already connected to this server".to_string());
             }
+            active.insert(
+                connection_id.clone(),
+                ActiveConnection {
+                    server_url: server_url.clone(),
+                    status: ConnectionStatus::Connecting,
+                    event_generation: 0,
+                },
+            );
         }
 
         // Send connecting event
         let _ = self.event_sender.send(ConnectionEvent {
             timestamp: SystemTime::now(),
             event_type: ConnectionEventType::Connected,
-            message: format!("Connecting to {}:{}...", hostname, port),
+            message: t(
+                "connection.connecting",
+                &[("host", hostname), ("port", &port.to_string())],
+            ),
         });
 
         // Test the connection
-        let server_info = self.test_server_connection(hostname, port, secure).await?;
-
-        // Store the server URL
-        let server_url = format!(
-            "{}://{}:{}",
-            if secure { "https" } else { "http" },
-            hostname,
-            port
-        );
-        match self.server_url.lock() {
-            Ok(mut url) => *url = Some(server_url.clone()),
-            Err(poisoned) => {
-                eprintln!(
-                    "[ERROR] ConnectionManager connect: failed to store server URL, mutex poisoned"
-                );
-                return Err("Internal error: connection state corrupted".to_string());
+        let server_info = match self.test_server_connection(hostname, port, secure).await {
+            Ok(server_info) => server_info,
+            Err(e) => {
+                self.active().remove(&connection_id);
+                return Err(e);
             }
-        }
-
-        // Update status to connected
-        match self.connection_status.lock() {
-            Ok(mut status) => *status = ConnectionStatus::Connected,
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager connect: failed to set connected status, mutex poisoned");
-                return Err("Internal error: connection state corrupted".to_string());
-            }
-        }
-
-        // Store connection info
-        let connection = ServerConnection {
-            name: format!("{}:{}", hostname, port),
-            hostname: hostname.to_string(),
-            port,
-            secure,
-            last_connected: Some(chrono::Utc::now().to_rfc3339()),
         };
 
-        let connection_id = connection.name.clone();
-        match self.connections.lock() {
-            Ok(mut connections) => {
-                connections.insert(connection_id.clone(), connection);
-            }
-            Err(poisoned) => {
-                eprintln!(
-                    "[ERROR] ConnectionManager connect: connections mutex poisoned, recovering..."
-                );
-                poisoned
-                    .into_inner()
-                    .insert(connection_id.clone(), connection);
-            }
+        if let Some(connection) = self.active().get_mut(&connection_id) {
+            connection.status = ConnectionStatus::Connected;
         }
-        match self.current_connection.lock() {
-            Ok(mut current) => *current = Some(connection_id.clone()),
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager connect: current_connection mutex poisoned, recovering...");
-                *poisoned.into_inner() = Some(connection_id.clone());
-            }
+        self.set_primary_id(Some(connection_id.clone()));
+
+        // Store connection info, keeping the pin of a previously saved entry
+        {
+            let mut connections = match self.connections.lock() {
+                Ok(connections) => connections,
+                Err(poisoned) => {
+                    eprintln!(
+                        "[ERROR] ConnectionManager connect: connections mutex poisoned, recovering..."
+                    );
+                    poisoned.into_inner()
+                }
+            };
+            let existing = connections.get(&connection_id);
+            let certificate_fingerprint =
+                existing.and_then(|existing| existing.certificate_fingerprint.clone());
+            let profile = existing
+                .map(|existing| existing.profile.clone())
+                .unwrap_or_default();
+            connections.insert(
+                connection_id.clone(),
+                ServerConnection {
+                    name: connection_id.clone(),
+                    hostname: hostname.to_string(),
+                    port,
+                    secure,
+                    last_connected: Some(chrono::Utc::now().to_rfc3339()),
+                    certificate_fingerprint,
+                    profile,
+                },
+            );
         }
 
         // Save connections to disk
-        self.save_connections()?;
+        self.save_connections().await?;
 
         // Send connected event
         let _ = self.event_sender.send(ConnectionEvent {
             timestamp: SystemTime::now(),
             event_type: ConnectionEventType::Connected,
-            message: format!(
-                "Connected to {} (version: {})",
-                server_info.name,
-                server_info.version.unwrap_or_else(|| "unknown".to_string())
+            message: t(
+                "connection.connected",
+                &[
+                    ("server", &server_info.name),
+                    (
+                        "version",
+                        &server_info
+                            .version
+                            .unwrap_or_else(|| t("connection.version_unknown", &[])),
+                    ),
+                ],
             ),
         });
 
-        // Start health monitoring
-        self.start_health_monitoring();
+        // Start health monitoring and follow the server's global events
+        self.start_health_monitoring(&connection_id);
+        self.start_server_events(&connection_id, &server_url);
 
         Ok(())
     }
@@ -255,7 +575,8 @@ impl ConnectionManager {
         port: u16,
         secure: bool,
     ) -> Result<ServerInfo, String> {
-        let client = self.client.clone();
+        let client = certificate_pinning::shared_client()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
         let hostname_clone = hostname.to_string();
 
         // Use retry logic with exponential backoff for network resilience
@@ -271,7 +592,7 @@ impl ConnectionManager {
                         port
                     );
 
-                    let response = client.get(&url).send().await?;
+                    let response = client.get(&url).timeout(REQUEST_TIMEOUT).send().await?;
 
                     if !response.status().is_success() {
                         return Err(AppError::ServerError {
@@ -315,46 +636,122 @@ impl ConnectionManager {
                     })
                 }
             },
-            RetryConfig::default(),
+            RetryConfig::for_operation(RetryOperation::ConnectionTest),
         )
         .await;
 
+        if let Err(AppError::CertificateMismatch {
+            hostname,
+            expected,
+            actual,
+        }) = &result
+        {
+            if let Some(app_handle) = &self.app_handle {
+                let _ = app_handle.emit(
+                    CERTIFICATE_MISMATCH_EVENT,
+                    certificate_pinning::CertificateMismatch {
+                        hostname: hostname.clone(),
+                        expected: expected.clone(),
+                        actual: actual.clone(),
+                    },
+                );
+            }
+        }
+
         // Convert AppError to String for backward compatibility
         result.map_err(|e| e.user_message())
     }
 
-    pub fn get_connection_status(&self) -> ConnectionStatus {
-        match self.connection_status.lock() {
-            Ok(status) => *status,
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager get_connection_status: mutex poisoned, returning Disconnected");
-                ConnectionStatus::Disconnected
+    /// Probe every saved server, a few at a time and without retries, so
+    /// the connection picker can show which are up. Results are sorted by
+    /// connection name.
+    pub async fn test_all_saved_connections(&self) -> Vec<Reachability> {
+        let mut connections = self.get_saved_connections();
+        connections.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let client = match certificate_pinning::shared_client() {
+            Ok(client) => client,
+            Err(e) => {
+                let error = format!("Failed to create HTTP client: {}", e);
+                return connections
+                    .into_iter()
+                    .map(|connection| Reachability {
+                        url: connection.to_url(),
+                        name: connection.name,
+                        reachable: false,
+                        latency_ms: None,
+                        status_code: None,
+                        error: Some(error.clone()),
+                    })
+                    .collect();
             }
+        };
+
+        futures_util::stream::iter(connections)
+            .map(|connection| probe(client.clone(), connection))
+            .buffered(PROBE_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Status of the primary connection
+    pub fn get_connection_status(&self) -> ConnectionStatus {
+        match self.primary_id() {
+            Some(connection_id) => self.connection_status(&connection_id),
+            None => ConnectionStatus::Disconnected,
         }
     }
 
+    pub fn connection_status(&self, connection_id: &str) -> ConnectionStatus {
+        self.active()
+            .get(connection_id)
+            .map_or(ConnectionStatus::Disconnected, |connection| {
+                connection.status
+            })
+    }
+
+    /// URL of the primary connection
     pub fn get_server_url(&self) -> Option<String> {
-        match self.server_url.lock() {
-            Ok(url) => url.clone(),
-            Err(poisoned) => {
-                eprintln!(
-                    "[ERROR] ConnectionManager get_server_url: mutex poisoned, returning None"
-                );
-                None
-            }
+        self.server_url_for(&self.primary_id()?)
+    }
+
+    /// URL of an active connection
+    pub fn server_url_for(&self, connection_id: &str) -> Option<String> {
+        self.active()
+            .get(connection_id)
+            .filter(|connection| connection.status != ConnectionStatus::Connecting)
+            .map(|connection| connection.server_url.clone())
+    }
+
+    /// Connections currently open, ordered by id
+    pub fn active_connections(&self) -> Vec<ActiveConnectionInfo> {
+        let primary = self.primary_id();
+        let mut connections: Vec<ActiveConnectionInfo> = self
+            .active()
+            .iter()
+            .map(|(connection_id, connection)| ActiveConnectionInfo {
+                connection_id: connection_id.clone(),
+                server_url: connection.server_url.clone(),
+                status: connection.status,
+                primary: primary.as_deref() == Some(connection_id.as_str()),
+            })
+            .collect();
+        connections.sort_by(|a, b| a.connection_id.cmp(&b.connection_id));
+        connections
+    }
+
+    /// Make an active connection the one commands use by default
+    pub fn set_primary(&self, connection_id: &str) -> Result<(), String> {
+        if self.server_url_for(connection_id).is_none() {
+            return Err(format!("Not connected to {}", connection_id));
         }
+        self.set_primary_id(Some(connection_id.to_string()));
+        Ok(())
     }
 
+    /// The saved entry of the primary connection
     pub fn get_current_connection(&self) -> Option<ServerConnection> {
-        let current_id = match self.current_connection.lock() {
-            Ok(id) => id.clone(),
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager get_current_connection: current_connection mutex poisoned");
-                None
-            }
-        };
-
-        if let Some(id) = current_id {
+        if let Some(id) = self.primary_id() {
             match self.connections.lock() {
                 Ok(connections) => connections.get(&id).cloned(),
                 Err(poisoned) => {
@@ -401,56 +798,175 @@ impl ConnectionManager {
         self.get_last_used_connection().map(|c| c.to_url())
     }
 
-    pub fn save_connection(&mut self, connection: ServerConnection) -> Result<(), String> {
-        let mut connections_guard = match self.connections.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager save_connection: mutex poisoned, cannot save connection");
-                return Err("Internal error: connection state corrupted".to_string());
+    pub async fn save_connection(
+        &mut self,
+        mut connection: ServerConnection,
+    ) -> Result<(), String> {
+        connection.validate()?;
+        if let Some(fingerprint) = &connection.certificate_fingerprint {
+            connection.certificate_fingerprint = Some(
+                certificate_pinning::normalize_fingerprint(fingerprint)
+                    .map_err(|e| e.user_message())?,
+            );
+        }
+
+        // Release the lock before saving
+        {
+            let mut connections_guard = match self.connections.lock() {
+                Ok(guard) => guard,
+                Err(_) => {
+                    eprintln!("[ERROR] ConnectionManager save_connection: mutex poisoned, cannot save connection");
+                    return Err("Internal error: connection state corrupted".to_string());
+                }
+            };
+            // Profiles, pins and proxies are keyed by host:port, so a server
+            // may only be saved once
+            let id = connection.id();
+            if let Some(other) = connections_guard
+                .values()
+                .find(|saved| saved.name != connection.name && saved.id() == id)
+            {
+                return Err(format!("{} is already saved as \"{}\"", id, other.name));
             }
-        };
-        connections_guard.insert(connection.name.clone(), connection);
-        drop(connections_guard); // Release lock before calling save_connections
-        self.save_connections()
+            connections_guard.insert(connection.name.clone(), connection);
+        }
+        self.apply_certificate_pins();
+        self.apply_profiles();
+        self.save_connections().await
     }
 
-    pub async fn disconnect_from_server(&mut self) -> Result<(), String> {
-        let current_status = match self.connection_status.lock() {
-            Ok(status) => *status,
-            Err(poisoned) => {
-                eprintln!(
-                    "[ERROR] ConnectionManager disconnect: status mutex poisoned, recovering..."
-                );
-                *poisoned.into_inner()
+    /// Pin or unpin the certificate fingerprint of a saved HTTPS connection
+    pub async fn set_certificate_pin(
+        &mut self,
+        name: &str,
+        fingerprint: Option<&str>,
+    ) -> Result<ServerConnection, String> {
+        let mut connection = self
+            .get_saved_connections()
+            .into_iter()
+            .find(|connection| connection.name == name)
+            .ok_or_else(|| format!("Connection not found: {}", name))?;
+        connection.certificate_fingerprint = fingerprint.map(str::to_string);
+        self.save_connection(connection.clone()).await?;
+        Ok(self
+            .get_saved_connections()
+            .into_iter()
+            .find(|connection| connection.name == name)
+            .unwrap_or(connection))
+    }
+
+    /// Profile of a saved connection by its id
+    pub fn connection_profile(&self, connection_id: &str) -> Option<ConnectionProfile> {
+        self.get_saved_connections()
+            .into_iter()
+            .find(|connection| connection.id() == connection_id)
+            .map(|connection| connection.profile)
+    }
+
+    /// Replace the profile of every saved entry for `connection_id`
+    pub async fn update_connection_profile(
+        &mut self,
+        connection_id: &str,
+        profile: ConnectionProfile,
+    ) -> Result<ConnectionProfile, String> {
+        profile.validate()?;
+        {
+            let mut connections = match self.connections.lock() {
+                Ok(guard) => guard,
+                Err(_) => {
+                    eprintln!("[ERROR] ConnectionManager update_connection_profile: mutex poisoned, cannot update profile");
+                    return Err("Internal error: connection state corrupted".to_string());
+                }
+            };
+            let mut found = false;
+            for connection in connections
+                .values_mut()
+                .filter(|connection| connection.id() == connection_id)
+            {
+                connection.profile = profile.clone();
+                found = true;
+            }
+            if !found {
+                return Err(format!("Connection not found: {}", connection_id));
             }
-        };
-        if matches!(current_status, ConnectionStatus::Disconnected) {
-            return Ok(());
         }
+        self.apply_profiles();
+        self.save_connections().await?;
+        Ok(profile)
+    }
 
-        // Update status
-        match self.connection_status.lock() {
-            Ok(mut status) => *status = ConnectionStatus::Disconnected,
+    /// Publish the profiles of saved connections to the HTTP clients
+    fn apply_profiles(&self) {
+        let mut profiles: BTreeMap<String, ConnectionProfile> = BTreeMap::new();
+        for connection in self.saved_connections_by_name() {
+            profiles
+                .entry(connection.id())
+                .or_insert(connection.profile);
+        }
+        proxy::set_connection_proxies(
+            profiles
+                .iter()
+                .filter_map(|(id, profile)| profile.proxy.clone().map(|proxy| (id.clone(), proxy))),
+        );
+        match PROFILES.write() {
+            Ok(mut guard) => *guard = profiles,
             Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager disconnect: failed to set disconnected status, mutex poisoned");
-                return Err("Internal error: connection state corrupted".to_string());
+                eprintln!("[ERROR] Connection profiles lock poisoned, recovering...");
+                *poisoned.into_inner() = profiles;
             }
         }
+    }
 
-        // Clear server URL
-        match self.server_url.lock() {
-            Ok(mut url) => *url = None,
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager disconnect: failed to clear server URL, mutex poisoned");
-                return Err("Internal error: connection state corrupted".to_string());
+    /// Publish the pins of saved connections to the TLS verifier
+    fn apply_certificate_pins(&self) {
+        let mut pins: BTreeMap<String, String> = BTreeMap::new();
+        for connection in self.saved_connections_by_name() {
+            if let (true, Some(fingerprint)) =
+                (connection.secure, connection.certificate_fingerprint)
+            {
+                pins.entry(connection.hostname).or_insert(fingerprint);
+            }
+        }
+        certificate_pinning::set_pins(pins);
+    }
+
+    /// Saved connections in name order, so that when files saved before
+    /// duplicates were refused hold a server twice, the same one wins
+    fn saved_connections_by_name(&self) -> Vec<ServerConnection> {
+        let mut connections = self.get_saved_connections();
+        connections.sort_by(|a, b| a.name.cmp(&b.name));
+        connections
+    }
+
+    /// Disconnect the primary connection
+    pub async fn disconnect_from_server(&mut self) -> Result<(), String> {
+        match self.primary_id() {
+            Some(connection_id) => self.disconnect(&connection_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Disconnect one server, leaving the others connected. When it was the
+    /// primary connection another active one takes over.
+    pub async fn disconnect(&mut self, connection_id: &str) -> Result<(), String> {
+        let remaining = {
+            let mut active = self.active();
+            if active.remove(connection_id).is_none() {
+                return Ok(());
             }
+            let mut remaining: Vec<String> = active.keys().cloned().collect();
+            remaining.sort();
+            remaining
+        };
+        if self.primary_id().as_deref() == Some(connection_id) {
+            self.set_primary_id(remaining.into_iter().next());
         }
 
         // Send disconnected event
         let _ = self.event_sender.send(ConnectionEvent {
             timestamp: SystemTime::now(),
             event_type: ConnectionEventType::Disconnected,
-            message: "Disconnected from server".to_string(),
+            message: t("connection.disconnected", &[]),
         });
 
         Ok(())
@@ -460,6 +976,16 @@ impl ConnectionManager {
         self.event_sender.subscribe()
     }
 
+    /// Global events from the connected server's `/event` feed
+    pub fn subscribe_to_server_events(&self) -> broadcast::Receiver<ServerEvent> {
+        self.server_event_sender.subscribe()
+    }
+
+    /// Events not yet read by every subscriber
+    pub fn event_backlog(&self) -> usize {
+        self.event_sender.len()
+    }
+
     /// Emit a connection event to the frontend via Tauri event system
     pub fn emit_event(&self, event: &ConnectionEvent) {
         if let Some(app_handle) = &self.app_handle {
@@ -471,35 +997,38 @@ impl ConnectionManager {
         self.config_dir.join("server_connections.json")
     }
 
-    fn save_connections(&self) -> Result<(), String> {
-        let connections_guard = match self.connections.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                eprintln!("[ERROR] ConnectionManager save_connections: mutex poisoned, cannot save connections");
-                return Err("Internal error: connection state corrupted".to_string());
-            }
+    async fn save_connections(&self) -> Result<(), String> {
+        let json = {
+            let connections_guard = match self.connections.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => {
+                    eprintln!("[ERROR] ConnectionManager save_connections: mutex poisoned, cannot save connections");
+                    return Err("Internal error: connection state corrupted".to_string());
+                }
+            };
+            let connections: Vec<&ServerConnection> = connections_guard.values().collect();
+            serde_json::to_string_pretty(&connections)
+                .map_err(|e| format!("Failed to serialize connections: {}", e))?
         };
-        let connections: Vec<&ServerConnection> = connections_guard.values().collect();
-        let json = serde_json::to_string_pretty(&connections)
-            .map_err(|e| format!("Failed to serialize connections: {}", e))?;
 
-        std::fs::write(self.get_connections_file_path(), json)
+        tokio::fs::write(self.get_connections_file_path(), json)
+            .await
             .map_err(|e| format!("Failed to write connections file: {}", e))?;
 
         Ok(())
     }
 
-    pub fn load_connections(&mut self) -> Result<(), String> {
+    pub async fn load_connections(&mut self) -> Result<(), String> {
         let file_path = self.get_connections_file_path();
 
-        if !file_path.exists() {
-            return Ok(()); // No connections file yet, that's fine
-        }
-
-        let json = std::fs::read_to_string(&file_path)
-            .map_err(|e| format!("Failed to read connections file: {}", e))?;
+        let json = match tokio::fs::read(&file_path).await {
+            Ok(json) => json,
+            // No connections file yet, that's fine
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("Failed to read connections file: {}", e)),
+        };
 
-        let connections: Vec<ServerConnection> = serde_json::from_str(&json)
+        let connections: Vec<ServerConnection> = serde_json::from_slice(&json)
             .map_err(|e| format!("Failed to deserialize connections: {}", e))?;
 
         let mut connections_map = match self.connections.lock() {
@@ -513,6 +1042,9 @@ impl ConnectionManager {
         for connection in connections {
             connections_map.insert(connection.name.clone(), connection);
         }
+        drop(connections_map);
+        self.apply_certificate_pins();
+        self.apply_profiles();
 
         Ok(())
     }
@@ -521,7 +1053,7 @@ impl ConnectionManager {
     /// Attempts to reconnect to the most recently used server
     pub async fn restore_connection(&mut self) -> Result<(), String> {
         // Load saved connections first
-        self.load_connections()?;
+        self.load_connections().await?;
 
         // Find the most recent connection (by last_connected timestamp)
         let connection_to_restore = {
@@ -541,9 +1073,10 @@ impl ConnectionManager {
         };
 
         if let Some(connection) = connection_to_restore {
-            log_info!(
-                "🔄 [CONNECTION] Attempting to restore connection to: {}",
-                connection.to_url()
+            info!(
+                target: "connection",
+                connection = %connection.to_url(),
+                "Attempting to restore connection"
             );
 
             // Attempt to reconnect
@@ -552,101 +1085,85 @@ impl ConnectionManager {
                 .await
             {
                 Ok(_) => {
-                    log_info!(
-                        "✅ [CONNECTION] Successfully restored connection to: {}",
-                        connection.to_url()
+                    info!(
+                        target: "connection",
+                        connection = %connection.to_url(),
+                        "Successfully restored connection"
                     );
                     self.emit_event(&ConnectionEvent {
                         timestamp: SystemTime::now(),
                         event_type: ConnectionEventType::Connected,
-                        message: format!("Restored connection to {}", connection.to_url()),
+                        message: t("connection.restored", &[("server", &connection.to_url())]),
                     });
                     Ok(())
                 }
                 Err(e) => {
-                    log_warn!(
-                        "⚠️ [CONNECTION] Failed to restore connection to {}: {}",
-                        connection.to_url(),
+                    warn!(
+                        target: "connection",
+                        connection = %connection.to_url(),
+                        "Failed to restore connection: {}",
                         e
                     );
+                    crate::error_stats::record(ErrorCode::Connection, "connection");
                     self.emit_event(&ConnectionEvent {
                         timestamp: SystemTime::now(),
                         event_type: ConnectionEventType::Error,
-                        message: format!("Failed to restore connection: {}", e),
+                        message: t("connection.restore_failed", &[("message", &e.to_string())]),
                     });
                     // Don't return error - just log it. App can still function without auto-connection
                     Ok(())
                 }
             }
         } else {
-            log_info!("ℹ️ [CONNECTION] No previous connections found to restore");
+            info!(target: "connection", "No previous connections found to restore");
             Ok(())
         }
     }
 
-    fn start_health_monitoring(&self) {
-        let connection_status = Arc::clone(&self.connection_status);
-        let server_url = Arc::clone(&self.server_url);
-        let event_sender = self.event_sender.clone();
+    fn start_health_monitoring(&self, connection_id: &str) {
+        let manager = self.clone();
+        let connection_id = connection_id.to_string();
 
         // Spawn health monitoring task with panic recovery
         let health_task = tokio::spawn(async move {
             loop {
                 tokio::time::sleep(Duration::from_secs(30)).await;
 
-                let should_continue = {
-                    match connection_status.lock() {
-                        Ok(status) => matches!(*status, ConnectionStatus::Connected),
-                        Err(poisoned) => {
-                            eprintln!("[ERROR] ConnectionManager health check: mutex poisoned, assuming disconnected");
-                            false // Stop health checks if mutex is poisoned
-                        }
-                    }
+                // Stop once the connection is closed or failed; extract the
+                // URL before the async operation
+                let url_to_check = manager
+                    .active()
+                    .get(&connection_id)
+                    .filter(|connection| connection.status == ConnectionStatus::Connected)
+                    .map(|connection| connection.server_url.clone());
+                let Some(url) = url_to_check else {
+                    break;
                 };
 
-                if !should_continue {
-                    break;
+                if check_health(&connection_id, &url).await {
+                    // Server is healthy
+                    debug!(target: "health", connection = %url, "Health check passed");
+                    let _ = manager.event_sender.send(ConnectionEvent {
+                        timestamp: SystemTime::now(),
+                        event_type: ConnectionEventType::HealthCheck,
+                        message: t("connection.health_ok", &[]),
+                    });
+                    continue;
                 }
 
-                // Perform health check - extract URL before async operation
-                let url_to_check = match server_url.lock() {
-                    Ok(url) => url.clone(),
-                    Err(poisoned) => {
-                        eprintln!(
-                            "[ERROR] ConnectionManager health check: server_url mutex poisoned"
-                        );
-                        None
-                    }
-                };
-
-                if let Some(url) = url_to_check {
-                    let health_url = format!("{}/session", url);
-                    match reqwest::get(&health_url).await {
-                        Ok(response) if response.status().is_success() => {
-                            // Server is healthy
-                            let _ = event_sender.send(ConnectionEvent {
-                                timestamp: SystemTime::now(),
-                                event_type: ConnectionEventType::HealthCheck,
-                                message: "Server health check passed".to_string(),
-                            });
-                        }
-                        _ => {
-                            // Server is unhealthy
-                            match connection_status.lock() {
-                                Ok(mut status) => *status = ConnectionStatus::Error,
-                                Err(poisoned) => {
-                                    eprintln!("[ERROR] ConnectionManager health check: failed to set error status, mutex poisoned");
-                                    // Continue trying health checks even if we can't update status
-                                }
-                            }
-                            let _ = event_sender.send(ConnectionEvent {
-                                timestamp: SystemTime::now(),
-                                event_type: ConnectionEventType::Error,
-                                message: "Server health check failed".to_string(),
-                            });
-                            break;
-                        }
-                    }
+                // Server is unhealthy
+                warn!(target: "health", connection = %url, "Health check failed");
+                crate::error_stats::record(ErrorCode::Connection, "health");
+                if let Some(connection) = manager.active().get_mut(&connection_id) {
+                    connection.status = ConnectionStatus::Error;
+                }
+                let _ = manager.event_sender.send(ConnectionEvent {
+                    timestamp: SystemTime::now(),
+                    event_type: ConnectionEventType::Error,
+                    message: t("connection.health_failed", &[]),
+                });
+                if !manager.reconnect(&connection_id, &url).await {
+                    break;
                 }
             }
         });
@@ -666,6 +1183,238 @@ impl ConnectionManager {
             }
         });
     }
+
+    /// Retry a connection whose health check failed, backing off as its
+    /// profile's reconnect policy says. True once it is healthy again; false
+    /// when the policy is off, the attempts ran out, or the connection was
+    /// closed or reconnected by hand in the meantime.
+    async fn reconnect(&self, connection_id: &str, server_url: &str) -> bool {
+        let policy = profile_for_url(server_url).reconnect;
+        self.reconnect_with(connection_id, server_url, &policy)
+            .await
+    }
+
+    async fn reconnect_with(
+        &self,
+        connection_id: &str,
+        server_url: &str,
+        policy: &ReconnectPolicy,
+    ) -> bool {
+        if !policy.enabled {
+            return false;
+        }
+        let config = RetryConfig::from(policy);
+        let still_failed = || {
+            self.active().get(connection_id).is_some_and(|connection| {
+                connection.status == ConnectionStatus::Error && connection.server_url == server_url
+            })
+        };
+
+        for attempt in 1..=policy.max_attempts {
+            let delay = config.get_jittered_delay(attempt - 1);
+            info!(target: "connection", connection = %server_url, attempt, delay_ms = delay.as_millis() as u64, "Reconnecting");
+            let _ = self.event_sender.send(ConnectionEvent {
+                timestamp: SystemTime::now(),
+                event_type: ConnectionEventType::Reconnecting {
+                    server_url: server_url.to_string(),
+                    attempt,
+                    max_attempts: policy.max_attempts,
+                    delay_ms: delay.as_millis() as u64,
+                },
+                message: t(
+                    "connection.reconnecting",
+                    &[
+                        ("server", server_url),
+                        ("attempt", &attempt.to_string()),
+                        ("max", &policy.max_attempts.to_string()),
+                    ],
+                ),
+            });
+            tokio::time::sleep(delay).await;
+            if !still_failed() {
+                return false;
+            }
+            if !check_health(connection_id, server_url).await {
+                continue;
+            }
+
+            let reconnected = match self.active().get_mut(connection_id) {
+                Some(connection) if connection.status == ConnectionStatus::Error => {
+                    connection.status = ConnectionStatus::Connected;
+                    true
+                }
+                _ => false,
+            };
+            if !reconnected {
+                return false;
+            }
+            self.start_server_events(connection_id, server_url);
+            info!(target: "connection", connection = %server_url, attempts = attempt, "Reconnected");
+            let _ = self.event_sender.send(ConnectionEvent {
+                timestamp: SystemTime::now(),
+                event_type: ConnectionEventType::Reconnected {
+                    server_url: server_url.to_string(),
+                    attempts: attempt,
+                },
+                message: t("connection.reconnected", &[("server", server_url)]),
+            });
+            return true;
+        }
+
+        warn!(target: "connection", connection = %server_url, "Gave up reconnecting");
+        let _ = self.event_sender.send(ConnectionEvent {
+            timestamp: SystemTime::now(),
+            event_type: ConnectionEventType::Error,
+            message: t(
+                "connection.reconnect_failed",
+                &[
+                    ("server", server_url),
+                    ("attempts", &policy.max_attempts.to_string()),
+                ],
+            ),
+        });
+        false
+    }
+
+    /// Subscribe to `/event` on a connected server until it disconnects or
+    /// a newer subscription for it takes over. The event source reconnects
+    /// on its own after dropped connections.
+    fn start_server_events(&self, connection_id: &str, server_url: &str) {
+        let generation = self.event_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(connection) = self.active().get_mut(connection_id) {
+            connection.event_generation = generation;
+        }
+        let active = Arc::clone(&self.active);
+        let connection_id = connection_id.to_string();
+        let server_url = server_url.to_string();
+        let sender = self.server_event_sender.clone();
+
+        tokio::spawn(async move {
+            let client = match certificate_pinning::shared_client() {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!(target: "connection", "Failed to create event client: {}", e);
+                    return;
+                }
+            };
+            let request = client
+                .get(format!("{}/event", server_url))
+                .header("Accept", "text/event-stream");
+            let mut event_source = match EventSource::new(request) {
+                Ok(source) => source,
+                Err(e) => {
+                    warn!(target: "connection", connection = %server_url, "Failed to subscribe to server events: {}", e);
+                    return;
+                }
+            };
+
+            let still_current = || {
+                active
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .get(&connection_id)
+                    .is_some_and(|connection| {
+                        connection.status == ConnectionStatus::Connected
+                            && connection.event_generation == generation
+                    })
+            };
+
+            // Wake up periodically so a quiet feed still notices a disconnect
+            while still_current() {
+                let next = match tokio::time::timeout(Duration::from_secs(30), event_source.next())
+                    .await
+                {
+                    Ok(next) => next,
+                    Err(_) => continue,
+                };
+                match next {
+                    Some(Ok(Event::Open)) => {
+                        debug!(target: "connection", connection = %server_url, "Subscribed to server events");
+                    }
+                    Some(Ok(Event::Message(message))) => {
+                        if let Some(mut event) = ServerEvent::parse(&message.data) {
+                            event.connection_id = Some(connection_id.clone());
+                            let _ = sender.send(event);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        debug!(target: "connection", connection = %server_url, "Server event feed interrupted: {}", e);
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    }
+                    None => break,
+                }
+            }
+            event_source.close();
+            debug!(target: "connection", connection = %server_url, "Stopped following server events");
+        });
+    }
+}
+
+/// Check that a server still answers, recording the outcome in the
+/// connection's health history
+async fn check_health(connection_id: &str, server_url: &str) -> bool {
+    let started = Instant::now();
+    let response = match certificate_pinning::shared_client() {
+        Ok(client) => {
+            client
+                .get(format!("{}/session", server_url))
+                .timeout(REQUEST_TIMEOUT)
+                .send()
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let latency_ms = response
+        .as_ref()
+        .ok()
+        .map(|_| started.elapsed().as_millis() as u64);
+    let healthy = matches!(&response, Ok(response) if response.status().is_success());
+    let status = if healthy {
+        HealthStatus::Healthy
+    } else {
+        HealthStatus::Unhealthy
+    };
+    health_history::record(connection_id, status, latency_ms);
+    healthy
+}
+
+/// Time one request to a saved server. Any HTTP response counts as
+/// reachable; error statuses are reported alongside.
+async fn probe(client: reqwest::Client, connection: ServerConnection) -> Reachability {
+    let url = connection.to_url();
+    let started = Instant::now();
+    let response = client
+        .get(format!("{}/session", url))
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await;
+    let latency_ms = started.elapsed().as_millis().try_into().unwrap_or(u64::MAX);
+
+    let (reachable, latency_ms, status_code, error) = match response {
+        Ok(response) => {
+            let status = response.status();
+            let error =
+                (!status.is_success()).then(|| format!("Server responded with status: {}", status));
+            (true, Some(latency_ms), Some(status.as_u16()), error)
+        }
+        Err(e) if e.is_timeout() => (
+            false,
+            None,
+            None,
+            Some(format!("No response within {}s", PROBE_TIMEOUT.as_secs())),
+        ),
+        Err(e) => (false, None, None, Some(AppError::from(e).user_message())),
+    };
+    debug!(target: "connection", connection = %url, reachable, ?latency_ms, "Probed saved connection");
+
+    Reachability {
+        name: connection.name,
+        url,
+        reachable,
+        latency_ms,
+        status_code,
+        error,
+    }
 }
 
 #[cfg(test)]
@@ -682,6 +1431,39 @@ mod tests {
         (manager, temp_dir)
     }
 
+    /// Record a connection as active and primary, as connecting would
+    fn activate(manager: &ConnectionManager, connection_id: &str, status: ConnectionStatus) {
+        manager.active().insert(
+            connection_id.to_string(),
+            ActiveConnection {
+                server_url: format!("http://{}", connection_id),
+                status,
+                event_generation: 0,
+            },
+        );
+        manager.set_primary_id(Some(connection_id.to_string()));
+    }
+
+    #[test]
+    fn test_connection_id_for_url_matches_connection() {
+        for (secure, port) in [(false, 4096), (true, 443), (true, 8443)] {
+            let connection = ServerConnection {
+                name: "Home".to_string(),
+                hostname: "example.com".to_string(),
+                port,
+                secure,
+                last_connected: None,
+                certificate_fingerprint: None,
+                profile: Default::default(),
+            };
+            assert_eq!(
+                connection_id_for_url(&connection.to_url()),
+                Some(connection.id())
+            );
+        }
+        assert_eq!(connection_id_for_url("not a url"), None);
+    }
+
     #[tokio::test]
     async fn test_connection_manager_initializes() {
         let (manager, _temp) = create_test_connection_manager();
@@ -714,7 +1496,7 @@ mod tests {
 
         // After successful connection (would test with mock server)
         // For now, we verify the data structure is correct
-        *manager.server_url.lock().unwrap() = Some("http://localhost:3000".to_string());
+        activate(&manager, "localhost:3000", ConnectionStatus::Connected);
 
         // Should now have server URL
         let url = manager.get_server_url();
@@ -733,24 +1515,48 @@ mod tests {
         );
 
         // Simulate connection status change
-        *manager.connection_status.lock().unwrap() = ConnectionStatus::Connecting;
+        activate(&manager, "localhost:3000", ConnectionStatus::Connecting);
         assert_eq!(
             manager.get_connection_status(),
             ConnectionStatus::Connecting
         );
 
         // Connected
-        *manager.connection_status.lock().unwrap() = ConnectionStatus::Connected;
+        activate(&manager, "localhost:3000", ConnectionStatus::Connected);
         assert_eq!(manager.get_connection_status(), ConnectionStatus::Connected);
 
         // Disconnected
-        *manager.connection_status.lock().unwrap() = ConnectionStatus::Disconnected;
+        manager.active().clear();
         assert_eq!(
             manager.get_connection_status(),
             ConnectionStatus::Disconnected
         );
     }
 
+    #[tokio::test]
+    async fn test_save_connection_refuses_a_server_saved_twice() {
+        let (mut manager, _temp) = create_test_connection_manager();
+        let connection = |name: &str| ServerConnection {
+            name: name.to_string(),
+            hostname: "home.lan".to_string(),
+            port: 4096,
+            secure: false,
+            last_connected: None,
+            certificate_fingerprint: None,
+            profile: Default::default(),
+        };
+
+        manager.save_connection(connection("home")).await.unwrap();
+        // Saving it again under its own name updates it
+        manager.save_connection(connection("home")).await.unwrap();
+        let error = manager
+            .save_connection(connection("Home server"))
+            .await
+            .unwrap_err();
+        assert!(error.contains("already saved as \"home\""));
+        assert_eq!(manager.get_saved_connections().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_save_and_load_connections() {
         let (manager, _temp) = create_test_connection_manager();
@@ -762,6 +1568,8 @@ mod tests {
             port: 3000,
             secure: false,
             last_connected: Some("2025-11-11T10:00:00Z".to_string()),
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         manager
@@ -773,6 +1581,7 @@ mod tests {
         // Save to disk
         manager
             .save_connections()
+            .await
             .expect("Failed to save connections");
 
         // Verify file was created
@@ -785,6 +1594,7 @@ mod tests {
 
         manager2
             .load_connections()
+            .await
             .expect("Failed to load connections");
 
         // Verify connections were loaded
@@ -804,8 +1614,7 @@ mod tests {
         let (mut manager, _temp) = create_test_connection_manager();
 
         // Set server URL
-        *manager.server_url.lock().unwrap() = Some("http://localhost:3000".to_string());
-        *manager.connection_status.lock().unwrap() = ConnectionStatus::Connected;
+        activate(&manager, "localhost:3000", ConnectionStatus::Connected);
 
         // Verify it's set
         assert!(manager.get_server_url().is_some());
@@ -824,6 +1633,50 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_simultaneous_connections() {
+        let (mut manager, _temp) = create_test_connection_manager();
+        activate(&manager, "home:4096", ConnectionStatus::Connected);
+        activate(&manager, "work:4096", ConnectionStatus::Connected);
+
+        // The latest connection is primary; the other stays reachable by id
+        assert_eq!(
+            manager.get_server_url().as_deref(),
+            Some("http://work:4096")
+        );
+        assert_eq!(
+            manager.server_url_for("home:4096").as_deref(),
+            Some("http://home:4096")
+        );
+        let active = manager.active_connections();
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].connection_id, "home:4096");
+        assert!(!active[0].primary && active[1].primary);
+
+        manager.set_primary("home:4096").unwrap();
+        assert_eq!(
+            manager.get_server_url().as_deref(),
+            Some("http://home:4096")
+        );
+        assert!(manager.set_primary("other:4096").is_err());
+
+        // Disconnecting the primary hands over to the remaining connection
+        manager.disconnect_from_server().await.unwrap();
+        assert_eq!(manager.server_url_for("home:4096"), None);
+        assert_eq!(
+            manager.get_server_url().as_deref(),
+            Some("http://work:4096")
+        );
+        assert_eq!(manager.get_connection_status(), ConnectionStatus::Connected);
+
+        manager.disconnect("work:4096").await.unwrap();
+        assert_eq!(manager.get_server_url(), None);
+        assert_eq!(
+            manager.get_connection_status(),
+            ConnectionStatus::Disconnected
+        );
+    }
+
     #[tokio::test]
     async fn test_event_subscription() {
         let (manager, _temp) = create_test_connection_manager();
@@ -859,6 +1712,8 @@ mod tests {
             port: 3000,
             secure: false,
             last_connected: None,
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         let url = connection.to_url();
@@ -871,6 +1726,8 @@ mod tests {
             port: 3001,
             secure: true,
             last_connected: None,
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         let secure_url = secure_connection.to_url();
@@ -887,6 +1744,8 @@ mod tests {
             port: 3000,
             secure: false,
             last_connected: None,
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         let connection_id = connection.name.clone();
@@ -895,7 +1754,7 @@ mod tests {
             .lock()
             .unwrap()
             .insert(connection_id.clone(), connection.clone());
-        *manager.current_connection.lock().unwrap() = Some(connection_id);
+        manager.set_primary_id(Some(connection_id));
 
         let current = manager.get_current_connection();
         assert!(current.is_some(), "Should have current connection");
@@ -912,6 +1771,8 @@ mod tests {
             port: 4096,
             secure: true,
             last_connected: Some("2025-01-01T00:00:00Z".to_string()),
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         let newer = ServerConnection {
@@ -920,6 +1781,8 @@ mod tests {
             port: 4096,
             secure: true,
             last_connected: Some("2025-02-01T00:00:00Z".to_string()),
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         manager
@@ -944,6 +1807,308 @@ mod tests {
         assert_eq!(url, "https://example.com:4096");
     }
 
+    #[tokio::test]
+    async fn test_set_certificate_pin_normalizes_and_requires_https() {
+        let (mut manager, _temp_dir) = create_test_connection_manager();
+        for (name, secure) in [("secure", true), ("plain", false)] {
+            manager
+                .save_connection(ServerConnection {
+                    name: name.to_string(),
+                    hostname: format!("{}.example", name),
+                    port: 443,
+                    secure,
+                    last_connected: None,
+                    certificate_fingerprint: None,
+                    profile: Default::default(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let pinned = manager
+            .set_certificate_pin("secure", Some(&"ab".repeat(32)))
+            .await
+            .unwrap();
+        assert_eq!(
+            pinned.certificate_fingerprint,
+            Some(vec!["AB"; 32].join(":"))
+        );
+        assert!(manager
+            .set_certificate_pin("plain", Some(&"ab".repeat(32)))
+            .await
+            .is_err());
+        assert!(manager
+            .set_certificate_pin("secure", Some("abc"))
+            .await
+            .is_err());
+        assert!(manager.set_certificate_pin("missing", None).await.is_err());
+
+        let unpinned = manager.set_certificate_pin("secure", None).await.unwrap();
+        assert!(unpinned.certificate_fingerprint.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_connection_profile_publishes_to_clients() {
+        let (mut manager, temp_dir) = create_test_connection_manager();
+        manager
+            .save_connection(ServerConnection {
+                name: "work".to_string(),
+                hostname: "profile.example".to_string(),
+                port: 4096,
+                secure: false,
+                last_connected: None,
+                certificate_fingerprint: None,
+                profile: Default::default(),
+            })
+            .await
+            .unwrap();
+
+        let profile = ConnectionProfile {
+            default_model: Some(ModelConfig {
+                provider_id: "anthropic".to_string(),
+                model_id: "claude".to_string(),
+            }),
+            system_prompt: Some("Be brief".to_string()),
+            request_timeout_secs: Some(90),
+            headers: BTreeMap::from([("X-Team".to_string(), "nexus".to_string())]),
+            auth_method: AuthMethod::Basic {
+                username: "me".to_string(),
+            },
+            reconnect: ReconnectPolicy {
+                max_attempts: 3,
+                ..Default::default()
+            },
+            proxy: Some(ProxySettings {
+                enabled: true,
+                url: "http://proxy.example:3128".to_string(),
+                ..Default::default()
+            }),
+        };
+        manager
+            .update_connection_profile("profile.example:4096", profile.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            manager.connection_profile("profile.example:4096"),
+            Some(profile.clone())
+        );
+        assert_eq!(profile_for_url("http://profile.example:4096"), profile);
+        assert_eq!(
+            profile_for_url("http://other.example:4096"),
+            ConnectionProfile::default()
+        );
+        assert!(manager
+            .update_connection_profile("missing:1", ConnectionProfile::default())
+            .await
+            .is_err());
+
+        // The profile survives a reload from disk
+        let mut reloaded = ConnectionManager::new(temp_dir.path().to_path_buf(), None).unwrap();
+        reloaded.load_connections().await.unwrap();
+        assert_eq!(
+            reloaded.connection_profile("profile.example:4096"),
+            Some(profile)
+        );
+    }
+
+    #[test]
+    fn test_connection_profile_validation() {
+        assert!(ConnectionProfile::default().validate().is_ok());
+
+        let timeout = |secs| ConnectionProfile {
+            request_timeout_secs: Some(secs),
+            ..Default::default()
+        };
+        assert!(timeout(0).validate().is_err());
+        assert!(timeout(MAX_PROFILE_TIMEOUT_SECS + 1).validate().is_err());
+        assert!(timeout(60).validate().is_ok());
+
+        let header = |name: &str, auth_method| ConnectionProfile {
+            headers: BTreeMap::from([(name.to_string(), "value".to_string())]),
+            auth_method,
+            ..Default::default()
+        };
+        assert!(header("bad header", AuthMethod::Bearer).validate().is_err());
+        assert!(header("Authorization", AuthMethod::Bearer)
+            .validate()
+            .is_err());
+        assert!(header("Authorization", AuthMethod::None).validate().is_ok());
+
+        let basic = ConnectionProfile {
+            auth_method: AuthMethod::Basic {
+                username: "a:b".to_string(),
+            },
+            ..Default::default()
+        };
+        assert!(basic.validate().is_err());
+    }
+
+    #[test]
+    fn test_reconnect_policy_validation() {
+        assert!(ReconnectPolicy::default().validate().is_ok());
+        let policy = |change: fn(&mut ReconnectPolicy)| {
+            let mut policy = ReconnectPolicy::default();
+            change(&mut policy);
+            policy
+        };
+        assert!(policy(|p| p.max_attempts = 0).validate().is_err());
+        assert!(
+            policy(|p| p.max_attempts = ReconnectPolicy::MAX_ATTEMPTS + 1)
+                .validate()
+                .is_err()
+        );
+        assert!(policy(|p| p.initial_delay_ms = 0).validate().is_err());
+        assert!(policy(|p| p.initial_delay_ms = p.max_delay_ms + 1)
+            .validate()
+            .is_err());
+        assert!(policy(|p| p.jitter = 1.5).validate().is_err());
+
+        let invalid = ConnectionProfile {
+            reconnect: policy(|p| p.max_attempts = 0),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+
+        let config = RetryConfig::from(&policy(|p| p.jitter = 0.0));
+        assert_eq!(config.get_delay(0), Duration::from_millis(1000));
+        assert_eq!(config.get_delay(3), Duration::from_millis(8000));
+        assert_eq!(config.get_delay(10), Duration::from_millis(60_000));
+    }
+
+    fn fast_reconnect() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay_ms: 1,
+            max_delay_ms: 1,
+            max_attempts: 2,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_stops_when_disabled_or_closed() {
+        let (manager, _temp) = create_test_connection_manager();
+        let mut events = manager.subscribe_to_events();
+
+        // Nothing is retried for a connection that is no longer failed
+        activate(&manager, "gone.example:1", ConnectionStatus::Connected);
+        assert!(
+            !manager
+                .reconnect_with("gone.example:1", "http://gone.example:1", &fast_reconnect())
+                .await
+        );
+        assert!(matches!(
+            events.try_recv().unwrap().event_type,
+            ConnectionEventType::Reconnecting { attempt: 1, .. }
+        ));
+        assert!(events.try_recv().is_err());
+
+        // A disabled policy never tries
+        activate(&manager, "off.example:1", ConnectionStatus::Error);
+        let disabled = ReconnectPolicy {
+            enabled: false,
+            ..fast_reconnect()
+        };
+        assert!(
+            !manager
+                .reconnect_with("off.example:1", "http://off.example:1", &disabled)
+                .await
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_gives_up_after_max_attempts() {
+        let port = {
+            let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            closed.local_addr().unwrap().port()
+        };
+        let connection_id = format!("127.0.0.1:{}", port);
+        let (manager, _temp) = create_test_connection_manager();
+        activate(&manager, &connection_id, ConnectionStatus::Error);
+        let mut events = manager.subscribe_to_events();
+
+        assert!(
+            !manager
+                .reconnect_with(
+                    &connection_id,
+                    &format!("http://{}", connection_id),
+                    &fast_reconnect()
+                )
+                .await
+        );
+        let kinds: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.event_type)
+            .collect();
+        assert_eq!(kinds.len(), 3);
+        assert!(matches!(
+            kinds[1],
+            ConnectionEventType::Reconnecting {
+                attempt: 2,
+                max_attempts: 2,
+                ..
+            }
+        ));
+        assert!(matches!(kinds[2], ConnectionEventType::Error));
+        assert_eq!(
+            manager.connection_status(&connection_id),
+            ConnectionStatus::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_restores_healthy_connection() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]")
+                .await
+                .unwrap();
+        });
+        let connection_id = format!("127.0.0.1:{}", port);
+        let (manager, _temp) = create_test_connection_manager();
+        activate(&manager, &connection_id, ConnectionStatus::Error);
+        let mut events = manager.subscribe_to_events();
+
+        assert!(
+            manager
+                .reconnect_with(
+                    &connection_id,
+                    &format!("http://{}", connection_id),
+                    &fast_reconnect()
+                )
+                .await
+        );
+        assert_eq!(
+            manager.connection_status(&connection_id),
+            ConnectionStatus::Connected
+        );
+        assert!(matches!(
+            events.try_recv().unwrap().event_type,
+            ConnectionEventType::Reconnecting { attempt: 1, .. }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap().event_type,
+            ConnectionEventType::Reconnected { attempts: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_connection_without_profile_deserializes() {
+        let connection: ServerConnection = serde_json::from_str(
+            r#"{"name":"old","hostname":"localhost","port":4096,"secure":false,"last_connected":null}"#,
+        )
+        .unwrap();
+        assert_eq!(connection.profile, ConnectionProfile::default());
+        assert_eq!(connection.profile.auth_method, AuthMethod::Bearer);
+    }
+
     #[tokio::test]
     async fn test_get_last_used_connection_without_timestamps() {
         let (manager, _temp) = create_test_connection_manager();
@@ -954,6 +2119,8 @@ mod tests {
             port: 3000,
             secure: false,
             last_connected: None,
+            certificate_fingerprint: None,
+            profile: Default::default(),
         };
 
         manager
@@ -970,4 +2137,71 @@ mod tests {
         let url = manager.get_last_used_server_url().expect("Should get URL");
         assert_eq!(url, "http://localhost:3000");
     }
+
+    #[test]
+    fn test_server_event_parse() {
+        let event = ServerEvent::parse(
+            r#"{"type":"session.updated","properties":{"info":{"id":"ses_1"}}}"#,
+        )
+        .expect("Should parse a bus event");
+        assert_eq!(event.event_type, "session.updated");
+        assert_eq!(event.properties["info"]["id"], "ses_1");
+
+        let bare = ServerEvent::parse(r#"{"type":"server.connected"}"#).unwrap();
+        assert!(bare.properties.is_null());
+
+        assert!(ServerEvent::parse("").is_none());
+        assert!(ServerEvent::parse("not json").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_all_saved_connections_are_probed() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // One server that answers, and one port nothing listens on
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let up_port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]")
+                .await
+                .unwrap();
+        });
+        let down_port = {
+            let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            closed.local_addr().unwrap().port()
+        };
+
+        let (mut manager, _temp_dir) = create_test_connection_manager();
+        for (name, port) in [("up", up_port), ("down", down_port)] {
+            manager
+                .save_connection(ServerConnection {
+                    name: name.to_string(),
+                    hostname: "127.0.0.1".to_string(),
+                    port,
+                    secure: false,
+                    last_connected: None,
+                    certificate_fingerprint: None,
+                    profile: Default::default(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let results = manager.test_all_saved_connections().await;
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["down", "up"]);
+
+        assert!(!results[0].reachable);
+        assert!(results[0].error.is_some());
+        assert!(results[0].latency_ms.is_none());
+
+        assert!(results[1].reachable);
+        assert_eq!(results[1].status_code, Some(200));
+        assert!(results[1].latency_ms.is_some());
+        assert!(results[1].error.is_none());
+    }
 }
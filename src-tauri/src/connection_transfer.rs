@@ -0,0 +1,446 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Moving saved connections between machines. Exports hold each
+//! connection with its profile; API keys and proxy passwords stay in the
+//! secret store and have to be entered again after an import.
+
+use crate::connection_manager::ServerConnection;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Identifies a connections export file
+pub const CONNECTIONS_FORMAT: &str = "opencode-nexus-connections";
+
+/// Version written by `export_connections`. Version 0 is a bare
+/// `server_connections.json` array, which imports as well.
+pub const CONNECTIONS_VERSION: u32 = 1;
+
+/// Largest export file read
+const MAX_EXPORT_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Saved connections as written by `export_connections`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionsExport {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: DateTime<Utc>,
+    pub connections: Vec<ServerConnection>,
+}
+
+/// What `export_connections` wrote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionsExportSummary {
+    pub path: String,
+    pub connection_count: usize,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// What to do with an imported connection whose name or server is already
+/// saved. A server saved under another name can be skipped or overwritten,
+/// but never saved twice, so `Rename` skips it.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the saved connection
+    #[default]
+    Skip,
+    /// Replace the saved connection
+    Overwrite,
+    /// Save the imported one under a free name
+    Rename,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RenamedConnection {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RejectedConnection {
+    pub name: String,
+    pub error: String,
+}
+
+/// Outcome of an import, by connection name
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConnectionImportSummary {
+    pub imported: Vec<String>,
+    pub overwritten: Vec<String>,
+    pub renamed: Vec<RenamedConnection>,
+    /// Name or server taken and left alone
+    pub skipped: Vec<String>,
+    /// Already saved with the same settings
+    pub unchanged: Vec<String>,
+    pub rejected: Vec<RejectedConnection>,
+}
+
+impl ConnectionsExport {
+    /// Export of `connections`, sorted by name. When each was last used is
+    /// local history and left out.
+    pub fn new(connections: &[ServerConnection]) -> Self {
+        let mut connections: Vec<ServerConnection> = connections
+            .iter()
+            .cloned()
+            .map(|connection| ServerConnection {
+                last_connected: None,
+                ..connection
+            })
+            .collect();
+        connections.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            format: CONNECTIONS_FORMAT.to_string(),
+            version: CONNECTIONS_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now(),
+            connections,
+        }
+    }
+
+    pub fn write(&self, output: &Path) -> Result<ConnectionsExportSummary, AppError> {
+        let export_json = serde_json::to_string_pretty(self).map_err(|e| AppError::DataError {
+            message: "Failed to serialize connections".to_string(),
+            details: e.to_string(),
+        })?;
+        std::fs::write(output, export_json).map_err(|e| AppError::FileSystemError {
+            path: output.display().to_string(),
+            message: "Failed to write connections export".to_string(),
+            details: e.to_string(),
+        })?;
+        Ok(ConnectionsExportSummary {
+            path: output.display().to_string(),
+            connection_count: self.connections.len(),
+            exported_at: self.exported_at,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, AppError> {
+        let size = std::fs::metadata(path)
+            .map_err(|e| AppError::FileSystemError {
+                path: path.display().to_string(),
+                message: "Failed to open connections export".to_string(),
+                details: e.to_string(),
+            })?
+            .len();
+        if size > MAX_EXPORT_BYTES {
+            return Err(invalid("The file is too large"));
+        }
+        let bytes = std::fs::read(path)?;
+        Self::parse(&bytes)
+    }
+
+    /// Parse an export of any known version, upgrading it to the current one
+    pub fn parse(bytes: &[u8]) -> Result<Self, AppError> {
+        let value: serde_json::Value =
+            serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+        if value.is_array() {
+            let connections = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+            return Ok(Self {
+                format: CONNECTIONS_FORMAT.to_string(),
+                version: CONNECTIONS_VERSION,
+                app_version: String::new(),
+                exported_at: Utc::now(),
+                connections,
+            });
+        }
+
+        if value.get("format").and_then(|format| format.as_str()) != Some(CONNECTIONS_FORMAT) {
+            return Err(invalid("Not a connections export"));
+        }
+        let version = value
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .ok_or_else(|| invalid("The export has no version"))?;
+        if version > u64::from(CONNECTIONS_VERSION) {
+            return Err(invalid(format!(
+                "The export is version {}, newer than this app reads ({})",
+                version, CONNECTIONS_VERSION
+            )));
+        }
+        serde_json::from_value(value).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+fn invalid(details: impl Into<String>) -> AppError {
+    AppError::DataError {
+        message: "Invalid connections export".to_string(),
+        details: details.into(),
+    }
+}
+
+/// Whether two connections differ only in when they were last used
+fn same_settings(a: &ServerConnection, b: &ServerConnection) -> bool {
+    let strip = |connection: &ServerConnection| {
+        serde_json::to_value(ServerConnection {
+            last_connected: None,
+            ..connection.clone()
+        })
+        .ok()
+    };
+    strip(a) == strip(b)
+}
+
+/// `name (2)`, `name (3)`, … whichever is free first
+fn free_name(name: &str, taken: &HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Decide what happens to each imported connection. Returns the
+/// connections to save and the summary to report; nothing is written.
+pub fn plan_import(
+    existing: &[ServerConnection],
+    incoming: Vec<ServerConnection>,
+    on_conflict: ConflictResolution,
+) -> (Vec<ServerConnection>, ConnectionImportSummary) {
+    let mut taken: HashSet<String> = existing
+        .iter()
+        .map(|connection| connection.name.clone())
+        .collect();
+    // Servers the import saves; one may not be saved twice
+    let mut planned: HashSet<String> = HashSet::new();
+    let mut to_save = Vec::new();
+    let mut summary = ConnectionImportSummary::default();
+
+    for mut connection in incoming {
+        if let Err(error) = connection.validate() {
+            summary.rejected.push(RejectedConnection {
+                name: connection.name,
+                error,
+            });
+            continue;
+        }
+        let id = connection.id();
+        if planned.contains(&id) {
+            // Listed twice in the file; the first one wins
+            summary.skipped.push(connection.name);
+            continue;
+        }
+        let same_name = existing.iter().find(|saved| saved.name == connection.name);
+        let same_server = existing.iter().find(|saved| saved.id() == id);
+
+        if let Some(server) = same_server.filter(|server| server.name != connection.name) {
+            // Saved under another name; it can only be updated in place
+            let name = std::mem::replace(&mut connection.name, server.name.clone());
+            if same_settings(server, &connection) {
+                summary.unchanged.push(name);
+                continue;
+            }
+            match (on_conflict, same_name) {
+                (ConflictResolution::Overwrite, None) => {
+                    connection.last_connected = server.last_connected.clone();
+                    summary.overwritten.push(connection.name.clone());
+                    planned.insert(id);
+                    to_save.push(connection);
+                }
+                (ConflictResolution::Overwrite, Some(_)) => {
+                    summary.rejected.push(RejectedConnection {
+                        name,
+                        error: format!("{} is already saved as \"{}\"", id, server.name),
+                    });
+                }
+                _ => summary.skipped.push(name),
+            }
+            continue;
+        }
+
+        let Some(saved) = same_name else {
+            if !taken.insert(connection.name.clone()) {
+                // Listed twice in the file; the first one wins
+                summary.skipped.push(connection.name);
+                continue;
+            }
+            summary.imported.push(connection.name.clone());
+            planned.insert(id);
+            to_save.push(connection);
+            continue;
+        };
+
+        if same_settings(saved, &connection) {
+            summary.unchanged.push(connection.name);
+            continue;
+        }
+        match on_conflict {
+            ConflictResolution::Skip => summary.skipped.push(connection.name),
+            ConflictResolution::Overwrite => {
+                connection.last_connected = saved.last_connected.clone();
+                summary.overwritten.push(connection.name.clone());
+                planned.insert(id);
+                to_save.push(connection);
+            }
+            // Same server with other settings; a copy would share its id
+            ConflictResolution::Rename if same_server.is_some() => {
+                summary.skipped.push(connection.name)
+            }
+            ConflictResolution::Rename => {
+                let name = free_name(&connection.name, &taken);
+                taken.insert(name.clone());
+                summary.renamed.push(RenamedConnection {
+                    from: std::mem::replace(&mut connection.name, name),
+                    to: connection.name.clone(),
+                });
+                planned.insert(id);
+                to_save.push(connection);
+            }
+        }
+    }
+
+    (to_save, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn connection(name: &str, hostname: &str) -> ServerConnection {
+        ServerConnection {
+            name: name.to_string(),
+            hostname: hostname.to_string(),
+            port: 4096,
+            secure: false,
+            last_connected: Some("2025-01-01T00:00:00Z".to_string()),
+            certificate_fingerprint: None,
+            profile: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_export_round_trips_without_history() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("connections.json");
+        let summary = ConnectionsExport::new(&[connection("b", "b.lan"), connection("a", "a.lan")])
+            .write(&path)
+            .unwrap();
+        assert_eq!(summary.connection_count, 2);
+
+        let export = ConnectionsExport::read(&path).unwrap();
+        assert_eq!(export.version, CONNECTIONS_VERSION);
+        let names: Vec<_> = export.connections.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(export.connections[0].last_connected.is_none());
+    }
+
+    #[test]
+    fn test_parse_versions() {
+        // A bare server_connections.json is version 0
+        let legacy = serde_json::to_vec(&[connection("home", "home.lan")]).unwrap();
+        let export = ConnectionsExport::parse(&legacy).unwrap();
+        assert_eq!(export.connections[0].hostname, "home.lan");
+
+        let newer = serde_json::json!({
+            "format": CONNECTIONS_FORMAT,
+            "version": CONNECTIONS_VERSION + 1,
+            "connections": [],
+        });
+        assert!(ConnectionsExport::parse(newer.to_string().as_bytes()).is_err());
+
+        let other = serde_json::json!({ "format": "something-else", "version": 1 });
+        assert!(ConnectionsExport::parse(other.to_string().as_bytes()).is_err());
+        assert!(ConnectionsExport::parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_plan_import_resolves_conflicts() {
+        let existing = vec![
+            connection("home", "home.lan"),
+            connection("work", "work.corp"),
+            connection("work (2)", "old.corp"),
+        ];
+        let mut same_home = connection("home", "home.lan");
+        same_home.last_connected = None;
+        let incoming = || {
+            vec![
+                same_home.clone(),
+                connection("work", "new.corp"),
+                connection("lab", "lab.lan"),
+                connection("", "nameless.lan"),
+            ]
+        };
+
+        let (to_save, summary) = plan_import(&existing, incoming(), ConflictResolution::Skip);
+        assert_eq!(summary.imported, vec!["lab"]);
+        assert_eq!(summary.skipped, vec!["work"]);
+        assert_eq!(summary.unchanged, vec!["home"]);
+        assert_eq!(summary.rejected.len(), 1);
+        assert_eq!(to_save.len(), 1);
+
+        let (to_save, summary) = plan_import(&existing, incoming(), ConflictResolution::Overwrite);
+        assert_eq!(summary.overwritten, vec!["work"]);
+        let work = to_save.iter().find(|c| c.name == "work").unwrap();
+        assert_eq!(work.hostname, "new.corp");
+        assert_eq!(work.last_connected, existing[1].last_connected);
+
+        let (to_save, summary) = plan_import(&existing, incoming(), ConflictResolution::Rename);
+        assert_eq!(
+            summary.renamed,
+            vec![RenamedConnection {
+                from: "work".to_string(),
+                to: "work (3)".to_string(),
+            }]
+        );
+        assert!(to_save
+            .iter()
+            .any(|c| c.name == "work (3)" && c.hostname == "new.corp"));
+    }
+
+    #[test]
+    fn test_plan_import_matches_servers_saved_under_other_names() {
+        let existing = vec![connection("home", "home.lan"), connection("lab", "lab.lan")];
+        let mut moved = connection("house", "home.lan");
+        moved.profile.system_prompt = Some("Be brief".to_string());
+        let mut lab = connection("lab", "home.lan");
+        lab.profile.system_prompt = Some("Lab work".to_string());
+        let incoming = || {
+            vec![
+                connection("Home server", "home.lan"),
+                lab.clone(),
+                moved.clone(),
+                connection("new", "new.lan"),
+                connection("new again", "new.lan"),
+            ]
+        };
+
+        let (to_save, summary) = plan_import(&existing, incoming(), ConflictResolution::Rename);
+        assert_eq!(summary.unchanged, vec!["Home server"]);
+        assert_eq!(summary.skipped, vec!["lab", "house", "new again"]);
+        assert!(summary.renamed.is_empty());
+        assert_eq!(summary.imported, vec!["new"]);
+        assert_eq!(to_save.len(), 1);
+
+        let (to_save, summary) = plan_import(&existing, incoming(), ConflictResolution::Overwrite);
+        assert_eq!(summary.overwritten, vec!["home"]);
+        assert_eq!(summary.rejected.len(), 1);
+        assert_eq!(summary.rejected[0].name, "lab");
+        let home = to_save.iter().find(|c| c.name == "home").unwrap();
+        assert_eq!(home.profile.system_prompt.as_deref(), Some("Be brief"));
+        assert!(to_save.iter().all(|c| c.name != "house"));
+    }
+}
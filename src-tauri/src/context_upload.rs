@@ -0,0 +1,109 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Sending a local file to a session as context. The server takes files as
+//! message parts, so the file is posted as a `file` part with `noReply`
+//! set: it joins the conversation without asking the agent to answer.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Tauri event emitted as an upload's request body is sent
+pub const UPLOAD_PROGRESS_EVENT: &str = "context-upload-progress";
+
+/// Size of each chunk of the request body, and so of each progress step
+pub const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Payload of `context-upload-progress`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UploadProgress {
+    pub upload_id: String,
+    pub session_id: String,
+    pub file_name: String,
+    pub sent_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// A file now attached to a session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextFile {
+    pub upload_id: String,
+    pub session_id: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: u64,
+    /// Message holding the file part, when the server reported it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+}
+
+/// Request body that adds `contents` to a session without a reply
+pub fn prompt_body(file_name: &str, mime_type: &str, contents: &[u8]) -> Vec<u8> {
+    let data_url = format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::engine::general_purpose::STANDARD.encode(contents)
+    );
+    serde_json::json!({
+        "noReply": true,
+        "parts": [{
+            "type": "file",
+            "mime": mime_type,
+            "filename": file_name,
+            "url": data_url,
+        }],
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Id of the message the server created for the upload
+pub fn message_id(response: &serde_json::Value) -> Option<String> {
+    response
+        .pointer("/info/id")
+        .or_else(|| response.get("id"))
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_body_is_a_silent_file_part() {
+        let body: serde_json::Value =
+            serde_json::from_slice(&prompt_body("notes.md", "text/markdown", b"# Notes")).unwrap();
+        assert_eq!(body["noReply"], true);
+        let part = &body["parts"][0];
+        assert_eq!(part["type"], "file");
+        assert_eq!(part["filename"], "notes.md");
+        assert_eq!(part["url"], "data:text/markdown;base64,IyBOb3Rlcw==");
+    }
+
+    #[test]
+    fn test_message_id_from_response() {
+        let response = serde_json::json!({ "info": { "id": "msg_1" }, "parts": [] });
+        assert_eq!(message_id(&response).as_deref(), Some("msg_1"));
+        assert_eq!(message_id(&serde_json::json!({})), None);
+    }
+}
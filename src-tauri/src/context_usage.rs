@@ -0,0 +1,300 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! How much of the model's context window a session takes up.
+
+use serde::{Deserialize, Serialize};
+
+/// Tauri event with a session's `ContextUsage` after each response
+pub const CONTEXT_USAGE_EVENT: &str = "context-usage";
+
+/// Share of the window at which a session counts as close to truncation
+const NEAR_LIMIT_RATIO: f64 = 0.8;
+
+/// Rough characters per token for text the server has not counted yet
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Where `used_tokens` comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSource {
+    /// The server's token count for the last reply, plus estimates for
+    /// anything sent since
+    Reported,
+    /// No reply has been counted yet; every figure is estimated
+    Estimated,
+}
+
+/// Estimated tokens by kind of content
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsageBreakdown {
+    pub system_prompt: u64,
+    pub messages: u64,
+    /// Files and images attached to prompts
+    pub attachments: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextUsage {
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    pub used_tokens: u64,
+    /// `None` when the model's window is unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent_used: Option<f64>,
+    pub near_limit: bool,
+    pub source: UsageSource,
+    pub breakdown: UsageBreakdown,
+}
+
+fn estimate_tokens(chars: usize) -> u64 {
+    chars.div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Tokens an attachment adds: data URLs by decoded size, otherwise by
+/// any inline text
+fn attachment_tokens(part: &serde_json::Value) -> u64 {
+    let url = part
+        .get("url")
+        .and_then(|url| url.as_str())
+        .unwrap_or_default();
+    match url.split_once(";base64,") {
+        Some((_, data)) => estimate_tokens(data.len() / 4 * 3),
+        None => estimate_tokens(
+            part.pointer("/source/text/value")
+                .and_then(|text| text.as_str())
+                .map_or(0, str::len),
+        ),
+    }
+}
+
+/// Estimated breakdown of one `{ info, parts }` message
+fn message_breakdown(message: &serde_json::Value) -> UsageBreakdown {
+    let mut breakdown = UsageBreakdown::default();
+    if let Some(system) = message.pointer("/info/system").and_then(|s| s.as_str()) {
+        breakdown.system_prompt += estimate_tokens(system.len());
+    }
+    for part in message
+        .get("parts")
+        .and_then(|parts| parts.as_array())
+        .into_iter()
+        .flatten()
+    {
+        match part.get("type").and_then(|kind| kind.as_str()) {
+            Some("text") | Some("reasoning") => {
+                breakdown.messages += estimate_tokens(
+                    part.get("text")
+                        .and_then(|text| text.as_str())
+                        .map_or(0, str::len),
+                )
+            }
+            Some("tool") => {
+                let state = part.get("state");
+                let input = state
+                    .and_then(|state| state.get("input"))
+                    .map_or(0, |input| input.to_string().len());
+                let output = state
+                    .and_then(|state| state.get("output"))
+                    .and_then(|output| output.as_str())
+                    .map_or(0, str::len);
+                breakdown.messages += estimate_tokens(input + output);
+            }
+            Some("file") => breakdown.attachments += attachment_tokens(part),
+            _ => {}
+        }
+    }
+    breakdown
+}
+
+/// Tokens the server counted for an assistant reply: everything it read
+/// plus what it wrote, which the next prompt carries along
+fn reported_tokens(message: &serde_json::Value) -> Option<u64> {
+    let tokens = message.pointer("/info/tokens")?;
+    let count = |pointer: &str| {
+        tokens
+            .pointer(pointer)
+            .and_then(|count| count.as_u64())
+            .unwrap_or(0)
+    };
+    let total = count("/input")
+        + count("/output")
+        + count("/reasoning")
+        + count("/cache/read")
+        + count("/cache/write");
+    (total > 0).then_some(total)
+}
+
+/// Context window of a model in a `GET /config/providers` response. The
+/// server lists models as a map keyed by id; older servers send a list.
+pub fn model_context_limit(
+    providers: &serde_json::Value,
+    provider_id: &str,
+    model_id: &str,
+) -> Option<u64> {
+    let provider = providers
+        .get("providers")?
+        .as_array()?
+        .iter()
+        .find(|provider| provider.get("id").and_then(|id| id.as_str()) == Some(provider_id))?;
+    let models = provider.get("models")?;
+    let model = match models {
+        serde_json::Value::Object(models) => models.get(model_id),
+        serde_json::Value::Array(models) => models
+            .iter()
+            .find(|model| model.get("id").and_then(|id| id.as_str()) == Some(model_id)),
+        _ => None,
+    }?;
+    model
+        .pointer("/limit/context")
+        .and_then(|limit| limit.as_u64())
+        .filter(|limit| *limit > 0)
+}
+
+/// Work out a session's usage from its `{ info, parts }` messages
+pub fn compute(
+    session_id: &str,
+    messages: &[serde_json::Value],
+    model: Option<(String, String)>,
+    context_limit: Option<u64>,
+) -> ContextUsage {
+    let mut breakdown = UsageBreakdown::default();
+    for message in messages {
+        let part = message_breakdown(message);
+        breakdown.system_prompt += part.system_prompt;
+        breakdown.messages += part.messages;
+        breakdown.attachments += part.attachments;
+    }
+
+    // The last counted reply covers everything before it
+    let last_counted = messages
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, message)| reported_tokens(message).map(|tokens| (index, tokens)));
+    let (used_tokens, source) = match last_counted {
+        Some((index, tokens)) => {
+            let since: u64 = messages[index + 1..]
+                .iter()
+                .map(|message| {
+                    let part = message_breakdown(message);
+                    part.system_prompt + part.messages + part.attachments
+                })
+                .sum();
+            (tokens + since, UsageSource::Reported)
+        }
+        None => (
+            breakdown.system_prompt + breakdown.messages + breakdown.attachments,
+            UsageSource::Estimated,
+        ),
+    };
+
+    let percent_used = context_limit.map(|limit| used_tokens as f64 / limit as f64 * 100.0);
+    let (provider_id, model_id) = model.unzip();
+    ContextUsage {
+        session_id: session_id.to_string(),
+        provider_id,
+        model_id,
+        used_tokens,
+        context_limit,
+        percent_used,
+        near_limit: percent_used.is_some_and(|percent| percent >= NEAR_LIMIT_RATIO * 100.0),
+        source,
+        breakdown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<serde_json::Value> {
+        vec![
+            serde_json::json!({
+                "info": { "id": "m1", "role": "user", "system": "x".repeat(400) },
+                "parts": [
+                    { "type": "text", "text": "y".repeat(40) },
+                    { "type": "file", "url": format!("data:text/plain;base64,{}", "A".repeat(160)) },
+                ],
+            }),
+            serde_json::json!({
+                "info": {
+                    "id": "m2",
+                    "role": "assistant",
+                    "providerID": "anthropic",
+                    "modelID": "claude",
+                    "tokens": { "input": 5000, "output": 300, "reasoning": 0, "cache": { "read": 1000, "write": 0 } },
+                },
+                "parts": [{ "type": "text", "text": "z".repeat(80) }],
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_estimated_without_counts() {
+        let usage = compute("s1", &messages()[..1], None, None);
+        assert_eq!(usage.source, UsageSource::Estimated);
+        assert_eq!(usage.breakdown.system_prompt, 100);
+        assert_eq!(usage.breakdown.messages, 10);
+        assert_eq!(usage.breakdown.attachments, 30);
+        assert_eq!(usage.used_tokens, 140);
+        assert_eq!(usage.percent_used, None);
+        assert!(!usage.near_limit);
+    }
+
+    #[test]
+    fn test_reported_plus_pending() {
+        let mut messages = messages();
+        messages.push(serde_json::json!({
+            "info": { "id": "m3", "role": "user" },
+            "parts": [{ "type": "text", "text": "w".repeat(400) }],
+        }));
+        let model = Some(("anthropic".to_string(), "claude".to_string()));
+        let usage = compute("s1", &messages, model, Some(8000));
+        assert_eq!(usage.source, UsageSource::Reported);
+        assert_eq!(usage.used_tokens, 6300 + 100);
+        assert!(usage.near_limit);
+        assert_eq!(usage.model_id.as_deref(), Some("claude"));
+    }
+
+    #[test]
+    fn test_model_context_limit() {
+        let providers = serde_json::json!({
+            "providers": [
+                { "id": "anthropic", "models": { "claude": { "limit": { "context": 200000, "output": 8192 } } } },
+                { "id": "legacy", "models": [{ "id": "old", "limit": { "context": 4096 } }] },
+            ],
+        });
+        assert_eq!(
+            model_context_limit(&providers, "anthropic", "claude"),
+            Some(200000)
+        );
+        assert_eq!(model_context_limit(&providers, "legacy", "old"), Some(4096));
+        assert_eq!(
+            model_context_limit(&providers, "anthropic", "missing"),
+            None
+        );
+    }
+}
@@ -0,0 +1,559 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Importer for conversation exports from ChatGPT (`conversations.json`,
+//! alone or in OpenAI's export zip) and Claude (Anthropic's data export),
+//! so history follows users who move to their own OpenCode server.
+
+use crate::error::AppError;
+use crate::session_manager::{
+    text_content, ChatMessage, ChatSession, MessagePart, MessageRole, SessionManager,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Name of the conversations file in both vendors' archives
+const CONVERSATIONS_ENTRY: &str = "conversations.json";
+
+/// Largest conversations file read from an archive
+const MAX_EXPORT_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Where an export came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSource {
+    ChatGpt,
+    Claude,
+}
+
+impl ExportSource {
+    fn prefix(self) -> &'static str {
+        match self {
+            ExportSource::ChatGpt => "chatgpt",
+            ExportSource::Claude => "claude",
+        }
+    }
+}
+
+/// What an import added
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConversationImportSummary {
+    pub imported: usize,
+    pub messages: usize,
+    /// Conversations imported before, left as they are
+    pub already_imported: usize,
+    /// Conversations with no messages, or in neither format
+    pub skipped: usize,
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::ParseError {
+        message: "Invalid conversation export".to_string(),
+        details: Some(message.into()),
+    }
+}
+
+/// Read an export, either the conversations file itself or a zip holding it
+pub fn read_export(path: &Path) -> Result<(Vec<ChatSession>, usize), AppError> {
+    let mut file = File::open(path).map_err(|e| AppError::FileSystemError {
+        path: path.display().to_string(),
+        message: "Failed to open conversation export".to_string(),
+        details: e.to_string(),
+    })?;
+    let mut magic = [0u8; 4];
+    let is_zip = file.read_exact(&mut magic).is_ok() && magic == *b"PK\x03\x04";
+    drop(file);
+
+    let bytes = if is_zip {
+        let file = File::open(path)?;
+        let mut archive = ZipArchive::new(file).map_err(|e| invalid(e.to_string()))?;
+        let name = archive
+            .file_names()
+            .filter(|name| name.rsplit('/').next() == Some(CONVERSATIONS_ENTRY))
+            .min_by_key(|name| name.len())
+            .map(str::to_string)
+            .ok_or_else(|| invalid(format!("The archive has no {}", CONVERSATIONS_ENTRY)))?;
+        let entry = archive.by_name(&name).map_err(|e| invalid(e.to_string()))?;
+        let mut bytes = Vec::new();
+        entry.take(MAX_EXPORT_BYTES + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > MAX_EXPORT_BYTES {
+            return Err(invalid(format!("{} is too large", CONVERSATIONS_ENTRY)));
+        }
+        bytes
+    } else {
+        std::fs::read(path)?
+    };
+    parse_export(&bytes)
+}
+
+/// Parse a conversations file from either vendor. Returns the sessions and
+/// how many conversations were skipped as empty or unrecognised.
+pub fn parse_export(bytes: &[u8]) -> Result<(Vec<ChatSession>, usize), AppError> {
+    let conversations: Vec<Value> =
+        serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+
+    let mut sessions = Vec::new();
+    let mut skipped = 0;
+    for conversation in &conversations {
+        let session = if conversation.get("mapping").is_some() {
+            parse_chatgpt(conversation)
+        } else if conversation.get("chat_messages").is_some() {
+            parse_claude(conversation)
+        } else {
+            None
+        };
+        match session {
+            Some(session) if !session.messages.is_empty() => sessions.push(session),
+            _ => skipped += 1,
+        }
+    }
+    Ok((sessions, skipped))
+}
+
+/// Add parsed sessions to the local store, leaving any imported before alone
+pub async fn import_sessions(
+    manager: &SessionManager,
+    sessions: Vec<ChatSession>,
+) -> Result<ConversationImportSummary, Box<dyn std::error::Error>> {
+    let mut summary = ConversationImportSummary::default();
+    for session in sessions {
+        let messages = session.messages.len();
+        if manager.import_session(session).await? {
+            summary.imported += 1;
+            summary.messages += messages;
+        } else {
+            summary.already_imported += 1;
+        }
+    }
+    Ok(summary)
+}
+
+fn epoch_seconds(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    let seconds = value?.as_f64()?;
+    DateTime::from_timestamp_millis((seconds * 1000.0) as i64)
+}
+
+fn rfc3339(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value?.as_str()?)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+}
+
+fn new_session(
+    source: ExportSource,
+    id: &str,
+    title: Option<&str>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    messages: Vec<ChatMessage>,
+) -> ChatSession {
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "imported_from".to_string(),
+        serde_json::to_value(source).unwrap_or_default(),
+    );
+    metadata.insert("original_id".to_string(), Value::String(id.to_string()));
+    let updated_at = messages
+        .last()
+        .map_or(updated_at, |message| message.timestamp.max(updated_at));
+    ChatSession {
+        id: format!("{}-{}", source.prefix(), id),
+        title: title.map(str::to_string),
+        created_at,
+        updated_at,
+        messages,
+        model_config: None,
+        metadata: Some(metadata),
+        connection_id: None,
+    }
+}
+
+fn new_message(
+    id: String,
+    role: MessageRole,
+    timestamp: DateTime<Utc>,
+    model: Option<String>,
+    parts: Vec<MessagePart>,
+) -> ChatMessage {
+    ChatMessage {
+        id,
+        role,
+        content: text_content(&parts),
+        timestamp,
+        model,
+        metadata: None,
+        parts,
+    }
+}
+
+/// ChatGPT keeps a conversation as a tree of edits and regenerations; the
+/// branch the user last saw runs from `current_node` back to the root.
+fn parse_chatgpt(conversation: &Value) -> Option<ChatSession> {
+    let id =
+        str_field(conversation, "conversation_id").or_else(|| str_field(conversation, "id"))?;
+    let mapping = conversation.get("mapping")?.as_object()?;
+    let created_at = epoch_seconds(conversation.get("create_time")).unwrap_or_else(Utc::now);
+    let updated_at = epoch_seconds(conversation.get("update_time")).unwrap_or(created_at);
+
+    let mut branch = Vec::new();
+    let mut node_id = str_field(conversation, "current_node");
+    while let Some(node) = node_id.and_then(|id| mapping.get(id)) {
+        if branch.len() > mapping.len() {
+            break; // a cycle; the export is damaged
+        }
+        branch.push(node);
+        node_id = str_field(node, "parent");
+    }
+    branch.reverse();
+
+    let mut messages = Vec::new();
+    let mut timestamp = created_at;
+    for node in branch {
+        let Some(message) = node.get("message") else {
+            continue;
+        };
+        let metadata = message.get("metadata");
+        let hidden = metadata
+            .and_then(|m| m.get("is_visually_hidden_from_conversation"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let role = match message.pointer("/author/role").and_then(Value::as_str) {
+            Some("user") => MessageRole::User,
+            Some("assistant") => MessageRole::Assistant,
+            Some("system") => MessageRole::System,
+            Some("tool") => MessageRole::Tool,
+            _ => continue,
+        };
+        let parts = chatgpt_parts(message.get("content"));
+        if hidden || parts.is_empty() {
+            continue;
+        }
+        // Older exports leave some times out; keep the order regardless
+        timestamp = epoch_seconds(message.get("create_time")).unwrap_or(timestamp);
+        let model = metadata
+            .and_then(|m| str_field(m, "model_slug"))
+            .map(str::to_string);
+        let message_id = str_field(message, "id")
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        messages.push(new_message(message_id, role, timestamp, model, parts));
+    }
+
+    Some(new_session(
+        ExportSource::ChatGpt,
+        id,
+        str_field(conversation, "title"),
+        created_at,
+        updated_at,
+        messages,
+    ))
+}
+
+fn chatgpt_parts(content: Option<&Value>) -> Vec<MessagePart> {
+    let Some(content) = content else {
+        return Vec::new();
+    };
+    let mut parts = Vec::new();
+    match content.get("content_type").and_then(Value::as_str) {
+        Some("thoughts") => {
+            for thought in content
+                .get("thoughts")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(text) = str_field(thought, "content") {
+                    parts.push(MessagePart::Reasoning {
+                        text: text.to_string(),
+                    });
+                }
+            }
+        }
+        Some("code") | Some("execution_output") | Some("tether_quote") => {
+            if let Some(text) = str_field(content, "text") {
+                parts.push(MessagePart::text(text));
+            }
+        }
+        // Text and multimodal text; images and other assets are objects
+        // pointing into files the export does not map back, so only the
+        // text parts come across
+        _ => {
+            let text = content
+                .get("parts")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .filter(|text| !text.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if !text.is_empty() {
+                parts.push(MessagePart::text(text));
+            }
+        }
+    }
+    parts
+}
+
+fn parse_claude(conversation: &Value) -> Option<ChatSession> {
+    let id = str_field(conversation, "uuid")?;
+    let created_at = rfc3339(conversation.get("created_at")).unwrap_or_else(Utc::now);
+    let updated_at = rfc3339(conversation.get("updated_at")).unwrap_or(created_at);
+
+    let mut messages = Vec::new();
+    let mut timestamp = created_at;
+    for message in conversation.get("chat_messages")?.as_array()? {
+        let role = match message.get("sender").and_then(Value::as_str) {
+            Some("human") => MessageRole::User,
+            Some("assistant") => MessageRole::Assistant,
+            _ => continue,
+        };
+        let parts = claude_parts(message);
+        if parts.is_empty() {
+            continue;
+        }
+        timestamp = rfc3339(message.get("created_at")).unwrap_or(timestamp);
+        let message_id = str_field(message, "uuid")
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        messages.push(new_message(message_id, role, timestamp, None, parts));
+    }
+
+    Some(new_session(
+        ExportSource::Claude,
+        id,
+        str_field(conversation, "name"),
+        created_at,
+        updated_at,
+        messages,
+    ))
+}
+
+/// Newer exports split a message into typed `content` blocks; older ones
+/// only have `text`
+fn claude_parts(message: &Value) -> Vec<MessagePart> {
+    let mut parts = Vec::new();
+    for block in message
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                if let Some(text) = str_field(block, "text") {
+                    parts.push(MessagePart::text(text));
+                }
+            }
+            Some("thinking") => {
+                if let Some(text) = str_field(block, "thinking") {
+                    parts.push(MessagePart::Reasoning {
+                        text: text.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    if !parts
+        .iter()
+        .any(|part| matches!(part, MessagePart::Text { .. }))
+    {
+        if let Some(text) = str_field(message, "text") {
+            parts.push(MessagePart::text(text));
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::ApiClient;
+    use serde_json::json;
+    use std::io::Write;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn chatgpt_export() -> Value {
+        json!([{
+            "id": "c1",
+            "title": "Rust lifetimes",
+            "create_time": 1_700_000_000.5,
+            "update_time": 1_700_000_100.0,
+            "current_node": "n4",
+            "mapping": {
+                "root": {"id": "root", "message": null, "parent": null, "children": ["n1"]},
+                "n1": {"id": "n1", "parent": "root", "children": ["n2", "n2b"], "message": {
+                    "id": "n1", "author": {"role": "system"}, "create_time": null,
+                    "content": {"content_type": "text", "parts": [""]},
+                    "metadata": {"is_visually_hidden_from_conversation": true}
+                }},
+                "n2": {"id": "n2", "parent": "n1", "children": ["n3"], "message": {
+                    "id": "n2", "author": {"role": "user"}, "create_time": 1_700_000_010.0,
+                    "content": {"content_type": "multimodal_text", "parts": [
+                        {"content_type": "image_asset_pointer", "asset_pointer": "file-1"},
+                        "What is 'a?"
+                    ]}
+                }},
+                "n2b": {"id": "n2b", "parent": "n1", "children": [], "message": {
+                    "id": "n2b", "author": {"role": "user"}, "create_time": 1_700_000_005.0,
+                    "content": {"content_type": "text", "parts": ["An abandoned edit"]}
+                }},
+                "n3": {"id": "n3", "parent": "n2", "children": ["n4"], "message": {
+                    "id": "n3", "author": {"role": "assistant"}, "create_time": 1_700_000_020.0,
+                    "content": {"content_type": "thoughts", "thoughts": [{"content": "Explain simply"}]}
+                }},
+                "n4": {"id": "n4", "parent": "n3", "children": [], "message": {
+                    "id": "n4", "author": {"role": "assistant"}, "create_time": 1_700_000_030.0,
+                    "content": {"content_type": "text", "parts": ["A lifetime parameter."]},
+                    "metadata": {"model_slug": "gpt-4o"}
+                }}
+            }
+        }])
+    }
+
+    fn claude_export() -> Value {
+        json!([
+            {
+                "uuid": "k1",
+                "name": "Haiku",
+                "created_at": "2024-03-01T12:00:00.000000Z",
+                "updated_at": "2024-03-01T12:05:00.000000Z",
+                "chat_messages": [
+                    {"uuid": "m1", "sender": "human", "text": "Write a haiku",
+                     "created_at": "2024-03-01T12:00:01.000000Z", "content": []},
+                    {"uuid": "m2", "sender": "assistant", "text": "Old pond",
+                     "created_at": "2024-03-01T12:00:02.000000Z",
+                     "content": [
+                        {"type": "thinking", "thinking": "Basho"},
+                        {"type": "text", "text": "Old pond, frog jumps in"}
+                     ]}
+                ]
+            },
+            {"uuid": "k2", "name": "Empty", "chat_messages": []}
+        ])
+    }
+
+    #[test]
+    fn chatgpt_follows_the_current_branch() {
+        let (sessions, skipped) = parse_export(chatgpt_export().to_string().as_bytes()).unwrap();
+        assert_eq!(skipped, 0);
+        let session = &sessions[0];
+        assert_eq!(session.id, "chatgpt-c1");
+        assert_eq!(session.title.as_deref(), Some("Rust lifetimes"));
+        assert_eq!(session.created_at.timestamp_millis(), 1_700_000_000_500);
+
+        let ids: Vec<_> = session.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["n2", "n3", "n4"]);
+        assert_eq!(session.messages[0].role, MessageRole::User);
+        assert_eq!(session.messages[0].content, "What is 'a?");
+        assert_eq!(
+            session.messages[1].parts,
+            vec![MessagePart::Reasoning {
+                text: "Explain simply".to_string()
+            }]
+        );
+        assert_eq!(session.messages[2].model.as_deref(), Some("gpt-4o"));
+        assert_eq!(session.messages[2].timestamp.timestamp(), 1_700_000_030);
+    }
+
+    #[test]
+    fn claude_maps_senders_and_blocks() {
+        let (sessions, skipped) = parse_export(claude_export().to_string().as_bytes()).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.id, "claude-k1");
+        assert_eq!(session.title.as_deref(), Some("Haiku"));
+        assert_eq!(session.messages[0].role, MessageRole::User);
+        assert_eq!(session.messages[0].content, "Write a haiku");
+        assert_eq!(session.messages[1].role, MessageRole::Assistant);
+        assert_eq!(session.messages[1].content, "Old pond, frog jumps in");
+        assert_eq!(session.messages[1].parts.len(), 2);
+        assert_eq!(
+            session.messages[1].timestamp,
+            "2024-03-01T12:00:02Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert!(parse_export(b"{\"not\": \"an export\"}").is_err());
+        let (sessions, skipped) = parse_export(b"[{\"title\": \"?\"}]").unwrap();
+        assert!(sessions.is_empty());
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn reads_conversations_from_a_zip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("export.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("chat.html", options).unwrap();
+        zip.write_all(b"<html></html>").unwrap();
+        zip.start_file("conversations.json", options).unwrap();
+        zip.write_all(claude_export().to_string().as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let (sessions, _) = read_export(&path).unwrap();
+        assert_eq!(sessions[0].id, "claude-k1");
+
+        let missing = temp.path().join("empty.zip");
+        zip::ZipWriter::new(File::create(&missing).unwrap())
+            .finish()
+            .unwrap();
+        assert!(read_export(&missing).is_err());
+    }
+
+    #[tokio::test]
+    async fn importing_twice_adds_nothing_new() {
+        let temp = TempDir::new().unwrap();
+        let api_client = Arc::new(ApiClient::new().unwrap());
+        let manager = SessionManager::new(api_client, temp.path().to_path_buf());
+
+        let (sessions, _) = parse_export(chatgpt_export().to_string().as_bytes()).unwrap();
+        let summary = import_sessions(&manager, sessions.clone()).await.unwrap();
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.messages, 3);
+
+        let summary = import_sessions(&manager, sessions).await.unwrap();
+        assert_eq!(summary.imported, 0);
+        assert_eq!(summary.already_imported, 1);
+        assert_eq!(manager.message_count("chatgpt-c1").await, Some(3));
+    }
+}
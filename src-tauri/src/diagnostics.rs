@@ -0,0 +1,318 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! LSP diagnostics for files the agent changed. The server runs language
+//! servers after each edit and attaches what they report to the edit's
+//! tool call, keyed by file.
+
+use crate::file_changes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Tauri event with a session's `SessionDiagnostics` after a response
+pub const DIAGNOSTICS_EVENT: &str = "session-diagnostics";
+
+/// Tools whose results carry diagnostics
+const EDIT_TOOLS: [&str; 3] = ["edit", "write", "patch"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl DiagnosticSeverity {
+    /// LSP numbers severities 1 (error) to 4 (hint) and treats a missing
+    /// one as an error
+    fn from_lsp(value: Option<u64>) -> Self {
+        match value {
+            Some(2) => Self::Warning,
+            Some(3) => Self::Information,
+            Some(4) => Self::Hint,
+            _ => Self::Error,
+        }
+    }
+}
+
+/// Zero-based, as in LSP
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub range: Range,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    /// Reporting tool, e.g. `rust-analyzer` or `typescript`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl Diagnostic {
+    fn from_lsp(file: &str, value: &Value) -> Option<Self> {
+        let message = value.get("message").and_then(Value::as_str)?;
+        let range = value
+            .get("range")
+            .and_then(|range| serde_json::from_value(range.clone()).ok())
+            .unwrap_or_default();
+        Some(Self {
+            file: file.to_string(),
+            range,
+            severity: DiagnosticSeverity::from_lsp(value.get("severity").and_then(Value::as_u64)),
+            message: message.to_string(),
+            source: value
+                .get("source")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            code: value.get("code").and_then(|code| match code {
+                Value::String(code) => Some(code.clone()),
+                Value::Number(code) => Some(code.to_string()),
+                _ => None,
+            }),
+        })
+    }
+}
+
+/// Diagnostics for the files changed in a session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionDiagnostics {
+    pub session_id: String,
+    /// Files the agent changed, whether or not they have diagnostics
+    pub files: Vec<String>,
+    /// Errors first, then by file and position
+    pub diagnostics: Vec<Diagnostic>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// Collect diagnostics from a session's messages (the server's
+/// `{ info, parts }` objects). Each edit reports the state of every file the
+/// language servers know, so the latest report for a file replaces earlier
+/// ones; only files the agent changed are kept.
+pub fn from_messages(session_id: &str, messages: &[Value]) -> SessionDiagnostics {
+    let files: BTreeSet<String> = file_changes::changes_from_messages(messages)
+        .into_iter()
+        .map(|change| change.diff.file)
+        .collect();
+
+    let mut latest: BTreeMap<String, Vec<Diagnostic>> = BTreeMap::new();
+    let parts = messages
+        .iter()
+        .filter_map(|message| message.get("parts").and_then(Value::as_array))
+        .flatten();
+    for part in parts {
+        let tool = part.get("tool").and_then(Value::as_str).unwrap_or_default();
+        if part.get("type").and_then(Value::as_str) != Some("tool") || !EDIT_TOOLS.contains(&tool) {
+            continue;
+        }
+        let Some(reported) = part
+            .pointer("/state/metadata/diagnostics")
+            .and_then(Value::as_object)
+        else {
+            continue;
+        };
+        for (file, diagnostics) in reported {
+            let diagnostics = diagnostics
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|diagnostic| Diagnostic::from_lsp(file, diagnostic))
+                .collect();
+            latest.insert(file.clone(), diagnostics);
+        }
+    }
+
+    let mut diagnostics: Vec<Diagnostic> = latest
+        .into_iter()
+        .filter(|(file, _)| is_changed(&files, file))
+        .flat_map(|(_, diagnostics)| diagnostics)
+        .collect();
+    diagnostics.sort_by(|a, b| {
+        (
+            a.severity,
+            &a.file,
+            a.range.start.line,
+            a.range.start.character,
+        )
+            .cmp(&(
+                b.severity,
+                &b.file,
+                b.range.start.line,
+                b.range.start.character,
+            ))
+    });
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+
+    SessionDiagnostics {
+        session_id: session_id.to_string(),
+        errors: count(DiagnosticSeverity::Error),
+        warnings: count(DiagnosticSeverity::Warning),
+        files: files.into_iter().collect(),
+        diagnostics,
+    }
+}
+
+/// Diagnostics are keyed by absolute path while diffs may name the file
+/// relative to the workspace
+fn is_changed(files: &BTreeSet<String>, file: &str) -> bool {
+    files.contains(file)
+        || files
+            .iter()
+            .any(|changed| file.ends_with(&format!("/{}", changed.trim_start_matches("./"))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edit(file: &str, diagnostics: Value) -> Value {
+        json!({
+            "type": "tool",
+            "tool": "edit",
+            "callID": "call_1",
+            "state": {
+                "status": "completed",
+                "input": { "filePath": file },
+                "metadata": {
+                    "diff": format!("--- {0}\n+++ {0}\n@@ -1 +1 @@\n-a\n+b\n", file),
+                    "diagnostics": diagnostics
+                }
+            }
+        })
+    }
+
+    fn lsp(severity: u64, line: u32, message: &str) -> Value {
+        json!({
+            "range": {
+                "start": { "line": line, "character": 4 },
+                "end": { "line": line, "character": 9 }
+            },
+            "severity": severity,
+            "message": message,
+            "source": "rust-analyzer",
+            "code": "E0308"
+        })
+    }
+
+    #[test]
+    fn test_latest_report_per_changed_file() {
+        let messages = vec![
+            json!({
+                "info": { "id": "msg_1" },
+                "parts": [edit("/repo/src/lib.rs", json!({
+                    "/repo/src/lib.rs": [lsp(1, 3, "mismatched types")],
+                    "/repo/src/untouched.rs": [lsp(1, 1, "not ours")]
+                }))]
+            }),
+            json!({
+                "info": { "id": "msg_2" },
+                "parts": [edit("/repo/src/main.rs", json!({
+                    "/repo/src/lib.rs": [lsp(2, 8, "unused variable")],
+                    "/repo/src/main.rs": [lsp(1, 2, "cannot find value"), lsp(4, 0, "consider")]
+                }))]
+            }),
+        ];
+
+        let report = from_messages("ses_1", &messages);
+        assert_eq!(report.files, vec!["/repo/src/lib.rs", "/repo/src/main.rs"]);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.warnings, 1);
+        let messages: Vec<_> = report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["cannot find value", "unused variable", "consider"]
+        );
+
+        let first = &report.diagnostics[0];
+        assert_eq!(first.file, "/repo/src/main.rs");
+        assert_eq!(
+            first.range.start,
+            Position {
+                line: 2,
+                character: 4
+            }
+        );
+        assert_eq!(first.source.as_deref(), Some("rust-analyzer"));
+        assert_eq!(first.code.as_deref(), Some("E0308"));
+    }
+
+    #[test]
+    fn test_no_edits_means_no_diagnostics() {
+        let messages = vec![json!({
+            "info": { "id": "msg_1" },
+            "parts": [{ "type": "text", "text": "Nothing changed" }]
+        })];
+        let report = from_messages("ses_1", &messages);
+        assert!(report.files.is_empty());
+        assert!(report.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_relative_diff_paths_match_absolute_diagnostics() {
+        let files = BTreeSet::from(["src/lib.rs".to_string()]);
+        assert!(is_changed(&files, "/repo/src/lib.rs"));
+        assert!(!is_changed(&files, "/repo/src/main.rs"));
+        assert!(!is_changed(&files, "/repo/mysrc/lib.rs"));
+    }
+
+    #[test]
+    fn test_severity_from_lsp() {
+        assert_eq!(
+            DiagnosticSeverity::from_lsp(Some(1)),
+            DiagnosticSeverity::Error
+        );
+        assert_eq!(
+            DiagnosticSeverity::from_lsp(Some(3)),
+            DiagnosticSeverity::Information
+        );
+        assert_eq!(
+            DiagnosticSeverity::from_lsp(None),
+            DiagnosticSeverity::Error
+        );
+    }
+}
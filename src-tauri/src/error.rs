@@ -27,9 +27,13 @@
 ///
 /// Provides structured error types with user-friendly messages,
 /// retry logic with exponential backoff, and detailed error context.
+use crate::certificate_pinning;
+use crate::i18n::t;
+use crate::settings::{RetryOperation, RetrySettings};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::future::Future;
+use std::sync::RwLock;
 use std::time::Duration;
 
 /// Main error type for the application
@@ -81,6 +85,20 @@ pub enum AppError {
     },
     /// Server is not connected
     NotConnectedError { message: String },
+    /// The server presented a certificate that does not match the pinned
+    /// fingerprint for its connection
+    CertificateMismatch {
+        hostname: String,
+        expected: String,
+        actual: String,
+    },
+    /// The connected server is too old for the feature
+    Unsupported {
+        feature: String,
+        required_version: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        server_version: Option<String>,
+    },
     /// Operation timed out
     TimeoutError {
         operation: String,
@@ -94,55 +112,60 @@ impl AppError {
     /// Get user-friendly error message
     pub fn user_message(&self) -> String {
         match self {
-            AppError::NetworkError { message, .. } => {
-                format!("Network error: {}", message)
-            }
+            AppError::NetworkError { message, .. } => t("error.network", &[("message", message)]),
             AppError::ServerError {
                 status_code,
                 message,
                 ..
             } => match status_code {
-                400 => format!("Invalid request: {}", message),
-                401 => "Authentication required. Please check your API key.".to_string(),
-                403 => "Access denied. Please verify your permissions.".to_string(),
-                404 => format!("Not found: {}", message),
-                429 => "Too many requests. Please wait a moment and try again.".to_string(),
-                500..=599 => format!("Server error: {}", message),
-                _ => format!("Server responded with error ({}): {}", status_code, message),
+                400 => t("error.server.bad_request", &[("message", message)]),
+                401 => t("error.server.unauthorized", &[]),
+                403 => t("error.server.forbidden", &[]),
+                404 => t("error.server.not_found", &[("message", message)]),
+                429 => t("error.server.rate_limited", &[]),
+                500..=599 => t("error.server.internal", &[("message", message)]),
+                _ => t(
+                    "error.server.other",
+                    &[("status", &status_code.to_string()), ("message", message)],
+                ),
             },
-            AppError::AuthError { message, .. } => {
-                format!("Authentication failed: {}", message)
-            }
-            AppError::ValidationError { field, message } => {
-                format!("Invalid {}: {}", field, message)
-            }
-            AppError::SessionError { message, .. } => {
-                format!("Session error: {}", message)
-            }
-            AppError::FileSystemError { message, .. } => {
-                format!("File error: {}", message)
-            }
-            AppError::DataError { message, .. } => {
-                format!("Data error: {}", message)
-            }
-            AppError::ParseError { message, .. } => {
-                format!("Parse error: {}", message)
-            }
+            AppError::AuthError { message, .. } => t("error.auth", &[("message", message)]),
+            AppError::ValidationError { field, message } => t(
+                "error.validation",
+                &[("field", field), ("message", message)],
+            ),
+            AppError::SessionError { message, .. } => t("error.session", &[("message", message)]),
+            AppError::FileSystemError { message, .. } => t("error.file", &[("message", message)]),
+            AppError::DataError { message, .. } => t("error.data", &[("message", message)]),
+            AppError::ParseError { message, .. } => t("error.parse", &[("message", message)]),
             AppError::ConnectionError { message, .. } => {
-                format!("Connection error: {}", message)
-            }
-            AppError::IoError { message, .. } => {
-                format!("I/O error: {}", message)
+                t("error.connection", &[("message", message)])
             }
+            AppError::IoError { message, .. } => t("error.io", &[("message", message)]),
             AppError::NotConnectedError { message } => {
-                format!("Not connected: {}", message)
+                t("error.not_connected", &[("message", message)])
             }
+            AppError::CertificateMismatch { hostname, .. } => {
+                t("error.certificate_mismatch", &[("host", hostname)])
+            }
+            AppError::Unsupported {
+                feature,
+                required_version,
+                ..
+            } => t(
+                "error.unsupported",
+                &[("feature", feature), ("version", required_version)],
+            ),
             AppError::TimeoutError {
                 operation,
                 timeout_secs,
-            } => {
-                format!("{} timed out after {} seconds", operation, timeout_secs)
-            }
+            } => t(
+                "error.timeout",
+                &[
+                    ("operation", operation),
+                    ("seconds", &timeout_secs.to_string()),
+                ],
+            ),
             AppError::Other { message } => message.clone(),
         }
     }
@@ -170,6 +193,24 @@ impl AppError {
             AppError::ConnectionError { details, .. } => details.clone().unwrap_or_default(),
             AppError::IoError { details, .. } => details.clone().unwrap_or_default(),
             AppError::NotConnectedError { message } => message.clone(),
+            AppError::CertificateMismatch {
+                hostname,
+                expected,
+                actual,
+            } => format!(
+                "Host: {}, Expected SHA-256: {}, Received SHA-256: {}",
+                hostname, expected, actual
+            ),
+            AppError::Unsupported {
+                feature,
+                required_version,
+                server_version,
+            } => format!(
+                "Feature: {}, Requires: {}, Server version: {}",
+                feature,
+                required_version,
+                server_version.as_deref().unwrap_or("unknown")
+            ),
             AppError::TimeoutError {
                 operation,
                 timeout_secs,
@@ -229,7 +270,14 @@ impl std::error::Error for AppError {}
 /// Convert from reqwest::Error to AppError
 impl From<reqwest::Error> for AppError {
     fn from(error: reqwest::Error) -> Self {
-        if error.is_timeout() {
+        let mismatch = error
+            .url()
+            .and_then(|url| url.host_str())
+            .filter(|_| error.is_connect())
+            .and_then(certificate_pinning::mismatch_for);
+        if let Some(mismatch) = mismatch {
+            AppError::from(mismatch)
+        } else if error.is_timeout() {
             AppError::TimeoutError {
                 operation: "HTTP request".to_string(),
                 timeout_secs: 30,
@@ -286,6 +334,205 @@ impl From<std::io::Error> for AppError {
     }
 }
 
+/// Stable error codes the frontend can branch on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Network,
+    Server,
+    Auth,
+    Validation,
+    Session,
+    FileSystem,
+    Data,
+    Parse,
+    Io,
+    Connection,
+    NotConnected,
+    Timeout,
+    NotInitialized,
+    NotFound,
+    /// The server was unreachable and the request was queued for later
+    Queued,
+    /// The app lock is engaged and the command needs the PIN first
+    Locked,
+    /// The server certificate does not match the pinned fingerprint
+    CertificateMismatch,
+    /// Outgoing content looks like it contains secrets
+    SecretDetected,
+    /// The connected server is too old for the feature
+    Unsupported,
+    Internal,
+}
+
+impl AppError {
+    /// Error code exposed across the Tauri boundary
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NetworkError { .. } => ErrorCode::Network,
+            AppError::ServerError {
+                status_code: 401 | 403,
+                ..
+            } => ErrorCode::Auth,
+            AppError::ServerError {
+                status_code: 404, ..
+            } => ErrorCode::NotFound,
+            AppError::ServerError { .. } => ErrorCode::Server,
+            AppError::AuthError { .. } => ErrorCode::Auth,
+            AppError::ValidationError { .. } => ErrorCode::Validation,
+            AppError::SessionError { .. } => ErrorCode::Session,
+            AppError::FileSystemError { .. } => ErrorCode::FileSystem,
+            AppError::DataError { .. } => ErrorCode::Data,
+            AppError::ParseError { .. } => ErrorCode::Parse,
+            AppError::IoError { .. } => ErrorCode::Io,
+            AppError::ConnectionError { .. } => ErrorCode::Connection,
+            AppError::NotConnectedError { .. } => ErrorCode::NotConnected,
+            AppError::CertificateMismatch { .. } => ErrorCode::CertificateMismatch,
+            AppError::Unsupported { .. } => ErrorCode::Unsupported,
+            AppError::TimeoutError { .. } => ErrorCode::Timeout,
+            AppError::Other { .. } => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Error returned by every Tauri command, so the frontend can match on
+/// `code` instead of parsing message strings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    /// User-facing message
+    pub message: String,
+    pub retryable: bool,
+    /// Technical details for logs and bug reports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: false,
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Validation, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn not_initialized(component: &str) -> Self {
+        Self::new(
+            ErrorCode::NotInitialized,
+            t("error.not_initialized", &[("component", component)]),
+        )
+    }
+
+    pub fn not_connected(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotConnected, message)
+    }
+
+    pub fn connection(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Connection, message)
+    }
+
+    pub fn data(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Data, message)
+    }
+
+    pub fn file_system(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::FileSystem, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn locked() -> Self {
+        Self::new(ErrorCode::Locked, t("error.app_locked", &[]))
+    }
+
+    /// Whether the failure means the server could not be reached at all
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self.code,
+            ErrorCode::Network
+                | ErrorCode::Connection
+                | ErrorCode::NotConnected
+                | ErrorCode::Timeout
+        )
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<AppError> for CommandError {
+    fn from(error: AppError) -> Self {
+        crate::error_stats::record(error.code(), "command");
+        let details = error.technical_details();
+        Self {
+            code: error.code(),
+            message: error.user_message(),
+            retryable: error.is_retryable(),
+            details: (!details.is_empty()).then_some(details),
+        }
+    }
+}
+
+/// Managers return `Box<dyn Error>`; keep the code when it wraps an `AppError`
+impl From<Box<dyn std::error::Error>> for CommandError {
+    fn from(error: Box<dyn std::error::Error>) -> Self {
+        match error.downcast::<AppError>() {
+            Ok(app_error) => (*app_error).into(),
+            Err(other) => CommandError::internal(other.to_string()),
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for CommandError {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match error.downcast::<AppError>() {
+            Ok(app_error) => (*app_error).into(),
+            Err(other) => CommandError::internal(other.to_string()),
+        }
+    }
+}
+
+/// Plain string errors from helpers without a more specific code
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::internal(message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        CommandError::internal(message)
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(error: std::io::Error) -> Self {
+        AppError::from(error).into()
+    }
+}
+
 /// Retry configuration for operations
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -293,6 +540,19 @@ pub struct RetryConfig {
     pub initial_delay_ms: u64,
     pub max_delay_ms: u64,
     pub backoff_multiplier: f64,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0
+    pub jitter: f64,
+}
+
+/// Retry settings in effect, replaced when the user changes them
+static RETRY_SETTINGS: RwLock<Option<RetrySettings>> = RwLock::new(None);
+
+/// Install the user's retry policy for subsequent operations
+pub fn set_retry_settings(settings: RetrySettings) {
+    match RETRY_SETTINGS.write() {
+        Ok(mut current) => *current = Some(settings),
+        Err(poisoned) => *poisoned.into_inner() = Some(settings),
+    }
 }
 
 impl Default for RetryConfig {
@@ -302,11 +562,23 @@ impl Default for RetryConfig {
             initial_delay_ms: 1000,
             max_delay_ms: 30000,
             backoff_multiplier: 2.0,
+            jitter: 0.0,
         }
     }
 }
 
 impl RetryConfig {
+    /// Config for an operation under the user's retry settings
+    pub fn for_operation(operation: RetryOperation) -> Self {
+        let settings = match RETRY_SETTINGS.read() {
+            Ok(settings) => settings.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        settings
+            .map(|settings| settings.policy_for(operation).into())
+            .unwrap_or_default()
+    }
+
     /// Create aggressive retry config (more retries, faster)
     pub fn aggressive() -> Self {
         Self {
@@ -314,6 +586,7 @@ impl RetryConfig {
             initial_delay_ms: 500,
             max_delay_ms: 10000,
             backoff_multiplier: 1.5,
+            jitter: 0.0,
         }
     }
 
@@ -324,6 +597,7 @@ impl RetryConfig {
             initial_delay_ms: 2000,
             max_delay_ms: 60000,
             backoff_multiplier: 3.0,
+            jitter: 0.0,
         }
     }
 
@@ -334,6 +608,16 @@ impl RetryConfig {
         let capped_delay_ms = delay_ms.min(self.max_delay_ms);
         Duration::from_millis(capped_delay_ms)
     }
+
+    /// Delay for a retry attempt with up to `jitter` of it randomized away
+    pub fn get_jittered_delay(&self, attempt: u32) -> Duration {
+        let delay = self.get_delay(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        delay.mul_f64(1.0 - jitter * rand::random::<f64>())
+    }
 }
 
 /// Retry a future with exponential backoff
@@ -361,13 +645,14 @@ where
                 }
 
                 // Calculate delay for this attempt
-                let delay = config.get_delay(attempt);
+                let delay = config.get_jittered_delay(attempt);
 
                 // Log retry attempt (for debugging)
-                eprintln!(
-                    "Retry attempt {} after {:?}: {}",
-                    attempt + 1,
-                    delay,
+                tracing::warn!(
+                    target: "retry",
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    "Retrying after error: {}",
                     error.user_message()
                 );
 
@@ -428,6 +713,24 @@ mod tests {
         assert_eq!(config.get_delay(10), Duration::from_millis(30000));
     }
 
+    #[test]
+    fn test_jittered_delay_stays_within_bounds() {
+        let config = RetryConfig {
+            jitter: 0.5,
+            ..RetryConfig::default()
+        };
+
+        for _ in 0..20 {
+            let delay = config.get_jittered_delay(1);
+            assert!(delay >= Duration::from_millis(1000));
+            assert!(delay <= Duration::from_millis(2000));
+        }
+        assert_eq!(
+            RetryConfig::default().get_jittered_delay(1),
+            Duration::from_millis(2000)
+        );
+    }
+
     #[test]
     fn test_aggressive_retry_config() {
         let config = RetryConfig::aggressive();
@@ -468,6 +771,7 @@ mod tests {
             initial_delay_ms: 10, // Fast for testing
             max_delay_ms: 100,
             backoff_multiplier: 2.0,
+            jitter: 0.0,
         };
 
         let result = retry_with_backoff(operation, config).await;
@@ -527,6 +831,7 @@ mod tests {
             initial_delay_ms: 10,
             max_delay_ms: 100,
             backoff_multiplier: 2.0,
+            jitter: 0.0,
         };
 
         let result = retry_with_backoff(operation, config).await;
@@ -534,4 +839,56 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(attempts.load(Ordering::SeqCst), 4); // Initial attempt + 3 retries
     }
+
+    #[test]
+    fn test_command_error_from_app_error() {
+        let error: CommandError = AppError::ServerError {
+            status_code: 503,
+            message: "Service Unavailable".to_string(),
+            details: "upstream down".to_string(),
+        }
+        .into();
+
+        assert_eq!(error.code, ErrorCode::Server);
+        assert!(error.retryable);
+        assert_eq!(error.details.as_deref(), Some("upstream down"));
+
+        let json = serde_json::to_value(&error).expect("Should serialize CommandError");
+        assert_eq!(json["code"], "SERVER");
+        assert_eq!(json["retryable"], true);
+    }
+
+    #[test]
+    fn test_command_error_from_boxed_error_keeps_code() {
+        let boxed: Box<dyn std::error::Error> = Box::new(AppError::NotConnectedError {
+            message: "No server".to_string(),
+        });
+        assert_eq!(CommandError::from(boxed).code, ErrorCode::NotConnected);
+
+        let other: Box<dyn std::error::Error> = "plain failure".into();
+        let error = CommandError::from(other);
+        assert_eq!(error.code, ErrorCode::Internal);
+        assert_eq!(error.message, "plain failure");
+    }
+
+    #[test]
+    fn test_command_error_helpers_serialize_without_details() {
+        let json = serde_json::to_value(CommandError::validation("Session ID cannot be empty"))
+            .expect("Should serialize CommandError");
+        assert_eq!(json["code"], "VALIDATION");
+        assert!(json.get("details").is_none());
+    }
+
+    #[test]
+    fn test_unreachable_codes() {
+        assert!(CommandError::not_connected("No server").is_unreachable());
+        assert!(CommandError::connection("Refused").is_unreachable());
+        assert!(!CommandError::validation("Empty").is_unreachable());
+        assert!(!CommandError::from(AppError::ServerError {
+            status_code: 500,
+            message: "Internal".to_string(),
+            details: String::new(),
+        })
+        .is_unreachable());
+    }
 }
@@ -0,0 +1,120 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::settings::PrivacySettings;
+use std::sync::Arc;
+
+const SENTRY_DSN: &str = "https://f7ded2177f996e519be91651f05d38a3@sentry.fergify.work/16";
+
+/// Sentry options with path and identity scrubbing. PII is only attached
+/// when the user explicitly allowed it.
+fn client_options(send_default_pii: bool) -> sentry::ClientOptions {
+    sentry::ClientOptions {
+        dsn: SENTRY_DSN.parse().ok(),
+        release: sentry::release_name!(),
+        environment: Some("production".into()),
+        send_default_pii,
+        // Privacy filtering - remove sensitive data
+        before_send: Some(Arc::new(|mut event| {
+            // Remove potentially sensitive information
+            event.server_name = None;
+            event.user = None;
+
+            // Sanitize any file paths that might contain user data
+            for exception in &mut event.exception {
+                if let Some(ref mut stacktrace) = exception.stacktrace {
+                    for frame in &mut stacktrace.frames {
+                        // Remove or sanitize file paths
+                        if let Some(ref filename) = frame.filename {
+                            let filename_str = filename.as_str();
+                            // Replace user home directory with generic placeholder
+                            if filename_str.contains("/Users/")
+                                || filename_str.contains("\\Users\\")
+                                || filename_str.contains("/home/")
+                            {
+                                frame.filename = Some("[USER_DIR]/sanitized_path".to_string());
+                            }
+                        }
+                    }
+                }
+            }
+
+            Some(event)
+        })),
+        ..Default::default()
+    }
+}
+
+/// Whether crash reports may be sent. Reporting stays off until the user
+/// has answered the consent prompt.
+pub fn is_enabled(privacy: &PrivacySettings) -> bool {
+    privacy.error_reporting_enabled == Some(true)
+}
+
+/// Bind or unbind the Sentry client on the main hub to match the user's
+/// consent, so toggling takes effect without a restart
+pub fn apply(privacy: &PrivacySettings) {
+    let hub = sentry::Hub::main();
+
+    if !is_enabled(privacy) {
+        if let Some(client) = hub.client() {
+            client.close(Some(std::time::Duration::from_secs(2)));
+        }
+        hub.bind_client(None);
+        return;
+    }
+
+    let client = sentry::Client::from(client_options(privacy.error_reporting_pii));
+    hub.bind_client(Some(Arc::new(client)));
+
+    // Add context for better error reporting
+    hub.configure_scope(|scope| {
+        scope.set_tag("app", "opencode-nexus");
+        scope.set_tag("component", "backend");
+        scope.set_tag("os", std::env::consts::OS);
+        scope.set_tag("arch", std::env::consts::ARCH);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reporting_requires_explicit_consent() {
+        let mut privacy = PrivacySettings::default();
+        assert!(!is_enabled(&privacy));
+
+        privacy.error_reporting_enabled = Some(false);
+        assert!(!is_enabled(&privacy));
+
+        privacy.error_reporting_enabled = Some(true);
+        assert!(is_enabled(&privacy));
+    }
+
+    #[test]
+    fn test_pii_follows_setting() {
+        assert!(!client_options(false).send_default_pii);
+        assert!(client_options(true).send_default_pii);
+        assert!(client_options(false).dsn.is_some());
+    }
+}
@@ -0,0 +1,316 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::{AppError, ErrorCode};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::warn;
+
+/// How long hourly buckets are kept
+const RETENTION_DAYS: i64 = 30;
+
+/// Recorder used by `record`, installed once at startup
+static RECORDER: OnceLock<ErrorStats> = OnceLock::new();
+
+/// Error occurrences for one code and module within one hour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBucket {
+    pub hour: DateTime<Utc>,
+    pub code: ErrorCode,
+    pub module: String,
+    pub count: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Window covered by `get_error_summary`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryPeriod {
+    #[default]
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl SummaryPeriod {
+    pub fn duration(&self) -> Duration {
+        match self {
+            SummaryPeriod::Hour => Duration::hours(1),
+            SummaryPeriod::Day => Duration::days(1),
+            SummaryPeriod::Week => Duration::weeks(1),
+            SummaryPeriod::Month => Duration::days(RETENTION_DAYS),
+        }
+    }
+}
+
+/// Occurrences of one code in one module over the summary window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ErrorSummaryEntry {
+    pub code: ErrorCode,
+    pub module: String,
+    pub count: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Aggregated error counts, most frequent first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorSummary {
+    pub period: SummaryPeriod,
+    pub since: DateTime<Utc>,
+    pub total: u64,
+    pub entries: Vec<ErrorSummaryEntry>,
+}
+
+/// Hourly error counts persisted in `error_stats.json`
+#[derive(Clone)]
+pub struct ErrorStats {
+    config_dir: PathBuf,
+    buckets: Arc<Mutex<Vec<ErrorBucket>>>,
+}
+
+impl ErrorStats {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            buckets: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn get_stats_file_path(&self) -> PathBuf {
+        self.config_dir.join("error_stats.json")
+    }
+
+    fn lock_buckets(&self) -> std::sync::MutexGuard<'_, Vec<ErrorBucket>> {
+        match self.buckets.lock() {
+            Ok(buckets) => buckets,
+            Err(poisoned) => {
+                eprintln!("[ERROR] ErrorStats: buckets mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Load saved buckets, dropping any past the retention window
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let stats_file = self.get_stats_file_path();
+        if !stats_file.exists() {
+            return Ok(());
+        }
+
+        let stats_json =
+            std::fs::read_to_string(&stats_file).map_err(|e| AppError::FileSystemError {
+                path: stats_file.to_string_lossy().to_string(),
+                message: "Failed to read error stats file".to_string(),
+                details: e.to_string(),
+            })?;
+        let mut loaded: Vec<ErrorBucket> =
+            serde_json::from_str(&stats_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse error stats file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+        let cutoff = Utc::now() - Duration::days(RETENTION_DAYS);
+        loaded.retain(|bucket| bucket.last_seen >= cutoff);
+
+        *self.lock_buckets() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, buckets: &[ErrorBucket]) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let stats_json = serde_json::to_string(buckets)?;
+        std::fs::write(self.get_stats_file_path(), stats_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_stats_file_path().to_string_lossy().to_string(),
+                message: "Failed to write error stats file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Count one occurrence and persist the updated buckets
+    pub fn record_at(
+        &self,
+        code: ErrorCode,
+        module: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let hour = at.duration_trunc(Duration::hours(1)).unwrap_or(at);
+        let cutoff = at - Duration::days(RETENTION_DAYS);
+
+        let mut buckets = self.lock_buckets();
+        buckets.retain(|bucket| bucket.last_seen >= cutoff);
+        match buckets
+            .iter_mut()
+            .find(|b| b.hour == hour && b.code == code && b.module == module)
+        {
+            Some(bucket) => {
+                bucket.count += 1;
+                bucket.last_seen = bucket.last_seen.max(at);
+            }
+            None => buckets.push(ErrorBucket {
+                hour,
+                code,
+                module: module.to_string(),
+                count: 1,
+                last_seen: at,
+            }),
+        }
+        self.save(&buckets)
+    }
+
+    /// Aggregate buckets touched within the period. Counts have hour
+    /// granularity, so a bucket straddling the window start is included.
+    pub fn summary(&self, period: SummaryPeriod) -> ErrorSummary {
+        let since = Utc::now() - period.duration();
+        let mut totals: HashMap<(ErrorCode, String), ErrorSummaryEntry> = HashMap::new();
+
+        for bucket in self.lock_buckets().iter() {
+            if bucket.last_seen < since {
+                continue;
+            }
+            let entry = totals
+                .entry((bucket.code, bucket.module.clone()))
+                .or_insert_with(|| ErrorSummaryEntry {
+                    code: bucket.code,
+                    module: bucket.module.clone(),
+                    count: 0,
+                    last_seen: bucket.last_seen,
+                });
+            entry.count += bucket.count;
+            entry.last_seen = entry.last_seen.max(bucket.last_seen);
+        }
+
+        let mut entries: Vec<ErrorSummaryEntry> = totals.into_values().collect();
+        entries.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+
+        ErrorSummary {
+            period,
+            since,
+            total: entries.iter().map(|entry| entry.count).sum(),
+            entries,
+        }
+    }
+}
+
+/// Install the process-wide recorder used by `record`
+pub fn install(stats: ErrorStats) {
+    let _ = RECORDER.set(stats);
+}
+
+/// Count an error occurrence. Metrics always see it; the persisted stats
+/// only once `install` has been called.
+pub fn record(code: ErrorCode, module: &str) {
+    crate::metrics::record_error(code);
+    if let Some(stats) = RECORDER.get() {
+        if let Err(e) = stats.record_at(code, module, Utc::now()) {
+            warn!(target: "metrics", "Failed to record error stats: {}", e);
+        }
+    }
+}
+
+/// Re-read saved stats, e.g. after the encrypted profile was unlocked
+pub fn reload() -> Result<(), Box<dyn std::error::Error>> {
+    match RECORDER.get() {
+        Some(stats) => stats.load(),
+        None => Ok(()),
+    }
+}
+
+/// Summary from the installed recorder
+pub fn summary(period: SummaryPeriod) -> Option<ErrorSummary> {
+    RECORDER.get().map(|stats| stats.summary(period))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_error_stats() -> (ErrorStats, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let stats = ErrorStats::new(temp_dir.path().to_path_buf());
+        (stats, temp_dir)
+    }
+
+    #[test]
+    fn test_summary_groups_by_code_and_module() {
+        let (stats, _temp) = create_test_error_stats();
+        let now = Utc::now();
+        for _ in 0..3 {
+            stats.record_at(ErrorCode::Network, "command", now).unwrap();
+        }
+        stats.record_at(ErrorCode::Network, "health", now).unwrap();
+        stats.record_at(ErrorCode::Auth, "command", now).unwrap();
+
+        let summary = stats.summary(SummaryPeriod::Hour);
+        assert_eq!(summary.total, 5);
+        assert_eq!(summary.entries.len(), 3);
+        assert_eq!(summary.entries[0].code, ErrorCode::Network);
+        assert_eq!(summary.entries[0].module, "command");
+        assert_eq!(summary.entries[0].count, 3);
+    }
+
+    #[test]
+    fn test_summary_respects_period() {
+        let (stats, _temp) = create_test_error_stats();
+        let now = Utc::now();
+        stats
+            .record_at(ErrorCode::Timeout, "command", now - Duration::days(2))
+            .unwrap();
+        stats.record_at(ErrorCode::Timeout, "command", now).unwrap();
+
+        assert_eq!(stats.summary(SummaryPeriod::Day).total, 1);
+        assert_eq!(stats.summary(SummaryPeriod::Week).total, 2);
+    }
+
+    #[test]
+    fn test_stats_persist_across_loads() {
+        let (stats, temp) = create_test_error_stats();
+        let now = Utc::now();
+        stats.record_at(ErrorCode::Server, "command", now).unwrap();
+        stats
+            .record_at(
+                ErrorCode::Server,
+                "command",
+                now - Duration::days(RETENTION_DAYS + 1),
+            )
+            .unwrap();
+
+        let reloaded = ErrorStats::new(temp.path().to_path_buf());
+        reloaded.load().expect("Should load error stats");
+        assert_eq!(reloaded.summary(SummaryPeriod::Month).total, 1);
+    }
+}
@@ -20,11 +20,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::connection_manager::{ConnectionEvent, ConnectionEventType};
-use crate::session_manager::{ChatMessage, ChatSession, MessageRole};
+use crate::connection_manager::{ConnectionEvent, ConnectionEventType, ServerEvent};
+use crate::session_manager::{ChatMessage, ChatSession, MessagePart, MessageRole};
+use crate::session_windows::SessionWindows;
 use crate::streaming_client::StreamEvent;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::{broadcast, RwLock};
@@ -71,6 +72,34 @@ pub enum AppEvent {
     },
 }
 
+impl AppEvent {
+    /// Session a message, stream or session error belongs to. Session list
+    /// changes are not scoped since every window's sidebar shows them.
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            AppEvent::Message { data, .. } => match data {
+                MessageEventData::Sent { session_id, .. }
+                | MessageEventData::Received { session_id, .. }
+                | MessageEventData::Updated { session_id, .. }
+                | MessageEventData::Deleted { session_id, .. } => Some(session_id),
+            },
+            AppEvent::Stream { data, .. } => match data {
+                StreamEventData::Started { session_id, .. }
+                | StreamEventData::Chunk { session_id, .. }
+                | StreamEventData::Completed { session_id, .. }
+                | StreamEventData::Error { session_id, .. }
+                | StreamEventData::Stopped { session_id, .. }
+                | StreamEventData::PermissionRequested { session_id, .. } => Some(session_id),
+            },
+            AppEvent::Error {
+                data: ErrorEventData::Session { session_id, .. },
+                ..
+            } => Some(session_id),
+            _ => None,
+        }
+    }
+}
+
 /// Connection event data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -85,6 +114,17 @@ pub enum ConnectionEventData {
     Connecting {
         server_url: String,
     },
+    /// Waiting `delay_ms` before reconnect attempt `attempt`
+    Reconnecting {
+        server_url: String,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+    },
+    Reconnected {
+        server_url: String,
+        attempts: u32,
+    },
     Error {
         error: String,
         server_url: Option<String>,
@@ -162,6 +202,8 @@ pub enum StreamEventData {
         stream_id: String,
         message_id: String,
         final_content: String,
+        #[serde(default)]
+        parts: Vec<MessagePart>,
     },
     Error {
         session_id: String,
@@ -173,6 +215,11 @@ pub enum StreamEventData {
         session_id: String,
         stream_id: String,
     },
+    PermissionRequested {
+        session_id: String,
+        stream_id: String,
+        permission_id: Option<String>,
+    },
 }
 
 /// Application event data
@@ -192,6 +239,10 @@ pub enum ApplicationEventData {
         key: String,
         value: serde_json::Value,
     },
+    /// The connected server reported a new installation
+    ServerUpdated {
+        version: Option<String>,
+    },
 }
 
 /// Error event data
@@ -232,12 +283,18 @@ pub struct ServerInfo {
     pub features: Option<Vec<String>>,
 }
 
+/// Number of emitted events kept for diagnostics
+const RECENT_EVENT_CAPACITY: usize = 200;
+
 /// Event bridge for converting and emitting events to frontend
 #[derive(Clone)]
 pub struct EventBridge {
     app_handle: Option<Arc<AppHandle>>,
+    /// Routes session-scoped events to the windows showing the session
+    windows: Option<SessionWindows>,
     event_sender: broadcast::Sender<AppEvent>,
     subscribers: Arc<RwLock<HashMap<String, broadcast::Sender<AppEvent>>>>,
+    recent_events: Arc<RwLock<VecDeque<AppEvent>>>,
 }
 
 impl EventBridge {
@@ -247,8 +304,10 @@ impl EventBridge {
 
         Self {
             app_handle: None,
+            windows: None,
             event_sender,
             subscribers: Arc::new(RwLock::new(HashMap::new())),
+            recent_events: Arc::new(RwLock::new(VecDeque::with_capacity(RECENT_EVENT_CAPACITY))),
         }
     }
 
@@ -258,11 +317,20 @@ impl EventBridge {
 
         Self {
             app_handle: Some(Arc::new(app_handle)),
+            windows: None,
             event_sender,
             subscribers: Arc::new(RwLock::new(HashMap::new())),
+            recent_events: Arc::new(RwLock::new(VecDeque::with_capacity(RECENT_EVENT_CAPACITY))),
         }
     }
 
+    /// Deliver session-scoped events only to the windows showing that
+    /// session. Events for a session no window claims are still broadcast.
+    pub fn with_window_routing(mut self, windows: SessionWindows) -> Self {
+        self.windows = Some(windows);
+        self
+    }
+
     /// Subscribe to all events
     pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
         self.event_sender.subscribe()
@@ -281,6 +349,15 @@ impl EventBridge {
 
     /// Emit an event to all subscribers and frontend
     pub async fn emit(&self, event: AppEvent) -> Result<(), Box<dyn std::error::Error>> {
+        // Keep a bounded history for support bundles
+        {
+            let mut recent = self.recent_events.write().await;
+            if recent.len() == RECENT_EVENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+
         // Send to all subscribers
         let _ = self.event_sender.send(event.clone());
 
@@ -312,7 +389,17 @@ impl EventBridge {
                 AppEvent::Error { .. } => "error-event",
             };
 
-            app_handle.emit(event_name, &event)?;
+            let targets = match (&self.windows, event.session_id()) {
+                (Some(windows), Some(session_id)) => windows.windows_showing(session_id),
+                _ => Vec::new(),
+            };
+            if targets.is_empty() {
+                app_handle.emit(event_name, &event)?;
+            } else {
+                for label in targets {
+                    app_handle.emit_to(label.as_str(), event_name, &event)?;
+                }
+            }
         }
 
         Ok(())
@@ -337,6 +424,24 @@ impl EventBridge {
                 status: connection_event.message,
                 latency_ms: None,
             },
+            ConnectionEventType::Reconnecting {
+                server_url,
+                attempt,
+                max_attempts,
+                delay_ms,
+            } => ConnectionEventData::Reconnecting {
+                server_url,
+                attempt,
+                max_attempts,
+                delay_ms,
+            },
+            ConnectionEventType::Reconnected {
+                server_url,
+                attempts,
+            } => ConnectionEventData::Reconnected {
+                server_url,
+                attempts,
+            },
         };
 
         AppEvent::Connection {
@@ -346,6 +451,109 @@ impl EventBridge {
         }
     }
 
+    /// Convert a global server event to an app event. Events Nexus has no
+    /// use for (file watches, LSP chatter, heartbeats) yield None.
+    pub fn server_to_app_event(&self, server_event: ServerEvent) -> Option<AppEvent> {
+        let properties = &server_event.properties;
+        let text = |value: &serde_json::Value, key: &str| {
+            value.get(key).and_then(|v| v.as_str()).map(str::to_string)
+        };
+        let object = |value: &serde_json::Value| -> HashMap<String, serde_json::Value> {
+            value
+                .as_object()
+                .map(|map| map.clone().into_iter().collect())
+                .unwrap_or_default()
+        };
+        let info = properties.get("info").unwrap_or(&serde_json::Value::Null);
+
+        let (event_id, timestamp) = (uuid::Uuid::new_v4().to_string(), chrono::Utc::now());
+        let event = match server_event.event_type.as_str() {
+            "session.updated" => AppEvent::Session {
+                event_id,
+                timestamp,
+                data: SessionEventData::Updated {
+                    session_id: text(info, "id")?,
+                    changes: object(info),
+                },
+            },
+            "session.deleted" => AppEvent::Session {
+                event_id,
+                timestamp,
+                data: SessionEventData::Deleted {
+                    session_id: text(info, "id")?,
+                },
+            },
+            "session.idle" => AppEvent::Session {
+                event_id,
+                timestamp,
+                data: SessionEventData::Updated {
+                    session_id: text(properties, "sessionID")?,
+                    changes: HashMap::from([("status".to_string(), serde_json::json!("idle"))]),
+                },
+            },
+            "session.error" => AppEvent::Error {
+                event_id,
+                timestamp,
+                data: ErrorEventData::Session {
+                    session_id: text(properties, "sessionID")?,
+                    error: properties
+                        .get("error")
+                        .map(|error| {
+                            text(&error["data"], "message").unwrap_or_else(|| error.to_string())
+                        })
+                        .unwrap_or_else(|| "Unknown error".to_string()),
+                },
+            },
+            "message.updated" => AppEvent::Message {
+                event_id,
+                timestamp,
+                data: MessageEventData::Updated {
+                    session_id: text(info, "sessionID")?,
+                    message_id: text(info, "id")?,
+                    changes: object(info),
+                },
+            },
+            "message.part.updated" => {
+                let part = properties.get("part")?;
+                AppEvent::Message {
+                    event_id,
+                    timestamp,
+                    data: MessageEventData::Updated {
+                        session_id: text(part, "sessionID")?,
+                        message_id: text(part, "messageID")?,
+                        changes: HashMap::from([("part".to_string(), part.clone())]),
+                    },
+                }
+            }
+            "message.removed" => AppEvent::Message {
+                event_id,
+                timestamp,
+                data: MessageEventData::Deleted {
+                    session_id: text(properties, "sessionID")?,
+                    message_id: text(properties, "messageID")?,
+                },
+            },
+            "permission.updated" => AppEvent::Stream {
+                event_id,
+                timestamp,
+                data: StreamEventData::PermissionRequested {
+                    session_id: text(properties, "sessionID")?,
+                    stream_id: "server".to_string(),
+                    permission_id: text(properties, "id"),
+                },
+            },
+            "installation.updated" => AppEvent::Application {
+                event_id,
+                timestamp,
+                data: ApplicationEventData::ServerUpdated {
+                    version: text(properties, "version"),
+                },
+            },
+            _ => return None,
+        };
+        Some(event)
+    }
+
     /// Convert stream event to app event
     pub fn stream_to_app_event(&self, stream_event: StreamEvent, session_id: String) -> AppEvent {
         let data = match stream_event {
@@ -369,12 +577,14 @@ impl EventBridge {
             StreamEvent::Complete {
                 message_id,
                 final_content,
+                parts,
                 ..
             } => StreamEventData::Completed {
                 session_id: session_id.clone(),
                 stream_id: uuid::Uuid::new_v4().to_string(),
                 message_id,
                 final_content,
+                parts,
             },
             StreamEvent::Error {
                 message_id,
@@ -391,6 +601,16 @@ impl EventBridge {
                 session_id: session_id.clone(),
                 stream_id: uuid::Uuid::new_v4().to_string(),
             },
+            StreamEvent::PermissionRequested { permission, .. } => {
+                StreamEventData::PermissionRequested {
+                    session_id: session_id.clone(),
+                    stream_id: uuid::Uuid::new_v4().to_string(),
+                    permission_id: permission
+                        .get("id")
+                        .and_then(|id| id.as_str())
+                        .map(str::to_string),
+                }
+            }
         };
 
         AppEvent::Stream {
@@ -488,6 +708,16 @@ impl EventBridge {
         self.emit(event).await
     }
 
+    /// Most recently emitted events, oldest first
+    pub async fn recent_events(&self) -> Vec<AppEvent> {
+        self.recent_events.read().await.iter().cloned().collect()
+    }
+
+    /// Events not yet read by every subscriber
+    pub fn backlog(&self) -> usize {
+        self.event_sender.len()
+    }
+
     /// Get number of active subscribers
     pub async fn subscriber_count(&self) -> usize {
         let subscribers = self.subscribers.read().await;
@@ -531,6 +761,80 @@ mod tests {
         assert_eq!(bridge.subscriber_count().now_or_never(), Some(0));
     }
 
+    #[test]
+    fn test_server_to_app_event() {
+        let bridge = EventBridge::new();
+        let server_event = |event_type: &str, properties: serde_json::Value| ServerEvent {
+            event_type: event_type.to_string(),
+            properties,
+            connection_id: None,
+        };
+
+        let updated = bridge.server_to_app_event(server_event(
+            "session.updated",
+            serde_json::json!({ "info": { "id": "ses_1", "title": "From the TUI" } }),
+        ));
+        match updated {
+            Some(AppEvent::Session {
+                data:
+                    SessionEventData::Updated {
+                        session_id,
+                        changes,
+                    },
+                ..
+            }) => {
+                assert_eq!(session_id, "ses_1");
+                assert_eq!(changes["title"], "From the TUI");
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        let part = bridge
+            .server_to_app_event(server_event(
+                "message.part.updated",
+                serde_json::json!({
+                    "part": { "id": "prt_1", "sessionID": "ses_1", "messageID": "msg_1" }
+                }),
+            ))
+            .expect("Part updates map to message updates");
+        assert_eq!(part.session_id(), Some("ses_1"));
+
+        let removed = bridge.server_to_app_event(server_event(
+            "message.removed",
+            serde_json::json!({ "sessionID": "ses_1", "messageID": "msg_1" }),
+        ));
+        assert!(matches!(
+            removed,
+            Some(AppEvent::Message {
+                data: MessageEventData::Deleted { .. },
+                ..
+            })
+        ));
+
+        let installed = bridge.server_to_app_event(server_event(
+            "installation.updated",
+            serde_json::json!({ "version": "0.5.0" }),
+        ));
+        assert!(matches!(
+            installed,
+            Some(AppEvent::Application {
+                data: ApplicationEventData::ServerUpdated { version: Some(_) },
+                ..
+            })
+        ));
+
+        // Missing identifiers and unrelated events are dropped
+        assert!(bridge
+            .server_to_app_event(server_event("session.updated", serde_json::json!({})))
+            .is_none());
+        assert!(bridge
+            .server_to_app_event(server_event(
+                "file.edited",
+                serde_json::json!({ "file": "a" })
+            ))
+            .is_none());
+    }
+
     #[test]
     fn test_app_event_serialization() {
         let event = AppEvent::Connection {
@@ -607,6 +911,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_session_scoped_events() {
+        let bridge = EventBridge::new();
+        let stream_event = StreamEvent::Chunk {
+            session_id: "session-123".to_string(),
+            message_id: "message-456".to_string(),
+            content: "Hello".to_string(),
+            index: 0,
+        };
+        let app_event = bridge.stream_to_app_event(stream_event, "session-123".to_string());
+        assert_eq!(app_event.session_id(), Some("session-123"));
+
+        let connection_event = AppEvent::Connection {
+            event_id: "event-1".to_string(),
+            timestamp: chrono::Utc::now(),
+            data: ConnectionEventData::Disconnected { reason: None },
+        };
+        assert_eq!(connection_event.session_id(), None);
+    }
+
     #[tokio::test]
     async fn test_event_subscription() {
         let bridge = EventBridge::new();
@@ -705,6 +1029,29 @@ mod tests {
         assert_eq!(bridge.subscriber_count().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_recent_events_are_bounded() {
+        let bridge = EventBridge::new();
+
+        for i in 0..(RECENT_EVENT_CAPACITY + 5) {
+            let event = AppEvent::Application {
+                event_id: format!("event-{}", i),
+                timestamp: Utc::now(),
+                data: ApplicationEventData::Started {
+                    version: "1.0.0".to_string(),
+                },
+            };
+            bridge.emit(event).await.expect("Should emit event");
+        }
+
+        let recent = bridge.recent_events().await;
+        assert_eq!(recent.len(), RECENT_EVENT_CAPACITY);
+        match &recent[0] {
+            AppEvent::Application { event_id, .. } => assert_eq!(event_id, "event-5"),
+            _ => panic!("Expected Application event"),
+        }
+    }
+
     #[test]
     fn test_error_event_data() {
         let error_data = ErrorEventData::Network {
@@ -0,0 +1,378 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! File edits made by the agent, parsed from the patches the server
+//! attaches to completed edit tool calls.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+    /// Line number in the old file; `None` for added lines
+    pub old_line: Option<u32>,
+    /// Line number in the new file; `None` for removed lines
+    pub new_line: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// Text after the second `@@`, usually the enclosing function
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub section: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// One file's changes from a unified diff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileDiff {
+    pub file: String,
+    pub hunks: Vec<DiffHunk>,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+/// A file edit and the tool call that made it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileChange {
+    pub message_id: String,
+    /// Tool call id, when the server sent one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
+    pub tool: String,
+    #[serde(flatten)]
+    pub diff: FileDiff,
+}
+
+/// Parse `@@ -a,b +c,d @@ section`
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ ")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let range = |text: &str, sign: char| -> Option<(u32, u32)> {
+        let text = text.strip_prefix(sign)?;
+        match text.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((text.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old, '-')?;
+    let (new_start, new_lines) = range(new, '+')?;
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        section: section.trim().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+/// File name from a `---`/`+++` header, without the `a/`/`b/` prefix
+fn header_path(path: &str) -> Option<String> {
+    let path = path.split('\t').next().unwrap_or(path).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(
+        path.strip_prefix("a/")
+            .or_else(|| path.strip_prefix("b/"))
+            .unwrap_or(path)
+            .to_string(),
+    )
+}
+
+/// Split a unified diff into per-file changes. Text outside hunks, such
+/// as `Index:` or `diff --git` lines, is skipped.
+pub fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut old_path: Option<String> = None;
+    let (mut old_line, mut new_line) = (0u32, 0u32);
+    // Lines still expected in the current hunk; a removed `-- comment`
+    // must not be mistaken for a file header
+    let (mut old_remaining, mut new_remaining) = (0u32, 0u32);
+
+    for line in text.lines() {
+        let in_hunk = old_remaining > 0 || new_remaining > 0;
+        if let Some(path) = line.strip_prefix("--- ").filter(|_| !in_hunk) {
+            old_path = header_path(path);
+            continue;
+        }
+        if let Some(path) = line.strip_prefix("+++ ").filter(|_| !in_hunk) {
+            let file = header_path(path).or(old_path.take()).unwrap_or_default();
+            files.push(FileDiff {
+                file,
+                hunks: Vec::new(),
+                additions: 0,
+                deletions: 0,
+            });
+            continue;
+        }
+        if line.starts_with("@@") && !in_hunk {
+            if let (Some(file), Some(hunk)) = (files.last_mut(), parse_hunk_header(line)) {
+                old_line = hunk.old_start;
+                new_line = hunk.new_start;
+                old_remaining = hunk.old_lines;
+                new_remaining = hunk.new_lines;
+                file.hunks.push(hunk);
+            }
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        let Some(hunk) = file.hunks.last_mut() else {
+            continue;
+        };
+        let (kind, content) = match line.chars().next() {
+            Some('+') => (DiffLineKind::Added, &line[1..]),
+            Some('-') => (DiffLineKind::Removed, &line[1..]),
+            Some(' ') => (DiffLineKind::Context, &line[1..]),
+            None => (DiffLineKind::Context, ""),
+            // `\ No newline at end of file` and anything unrecognized
+            _ => continue,
+        };
+        let diff_line = match kind {
+            DiffLineKind::Added => {
+                file.additions += 1;
+                new_line += 1;
+                new_remaining = new_remaining.saturating_sub(1);
+                DiffLine {
+                    kind,
+                    content: content.to_string(),
+                    old_line: None,
+                    new_line: Some(new_line - 1),
+                }
+            }
+            DiffLineKind::Removed => {
+                file.deletions += 1;
+                old_line += 1;
+                old_remaining = old_remaining.saturating_sub(1);
+                DiffLine {
+                    kind,
+                    content: content.to_string(),
+                    old_line: Some(old_line - 1),
+                    new_line: None,
+                }
+            }
+            DiffLineKind::Context => {
+                old_line += 1;
+                new_line += 1;
+                old_remaining = old_remaining.saturating_sub(1);
+                new_remaining = new_remaining.saturating_sub(1);
+                DiffLine {
+                    kind,
+                    content: content.to_string(),
+                    old_line: Some(old_line - 1),
+                    new_line: Some(new_line - 1),
+                }
+            }
+        };
+        hunk.lines.push(diff_line);
+    }
+
+    files.retain(|file| !file.hunks.is_empty());
+    files
+}
+
+/// A whole new file, as written by a `write` tool call without a patch
+fn new_file_diff(file: &str, content: &str) -> FileDiff {
+    let lines: Vec<DiffLine> = content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| DiffLine {
+            kind: DiffLineKind::Added,
+            content: line.to_string(),
+            old_line: None,
+            new_line: Some(index as u32 + 1),
+        })
+        .collect();
+    let count = lines.len() as u32;
+    FileDiff {
+        file: file.to_string(),
+        hunks: vec![DiffHunk {
+            old_start: 0,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: count,
+            section: String::new(),
+            lines,
+        }],
+        additions: count,
+        deletions: 0,
+    }
+}
+
+/// File changes from completed tool calls in a session's messages, oldest
+/// first. Messages are the server's `{ info, parts }` objects.
+pub fn changes_from_messages(messages: &[Value]) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+    for message in messages {
+        let message_id = message
+            .pointer("/info/id")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let parts = message
+            .get("parts")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for part in parts {
+            if part.get("type").and_then(Value::as_str) != Some("tool") {
+                continue;
+            }
+            let state = part.get("state").unwrap_or(&Value::Null);
+            if state.get("status").and_then(Value::as_str) != Some("completed") {
+                continue;
+            }
+            let tool = part.get("tool").and_then(Value::as_str).unwrap_or_default();
+            let file_path = state.pointer("/input/filePath").and_then(Value::as_str);
+
+            let diffs = match state.pointer("/metadata/diff").and_then(Value::as_str) {
+                Some(patch) => parse_unified_diff(patch)
+                    .into_iter()
+                    .map(|mut diff| {
+                        if let Some(path) = file_path.filter(|_| diff.file.is_empty()) {
+                            diff.file = path.to_string();
+                        }
+                        diff
+                    })
+                    .collect(),
+                None => match (
+                    tool,
+                    file_path,
+                    state.pointer("/input/content").and_then(Value::as_str),
+                ) {
+                    ("write", Some(path), Some(content)) => vec![new_file_diff(path, content)],
+                    _ => Vec::new(),
+                },
+            };
+
+            let call_id = part
+                .get("callID")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            changes.extend(diffs.into_iter().map(|diff| FileChange {
+                message_id: message_id.to_string(),
+                call_id: call_id.clone(),
+                tool: tool.to_string(),
+                diff,
+            }));
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "Index: src/main.rs
+===================================================================
+--- src/main.rs
++++ src/main.rs
+@@ -1,3 +1,4 @@ fn main() {
+ fn main() {
+-    println!(\"hi\");
++    println!(\"hello\");
++    println!(\"world\");
+ }
+";
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let files = parse_unified_diff(PATCH);
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.file, "src/main.rs");
+        assert_eq!((file.additions, file.deletions), (2, 1));
+
+        let hunk = &file.hunks[0];
+        assert_eq!((hunk.old_start, hunk.new_lines), (1, 4));
+        assert_eq!(hunk.section, "fn main() {");
+        assert_eq!(hunk.lines.len(), 5);
+        assert_eq!(hunk.lines[1].old_line, Some(2));
+        assert_eq!(hunk.lines[3].new_line, Some(3));
+        assert_eq!(hunk.lines[4].new_line, Some(4));
+    }
+
+    #[test]
+    fn test_removed_line_that_looks_like_a_header() {
+        let patch = "--- q.sql\n+++ q.sql\n@@ -1,2 +1,1 @@\n--- old comment\n select 1;\n";
+        let files = parse_unified_diff(patch);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].deletions, 1);
+        assert_eq!(files[0].hunks[0].lines[0].content, "-- old comment");
+    }
+
+    #[test]
+    fn test_git_style_headers_and_new_files() {
+        let patch = "diff --git a/new.txt b/new.txt\n--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+first\n\\ No newline at end of file\n";
+        let files = parse_unified_diff(patch);
+        assert_eq!(files[0].file, "new.txt");
+        assert_eq!(files[0].additions, 1);
+        assert_eq!(files[0].hunks[0].new_lines, 1);
+    }
+
+    #[test]
+    fn test_changes_from_tool_parts() {
+        let messages = vec![serde_json::json!({
+            "info": { "id": "msg_1", "role": "assistant" },
+            "parts": [
+                { "type": "text", "text": "Editing" },
+                { "type": "tool", "tool": "edit", "callID": "call_1", "state": {
+                    "status": "completed",
+                    "input": { "filePath": "/repo/src/main.rs" },
+                    "metadata": { "diff": PATCH }
+                }},
+                { "type": "tool", "tool": "write", "state": {
+                    "status": "completed",
+                    "input": { "filePath": "/repo/README.md", "content": "# Title\nBody" }
+                }},
+                { "type": "tool", "tool": "edit", "state": { "status": "running" } }
+            ]
+        })];
+
+        let changes = changes_from_messages(&messages);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].call_id.as_deref(), Some("call_1"));
+        assert_eq!(changes[0].diff.file, "src/main.rs");
+        assert_eq!(changes[1].tool, "write");
+        assert_eq!(changes[1].diff.additions, 2);
+    }
+}
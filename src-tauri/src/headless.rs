@@ -0,0 +1,321 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Command line entry point that talks to the OpenCode server without
+//! starting the Tauri UI, so sessions can be scripted from a shell.
+
+use crate::api_client::ApiClient;
+use crate::connection_manager::ConnectionManager;
+use crate::error::CommandError;
+use crate::messaging::{Messaging, MessagingService};
+use crate::secret_scan;
+use crate::session_manager::SessionSummary;
+use crate::settings::{SecretScanMode, SettingsManager};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+/// Flag that switches `main` into headless mode
+pub const HEADLESS_FLAG: &str = "--headless";
+
+const USAGE: &str = "\
+Usage: opencode-nexus --headless [--server <url>] [--json] <command>
+
+Commands:
+  --list-sessions              List sessions on the server
+  send --session <id> <prompt> Send a prompt and print the reply
+  --start-server               Not supported; run `opencode serve` instead
+  --help                       Show this message";
+
+/// What the headless invocation asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessCommand {
+    ListSessions,
+    Send { session: String, prompt: String },
+    StartServer,
+    Help,
+}
+
+/// Parsed headless invocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessArgs {
+    pub command: HeadlessCommand,
+    /// Server to use instead of the last connected one
+    pub server: Option<String>,
+    /// Print machine readable JSON instead of text
+    pub json: bool,
+}
+
+/// Whether the process was started with `--headless`
+pub fn is_headless(args: &[String]) -> bool {
+    args.iter().skip(1).any(|arg| arg == HEADLESS_FLAG)
+}
+
+/// Parse the arguments following the program name
+pub fn parse_args(args: &[String]) -> Result<HeadlessArgs, String> {
+    let mut command = None;
+    let mut server = None;
+    let mut json = false;
+    let mut session = None;
+    let mut prompt: Vec<String> = Vec::new();
+    let mut sending = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            HEADLESS_FLAG => {}
+            "--json" => json = true,
+            "--server" => {
+                let url = iter.next().ok_or("--server requires a URL")?;
+                server = Some(url.clone());
+            }
+            "--session" => {
+                let id = iter.next().ok_or("--session requires a session id")?;
+                session = Some(id.clone());
+            }
+            "--list-sessions" => set_command(&mut command, HeadlessCommand::ListSessions)?,
+            "--start-server" => set_command(&mut command, HeadlessCommand::StartServer)?,
+            "--help" | "-h" => set_command(&mut command, HeadlessCommand::Help)?,
+            "send" if !sending => {
+                if command.is_some() {
+                    return Err("Only one command may be given".to_string());
+                }
+                sending = true;
+            }
+            other if other.starts_with("--") && !sending => {
+                return Err(format!("Unknown option: {}", other));
+            }
+            other if sending => prompt.push(other.to_string()),
+            other => return Err(format!("Unknown command: {}", other)),
+        }
+    }
+
+    let command = if sending {
+        if command.is_some() {
+            return Err("Only one command may be given".to_string());
+        }
+        let session = session.ok_or("send requires --session <id>")?;
+        let prompt = prompt.join(" ");
+        if prompt.trim().is_empty() {
+            return Err("send requires a prompt".to_string());
+        }
+        HeadlessCommand::Send { session, prompt }
+    } else {
+        if session.is_some() {
+            return Err("--session is only valid with send".to_string());
+        }
+        command.unwrap_or(HeadlessCommand::Help)
+    };
+
+    Ok(HeadlessArgs {
+        command,
+        server,
+        json,
+    })
+}
+
+fn set_command(slot: &mut Option<HeadlessCommand>, command: HeadlessCommand) -> Result<(), String> {
+    if slot.is_some() {
+        return Err("Only one command may be given".to_string());
+    }
+    *slot = Some(command);
+    Ok(())
+}
+
+/// Run a headless invocation and report the outcome as an exit code
+pub fn run(args: &[String]) -> ExitCode {
+    let parsed = match parse_args(args.get(1..).unwrap_or_default()) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match tauri::async_runtime::block_on(execute(&parsed)) {
+        Ok(output) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn execute(args: &HeadlessArgs) -> Result<String, CommandError> {
+    match &args.command {
+        HeadlessCommand::Help => Ok(USAGE.to_string()),
+        HeadlessCommand::StartServer => Err(CommandError::validation(
+            "OpenCode Nexus connects to an existing server and no longer manages one; \
+             start it with `opencode serve`",
+        )),
+        HeadlessCommand::ListSessions => {
+            let client = connect(args).await?;
+            let sessions = client.list_sessions().await?;
+            if args.json {
+                serde_json::to_string_pretty(&sessions)
+                    .map_err(|e| CommandError::internal(e.to_string()))
+            } else {
+                Ok(format_sessions(&sessions))
+            }
+        }
+        HeadlessCommand::Send { session, prompt } => {
+            check_secrets(prompt)?;
+            let client = connect(args).await?;
+            let reply = client.send_message(session, prompt).await?;
+            if args.json {
+                serde_json::to_string_pretty(&reply)
+                    .map_err(|e| CommandError::internal(e.to_string()))
+            } else {
+                Ok(reply.content)
+            }
+        }
+    }
+}
+
+/// Headless runs cannot ask for confirmation, so any scanning mode other
+/// than off blocks prompts that look like they contain a secret
+fn check_secrets(prompt: &str) -> Result<(), CommandError> {
+    let settings = SettingsManager::new(crate::get_config_dir()?);
+    settings.load().map_err(|e| {
+        CommandError::file_system("Failed to load settings").with_details(e.to_string())
+    })?;
+    if settings.get().privacy.secret_scanning == SecretScanMode::Off {
+        return Ok(());
+    }
+    match secret_scan::scan_text("message", prompt).first() {
+        Some(finding) => Err(CommandError::validation(format!(
+            "Prompt appears to contain a secret on line {} ({}); not sending",
+            finding.line, finding.preview
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Messaging without a local session store; a one-off run keeps no history
+async fn connect(args: &HeadlessArgs) -> Result<Messaging, CommandError> {
+    let server_url = match &args.server {
+        Some(url) => url.clone(),
+        None => {
+            // No app state here, so read the saved connections once
+            let mut manager =
+                ConnectionManager::new(crate::get_config_dir()?, None).map_err(|e| {
+                    CommandError::internal(format!("Failed to create connection manager: {}", e))
+                })?;
+            manager.load_connections().await.map_err(|e| {
+                CommandError::file_system(format!("Failed to load connections: {}", e))
+            })?;
+            crate::connected_server_url(Some(&manager))?
+        }
+    };
+    let api_client = Arc::new(ApiClient::new()?);
+    api_client.set_server_url(server_url.clone()).await?;
+    crate::attach_connection_secret(&api_client, &server_url).await;
+    Ok(Messaging::new(api_client, Arc::default()))
+}
+
+fn format_sessions(sessions: &[SessionSummary]) -> String {
+    sessions
+        .iter()
+        .map(|session| {
+            format!(
+                "{}\t{}\t{}",
+                session.id,
+                session.created_at.to_rfc3339(),
+                session.title.as_deref().unwrap_or("Untitled")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_headless_flag_after_program_name() {
+        assert!(is_headless(&args(&[
+            "nexus",
+            "--headless",
+            "--list-sessions"
+        ])));
+        assert!(!is_headless(&args(&["nexus"])));
+    }
+
+    #[test]
+    fn parses_list_sessions_with_options() {
+        let parsed = parse_args(&args(&[
+            "--headless",
+            "--json",
+            "--server",
+            "http://localhost:4096",
+            "--list-sessions",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.command, HeadlessCommand::ListSessions);
+        assert_eq!(parsed.server.as_deref(), Some("http://localhost:4096"));
+        assert!(parsed.json);
+    }
+
+    #[test]
+    fn parses_send_with_prompt_words() {
+        let parsed = parse_args(&args(&[
+            "--headless",
+            "send",
+            "--session",
+            "ses_1",
+            "fix",
+            "tests",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed.command,
+            HeadlessCommand::Send {
+                session: "ses_1".to_string(),
+                prompt: "fix tests".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_or_conflicting_commands() {
+        assert!(parse_args(&args(&["send", "hello"])).is_err());
+        assert!(parse_args(&args(&["send", "--session", "ses_1"])).is_err());
+        assert!(parse_args(&args(&["--list-sessions", "--start-server"])).is_err());
+        assert!(parse_args(&args(&["--server"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
+    }
+
+    #[test]
+    fn defaults_to_help() {
+        let parsed = parse_args(&args(&["--headless"])).unwrap();
+        assert_eq!(parsed.command, HeadlessCommand::Help);
+    }
+}
@@ -0,0 +1,329 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Rolling history of the health checks run against each connection, kept
+//! so the frontend can graph latency and report uptime.
+
+use crate::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::warn;
+
+/// Checks kept per connection, a day's worth at one check every 30 seconds
+const MAX_CHECKS_PER_CONNECTION: usize = 2880;
+
+/// Window `get_connection_uptime_stats` covers unless told otherwise
+pub const DEFAULT_UPTIME_HOURS: u32 = 24;
+
+/// Recorder used by `record`, installed once at startup
+static RECORDER: OnceLock<HealthHistory> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    Unhealthy,
+}
+
+/// Outcome of one health check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HealthCheck {
+    pub timestamp: DateTime<Utc>,
+    pub status: HealthStatus,
+    /// Time until the server responded; `None` when it never did
+    pub latency_ms: Option<u64>,
+}
+
+/// Uptime and latency of a connection over a window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UptimeStats {
+    pub connection_id: String,
+    pub since: DateTime<Utc>,
+    pub checks: usize,
+    pub healthy_checks: usize,
+    /// Share of healthy checks, `None` without any checks
+    pub uptime_percent: Option<f64>,
+    pub average_latency_ms: Option<f64>,
+    pub min_latency_ms: Option<u64>,
+    pub max_latency_ms: Option<u64>,
+    pub last_check: Option<HealthCheck>,
+}
+
+/// Health checks by connection id persisted in `health_history.json`
+#[derive(Clone)]
+pub struct HealthHistory {
+    config_dir: PathBuf,
+    checks: Arc<Mutex<HashMap<String, VecDeque<HealthCheck>>>>,
+}
+
+impl HealthHistory {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            checks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn get_history_file_path(&self) -> PathBuf {
+        self.config_dir.join("health_history.json")
+    }
+
+    fn lock_checks(&self) -> std::sync::MutexGuard<'_, HashMap<String, VecDeque<HealthCheck>>> {
+        match self.checks.lock() {
+            Ok(checks) => checks,
+            Err(poisoned) => {
+                eprintln!("[ERROR] HealthHistory: checks mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Load saved checks
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let history_file = self.get_history_file_path();
+        if !history_file.exists() {
+            return Ok(());
+        }
+
+        let history_json =
+            std::fs::read_to_string(&history_file).map_err(|e| AppError::FileSystemError {
+                path: history_file.to_string_lossy().to_string(),
+                message: "Failed to read health history file".to_string(),
+                details: e.to_string(),
+            })?;
+        let loaded: HashMap<String, VecDeque<HealthCheck>> = serde_json::from_str(&history_json)
+            .map_err(|e| AppError::ParseError {
+                message: "Failed to parse health history file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        *self.lock_checks() = loaded;
+        Ok(())
+    }
+
+    fn save(
+        &self,
+        checks: &HashMap<String, VecDeque<HealthCheck>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let history_json = serde_json::to_string(checks)?;
+        std::fs::write(self.get_history_file_path(), history_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_history_file_path().to_string_lossy().to_string(),
+                message: "Failed to write health history file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Append a check, dropping the oldest past the limit, and persist
+    pub fn record_check(
+        &self,
+        connection_id: &str,
+        check: HealthCheck,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut checks = self.lock_checks();
+        let history = checks.entry(connection_id.to_string()).or_default();
+        history.push_back(check);
+        while history.len() > MAX_CHECKS_PER_CONNECTION {
+            history.pop_front();
+        }
+        self.save(&checks)
+    }
+
+    /// The latest `limit` checks of a connection, oldest first
+    pub fn history(&self, connection_id: &str, limit: Option<usize>) -> Vec<HealthCheck> {
+        let checks = self.lock_checks();
+        let Some(history) = checks.get(connection_id) else {
+            return Vec::new();
+        };
+        let skip = limit.map_or(0, |limit| history.len().saturating_sub(limit));
+        history.iter().skip(skip).cloned().collect()
+    }
+
+    /// Uptime and latency over the last `hours`
+    pub fn uptime(&self, connection_id: &str, hours: u32) -> UptimeStats {
+        let since = Utc::now() - Duration::hours(i64::from(hours));
+        let checks = self.lock_checks();
+        let window: Vec<&HealthCheck> = checks
+            .get(connection_id)
+            .map(|history| {
+                history
+                    .iter()
+                    .filter(|check| check.timestamp >= since)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let healthy_checks = window
+            .iter()
+            .filter(|check| check.status == HealthStatus::Healthy)
+            .count();
+        let latencies: Vec<u64> = window.iter().filter_map(|check| check.latency_ms).collect();
+
+        UptimeStats {
+            connection_id: connection_id.to_string(),
+            since,
+            checks: window.len(),
+            healthy_checks,
+            uptime_percent: (!window.is_empty())
+                .then(|| healthy_checks as f64 * 100.0 / window.len() as f64),
+            average_latency_ms: (!latencies.is_empty())
+                .then(|| latencies.iter().sum::<u64>() as f64 / latencies.len() as f64),
+            min_latency_ms: latencies.iter().min().copied(),
+            max_latency_ms: latencies.iter().max().copied(),
+            last_check: window.last().map(|check| (*check).clone()),
+        }
+    }
+}
+
+/// Install the process-wide recorder used by `record`
+pub fn install(history: HealthHistory) {
+    let _ = RECORDER.set(history);
+}
+
+/// Record a health check once `install` has been called
+pub fn record(connection_id: &str, status: HealthStatus, latency_ms: Option<u64>) {
+    if let Some(history) = RECORDER.get() {
+        let check = HealthCheck {
+            timestamp: Utc::now(),
+            status,
+            latency_ms,
+        };
+        if let Err(e) = history.record_check(connection_id, check) {
+            warn!(target: "health", "Failed to record health check: {}", e);
+        }
+    }
+}
+
+/// Re-read saved checks, e.g. after the encrypted profile was unlocked
+pub fn reload() -> Result<(), Box<dyn std::error::Error>> {
+    match RECORDER.get() {
+        Some(history) => history.load(),
+        None => Ok(()),
+    }
+}
+
+/// History from the installed recorder
+pub fn history(connection_id: &str, limit: Option<usize>) -> Option<Vec<HealthCheck>> {
+    RECORDER
+        .get()
+        .map(|history| history.history(connection_id, limit))
+}
+
+/// Uptime stats from the installed recorder
+pub fn uptime(connection_id: &str, hours: u32) -> Option<UptimeStats> {
+    RECORDER
+        .get()
+        .map(|history| history.uptime(connection_id, hours))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_health_history() -> (HealthHistory, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let history = HealthHistory::new(temp_dir.path().to_path_buf());
+        (history, temp_dir)
+    }
+
+    fn check(minutes_ago: i64, status: HealthStatus, latency_ms: Option<u64>) -> HealthCheck {
+        HealthCheck {
+            timestamp: Utc::now() - Duration::minutes(minutes_ago),
+            status,
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn test_history_is_capped_and_limited() {
+        let (history, _temp) = create_test_health_history();
+        for _ in 0..MAX_CHECKS_PER_CONNECTION + 5 {
+            history
+                .record_check("a:1", check(0, HealthStatus::Healthy, Some(5)))
+                .unwrap();
+        }
+        assert_eq!(
+            history.history("a:1", None).len(),
+            MAX_CHECKS_PER_CONNECTION
+        );
+        assert_eq!(history.history("a:1", Some(10)).len(), 10);
+        assert!(history.history("b:1", None).is_empty());
+    }
+
+    #[test]
+    fn test_uptime_stats() {
+        let (history, _temp) = create_test_health_history();
+        history
+            .record_check("a:1", check(48 * 60, HealthStatus::Unhealthy, None))
+            .unwrap();
+        history
+            .record_check("a:1", check(30, HealthStatus::Healthy, Some(10)))
+            .unwrap();
+        history
+            .record_check("a:1", check(20, HealthStatus::Healthy, Some(30)))
+            .unwrap();
+        history
+            .record_check("a:1", check(10, HealthStatus::Unhealthy, None))
+            .unwrap();
+        history
+            .record_check("a:1", check(0, HealthStatus::Healthy, Some(20)))
+            .unwrap();
+
+        let stats = history.uptime("a:1", DEFAULT_UPTIME_HOURS);
+        assert_eq!(stats.checks, 4);
+        assert_eq!(stats.healthy_checks, 3);
+        assert_eq!(stats.uptime_percent, Some(75.0));
+        assert_eq!(stats.average_latency_ms, Some(20.0));
+        assert_eq!(stats.min_latency_ms, Some(10));
+        assert_eq!(stats.max_latency_ms, Some(30));
+        assert_eq!(stats.last_check.unwrap().latency_ms, Some(20));
+
+        let empty = history.uptime("b:1", DEFAULT_UPTIME_HOURS);
+        assert_eq!(empty.checks, 0);
+        assert_eq!(empty.uptime_percent, None);
+        assert_eq!(empty.average_latency_ms, None);
+    }
+
+    #[test]
+    fn test_history_persists_across_loads() {
+        let (history, temp) = create_test_health_history();
+        history
+            .record_check("a:1", check(0, HealthStatus::Healthy, Some(12)))
+            .unwrap();
+
+        let reloaded = HealthHistory::new(temp.path().to_path_buf());
+        reloaded.load().expect("Should load health history");
+        assert_eq!(reloaded.history("a:1", None), history.history("a:1", None));
+    }
+}
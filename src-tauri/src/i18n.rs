@@ -0,0 +1,462 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::sync::RwLock;
+
+/// Locale used when none is configured or a key is missing
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Locales with a catalog
+pub const SUPPORTED_LOCALES: &[&str] = &["en", "es", "fr", "de"];
+
+/// Active locale for backend-generated messages
+static LOCALE: RwLock<Option<String>> = RwLock::new(None);
+
+const EN: &[(&str, &str)] = &[
+    ("error.network", "Network error: {message}"),
+    ("error.server.bad_request", "Invalid request: {message}"),
+    (
+        "error.server.unauthorized",
+        "Authentication required. Please check your API key.",
+    ),
+    (
+        "error.server.forbidden",
+        "Access denied. Please verify your permissions.",
+    ),
+    ("error.server.not_found", "Not found: {message}"),
+    (
+        "error.server.rate_limited",
+        "Too many requests. Please wait a moment and try again.",
+    ),
+    ("error.server.internal", "Server error: {message}"),
+    (
+        "error.server.other",
+        "Server responded with error ({status}): {message}",
+    ),
+    ("error.auth", "Authentication failed: {message}"),
+    ("error.validation", "Invalid {field}: {message}"),
+    ("error.session", "Session error: {message}"),
+    ("error.file", "File error: {message}"),
+    ("error.data", "Data error: {message}"),
+    ("error.parse", "Parse error: {message}"),
+    ("error.connection", "Connection error: {message}"),
+    ("error.io", "I/O error: {message}"),
+    ("error.not_connected", "Not connected: {message}"),
+    (
+        "error.timeout",
+        "{operation} timed out after {seconds} seconds",
+    ),
+    ("error.not_initialized", "{component} not initialized"),
+    (
+        "error.queued",
+        "Server is unreachable. The message was queued and will be sent when the connection recovers.",
+    ),
+    ("error.app_locked", "The app is locked. Enter your PIN to continue."),
+    ("error.certificate_mismatch", "Security warning: the certificate presented by {host} does not match the pinned fingerprint. The connection was blocked."),
+    ("error.unsupported", "{feature} is not supported by this server (needs ≥ v{version})"),
+    ("error.secret_detected", "This message appears to contain {count} secret(s) ({kinds}). Remove them or confirm to send anyway."),
+    ("error.secret_blocked", "This message appears to contain {count} secret(s) ({kinds}) and was not sent."),
+    ("tray.status.connected", "Connected to {server}"),
+    ("tray.status.connecting", "Connecting…"),
+    ("tray.status.disconnected", "Not connected"),
+    ("tray.status.error", "Connection error"),
+    ("tray.reconnect", "Reconnect"),
+    ("tray.disconnect", "Disconnect"),
+    ("tray.quick_prompt", "New quick prompt…"),
+    ("tray.recent_sessions", "Recent sessions"),
+    ("tray.no_sessions", "No recent sessions"),
+    ("tray.untitled_session", "Untitled session"),
+    ("tray.show", "Show OpenCode Nexus"),
+    ("tray.quit", "Quit"),
+    ("notification.response_completed", "Response ready: {session}"),
+    ("notification.response_completed.body", "The response is complete."),
+    ("notification.response_failed", "Response failed: {session}"),
+    ("notification.response_failed.body", "The response could not be completed."),
+    ("notification.untitled_session", "Untitled session"),
+    ("connection.connecting", "Connecting to {host}:{port}…"),
+    ("connection.connected", "Connected to {server} (version {version})"),
+    ("connection.version_unknown", "unknown"),
+    ("connection.disconnected", "Disconnected from server"),
+    ("connection.restored", "Restored connection to {server}"),
+    ("connection.restore_failed", "Failed to restore connection: {message}"),
+    ("connection.health_ok", "Server health check passed"),
+    ("connection.health_failed", "Server health check failed"),
+    ("connection.reconnecting", "Reconnecting to {server} (attempt {attempt} of {max})…"),
+    ("connection.reconnected", "Reconnected to {server}"),
+    ("connection.reconnect_failed", "Gave up reconnecting to {server} after {attempts} attempts"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("error.network", "Error de red: {message}"),
+    ("error.server.bad_request", "Solicitud no válida: {message}"),
+    (
+        "error.server.unauthorized",
+        "Se requiere autenticación. Comprueba tu clave de API.",
+    ),
+    (
+        "error.server.forbidden",
+        "Acceso denegado. Verifica tus permisos.",
+    ),
+    ("error.server.not_found", "No encontrado: {message}"),
+    (
+        "error.server.rate_limited",
+        "Demasiadas solicitudes. Espera un momento e inténtalo de nuevo.",
+    ),
+    ("error.server.internal", "Error del servidor: {message}"),
+    (
+        "error.server.other",
+        "El servidor respondió con un error ({status}): {message}",
+    ),
+    ("error.auth", "Error de autenticación: {message}"),
+    ("error.validation", "{field} no válido: {message}"),
+    ("error.session", "Error de sesión: {message}"),
+    ("error.file", "Error de archivo: {message}"),
+    ("error.data", "Error de datos: {message}"),
+    ("error.parse", "Error de análisis: {message}"),
+    ("error.connection", "Error de conexión: {message}"),
+    ("error.io", "Error de E/S: {message}"),
+    ("error.not_connected", "Sin conexión: {message}"),
+    (
+        "error.timeout",
+        "{operation} agotó el tiempo de espera tras {seconds} segundos",
+    ),
+    ("error.not_initialized", "{component} no está inicializado"),
+    (
+        "error.queued",
+        "No se puede acceder al servidor. El mensaje se ha puesto en cola y se enviará cuando se recupere la conexión.",
+    ),
+    ("error.app_locked", "La aplicación está bloqueada. Introduce tu PIN para continuar."),
+    ("error.certificate_mismatch", "Advertencia de seguridad: el certificado presentado por {host} no coincide con la huella fijada. Se bloqueó la conexión."),
+    ("error.unsupported", "Este servidor no admite {feature} (requiere ≥ v{version})"),
+    ("error.secret_detected", "Este mensaje parece contener {count} secreto(s) ({kinds}). Elimínalos o confirma para enviarlo de todos modos."),
+    ("error.secret_blocked", "Este mensaje parece contener {count} secreto(s) ({kinds}) y no se envió."),
+    ("tray.status.connected", "Conectado a {server}"),
+    ("tray.status.connecting", "Conectando…"),
+    ("tray.status.disconnected", "Sin conexión"),
+    ("tray.status.error", "Error de conexión"),
+    ("tray.reconnect", "Reconectar"),
+    ("tray.disconnect", "Desconectar"),
+    ("tray.quick_prompt", "Nueva consulta rápida…"),
+    ("tray.recent_sessions", "Sesiones recientes"),
+    ("tray.no_sessions", "No hay sesiones recientes"),
+    ("tray.untitled_session", "Sesión sin título"),
+    ("tray.show", "Mostrar OpenCode Nexus"),
+    ("tray.quit", "Salir"),
+    ("notification.response_completed", "Respuesta lista: {session}"),
+    ("notification.response_completed.body", "La respuesta está completa."),
+    ("notification.response_failed", "Respuesta fallida: {session}"),
+    ("notification.response_failed.body", "No se pudo completar la respuesta."),
+    ("notification.untitled_session", "Sesión sin título"),
+    ("connection.connecting", "Conectando a {host}:{port}…"),
+    ("connection.connected", "Conectado a {server} (versión {version})"),
+    ("connection.version_unknown", "desconocida"),
+    ("connection.disconnected", "Desconectado del servidor"),
+    ("connection.restored", "Conexión restaurada con {server}"),
+    ("connection.restore_failed", "No se pudo restaurar la conexión: {message}"),
+    ("connection.health_ok", "El servidor respondió correctamente"),
+    ("connection.health_failed", "El servidor no respondió a la comprobación de estado"),
+    ("connection.reconnecting", "Reconectando a {server} (intento {attempt} de {max})…"),
+    ("connection.reconnected", "Reconectado a {server}"),
+    ("connection.reconnect_failed", "No se pudo reconectar a {server} tras {attempts} intentos"),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("error.network", "Erreur réseau : {message}"),
+    ("error.server.bad_request", "Requête invalide : {message}"),
+    (
+        "error.server.unauthorized",
+        "Authentification requise. Vérifiez votre clé API.",
+    ),
+    (
+        "error.server.forbidden",
+        "Accès refusé. Vérifiez vos autorisations.",
+    ),
+    ("error.server.not_found", "Introuvable : {message}"),
+    (
+        "error.server.rate_limited",
+        "Trop de requêtes. Patientez un instant puis réessayez.",
+    ),
+    ("error.server.internal", "Erreur du serveur : {message}"),
+    (
+        "error.server.other",
+        "Le serveur a répondu par une erreur ({status}) : {message}",
+    ),
+    ("error.auth", "Échec de l'authentification : {message}"),
+    ("error.validation", "{field} invalide : {message}"),
+    ("error.session", "Erreur de session : {message}"),
+    ("error.file", "Erreur de fichier : {message}"),
+    ("error.data", "Erreur de données : {message}"),
+    ("error.parse", "Erreur d'analyse : {message}"),
+    ("error.connection", "Erreur de connexion : {message}"),
+    ("error.io", "Erreur d'E/S : {message}"),
+    ("error.not_connected", "Non connecté : {message}"),
+    (
+        "error.timeout",
+        "{operation} a expiré après {seconds} secondes",
+    ),
+    ("error.not_initialized", "{component} n'est pas initialisé"),
+    (
+        "error.queued",
+        "Le serveur est injoignable. Le message a été mis en file d'attente et sera envoyé au rétablissement de la connexion.",
+    ),
+    ("error.app_locked", "L'application est verrouillée. Saisissez votre code PIN pour continuer."),
+    ("error.certificate_mismatch", "Alerte de sécurité : le certificat présenté par {host} ne correspond pas à l'empreinte épinglée. La connexion a été bloquée."),
+    ("error.unsupported", "{feature} n'est pas pris en charge par ce serveur (nécessite ≥ v{version})"),
+    ("error.secret_detected", "Ce message semble contenir {count} secret(s) ({kinds}). Supprimez-les ou confirmez pour l'envoyer quand même."),
+    ("error.secret_blocked", "Ce message semble contenir {count} secret(s) ({kinds}) et n'a pas été envoyé."),
+    ("tray.status.connected", "Connecté à {server}"),
+    ("tray.status.connecting", "Connexion…"),
+    ("tray.status.disconnected", "Non connecté"),
+    ("tray.status.error", "Erreur de connexion"),
+    ("tray.reconnect", "Se reconnecter"),
+    ("tray.disconnect", "Se déconnecter"),
+    ("tray.quick_prompt", "Nouvelle requête rapide…"),
+    ("tray.recent_sessions", "Sessions récentes"),
+    ("tray.no_sessions", "Aucune session récente"),
+    ("tray.untitled_session", "Session sans titre"),
+    ("tray.show", "Afficher OpenCode Nexus"),
+    ("tray.quit", "Quitter"),
+    ("notification.response_completed", "Réponse prête : {session}"),
+    ("notification.response_completed.body", "La réponse est terminée."),
+    ("notification.response_failed", "Échec de la réponse : {session}"),
+    ("notification.response_failed.body", "La réponse n'a pas pu être terminée."),
+    ("notification.untitled_session", "Session sans titre"),
+    ("connection.connecting", "Connexion à {host}:{port}…"),
+    ("connection.connected", "Connecté à {server} (version {version})"),
+    ("connection.version_unknown", "inconnue"),
+    ("connection.disconnected", "Déconnecté du serveur"),
+    ("connection.restored", "Connexion rétablie avec {server}"),
+    ("connection.restore_failed", "Impossible de rétablir la connexion : {message}"),
+    ("connection.health_ok", "Le serveur répond correctement"),
+    ("connection.health_failed", "Le serveur ne répond pas à la vérification d'état"),
+    ("connection.reconnecting", "Reconnexion à {server} (tentative {attempt} sur {max})…"),
+    ("connection.reconnected", "Reconnecté à {server}"),
+    ("connection.reconnect_failed", "Abandon de la reconnexion à {server} après {attempts} tentatives"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("error.network", "Netzwerkfehler: {message}"),
+    ("error.server.bad_request", "Ungültige Anfrage: {message}"),
+    (
+        "error.server.unauthorized",
+        "Authentifizierung erforderlich. Bitte überprüfe deinen API-Schlüssel.",
+    ),
+    (
+        "error.server.forbidden",
+        "Zugriff verweigert. Bitte überprüfe deine Berechtigungen.",
+    ),
+    ("error.server.not_found", "Nicht gefunden: {message}"),
+    (
+        "error.server.rate_limited",
+        "Zu viele Anfragen. Bitte warte einen Moment und versuche es erneut.",
+    ),
+    ("error.server.internal", "Serverfehler: {message}"),
+    (
+        "error.server.other",
+        "Der Server hat mit einem Fehler geantwortet ({status}): {message}",
+    ),
+    ("error.auth", "Authentifizierung fehlgeschlagen: {message}"),
+    ("error.validation", "Ungültiges Feld {field}: {message}"),
+    ("error.session", "Sitzungsfehler: {message}"),
+    ("error.file", "Dateifehler: {message}"),
+    ("error.data", "Datenfehler: {message}"),
+    ("error.parse", "Verarbeitungsfehler: {message}"),
+    ("error.connection", "Verbindungsfehler: {message}"),
+    ("error.io", "E/A-Fehler: {message}"),
+    ("error.not_connected", "Nicht verbunden: {message}"),
+    (
+        "error.timeout",
+        "{operation} hat nach {seconds} Sekunden das Zeitlimit überschritten",
+    ),
+    ("error.not_initialized", "{component} ist nicht initialisiert"),
+    (
+        "error.queued",
+        "Der Server ist nicht erreichbar. Die Nachricht wurde in die Warteschlange gestellt und wird gesendet, sobald die Verbindung wiederhergestellt ist.",
+    ),
+    ("error.app_locked", "Die App ist gesperrt. Gib deine PIN ein, um fortzufahren."),
+    ("error.certificate_mismatch", "Sicherheitswarnung: Das von {host} vorgelegte Zertifikat stimmt nicht mit dem hinterlegten Fingerabdruck überein. Die Verbindung wurde blockiert."),
+    ("error.unsupported", "{feature} wird von diesem Server nicht unterstützt (benötigt ≥ v{version})"),
+    ("error.secret_detected", "Diese Nachricht scheint {count} Geheimnis(se) zu enthalten ({kinds}). Entferne sie oder bestätige, um sie trotzdem zu senden."),
+    ("error.secret_blocked", "Diese Nachricht scheint {count} Geheimnis(se) zu enthalten ({kinds}) und wurde nicht gesendet."),
+    ("tray.status.connected", "Verbunden mit {server}"),
+    ("tray.status.connecting", "Verbinde…"),
+    ("tray.status.disconnected", "Nicht verbunden"),
+    ("tray.status.error", "Verbindungsfehler"),
+    ("tray.reconnect", "Neu verbinden"),
+    ("tray.disconnect", "Trennen"),
+    ("tray.quick_prompt", "Neue Schnellanfrage…"),
+    ("tray.recent_sessions", "Letzte Sitzungen"),
+    ("tray.no_sessions", "Keine letzten Sitzungen"),
+    ("tray.untitled_session", "Unbenannte Sitzung"),
+    ("tray.show", "OpenCode Nexus anzeigen"),
+    ("tray.quit", "Beenden"),
+    ("notification.response_completed", "Antwort fertig: {session}"),
+    ("notification.response_completed.body", "Die Antwort ist vollständig."),
+    ("notification.response_failed", "Antwort fehlgeschlagen: {session}"),
+    ("notification.response_failed.body", "Die Antwort konnte nicht abgeschlossen werden."),
+    ("notification.untitled_session", "Unbenannte Sitzung"),
+    ("connection.connecting", "Verbinde mit {host}:{port}…"),
+    ("connection.connected", "Verbunden mit {server} (Version {version})"),
+    ("connection.version_unknown", "unbekannt"),
+    ("connection.disconnected", "Vom Server getrennt"),
+    ("connection.restored", "Verbindung zu {server} wiederhergestellt"),
+    ("connection.restore_failed", "Verbindung konnte nicht wiederhergestellt werden: {message}"),
+    ("connection.health_ok", "Server-Statusprüfung erfolgreich"),
+    ("connection.health_failed", "Server-Statusprüfung fehlgeschlagen"),
+    ("connection.reconnecting", "Verbinde erneut mit {server} (Versuch {attempt} von {max})…"),
+    ("connection.reconnected", "Wieder verbunden mit {server}"),
+    ("connection.reconnect_failed", "Erneutes Verbinden mit {server} nach {attempts} Versuchen aufgegeben"),
+];
+
+fn catalog(locale: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    match locale {
+        "en" => Some(EN),
+        "es" => Some(ES),
+        "fr" => Some(FR),
+        "de" => Some(DE),
+        _ => None,
+    }
+}
+
+fn lookup(catalog: &[(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog
+        .iter()
+        .find(|(entry_key, _)| *entry_key == key)
+        .map(|(_, template)| *template)
+}
+
+/// Reduce a locale tag such as `es-MX` or `de_AT.UTF-8` to a supported
+/// language, or `None` if there is no catalog for it
+pub fn normalize_locale(locale: &str) -> Option<&'static str> {
+    let language = locale
+        .split(['-', '_', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    SUPPORTED_LOCALES
+        .iter()
+        .copied()
+        .find(|supported| *supported == language)
+}
+
+/// Switch the locale for subsequent messages; unsupported locales fall back to English
+pub fn set_locale(locale: &str) {
+    let locale = normalize_locale(locale)
+        .unwrap_or(DEFAULT_LOCALE)
+        .to_string();
+    match LOCALE.write() {
+        Ok(mut current) => *current = Some(locale),
+        Err(poisoned) => *poisoned.into_inner() = Some(locale),
+    }
+}
+
+/// Currently active locale
+pub fn current_locale() -> String {
+    let locale = match LOCALE.read() {
+        Ok(locale) => locale.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    locale.unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Render `key` in `locale`, falling back to English and then the key itself
+pub fn translate(locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let template = catalog(locale)
+        .and_then(|catalog| lookup(catalog, key))
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key);
+
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Render `key` in the active locale
+pub fn t(key: &str, args: &[(&str, &str)]) -> String {
+    translate(&current_locale(), key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_substitutes_arguments() {
+        assert_eq!(
+            translate(
+                "en",
+                "error.validation",
+                &[("field", "port"), ("message", "out of range")]
+            ),
+            "Invalid port: out of range"
+        );
+        assert_eq!(
+            translate("es", "error.network", &[("message", "timeout")]),
+            "Error de red: timeout"
+        );
+    }
+
+    #[test]
+    fn test_translate_falls_back_to_english() {
+        assert_eq!(
+            translate("ja", "error.session", &[("message", "gone")]),
+            "Session error: gone"
+        );
+        assert_eq!(translate("fr", "missing.key", &[]), "missing.key");
+    }
+
+    #[test]
+    fn test_catalogs_cover_every_english_key() {
+        for locale in SUPPORTED_LOCALES {
+            let catalog = catalog(locale).expect("Supported locale should have a catalog");
+            for (key, _) in EN {
+                assert!(
+                    lookup(catalog, key).is_some(),
+                    "{} is missing {}",
+                    locale,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_connection_messages_follow_locale() {
+        assert_eq!(
+            translate(
+                "de",
+                "connection.restored",
+                &[("server", "http://nas:4096")]
+            ),
+            "Verbindung zu http://nas:4096 wiederhergestellt"
+        );
+    }
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("es-MX"), Some("es"));
+        assert_eq!(normalize_locale("de_AT.UTF-8"), Some("de"));
+        assert_eq!(normalize_locale("FR"), Some("fr"));
+        assert_eq!(normalize_locale("ja"), None);
+    }
+}
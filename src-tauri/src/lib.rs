@@ -21,112 +21,248 @@
 // SOFTWARE.
 
 mod api_client;
-mod chat_client;
+mod app_lock;
+mod attachments;
+mod audit_log;
+mod backup;
+mod budgets;
+mod certificate_pinning;
+mod checkpoints;
+mod code_blocks;
+mod compatibility;
+mod config_profile;
 mod connection_manager;
+mod connection_transfer;
+mod context_upload;
+mod context_usage;
+mod conversation_import;
+mod diagnostics;
 mod error;
+mod error_reporting;
+mod error_stats;
 mod event_bridge;
+mod file_changes;
+mod headless;
+mod health_history;
+mod i18n;
+mod log_forwarding;
+mod log_query;
+mod log_stream;
+mod logging;
+mod message_feedback;
+mod message_journal;
+mod messaging;
+mod metrics;
+mod migrations;
 mod model_manager;
+mod notifications;
+mod outbox;
+mod pagination;
+mod plugins;
+mod prefetch;
+mod problem_report;
+mod profile_vault;
+mod project_context;
+mod prompt_library;
+mod provider_auth;
+mod proxy;
+mod quick_prompt;
+mod recovery_journal;
+mod resource_usage;
+mod secret_scan;
+mod secrets;
+mod server_config;
+mod server_files;
 mod session_manager;
+mod session_windows;
+mod settings;
+mod setup;
+mod shell_approval;
+mod slash_commands;
+mod startup_profile;
 mod streaming_client;
+mod subsystems;
+mod support_bundle;
+mod telemetry;
+mod terminal;
+mod transcription;
+mod tray;
+mod updater;
+mod workspace;
 
 use api_client::{ApiClient, ModelConfig};
-use chat_client::{ChatClient, ChatEvent};
-use connection_manager::{ConnectionManager, ConnectionStatus, ServerConnection};
+use app_lock::{AppLock, AppLockStatus};
+use attachments::ValidatedAttachment;
+use audit_log::{AuditAction, AuditLog, AuditLogReport};
+use backup::BackupSummary;
+use budgets::{BudgetScope, BudgetStatus, BudgetTracker};
+use checkpoints::Checkpoint;
+use code_blocks::{ApplyMode, CodeBlock, CodeBlockCache};
+use compatibility::{Compatibility, FeatureMatrix, FeatureSupport, ServerFeature};
+use config_profile::{ConfigProfile, ConfigProfileSummary};
+use connection_manager::{
+    ActiveConnectionInfo, ConnectionEvent, ConnectionEventType, ConnectionManager,
+    ConnectionProfile, ConnectionStatus, Reachability, ServerConnection, ServerEvent,
+};
+use connection_transfer::{
+    ConflictResolution, ConnectionImportSummary, ConnectionsExport, ConnectionsExportSummary,
+};
+use context_upload::{ContextFile, UploadProgress};
+use context_usage::ContextUsage;
+use conversation_import::ConversationImportSummary;
+use diagnostics::SessionDiagnostics;
+use error::CommandError;
+use error_stats::{ErrorStats, ErrorSummary, ErrorSummaryEntry, SummaryPeriod};
 use event_bridge::{AppEvent, EventBridge};
+use file_changes::FileChange;
+use health_history::{HealthCheck, HealthHistory, UptimeStats};
+use log_query::{LogQuery, LogQueryResult};
+use log_stream::LogStreamer;
+use message_feedback::{FeedbackStore, MessageFeedback, Rating};
+use messaging::{Messaging, MessagingService};
 use model_manager::{ModelManager, ModelPreferences};
+use notifications::NotificationKind;
+use outbox::{Outbox, OutboxDelivery, OutboxEvent, OutboxItem, OutboxStatus};
+use pagination::{Page, PageRequest};
+use plugins::{PluginEvent, PluginHost, PluginInfo, PluginPermission};
+use prefetch::{PrefetchCache, PrefetchProgress, PREFETCH_EVENT};
+use problem_report::ProblemReport;
+use profile_vault::{ProfileKey, ProfileVault};
+use project_context::ProjectScan;
+use prompt_library::{Prompt, PromptImportSummary, PromptInput, PromptLibrary};
+use provider_auth::{ProviderAuthStatus, ProviderCredentials};
+use proxy::{ProxySettings, ProxyTestResult};
+use recovery_journal::{JournalRecord, RecoveredWork, RecoveryJournal};
+use resource_usage::{Activity, ResourceUsage};
+use secret_scan::SecretFinding;
+use secrets::{SecretBackend, SecretRotation, SecretStore, SecretStoreStatus};
+use server_config::{ServerConfig, ServerConfigReport};
+use server_files::{FileNode, FileSearchBatch, SearchHit, ServerFile, ServerFileCache};
 use session_manager::{
-    ChatMessage, ChatSession, CreateSessionRequest, SendMessageRequest, SessionManager,
+    ChatMessage, ChatSession, CreateSessionRequest, MessagePart, MessageRole, SendMessageRequest,
+    SessionManager, SessionSummary,
+};
+use session_windows::{SessionWindow, SessionWindows};
+use settings::{
+    AppLockSettings, AppSettings, NotificationSettings, PluginGrant, PrivacySettings,
+    QuickPromptSettings, RemoteLogSettings, RetrySettings, SecretScanMode, SettingsManager,
+};
+use setup::{SetupMethod, SetupState};
+use shell_approval::{ApprovalDecision, PendingApprovals, RiskLevel, ShellApprovalRequest};
+use slash_commands::{
+    BuiltinCommand, ServerCommand, SlashCommandInfo, SlashCommandResult, SlashRoute,
 };
+use startup_profile::{StartupProfiler, StartupReport, StartupStep};
 use streaming_client::{StreamEvent, StreamRequest, StreamingClient};
-
-use chrono::Utc;
-use serde::Deserialize;
-use std::fs::OpenOptions;
-use std::io::Write;
+use subsystems::{Subsystem, SubsystemHealth, SubsystemRegistry, SubsystemStatus};
+use support_bundle::SupportBundleInput;
+use telemetry::{Feature, Telemetry, TelemetryReport};
+use terminal::{TerminalInfo, Terminals};
+use transcription::{AudioClip, AudioSource, TranscriptionProgress};
+#[cfg(desktop)]
+use tray::TrayAction;
+use tray::{RecentSessions, TraySession, TrayStatus};
+use updater::{DownloadedUpdate, UpdateChannel, UpdateInfo, UpdateProgress};
+use workspace::{Workspace, WorkspaceStore};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 use tauri::{Emitter, Manager};
 use tokio::sync::Mutex as AsyncMutex;
+use tracing::{debug, error, info, warn};
+
+/// How often queued messages are retried while the server stays unreachable
+const OUTBOX_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the idle timeout of the app lock is checked
+const APP_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(15);
 
 // Managed state for singletons
-pub struct ApiClientState(pub Arc<AsyncMutex<Option<ApiClient>>>);
+pub struct ApiClientState(pub Arc<AsyncMutex<Option<Arc<ApiClient>>>>);
+/// Clients for servers other than the one the shared client points at, by
+/// server URL, so requests to several connected servers don't share a URL
+pub struct ServerClientsState(pub AsyncMutex<HashMap<String, Arc<ApiClient>>>);
 pub struct SessionManagerState(pub Arc<AsyncMutex<Option<SessionManager>>>);
 pub struct ModelManagerState(pub Arc<AsyncMutex<Option<ModelManager>>>);
 pub struct StreamingClientState(pub Arc<AsyncMutex<Option<StreamingClient>>>);
 pub struct EventBridgeState(pub Arc<AsyncMutex<Option<EventBridge>>>);
 pub struct ConnectionManagerState(pub Arc<AsyncMutex<Option<ConnectionManager>>>);
+pub struct SettingsState(pub Arc<AsyncMutex<Option<SettingsManager>>>);
+pub struct LogStreamerState(pub Arc<AsyncMutex<Option<LogStreamer>>>);
+pub struct RecoveryJournalState(pub Arc<AsyncMutex<Option<RecoveryJournal>>>);
+pub struct OutboxState(pub Arc<AsyncMutex<Option<Outbox>>>);
+pub struct WorkspaceState(pub Arc<AsyncMutex<Option<WorkspaceStore>>>);
 
-// Legacy state for backward compatibility
-pub struct ChatClientState(pub Arc<AsyncMutex<Option<ChatClient>>>);
+/// Saved prompts; `None` until the profile is unlocked
+pub struct PromptLibraryState(pub Arc<AsyncMutex<Option<PromptLibrary>>>);
 
-// Custom panic hook for crash reporting
-pub fn setup_panic_hook() {
-    let default_hook = std::panic::take_hook();
-    std::panic::set_hook(Box::new(move |panic_info| {
-        let panic_message = format!("Application panicked: {:?}", panic_info);
-        eprintln!("{}", panic_message);
-        log_to_file(&panic_message);
+/// Message ratings; `None` until the profile is unlocked
+pub struct FeedbackState(pub Arc<AsyncMutex<Option<FeedbackStore>>>);
 
-        // Call the default hook to maintain normal panic behavior
-        default_hook(panic_info);
-    }));
-}
+/// Recorded spending and per-session budgets; `None` when the config
+/// directory is unknown
+pub struct BudgetState(pub Arc<AsyncMutex<Option<BudgetTracker>>>);
+pub struct SubsystemRegistryState(pub SubsystemRegistry);
 
-// Logging utility function
-pub fn log_to_file(message: &str) {
-    if let Some(config_dir) = dirs::config_dir() {
-        let log_path = config_dir.join("opencode-nexus").join("application.log");
+/// How long each startup step took
+pub struct StartupProfilerState(pub StartupProfiler);
 
-        // Create directory if it doesn't exist
-        if let Some(parent) = log_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
+pub struct AppLockState(pub AppLock);
 
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
-        let log_entry = format!("[{}] {}\n", timestamp, message);
+/// Sessions offered in the tray's "Recent sessions" menu
+pub struct TrayState(pub RecentSessions);
 
-        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_path) {
-            let _ = file.write_all(log_entry.as_bytes());
-            let _ = file.flush();
-        }
-    }
-}
+/// Which session each window shows
+pub struct SessionWindowState(pub SessionWindows);
 
-// Enhanced logging macros that log to both console and file
-#[macro_export]
-macro_rules! log_info {
-    ($($arg:tt)*) => {
-        let message = format!("[INFO] {}", format!($($arg)*));
-        println!("{}", message);
-        log_to_file(&message);
-    };
-}
+/// Code blocks extracted from messages, by block id
+pub struct CodeBlockState(pub CodeBlockCache);
 
-#[macro_export]
-macro_rules! log_error {
-    ($($arg:tt)*) => {
-        let message = format!("[ERROR] {}", format!($($arg)*));
-        eprintln!("{}", message);
-        log_to_file(&message);
-    };
+/// Recently browsed server directories and files
+pub struct ServerFileState(pub ServerFileCache);
+
+/// Models, sessions and commands fetched right after connecting
+pub struct PrefetchState(pub PrefetchCache);
+
+/// Features the connected server supports
+pub struct CompatibilityState(pub Compatibility);
+
+/// Bash commands waiting for the user's approval
+pub struct ShellApprovalState(pub PendingApprovals);
+
+/// Shells opened from the app
+pub struct TerminalState(pub Terminals);
+
+/// Installed plugins; `None` when the config directory is unknown
+pub struct PluginState(pub Option<PluginHost>);
+
+/// Downloaded and verified update waiting for "restart to update"
+pub struct UpdaterState(pub Arc<AsyncMutex<Option<DownloadedUpdate>>>);
+
+/// Key of the unlocked encrypted profile, and whether startup may proceed
+pub struct ProfileState {
+    pub key: AsyncMutex<Option<ProfileKey>>,
+    pub unlocked: tokio::sync::watch::Sender<bool>,
 }
 
-#[macro_export]
-macro_rules! log_warn {
-    ($($arg:tt)*) => {
-        let message = format!("[WARN] {}", format!($($arg)*));
-        println!("{}", message);
-        log_to_file(&message);
-    };
+/// Run the command line interface when started with `--headless`, or
+/// `None` so the caller goes on to start the UI
+pub fn run_headless() -> Option<std::process::ExitCode> {
+    let args: Vec<String> = std::env::args().collect();
+    headless::is_headless(&args).then(|| headless::run(&args))
 }
 
-#[macro_export]
-macro_rules! log_debug {
-    ($($arg:tt)*) => {
-        let message = format!("[DEBUG] {}", format!($($arg)*));
-        println!("{}", message);
-        log_to_file(&message);
-    };
+// Custom panic hook for crash reporting
+pub fn setup_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        error!(target: "panic", "Application panicked: {:?}", panic_info);
+
+        // Call the default hook to maintain normal panic behavior
+        default_hook(panic_info);
+    }));
 }
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -151,40 +287,96 @@ fn greet(name: &str) -> String {
 // ============================================================================
 
 // Helper functions
-fn get_config_dir() -> Result<std::path::PathBuf, String> {
+fn get_config_dir() -> Result<std::path::PathBuf, CommandError> {
     dirs::config_dir()
         .map(|dir| dir.join("opencode-nexus"))
-        .ok_or_else(|| "Could not determine config directory".to_string())
+        .ok_or_else(|| CommandError::file_system("Could not determine config directory"))
+}
+
+/// Connection failure, reported as a certificate mismatch when the host
+/// failed its pinned fingerprint
+fn connection_error(hostname: &str, message: String) -> CommandError {
+    match certificate_pinning::mismatch_for(hostname) {
+        Some(mismatch) => error::AppError::from(mismatch).into(),
+        None => CommandError::connection(message),
+    }
+}
+
+fn secret_store() -> Result<SecretStore, CommandError> {
+    Ok(SecretStore::new(&get_config_dir()?))
 }
 
-fn get_server_url() -> Result<String, String> {
+/// Run secret store work on the blocking pool, since keychain access waits
+/// on the platform tool's child process
+async fn with_secret_store<T, F>(work: F) -> Result<T, CommandError>
+where
+    T: Send + 'static,
+    F: FnOnce(&SecretStore) -> Result<T, error::AppError> + Send + 'static,
+{
     let config_dir = get_config_dir()?;
-    let mut connection_manager = ConnectionManager::new(config_dir, None)
-        .map_err(|e| format!("Failed to create connection manager: {}", e))?;
-    connection_manager
-        .load_connections()
-        .map_err(|e| format!("Failed to load connections: {}", e))?;
-    connection_manager
-        .get_last_used_server_url()
-        .ok_or_else(|| "No server URL available".to_string())
+    tauri::async_runtime::spawn_blocking(move || work(&SecretStore::new(&config_dir)))
+        .await
+        .map_err(|e| CommandError::internal(format!("Secret store task failed: {}", e)))?
+        .map_err(CommandError::from)
+}
+
+/// Move an API key stored under the connection's pre-`host:port` id
+async fn migrate_connection_secret(hostname: &str, connection_id: &str) {
+    let legacy_ids = secrets::legacy_connection_ids(hostname);
+    let id = connection_id.to_string();
+    let migrated =
+        with_secret_store(move |store| store.migrate_connection_api_key(&id, &legacy_ids)).await;
+    match migrated {
+        Ok(true) => {
+            info!(target: "secrets", connection = %connection_id, "Migrated connection API key")
+        }
+        Ok(false) => {}
+        Err(e) => {
+            warn!(target: "secrets", connection = %connection_id, "Failed to migrate connection API key: {}", e)
+        }
+    }
+}
+
+/// Create a connection manager with the saved connections loaded. Refused
+/// while the encrypted profile is sealed: the connections file is not
+/// restored yet, so the manager would start empty and overwrite it on the
+/// next save.
+async fn load_connection_manager(
+    config_dir: std::path::PathBuf,
+    app_handle: Option<tauri::AppHandle>,
+    profile_unlocked: bool,
+) -> Result<ConnectionManager, CommandError> {
+    if !profile_unlocked {
+        return Err(CommandError::validation(
+            "Unlock the encrypted profile before using saved connections",
+        ));
+    }
+    let mut manager = ConnectionManager::new(config_dir, app_handle).map_err(|e| {
+        CommandError::internal(format!("Failed to create connection manager: {}", e))
+    })?;
+
+    if let Err(e) = manager.load_connections().await {
+        warn!(target: "init", "Failed to load connections: {}", e);
+    }
+    Ok(manager)
 }
 
 /// Helper to get or create the ConnectionManager from managed state
 async fn get_connection_manager<'a>(
     state: &'a tauri::State<'a, ConnectionManagerState>,
     app_handle: Option<tauri::AppHandle>,
-) -> Result<tokio::sync::MutexGuard<'a, Option<ConnectionManager>>, String> {
+) -> Result<tokio::sync::MutexGuard<'a, Option<ConnectionManager>>, CommandError> {
     let mut guard = state.0.lock().await;
 
     // Initialize connection manager if not already created
     if guard.is_none() {
-        let config_dir = get_config_dir()?;
-        let mut manager = ConnectionManager::new(config_dir, app_handle)
-            .map_err(|e| format!("Failed to create connection manager: {}", e))?;
-
-        // Load saved connections
-        if let Err(e) = manager.load_connections() {
-            log_warn!("⚠️ [INIT] Failed to load connections: {}", e);
+        let profile_unlocked = app_handle
+            .as_ref()
+            .is_none_or(|app_handle| *app_handle.state::<ProfileState>().unlocked.borrow());
+        let manager =
+            load_connection_manager(get_config_dir()?, app_handle, profile_unlocked).await?;
+        for connection in manager.get_saved_connections() {
+            migrate_connection_secret(&connection.hostname, &connection.id()).await;
         }
 
         *guard = Some(manager);
@@ -193,12 +385,150 @@ async fn get_connection_manager<'a>(
     Ok(guard)
 }
 
-/// Ensure a server connection exists before executing chat commands
-/// Returns a user-friendly error message if no connection is available
-fn ensure_server_connected() -> Result<String, String> {
-    get_server_url().map_err(|_| {
-        "Please connect to an OpenCode server first. Use the Connection settings to add a server.".to_string()
-    })
+/// The server chat commands talk to: the live connection's, or else the
+/// last one used. Errors with a user-friendly message when there is none.
+fn connected_server_url(manager: Option<&ConnectionManager>) -> Result<String, CommandError> {
+    manager
+        .and_then(|manager| {
+            manager
+                .get_server_url()
+                .or_else(|| manager.get_last_used_server_url())
+        })
+        .ok_or_else(|| {
+            CommandError::not_connected(
+                "Please connect to an OpenCode server first. Use the Connection settings to add a server.",
+            )
+        })
+}
+
+/// Ensure a server connection exists before executing chat commands, using
+/// the managed connection manager rather than re-reading saved connections
+async fn ensure_server_connected(app_handle: &tauri::AppHandle) -> Result<String, CommandError> {
+    let state = app_handle.state::<ConnectionManagerState>();
+    let guard = get_connection_manager(&state, Some(app_handle.clone())).await?;
+    connected_server_url(guard.as_ref())
+}
+
+/// The API client every command shares, so requests reuse one connection
+/// pool. Created on first use if startup has not made it yet.
+async fn shared_api_client(app_handle: &tauri::AppHandle) -> Result<Arc<ApiClient>, CommandError> {
+    let state = app_handle.state::<ApiClientState>();
+    let mut guard = state.0.lock().await;
+    if let Some(api_client) = guard.as_ref() {
+        return Ok(api_client.clone());
+    }
+    let api_client = Arc::new(ApiClient::new()?);
+    *guard = Some(api_client.clone());
+    Ok(api_client)
+}
+
+/// The API client for `server_url`: the shared one when it points there,
+/// or has yet to point anywhere, else that server's own
+async fn api_client_for(
+    app_handle: &tauri::AppHandle,
+    server_url: String,
+) -> Result<Arc<ApiClient>, CommandError> {
+    let shared = shared_api_client(app_handle).await?;
+    match shared.get_server_url().await {
+        Some(url) if url == server_url => return Ok(shared),
+        Some(_) => {}
+        None => {
+            shared.set_server_url(server_url.clone()).await?;
+            attach_connection_secret(&shared, &server_url).await;
+            return Ok(shared);
+        }
+    }
+
+    let state = app_handle.state::<ServerClientsState>();
+    let mut clients = state.0.lock().await;
+    if let Some(api_client) = clients.get(&server_url) {
+        return Ok(api_client.clone());
+    }
+    let api_client = Arc::new(ApiClient::new()?);
+    api_client.set_server_url(server_url.clone()).await?;
+    attach_connection_secret(&api_client, &server_url).await;
+    clients.insert(server_url, api_client.clone());
+    Ok(api_client)
+}
+
+/// The server a command talks to: `connection_id`'s when it names one,
+/// which must be connected, or else the primary connection's
+async fn resolve_server_url(
+    app_handle: &tauri::AppHandle,
+    connection_id: Option<&str>,
+) -> Result<String, CommandError> {
+    let Some(connection_id) = connection_id else {
+        return ensure_server_connected(app_handle).await;
+    };
+    let state = app_handle.state::<ConnectionManagerState>();
+    let guard = get_connection_manager(&state, Some(app_handle.clone())).await?;
+    guard
+        .as_ref()
+        .and_then(|manager| manager.server_url_for(connection_id))
+        .ok_or_else(|| CommandError::not_connected(format!("Not connected to {}", connection_id)))
+}
+
+/// Send the API key stored for `server_url`'s connection with every
+/// request, or no key when none is stored
+async fn attach_connection_secret(api_client: &ApiClient, server_url: &str) {
+    let key = match connection_manager::connection_id_for_url(server_url) {
+        Some(connection_id) => {
+            let name = secrets::connection_api_key(&connection_id);
+            with_secret_store(move |store| store.get(&name)).await
+        }
+        None => Ok(None),
+    };
+    match key {
+        Ok(Some(key)) => api_client.set_api_key(key).await,
+        Ok(None) => api_client.clear_api_key().await,
+        Err(e) => {
+            warn!(target: "secrets", connection = %server_url, "Failed to read connection API key: {}", e);
+            api_client.clear_api_key().await;
+        }
+    }
+}
+
+/// Pick up a changed API key when `connection_id` is the server commands
+/// talk to
+async fn refresh_connection_secret(app_handle: &tauri::AppHandle, connection_id: &str) {
+    let Ok(server_url) = ensure_server_connected(app_handle).await else {
+        return;
+    };
+    if connection_manager::connection_id_for_url(&server_url).as_deref() != Some(connection_id) {
+        return;
+    }
+    match api_client_for(app_handle, server_url.clone()).await {
+        Ok(api_client) => attach_connection_secret(&api_client, &server_url).await,
+        Err(e) => warn!(target: "secrets", "Failed to refresh connection API key: {}", e),
+    }
+}
+
+/// The shared API client, pointed at the active server
+async fn connected_api_client(
+    app_handle: &tauri::AppHandle,
+) -> Result<Arc<ApiClient>, CommandError> {
+    let server_url = ensure_server_connected(app_handle).await?;
+    api_client_for(app_handle, server_url).await
+}
+
+/// The connected server's feature matrix, failing early with "needs ≥
+/// vX.Y" when the server predates `feature`
+fn require_feature(
+    app_handle: &tauri::AppHandle,
+    feature: ServerFeature,
+) -> Result<Compatibility, CommandError> {
+    let compatibility = app_handle.state::<CompatibilityState>().0.clone();
+    compatibility.require(feature)?;
+    Ok(compatibility)
+}
+
+/// Append to the crash recovery journal; failures only cost recoverability
+fn journal_record(journal: &Option<RecoveryJournal>, record: &JournalRecord) {
+    if let Some(journal) = journal {
+        if let Err(e) = journal.record(record) {
+            warn!(target: "recovery", "Failed to write recovery journal: {}", e);
+        }
+    }
 }
 
 // Connection management commands
@@ -210,16 +540,16 @@ async fn connect_to_server(
     api_key: Option<String>,
     method: String,
     _name: String,
-) -> Result<String, String> {
-    log_info!(
-        "🔗 [CONNECTION] Connecting to server: {} (method: {})",
-        server_url,
-        method
-    );
+) -> Result<String, CommandError> {
+    info!(target: "connection", connection = %server_url, method = %method, "Connecting to server");
 
     // Parse the server URL to extract components
-    let url = url::Url::parse(&server_url).map_err(|e| format!("Invalid server URL: {}", e))?;
-    let hostname = url.host_str().ok_or("No hostname in URL")?.to_string();
+    let url = url::Url::parse(&server_url)
+        .map_err(|e| CommandError::validation(format!("Invalid server URL: {}", e)))?;
+    let hostname = url
+        .host_str()
+        .ok_or_else(|| CommandError::validation("No hostname in URL"))?
+        .to_string();
     let port = url
         .port()
         .unwrap_or(if url.scheme() == "https" { 443 } else { 4096 });
@@ -229,48 +559,138 @@ async fn connect_to_server(
         get_connection_manager(&state, Some(app_handle.clone())).await?;
     let connection_manager = connection_manager_guard
         .as_mut()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
 
     // Connect to the server
     connection_manager
         .connect_to_server(&hostname, port, secure)
-        .await?;
+        .await
+        .map_err(|e| connection_error(&hostname, e))?;
+
+    // The id the connection's secrets are stored under
+    let connection_id = format!("{}:{}", hostname, port);
+    migrate_connection_secret(&hostname, &connection_id).await;
+
+    if let Some(key) = api_key.filter(|key| !key.is_empty()) {
+        let name = secrets::connection_api_key(&connection_id);
+        let backend = with_secret_store(move |store| store.set(&name, &key)).await?;
+        info!(target: "connection", connection = %server_url, ?backend, "Stored API key");
+    }
 
-    // TODO: Store API key securely if provided
-    if let Some(key) = &api_key {
-        log_info!("🔐 [CONNECTION] API key provided (length: {})", key.len());
-        // Store in secure storage (keychain/keystore)
-        // For now, just log that we received it
+    if let Some(url) = connection_manager.get_server_url() {
+        let api_client = api_client_for(&app_handle, url.clone()).await?;
+        // Reconnecting to the same server keeps the client's old key
+        attach_connection_secret(&api_client, &url).await;
+        detect_server_features(&app_handle, &api_client).await;
+        spawn_prefetch(&app_handle, url);
     }
 
-    log_info!("✅ [CONNECTION] Successfully connected to: {}", server_url);
+    info!(target: "connection", connection = %server_url, "Successfully connected");
+    audit_log::record(
+        AuditAction::ConnectionChanged,
+        &connection_id,
+        Some(format!("connected to {}", server_url)),
+    );
 
-    // Return a connection ID (could be UUID or hash of server_url)
-    let connection_id = format!("{}-{}", method, hostname);
     Ok(connection_id)
 }
 
+/// Build the feature matrix from what the server reports about itself.
+/// Older servers have no `/info`, so fall back to the version in `/health`.
+async fn detect_server_features(app_handle: &tauri::AppHandle, api_client: &ApiClient) {
+    let info = api_client.get_server_info().await.ok();
+    let matrix = match info {
+        Some(info) => FeatureMatrix::new(Some(&info.version), info.features.as_deref()),
+        None => match api_client.get_health().await {
+            Ok(health) => FeatureMatrix::new(health.version.as_deref(), None),
+            Err(e) => {
+                warn!(target: "connection", "Failed to detect server version: {}", e);
+                FeatureMatrix::default()
+            }
+        },
+    };
+    debug!(target: "connection", version = ?matrix.server_version(), "Detected server features");
+    app_handle.state::<CompatibilityState>().0.set(matrix);
+}
+
+/// Fetch what the first chat interaction needs in parallel, in the
+/// background, reporting each fetch as it finishes
+fn spawn_prefetch(app_handle: &tauri::AppHandle, server_url: String) {
+    let cache = app_handle.state::<PrefetchState>().0.clone();
+    cache.begin(&server_url);
+    let app_handle = app_handle.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let report = |progress: Option<PrefetchProgress>| {
+            if let Some(progress) = progress {
+                debug!(target: "connection", prefetch = ?progress.target, completed = progress.completed, "Prefetch finished");
+                if let Err(e) = app_handle.emit(PREFETCH_EVENT, &progress) {
+                    warn!(target: "connection", "Failed to emit prefetch progress: {}", e);
+                }
+            }
+        };
+
+        let models = async {
+            let result = match api_client_for(&app_handle, server_url.clone()).await {
+                Ok(api_client) => api_client
+                    .get_available_models()
+                    .await
+                    .map(|models| {
+                        models
+                            .into_iter()
+                            .map(|m| serde_json::to_value(m).unwrap_or_default())
+                            .collect()
+                    })
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            report(cache.finish_models(&server_url, result));
+        };
+        let sessions = async {
+            let result = match messaging(&app_handle).await {
+                Ok(messaging) => messaging.list_sessions().await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            report(cache.finish_sessions(&server_url, result));
+        };
+        let capabilities = async {
+            let result = match api_client_for(&app_handle, server_url.clone()).await {
+                Ok(api_client) => api_client.list_commands().await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            report(cache.finish_capabilities(&server_url, result));
+        };
+
+        tokio::join!(models, sessions, capabilities);
+    });
+}
+
 #[tauri::command]
 async fn test_server_connection(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, ConnectionManagerState>,
     server_url: String,
     #[allow(unused_variables)] api_key: Option<String>,
-) -> Result<bool, String> {
-    log_info!("🧪 [CONNECTION] Testing connection to: {}", server_url);
+) -> Result<bool, CommandError> {
+    info!(target: "connection", connection = %server_url, "Testing connection");
     // Note: API key will be used for HMAC signing in future implementation
 
     // Parse the server URL to extract components
-    let url = url::Url::parse(&server_url).map_err(|e| format!("Invalid server URL: {}", e))?;
-    let hostname = url.host_str().ok_or("No hostname in URL")?.to_string();
+    let url = url::Url::parse(&server_url)
+        .map_err(|e| CommandError::validation(format!("Invalid server URL: {}", e)))?;
+    let hostname = url
+        .host_str()
+        .ok_or_else(|| CommandError::validation("No hostname in URL"))?
+        .to_string();
     let port = url
         .port()
         .unwrap_or(if url.scheme() == "https" { 443 } else { 4096 });
     let secure = url.scheme() == "https";
 
-    let connection_manager_guard = get_connection_manager(&state, None).await?;
+    let connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
     let connection_manager = connection_manager_guard
         .as_ref()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
 
     // Test the connection
     match connection_manager
@@ -278,12 +698,12 @@ async fn test_server_connection(
         .await
     {
         Ok(_server_info) => {
-            log_info!("✅ [CONNECTION] Test successful: {}", server_url);
+            info!(target: "connection", connection = %server_url, "Test successful");
             Ok(true)
         }
         Err(e) => {
-            log_error!("❌ [CONNECTION] Test failed: {}", e);
-            Err(e)
+            error!(target: "connection", connection = %server_url, "Test failed: {}", e);
+            Err(connection_error(&hostname, e))
         }
     }
 }
@@ -292,11 +712,11 @@ async fn test_server_connection(
 async fn get_connection_status(
     state: tauri::State<'_, ConnectionManagerState>,
     app_handle: tauri::AppHandle,
-) -> Result<ConnectionStatus, String> {
+) -> Result<ConnectionStatus, CommandError> {
     let connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
     let connection_manager = connection_manager_guard
         .as_ref()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
     Ok(connection_manager.get_connection_status())
 }
 
@@ -304,11 +724,11 @@ async fn get_connection_status(
 async fn get_current_connection(
     state: tauri::State<'_, ConnectionManagerState>,
     app_handle: tauri::AppHandle,
-) -> Result<Option<ServerConnection>, String> {
+) -> Result<Option<ServerConnection>, CommandError> {
     let connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
     let connection_manager = connection_manager_guard
         .as_ref()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
     Ok(connection_manager.get_current_connection())
 }
 
@@ -316,109 +736,444 @@ async fn get_current_connection(
 async fn disconnect_from_server(
     state: tauri::State<'_, ConnectionManagerState>,
     app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let mut connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
+    connection_id: Option<String>,
+) -> Result<(), CommandError> {
+    let mut connection_manager_guard =
+        get_connection_manager(&state, Some(app_handle.clone())).await?;
     let connection_manager = connection_manager_guard
         .as_mut()
-        .ok_or("Connection manager not initialized")?;
-    connection_manager.disconnect_from_server().await
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+    let primary_url = connection_manager.get_server_url();
+    let server_url = match connection_id.as_deref() {
+        Some(connection_id) => connection_manager.server_url_for(connection_id),
+        None => primary_url.clone(),
+    };
+    match connection_id.as_deref() {
+        Some(connection_id) => connection_manager.disconnect(connection_id).await,
+        None => connection_manager.disconnect_from_server().await,
+    }
+    .map_err(CommandError::connection)?;
+    let new_primary_url = connection_manager.get_server_url();
+    drop(connection_manager_guard);
+
+    if let Some(server_url) = &server_url {
+        app_handle
+            .state::<ServerClientsState>()
+            .0
+            .lock()
+            .await
+            .remove(server_url);
+    }
+    if new_primary_url != primary_url {
+        primary_changed(&app_handle, new_primary_url).await;
+    }
+    if let Some(server_url) = server_url {
+        audit_log::record(
+            AuditAction::ConnectionChanged,
+            &server_url,
+            Some("disconnected".to_string()),
+        );
+    }
+    Ok(())
+}
+
+/// Reset what was fetched for the old primary connection and fetch it for
+/// the new one, if any
+async fn primary_changed(app_handle: &tauri::AppHandle, server_url: Option<String>) {
+    app_handle.state::<PrefetchState>().0.clear();
+    app_handle.state::<CompatibilityState>().0.clear();
+    let Some(server_url) = server_url else {
+        return;
+    };
+    match api_client_for(app_handle, server_url.clone()).await {
+        Ok(api_client) => detect_server_features(app_handle, &api_client).await,
+        Err(e) => warn!(target: "connection", "Failed to switch API client: {}", e),
+    }
+    spawn_prefetch(app_handle, server_url);
+}
+
+/// Servers connected at the same time, and which one is primary
+#[tauri::command]
+async fn get_active_connections(
+    state: tauri::State<'_, ConnectionManagerState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<ActiveConnectionInfo>, CommandError> {
+    let connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
+    let connection_manager = connection_manager_guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+    Ok(connection_manager.active_connections())
+}
+
+/// Make another connected server the one commands use when they don't
+/// name a connection
+#[tauri::command]
+async fn set_primary_connection(
+    state: tauri::State<'_, ConnectionManagerState>,
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+) -> Result<(), CommandError> {
+    let server_url = {
+        let connection_manager_guard =
+            get_connection_manager(&state, Some(app_handle.clone())).await?;
+        let connection_manager = connection_manager_guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+        if connection_manager.get_server_url() == connection_manager.server_url_for(&connection_id)
+        {
+            return Ok(());
+        }
+        connection_manager
+            .set_primary(&connection_id)
+            .map_err(CommandError::not_connected)?;
+        connection_manager.get_server_url()
+    };
+    info!(target: "connection", connection = %connection_id, "Switched primary connection");
+    primary_changed(&app_handle, server_url).await;
+    Ok(())
+}
+
+/// Which optional features the connected server supports, so the UI can
+/// hide what it can't do
+#[tauri::command]
+async fn get_server_features(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<FeatureSupport>, CommandError> {
+    ensure_server_connected(&app_handle).await?;
+    Ok(app_handle
+        .state::<CompatibilityState>()
+        .0
+        .matrix()
+        .summary())
 }
 
 #[tauri::command]
 async fn get_saved_connections(
     state: tauri::State<'_, ConnectionManagerState>,
     app_handle: tauri::AppHandle,
-) -> Result<Vec<ServerConnection>, String> {
+) -> Result<Vec<ServerConnection>, CommandError> {
     let mut connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
     let connection_manager = connection_manager_guard
         .as_mut()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
     connection_manager
         .load_connections()
-        .map_err(|e| e.to_string())?;
+        .await
+        .map_err(CommandError::file_system)?;
     Ok(connection_manager.get_saved_connections())
 }
 
+/// Probe every saved server concurrently, for the connection picker
+#[tauri::command]
+async fn test_all_saved_connections(
+    state: tauri::State<'_, ConnectionManagerState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<Reachability>, CommandError> {
+    // Probe a clone so the manager isn't locked while servers answer
+    let connection_manager = get_connection_manager(&state, Some(app_handle))
+        .await?
+        .clone()
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+    let results = connection_manager.test_all_saved_connections().await;
+    info!(
+        target: "connection",
+        reachable = results.iter().filter(|r| r.reachable).count(),
+        total = results.len(),
+        "Probed saved connections"
+    );
+    Ok(results)
+}
+
+/// What the first-run wizard should show, including any OpenCode server
+/// detected on this machine
+#[tauri::command]
+async fn get_setup_state(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<SetupState, CommandError> {
+    let setup = {
+        let guard = settings_state.0.lock().await;
+        guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Settings"))?
+            .get()
+            .setup
+    };
+    let has_saved_connections = ensure_server_connected(&app_handle).await.is_ok();
+    let local_servers = setup::detect_local_servers().await;
+    debug!(target: "connection", detected = local_servers.len(), "Probed for local servers");
+    Ok(SetupState::new(
+        &setup,
+        has_saved_connections,
+        local_servers,
+    ))
+}
+
+/// Finish the wizard once a connection has been saved with
+/// `connect_to_server`
+#[tauri::command]
+async fn complete_setup(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    method: SetupMethod,
+    server_url: Option<String>,
+) -> Result<SetupState, CommandError> {
+    if let Some(url) = &server_url {
+        setup::validate_server_url(method, url).map_err(CommandError::validation)?;
+    }
+    ensure_server_connected(&app_handle)
+        .await
+        .map_err(|_| CommandError::validation("Connect to a server before finishing setup"))?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| {
+            s.setup.completed = true;
+            s.setup.method = Some(method);
+            s.setup.completed_at = Some(chrono::Utc::now());
+        })
+        .map_err(|e| {
+            CommandError::file_system("Failed to save setup state").with_details(e.to_string())
+        })?;
+    info!(target: "connection", method = ?method, "First-run setup completed");
+    Ok(SetupState::new(&updated.setup, true, Vec::new()))
+}
+
+#[tauri::command]
+async fn set_connection_certificate_pin(
+    state: tauri::State<'_, ConnectionManagerState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+    fingerprint: Option<String>,
+) -> Result<ServerConnection, CommandError> {
+    let mut connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
+    let connection_manager = connection_manager_guard
+        .as_mut()
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+    let connection = connection_manager
+        .set_certificate_pin(&name, fingerprint.as_deref())
+        .await
+        .map_err(CommandError::validation)?;
+    info!(
+        target: "security",
+        connection = %name,
+        pinned = connection.certificate_fingerprint.is_some(),
+        "Updated certificate pin"
+    );
+    audit_log::record(
+        AuditAction::ConnectionChanged,
+        &name,
+        Some(match &connection.certificate_fingerprint {
+            Some(fingerprint) => format!("pinned certificate {}", fingerprint),
+            None => "removed certificate pin".to_string(),
+        }),
+    );
+    Ok(connection)
+}
+
+/// Request settings of a saved connection
+#[tauri::command]
+async fn get_connection_profile(
+    state: tauri::State<'_, ConnectionManagerState>,
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+) -> Result<ConnectionProfile, CommandError> {
+    let connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
+    connection_manager_guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?
+        .connection_profile(&connection_id)
+        .ok_or_else(|| CommandError::validation(format!("Connection not found: {}", connection_id)))
+}
+
+/// Replace the request settings of a saved connection. Clients already
+/// talking to it use them from their next request.
+#[tauri::command]
+async fn update_connection_profile(
+    state: tauri::State<'_, ConnectionManagerState>,
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    profile: ConnectionProfile,
+) -> Result<ConnectionProfile, CommandError> {
+    let mut connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
+    let connection_manager = connection_manager_guard
+        .as_mut()
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+    let profile = connection_manager
+        .update_connection_profile(&connection_id, profile)
+        .await
+        .map_err(CommandError::validation)?;
+    audit_log::record(
+        AuditAction::ConnectionChanged,
+        &connection_id,
+        Some("updated profile".to_string()),
+    );
+    Ok(profile)
+}
+
+/// Fingerprint of the certificate a server presents now, so the user can
+/// confirm it and pin it
+#[tauri::command]
+async fn get_server_certificate_fingerprint(server_url: String) -> Result<String, CommandError> {
+    let url = url::Url::parse(&server_url)
+        .map_err(|e| CommandError::validation(format!("Invalid server URL: {}", e)))?;
+    if url.scheme() != "https" {
+        return Err(CommandError::validation(
+            "Certificate fingerprints are only available for HTTPS servers",
+        ));
+    }
+    let hostname = url
+        .host_str()
+        .ok_or_else(|| CommandError::validation("No hostname in URL"))?
+        .to_string();
+
+    let client = proxy::apply(certificate_pinning::client_builder())
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| CommandError::internal(format!("Failed to create HTTP client: {}", e)))?;
+    // The handshake records the certificate even when it is not trusted
+    if let Err(e) = client.get(url).send().await {
+        debug!(target: "security", host = %hostname, "Fingerprint probe failed: {}", e);
+    }
+
+    certificate_pinning::observed_fingerprint(&hostname).ok_or_else(|| {
+        CommandError::connection(format!("No certificate received from {}", hostname))
+    })
+}
+
 #[tauri::command]
 async fn save_connection(
     state: tauri::State<'_, ConnectionManagerState>,
     app_handle: tauri::AppHandle,
     connection: ServerConnection,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let mut connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
     let connection_manager = connection_manager_guard
         .as_mut()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
     connection_manager
         .load_connections()
-        .map_err(|e| e.to_string())?;
-    connection_manager.save_connection(connection)
+        .await
+        .map_err(CommandError::file_system)?;
+    let name = connection.name.clone();
+    connection_manager
+        .save_connection(connection)
+        .await
+        .map_err(CommandError::file_system)?;
+    audit_log::record(
+        AuditAction::ConnectionChanged,
+        &name,
+        Some("saved".to_string()),
+    );
+    Ok(())
 }
 
 #[tauri::command]
 async fn get_last_used_connection(
     state: tauri::State<'_, ConnectionManagerState>,
     app_handle: tauri::AppHandle,
-) -> Result<Option<ServerConnection>, String> {
+) -> Result<Option<ServerConnection>, CommandError> {
     let mut connection_manager_guard = get_connection_manager(&state, Some(app_handle)).await?;
     let connection_manager = connection_manager_guard
         .as_mut()
-        .ok_or("Connection manager not initialized")?;
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
     connection_manager
         .load_connections()
-        .map_err(|e| e.to_string())?;
+        .await
+        .map_err(CommandError::file_system)?;
     Ok(connection_manager.get_last_used_connection())
 }
 #[tauri::command]
-async fn get_application_logs() -> Result<Vec<String>, String> {
-    log_info!("📋 [LOGS] Getting application logs...");
+async fn get_application_logs(
+    lines: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<String>, CommandError> {
+    info!(target: "logs", "Getting application logs...");
 
-    let config_dir = dirs::config_dir()
-        .ok_or("Could not determine config directory")?
-        .join("opencode-nexus");
+    let config_dir = get_config_dir()?;
 
-    let log_path = config_dir.join("application.log");
+    let log_path = logging::active_log_path(&config_dir);
 
     if !log_path.exists() {
-        log_info!("📋 [LOGS] No log file found, returning empty logs");
+        info!(target: "logs", "No log file found, returning empty logs");
         return Ok(Vec::new());
     }
 
-    match std::fs::read_to_string(&log_path) {
-        Ok(content) => {
-            let logs: Vec<String> = content.lines().map(|line| line.to_string()).collect();
-
-            log_info!("📋 [LOGS] Retrieved {} log entries", logs.len());
+    // Only the requested tail is read so large histories stay cheap over IPC;
+    // `offset` skips the newest lines the caller already has, to load more
+    let lines = lines.unwrap_or(logging::DEFAULT_TAIL_LINES);
+    match logging::read_tail(&log_path, lines, offset.unwrap_or(0)) {
+        Ok(logs) => {
+            info!(target: "logs", "Retrieved {} log entries", logs.len());
             Ok(logs)
         }
         Err(e) => {
-            log_error!("❌ [LOGS] Failed to read log file: {}", e);
-            Err(format!("Failed to read log file: {}", e))
+            error!(target: "logs", "Failed to read log file: {}", e);
+            Err(CommandError::file_system("Failed to read log file").with_details(e.to_string()))
         }
     }
 }
 
+#[tauri::command]
+async fn query_logs(query: Option<LogQuery>) -> Result<LogQueryResult, CommandError> {
+    let config_dir = get_config_dir()?;
+    let query = query.unwrap_or_default();
+
+    let result = log_query::query_logs(&config_dir, &query).map_err(|e| {
+        CommandError::file_system("Failed to query logs").with_details(e.to_string())
+    })?;
+
+    debug!(target: "logs", "Log query returned {} entries", result.entries.len());
+    Ok(result)
+}
+
+#[tauri::command]
+async fn subscribe_to_logs(
+    log_streamer_state: tauri::State<'_, LogStreamerState>,
+    filter: Option<LogQuery>,
+) -> Result<String, CommandError> {
+    let guard = log_streamer_state.0.lock().await;
+    let streamer = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Log streaming"))?;
+    Ok(streamer.subscribe(filter.unwrap_or_default()))
+}
+
+#[tauri::command]
+async fn unsubscribe_from_logs(
+    log_streamer_state: tauri::State<'_, LogStreamerState>,
+    subscription_id: String,
+) -> Result<bool, CommandError> {
+    let guard = log_streamer_state.0.lock().await;
+    let streamer = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Log streaming"))?;
+    Ok(streamer.unsubscribe(&subscription_id))
+}
+
 #[tauri::command]
 async fn log_frontend_error(
     level: String,
     message: String,
     details: Option<String>,
-) -> Result<(), String> {
-    let details_str = details.map_or_else(|| String::new(), |d| format!(" | Details: {}", d));
-    let full_message = format!("🌐 [FRONTEND] {}{}", message, details_str);
+) -> Result<(), CommandError> {
+    let details = details.unwrap_or_default();
 
     match level.to_lowercase().as_str() {
         "error" => {
-            log_error!("{}", full_message);
+            error!(target: "frontend", details = %details, "{}", message);
         }
         "warn" => {
-            log_warn!("{}", full_message);
+            warn!(target: "frontend", details = %details, "{}", message);
         }
         "info" => {
-            log_info!("{}", full_message);
+            info!(target: "frontend", details = %details, "{}", message);
         }
         _ => {
-            log_debug!("{}", full_message);
+            debug!(target: "frontend", details = %details, "{}", message);
         }
     }
     Ok(())
@@ -426,436 +1181,5383 @@ async fn log_frontend_error(
 
 // Chat/Session management commands
 
-// Helper to get or create the ChatClient from managed state
-async fn get_chat_client<'a>(
-    state: &'a tauri::State<'a, ChatClientState>,
-) -> Result<tokio::sync::MutexGuard<'a, Option<ChatClient>>, String> {
-    let mut guard = state.0.lock().await;
-
-    // Initialize client if not already created
-    if guard.is_none() {
-        let config_dir = get_config_dir()?;
-        let client = ChatClient::new(config_dir)
-            .map_err(|e| format!("Failed to create chat client: {}", e))?;
-        *guard = Some(client);
-    }
+/// The messaging pipeline for the active server, keeping the local
+/// session store in step with what passes through it
+async fn messaging(app_handle: &tauri::AppHandle) -> Result<Messaging, CommandError> {
+    messaging_for(app_handle, None).await
+}
 
-    Ok(guard)
+/// Messaging with the server of `connection_id`, or the primary connection
+async fn messaging_for(
+    app_handle: &tauri::AppHandle,
+    connection_id: Option<&str>,
+) -> Result<Messaging, CommandError> {
+    let server_url = resolve_server_url(app_handle, connection_id).await?;
+    let api_client = api_client_for(app_handle, server_url).await?;
+    let store = app_handle.state::<SessionManagerState>().0.clone();
+    Ok(Messaging::new(api_client, store))
 }
 
+/// List sessions for the sidebar. Only summaries are returned; a session's
+/// messages are fetched with `get_session_messages` when it is opened.
 #[tauri::command]
 async fn list_sessions(
-    state: tauri::State<'_, ChatClientState>,
-) -> Result<Vec<serde_json::Value>, String> {
-    log_info!("📥 [CHAT] Listing sessions");
-
-    let guard = get_chat_client(&state).await?;
-    let client = guard
-        .as_ref()
-        .ok_or_else(|| "Chat client not initialized".to_string())?;
+    app_handle: tauri::AppHandle,
+    tray_state: tauri::State<'_, TrayState>,
+    prefetch_state: tauri::State<'_, PrefetchState>,
+    page: Option<PageRequest>,
+    connection_id: Option<String>,
+) -> Result<Page<SessionSummary>, CommandError> {
+    info!(target: "chat", connection = ?connection_id, "Listing sessions");
+
+    // Prefetching and the tray only cover the primary connection
+    if connection_id.is_some() {
+        let sessions = messaging_for(&app_handle, connection_id.as_deref())
+            .await?
+            .list_sessions()
+            .await?;
+        return Ok(pagination::paginate(sessions, page));
+    }
 
-    let sessions = client.list_sessions().await.map_err(|e| e.to_string())?;
+    let sessions = match prefetch_state.0.take_sessions() {
+        Some(sessions) => sessions,
+        None => messaging(&app_handle).await?.list_sessions().await?,
+    };
+    tray_state.0.replace(
+        sessions
+            .iter()
+            .map(|session| TraySession {
+                id: session.id.clone(),
+                title: session.title.clone(),
+            })
+            .collect(),
+    );
+    refresh_tray(&app_handle).await;
 
-    let sessions_json = serde_json::to_value(&sessions)
-        .map_err(|e| format!("Failed to serialize sessions: {}", e))?;
-    Ok(sessions_json.as_array().cloned().unwrap_or_default())
+    Ok(pagination::paginate(sessions, page))
 }
 
 #[tauri::command]
 async fn create_session(
-    state: tauri::State<'_, ChatClientState>,
+    app_handle: tauri::AppHandle,
+    tray_state: tauri::State<'_, TrayState>,
+    workspace_state: tauri::State<'_, WorkspaceState>,
     title: Option<String>,
-) -> Result<serde_json::Value, String> {
-    log_info!("📝 [CHAT] Creating session: {:?}", title);
-
-    let guard = get_chat_client(&state).await?;
-    let client = guard
-        .as_ref()
-        .ok_or_else(|| "Chat client not initialized".to_string())?;
+    connection_id: Option<String>,
+) -> Result<serde_json::Value, CommandError> {
+    info!(target: "chat", connection = ?connection_id, "Creating session: {:?}", title);
 
-    let session = client
+    let session = messaging_for(&app_handle, connection_id.as_deref())
+        .await?
         .create_session(title)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let session_json = serde_json::to_value(&session)
-        .map_err(|e| format!("Failed to serialize session: {}", e))?;
-    Ok(session_json)
+        .await?;
+    app_handle.state::<PrefetchState>().0.forget_sessions();
+    // New conversations land in whichever workspace is open
+    if let Some(workspaces) = workspace_state.0.lock().await.as_ref() {
+        if let Some(active) = workspaces.active() {
+            if let Err(e) = workspaces.add_session(&active.id, &session.id) {
+                warn!(target: "session", "Failed to add session to workspace: {}", e);
+            }
+        }
+    }
+    tray_state.0.touch(TraySession {
+        id: session.id.clone(),
+        title: session.title.clone(),
+    });
+    refresh_tray(&app_handle).await;
+    telemetry::record(Feature::SessionCreated);
+    dispatch_plugin_event(
+        &app_handle,
+        PluginEvent::SessionCreated,
+        serde_json::json!({ "session_id": session.id, "title": session.title }),
+    );
+
+    let session_json = serde_json::to_value(&session).map_err(|e| {
+        CommandError::data("Failed to serialize session").with_details(e.to_string())
+    })?;
+    Ok(session_json)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn send_message(
-    state: tauri::State<'_, ChatClientState>,
+    app_handle: tauri::AppHandle,
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+    outbox_state: tauri::State<'_, OutboxState>,
+    settings_state: tauri::State<'_, SettingsState>,
     session_id: String,
     content: String,
-) -> Result<serde_json::Value, String> {
-    log_info!("💬 [CHAT] Sending message to session: {}", session_id);
+    allow_secrets: Option<bool>,
+    connection_id: Option<String>,
+) -> Result<serde_json::Value, CommandError> {
+    info!(target: "chat", session_id = %session_id, "Sending message");
 
     // Input validation
     if session_id.trim().is_empty() {
-        return Err("Session ID cannot be empty".to_string());
+        return Err(CommandError::validation("Session ID cannot be empty"));
     }
     let trimmed_content = content.trim();
     if trimmed_content.is_empty() {
-        return Err("Message content cannot be empty".to_string());
+        return Err(CommandError::validation("Message content cannot be empty"));
     }
     if content.len() > 100_000 {
-        return Err("Message content exceeds maximum length (100KB)".to_string());
+        return Err(CommandError::validation(
+            "Message content exceeds maximum length (100KB)",
+        ));
+    }
+    check_outgoing_secrets(
+        &settings_state,
+        trimmed_content,
+        allow_secrets.unwrap_or(false),
+    )
+    .await?;
+    ensure_within_budget(&app_handle, &session_id).await?;
+
+    let journal = journal_state.0.lock().await.clone();
+    let result = match messaging_for(&app_handle, connection_id.as_deref()).await {
+        Ok(messaging) => deliver_message(&messaging, &journal, &session_id, trimmed_content).await,
+        Err(e) => Err(e),
+    };
+    if result.is_ok() {
+        telemetry::record(Feature::MessageSent);
+        tokio::spawn({
+            let app_handle = app_handle.clone();
+            let session_id = session_id.clone();
+            async move {
+                emit_context_usage(&app_handle, &session_id).await;
+                track_spending(&app_handle, &session_id).await;
+            }
+        });
     }
 
-    let guard = get_chat_client(&state).await?;
-    let client = guard
-        .as_ref()
-        .ok_or_else(|| "Chat client not initialized".to_string())?;
+    // The outbox delivers to the primary connection, so only its messages wait there
+    match result {
+        Err(e) if e.is_unreachable() && connection_id.is_none() => Err(queue_in_outbox(
+            &app_handle,
+            &outbox_state,
+            &session_id,
+            trimmed_content,
+            OutboxDelivery::Message,
+            None,
+            e,
+        )
+        .await),
+        result => result,
+    }
+}
 
-    let message = client
-        .send_message(&session_id, trimmed_content)
-        .await
-        .map_err(|e| e.to_string())?;
+/// Hold back a prompt that looks like it contains secrets, as configured by
+/// the secret scanning setting. In confirm mode `allow_secrets` sends it
+/// anyway once the user has agreed.
+async fn check_outgoing_secrets(
+    settings_state: &tauri::State<'_, SettingsState>,
+    content: &str,
+    allow_secrets: bool,
+) -> Result<(), CommandError> {
+    check_secret_findings(settings_state, allow_secrets, || {
+        secret_scan::scan_text("message", content)
+    })
+    .await
+}
+
+/// Hold outgoing content when `scan` finds secrets, unless scanning is off
+/// or the user confirmed sending anyway
+async fn check_secret_findings(
+    settings_state: &tauri::State<'_, SettingsState>,
+    allow_secrets: bool,
+    scan: impl FnOnce() -> Vec<SecretFinding>,
+) -> Result<(), CommandError> {
+    let mode = match settings_state.0.lock().await.as_ref() {
+        Some(settings) => settings.get().privacy.secret_scanning,
+        None => SecretScanMode::Off,
+    };
+    if mode == SecretScanMode::Off || (mode == SecretScanMode::Confirm && allow_secrets) {
+        return Ok(());
+    }
+
+    let findings = scan();
+    if findings.is_empty() {
+        return Ok(());
+    }
+    let mut kinds: Vec<String> = findings
+        .iter()
+        .filter_map(|finding| serde_json::to_value(finding.kind).ok())
+        .filter_map(|kind| kind.as_str().map(str::to_string))
+        .collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+
+    warn!(target: "chat", findings = findings.len(), ?mode, "Outgoing message held by secret scanning");
+    let key = match mode {
+        SecretScanMode::Block => "error.secret_blocked",
+        _ => "error.secret_detected",
+    };
+    let message = i18n::t(
+        key,
+        &[
+            ("count", &findings.len().to_string()),
+            ("kinds", &kinds.join(", ")),
+        ],
+    );
+    Err(CommandError::new(error::ErrorCode::SecretDetected, message)
+        .with_details(serde_json::to_string(&findings).unwrap_or_default()))
+}
+
+/// Send a message, journaling it while in flight
+async fn deliver_message(
+    client: &impl MessagingService,
+    journal: &Option<RecoveryJournal>,
+    session_id: &str,
+    content: &str,
+) -> Result<serde_json::Value, CommandError> {
+    // Journal the prompt so it can be offered again if the app dies mid-send
+    let send_id = uuid::Uuid::new_v4().to_string();
+    journal_record(
+        journal,
+        &JournalRecord::PendingSend {
+            id: send_id.clone(),
+            session_id: session_id.to_string(),
+            content: content.to_string(),
+            created_at: chrono::Utc::now(),
+        },
+    );
+
+    let result = client.send_message(session_id, content).await;
+    journal_record(journal, &JournalRecord::SendFinished { id: send_id });
+    let message = result?;
 
-    let message_json = serde_json::to_value(&message)
-        .map_err(|e| format!("Failed to serialize message: {}", e))?;
+    let message_json = serde_json::to_value(&message).map_err(|e| {
+        CommandError::data("Failed to serialize message").with_details(e.to_string())
+    })?;
     Ok(message_json)
 }
 
+/// Park a prompt in the outbox after the server proved unreachable. Returns
+/// a `Queued` error, or the original error if the prompt could not be queued.
+async fn queue_in_outbox(
+    app_handle: &tauri::AppHandle,
+    outbox_state: &tauri::State<'_, OutboxState>,
+    session_id: &str,
+    content: &str,
+    delivery: OutboxDelivery,
+    model_config: Option<ModelConfig>,
+    cause: CommandError,
+) -> CommandError {
+    let guard = outbox_state.0.lock().await;
+    let Some(outbox) = guard.as_ref() else {
+        return cause;
+    };
+
+    match outbox.enqueue(session_id, content, delivery, model_config) {
+        Ok(item) => {
+            info!(target: "outbox", session_id = %session_id, outbox_id = %item.id, "Server unreachable, message queued");
+            emit_outbox_event(app_handle, item.clone(), None);
+            CommandError::new(error::ErrorCode::Queued, i18n::t("error.queued", &[]))
+                .with_details(item.id)
+        }
+        Err(e) => {
+            error!(target: "outbox", session_id = %session_id, "Failed to queue message: {}", e);
+            cause
+        }
+    }
+}
+
+fn emit_outbox_event(app_handle: &tauri::AppHandle, item: OutboxItem, stream_id: Option<String>) {
+    if let Err(e) = app_handle.emit(outbox::OUTBOX_EVENT, OutboxEvent { item, stream_id }) {
+        warn!(target: "outbox", "Failed to emit outbox event: {}", e);
+    }
+}
+
+/// Deliver queued prompts in order. Stops at the first unreachable error so
+/// the remaining items keep their place in the queue.
+async fn flush_outbox(app_handle: tauri::AppHandle) {
+    let Some(outbox) = app_handle.state::<OutboxState>().0.lock().await.clone() else {
+        return;
+    };
+    if outbox.pending().is_empty() || !outbox.try_begin_flush() {
+        return;
+    }
+    let journal = app_handle
+        .state::<RecoveryJournalState>()
+        .0
+        .lock()
+        .await
+        .clone();
+
+    for item in outbox.pending() {
+        if let Err(e) = outbox.mark_sending(&item.id) {
+            warn!(target: "outbox", "Failed to update outbox: {}", e);
+        }
+
+        let result = match item.delivery {
+            OutboxDelivery::Message => match messaging(&app_handle).await {
+                Ok(messaging) => {
+                    deliver_message(&messaging, &journal, &item.session_id, &item.content)
+                        .await
+                        .map(|_| None)
+                }
+                Err(e) => Err(e),
+            },
+            OutboxDelivery::Stream => spawn_message_stream(
+                app_handle.clone(),
+                journal.clone(),
+                item.session_id.clone(),
+                item.content.clone(),
+                item.model_config.clone(),
+                false,
+            )
+            .await
+            .map(Some),
+        };
+
+        let (updated, stream_id, stop) = match result {
+            Ok(stream_id) => {
+                info!(target: "outbox", session_id = %item.session_id, outbox_id = %item.id, "Delivered queued message");
+                (outbox.mark_delivered(&item.id), stream_id, false)
+            }
+            Err(e) if e.is_unreachable() => (outbox.mark_pending(&item.id, &e.message), None, true),
+            Err(e) => {
+                warn!(target: "outbox", outbox_id = %item.id, "Queued message failed: {}", e);
+                (outbox.mark_failed(&item.id, &e.message), None, false)
+            }
+        };
+
+        match updated {
+            Ok(Some(updated)) => emit_outbox_event(&app_handle, updated, stream_id),
+            Ok(None) => {}
+            Err(e) => warn!(target: "outbox", "Failed to update outbox: {}", e),
+        }
+        if stop {
+            break;
+        }
+    }
+
+    outbox.end_flush();
+}
+
+/// Flush the outbox on every reconnect and periodically while items wait
+async fn run_outbox_delivery(
+    app_handle: tauri::AppHandle,
+    mut connection_events: Option<tokio::sync::broadcast::Receiver<ConnectionEvent>>,
+) {
+    let mut interval = tokio::time::interval(OUTBOX_RETRY_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            event = async {
+                match connection_events.as_mut() {
+                    Some(receiver) => receiver.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => match event {
+                Ok(ConnectionEvent { event_type: ConnectionEventType::Connected, .. }) => {}
+                Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    connection_events = None;
+                    continue;
+                }
+            },
+        }
+
+        flush_outbox(app_handle.clone()).await;
+    }
+}
+
 #[tauri::command]
-async fn get_session_messages(
-    state: tauri::State<'_, ChatClientState>,
+async fn list_workspaces(
+    workspace_state: tauri::State<'_, WorkspaceState>,
+) -> Result<Vec<Workspace>, CommandError> {
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    Ok(workspaces.list())
+}
+
+#[tauri::command]
+async fn get_active_workspace(
+    workspace_state: tauri::State<'_, WorkspaceState>,
+) -> Result<Option<Workspace>, CommandError> {
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    Ok(workspaces.active())
+}
+
+/// Register a project directory, optionally with its own server
+#[tauri::command]
+async fn create_workspace(
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    name: String,
+    directory: String,
+    server_url: Option<String>,
+) -> Result<Workspace, CommandError> {
+    if let Some(url) = &server_url {
+        url::Url::parse(url).map_err(|e| {
+            CommandError::validation("Invalid workspace server URL").with_details(e.to_string())
+        })?;
+    }
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    let workspace = workspaces.create(&name, std::path::Path::new(&directory), server_url)?;
+    info!(
+        target: "session",
+        workspace_id = %workspace.id,
+        directory = %workspace.directory.display(),
+        "Created workspace"
+    );
+    Ok(workspace)
+}
+
+/// Make a workspace active so new sessions are filed under it. The
+/// frontend connects to `server_url` when the workspace has one.
+#[tauri::command]
+async fn open_workspace(
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    workspace_id: String,
+) -> Result<Workspace, CommandError> {
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    let workspace = workspaces.open(&workspace_id)?;
+    info!(target: "session", workspace_id = %workspace.id, "Opened workspace");
+    Ok(workspace)
+}
+
+/// Forget a workspace; its sessions are kept on the server
+#[tauri::command]
+async fn delete_workspace(
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    workspace_id: String,
+) -> Result<(), CommandError> {
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    workspaces.delete(&workspace_id)?;
+    info!(target: "session", workspace_id = %workspace_id, "Deleted workspace");
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_session_to_workspace(
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    workspace_id: String,
+    session_id: String,
+) -> Result<Workspace, CommandError> {
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    Ok(workspaces.add_session(&workspace_id, &session_id)?)
+}
+
+#[tauri::command]
+async fn remove_session_from_workspace(
+    workspace_state: tauri::State<'_, WorkspaceState>,
     session_id: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    log_info!("📜 [CHAT] Getting messages for session: {}", session_id);
+) -> Result<(), CommandError> {
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    Ok(workspaces.remove_session(&session_id)?)
+}
 
-    let guard = get_chat_client(&state).await?;
-    let client = guard
+#[tauri::command]
+async fn list_prompts(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    tag: Option<String>,
+) -> Result<Vec<Prompt>, CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
         .as_ref()
-        .ok_or_else(|| "Chat client not initialized".to_string())?;
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    Ok(prompts.list(tag.as_deref()))
+}
 
-    let messages = client
-        .get_session_messages(&session_id)
-        .await
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn create_prompt(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    prompt: PromptInput,
+) -> Result<Prompt, CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    let prompt = prompts.create(prompt)?;
+    info!(target: "chat", prompt_id = %prompt.id, "Created prompt");
+    Ok(prompt)
+}
 
-    let messages_json: Vec<serde_json::Value> = messages
-        .into_iter()
-        .filter_map(|m| serde_json::to_value(&m).ok())
-        .collect();
-    Ok(messages_json)
+#[tauri::command]
+async fn update_prompt(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    prompt_id: String,
+    prompt: PromptInput,
+) -> Result<Prompt, CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    Ok(prompts.update(&prompt_id, prompt)?)
 }
 
 #[tauri::command]
-async fn subscribe_to_chat_events(
-    state: tauri::State<'_, ChatClientState>,
-) -> Result<String, String> {
-    log_info!("🎧 [CHAT] Subscribing to chat events");
+async fn delete_prompt(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    prompt_id: String,
+) -> Result<(), CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    prompts.delete(&prompt_id)?;
+    info!(target: "chat", prompt_id = %prompt_id, "Deleted prompt");
+    Ok(())
+}
 
-    // Ensure client is initialized
-    let _guard = get_chat_client(&state).await?;
+/// A saved prompt with its variables filled in, for previewing
+#[tauri::command]
+async fn render_prompt(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    prompt_id: String,
+    variables: HashMap<String, String>,
+) -> Result<String, CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    let prompt = prompts
+        .get(&prompt_id)
+        .ok_or_else(|| CommandError::validation(format!("Prompt not found: {}", prompt_id)))?;
+    Ok(prompt_library::render(&prompt.content, &variables)?)
+}
 
-    // Return subscription channel identifier
-    Ok("chat_events".to_string())
+/// Fill in a saved prompt and stream it to a session like a typed message
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn send_prompt(
+    app_handle: tauri::AppHandle,
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+    outbox_state: tauri::State<'_, OutboxState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    session_id: String,
+    prompt_id: String,
+    variables: HashMap<String, String>,
+    model_config: Option<ModelConfig>,
+    allow_secrets: Option<bool>,
+) -> Result<String, CommandError> {
+    let content = render_prompt(prompt_library_state, prompt_id, variables).await?;
+    start_message_stream(
+        app_handle,
+        journal_state,
+        outbox_state,
+        settings_state,
+        session_id,
+        content,
+        model_config,
+        allow_secrets,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn export_prompts(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    path: String,
+) -> Result<usize, CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    let count = prompts.export(std::path::Path::new(&path))?;
+    info!(target: "chat", path = %path, count, "Exported prompts");
+    Ok(count)
+}
+
+#[tauri::command]
+async fn import_prompts(
+    prompt_library_state: tauri::State<'_, PromptLibraryState>,
+    path: String,
+) -> Result<PromptImportSummary, CommandError> {
+    let guard = prompt_library_state.0.lock().await;
+    let prompts = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Prompt library"))?;
+    let summary = prompts.import(std::path::Path::new(&path))?;
+    info!(target: "chat", path = %path, ?summary, "Imported prompts");
+    Ok(summary)
 }
 
+/// Bring in history from a ChatGPT or Claude export, either the archive
+/// or its `conversations.json`. Conversations imported before are skipped.
 #[tauri::command]
-async fn clear_application_logs() -> Result<(), String> {
-    log_info!("🗑️ [LOGS] Clearing application logs...");
+async fn import_conversations(
+    session_manager_state: tauri::State<'_, SessionManagerState>,
+    path: String,
+) -> Result<ConversationImportSummary, CommandError> {
+    let export_path = std::path::PathBuf::from(&path);
+    let (sessions, skipped) = tauri::async_runtime::spawn_blocking(move || {
+        conversation_import::read_export(&export_path)
+    })
+    .await
+    .map_err(|e| CommandError::internal(format!("Import task failed: {}", e)))??;
 
-    let config_dir = dirs::config_dir()
-        .ok_or("Could not determine config directory")?
-        .join("opencode-nexus");
+    let guard = session_manager_state.0.lock().await;
+    let session_manager = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Session manager"))?;
+    let mut summary = conversation_import::import_sessions(session_manager, sessions).await?;
+    summary.skipped = skipped;
+    info!(target: "chat", path = %path, ?summary, "Imported conversations");
+    Ok(summary)
+}
 
-    let log_path = config_dir.join("application.log");
+#[tauri::command]
+async fn get_outbox(
+    outbox_state: tauri::State<'_, OutboxState>,
+) -> Result<Vec<OutboxItem>, CommandError> {
+    let guard = outbox_state.0.lock().await;
+    let outbox = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Outbox"))?;
+    Ok(outbox.list())
+}
 
-    if log_path.exists() {
-        std::fs::remove_file(&log_path).map_err(|e| format!("Failed to clear log file: {}", e))?;
+/// Retry failed items and flush the queue now
+#[tauri::command]
+async fn retry_outbox(
+    app_handle: tauri::AppHandle,
+    outbox_state: tauri::State<'_, OutboxState>,
+) -> Result<(), CommandError> {
+    {
+        let guard = outbox_state.0.lock().await;
+        let outbox = guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Outbox"))?;
+        for item in outbox.list() {
+            if item.status == OutboxStatus::Failed {
+                let last_error = item.last_error.unwrap_or_default();
+                outbox.mark_pending(&item.id, &last_error)?;
+            }
+        }
     }
 
-    log_info!("✅ [LOGS] Application logs cleared successfully");
+    flush_outbox(app_handle).await;
     Ok(())
 }
 
-// Model configuration commands
 #[tauri::command]
-async fn get_available_models() -> Result<Vec<serde_json::Value>, String> {
-    log_info!("🤖 [MODELS] Getting available models...");
+async fn remove_outbox_item(
+    outbox_state: tauri::State<'_, OutboxState>,
+    outbox_id: String,
+) -> Result<bool, CommandError> {
+    let guard = outbox_state.0.lock().await;
+    let outbox = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Outbox"))?;
+    Ok(outbox.remove(&outbox_id)?.is_some())
+}
 
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let model_manager = ModelManager::new(api_client, config_dir);
+#[tauri::command]
+async fn get_recovered_work(
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+) -> Result<RecoveredWork, CommandError> {
+    let guard = journal_state.0.lock().await;
+    Ok(guard
+        .as_ref()
+        .map(|journal| journal.recovered())
+        .unwrap_or_default())
+}
 
-    // Try to fetch from server first - convert to Send-safe type immediately
-    let server_result: Result<Vec<serde_json::Value>, String> = model_manager
-        .fetch_available_models()
+/// Send a prompt recovered from the journal and drop it from the recovery list
+#[tauri::command]
+async fn resend_recovered_prompt(
+    app_handle: tauri::AppHandle,
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+    prompt_id: String,
+) -> Result<serde_json::Value, CommandError> {
+    let journal = journal_state
+        .0
+        .lock()
         .await
-        .map(|models| {
-            models
-                .into_iter()
-                .map(|m| serde_json::to_value(m).unwrap_or_default())
-                .collect()
-        })
-        .map_err(|e| e.to_string());
+        .clone()
+        .ok_or_else(|| CommandError::not_initialized("Recovery journal"))?;
+    let prompt = journal
+        .recovered()
+        .pending_prompts
+        .into_iter()
+        .find(|p| p.id == prompt_id)
+        .ok_or_else(|| format!("Recovered prompt not found: {}", prompt_id))?;
 
-    match server_result {
-        Ok(models_json) => {
-            log_info!(
-                "✅ [MODELS] Retrieved {} models from server",
-                models_json.len()
-            );
-            Ok(models_json)
+    info!(target: "recovery", session_id = %prompt.session_id, "Resending recovered prompt");
+
+    let message = messaging(&app_handle)
+        .await?
+        .send_message(&prompt.session_id, &prompt.content)
+        .await?;
+
+    journal.resolve_prompt(&prompt_id).map_err(|e| {
+        CommandError::file_system("Failed to update recovery journal").with_details(e.to_string())
+    })?;
+
+    serde_json::to_value(&message)
+        .map_err(|e| CommandError::data("Failed to serialize message").with_details(e.to_string()))
+}
+
+#[tauri::command]
+async fn discard_recovered_work(
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+    prompt_id: Option<String>,
+) -> Result<(), CommandError> {
+    let guard = journal_state.0.lock().await;
+    let journal = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Recovery journal"))?;
+
+    match prompt_id {
+        Some(id) => journal.resolve_prompt(&id).map(|_| ()),
+        None => journal.clear_recovered(),
+    }
+    .map_err(|e| {
+        CommandError::file_system("Failed to update recovery journal").with_details(e.to_string())
+    })
+}
+
+#[tauri::command]
+async fn get_session_messages(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    page: Option<PageRequest>,
+    connection_id: Option<String>,
+) -> Result<Page<serde_json::Value>, CommandError> {
+    info!(target: "chat", session_id = %session_id, "Getting session messages");
+
+    let messages = messaging_for(&app_handle, connection_id.as_deref())
+        .await?
+        .get_session_messages(&session_id)
+        .await?;
+
+    Ok(pagination::paginate(messages, page).map(|m| serde_json::to_value(&m).unwrap_or_default()))
+}
+
+/// Number of messages in a session, for sizing a virtual scroller
+#[tauri::command]
+async fn get_message_count(
+    app_handle: tauri::AppHandle,
+    session_manager_state: tauri::State<'_, SessionManagerState>,
+    session_id: String,
+) -> Result<usize, CommandError> {
+    if let Some(session_manager) = session_manager_state.0.lock().await.as_ref() {
+        if let Some(count) = session_manager.message_count(&session_id).await {
+            return Ok(count);
         }
-        Err(server_err) => {
-            log_warn!("⚠️ [MODELS] Failed to fetch from server: {}", server_err);
+    }
 
-            // Fallback to cached models
-            match model_manager.get_available_models().await {
-                Ok(cached_models) => {
-                    let models_json: Vec<serde_json::Value> = cached_models
-                        .into_iter()
-                        .map(|m| serde_json::to_value(m).unwrap_or_default())
-                        .collect();
-                    log_info!("✅ [MODELS] Retrieved {} cached models", models_json.len());
-                    Ok(models_json)
-                }
-                Err(cache_err) => {
-                    log_error!("❌ [MODELS] Failed to get cached models: {}", cache_err);
-                    Err(format!("Failed to get available models: {}", cache_err))
-                }
+    Ok(messaging(&app_handle)
+        .await?
+        .get_session_messages(&session_id)
+        .await?
+        .len())
+}
+
+/// The messages from `start_index`, at most `count` of them, so a virtual
+/// scroller only loads the rows it shows. Sessions stored locally are
+/// sliced in place; others are fetched from the server.
+#[tauri::command]
+async fn get_message_range(
+    app_handle: tauri::AppHandle,
+    session_manager_state: tauri::State<'_, SessionManagerState>,
+    session_id: String,
+    start_index: usize,
+    count: usize,
+) -> Result<Vec<serde_json::Value>, CommandError> {
+    let count = count.min(pagination::MAX_PAGE_SIZE);
+    debug!(target: "chat", session_id = %session_id, start_index, count, "Getting message range");
+
+    if let Some(session_manager) = session_manager_state.0.lock().await.as_ref() {
+        if let Some(messages) = session_manager
+            .message_range(&session_id, start_index, count)
+            .await
+        {
+            return Ok(messages
+                .iter()
+                .map(|m| serde_json::to_value(m).unwrap_or_default())
+                .collect());
+        }
+    }
+
+    let messages = messaging(&app_handle)
+        .await?
+        .get_session_messages(&session_id)
+        .await?;
+    Ok(messages
+        .iter()
+        .skip(start_index)
+        .take(count)
+        .map(|m| serde_json::to_value(m).unwrap_or_default())
+        .collect())
+}
+
+/// Rate an assistant reply. The rating is kept locally and passed on to
+/// the server when it accepts feedback.
+#[tauri::command]
+async fn rate_message(
+    app_handle: tauri::AppHandle,
+    feedback_state: tauri::State<'_, FeedbackState>,
+    session_id: String,
+    message_id: String,
+    rating: Rating,
+    comment: Option<String>,
+) -> Result<MessageFeedback, CommandError> {
+    let guard = feedback_state.0.lock().await;
+    let store = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Message feedback"))?;
+    let mut feedback = store.rate(&session_id, &message_id, rating, comment)?;
+    info!(target: "chat", session_id = %session_id, message_id = %message_id, ?rating, "Rated message");
+
+    if let Ok(api_client) = connected_api_client(&app_handle).await {
+        match api_client.send_message_feedback(&feedback).await {
+            Ok(true) => {
+                store.mark_forwarded(&message_id)?;
+                feedback.forwarded = true;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                warn!(target: "chat", message_id = %message_id, "Failed to forward feedback: {}", e)
             }
         }
     }
+    Ok(feedback)
 }
 
 #[tauri::command]
-async fn get_model_preferences() -> Result<serde_json::Value, String> {
-    log_info!("⚙️ [MODELS] Getting model preferences...");
+async fn clear_message_rating(
+    feedback_state: tauri::State<'_, FeedbackState>,
+    message_id: String,
+) -> Result<bool, CommandError> {
+    let guard = feedback_state.0.lock().await;
+    let store = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Message feedback"))?;
+    Ok(store.remove(&message_id)?)
+}
 
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let model_manager = ModelManager::new(api_client, config_dir);
+#[tauri::command]
+async fn list_message_feedback(
+    feedback_state: tauri::State<'_, FeedbackState>,
+    session_id: Option<String>,
+) -> Result<Vec<MessageFeedback>, CommandError> {
+    let guard = feedback_state.0.lock().await;
+    let store = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Message feedback"))?;
+    Ok(store.list(session_id.as_deref()))
+}
+
+/// Write rated replies with their prompts to `path` as JSON lines,
+/// optionally only those with `rating`. Returns how many were written.
+#[tauri::command]
+async fn export_rated_exchanges(
+    app_handle: tauri::AppHandle,
+    feedback_state: tauri::State<'_, FeedbackState>,
+    path: String,
+    rating: Option<Rating>,
+) -> Result<usize, CommandError> {
+    let feedback: Vec<_> = {
+        let guard = feedback_state.0.lock().await;
+        let store = guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Message feedback"))?;
+        store
+            .list(None)
+            .into_iter()
+            .filter(|feedback| rating.is_none_or(|rating| feedback.rating == rating))
+            .collect()
+    };
 
-    model_manager
-        .load_preferences()
-        .map_err(|e| e.to_string())?;
-    let preferences = model_manager.get_preferences();
+    let api_client = connected_api_client(&app_handle).await?;
+    let mut by_session: BTreeMap<String, Vec<MessageFeedback>> = BTreeMap::new();
+    for feedback in feedback {
+        by_session
+            .entry(feedback.session_id.clone())
+            .or_default()
+            .push(feedback);
+    }
+    let mut exchanges = Vec::new();
+    for (session_id, feedback) in by_session {
+        match api_client.get_session_message_parts(&session_id).await {
+            Ok(messages) => {
+                exchanges.extend(message_feedback::rated_exchanges(&feedback, &messages))
+            }
+            Err(e) => {
+                warn!(target: "chat", session_id = %session_id, "Skipping session in feedback export: {}", e)
+            }
+        }
+    }
+    exchanges.sort_by_key(|exchange| exchange.rated_at);
 
-    let preferences_json = serde_json::to_value(&preferences)
-        .map_err(|e| format!("Failed to serialize preferences: {}", e))?;
+    message_feedback::write_exchanges(std::path::Path::new(&path), &exchanges)?;
+    audit_log::record(
+        AuditAction::DataExported,
+        &path,
+        Some(format!("{} rated exchanges", exchanges.len())),
+    );
+    info!(target: "chat", path = %path, count = exchanges.len(), "Exported rated exchanges");
+    Ok(exchanges.len())
+}
 
-    log_info!("✅ [MODELS] Retrieved model preferences");
-    Ok(preferences_json)
+/// How much of its model's context window a session uses. The model is
+/// the one that answered last, or the default model for a new session.
+async fn compute_context_usage(
+    app_handle: &tauri::AppHandle,
+    settings_state: &SettingsState,
+    session_id: &str,
+) -> Result<ContextUsage, CommandError> {
+    let api_client = connected_api_client(app_handle).await?;
+    let messages = api_client.get_session_message_parts(session_id).await?;
+
+    let model = match slash_commands::last_model(&messages) {
+        Some(model) => Some(model),
+        None => settings_state
+            .0
+            .lock()
+            .await
+            .as_ref()
+            .map(|settings| settings.get().models)
+            .and_then(|models| models.default_provider.zip(models.default_model)),
+    };
+    let context_limit = match &model {
+        Some((provider_id, model_id)) => match api_client.get_provider_config().await {
+            Ok(providers) => context_usage::model_context_limit(&providers, provider_id, model_id),
+            Err(e) => {
+                warn!(target: "chat", "Failed to fetch model limits: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    Ok(context_usage::compute(
+        session_id,
+        &messages,
+        model,
+        context_limit,
+    ))
+}
+
+#[tauri::command]
+async fn get_context_usage(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    session_id: String,
+) -> Result<ContextUsage, CommandError> {
+    compute_context_usage(&app_handle, &settings_state, &session_id).await
+}
+
+/// Send the frontend a session's context usage once a response is done
+async fn emit_context_usage(app_handle: &tauri::AppHandle, session_id: &str) {
+    match compute_context_usage(app_handle, &app_handle.state::<SettingsState>(), session_id).await
+    {
+        Ok(usage) => {
+            if let Err(e) = app_handle.emit(context_usage::CONTEXT_USAGE_EVENT, usage) {
+                warn!(target: "chat", "Failed to emit context usage: {}", e);
+            }
+        }
+        Err(e) => {
+            warn!(target: "chat", session_id = %session_id, "Failed to compute context usage: {}", e)
+        }
+    }
+}
+
+/// Refuse a send once a budget's hard limit is reached, unless overridden
+async fn ensure_within_budget(
+    app_handle: &tauri::AppHandle,
+    session_id: &str,
+) -> Result<(), CommandError> {
+    let Some(settings) = app_handle
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .map(|settings| settings.get().budgets)
+    else {
+        return Ok(());
+    };
+    let Some(tracker) = app_handle.state::<BudgetState>().0.lock().await.clone() else {
+        return Ok(());
+    };
+    let status = tracker.status(&settings, Some(session_id), chrono::Local::now());
+    match status.blocking() {
+        Some(usage) => {
+            warn!(target: "chat", session_id = %session_id, scope = ?usage.scope, "Send refused by budget");
+            Err(CommandError::validation(format!(
+                "The {} budget of ${:.2} is used up (${:.2} spent). Override it to keep sending.",
+                usage.scope.label(),
+                usage.limit,
+                usage.spent
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Record what a response cost and alert on any budget threshold it crossed
+async fn track_spending(app_handle: &tauri::AppHandle, session_id: &str) {
+    let Some(settings) = app_handle
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .map(|settings| settings.get().budgets)
+    else {
+        return;
+    };
+    let Some(tracker) = app_handle.state::<BudgetState>().0.lock().await.clone() else {
+        return;
+    };
+
+    let result = async {
+        let api_client = connected_api_client(app_handle).await?;
+        let messages = api_client.get_session_message_parts(session_id).await?;
+        let now = chrono::Local::now();
+        tracker.record(session_id, &messages, now)?;
+        Ok::<_, CommandError>(tracker.take_alerts(&settings, session_id, now)?)
+    }
+    .await;
+    match result {
+        Ok(alerts) => {
+            for alert in alerts {
+                info!(target: "chat", scope = ?alert.scope, threshold = alert.threshold, "Budget threshold reached");
+                if let Err(e) = app_handle.emit(budgets::BUDGET_EVENT, alert) {
+                    warn!(target: "chat", "Failed to emit budget alert: {}", e);
+                }
+            }
+        }
+        Err(e) => {
+            warn!(target: "chat", session_id = %session_id, "Failed to record spending: {}", e)
+        }
+    }
+}
+
+/// Spending today, this month and, given a session, in that session, with
+/// each configured limit
+#[tauri::command]
+async fn get_budget_status(
+    settings_state: tauri::State<'_, SettingsState>,
+    budget_state: tauri::State<'_, BudgetState>,
+    session_id: Option<String>,
+) -> Result<BudgetStatus, CommandError> {
+    let settings = settings_state
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?
+        .get()
+        .budgets;
+    let guard = budget_state.0.lock().await;
+    let tracker = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Budgets"))?;
+    Ok(tracker.status(&settings, session_id.as_deref(), chrono::Local::now()))
+}
+
+/// Give a session its own spending limit; `None` returns it to the default
+#[tauri::command]
+async fn set_session_budget(
+    budget_state: tauri::State<'_, BudgetState>,
+    session_id: String,
+    limit: Option<f64>,
+) -> Result<(), CommandError> {
+    let guard = budget_state.0.lock().await;
+    let tracker = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Budgets"))?;
+    tracker.set_session_limit(&session_id, limit)?;
+    info!(target: "chat", session_id = %session_id, ?limit, "Set session budget");
+    Ok(())
+}
+
+/// Keep sending past a reached limit for the rest of its day, month or
+/// session
+#[tauri::command]
+async fn override_budget(
+    budget_state: tauri::State<'_, BudgetState>,
+    scope: BudgetScope,
+    session_id: Option<String>,
+) -> Result<(), CommandError> {
+    let guard = budget_state.0.lock().await;
+    let tracker = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Budgets"))?;
+    tracker.grant_override(scope, session_id.as_deref(), chrono::Local::now())?;
+    info!(target: "chat", ?scope, "Budget overridden");
+    Ok(())
+}
+
+/// Points a session can be reverted to, one before each prompt, oldest first
+#[tauri::command]
+async fn list_checkpoints(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+) -> Result<Vec<Checkpoint>, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let messages = api_client.get_session_message_parts(&session_id).await?;
+    let session = api_client.get_session_info(&session_id).await?;
+    Ok(checkpoints::list(&session_id, &messages, &session))
+}
+
+/// Roll a session and the agent's file changes back to just before a
+/// checkpoint's prompt, leaving a marker message in local history. Sending
+/// `/redo` restores it until the next message is sent.
+#[tauri::command]
+async fn revert_to_checkpoint(
+    app_handle: tauri::AppHandle,
+    session_manager_state: tauri::State<'_, SessionManagerState>,
+    session_id: String,
+    checkpoint_id: String,
+) -> Result<Checkpoint, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let messages = api_client.get_session_message_parts(&session_id).await?;
+    let session = api_client.get_session_info(&session_id).await?;
+    let mut checkpoint = checkpoints::list(&session_id, &messages, &session)
+        .into_iter()
+        .find(|checkpoint| checkpoint.id == checkpoint_id)
+        .ok_or_else(|| {
+            CommandError::validation(format!("Checkpoint not found: {}", checkpoint_id))
+        })?;
+    api_client
+        .revert_message(&session_id, &checkpoint_id)
+        .await?;
+    checkpoint.reverted = true;
+
+    let text = checkpoints::marker_text(&checkpoint);
+    let marker = ChatMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        role: MessageRole::System,
+        content: text.clone(),
+        timestamp: chrono::Utc::now(),
+        model: None,
+        metadata: Some(HashMap::from([(
+            "checkpoint".to_string(),
+            serde_json::Value::from(checkpoint.id.clone()),
+        )])),
+        parts: vec![MessagePart::text(text)],
+    };
+    if let Some(session_manager) = session_manager_state.0.lock().await.as_ref() {
+        if let Err(e) = session_manager
+            .record_revert(
+                &session_id,
+                &checkpoint.id,
+                checkpoint.created_at,
+                marker.clone(),
+            )
+            .await
+        {
+            warn!(target: "chat", session_id = %session_id, "Failed to record revert locally: {}", e);
+        }
+    }
+
+    let event_bridge = EventBridge::with_app_handle(app_handle.clone())
+        .with_window_routing(app_handle.state::<SessionWindowState>().0.clone());
+    if let Err(e) = event_bridge
+        .emit_message_received(session_id.clone(), marker)
+        .await
+    {
+        warn!(target: "chat", session_id = %session_id, "Failed to emit revert marker: {}", e);
+    }
+    info!(
+        target: "chat",
+        session_id = %session_id,
+        checkpoint_id = %checkpoint.id,
+        files = checkpoint.files_changed,
+        "Reverted to checkpoint"
+    );
+    Ok(checkpoint)
+}
+
+/// Language server diagnostics for the files the agent changed in a session
+#[tauri::command]
+async fn get_session_diagnostics(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+) -> Result<SessionDiagnostics, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let messages = api_client.get_session_message_parts(&session_id).await?;
+    let report = diagnostics::from_messages(&session_id, &messages);
+    debug!(
+        target: "chat",
+        session_id = %session_id,
+        errors = report.errors,
+        warnings = report.warnings,
+        "Collected diagnostics"
+    );
+    Ok(report)
+}
+
+/// Tell the frontend whether a response's edits left problems behind
+async fn emit_session_diagnostics(app_handle: &tauri::AppHandle, session_id: &str) {
+    match get_session_diagnostics(app_handle.clone(), session_id.to_string()).await {
+        Ok(report) if report.files.is_empty() => {}
+        Ok(report) => {
+            if let Err(e) = app_handle.emit(diagnostics::DIAGNOSTICS_EVENT, report) {
+                warn!(target: "chat", "Failed to emit diagnostics: {}", e);
+            }
+        }
+        Err(e) => {
+            warn!(target: "chat", session_id = %session_id, "Failed to collect diagnostics: {}", e)
+        }
+    }
+}
+
+/// The connected server's effective config, with where it departs from
+/// `expected` (a partial config in the server's shape) and from the app's
+/// default model
+#[tauri::command]
+async fn get_server_config(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    expected: Option<serde_json::Value>,
+) -> Result<ServerConfigReport, CommandError> {
+    let mut expected = match expected {
+        None => serde_json::json!({}),
+        Some(value @ serde_json::Value::Object(_)) => value,
+        Some(_) => {
+            return Err(CommandError::validation(
+                "Expected config must be an object",
+            ))
+        }
+    };
+    let models = settings_state
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?
+        .get()
+        .models;
+    if let (Some(provider), Some(model), Some(fields)) = (
+        models.default_provider,
+        models.default_model,
+        expected.as_object_mut(),
+    ) {
+        fields
+            .entry("model")
+            .or_insert_with(|| format!("{}/{}", provider, model).into());
+    }
+
+    let server_url = ensure_server_connected(&app_handle).await?;
+    let api_client = api_client_for(&app_handle, server_url.clone()).await?;
+    let raw = api_client.get_config().await?;
+    let differences = server_config::differences(&expected, &raw);
+    debug!(
+        target: "connection",
+        differences = differences.len(),
+        "Fetched server config"
+    );
+    Ok(ServerConfigReport {
+        server_url,
+        config: ServerConfig::parse(raw),
+        differences,
+    })
+}
+
+/// Which providers the server has credentials for, missing ones first
+#[tauri::command]
+async fn get_provider_auth_status(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<ProviderAuthStatus>, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let catalog = match api_client.list_provider_auth().await {
+        Ok(catalog) => Some(catalog),
+        Err(e) => {
+            debug!(target: "connection", "Provider catalog unavailable: {}", e);
+            None
+        }
+    };
+    let usable = api_client.get_provider_config().await?;
+    Ok(provider_auth::statuses(catalog.as_ref(), &usable))
+}
+
+/// Give the server credentials for a provider, then report its status
+#[tauri::command]
+async fn authenticate_provider(
+    app_handle: tauri::AppHandle,
+    provider_id: String,
+    credentials: ProviderCredentials,
+) -> Result<ProviderAuthStatus, CommandError> {
+    provider_auth::validate_provider_id(&provider_id)?;
+    credentials.validate()?;
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let body = serde_json::to_value(&credentials)
+        .map_err(|e| CommandError::internal(format!("Failed to encode credentials: {}", e)))?;
+    api_client.set_provider_auth(&provider_id, body).await?;
+    audit_log::record(
+        AuditAction::CredentialStored,
+        &format!("provider:{}", provider_id),
+        Some("server auth".to_string()),
+    );
+    info!(target: "connection", provider_id = %provider_id, "Stored provider credentials on server");
+
+    get_provider_auth_status(app_handle)
+        .await?
+        .into_iter()
+        .find(|status| status.provider_id == provider_id)
+        .ok_or_else(|| CommandError::validation(format!("Unknown provider: {}", provider_id)))
+}
+
+/// Built-in and server-defined slash commands, for completion while typing
+#[tauri::command]
+async fn list_slash_commands(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<SlashCommandInfo>, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+    let server_commands = server_commands(&app_handle, &api_client).await;
+    Ok(slash_commands::available(&server_commands))
+}
+
+/// The server's commands, prefetched when connecting if still fresh
+async fn server_commands(
+    app_handle: &tauri::AppHandle,
+    api_client: &ApiClient,
+) -> Vec<ServerCommand> {
+    if let Some(commands) = app_handle.state::<PrefetchState>().0.capabilities() {
+        return commands;
+    }
+    api_client.list_commands().await.unwrap_or_else(|e| {
+        warn!(target: "chat", "Failed to fetch server commands: {}", e);
+        Vec::new()
+    })
+}
+
+/// Run `input` if it is a slash command the app or server knows, and post
+/// the outcome to the session as a system message. Returns `None` when the
+/// input should be sent as an ordinary message.
+#[tauri::command]
+async fn run_slash_command(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    session_id: String,
+    input: String,
+) -> Result<Option<SlashCommandResult>, CommandError> {
+    let Some(invocation) = slash_commands::parse(&input) else {
+        return Ok(None);
+    };
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let server_commands = server_commands(&app_handle, &api_client).await;
+    let Some(route) = slash_commands::resolve(&invocation, &server_commands) else {
+        return Ok(None);
+    };
+
+    let (message, data) = match route {
+        SlashRoute::Builtin(BuiltinCommand::Undo) => {
+            let messages = api_client.get_session_message_parts(&session_id).await?;
+            let message_id = slash_commands::last_user_message_id(&messages)
+                .ok_or_else(|| CommandError::validation("Nothing to undo"))?;
+            let data = api_client.revert_message(&session_id, &message_id).await?;
+            ("Reverted the last message".to_string(), data)
+        }
+        SlashRoute::Builtin(BuiltinCommand::Redo) => {
+            let data = api_client.unrevert_session(&session_id).await?;
+            ("Restored the reverted messages".to_string(), data)
+        }
+        SlashRoute::Builtin(BuiltinCommand::Compact) => {
+            let messages = api_client.get_session_message_parts(&session_id).await?;
+            let model = match slash_commands::last_model(&messages) {
+                Some(model) => Some(model),
+                None => {
+                    let guard = settings_state.0.lock().await;
+                    let models = guard
+                        .as_ref()
+                        .ok_or_else(|| CommandError::not_initialized("Settings"))?
+                        .get()
+                        .models;
+                    models.default_provider.zip(models.default_model)
+                }
+            };
+            let (provider_id, model_id) = model.ok_or_else(|| {
+                CommandError::validation("Choose a model before compacting the session")
+            })?;
+            let data = api_client
+                .summarize_session(
+                    &session_id,
+                    &ModelConfig {
+                        provider_id,
+                        model_id,
+                    },
+                )
+                .await?;
+            ("Compacted the session".to_string(), data)
+        }
+        SlashRoute::Builtin(BuiltinCommand::Share) => {
+            let compatibility = require_feature(&app_handle, ServerFeature::Share)?;
+            let data = compatibility.check(
+                ServerFeature::Share,
+                api_client.set_session_shared(&session_id, true).await,
+            )?;
+            let message = match data.pointer("/share/url").and_then(|url| url.as_str()) {
+                Some(url) => format!("Shared the session: {}", url),
+                None => "Shared the session".to_string(),
+            };
+            (message, data)
+        }
+        SlashRoute::Builtin(BuiltinCommand::Unshare) => {
+            let compatibility = require_feature(&app_handle, ServerFeature::Share)?;
+            let data = compatibility.check(
+                ServerFeature::Share,
+                api_client.set_session_shared(&session_id, false).await,
+            )?;
+            ("Stopped sharing the session".to_string(), data)
+        }
+        SlashRoute::Builtin(BuiltinCommand::Abort) => {
+            let data = api_client.abort_session(&session_id).await?;
+            ("Stopped the response".to_string(), data)
+        }
+        SlashRoute::Server(name) => {
+            let data = api_client
+                .run_session_command(&session_id, &name, &invocation.arguments)
+                .await?;
+            (format!("Ran /{}", name), data)
+        }
+    };
+
+    let result = SlashCommandResult {
+        command: invocation.name,
+        message,
+        data: Some(data).filter(|data| !data.is_null()),
+    };
+    let event_bridge = EventBridge::with_app_handle(app_handle.clone())
+        .with_window_routing(app_handle.state::<SessionWindowState>().0.clone());
+    let system_message = ChatMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        role: MessageRole::System,
+        content: result.message.clone(),
+        timestamp: chrono::Utc::now(),
+        model: None,
+        metadata: Some(HashMap::from([(
+            "slash_command".to_string(),
+            serde_json::Value::from(result.command.clone()),
+        )])),
+        parts: vec![MessagePart::text(result.message.clone())],
+    };
+    if let Err(e) = event_bridge
+        .emit_message_received(session_id.clone(), system_message)
+        .await
+    {
+        warn!(target: "chat", session_id = %session_id, "Failed to emit command result: {}", e);
+    }
+    info!(target: "chat", session_id = %session_id, command = %result.command, "Ran slash command");
+    Ok(Some(result))
+}
+
+/// Files the agent edited in a session, parsed into hunks for a diff viewer
+#[tauri::command]
+async fn get_session_file_changes(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+) -> Result<Vec<FileChange>, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let messages = api_client.get_session_message_parts(&session_id).await?;
+    let changes = file_changes::changes_from_messages(&messages);
+    debug!(target: "chat", session_id = %session_id, changes = changes.len(), "Collected file changes");
+    Ok(changes)
+}
+
+/// Fenced code blocks in one of a session's messages
+#[tauri::command]
+async fn extract_code_blocks(
+    app_handle: tauri::AppHandle,
+    code_block_state: tauri::State<'_, CodeBlockState>,
+    session_id: String,
+    message_id: String,
+) -> Result<Vec<CodeBlock>, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+
+    let messages = api_client.get_session_message_parts(&session_id).await?;
+    let message = messages
+        .iter()
+        .find(|message| {
+            message.pointer("/info/id").and_then(|id| id.as_str()) == Some(message_id.as_str())
+        })
+        .ok_or_else(|| CommandError::validation(format!("Message not found: {}", message_id)))?;
+    let blocks = code_blocks::extract(&message_id, &message_feedback::message_text(message));
+    code_block_state.0.insert(&blocks);
+    Ok(blocks)
+}
+
+/// Entries of a directory in the server's workspace, directories first.
+/// `path` is relative to the workspace; omit it for the root.
+#[tauri::command]
+async fn list_server_files(
+    app_handle: tauri::AppHandle,
+    server_file_state: tauri::State<'_, ServerFileState>,
+    path: Option<String>,
+    refresh: Option<bool>,
+) -> Result<Vec<FileNode>, CommandError> {
+    let path = server_files::normalize_path(path.as_deref().unwrap_or_default())?;
+    let server_url = ensure_server_connected(&app_handle).await?;
+    if !refresh.unwrap_or(false) {
+        if let Some(nodes) = server_file_state.0.listing(&server_url, &path) {
+            return Ok(nodes);
+        }
+    }
+
+    let compatibility = require_feature(&app_handle, ServerFeature::FileApi)?;
+    let api_client = api_client_for(&app_handle, server_url.clone()).await?;
+    let nodes = server_files::parse_listing(
+        compatibility.check(ServerFeature::FileApi, api_client.list_files(&path).await)?,
+    )?;
+    debug!(target: "files", path = %path, entries = nodes.len(), "Listed server files");
+    server_file_state
+        .0
+        .store_listing(&server_url, &path, nodes.clone());
+    Ok(nodes)
+}
+
+/// A file from the server's workspace with its uncommitted diff. Binary
+/// files come back without content and large ones are truncated.
+#[tauri::command]
+async fn read_server_file(
+    app_handle: tauri::AppHandle,
+    server_file_state: tauri::State<'_, ServerFileState>,
+    path: String,
+    refresh: Option<bool>,
+) -> Result<ServerFile, CommandError> {
+    let path = server_files::normalize_path(&path)?;
+    if path.is_empty() {
+        return Err(CommandError::validation("A file path is required"));
+    }
+    let server_url = ensure_server_connected(&app_handle).await?;
+    if !refresh.unwrap_or(false) {
+        if let Some(file) = server_file_state.0.file(&server_url, &path) {
+            return Ok(file);
+        }
+    }
+
+    let compatibility = require_feature(&app_handle, ServerFeature::FileApi)?;
+    let api_client = api_client_for(&app_handle, server_url.clone()).await?;
+    let file = server_files::parse_content(
+        &path,
+        &compatibility.check(ServerFeature::FileApi, api_client.read_file(&path).await)?,
+    );
+    debug!(target: "files", path = %path, size = file.size, truncated = file.truncated, "Read server file");
+    server_file_state.0.store_file(&server_url, file.clone());
+    Ok(file)
+}
+
+/// Outcome of `search_server_files`; the hits arrive as events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSearchSummary {
+    pub search_id: String,
+    pub total: usize,
+    /// More hits matched than were sent
+    pub truncated: bool,
+}
+
+/// Search the server's workspace by file name, or by file contents with
+/// `content`. Hits are emitted in `file-search-results` batches so large
+/// repositories fill the picker progressively; `glob` narrows them by path.
+#[tauri::command]
+async fn search_server_files(
+    app_handle: tauri::AppHandle,
+    query: String,
+    glob: Option<String>,
+    content: Option<bool>,
+    search_id: Option<String>,
+) -> Result<FileSearchSummary, CommandError> {
+    if query.trim().is_empty() {
+        return Err(CommandError::validation("Search query cannot be empty"));
+    }
+    let api_client = connected_api_client(&app_handle).await?;
+    let compatibility = require_feature(&app_handle, ServerFeature::FileApi)?;
+
+    let mut hits = if content.unwrap_or(false) {
+        server_files::parse_text_hits(
+            &compatibility.check(ServerFeature::FileApi, api_client.find_text(&query).await)?,
+        )
+    } else {
+        server_files::parse_file_hits(
+            &compatibility.check(ServerFeature::FileApi, api_client.find_files(&query).await)?,
+        )
+    };
+    let truncated = server_files::filter_hits(&mut hits, glob.as_deref())?;
+
+    let search_id = search_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let total = hits.len();
+    let mut batches: Vec<Vec<SearchHit>> = hits
+        .chunks(server_files::SEARCH_BATCH_SIZE)
+        .map(<[SearchHit]>::to_vec)
+        .collect();
+    if batches.is_empty() {
+        batches.push(Vec::new());
+    }
+    let count = batches.len();
+    for (index, batch) in batches.into_iter().enumerate() {
+        emit_search_batch(&app_handle, &search_id, batch, index + 1 == count);
+        tokio::task::yield_now().await;
+    }
+    debug!(target: "files", search_id = %search_id, total, truncated, "Searched server files");
+
+    Ok(FileSearchSummary {
+        search_id,
+        total,
+        truncated,
+    })
+}
+
+fn emit_search_batch(
+    app_handle: &tauri::AppHandle,
+    search_id: &str,
+    hits: Vec<SearchHit>,
+    done: bool,
+) {
+    let batch = FileSearchBatch {
+        search_id: search_id.to_string(),
+        hits,
+        done,
+    };
+    if let Err(e) = app_handle.emit(server_files::FILE_SEARCH_EVENT, batch) {
+        warn!(target: "files", "Failed to emit search results: {}", e);
+    }
+}
+
+/// Agents the connected server offers, as it describes them
+#[tauri::command]
+async fn list_agents(app_handle: tauri::AppHandle) -> Result<Vec<serde_json::Value>, CommandError> {
+    let api_client = connected_api_client(&app_handle).await?;
+    let compatibility = require_feature(&app_handle, ServerFeature::Agents)?;
+    let agents = compatibility.check(ServerFeature::Agents, api_client.list_agents().await)?;
+    Ok(agents.as_array().cloned().unwrap_or_default())
+}
+
+/// Result of `apply_code_block_to_file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeBlockApplication {
+    pub path: String,
+    pub mode: ApplyMode,
+    /// Whether the file existed before
+    pub existed: bool,
+    /// The file's contents after applying the block
+    pub content: String,
+    /// `false` for a preview that wrote nothing
+    pub applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<String>,
+}
+
+/// Write an extracted code block to a local file. Without `confirm` this
+/// only previews the result; with it, any existing file is backed up to the
+/// config directory first.
+#[tauri::command]
+async fn apply_code_block_to_file(
+    code_block_state: tauri::State<'_, CodeBlockState>,
+    block_id: String,
+    path: String,
+    mode: ApplyMode,
+    confirm: Option<bool>,
+) -> Result<CodeBlockApplication, CommandError> {
+    let block = code_block_state
+        .0
+        .get(&block_id)
+        .ok_or_else(|| CommandError::validation(format!("Unknown code block: {}", block_id)))?;
+    let config_dir = get_config_dir()?;
+    let target = code_blocks::check_target(
+        std::path::Path::new(&path),
+        std::slice::from_ref(&config_dir),
+    )?;
+
+    let current = if target.exists() {
+        let metadata = std::fs::metadata(&target).map_err(error::AppError::from)?;
+        if metadata.len() > attachments::MAX_ATTACHMENT_BYTES {
+            return Err(CommandError::validation("File is too large to edit"));
+        }
+        Some(std::fs::read_to_string(&target).map_err(|e| {
+            CommandError::file_system("Failed to read file").with_details(e.to_string())
+        })?)
+    } else {
+        None
+    };
+    let content = code_blocks::apply(&block, current.as_deref(), mode)?;
+
+    let mut application = CodeBlockApplication {
+        path: target.to_string_lossy().to_string(),
+        mode,
+        existed: current.is_some(),
+        content,
+        applied: false,
+        backup_path: None,
+    };
+    if !confirm.unwrap_or(false) {
+        return Ok(application);
+    }
+
+    let backup = code_blocks::write_with_backup(
+        &target,
+        &application.content,
+        &config_dir.join("code_block_backups"),
+    )?;
+    application.applied = true;
+    application.backup_path = backup.map(|backup| backup.to_string_lossy().to_string());
+    info!(
+        target: "chat",
+        block_id = %block_id,
+        path = %application.path,
+        ?mode,
+        "Applied code block to file"
+    );
+    Ok(application)
+}
+
+/// Bash commands the agent is waiting to run, optionally for one session
+#[tauri::command]
+async fn list_pending_shell_approvals(
+    shell_approval_state: tauri::State<'_, ShellApprovalState>,
+    session_id: Option<String>,
+) -> Result<Vec<ShellApprovalRequest>, CommandError> {
+    Ok(shell_approval_state.0.list(session_id.as_deref()))
+}
+
+/// Answer a pending bash command. Allowing it requires `confirmed`, set by
+/// the frontend only after the user has seen the command, and high-risk
+/// commands can only be allowed once.
+#[tauri::command]
+async fn respond_to_shell_approval(
+    app_handle: tauri::AppHandle,
+    shell_approval_state: tauri::State<'_, ShellApprovalState>,
+    approval_id: String,
+    decision: ApprovalDecision,
+    confirmed: Option<bool>,
+) -> Result<(), CommandError> {
+    let request = shell_approval_state
+        .0
+        .take(&approval_id)
+        .ok_or_else(|| CommandError::validation(format!("No pending approval: {}", approval_id)))?;
+    let refusal = match decision {
+        ApprovalDecision::Reject => None,
+        _ if !confirmed.unwrap_or(false) => {
+            Some("Running a shell command needs explicit confirmation")
+        }
+        ApprovalDecision::Always if request.risk.level == RiskLevel::High => {
+            Some("High-risk commands can only be allowed once")
+        }
+        _ => None,
+    };
+    if let Some(refusal) = refusal {
+        shell_approval_state.0.insert(request);
+        return Err(CommandError::validation(refusal));
+    }
+
+    let result = async {
+        let api_client = connected_api_client(&app_handle).await?;
+        let compatibility = require_feature(&app_handle, ServerFeature::Permissions)?;
+        compatibility.check(
+            ServerFeature::Permissions,
+            api_client
+                .respond_to_permission(&request.session_id, &request.id, decision)
+                .await,
+        )?;
+        Ok::<_, CommandError>(())
+    }
+    .await;
+    if let Err(e) = result {
+        // Keep it pending so the user can answer again
+        shell_approval_state.0.insert(request);
+        return Err(e);
+    }
+
+    if decision != ApprovalDecision::Reject {
+        audit_log::record(
+            AuditAction::ShellCommandApproved,
+            &request.session_id,
+            Some(format!("{:?}: {}", decision, request.command)),
+        );
+    }
+    info!(
+        target: "stream",
+        session_id = %request.session_id,
+        approval_id = %request.id,
+        ?decision,
+        "Answered shell command approval"
+    );
+    Ok(())
+}
+
+/// Open a shell for running what the agent suggested. It starts in `cwd`,
+/// else the directory of the session's workspace, else the active one;
+/// output and exit arrive as `terminal-event` events. Refused while
+/// connected to a server on another machine, which needs SSH.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn open_terminal(
+    app_handle: tauri::AppHandle,
+    terminal_state: tauri::State<'_, TerminalState>,
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    cwd: Option<String>,
+    session_id: Option<String>,
+    shell: Option<String>,
+    rows: Option<u16>,
+    cols: Option<u16>,
+) -> Result<TerminalInfo, CommandError> {
+    let server_url = connection_state
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .and_then(|manager| manager.get_server_url());
+    if let Some(server_url) = server_url.filter(|url| !terminal::is_local_server(url)) {
+        return Err(CommandError::validation(format!(
+            "Terminals are only available for servers on this machine, not {}",
+            server_url
+        )));
+    }
+    let cwd = match cwd.filter(|cwd| !cwd.trim().is_empty()) {
+        Some(cwd) => std::path::PathBuf::from(cwd),
+        None => {
+            let guard = workspace_state.0.lock().await;
+            let workspaces = guard.as_ref();
+            session_id
+                .as_deref()
+                .and_then(|session_id| {
+                    workspaces.and_then(|workspaces| workspaces.workspace_for_session(session_id))
+                })
+                .or_else(|| workspaces.and_then(|workspaces| workspaces.active()))
+                .map(|workspace| workspace.directory)
+                .ok_or_else(|| {
+                    CommandError::validation("Choose a directory or open a workspace first")
+                })?
+        }
+    };
+    let shell = shell
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(terminal::default_shell);
+
+    let emitter = app_handle.clone();
+    let info = terminal_state
+        .0
+        .open(&cwd, &shell, rows.zip(cols), move |event| {
+            if let Err(e) = emitter.emit(terminal::TERMINAL_EVENT, event) {
+                warn!(target: "terminal", "Failed to emit terminal event: {}", e);
+            }
+        })
+        .await?;
+    audit_log::record(
+        AuditAction::TerminalOpened,
+        &info.id,
+        Some(format!("{} in {}", info.shell, info.cwd.display())),
+    );
+    info!(target: "terminal", terminal_id = %info.id, cwd = %info.cwd.display(), "Opened terminal");
+    Ok(info)
+}
+
+/// Send keyboard input, e.g. a command followed by a newline
+#[tauri::command]
+async fn write_terminal(
+    terminal_state: tauri::State<'_, TerminalState>,
+    terminal_id: String,
+    data: String,
+) -> Result<(), CommandError> {
+    Ok(terminal_state.0.write(&terminal_id, &data).await?)
+}
+
+/// Match the terminal to the size the frontend shows it at
+#[tauri::command]
+async fn resize_terminal(
+    terminal_state: tauri::State<'_, TerminalState>,
+    terminal_id: String,
+    rows: u16,
+    cols: u16,
+) -> Result<(), CommandError> {
+    Ok(terminal_state.0.resize(&terminal_id, rows, cols).await?)
+}
+
+#[tauri::command]
+async fn close_terminal(
+    terminal_state: tauri::State<'_, TerminalState>,
+    terminal_id: String,
+) -> Result<(), CommandError> {
+    terminal_state.0.close(&terminal_id).await?;
+    info!(target: "terminal", terminal_id = %terminal_id, "Closed terminal");
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_terminals(
+    terminal_state: tauri::State<'_, TerminalState>,
+) -> Result<Vec<TerminalInfo>, CommandError> {
+    Ok(terminal_state.0.list().await)
+}
+
+#[tauri::command]
+async fn subscribe_to_chat_events(app_handle: tauri::AppHandle) -> Result<String, CommandError> {
+    info!(target: "chat", "Subscribing to chat events");
+
+    ensure_server_connected(&app_handle).await?;
+
+    // Return subscription channel identifier
+    Ok("chat_events".to_string())
+}
+
+#[tauri::command]
+async fn clear_application_logs() -> Result<(), CommandError> {
+    info!(target: "logs", "Clearing application logs...");
+
+    let config_dir = get_config_dir()?;
+
+    logging::clear_logs(&config_dir).map_err(|e| {
+        CommandError::file_system("Failed to clear log file").with_details(e.to_string())
+    })?;
+
+    info!(target: "logs", "Application logs cleared successfully");
+    Ok(())
+}
+
+/// Current application settings, with PIN hashes and key material blanked
+#[tauri::command]
+async fn get_settings(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<AppSettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().redacted())
+}
+
+/// Save the user-editable settings in one go and apply them to the running
+/// app. The security section is ignored; it has its own commands.
+#[tauri::command]
+async fn update_settings(
+    settings_state: tauri::State<'_, SettingsState>,
+    settings: AppSettings,
+) -> Result<AppSettings, CommandError> {
+    let level = logging::parse_level(&settings.logging.level).map_err(CommandError::validation)?;
+    logging::build_directives(&level, &settings.logging.targets)
+        .map_err(CommandError::validation)?;
+    settings
+        .retry
+        .validate()
+        .map_err(CommandError::validation)?;
+    if i18n::normalize_locale(&settings.locale).is_none() {
+        return Err(CommandError::validation(format!(
+            "Unsupported locale: {}",
+            settings.locale
+        )));
+    }
+    log_forwarding::validate(&settings.logging.remote).map_err(CommandError::validation)?;
+    metrics::validate(&settings.metrics).map_err(CommandError::validation)?;
+    settings
+        .proxy
+        .validate()
+        .map_err(CommandError::validation)?;
+    settings.transcription.validate()?;
+    settings.budgets.validate()?;
+
+    let guard = settings_state.0.lock().await;
+    let manager = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = manager
+        .update(|current| *current = current.merge_user_settings(settings))
+        .map_err(|e| {
+            CommandError::file_system("Failed to save settings").with_details(e.to_string())
+        })?;
+
+    apply_settings(&updated)?;
+
+    info!(target: "init", "Settings updated");
+    Ok(updated.redacted())
+}
+
+/// Put saved settings into effect without a restart
+fn apply_settings(settings: &AppSettings) -> Result<(), CommandError> {
+    logging::set_filter(&settings.logging.level, &settings.logging.targets)
+        .map_err(CommandError::validation)?;
+    log_forwarding::configure(&settings.logging.remote).map_err(CommandError::validation)?;
+    metrics::configure(&settings.metrics).map_err(CommandError::validation)?;
+    error_reporting::apply(&settings.privacy);
+    error::set_retry_settings(settings.retry.clone());
+    i18n::set_locale(&settings.locale);
+    proxy::set_global(&settings.proxy);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LocaleInfo {
+    locale: String,
+    supported: Vec<String>,
+}
+
+fn locale_info() -> LocaleInfo {
+    LocaleInfo {
+        locale: i18n::current_locale(),
+        supported: i18n::SUPPORTED_LOCALES
+            .iter()
+            .map(|locale| locale.to_string())
+            .collect(),
+    }
+}
+
+/// Active language for backend messages and the languages available
+#[tauri::command]
+async fn get_locale() -> Result<LocaleInfo, CommandError> {
+    Ok(locale_info())
+}
+
+/// Switch the language of backend messages without restarting. Tags such
+/// as `es-MX` are reduced to their language.
+#[tauri::command]
+async fn set_locale(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    locale: String,
+) -> Result<LocaleInfo, CommandError> {
+    let normalized = i18n::normalize_locale(&locale)
+        .ok_or_else(|| CommandError::validation(format!("Unsupported locale: {}", locale)))?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    settings
+        .update(|s| s.locale = normalized.to_string())
+        .map_err(|e| {
+            CommandError::file_system("Failed to save locale").with_details(e.to_string())
+        })?;
+    drop(guard);
+
+    i18n::set_locale(normalized);
+    refresh_tray(&app_handle).await;
+    telemetry::record(Feature::LocaleChanged);
+    info!(target: "init", locale = %normalized, "Locale changed");
+    Ok(locale_info())
+}
+
+/// Push every settings change to the frontend so open views stay in sync
+async fn forward_settings_changes(
+    app_handle: tauri::AppHandle,
+    mut changes: tokio::sync::broadcast::Receiver<AppSettings>,
+) {
+    loop {
+        match changes.recv().await {
+            Ok(settings) => {
+                // Rebuild native menus when the language changed elsewhere
+                if i18n::normalize_locale(&settings.locale)
+                    .is_some_and(|locale| locale != i18n::current_locale())
+                {
+                    i18n::set_locale(&settings.locale);
+                    refresh_tray(&app_handle).await;
+                }
+                if let Err(e) =
+                    app_handle.emit(settings::SETTINGS_CHANGED_EVENT, settings.redacted())
+                {
+                    warn!(target: "init", "Failed to emit settings change: {}", e);
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_log_level(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<String, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().logging.level)
+}
+
+#[tauri::command]
+async fn set_log_level(
+    settings_state: tauri::State<'_, SettingsState>,
+    level: String,
+) -> Result<(), CommandError> {
+    let level = logging::parse_level(&level).map_err(CommandError::validation)?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    logging::set_filter(&level, &settings.get().logging.targets)
+        .map_err(CommandError::validation)?;
+    settings
+        .update(|s| s.logging.level = level.clone())
+        .map_err(|e| {
+            CommandError::file_system("Failed to save log level").with_details(e.to_string())
+        })?;
+
+    info!(target: "logs", "Log level set to {}", level);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_remote_logging(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<RemoteLogSettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().logging.remote)
+}
+
+#[tauri::command]
+async fn set_remote_logging(
+    settings_state: tauri::State<'_, SettingsState>,
+    remote: RemoteLogSettings,
+) -> Result<(), CommandError> {
+    log_forwarding::configure(&remote).map_err(CommandError::validation)?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let enabled = remote.enabled;
+    settings
+        .update(|s| s.logging.remote = remote)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save remote logging settings")
+                .with_details(e.to_string())
+        })?;
+
+    info!(target: "logs", "Remote log forwarding {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_error_reporting_settings(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<PrivacySettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().privacy)
+}
+
+#[tauri::command]
+async fn set_error_reporting_enabled(
+    settings_state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+    include_pii: Option<bool>,
+) -> Result<PrivacySettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| {
+            s.privacy.error_reporting_enabled = Some(enabled);
+            s.privacy.error_reporting_pii = enabled && include_pii.unwrap_or(false);
+        })
+        .map_err(|e| {
+            CommandError::file_system("Failed to save error reporting setting")
+                .with_details(e.to_string())
+        })?;
+
+    error_reporting::apply(&updated.privacy);
+
+    info!(target: "init", "Error reporting {}", if enabled { "enabled" } else { "disabled" });
+    Ok(updated.privacy)
+}
+
+#[tauri::command]
+async fn set_secret_scanning(
+    settings_state: tauri::State<'_, SettingsState>,
+    mode: SecretScanMode,
+) -> Result<PrivacySettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| s.privacy.secret_scanning = mode)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save secret scanning setting")
+                .with_details(e.to_string())
+        })?;
+
+    info!(target: "chat", ?mode, "Secret scanning updated");
+    Ok(updated.privacy)
+}
+
+/// Preview what the secret scanner flags in a prompt and the files about
+/// to be attached to it
+#[tauri::command]
+async fn scan_outgoing_content(
+    content: String,
+    attachment_paths: Option<Vec<String>>,
+) -> Result<Vec<SecretFinding>, CommandError> {
+    let mut findings = secret_scan::scan_text("message", &content);
+    for path in attachment_paths.unwrap_or_default() {
+        let (_, contents) = attachments::validate_file(std::path::Path::new(&path))?;
+        findings.extend(secret_scan::scan_attachment(&path, &contents));
+    }
+    Ok(findings)
+}
+
+/// Check a user-selected file before it is uploaded or displayed
+#[tauri::command]
+async fn validate_attachment(path: String) -> Result<ValidatedAttachment, CommandError> {
+    let (attachment, _) = attachments::validate_file(std::path::Path::new(&path))?;
+    telemetry::record(Feature::AttachmentValidated);
+    debug!(target: "chat", name = %attachment.name, mime = %attachment.mime_type, "Validated attachment");
+    Ok(attachment)
+}
+
+/// Send a local file to a session as context for the agent. Progress is
+/// reported through `context-upload-progress` while the body is sent.
+#[tauri::command]
+async fn upload_context_file(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    session_id: String,
+    path: String,
+    allow_secrets: Option<bool>,
+) -> Result<ContextFile, CommandError> {
+    let server_url = ensure_server_connected(&app_handle).await?;
+    let (attachment, contents) = attachments::validate_file(std::path::Path::new(&path))?;
+    check_secret_findings(&settings_state, allow_secrets.unwrap_or(false), || {
+        secret_scan::scan_attachment(&path, &contents)
+    })
+    .await?;
+
+    let api_client = api_client_for(&app_handle, server_url).await?;
+    let body = context_upload::prompt_body(&attachment.name, &attachment.mime_type, &contents);
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    let total_bytes = body.len() as u64;
+    let progress = UploadProgress {
+        upload_id: upload_id.clone(),
+        session_id: session_id.clone(),
+        file_name: attachment.name.clone(),
+        sent_bytes: 0,
+        total_bytes,
+    };
+    let emitter = app_handle.clone();
+    let response = api_client
+        .post_session_message_streamed(&session_id, body, move |sent_bytes| {
+            let progress = UploadProgress {
+                sent_bytes,
+                ..progress.clone()
+            };
+            if let Err(e) = emitter.emit(context_upload::UPLOAD_PROGRESS_EVENT, &progress) {
+                warn!(target: "chat", "Failed to emit upload progress: {}", e);
+            }
+        })
+        .await?;
+
+    info!(
+        target: "chat",
+        session_id = %session_id,
+        name = %attachment.name,
+        size = attachment.size,
+        "Uploaded context file"
+    );
+    telemetry::record(Feature::ContextFileUploaded);
+    Ok(ContextFile {
+        upload_id,
+        session_id,
+        file_name: attachment.name,
+        mime_type: attachment.mime_type,
+        size: attachment.size,
+        message_id: context_upload::message_id(&response),
+    })
+}
+
+/// Turn a recording into text for the prompt box. Partial transcripts are
+/// emitted as `transcription-progress` events when the endpoint streams.
+#[tauri::command]
+async fn transcribe_audio(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    source: AudioSource,
+) -> Result<String, CommandError> {
+    let settings = settings_state
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?
+        .get()
+        .transcription;
+    let clip = AudioClip::load(source)?;
+
+    let (endpoint, api_key) = match settings.endpoint_for(None) {
+        Some(endpoint) => (
+            endpoint,
+            with_secret_store(|store| store.get(transcription::TRANSCRIPTION_API_KEY)).await?,
+        ),
+        None => {
+            let server_url = ensure_server_connected(&app_handle).await?;
+            let endpoint = settings
+                .endpoint_for(Some(&server_url))
+                .ok_or_else(|| CommandError::internal("No transcription endpoint"))?;
+            (endpoint, None)
+        }
+    };
+
+    let transcription_id = uuid::Uuid::new_v4().to_string();
+    let emitter = app_handle.clone();
+    let progress_id = transcription_id.clone();
+    let text = transcription::transcribe(&endpoint, api_key.as_deref(), &settings, &clip, |text| {
+        let progress = TranscriptionProgress {
+            transcription_id: progress_id.clone(),
+            text: text.to_string(),
+            done: false,
+        };
+        if let Err(e) = emitter.emit(transcription::TRANSCRIPTION_EVENT, progress) {
+            warn!(target: "chat", "Failed to emit transcription progress: {}", e);
+        }
+    })
+    .await?;
+
+    if let Err(e) = app_handle.emit(
+        transcription::TRANSCRIPTION_EVENT,
+        TranscriptionProgress {
+            transcription_id,
+            text: text.clone(),
+            done: true,
+        },
+    ) {
+        warn!(target: "chat", "Failed to emit transcription progress: {}", e);
+    }
+    info!(
+        target: "chat",
+        size = clip.data.len(),
+        chars = text.chars().count(),
+        "Transcribed audio"
+    );
+    telemetry::record(Feature::AudioTranscribed);
+    Ok(text)
+}
+
+/// Store or, with `None`, remove the key for a custom transcription endpoint
+#[tauri::command]
+async fn set_transcription_api_key(api_key: Option<String>) -> Result<(), CommandError> {
+    with_secret_store(move |store| {
+        match api_key.filter(|key| !key.trim().is_empty()) {
+            Some(key) => {
+                store.set(transcription::TRANSCRIPTION_API_KEY, key.trim())?;
+            }
+            None => {
+                store.delete(transcription::TRANSCRIPTION_API_KEY)?;
+            }
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// A project directory attached to a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAttachment {
+    pub scan: ProjectScan,
+    pub workspace: Workspace,
+    /// Directory the server opened; `None` when it could not be reached
+    pub server_directory: Option<String>,
+}
+
+/// Preview what attaching `directory` would expose, without attaching it
+#[tauri::command]
+async fn scan_project_directory(
+    directory: String,
+    exclude: Option<Vec<String>>,
+) -> Result<ProjectScan, CommandError> {
+    let exclude = exclude.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        project_context::scan(std::path::Path::new(&directory), &exclude)
+    })
+    .await
+    .map_err(|e| CommandError::internal("Project scan failed").with_details(e.to_string()))?
+    .map_err(CommandError::from)
+}
+
+/// Point the server at a local project directory and file the session
+/// under that directory's workspace so the agent works against the code.
+/// The server must be able to see the same path, so remote servers report
+/// a warning when they resolve it elsewhere.
+#[tauri::command]
+async fn attach_project_directory(
+    app_handle: tauri::AppHandle,
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    session_id: String,
+    directory: String,
+    exclude: Option<Vec<String>>,
+) -> Result<ProjectAttachment, CommandError> {
+    let server_url = ensure_server_connected(&app_handle).await?;
+    let mut scan = scan_project_directory(directory, exclude).await?;
+    let root = scan.root.to_string_lossy().to_string();
+
+    let api_client = api_client_for(&app_handle, server_url).await?;
+    let server_directory = match api_client.open_project_directory(&root).await {
+        Ok(resolved) => {
+            if resolved.as_deref().is_some_and(|resolved| resolved != root) {
+                scan.warnings.push(format!(
+                    "The server opened {} instead; it may not be able to see this machine's files",
+                    resolved.as_deref().unwrap_or_default()
+                ));
+            }
+            resolved
+        }
+        Err(e) => {
+            warn!(target: "session", directory = %root, "Server could not open project: {}", e);
+            scan.warnings
+                .push(format!("The server could not open the directory: {}", e));
+            None
+        }
+    };
+
+    let guard = workspace_state.0.lock().await;
+    let workspaces = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Workspaces"))?;
+    let workspace = match workspaces.find_by_directory(&scan.root) {
+        Some(workspace) => workspace,
+        None => {
+            let name = scan
+                .root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.clone());
+            workspaces.create(&name, &scan.root, None)?
+        }
+    };
+    let workspace = workspaces.add_session(&workspace.id, &session_id)?;
+
+    info!(
+        target: "session",
+        session_id = %session_id,
+        directory = %root,
+        files = scan.file_count,
+        bytes = scan.total_bytes,
+        "Attached project directory"
+    );
+    Ok(ProjectAttachment {
+        scan,
+        workspace,
+        server_directory,
+    })
+}
+
+/// Unpack a zip attachment; never into the app's config directory
+#[tauri::command]
+async fn extract_attachment_archive(
+    path: String,
+    destination: String,
+) -> Result<Vec<String>, CommandError> {
+    let (attachment, contents) = attachments::validate_file(std::path::Path::new(&path))?;
+    if attachment.mime_type != "application/zip" {
+        return Err(CommandError::validation(
+            "Only zip archives can be extracted",
+        ));
+    }
+
+    let protected = vec![get_config_dir()?];
+    let extracted =
+        attachments::extract_zip(&contents, std::path::Path::new(&destination), &protected)?;
+    info!(target: "chat", archive = %attachment.name, files = extracted.len(), "Extracted attachment archive");
+    Ok(extracted
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Error counts by code and module over the last hour, day, week or month
+#[tauri::command]
+async fn get_error_summary(period: Option<SummaryPeriod>) -> Result<ErrorSummary, CommandError> {
+    error_stats::summary(period.unwrap_or_default())
+        .ok_or_else(|| CommandError::not_initialized("Error statistics"))
+}
+
+/// Recent health checks of a connection, oldest first, for a latency graph
+#[tauri::command]
+async fn get_connection_health_history(
+    connection_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<HealthCheck>, CommandError> {
+    health_history::history(&connection_id, limit)
+        .ok_or_else(|| CommandError::not_initialized("Health history"))
+}
+
+/// Uptime and latency of a connection over the last `hours`, a day by
+/// default
+#[tauri::command]
+async fn get_connection_uptime_stats(
+    connection_id: String,
+    hours: Option<u32>,
+) -> Result<UptimeStats, CommandError> {
+    health_history::uptime(
+        &connection_id,
+        hours.unwrap_or(health_history::DEFAULT_UPTIME_HOURS),
+    )
+    .ok_or_else(|| CommandError::not_initialized("Health history"))
+}
+
+/// Everything the next telemetry upload would contain, so users can check
+/// it before or after opting in
+#[tauri::command]
+async fn get_collected_telemetry() -> Result<TelemetryReport, CommandError> {
+    telemetry::recorder()
+        .map(|telemetry| telemetry.collected())
+        .ok_or_else(|| CommandError::not_initialized("Telemetry"))
+}
+
+/// Opt in to or out of telemetry uploads
+#[tauri::command]
+async fn set_telemetry_enabled(
+    settings_state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<bool, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| s.privacy.telemetry_enabled = enabled)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save telemetry settings")
+                .with_details(e.to_string())
+        })?;
+    info!(target: "init", enabled, "Telemetry preference changed");
+    Ok(updated.privacy.telemetry_enabled)
+}
+
+/// Discard the locally collected counts
+#[tauri::command]
+async fn clear_collected_telemetry() -> Result<TelemetryReport, CommandError> {
+    let telemetry =
+        telemetry::recorder().ok_or_else(|| CommandError::not_initialized("Telemetry"))?;
+    telemetry.clear().map_err(|e| {
+        CommandError::file_system("Failed to clear telemetry").with_details(e.to_string())
+    })?;
+    Ok(telemetry.collected())
+}
+
+#[tauri::command]
+async fn get_retry_policy(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<RetrySettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().retry)
+}
+
+/// Replace the global retry policy and its per-operation overrides
+#[tauri::command]
+async fn set_retry_policy(
+    settings_state: tauri::State<'_, SettingsState>,
+    policy: RetrySettings,
+) -> Result<RetrySettings, CommandError> {
+    policy.validate().map_err(CommandError::validation)?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings.update(|s| s.retry = policy.clone()).map_err(|e| {
+        CommandError::file_system("Failed to save retry policy").with_details(e.to_string())
+    })?;
+    error::set_retry_settings(policy);
+
+    info!(target: "retry", "Retry policy updated");
+    Ok(updated.retry)
+}
+
+#[tauri::command]
+async fn get_log_targets(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<BTreeMap<String, String>, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().logging.targets)
+}
+
+/// Override the level of a single target; `None` falls back to the global level
+#[tauri::command]
+async fn set_log_target_level(
+    settings_state: tauri::State<'_, SettingsState>,
+    target: String,
+    level: Option<String>,
+) -> Result<(), CommandError> {
+    let target = logging::parse_target(&target).map_err(CommandError::validation)?;
+    let level = level
+        .map(|l| logging::parse_level(&l))
+        .transpose()
+        .map_err(CommandError::validation)?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let mut logging_settings = settings.get().logging;
+    match &level {
+        Some(level) => logging_settings
+            .targets
+            .insert(target.clone(), level.clone()),
+        None => logging_settings.targets.remove(&target),
+    };
+    logging::set_filter(&logging_settings.level, &logging_settings.targets)
+        .map_err(CommandError::validation)?;
+    settings
+        .update(|s| s.logging = logging_settings)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save log targets").with_details(e.to_string())
+        })?;
+
+    info!(target: "logs", "Log level for {} set to {:?}", target, level);
+    Ok(())
+}
+
+/// Connection state and server health for support bundles; failures are
+/// recorded rather than returned so the bundle is still produced offline
+async fn collect_server_diagnostics(
+    connection_state: &tauri::State<'_, ConnectionManagerState>,
+) -> serde_json::Value {
+    let (status, current_connection, server_url) = {
+        let guard = connection_state.0.lock().await;
+        match guard.as_ref() {
+            Some(cm) => (
+                Some(cm.get_connection_status()),
+                cm.get_current_connection(),
+                cm.get_server_url(),
+            ),
+            None => (None, None, None),
+        }
+    };
+
+    let mut diagnostics = serde_json::json!({
+        "connection_status": status,
+        "current_connection": current_connection,
+    });
+
+    if let Some(url) = server_url {
+        let api_client = match ApiClient::new() {
+            Ok(client) => client,
+            Err(e) => {
+                diagnostics["api_client_error"] = serde_json::json!(e.to_string());
+                return diagnostics;
+            }
+        };
+        if let Err(e) = api_client.set_server_url(url.clone()).await {
+            diagnostics["api_client_error"] = serde_json::json!(e.to_string());
+            return diagnostics;
+        }
+        attach_connection_secret(&api_client, &url).await;
+
+        diagnostics["health"] = match api_client.get_health().await {
+            Ok(health) => serde_json::json!(health),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        diagnostics["server_info"] = match api_client.get_server_info().await {
+            Ok(info) => serde_json::json!(info),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+    }
+
+    diagnostics
+}
+
+#[tauri::command]
+async fn export_support_bundle(
+    event_bridge_state: tauri::State<'_, EventBridgeState>,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    destination: Option<String>,
+) -> Result<String, CommandError> {
+    info!(target: "logs", "Exporting support bundle...");
+
+    let config_dir = get_config_dir()?;
+
+    let recent_events = {
+        let guard = event_bridge_state.0.lock().await;
+        match guard.as_ref() {
+            Some(bridge) => bridge
+                .recent_events()
+                .await
+                .iter()
+                .filter_map(|event| serde_json::to_value(event).ok())
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    let input = SupportBundleInput {
+        recent_events,
+        server_diagnostics: collect_server_diagnostics(&connection_state).await,
+    };
+
+    let output = match destination.map(std::path::PathBuf::from) {
+        Some(path) if path.is_dir() => path.join(support_bundle::bundle_file_name()),
+        Some(path) => path,
+        None => dirs::download_dir()
+            .unwrap_or_else(|| config_dir.join("support"))
+            .join(support_bundle::bundle_file_name()),
+    };
+
+    let path = support_bundle::write_bundle(&config_dir, &input, &output).map_err(|e| {
+        CommandError::file_system("Failed to export support bundle").with_details(e.to_string())
+    })?;
+
+    info!(target: "logs", "Support bundle written to {}", path.display());
+    telemetry::record(Feature::SupportBundleExported);
+    audit_log::record(
+        AuditAction::DataExported,
+        "support_bundle",
+        Some(path.display().to_string()),
+    );
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Backups hold plaintext profile data, so they are refused while an
+/// encrypted profile is still locked
+fn ensure_profile_unlocked(profile_state: &ProfileState) -> Result<(), CommandError> {
+    if !*profile_state.unlocked.borrow() {
+        return Err(CommandError::validation(
+            "Unlock the encrypted profile before backing up or restoring",
+        ));
+    }
+    Ok(())
+}
+
+/// Package settings, model preferences, connections and cached sessions
+/// into a versioned archive. Secrets are included only with
+/// `include_secrets`, encrypted with `passphrase`.
+#[tauri::command]
+async fn create_backup(
+    settings_state: tauri::State<'_, SettingsState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    path: String,
+    include_secrets: Option<bool>,
+    passphrase: Option<String>,
+) -> Result<BackupSummary, CommandError> {
+    ensure_profile_unlocked(&profile_state)?;
+    let config_dir = get_config_dir()?;
+    let settings = {
+        let guard = settings_state.0.lock().await;
+        guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Settings"))?
+            .get()
+    };
+
+    let secrets = if include_secrets.unwrap_or(false) {
+        let passphrase = passphrase.as_deref().ok_or_else(|| {
+            CommandError::validation("A passphrase is required to back up secrets")
+        })?;
+        let values = with_secret_store(|store| {
+            let mut values = BTreeMap::new();
+            for info in store.list()? {
+                if info.backend == SecretBackend::Environment {
+                    continue;
+                }
+                if let Some(value) = store.get(&info.name)? {
+                    values.insert(info.name, value);
+                }
+            }
+            Ok(values)
+        })
+        .await?;
+        Some((passphrase, values))
+    } else {
+        None
+    };
+
+    let output = std::path::PathBuf::from(&path);
+    let manifest = backup::write_backup(
+        &config_dir,
+        &settings,
+        secrets
+            .as_ref()
+            .map(|(passphrase, values)| (*passphrase, values)),
+        &output,
+    )?;
+    let secret_count = secrets.as_ref().map_or(0, |(_, values)| values.len());
+
+    info!(target: "init", path = %output.display(), secrets = secret_count, "Backup created");
+    audit_log::record(
+        AuditAction::DataExported,
+        "backup",
+        Some(format!("{} ({} secrets)", output.display(), secret_count)),
+    );
+    Ok(BackupSummary::new(&output, &manifest, secret_count))
+}
+
+/// Write settings, model preferences and connection endpoints to `path` for
+/// a teammate to import. Nothing that grants access is included.
+#[tauri::command]
+async fn export_config_profile(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    path: String,
+) -> Result<ConfigProfileSummary, CommandError> {
+    ensure_profile_unlocked(&profile_state)?;
+    let settings = {
+        let guard = settings_state.0.lock().await;
+        guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Settings"))?
+            .get()
+    };
+    let connections = {
+        let guard = get_connection_manager(&connection_state, Some(app_handle)).await?;
+        guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Connection manager"))?
+            .get_saved_connections()
+    };
+
+    let output = std::path::PathBuf::from(&path);
+    let summary = ConfigProfile::new(&settings, &connections).write(&output)?;
+    info!(
+        target: "init",
+        path = %output.display(),
+        connections = summary.connection_count,
+        "Configuration profile exported"
+    );
+    audit_log::record(
+        AuditAction::DataExported,
+        "config_profile",
+        Some(output.display().to_string()),
+    );
+    Ok(summary)
+}
+
+/// Write the saved connections and their profiles to `path` for importing
+/// on another machine. API keys and proxy passwords are not included.
+#[tauri::command]
+async fn export_connections(
+    app_handle: tauri::AppHandle,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    path: String,
+) -> Result<ConnectionsExportSummary, CommandError> {
+    ensure_profile_unlocked(&profile_state)?;
+    let connections = {
+        let guard = get_connection_manager(&connection_state, Some(app_handle)).await?;
+        guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Connection manager"))?
+            .get_saved_connections()
+    };
+
+    let output = std::path::PathBuf::from(&path);
+    let summary = ConnectionsExport::new(&connections).write(&output)?;
+    info!(
+        target: "connection",
+        path = %output.display(),
+        connections = summary.connection_count,
+        "Connections exported"
+    );
+    audit_log::record(
+        AuditAction::DataExported,
+        "connections",
+        Some(output.display().to_string()),
+    );
+    Ok(summary)
+}
+
+/// Save the connections from a file written by `export_connections`, or
+/// from another machine's `server_connections.json`. A name that is
+/// already saved is skipped, overwritten or renamed per `on_conflict`.
+#[tauri::command]
+async fn import_connections(
+    app_handle: tauri::AppHandle,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    path: String,
+    on_conflict: Option<ConflictResolution>,
+) -> Result<ConnectionImportSummary, CommandError> {
+    ensure_profile_unlocked(&profile_state)?;
+    let import_path = std::path::PathBuf::from(&path);
+    let export =
+        tauri::async_runtime::spawn_blocking(move || ConnectionsExport::read(&import_path))
+            .await
+            .map_err(|e| CommandError::internal(format!("Import task failed: {}", e)))??;
+
+    let mut guard = get_connection_manager(&connection_state, Some(app_handle)).await?;
+    let connection_manager = guard
+        .as_mut()
+        .ok_or_else(|| CommandError::not_initialized("Connection manager"))?;
+    connection_manager
+        .load_connections()
+        .await
+        .map_err(CommandError::file_system)?;
+    let (to_save, summary) = connection_transfer::plan_import(
+        &connection_manager.get_saved_connections(),
+        export.connections,
+        on_conflict.unwrap_or_default(),
+    );
+    for connection in to_save {
+        connection_manager
+            .save_connection(connection)
+            .await
+            .map_err(CommandError::file_system)?;
+    }
+
+    info!(target: "connection", path = %path, ?summary, "Imported connections");
+    audit_log::record(
+        AuditAction::DataImported,
+        "connections",
+        Some(format!(
+            "{}: {} added, {} overwritten, {} renamed",
+            path,
+            summary.imported.len(),
+            summary.overwritten.len(),
+            summary.renamed.len()
+        )),
+    );
+    Ok(summary)
+}
+
+/// Restore a backup made by `create_backup`. The archive is verified before
+/// anything is written. Security settings on this machine are kept, and
+/// secrets are restored only when `passphrase` is given.
+#[tauri::command]
+async fn restore_backup(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<BackupSummary, CommandError> {
+    ensure_profile_unlocked(&profile_state)?;
+    let config_dir = get_config_dir()?;
+    let input = std::path::PathBuf::from(&path);
+    let contents = backup::read_backup(&input, passphrase.as_deref())?;
+
+    // Drop cached managers so they reload the restored files on next use
+    if let Some(mut manager) = connection_state.0.lock().await.take() {
+        if let Err(e) = manager.disconnect_from_server().await {
+            debug!(target: "connection", "Disconnect before restore failed: {}", e);
+        }
+    }
+    backup::restore_files(&config_dir, &contents.files)?;
+    // Reloading also drops session saves still pending from before
+    if let Some(session_manager) = app_handle
+        .state::<SessionManagerState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+    {
+        if let Err(e) = session_manager.load_sessions().await {
+            warn!(target: "init", "Failed to reload restored sessions: {}", e);
+        }
+    }
+
+    let updated = {
+        let guard = settings_state.0.lock().await;
+        let manager = guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+        manager
+            .update(|current| *current = current.merge_user_settings(contents.settings.clone()))
+            .map_err(|e| {
+                CommandError::file_system("Failed to restore settings").with_details(e.to_string())
+            })?
+    };
+    apply_settings(&updated)?;
+
+    if !contents.secrets.is_empty() {
+        let secrets = contents.secrets.clone();
+        with_secret_store(move |store| {
+            for (name, value) in &secrets {
+                store.set(name, value)?;
+            }
+            Ok(())
+        })
+        .await?;
+    }
+
+    app_handle.state::<TrayState>().0.replace(Vec::new());
+    refresh_tray(&app_handle).await;
+
+    info!(
+        target: "init",
+        path = %input.display(),
+        created_at = %contents.manifest.created_at,
+        secrets = contents.secrets.len(),
+        "Backup restored"
+    );
+    audit_log::record(
+        AuditAction::DataImported,
+        "backup",
+        Some(input.display().to_string()),
+    );
+    Ok(BackupSummary::new(
+        &input,
+        &contents.manifest,
+        contents.secrets.len(),
+    ))
+}
+
+/// Capture connection state, recent events and errors alongside the user's
+/// description, optionally with a pre-filled GitHub issue link
+#[tauri::command]
+async fn report_problem(
+    event_bridge_state: tauri::State<'_, EventBridgeState>,
+    connection_state: tauri::State<'_, ConnectionManagerState>,
+    subsystem_state: tauri::State<'_, SubsystemRegistryState>,
+    description: String,
+    include_issue_url: Option<bool>,
+) -> Result<ProblemReport, CommandError> {
+    if description.trim().is_empty() {
+        return Err(CommandError::validation(
+            "Problem description cannot be empty",
+        ));
+    }
+
+    let config_dir = get_config_dir()?;
+    let mut report = ProblemReport::new(&description);
+    report.connection = collect_server_diagnostics(&connection_state).await;
+    report.subsystems = subsystem_state.0.statuses();
+    report.recent_events = {
+        let guard = event_bridge_state.0.lock().await;
+        match guard.as_ref() {
+            Some(bridge) => bridge
+                .recent_events()
+                .await
+                .iter()
+                .filter_map(|event| serde_json::to_value(event).ok())
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+    report.recent_errors = problem_report::recent_errors(&config_dir);
+    report.error_summary = error_stats::summary(SummaryPeriod::Day);
+
+    let mut report = report.redacted();
+    if include_issue_url.unwrap_or(true) {
+        report.issue_url = Some(report.build_issue_url());
+    }
+
+    let path = report.save(&config_dir).map_err(|e| {
+        CommandError::file_system("Failed to save problem report").with_details(e.to_string())
+    })?;
+
+    info!(target: "logs", report_id = %report.id, "Problem report saved to {}", path.display());
+    audit_log::record(
+        AuditAction::DataExported,
+        "problem_report",
+        Some(path.display().to_string()),
+    );
+    Ok(report)
+}
+
+/// Security audit entries, newest last, with the result of verifying the
+/// hash chain
+#[tauri::command]
+async fn get_security_audit_log(limit: Option<usize>) -> Result<AuditLogReport, CommandError> {
+    let report = audit_log::report(limit)?;
+    if !report.verified {
+        warn!(
+            target: "security",
+            line = ?report.first_invalid_line,
+            "Security audit log failed verification"
+        );
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProfileEncryptionStatus {
+    enabled: bool,
+    locked: bool,
+}
+
+#[tauri::command]
+async fn list_secrets() -> Result<SecretStoreStatus, CommandError> {
+    with_secret_store(|store| store.status()).await
+}
+
+/// Save a connection's API key in the OS keychain, or the encrypted file
+/// when there is none. Requests to that connection send it from then on.
+#[tauri::command]
+async fn store_connection_secret(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    api_key: String,
+) -> Result<SecretBackend, CommandError> {
+    if connection_id.trim().is_empty() {
+        return Err(CommandError::validation("Connection ID cannot be empty"));
+    }
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err(CommandError::validation("API key cannot be empty"));
+    }
+
+    let name = secrets::connection_api_key(&connection_id);
+    let api_key = api_key.to_string();
+    let backend = with_secret_store(move |store| store.set(&name, &api_key)).await?;
+    info!(target: "secrets", connection = %connection_id, ?backend, "Stored connection API key");
+    refresh_connection_secret(&app_handle, &connection_id).await;
+    Ok(backend)
+}
+
+/// Hand the stored proxy passwords to the proxy routes
+fn load_proxy_passwords() {
+    let result = secret_store().and_then(|store| {
+        for info in store.list()? {
+            if let Some(scope) = secrets::proxy_of_password(&info.name) {
+                proxy::set_password(scope, store.get(&info.name)?);
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        warn!(target: "secrets", "Failed to load proxy passwords: {}", e);
+    }
+}
+
+/// Save the password of the global proxy, or of a connection's proxy when
+/// `connection_id` is given. No password removes the stored one.
+#[tauri::command]
+async fn store_proxy_password(
+    connection_id: Option<String>,
+    password: Option<String>,
+) -> Result<Option<SecretBackend>, CommandError> {
+    let scope = connection_id.unwrap_or_else(|| proxy::GLOBAL_SCOPE.to_string());
+    if scope.trim().is_empty() {
+        return Err(CommandError::validation("Connection ID cannot be empty"));
+    }
+    let name = secrets::proxy_password(&scope);
+    match password.filter(|password| !password.is_empty()) {
+        Some(password) => {
+            let value = password.clone();
+            let backend = with_secret_store(move |store| store.set(&name, &value)).await?;
+            proxy::set_password(&scope, Some(password));
+            info!(target: "secrets", proxy = %scope, ?backend, "Stored proxy password");
+            Ok(Some(backend))
+        }
+        None => {
+            with_secret_store(move |store| store.delete(&name)).await?;
+            proxy::set_password(&scope, None);
+            info!(target: "secrets", proxy = %scope, "Removed proxy password");
+            Ok(None)
+        }
+    }
+}
+
+/// Send a request through proxy settings before saving them. The target
+/// defaults to the connected server.
+#[tauri::command]
+async fn test_proxy_settings(
+    app_handle: tauri::AppHandle,
+    settings: ProxySettings,
+    password: Option<String>,
+    target_url: Option<String>,
+) -> Result<ProxyTestResult, CommandError> {
+    let target_url = match target_url {
+        Some(url) => url,
+        None => ensure_server_connected(&app_handle)
+            .await
+            .map_err(|_| CommandError::validation("No server to test the proxy against"))?,
+    };
+    proxy::test(&settings, password.as_deref(), &target_url)
+        .await
+        .map_err(CommandError::validation)
+}
+
+/// The API key stored for a connection, if any
+#[tauri::command]
+async fn get_connection_secret(connection_id: String) -> Result<Option<String>, CommandError> {
+    let name = secrets::connection_api_key(&connection_id);
+    with_secret_store(move |store| store.get(&name)).await
+}
+
+#[tauri::command]
+async fn rotate_connection_credentials(
+    app_handle: tauri::AppHandle,
+    connection_id: String,
+    new_key: Option<String>,
+    grace_minutes: Option<i64>,
+) -> Result<SecretRotation, CommandError> {
+    let grace_minutes = grace_minutes.unwrap_or(secrets::DEFAULT_ROTATION_GRACE_MINUTES);
+    if grace_minutes < 0 {
+        return Err(CommandError::validation("Grace window cannot be negative"));
+    }
+
+    let name = secrets::connection_api_key(&connection_id);
+    let rotation = with_secret_store(move |store| {
+        store.rotate(&name, new_key, chrono::Duration::minutes(grace_minutes))
+    })
+    .await?;
+    info!(
+        target: "secrets",
+        connection = %connection_id,
+        backend = ?rotation.backend,
+        previous_valid_until = ?rotation.previous_valid_until,
+        "Rotated connection credentials"
+    );
+    refresh_connection_secret(&app_handle, &connection_id).await;
+    Ok(rotation)
+}
+
+#[tauri::command]
+async fn delete_secret(app_handle: tauri::AppHandle, name: String) -> Result<bool, CommandError> {
+    let secret = name.clone();
+    let deleted = with_secret_store(move |store| store.delete(&secret)).await?;
+    info!(target: "secrets", secret = %name, deleted, "Deleted secret");
+    if let Some(connection_id) = secrets::connection_of_api_key(&name) {
+        refresh_connection_secret(&app_handle, connection_id).await;
+    }
+    Ok(deleted)
+}
+
+#[tauri::command]
+async fn get_profile_encryption_status(
+    settings_state: tauri::State<'_, SettingsState>,
+    profile_state: tauri::State<'_, ProfileState>,
+) -> Result<ProfileEncryptionStatus, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let enabled = settings.get().security.encrypted_profile.is_some();
+    let locked = enabled && profile_state.key.lock().await.is_none();
+    Ok(ProfileEncryptionStatus { enabled, locked })
+}
+
+/// Store the profile in an encrypted container from the next exit onwards
+#[tauri::command]
+async fn enable_profile_encryption(
+    settings_state: tauri::State<'_, SettingsState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    passphrase: String,
+) -> Result<ProfileEncryptionStatus, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    if settings.get().security.encrypted_profile.is_some() {
+        return Err(CommandError::validation(
+            "Profile encryption is already enabled",
+        ));
+    }
+
+    let (key, key_settings) =
+        tauri::async_runtime::spawn_blocking(move || ProfileKey::create(&passphrase))
+            .await
+            .map_err(|e| CommandError::internal(format!("Key derivation task failed: {}", e)))??;
+    settings
+        .update(|s| s.security.encrypted_profile = Some(key_settings))
+        .map_err(|e| {
+            CommandError::file_system("Failed to save encryption settings")
+                .with_details(e.to_string())
+        })?;
+    *profile_state.key.lock().await = Some(key);
+
+    info!(target: "init", "Profile encryption enabled");
+    Ok(ProfileEncryptionStatus {
+        enabled: true,
+        locked: false,
+    })
+}
+
+/// Derive the profile key from the passphrase and restore the sealed files
+#[tauri::command]
+async fn unlock_profile(
+    settings_state: tauri::State<'_, SettingsState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    passphrase: String,
+) -> Result<ProfileEncryptionStatus, CommandError> {
+    let key_settings = {
+        let guard = settings_state.0.lock().await;
+        let settings = guard
+            .as_ref()
+            .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+        settings
+            .get()
+            .security
+            .encrypted_profile
+            .ok_or_else(|| CommandError::validation("Profile encryption is not enabled"))?
+    };
+
+    let mut key_guard = profile_state.key.lock().await;
+    if key_guard.is_none() {
+        let key = tauri::async_runtime::spawn_blocking(move || {
+            ProfileKey::unlock(&passphrase, &key_settings)
+        })
+        .await
+        .map_err(|e| CommandError::internal(format!("Key derivation task failed: {}", e)))??;
+
+        let vault = ProfileVault::new(get_config_dir()?);
+        if vault.is_open() {
+            // The last session ended without sealing, so its plaintext is
+            // newer than the vault; seal it before carrying on
+            let sealed = vault.seal(&key)?;
+            warn!(target: "init", "Resealed {} files left unencrypted by the last session", sealed);
+        }
+        if vault.is_sealed() {
+            let restored = vault.open(&key)?;
+            info!(target: "init", "Restored {} files from the encrypted profile", restored);
+        }
+        *key_guard = Some(key);
+    }
+    profile_state.unlocked.send_replace(true);
+
+    Ok(ProfileEncryptionStatus {
+        enabled: true,
+        locked: false,
+    })
+}
+
+/// Turn encryption off; files stay in plaintext from now on
+#[tauri::command]
+async fn disable_profile_encryption(
+    settings_state: tauri::State<'_, SettingsState>,
+    profile_state: tauri::State<'_, ProfileState>,
+    passphrase: String,
+) -> Result<ProfileEncryptionStatus, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let key_settings = settings
+        .get()
+        .security
+        .encrypted_profile
+        .ok_or_else(|| CommandError::validation("Profile encryption is not enabled"))?;
+
+    let key = tauri::async_runtime::spawn_blocking(move || {
+        ProfileKey::unlock(&passphrase, &key_settings)
+    })
+    .await
+    .map_err(|e| CommandError::internal(format!("Key derivation task failed: {}", e)))??;
+
+    let vault = ProfileVault::new(get_config_dir()?);
+    if vault.is_sealed() {
+        vault.open(&key)?;
+    }
+    vault.remove().map_err(|e| {
+        CommandError::file_system("Failed to remove the profile vault").with_details(e.to_string())
+    })?;
+    settings
+        .update(|s| s.security.encrypted_profile = None)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save encryption settings")
+                .with_details(e.to_string())
+        })?;
+    *profile_state.key.lock().await = None;
+    profile_state.unlocked.send_replace(true);
+
+    info!(target: "init", "Profile encryption disabled");
+    Ok(ProfileEncryptionStatus {
+        enabled: false,
+        locked: false,
+    })
+}
+
+#[tauri::command]
+fn get_app_lock_status(app_lock: tauri::State<'_, AppLockState>) -> AppLockStatus {
+    app_lock.0.status()
+}
+
+/// Called by the frontend on user input so the idle timer restarts
+#[tauri::command]
+fn report_user_activity(app_lock: tauri::State<'_, AppLockState>) {
+    app_lock.0.record_activity();
+}
+
+/// Turn on the idle lock, or change its PIN or timeout (`current_pin` is
+/// required when a lock is already configured)
+#[tauri::command]
+async fn enable_app_lock(
+    settings_state: tauri::State<'_, SettingsState>,
+    app_lock: tauri::State<'_, AppLockState>,
+    pin: String,
+    idle_timeout_minutes: u32,
+    current_pin: Option<String>,
+) -> Result<AppLockStatus, CommandError> {
+    if idle_timeout_minutes == 0 {
+        return Err(CommandError::validation(
+            "Idle timeout must be at least one minute",
+        ));
+    }
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    if let Some(existing) = settings.get().security.app_lock {
+        let current_pin = current_pin.unwrap_or_default();
+        let pin_hash = existing.pin_hash.clone();
+        let verified = tauri::async_runtime::spawn_blocking(move || {
+            app_lock::verify_pin(&current_pin, &pin_hash)
+        })
+        .await
+        .map_err(|e| CommandError::internal(format!("PIN check failed: {}", e)))?;
+        if !verified {
+            return Err(CommandError::new(
+                error::ErrorCode::Auth,
+                "Current PIN is required to change the app lock",
+            ));
+        }
+    }
+
+    let pin_hash = tauri::async_runtime::spawn_blocking(move || app_lock::hash_pin(&pin))
+        .await
+        .map_err(|e| CommandError::internal(format!("PIN hashing failed: {}", e)))??;
+    let lock_settings = AppLockSettings {
+        idle_timeout_minutes,
+        pin_hash,
+    };
+    settings
+        .update(|s| s.security.app_lock = Some(lock_settings.clone()))
+        .map_err(|e| {
+            CommandError::file_system("Failed to save app lock settings")
+                .with_details(e.to_string())
+        })?;
+    app_lock.0.configure(Some(lock_settings));
+
+    info!(target: "init", "App lock enabled with a {} minute idle timeout", idle_timeout_minutes);
+    Ok(app_lock.0.status())
+}
+
+#[tauri::command]
+async fn disable_app_lock(
+    settings_state: tauri::State<'_, SettingsState>,
+    app_lock: tauri::State<'_, AppLockState>,
+    pin: String,
+) -> Result<AppLockStatus, CommandError> {
+    let lock = app_lock.0.clone();
+    tauri::async_runtime::spawn_blocking(move || lock.unlock(&pin))
+        .await
+        .map_err(|e| CommandError::internal(format!("PIN check failed: {}", e)))??;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    settings
+        .update(|s| s.security.app_lock = None)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save app lock settings")
+                .with_details(e.to_string())
+        })?;
+    app_lock.0.configure(None);
+
+    info!(target: "init", "App lock disabled");
+    Ok(app_lock.0.status())
+}
+
+#[tauri::command]
+fn lock_app(app_lock: tauri::State<'_, AppLockState>) -> AppLockStatus {
+    app_lock.0.lock();
+    app_lock.0.status()
+}
+
+#[tauri::command]
+async fn unlock_app(
+    app_lock: tauri::State<'_, AppLockState>,
+    pin: String,
+) -> Result<AppLockStatus, CommandError> {
+    let lock = app_lock.0.clone();
+    tauri::async_runtime::spawn_blocking(move || lock.unlock(&pin))
+        .await
+        .map_err(|e| CommandError::internal(format!("PIN check failed: {}", e)))??;
+    Ok(app_lock.0.status())
+}
+
+/// Lock the app once the idle timeout passes and tell the frontend
+async fn run_idle_lock_timer(app_handle: tauri::AppHandle, app_lock: AppLock) {
+    let mut interval = tokio::time::interval(APP_LOCK_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if app_lock.check_idle() {
+            info!(target: "init", "App locked after inactivity");
+            if let Err(e) = app_handle.emit(app_lock::APP_LOCKED_EVENT, app_lock.status()) {
+                warn!(target: "init", "Failed to emit app-locked event: {}", e);
+            }
+        }
+    }
+}
+
+// Model configuration commands
+#[tauri::command]
+async fn get_available_models(
+    app_handle: tauri::AppHandle,
+    page: Option<PageRequest>,
+) -> Result<Page<serde_json::Value>, CommandError> {
+    let models = available_models(&app_handle).await?;
+    Ok(pagination::paginate(models, page))
+}
+
+/// Models from the server, or the cached list when it can't be reached
+async fn available_models(
+    app_handle: &tauri::AppHandle,
+) -> Result<Vec<serde_json::Value>, CommandError> {
+    info!(target: "models", "Getting available models...");
+
+    if let Some(models) = app_handle.state::<PrefetchState>().0.models() {
+        info!(target: "models", "Using {} prefetched models", models.len());
+        return Ok(models);
+    }
+
+    let config_dir = get_config_dir()?;
+    let api_client = shared_api_client(app_handle).await?;
+    let model_manager = ModelManager::new(api_client, config_dir);
+
+    // Try to fetch from server first - convert to Send-safe type immediately
+    let server_result: Result<Vec<serde_json::Value>, String> = model_manager
+        .fetch_available_models()
+        .await
+        .map(|models| {
+            models
+                .into_iter()
+                .map(|m| serde_json::to_value(m).unwrap_or_default())
+                .collect()
+        })
+        .map_err(|e| e.to_string());
+
+    match server_result {
+        Ok(models_json) => {
+            info!(target: "models", "Retrieved {} models from server", models_json.len());
+            Ok(models_json)
+        }
+        Err(server_err) => {
+            warn!(target: "models", "Failed to fetch from server: {}", server_err);
+
+            // Fallback to cached models
+            match model_manager.get_available_models().await {
+                Ok(cached_models) => {
+                    let models_json: Vec<serde_json::Value> = cached_models
+                        .into_iter()
+                        .map(|m| serde_json::to_value(m).unwrap_or_default())
+                        .collect();
+                    info!(target: "models", "Retrieved {} cached models", models_json.len());
+                    Ok(models_json)
+                }
+                Err(cache_err) => {
+                    error!(target: "models", "Failed to get cached models: {}", cache_err);
+                    Err(CommandError::from(cache_err))
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_model_preferences(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<serde_json::Value, CommandError> {
+    info!(target: "models", "Getting model preferences...");
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let preferences = settings.get().models;
+
+    let preferences_json = serde_json::to_value(&preferences).map_err(|e| {
+        CommandError::data("Failed to serialize preferences").with_details(e.to_string())
+    })?;
+
+    info!(target: "models", "Retrieved model preferences");
+    Ok(preferences_json)
+}
+
+#[tauri::command]
+async fn set_model_preferences(
+    settings_state: tauri::State<'_, SettingsState>,
+    preferences: serde_json::Value,
+) -> Result<(), CommandError> {
+    info!(target: "models", "Setting model preferences...");
+
+    let model_preferences: ModelPreferences = serde_json::from_value(preferences).map_err(|e| {
+        CommandError::data("Failed to parse preferences").with_details(e.to_string())
+    })?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    settings
+        .update(|s| s.models = model_preferences)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save model preferences")
+                .with_details(e.to_string())
+        })?;
+
+    info!(target: "models", "Updated model preferences");
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_default_model(
+    settings_state: tauri::State<'_, SettingsState>,
+    provider_id: String,
+    model_id: String,
+) -> Result<(), CommandError> {
+    info!(target: "models", "Setting default model: {}/{}", provider_id, model_id);
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    settings
+        .update(|s| {
+            s.models.default_provider = Some(provider_id);
+            s.models.default_model = Some(model_id);
+        })
+        .map_err(|e| {
+            CommandError::file_system("Failed to save default model").with_details(e.to_string())
+        })?;
+
+    info!(target: "models", "Updated default model");
+    Ok(())
+}
+
+// Enhanced session management commands
+#[tauri::command]
+async fn delete_session(
+    app_handle: tauri::AppHandle,
+    tray_state: tauri::State<'_, TrayState>,
+    workspace_state: tauri::State<'_, WorkspaceState>,
+    session_id: String,
+    connection_id: Option<String>,
+) -> Result<(), CommandError> {
+    info!(target: "session", session_id = %session_id, "Deleting session");
+
+    messaging_for(&app_handle, connection_id.as_deref())
+        .await?
+        .delete_session(&session_id)
+        .await?;
+    app_handle.state::<PrefetchState>().0.forget_sessions();
+    tray_state.0.remove(&session_id);
+    refresh_tray(&app_handle).await;
+    close_windows_for_session(&app_handle, &session_id);
+    if let Some(workspaces) = workspace_state.0.lock().await.as_ref() {
+        if let Err(e) = workspaces.remove_session(&session_id) {
+            warn!(target: "session", "Failed to remove session from workspace: {}", e);
+        }
+    }
+    telemetry::record(Feature::SessionDeleted);
+    dispatch_plugin_event(
+        &app_handle,
+        PluginEvent::SessionDeleted,
+        serde_json::json!({ "session_id": session_id }),
+    );
+
+    info!(target: "session", session_id = %session_id, "Deleted session");
+    Ok(())
+}
+
+#[tauri::command]
+async fn update_session_title(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    title: String,
+    connection_id: Option<String>,
+) -> Result<(), CommandError> {
+    info!(target: "session", session_id = %session_id, title = %title, "Updating session title");
+
+    messaging_for(&app_handle, connection_id.as_deref())
+        .await?
+        .rename_session(&session_id, &title)
+        .await?;
+
+    info!(target: "session", session_id = %session_id, "Updated session title");
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_session_stats(
+    session_manager_state: tauri::State<'_, SessionManagerState>,
+    session_id: String,
+) -> Result<serde_json::Value, CommandError> {
+    info!(target: "session", session_id = %session_id, "Getting session stats");
+
+    let guard = session_manager_state.0.lock().await;
+    let session_manager = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Session manager"))?;
+    let stats = session_manager.get_session_stats(&session_id).await?;
+
+    let stats_json = serde_json::to_value(&stats)
+        .map_err(|e| CommandError::data("Failed to serialize stats").with_details(e.to_string()))?;
+
+    info!(target: "session", session_id = %session_id, "Retrieved session stats");
+    Ok(stats_json)
+}
+
+// Streaming commands
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn start_message_stream(
+    app_handle: tauri::AppHandle,
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+    outbox_state: tauri::State<'_, OutboxState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    session_id: String,
+    content: String,
+    model_config: Option<ModelConfig>,
+    allow_secrets: Option<bool>,
+) -> Result<String, CommandError> {
+    info!(target: "stream", session_id = %session_id, "Starting message stream");
+
+    // Validate inputs
+    if session_id.trim().is_empty() {
+        return Err(CommandError::validation("Session ID cannot be empty"));
+    }
+    let trimmed_content = content.trim();
+    if trimmed_content.is_empty() {
+        return Err(CommandError::validation("Message content cannot be empty"));
+    }
+    check_outgoing_secrets(
+        &settings_state,
+        trimmed_content,
+        allow_secrets.unwrap_or(false),
+    )
+    .await?;
+    ensure_within_budget(&app_handle, &session_id).await?;
+
+    let journal = journal_state.0.lock().await.clone();
+    match spawn_message_stream(
+        app_handle.clone(),
+        journal,
+        session_id.clone(),
+        trimmed_content.to_string(),
+        model_config.clone(),
+        false,
+    )
+    .await
+    {
+        Err(e) if e.is_unreachable() => Err(queue_in_outbox(
+            &app_handle,
+            &outbox_state,
+            &session_id,
+            trimmed_content,
+            OutboxDelivery::Stream,
+            model_config,
+            e,
+        )
+        .await),
+        result => result,
+    }
+}
+
+/// Streaming client for `api_client` whose streams are registered with the
+/// managed client, so `stop_message_stream` can reach them
+async fn streaming_client_for(
+    app_handle: &tauri::AppHandle,
+    api_client: Arc<ApiClient>,
+) -> Result<StreamingClient, CommandError> {
+    let state = app_handle.state::<StreamingClientState>();
+    let mut guard = state.0.lock().await;
+    let managed = match guard.as_ref() {
+        Some(managed) => managed,
+        None => guard.insert(StreamingClient::new(api_client.clone())?),
+    };
+    Ok(managed.with_api_client(api_client))
+}
+
+/// Start a stream and forward its events to the frontend; shared by the
+/// command, outbox delivery and the quick prompt, which brings the main
+/// window forward once the response is complete
+async fn spawn_message_stream(
+    app_handle: tauri::AppHandle,
+    journal: Option<RecoveryJournal>,
+    session_id: String,
+    content: String,
+    model_config: Option<ModelConfig>,
+    focus_main_on_complete: bool,
+) -> Result<String, CommandError> {
+    // Ensure server connection
+    let server_url = ensure_server_connected(&app_handle).await?;
+    require_feature(&app_handle, ServerFeature::Streaming)?;
+
+    // Create streaming components
+    let config_dir = get_config_dir()?;
+    let api_client = api_client_for(&app_handle, server_url).await?;
+
+    let streaming_client = streaming_client_for(&app_handle, api_client).await?;
+    let event_bridge = EventBridge::with_app_handle(app_handle.clone())
+        .with_window_routing(app_handle.state::<SessionWindowState>().0.clone());
+
+    // Create stream request
+    let stream_request = StreamRequest {
+        session_id: session_id.clone(),
+        content: content.clone(),
+        model_config,
+        system_prompt: None,
+        temperature: None,
+        max_tokens: None,
+    };
+
+    // Journal the prompt until the stream has been accepted
+    let send_id = uuid::Uuid::new_v4().to_string();
+    journal_record(
+        &journal,
+        &JournalRecord::PendingSend {
+            id: send_id.clone(),
+            session_id: session_id.clone(),
+            content: content.clone(),
+            created_at: chrono::Utc::now(),
+        },
+    );
+
+    // Start streaming
+    let result = streaming_client.start_stream(stream_request).await;
+    journal_record(&journal, &JournalRecord::SendFinished { id: send_id });
+    let stream_id = result?;
+    telemetry::record(Feature::MessageSent);
+
+    // Spawn event forwarding task
+    let stream_id_clone = stream_id.clone();
+    let session_id_clone = session_id.clone();
+    let event_bridge_clone = event_bridge.clone();
+    let streaming_client_clone = streaming_client.clone();
+
+    tokio::spawn(resource_usage::tracked(Activity::Stream, async move {
+        let mut receiver = streaming_client_clone.subscribe();
+
+        while let Ok(stream_event) = receiver.recv().await {
+            // Journal streamed content until the message is finished
+            match &stream_event {
+                StreamEvent::Chunk {
+                    session_id,
+                    message_id,
+                    content,
+                    index,
+                } => journal_record(
+                    &journal,
+                    &JournalRecord::StreamChunk {
+                        session_id: session_id.clone(),
+                        message_id: message_id.clone(),
+                        content: content.clone(),
+                        index: *index,
+                    },
+                ),
+                StreamEvent::Complete {
+                    session_id,
+                    message_id,
+                    ..
+                }
+                | StreamEvent::End {
+                    session_id,
+                    message_id,
+                } => journal_record(
+                    &journal,
+                    &JournalRecord::StreamFinished {
+                        session_id: session_id.clone(),
+                        message_id: message_id.clone(),
+                    },
+                ),
+                StreamEvent::Error {
+                    session_id,
+                    message_id: Some(message_id),
+                    ..
+                } => journal_record(
+                    &journal,
+                    &JournalRecord::StreamFinished {
+                        session_id: session_id.clone(),
+                        message_id: message_id.clone(),
+                    },
+                ),
+                _ => {}
+            }
+
+            match &stream_event {
+                StreamEvent::Complete {
+                    session_id,
+                    final_content,
+                    ..
+                } => {
+                    #[cfg(desktop)]
+                    if focus_main_on_complete {
+                        show_main_window(&app_handle);
+                    }
+                    tokio::spawn({
+                        let app_handle = app_handle.clone();
+                        let session_id = session_id.clone();
+                        async move {
+                            emit_context_usage(&app_handle, &session_id).await;
+                            emit_session_diagnostics(&app_handle, &session_id).await;
+                            track_spending(&app_handle, &session_id).await;
+                        }
+                    });
+                    dispatch_plugin_event(
+                        &app_handle,
+                        PluginEvent::ResponseCompleted,
+                        serde_json::json!({
+                            "session_id": session_id,
+                            "content": final_content,
+                        }),
+                    );
+                    notify_stream_outcome(
+                        &app_handle,
+                        NotificationKind::ResponseCompleted,
+                        session_id,
+                        final_content,
+                    )
+                    .await
+                }
+                StreamEvent::PermissionRequested { permission, .. } => {
+                    relay_shell_approval(&app_handle, permission).await
+                }
+                StreamEvent::Error {
+                    session_id, error, ..
+                } => {
+                    dispatch_plugin_event(
+                        &app_handle,
+                        PluginEvent::ResponseFailed,
+                        serde_json::json!({
+                            "session_id": session_id,
+                            "error": error,
+                        }),
+                    );
+                    notify_stream_outcome(
+                        &app_handle,
+                        NotificationKind::ResponseFailed,
+                        session_id,
+                        error,
+                    )
+                    .await
+                }
+                _ => {}
+            }
+
+            if let Err(e) = event_bridge_clone
+                .emit_stream_event(stream_event.clone(), session_id_clone.clone())
+                .await
+            {
+                error!(target: "stream", session_id = %session_id_clone, "Failed to emit event: {}", e);
+            }
+        }
+
+        // Cleanup when stream ends
+        let _ = streaming_client_clone.stop_stream(&stream_id_clone).await;
+    }));
+
+    info!(target: "stream", session_id = %session_id, stream_id = %stream_id, "Started message stream");
+    Ok(stream_id)
+}
+
+/// Raise an OS notification for a finished or failed response when the main
+/// window is in the background, that event type is enabled and neither
+/// focus mode nor quiet hours are holding notifications back
+async fn notify_stream_outcome(
+    app_handle: &tauri::AppHandle,
+    kind: NotificationKind,
+    session_id: &str,
+    text: &str,
+) {
+    let mut settings = {
+        let state = app_handle.state::<SettingsState>();
+        let guard = state.0.lock().await;
+        match guard.as_ref() {
+            Some(settings) => settings.get().notifications,
+            None => return,
+        }
+    };
+    let focused = app_handle
+        .get_webview_window("main")
+        .map(|window| {
+            window.is_focused().unwrap_or(false)
+                && window.is_visible().unwrap_or(true)
+                && !window.is_minimized().unwrap_or(false)
+        })
+        .unwrap_or(false);
+    let now = chrono::Local::now().time();
+    if !notifications::should_notify(&settings, kind, focused, now) {
+        return;
+    }
+    // Keep content off the screen while the app is locked
+    if app_handle.state::<AppLockState>().0.is_locked() {
+        settings.include_snippet = false;
+    }
+
+    let session_title = app_handle
+        .state::<TrayState>()
+        .0
+        .list()
+        .into_iter()
+        .find(|session| session.id == session_id)
+        .and_then(|session| session.title);
+    let notification = notifications::compose(&settings, kind, session_title.as_deref(), text);
+    if let Err(e) = notifications::show(app_handle, &notification) {
+        warn!(target: "stream", session_id = %session_id, "Failed to show notification: {}", e);
+    }
+}
+
+/// Hold a bash permission request from the agent and show it to the user.
+/// Nothing is sent back to the server until `respond_to_shell_approval`.
+async fn relay_shell_approval(app_handle: &tauri::AppHandle, permission: &serde_json::Value) {
+    let Some(mut request) = ShellApprovalRequest::from_permission(permission) else {
+        return;
+    };
+    if request.working_directory.is_none() {
+        request.working_directory = app_handle
+            .state::<WorkspaceState>()
+            .0
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|workspaces| workspaces.workspace_for_session(&request.session_id))
+            .map(|workspace| workspace.directory.to_string_lossy().to_string());
+    }
+
+    info!(
+        target: "stream",
+        session_id = %request.session_id,
+        approval_id = %request.id,
+        risk = ?request.risk.level,
+        "Shell command awaiting approval"
+    );
+    if !app_handle
+        .state::<ShellApprovalState>()
+        .0
+        .insert(request.clone())
+    {
+        return;
+    }
+    if let Err(e) = app_handle.emit(shell_approval::SHELL_APPROVAL_EVENT, request) {
+        warn!(target: "stream", "Failed to emit shell approval request: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn get_notification_settings(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<NotificationSettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().notifications)
+}
+
+#[tauri::command]
+async fn set_notification_settings(
+    settings_state: tauri::State<'_, SettingsState>,
+    notifications: NotificationSettings,
+) -> Result<NotificationSettings, CommandError> {
+    notifications::validate(&notifications).map_err(CommandError::validation)?;
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| s.notifications = notifications)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save notification settings")
+                .with_details(e.to_string())
+        })?;
+    Ok(updated.notifications)
+}
+
+/// Turn do-not-disturb on or off without touching the other notification
+/// settings
+#[tauri::command]
+async fn set_focus_mode(
+    settings_state: tauri::State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<NotificationSettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| s.notifications.focus_mode = enabled)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save notification settings")
+                .with_details(e.to_string())
+        })?;
+    Ok(updated.notifications)
+}
+
+/// Show the floating quick prompt window
+#[tauri::command]
+async fn open_quick_prompt(app_handle: tauri::AppHandle) -> Result<(), CommandError> {
+    #[cfg(desktop)]
+    quick_prompt::open_window(&app_handle).map_err(|e| {
+        CommandError::internal("Failed to open quick prompt").with_details(e.to_string())
+    })?;
+    #[cfg(not(desktop))]
+    let _ = app_handle;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_quick_prompt_settings(
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<QuickPromptSettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().quick_prompt)
+}
+
+#[tauri::command]
+async fn set_quick_prompt_shortcut(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    shortcut: String,
+) -> Result<QuickPromptSettings, CommandError> {
+    let shortcut = quick_prompt::normalize_shortcut(&shortcut).map_err(CommandError::validation)?;
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    #[cfg(desktop)]
+    quick_prompt::register_shortcut(
+        &app_handle,
+        Some(&settings.get().quick_prompt.shortcut),
+        &shortcut,
+    )
+    .map_err(CommandError::validation)?;
+    #[cfg(not(desktop))]
+    let _ = app_handle;
+    let updated = settings
+        .update(|s| s.quick_prompt.shortcut = shortcut)
+        .map_err(|e| {
+            CommandError::file_system("Failed to save quick prompt shortcut")
+                .with_details(e.to_string())
+        })?;
+    Ok(updated.quick_prompt)
+}
+
+/// Stream a prompt typed into the quick prompt window into the scratchpad
+/// session, then hide the window
+#[tauri::command]
+async fn submit_quick_prompt(
+    app_handle: tauri::AppHandle,
+    journal_state: tauri::State<'_, RecoveryJournalState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    tray_state: tauri::State<'_, TrayState>,
+    content: String,
+    allow_secrets: Option<bool>,
+) -> Result<String, CommandError> {
+    let trimmed_content = content.trim();
+    if trimmed_content.is_empty() {
+        return Err(CommandError::validation("Message content cannot be empty"));
+    }
+    check_outgoing_secrets(
+        &settings_state,
+        trimmed_content,
+        allow_secrets.unwrap_or(false),
+    )
+    .await?;
+
+    let session_id = scratchpad_session(&app_handle, &settings_state, &tray_state).await?;
+    ensure_within_budget(&app_handle, &session_id).await?;
+    let journal = journal_state.0.lock().await.clone();
+    let stream_id = spawn_message_stream(
+        app_handle.clone(),
+        journal,
+        session_id.clone(),
+        trimmed_content.to_string(),
+        None,
+        true,
+    )
+    .await?;
+
+    #[cfg(desktop)]
+    quick_prompt::hide_window(&app_handle);
+    info!(target: "chat", session_id = %session_id, "Sent quick prompt");
+    telemetry::record(Feature::QuickPrompt);
+    Ok(stream_id)
+}
+
+/// Id of the scratchpad session, created again if it no longer exists
+async fn scratchpad_session(
+    app_handle: &tauri::AppHandle,
+    settings_state: &tauri::State<'_, SettingsState>,
+    tray_state: &tauri::State<'_, TrayState>,
+) -> Result<String, CommandError> {
+    let saved = settings_state
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .and_then(|settings| settings.get().quick_prompt.scratchpad_session_id);
+
+    let messaging = messaging(app_handle).await?;
+    if let Some(session_id) = saved {
+        let sessions = messaging.list_sessions().await?;
+        if sessions.iter().any(|session| session.id == session_id) {
+            return Ok(session_id);
+        }
+    }
+
+    let session = messaging
+        .create_session(Some(quick_prompt::SCRATCHPAD_TITLE.to_string()))
+        .await?;
+    tray_state.0.touch(TraySession {
+        id: session.id.clone(),
+        title: session.title.clone(),
+    });
+    if let Some(settings) = settings_state.0.lock().await.as_ref() {
+        if let Err(e) =
+            settings.update(|s| s.quick_prompt.scratchpad_session_id = Some(session.id.clone()))
+        {
+            warn!(target: "chat", "Failed to remember scratchpad session: {}", e);
+        }
+    }
+    Ok(session.id)
+}
+
+fn plugin_host(plugin_state: &PluginState) -> Result<&PluginHost, CommandError> {
+    plugin_state
+        .0
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Plugins"))
+}
+
+async fn plugin_settings(
+    settings_state: &tauri::State<'_, SettingsState>,
+) -> Result<settings::PluginSettings, CommandError> {
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    Ok(settings.get().plugins)
+}
+
+/// Close the dedicated windows of a deleted session and stop routing to any
+/// other window that showed it
+fn close_windows_for_session(app_handle: &tauri::AppHandle, session_id: &str) {
+    let windows = &app_handle.state::<SessionWindowState>().0;
+    for label in windows.windows_showing(session_id) {
+        if label.starts_with(session_windows::SESSION_WINDOW_PREFIX) {
+            if let Some(window) = app_handle.get_webview_window(&label) {
+                if let Err(e) = window.close() {
+                    warn!(target: "session", window = %label, "Failed to close session window: {}", e);
+                }
+            }
+        }
+    }
+    windows.release_session(session_id);
+}
+
+/// Deliver an app event to subscribed plugins in the background
+fn dispatch_plugin_event(
+    app_handle: &tauri::AppHandle,
+    event: PluginEvent,
+    payload: serde_json::Value,
+) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(host) = app_handle.state::<PluginState>().0.clone() else {
+            return;
+        };
+        let settings = {
+            let state = app_handle.state::<SettingsState>();
+            let guard = state.0.lock().await;
+            match guard.as_ref() {
+                Some(settings) => settings.get().plugins,
+                None => return,
+            }
+        };
+        for (plugin_id, e) in host.dispatch(&settings, event, payload).await {
+            warn!(target: "plugins", plugin = %plugin_id, event = ?event, "Plugin failed to handle event: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+async fn list_plugins(
+    plugin_state: tauri::State<'_, PluginState>,
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<Vec<PluginInfo>, CommandError> {
+    let host = plugin_host(&plugin_state)?;
+    Ok(host.list(&plugin_settings(&settings_state).await?))
+}
+
+/// Re-read plugin manifests after plugins were added or changed on disk
+#[tauri::command]
+async fn reload_plugins(
+    plugin_state: tauri::State<'_, PluginState>,
+    settings_state: tauri::State<'_, SettingsState>,
+) -> Result<Vec<PluginInfo>, CommandError> {
+    let host = plugin_host(&plugin_state)?;
+    host.reload()?;
+    info!(target: "plugins", dir = %host.plugins_dir().display(), "Reloaded plugins");
+    Ok(host.list(&plugin_settings(&settings_state).await?))
+}
+
+/// Enable a plugin with the permissions the user accepted in the prompt.
+/// Only permissions the manifest requests can be granted.
+#[tauri::command]
+async fn enable_plugin(
+    plugin_state: tauri::State<'_, PluginState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    plugin_id: String,
+    permissions: Vec<PluginPermission>,
+) -> Result<Vec<PluginInfo>, CommandError> {
+    let host = plugin_host(&plugin_state)?;
+    let manifest = host.manifest(&plugin_id).ok_or_else(|| {
+        CommandError::validation(format!("Plugin '{}' is not installed", plugin_id))
+    })?;
+    if let Some(permission) = permissions
+        .iter()
+        .find(|permission| !manifest.permissions.contains(permission))
+    {
+        return Err(CommandError::validation(format!(
+            "Plugin '{}' did not request {:?}",
+            plugin_id, permission
+        )));
+    }
+
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| {
+            s.plugins.grants.insert(
+                plugin_id.clone(),
+                PluginGrant {
+                    enabled: true,
+                    permissions: permissions.clone(),
+                },
+            );
+        })
+        .map_err(|e| {
+            CommandError::file_system("Failed to save plugin settings").with_details(e.to_string())
+        })?;
+    info!(target: "plugins", plugin = %plugin_id, permissions = ?permissions, "Enabled plugin");
+    Ok(host.list(&updated.plugins))
+}
+
+/// Stop running a plugin and forget its permissions
+#[tauri::command]
+async fn disable_plugin(
+    plugin_state: tauri::State<'_, PluginState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    plugin_id: String,
+) -> Result<Vec<PluginInfo>, CommandError> {
+    let host = plugin_host(&plugin_state)?;
+    let guard = settings_state.0.lock().await;
+    let settings = guard
+        .as_ref()
+        .ok_or_else(|| CommandError::not_initialized("Settings"))?;
+    let updated = settings
+        .update(|s| {
+            s.plugins.grants.remove(&plugin_id);
+        })
+        .map_err(|e| {
+            CommandError::file_system("Failed to save plugin settings").with_details(e.to_string())
+        })?;
+    info!(target: "plugins", plugin = %plugin_id, "Disabled plugin");
+    Ok(host.list(&updated.plugins))
+}
+
+/// Run a command declared in a plugin's manifest
+#[tauri::command]
+async fn invoke_plugin_command(
+    plugin_state: tauri::State<'_, PluginState>,
+    settings_state: tauri::State<'_, SettingsState>,
+    plugin_id: String,
+    command: String,
+    args: Option<serde_json::Value>,
+) -> Result<serde_json::Value, CommandError> {
+    let host = plugin_host(&plugin_state)?;
+    let settings = plugin_settings(&settings_state).await?;
+    debug!(target: "plugins", plugin = %plugin_id, command = %command, "Invoking plugin command");
+    telemetry::record(Feature::PluginCommand);
+    Ok(host
+        .invoke_command(
+            &settings,
+            &plugin_id,
+            &command,
+            args.unwrap_or(serde_json::Value::Null),
+        )
+        .await?)
+}
+
+/// Open a session in its own window, or focus the window already showing it
+#[tauri::command]
+async fn open_session_window(
+    app_handle: tauri::AppHandle,
+    window_state: tauri::State<'_, SessionWindowState>,
+    tray_state: tauri::State<'_, TrayState>,
+    session_id: String,
+) -> Result<String, CommandError> {
+    if session_id.trim().is_empty() {
+        return Err(CommandError::validation("Session ID cannot be empty"));
+    }
+    #[cfg(desktop)]
+    {
+        let title = tray_state
+            .0
+            .list()
+            .into_iter()
+            .find(|session| session.id == session_id)
+            .and_then(|session| session.title)
+            .unwrap_or_else(|| "OpenCode Nexus".to_string());
+        let label = session_windows::open_window(&app_handle, &window_state.0, &session_id, &title)
+            .map_err(|e| {
+                CommandError::internal("Failed to open session window").with_details(e.to_string())
+            })?;
+        info!(target: "session", session_id = %session_id, window = %label, "Opened session window");
+        Ok(label)
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = (app_handle, window_state, tray_state);
+        Err(CommandError::validation(
+            "Session windows are only available on desktop",
+        ))
+    }
+}
+
+/// Close a session window opened with `open_session_window`
+#[tauri::command]
+async fn close_session_window(
+    app_handle: tauri::AppHandle,
+    window_state: tauri::State<'_, SessionWindowState>,
+    label: String,
+) -> Result<(), CommandError> {
+    if !label.starts_with(session_windows::SESSION_WINDOW_PREFIX) {
+        return Err(CommandError::validation(format!(
+            "{} is not a session window",
+            label
+        )));
+    }
+    if let Some(window) = app_handle.get_webview_window(&label) {
+        window.close().map_err(|e| {
+            CommandError::internal("Failed to close session window").with_details(e.to_string())
+        })?;
+    }
+    window_state.0.release(&label);
+    Ok(())
+}
+
+/// Record the session the calling window is showing, so its session events
+/// are routed to it. `None` stops routing to the window.
+#[tauri::command]
+async fn set_window_session(
+    window: tauri::WebviewWindow,
+    window_state: tauri::State<'_, SessionWindowState>,
+    session_id: Option<String>,
+) -> Result<(), CommandError> {
+    match session_id.filter(|id| !id.trim().is_empty()) {
+        Some(session_id) => window_state.0.assign(window.label(), &session_id),
+        None => {
+            window_state.0.release(window.label());
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_session_windows(
+    window_state: tauri::State<'_, SessionWindowState>,
+) -> Result<Vec<SessionWindow>, CommandError> {
+    Ok(window_state.0.list())
+}
+
+#[tauri::command]
+async fn stop_message_stream(
+    app_handle: tauri::AppHandle,
+    stream_id: String,
+) -> Result<(), CommandError> {
+    info!(target: "stream", stream_id = %stream_id, "Stopping message stream");
+
+    if let Some(streaming_client) = app_handle
+        .state::<StreamingClientState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+    {
+        streaming_client.stop_stream(&stream_id).await?;
+    }
+
+    info!(target: "stream", stream_id = %stream_id, "Stopped message stream");
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_active_streams(app_handle: tauri::AppHandle) -> Result<Vec<String>, CommandError> {
+    info!(target: "stream", "Getting active streams...");
+
+    let active_streams = match app_handle
+        .state::<StreamingClientState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+    {
+        Some(streaming_client) => streaming_client.get_active_streams().await,
+        None => Vec::new(),
+    };
+
+    info!(target: "stream", "Retrieved {} active streams", active_streams.len());
+    Ok(active_streams)
+}
+
+/// Everything the status bar shows, gathered in one call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStatus {
+    pub connection_status: ConnectionStatus,
+    /// Name of the connected server
+    pub server: Option<String>,
+    pub active_streams: usize,
+    /// Messages queued while offline, waiting to be delivered
+    pub pending_outbox: usize,
+    /// The most recent error in the last hour
+    pub last_error: Option<ErrorSummaryEntry>,
+    pub profile_locked: bool,
+}
+
+/// Connection, stream, outbox and error state for the status bar. Each
+/// part is read from memory, so the frontend can poll this cheaply.
+#[tauri::command]
+async fn get_app_status(app_handle: tauri::AppHandle) -> Result<AppStatus, CommandError> {
+    let TrayStatus {
+        connection_status,
+        server,
+        ..
+    } = tray_status(&app_handle).await;
+    let pending_outbox = app_handle
+        .state::<OutboxState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+        .map_or(0, |outbox| outbox.pending().len());
+    let last_error = error_stats::summary(SummaryPeriod::Hour).and_then(|summary| {
+        summary
+            .entries
+            .into_iter()
+            .max_by_key(|entry| entry.last_seen)
+    });
+    let profile_locked = !*app_handle.state::<ProfileState>().unlocked.borrow();
+
+    Ok(AppStatus {
+        connection_status,
+        server,
+        active_streams: resource_usage::open_streams(),
+        pending_outbox,
+        last_error,
+        profile_locked,
+    })
+}
+
+/// Memory, streams, background tasks and channel backlogs of this process,
+/// for diagnosing suspected leaks
+#[tauri::command]
+async fn get_app_resource_usage(
+    app_handle: tauri::AppHandle,
+) -> Result<ResourceUsage, CommandError> {
+    let mut usage = tauri::async_runtime::spawn_blocking(ResourceUsage::collect)
+        .await
+        .map_err(|e| {
+            CommandError::internal("Failed to collect resource usage").with_details(e.to_string())
+        })?;
+
+    usage
+        .channel_backlog
+        .insert("log_lines".to_string(), logging::line_backlog());
+    if let Some(event_bridge) = app_handle
+        .state::<EventBridgeState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+    {
+        usage
+            .channel_backlog
+            .insert("app_events".to_string(), event_bridge.backlog());
+    }
+    if let Some(manager) = app_handle
+        .state::<ConnectionManagerState>()
+        .0
+        .lock()
+        .await
+        .as_ref()
+    {
+        usage
+            .channel_backlog
+            .insert("connection_events".to_string(), manager.event_backlog());
+    }
+    if let Ok(config_dir) = get_config_dir() {
+        usage.session_cache_bytes = std::fs::metadata(config_dir.join("chat_sessions.json"))
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+            + message_journal::MessageJournal::new(&config_dir).total_bytes();
+    }
+    usage.recent_sessions = app_handle.state::<TrayState>().0.list().len();
+    usage.session_windows = app_handle.state::<SessionWindowState>().0.list().len();
+
+    debug!(
+        target: "init",
+        rss = usage.memory.map(|memory| memory.rss_bytes),
+        streams = usage.open_streams,
+        tasks = usage.background_tasks,
+        "Collected resource usage"
+    );
+    Ok(usage)
+}
+
+/// Log a subsystem state change and notify the frontend
+fn report_subsystem(app_handle: &tauri::AppHandle, status: SubsystemStatus) {
+    match status.health {
+        SubsystemHealth::Unavailable | SubsystemHealth::Degraded => {
+            warn!(target: "init", subsystem = ?status.subsystem, health = ?status.health, "{}", status.error.as_deref().unwrap_or_default())
+        }
+        _ => debug!(target: "init", subsystem = ?status.subsystem, "Subsystem available"),
+    }
+    if let Err(e) = app_handle.emit(subsystems::SUBSYSTEM_STATUS_EVENT, &status) {
+        warn!(target: "init", "Failed to emit subsystem status: {}", e);
+    }
+    let progress = app_handle
+        .state::<SubsystemRegistryState>()
+        .0
+        .progress(status);
+    if let Err(e) = app_handle.emit(subsystems::INIT_PROGRESS_EVENT, &progress) {
+        warn!(target: "init", "Failed to emit init progress: {}", e);
+    }
+}
+
+/// Load saved connections, then restore the last one in the background so
+/// commands never wait on the network call
+async fn init_connection(
+    app_handle: &tauri::AppHandle,
+    subsystems: &SubsystemRegistry,
+    startup: &StartupProfiler,
+    config_dir: Option<&std::path::Path>,
+    event_bridge: &EventBridge,
+) {
+    let Some(config_dir) = config_dir else {
+        return;
+    };
+    let connection_manager = {
+        let connection_manager_state = app_handle.state::<ConnectionManagerState>();
+        let mut state_guard = connection_manager_state.0.lock().await;
+        if state_guard.is_none() {
+            match ConnectionManager::new(config_dir.to_path_buf(), Some(app_handle.clone())) {
+                Ok(mut cm) => {
+                    match startup
+                        .time_async(StartupStep::Connections, cm.load_connections())
+                        .await
+                    {
+                        Ok(()) => subsystems.mark_available(Subsystem::Connection),
+                        Err(e) => {
+                            warn!(target: "init", "Failed to load connections: {}", e);
+                            subsystems.mark_degraded(Subsystem::Connection, e)
+                        }
+                    };
+                    *state_guard = Some(cm);
+                }
+                Err(e) => {
+                    error!(target: "init", "Failed to create connection manager: {}", e);
+                    subsystems.mark_unavailable(Subsystem::Connection, e);
+                }
+            }
+        } else if subsystems
+            .status(Subsystem::Connection)
+            .map(|status| status.health)
+            == Some(SubsystemHealth::Pending)
+        {
+            // A command created it first
+            subsystems.mark_available(Subsystem::Connection);
+        }
+        state_guard.clone()
+    };
+    if let Some(status) = subsystems.status(Subsystem::Connection) {
+        report_subsystem(app_handle, status);
+    }
+
+    // Deliver queued messages once the server is back
+    let connection_events = connection_manager
+        .as_ref()
+        .map(|cm| cm.subscribe_to_events());
+    tauri::async_runtime::spawn(resource_usage::tracked(
+        Activity::BackgroundTask,
+        run_outbox_delivery(app_handle.clone(), connection_events),
+    ));
+
+    // Mirror connection changes onto the event bridge for the tray
+    if let Some(receiver) = connection_manager
+        .as_ref()
+        .map(|cm| cm.subscribe_to_events())
+    {
+        tauri::async_runtime::spawn(resource_usage::tracked(
+            Activity::BackgroundTask,
+            forward_connection_events(event_bridge.clone(), receiver),
+        ));
+    }
+
+    // Surface changes made from other clients, such as the TUI
+    if let Some(receiver) = connection_manager
+        .as_ref()
+        .map(|cm| cm.subscribe_to_server_events())
+    {
+        tauri::async_runtime::spawn(resource_usage::tracked(
+            Activity::BackgroundTask,
+            forward_server_events(event_bridge.clone(), receiver),
+        ));
+    }
+
+    // Attempt to restore the last connection
+    if let Some(mut cm) = connection_manager {
+        startup
+            .run_within_budget(StartupStep::ConnectionRestore, async move {
+                if let Err(e) = cm.restore_connection().await {
+                    warn!(target: "init", "Failed to restore connection on startup: {}", e);
+                }
+            })
+            .await;
+    }
+}
+
+/// Load cached sessions and model providers concurrently. Either load may
+/// outlast its startup budget, so each publishes its own result.
+async fn init_managers(
+    app_handle: &tauri::AppHandle,
+    subsystems: &SubsystemRegistry,
+    startup: &StartupProfiler,
+    api_client: Option<&Arc<ApiClient>>,
+    config_dir: Option<&std::path::Path>,
+) {
+    let (Some(api_client), Some(config_dir)) = (api_client, config_dir) else {
+        let reason = if api_client.is_none() {
+            "API client unavailable"
+        } else {
+            "Could not determine config directory"
+        };
+        for subsystem in [Subsystem::Sessions, Subsystem::Models] {
+            report_subsystem(app_handle, subsystems.mark_unavailable(subsystem, reason));
+        }
+        return;
+    };
+
+    let session_manager = SessionManager::new(api_client.clone(), config_dir.to_path_buf());
+    let model_manager = ModelManager::new(api_client.clone(), config_dir.to_path_buf());
+
+    let load_sessions = {
+        let app_handle = app_handle.clone();
+        let subsystems = subsystems.clone();
+        async move {
+            // Errors become strings here so the spawned future stays `Send`
+            let loaded = session_manager
+                .load_sessions()
+                .await
+                .map_err(|e| e.to_string());
+            *app_handle.state::<SessionManagerState>().0.lock().await = Some(session_manager);
+            let status = match loaded {
+                Ok(()) => subsystems.mark_available(Subsystem::Sessions),
+                Err(e) => {
+                    warn!(target: "init", "Failed to load sessions: {}", e);
+                    subsystems.mark_degraded(Subsystem::Sessions, e)
+                }
+            };
+            report_subsystem(&app_handle, status);
+        }
+    };
+    let load_providers = {
+        let app_handle = app_handle.clone();
+        let subsystems = subsystems.clone();
+        async move {
+            let loaded = model_manager
+                .load_providers()
+                .await
+                .map_err(|e| e.to_string());
+            let status = match loaded {
+                Ok(()) => subsystems.mark_available(Subsystem::Models),
+                Err(e) => {
+                    warn!(target: "init", "Failed to load providers: {}", e);
+                    subsystems.mark_degraded(
+                        Subsystem::Models,
+                        format!("Failed to load providers: {}", e),
+                    )
+                }
+            };
+            report_subsystem(&app_handle, status);
+        }
+    };
+    tokio::join!(
+        startup.run_within_budget(StartupStep::Sessions, load_sessions),
+        startup.run_within_budget(StartupStep::Models, load_providers),
+    );
+}
+
+/// Log how long each startup step took and which ran over budget
+fn log_startup_report(report: &StartupReport) {
+    for timing in &report.steps {
+        debug!(
+            target: "init",
+            step = ?timing.step,
+            started_ms = timing.started_ms,
+            duration_ms = ?timing.duration_ms,
+            budget_ms = timing.budget_ms,
+            deferred = timing.deferred,
+            "Startup step"
+        );
+    }
+    info!(
+        target: "init",
+        ready_ms = ?report.ready_ms,
+        over_budget = ?report.over_budget,
+        "Startup report"
+    );
 }
 
+/// Timings of the startup steps, including deferred steps that finished later
 #[tauri::command]
-async fn set_model_preferences(preferences: serde_json::Value) -> Result<(), String> {
-    log_info!("⚙️ [MODELS] Setting model preferences...");
-
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let model_manager = ModelManager::new(api_client, config_dir);
-
-    let model_preferences: ModelPreferences = serde_json::from_value(preferences)
-        .map_err(|e| format!("Failed to parse preferences: {}", e))?;
-
-    model_manager
-        .update_preferences(model_preferences)
-        .map_err(|e| e.to_string())?;
-
-    log_info!("✅ [MODELS] Updated model preferences");
-    Ok(())
+fn get_startup_report(startup: tauri::State<'_, StartupProfilerState>) -> StartupReport {
+    startup.0.report()
 }
 
+/// Which backend subsystems initialized, so the UI can explain missing features
 #[tauri::command]
-async fn set_default_model(provider_id: String, model_id: String) -> Result<(), String> {
-    log_info!(
-        "🎯 [MODELS] Setting default model: {}/{}",
-        provider_id,
-        model_id
-    );
-
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let model_manager = ModelManager::new(api_client, config_dir);
+fn get_subsystem_status(
+    registry: tauri::State<'_, SubsystemRegistryState>,
+) -> Vec<SubsystemStatus> {
+    registry.0.statuses()
+}
 
-    model_manager
-        .set_default_model(provider_id, model_id)
-        .map_err(|e| e.to_string())?;
+/// Stores kept in the (possibly encrypted) profile directory
+struct LocalStores {
+    recovery_journal: Option<RecoveryJournal>,
+    outbox: Option<Outbox>,
+    workspaces: Option<WorkspaceStore>,
+    prompts: Option<PromptLibrary>,
+    feedback: Option<FeedbackStore>,
+    budgets: Option<BudgetTracker>,
+}
 
-    log_info!("✅ [MODELS] Updated default model");
-    Ok(())
+/// Load the recovery journal, outbox, workspaces, prompt library, message
+/// ratings and spending, recording subsystem status for the first two
+fn open_local_stores(subsystems: &SubsystemRegistry) -> LocalStores {
+    // Upgrade data files before anything reads them
+    if let Ok(config_dir) = get_config_dir() {
+        let (migrated, errors) = migrations::run(&config_dir);
+        for report in migrated {
+            info!(
+                target: "init",
+                file = %report.file,
+                from = report.from,
+                to = report.to,
+                backup = %report.backup.display(),
+                "Migrated data file"
+            );
+        }
+        for e in errors {
+            warn!(target: "init", "Failed to migrate data file: {}", e);
+        }
+    }
+    let recovery_journal = get_config_dir().ok().map(|config_dir| {
+        let journal = RecoveryJournal::new(config_dir);
+        match journal.recover() {
+            Ok(recovered) if !recovered.is_empty() => info!(
+                target: "recovery",
+                "Recovered {} pending prompts and {} partial messages",
+                recovered.pending_prompts.len(),
+                recovered.partial_messages.len()
+            ),
+            Ok(_) => {}
+            Err(e) => {
+                warn!(target: "recovery", "Failed to replay recovery journal: {}", e);
+                subsystems.mark_degraded(Subsystem::RecoveryJournal, e);
+            }
+        }
+        journal
+    });
+    let outbox = get_config_dir().ok().map(|config_dir| {
+        let outbox = Outbox::new(config_dir);
+        if let Err(e) = outbox.load() {
+            warn!(target: "outbox", "Failed to load outbox: {}", e);
+            subsystems.mark_degraded(Subsystem::Outbox, e);
+        }
+        outbox
+    });
+    let workspaces = get_config_dir().ok().map(|config_dir| {
+        let workspaces = WorkspaceStore::new(config_dir);
+        if let Err(e) = workspaces.load() {
+            warn!(target: "session", "Failed to load workspaces: {}", e);
+        }
+        workspaces
+    });
+    let prompts = get_config_dir().ok().map(|config_dir| {
+        let prompts = PromptLibrary::new(config_dir);
+        if let Err(e) = prompts.load() {
+            warn!(target: "chat", "Failed to load prompt library: {}", e);
+        }
+        prompts
+    });
+    let feedback = get_config_dir().ok().map(|config_dir| {
+        let feedback = FeedbackStore::new(config_dir);
+        if let Err(e) = feedback.load() {
+            warn!(target: "chat", "Failed to load message feedback: {}", e);
+        }
+        feedback
+    });
+    let budgets = get_config_dir().ok().map(|config_dir| {
+        let budgets = BudgetTracker::new(config_dir);
+        if let Err(e) = budgets.load() {
+            warn!(target: "chat", "Failed to load spending: {}", e);
+        }
+        budgets
+    });
+    for (subsystem, initialized) in [
+        (Subsystem::RecoveryJournal, recovery_journal.is_some()),
+        (Subsystem::Outbox, outbox.is_some()),
+    ] {
+        if !initialized {
+            subsystems.mark_unavailable(subsystem, "Could not determine config directory");
+        } else if subsystems.status(subsystem).map(|status| status.health)
+            == Some(SubsystemHealth::Pending)
+        {
+            subsystems.mark_available(subsystem);
+        }
+    }
+    LocalStores {
+        recovery_journal,
+        outbox,
+        workspaces,
+        prompts,
+        feedback,
+        budgets,
+    }
 }
 
-// Enhanced session management commands
+/// Check for a newer release on the configured channel
 #[tauri::command]
-async fn delete_session(session_id: String) -> Result<(), String> {
-    log_info!("🗑️ [SESSION] Deleting session: {}", session_id);
-
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let session_manager = SessionManager::new(api_client, config_dir);
-
-    session_manager
-        .delete_session(&session_id)
-        .await
-        .map_err(|e| e.to_string())?;
+async fn check_for_updates(
+    app_handle: tauri::AppHandle,
+    settings_state: tauri::State<'_, SettingsState>,
+    channel: Option<UpdateChannel>,
+) -> Result<Option<UpdateInfo>, CommandError> {
+    if !updater::is_configured() {
+        return Err(CommandError::validation(
+            "Updates are not available in this build",
+        ));
+    }
+    let channel = match channel {
+        Some(channel) => channel,
+        None => settings_state
+            .0
+            .lock()
+            .await
+            .as_ref()
+            .map(|settings| settings.get().updates.channel)
+            .unwrap_or_default(),
+    };
 
-    log_info!("✅ [SESSION] Deleted session: {}", session_id);
-    Ok(())
+    let update = updater::check(&app_handle, channel).await?;
+    telemetry::record(Feature::UpdateCheck);
+    match &update {
+        Some(update) => {
+            info!(target: "init", version = %update.version, ?channel, "Update available")
+        }
+        None => info!(target: "init", ?channel, "Application is up to date"),
+    }
+    Ok(update)
 }
 
+/// Download and verify an update found by `check_for_updates`, emitting
+/// progress events along the way
 #[tauri::command]
-async fn update_session_title(session_id: String, title: String) -> Result<(), String> {
-    log_info!(
-        "✏️ [SESSION] Updating session title: {} -> {}",
-        session_id,
-        title
-    );
-
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let session_manager = SessionManager::new(api_client, config_dir);
-
-    session_manager
-        .update_session_title(&session_id, title)
-        .await
-        .map_err(|e| e.to_string())?;
+async fn download_update(
+    app_handle: tauri::AppHandle,
+    updater_state: tauri::State<'_, UpdaterState>,
+    update: UpdateInfo,
+) -> Result<(), CommandError> {
+    let progress_handle = app_handle.clone();
+    let downloaded = updater::download(&app_handle, &update, |progress: UpdateProgress| {
+        if let Err(e) = progress_handle.emit(updater::UPDATE_PROGRESS_EVENT, &progress) {
+            warn!(target: "init", "Failed to emit update progress: {}", e);
+        }
+    })
+    .await?;
 
-    log_info!("✅ [SESSION] Updated session title");
+    info!(target: "init", version = %update.version, "Update downloaded and verified");
+    *updater_state.0.lock().await = Some(downloaded);
     Ok(())
 }
 
+/// Install the downloaded update and restart into it
 #[tauri::command]
-async fn get_session_stats(session_id: String) -> Result<serde_json::Value, String> {
-    log_info!("📊 [SESSION] Getting session stats: {}", session_id);
-
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let session_manager = SessionManager::new(api_client, config_dir);
-
-    let stats = session_manager
-        .get_session_stats(&session_id)
+async fn install_update_and_restart(
+    app_handle: tauri::AppHandle,
+    updater_state: tauri::State<'_, UpdaterState>,
+) -> Result<(), CommandError> {
+    let downloaded = updater_state
+        .0
+        .lock()
         .await
-        .map_err(|e| e.to_string())?;
+        .take()
+        .ok_or_else(|| CommandError::validation("No update has been downloaded"))?;
 
-    let stats_json =
-        serde_json::to_value(&stats).map_err(|e| format!("Failed to serialize stats: {}", e))?;
+    if let Err(e) = updater::install(&downloaded) {
+        // Keep the download so the user can retry
+        *updater_state.0.lock().await = Some(downloaded);
+        return Err(e.into());
+    }
 
-    log_info!("✅ [SESSION] Retrieved session stats");
-    Ok(stats_json)
+    info!(target: "init", version = %downloaded.info.version, "Update installed, restarting");
+    app_handle.restart()
 }
 
-// Streaming commands
-#[tauri::command]
-async fn start_message_stream(
-    app_handle: tauri::AppHandle,
-    session_id: String,
-    content: String,
-    model_config: Option<ModelConfig>,
-) -> Result<String, String> {
-    log_info!(
-        "🌊 [STREAM] Starting message stream for session: {}",
-        session_id
-    );
-
-    // Validate inputs
-    if session_id.trim().is_empty() {
-        return Err("Session ID cannot be empty".to_string());
-    }
-    let trimmed_content = content.trim();
-    if trimmed_content.is_empty() {
-        return Err("Message content cannot be empty".to_string());
+/// Look for an update once at startup and tell the frontend if there is one
+async fn check_for_updates_on_startup(app_handle: tauri::AppHandle, channel: UpdateChannel) {
+    match updater::check(&app_handle, channel).await {
+        Ok(Some(update)) => {
+            info!(target: "init", version = %update.version, "Update available");
+            if let Err(e) = app_handle.emit(updater::UPDATE_AVAILABLE_EVENT, &update) {
+                warn!(target: "init", "Failed to emit update-available event: {}", e);
+            }
+        }
+        Ok(None) => debug!(target: "init", "Application is up to date"),
+        Err(e) => warn!(target: "init", "Update check failed: {}", e),
     }
+}
 
-    // Ensure server connection
-    let server_url = ensure_server_connected()?;
-
-    // Create streaming components
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    api_client
-        .set_server_url(server_url)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let streaming_client = StreamingClient::new(api_client).map_err(|e| e.to_string())?;
-    let event_bridge = EventBridge::with_app_handle(app_handle);
+/// Upload collected telemetry once a day while the user has opted in
+async fn run_telemetry_uploads(app_handle: tauri::AppHandle) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+    loop {
+        interval.tick().await;
+        let enabled = {
+            let state = app_handle.state::<SettingsState>();
+            let guard = state.0.lock().await;
+            guard
+                .as_ref()
+                .is_some_and(|settings| settings.get().privacy.telemetry_enabled)
+        };
+        let Some(telemetry) = telemetry::recorder().filter(|_| enabled) else {
+            continue;
+        };
+        match telemetry.upload_if_due().await {
+            Ok(true) => debug!(target: "init", "Uploaded usage telemetry"),
+            Ok(false) => {}
+            Err(e) => warn!(target: "init", "Telemetry upload failed: {}", e),
+        }
+    }
+}
 
-    // Create stream request
-    let stream_request = StreamRequest {
-        session_id: session_id.clone(),
-        content: trimmed_content.to_string(),
-        model_config,
-        system_prompt: None,
-        temperature: None,
-        max_tokens: None,
+/// Connection state and recent sessions as shown in the tray
+async fn tray_status(app_handle: &tauri::AppHandle) -> TrayStatus {
+    let (connection_status, server) = {
+        let state = app_handle.state::<ConnectionManagerState>();
+        let guard = state.0.lock().await;
+        match guard.as_ref() {
+            Some(cm) => (
+                cm.get_connection_status(),
+                cm.get_current_connection()
+                    .map(|connection| connection.name),
+            ),
+            None => (ConnectionStatus::Disconnected, None),
+        }
     };
+    TrayStatus {
+        connection_status,
+        server,
+        recent_sessions: app_handle.state::<TrayState>().0.list(),
+    }
+}
 
-    // Start streaming
-    let stream_id = streaming_client
-        .start_stream(stream_request)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Spawn event forwarding task
-    let stream_id_clone = stream_id.clone();
-    let session_id_clone = session_id.clone();
-    let event_bridge_clone = event_bridge.clone();
-    let streaming_client_clone = streaming_client.clone();
+/// Rebuild the tray menu after a connection or session change
+async fn refresh_tray(app_handle: &tauri::AppHandle) {
+    #[cfg(desktop)]
+    if let Err(e) = tray::update(app_handle, &tray_status(app_handle).await) {
+        warn!(target: "tray", "Failed to update tray menu: {}", e);
+    }
+    #[cfg(not(desktop))]
+    let _ = app_handle;
+}
 
-    tokio::spawn(async move {
-        let mut receiver = streaming_client_clone.subscribe();
+#[tauri::command]
+async fn get_tray_status(app_handle: tauri::AppHandle) -> Result<TrayStatus, CommandError> {
+    Ok(tray_status(&app_handle).await)
+}
 
-        while let Ok(stream_event) = receiver.recv().await {
-            if let Err(e) = event_bridge_clone
-                .emit_stream_event(stream_event.clone(), session_id_clone.clone())
-                .await
-            {
-                log_error!("❌ [STREAM] Failed to emit event: {}", e);
+/// Carry out an item picked from the tray menu
+#[cfg(desktop)]
+fn handle_tray_action(app_handle: &tauri::AppHandle, action: TrayAction) {
+    debug!(target: "tray", ?action, "Tray action");
+    match action {
+        TrayAction::ToggleConnection => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                toggle_connection_from_tray(&app_handle).await;
+            });
+        }
+        TrayAction::QuickPrompt => {
+            if let Err(e) = quick_prompt::open_window(app_handle) {
+                warn!(target: "tray", "Failed to open quick prompt: {}", e);
+            }
+        }
+        TrayAction::OpenSession(session_id) => {
+            show_main_window(app_handle);
+            if let Err(e) = app_handle.emit(tray::TRAY_OPEN_SESSION_EVENT, session_id) {
+                warn!(target: "tray", "Failed to emit open session event: {}", e);
             }
         }
+        TrayAction::ShowWindow => show_main_window(app_handle),
+        TrayAction::Quit => app_handle.exit(0),
+    }
+}
 
-        // Cleanup when stream ends
-        let _ = streaming_client_clone.stop_stream(&stream_id_clone).await;
-    });
+/// Disconnect from the current server, or restore the last connection
+#[cfg(desktop)]
+async fn toggle_connection_from_tray(app_handle: &tauri::AppHandle) {
+    {
+        let state = app_handle.state::<ConnectionManagerState>();
+        let mut guard = state.0.lock().await;
+        let Some(cm) = guard.as_mut() else {
+            return;
+        };
+        let connected = cm.get_connection_status() == ConnectionStatus::Connected;
+        let result = if connected {
+            cm.disconnect_from_server().await
+        } else {
+            cm.restore_connection().await
+        };
+        match result {
+            Ok(()) => {
+                if let Some(server_url) = cm.get_server_url().or(cm.get_last_used_server_url()) {
+                    audit_log::record(
+                        AuditAction::ConnectionChanged,
+                        &server_url,
+                        Some(
+                            if connected {
+                                "disconnected"
+                            } else {
+                                "reconnected"
+                            }
+                            .to_string(),
+                        ),
+                    );
+                }
+            }
+            Err(e) => warn!(target: "tray", "Tray connection toggle failed: {}", e),
+        }
+    }
+    refresh_tray(app_handle).await;
+}
 
-    log_info!("✅ [STREAM] Started message stream: {}", stream_id);
-    Ok(stream_id)
+#[cfg(desktop)]
+fn show_main_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
 }
 
-#[tauri::command]
-async fn stop_message_stream(stream_id: String) -> Result<(), String> {
-    log_info!("🛑 [STREAM] Stopping message stream: {}", stream_id);
+/// Publish connection manager events on the event bridge
+async fn forward_connection_events(
+    event_bridge: EventBridge,
+    mut connection_events: tokio::sync::broadcast::Receiver<ConnectionEvent>,
+) {
+    loop {
+        match connection_events.recv().await {
+            Ok(event) => {
+                let app_event = event_bridge.connection_to_app_event(event);
+                if let Err(e) = event_bridge.emit(app_event).await {
+                    warn!(target: "events", "Failed to emit connection event: {}", e);
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
 
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let streaming_client = StreamingClient::new(api_client).map_err(|e| e.to_string())?;
+/// Publish the server's global events on the event bridge
+async fn forward_server_events(
+    event_bridge: EventBridge,
+    mut server_events: tokio::sync::broadcast::Receiver<ServerEvent>,
+) {
+    loop {
+        match server_events.recv().await {
+            Ok(event) => {
+                let Some(app_event) = event_bridge.server_to_app_event(event) else {
+                    continue;
+                };
+                if let Err(e) = event_bridge.emit(app_event).await {
+                    warn!(target: "events", "Failed to emit server event: {}", e);
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
 
-    streaming_client
-        .stop_stream(&stream_id)
-        .await
-        .map_err(|e| e.to_string())?;
+/// Keep the tray's connection status live from event bridge updates
+async fn run_tray_updates(
+    app_handle: tauri::AppHandle,
+    mut events: tokio::sync::broadcast::Receiver<AppEvent>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(AppEvent::Connection { .. }) => refresh_tray(&app_handle).await,
+            Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
 
-    log_info!("✅ [STREAM] Stopped message stream: {}", stream_id);
-    Ok(())
+/// Offer work interrupted by a crash to the frontend
+fn emit_recovered_work(app_handle: &tauri::AppHandle, recovery_journal: &Option<RecoveryJournal>) {
+    if let Some(recovered) = recovery_journal
+        .as_ref()
+        .map(|journal| journal.recovered())
+        .filter(|recovered| !recovered.is_empty())
+    {
+        if let Err(e) = app_handle.emit("recovery-available", &recovered) {
+            warn!(target: "recovery", "Failed to emit recovery event: {}", e);
+        }
+    }
 }
 
-#[tauri::command]
-async fn get_active_streams() -> Result<Vec<String>, String> {
-    log_info!("📋 [STREAM] Getting active streams...");
+/// Block startup until the user unlocks the encrypted profile, then load
+/// the stores that were sealed
+async fn wait_for_profile_unlock(app_handle: &tauri::AppHandle, subsystems: &SubsystemRegistry) {
+    let mut unlocked = app_handle.state::<ProfileState>().unlocked.subscribe();
+    if let Err(e) = app_handle.emit("profile-locked", ()) {
+        warn!(target: "init", "Failed to emit profile-locked event: {}", e);
+    }
+    info!(target: "init", "Waiting for the encrypted profile to be unlocked");
+    if unlocked.wait_for(|unlocked| *unlocked).await.is_err() {
+        return;
+    }
 
-    let config_dir = get_config_dir()?;
-    let api_client = Arc::new(ApiClient::new().map_err(|e| e.to_string())?);
-    let streaming_client = StreamingClient::new(api_client).map_err(|e| e.to_string())?;
+    let LocalStores {
+        recovery_journal,
+        outbox,
+        workspaces,
+        prompts,
+        feedback,
+        budgets,
+    } = open_local_stores(subsystems);
+    *app_handle.state::<RecoveryJournalState>().0.lock().await = recovery_journal.clone();
+    *app_handle.state::<OutboxState>().0.lock().await = outbox;
+    *app_handle.state::<WorkspaceState>().0.lock().await = workspaces;
+    *app_handle.state::<PromptLibraryState>().0.lock().await = prompts;
+    *app_handle.state::<FeedbackState>().0.lock().await = feedback;
+    *app_handle.state::<BudgetState>().0.lock().await = budgets;
+    for subsystem in [Subsystem::RecoveryJournal, Subsystem::Outbox] {
+        if let Some(status) = subsystems.status(subsystem) {
+            report_subsystem(app_handle, status);
+        }
+    }
+    emit_recovered_work(app_handle, &recovery_journal);
+    if let Err(e) = error_stats::reload() {
+        warn!(target: "init", "Failed to reload error stats: {}", e);
+    }
+    if let Err(e) = health_history::reload() {
+        warn!(target: "init", "Failed to reload health history: {}", e);
+    }
+    load_proxy_passwords();
+}
 
-    let active_streams = streaming_client.get_active_streams().await;
+/// Write session changes still waiting out the save delay
+fn flush_sessions(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<SessionManagerState>();
+    tauri::async_runtime::block_on(async {
+        if let Some(session_manager) = state.0.lock().await.as_ref() {
+            if let Err(e) = session_manager.save_sessions().await {
+                error!(target: "init", "Failed to save sessions on exit: {}", e);
+            }
+        }
+    });
+}
 
-    log_info!(
-        "✅ [STREAM] Retrieved {} active streams",
-        active_streams.len()
-    );
-    Ok(active_streams)
+/// Seal the encrypted profile on exit so no plaintext is left behind
+fn seal_profile(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<ProfileState>();
+    let key = tauri::async_runtime::block_on(async { state.key.lock().await.take() });
+    let (Some(key), Ok(config_dir)) = (key, get_config_dir()) else {
+        return;
+    };
+    match ProfileVault::new(config_dir).seal(&key) {
+        Ok(count) => info!(target: "init", "Sealed {} files into the encrypted profile", count),
+        Err(e) => error!(target: "init", "Failed to seal encrypted profile: {}", e),
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    resource_usage::mark_started();
+    let startup = StartupProfiler::new();
+    let subsystems = SubsystemRegistry::new();
+
+    let settings_manager = get_config_dir().ok().map(|config_dir| {
+        let settings = SettingsManager::new(config_dir.clone());
+        match settings.load() {
+            Ok(()) => {
+                subsystems.mark_available(Subsystem::Settings);
+            }
+            Err(e) => {
+                eprintln!("[WARN] Failed to load settings, using defaults: {}", e);
+                subsystems.mark_degraded(Subsystem::Settings, e);
+            }
+        }
+        let logging_settings = settings.get().logging;
+        let directives =
+            logging::build_directives(&logging_settings.level, &logging_settings.targets)
+                .unwrap_or_else(|_| logging::DEFAULT_FILTER.to_string());
+        logging::init(config_dir.clone(), &directives);
+        if let Err(e) = log_forwarding::configure(&logging_settings.remote) {
+            warn!(target: "logs", "Remote log forwarding disabled: {}", e);
+        }
+        if let Err(e) = metrics::configure(&settings.get().metrics) {
+            warn!(target: "metrics", "Metrics endpoint disabled: {}", e);
+        }
+        error_reporting::apply(&settings.get().privacy);
+        error::set_retry_settings(settings.get().retry);
+        i18n::set_locale(&settings.get().locale);
+        proxy::set_global(&settings.get().proxy);
+        load_proxy_passwords();
+
+        let error_stats = ErrorStats::new(config_dir.clone());
+        if let Err(e) = error_stats.load() {
+            warn!(target: "init", "Failed to load error stats: {}", e);
+        }
+        error_stats::install(error_stats);
+        let health_history = HealthHistory::new(config_dir.clone());
+        if let Err(e) = health_history.load() {
+            warn!(target: "init", "Failed to load health history: {}", e);
+        }
+        health_history::install(health_history);
+        let telemetry = Telemetry::new(config_dir.clone());
+        if let Err(e) = telemetry.load() {
+            warn!(target: "init", "Failed to load telemetry: {}", e);
+        }
+        telemetry::install(telemetry);
+        audit_log::install(AuditLog::new(config_dir));
+        settings
+    });
+    if settings_manager.is_none() {
+        subsystems.mark_unavailable(Subsystem::Settings, "Could not determine config directory");
+    }
+
     // Initialize managed state (singletons)
     let api_client_state = ApiClientState(Arc::new(AsyncMutex::new(None)));
     let session_manager_state = SessionManagerState(Arc::new(AsyncMutex::new(None)));
@@ -864,147 +6566,473 @@ pub fn run() {
     let event_bridge_state = EventBridgeState(Arc::new(AsyncMutex::new(None)));
     let connection_manager_state = ConnectionManagerState(Arc::new(AsyncMutex::new(None)));
 
+    // An encrypted profile keeps its journal and outbox sealed until unlocked
+    let profile_locked = settings_manager
+        .as_ref()
+        .is_some_and(|settings| settings.get().security.encrypted_profile.is_some());
+    if profile_locked {
+        if let Ok(config_dir) = get_config_dir() {
+            if ProfileVault::new(config_dir).is_open() {
+                warn!(target: "init", "Encrypted profile was not sealed on last exit; it is resealed on unlock");
+            }
+        }
+    }
+    let app_lock = AppLock::new(
+        settings_manager
+            .as_ref()
+            .and_then(|settings| settings.get().security.app_lock),
+    );
+    let settings_changes = settings_manager
+        .as_ref()
+        .map(|settings| settings.subscribe());
+    #[cfg(desktop)]
+    let quick_prompt_shortcut = settings_manager
+        .as_ref()
+        .map(|settings| settings.get().quick_prompt.shortcut)
+        .unwrap_or_else(|| quick_prompt::DEFAULT_SHORTCUT.to_string());
+    let startup_update_channel = settings_manager
+        .as_ref()
+        .map(|settings| settings.get().updates)
+        .filter(|updates| updates.check_on_startup && updater::is_configured())
+        .map(|updates| updates.channel);
+    let settings_state = SettingsState(Arc::new(AsyncMutex::new(settings_manager)));
+    let LocalStores {
+        recovery_journal,
+        outbox,
+        workspaces,
+        prompts,
+        feedback,
+        budgets,
+    } = if profile_locked {
+        LocalStores {
+            recovery_journal: None,
+            outbox: None,
+            workspaces: None,
+            prompts: None,
+            feedback: None,
+            budgets: None,
+        }
+    } else {
+        startup.time(StartupStep::LocalStores, || open_local_stores(&subsystems))
+    };
+    let recovery_journal_state =
+        RecoveryJournalState(Arc::new(AsyncMutex::new(recovery_journal.clone())));
+    let outbox_state = OutboxState(Arc::new(AsyncMutex::new(outbox)));
+    let workspace_state = WorkspaceState(Arc::new(AsyncMutex::new(workspaces)));
+    let prompt_library_state = PromptLibraryState(Arc::new(AsyncMutex::new(prompts)));
+    let feedback_state = FeedbackState(Arc::new(AsyncMutex::new(feedback)));
+    let budget_state = BudgetState(Arc::new(AsyncMutex::new(budgets)));
+    let log_streamer = LogStreamer::new();
+    let log_streamer_state =
+        LogStreamerState(Arc::new(AsyncMutex::new(Some(log_streamer.clone()))));
+
+    let subsystem_registry_state = SubsystemRegistryState(subsystems.clone());
+    let profile_state = ProfileState {
+        key: AsyncMutex::new(None),
+        unlocked: tokio::sync::watch::Sender::new(!profile_locked),
+    };
+
+    let plugin_host = get_config_dir().ok().map(|config_dir| {
+        let host = PluginHost::new(&config_dir);
+        if let Err(e) = host.reload() {
+            warn!(target: "plugins", "Failed to load plugins: {}", e);
+        }
+        host
+    });
+
     // Legacy state for backward compatibility
-    let chat_client_state = ChatClientState(Arc::new(AsyncMutex::new(None)));
 
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    #[cfg(desktop)]
+    let builder = builder
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build());
+
+    builder
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(api_client_state)
         .manage(session_manager_state)
         .manage(model_manager_state)
         .manage(streaming_client_state)
         .manage(event_bridge_state)
         .manage(connection_manager_state)
-        .manage(chat_client_state)
-        .setup(|app| {
-            // Initialize all components on app startup
+        .manage(settings_state)
+        .manage(log_streamer_state)
+        .manage(recovery_journal_state)
+        .manage(outbox_state)
+        .manage(workspace_state)
+        .manage(prompt_library_state)
+        .manage(feedback_state)
+        .manage(budget_state)
+        .manage(subsystem_registry_state)
+        .manage(StartupProfilerState(startup.clone()))
+        .manage(profile_state)
+        .manage(AppLockState(app_lock.clone()))
+        .manage(UpdaterState(Arc::new(AsyncMutex::new(None))))
+        .manage(TrayState(RecentSessions::new()))
+        .manage(PluginState(plugin_host))
+        .manage(SessionWindowState(SessionWindows::new()))
+        .manage(CodeBlockState(CodeBlockCache::new()))
+        .manage(ServerFileState(ServerFileCache::new()))
+        .manage(PrefetchState(PrefetchCache::new()))
+        .manage(ServerClientsState(AsyncMutex::new(HashMap::new())))
+        .manage(CompatibilityState(Compatibility::new()))
+        .manage(ShellApprovalState(PendingApprovals::new()))
+        .manage(TerminalState(Terminals::new()))
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                window
+                    .state::<SessionWindowState>()
+                    .0
+                    .release(window.label());
+            }
+        })
+        .setup(move |app| {
+            // Forward new log lines to live log viewers
+            log_streamer.start(app.handle().clone(), logging::subscribe_lines());
+
+            emit_recovered_work(app.handle(), &recovery_journal);
+            #[cfg(desktop)]
+            {
+                let initial = TrayStatus {
+                    connection_status: ConnectionStatus::Disconnected,
+                    server: None,
+                    recent_sessions: Vec::new(),
+                };
+                if let Err(e) = tray::create(app.handle(), &initial, handle_tray_action) {
+                    warn!(target: "tray", "Failed to create tray icon: {}", e);
+                }
+                if let Err(e) =
+                    quick_prompt::register_shortcut(app.handle(), None, &quick_prompt_shortcut)
+                {
+                    warn!(target: "tray", "{}", e);
+                }
+            }
+            if let Some(channel) = startup_update_channel {
+                tauri::async_runtime::spawn(check_for_updates_on_startup(
+                    app.handle().clone(),
+                    channel,
+                ));
+            }
+            if let Some(changes) = settings_changes {
+                tauri::async_runtime::spawn(resource_usage::tracked(
+                    Activity::BackgroundTask,
+                    forward_settings_changes(app.handle().clone(), changes),
+                ));
+            }
+            if telemetry::is_configured() {
+                tauri::async_runtime::spawn(resource_usage::tracked(
+                    Activity::BackgroundTask,
+                    run_telemetry_uploads(app.handle().clone()),
+                ));
+            }
+            tauri::async_runtime::spawn(resource_usage::tracked(
+                Activity::BackgroundTask,
+                run_idle_lock_timer(app.handle().clone(), app.state::<AppLockState>().0.clone()),
+            ));
+
+            // Initialize all components on app startup. A failing subsystem is
+            // recorded and skipped so the rest of the app stays usable.
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                let config_dir = match dirs::config_dir() {
-                    Some(dir) => dir.join("opencode-nexus"),
-                    None => {
-                        log_warn!("Could not determine config directory");
-                        return;
-                    }
-                };
+                if profile_locked {
+                    wait_for_profile_unlock(&app_handle, &subsystems).await;
+                }
+
+                let config_dir = dirs::config_dir().map(|dir| dir.join("opencode-nexus"));
+                if config_dir.is_none() {
+                    warn!(target: "init", "Could not determine config directory");
+                }
 
                 // Initialize API client
                 let api_client = match ApiClient::new() {
-                    Ok(client) => Arc::new(client),
+                    Ok(client) => {
+                        report_subsystem(&app_handle, subsystems.mark_available(Subsystem::ApiClient));
+                        Some(Arc::new(client))
+                    }
                     Err(e) => {
-                        log_error!("❌ [INIT] Failed to create API client: {}", e);
-                        return;
+                        error!(target: "init", "Failed to create API client: {}", e);
+                        report_subsystem(
+                            &app_handle,
+                            subsystems.mark_unavailable(Subsystem::ApiClient, e),
+                        );
+                        None
                     }
                 };
 
-                // Initialize event bridge
-                let event_bridge = EventBridge::with_app_handle(app_handle.clone());
+                // Commands may have made the shared client while startup waited
+                let api_client = match api_client {
+                    Some(client) => Some(
+                        app_handle
+                            .state::<ApiClientState>()
+                            .0
+                            .lock()
+                            .await
+                            .get_or_insert(client)
+                            .clone(),
+                    ),
+                    None => None,
+                };
 
-                // Initialize connection manager in managed state
+                // Initialize event bridge
+                let event_bridge = EventBridge::with_app_handle(app_handle.clone())
+                    .with_window_routing(app_handle.state::<SessionWindowState>().0.clone());
                 {
-                    let connection_manager_state = app_handle.state::<ConnectionManagerState>();
-                    let mut state_guard = connection_manager_state.0.lock().await;
-                    if state_guard.is_none() {
-                        match ConnectionManager::new(config_dir.clone(), Some(app_handle.clone())) {
-                            Ok(mut cm) => {
-                                if let Err(e) = cm.load_connections() {
-                                    log_warn!("⚠️ [INIT] Failed to load connections: {}", e);
-                                }
-                                *state_guard = Some(cm);
-                            }
-                            Err(e) => {
-                                log_error!("❌ [INIT] Failed to create connection manager: {}", e);
-                                return;
-                            }
-                        }
-                    }
-
-                    // Attempt to restore the last connection
-                    if let Some(ref mut cm) = *state_guard {
-                        if let Err(e) = cm.restore_connection().await {
-                            log_warn!("⚠️ [INIT] Failed to restore connection on startup: {}", e);
-                        }
-                    }
-                }
-
-                // Initialize session manager
-                let session_manager = SessionManager::new(api_client.clone(), config_dir.clone());
-                if let Err(e) = session_manager.load_sessions().await {
-                    log_warn!("⚠️ [INIT] Failed to load sessions: {}", e);
+                    let event_bridge_state = app_handle.state::<EventBridgeState>();
+                    *event_bridge_state.0.lock().await = Some(event_bridge.clone());
                 }
+                report_subsystem(&app_handle, subsystems.mark_available(Subsystem::EventBridge));
 
-                // Initialize model manager
-                let model_manager = ModelManager::new(api_client.clone(), config_dir.clone());
-                if let Err(e) = model_manager.load_providers().await {
-                    log_warn!("⚠️ [INIT] Failed to load providers: {}", e);
-                }
-                if let Err(e) = model_manager.load_preferences() {
-                    log_warn!("⚠️ [INIT] Failed to load model preferences: {}", e);
-                }
+                // Connections and the session/model managers are independent,
+                // so load them side by side
+                tokio::join!(
+                    init_connection(&app_handle, &subsystems, &startup, config_dir.as_deref(), &event_bridge),
+                    init_managers(&app_handle, &subsystems, &startup, api_client.as_ref(), config_dir.as_deref()),
+                );
+                tauri::async_runtime::spawn(resource_usage::tracked(
+                    Activity::BackgroundTask,
+                    run_tray_updates(app_handle.clone(), event_bridge.subscribe()),
+                ));
+                refresh_tray(&app_handle).await;
 
                 // Initialize streaming client
-                let streaming_client = match StreamingClient::new(api_client.clone()) {
-                    Ok(client) => client,
-                    Err(e) => {
-                        log_error!("❌ [INIT] Failed to create streaming client: {}", e);
-                        return;
+                let streaming_status = match api_client.as_ref().map(|client| StreamingClient::new(client.clone())) {
+                    Some(Ok(client)) => {
+                        // Commands may have made the managed client while startup waited
+                        app_handle.state::<StreamingClientState>().0.lock().await.get_or_insert(client);
+                        subsystems.mark_available(Subsystem::Streaming)
+                    }
+                    Some(Err(e)) => {
+                        error!(target: "init", "Failed to create streaming client: {}", e);
+                        subsystems.mark_unavailable(Subsystem::Streaming, e)
                     }
+                    None => subsystems.mark_unavailable(Subsystem::Streaming, "API client unavailable"),
                 };
+                report_subsystem(&app_handle, streaming_status);
 
                 // Emit application ready event
                 if let Err(e) = event_bridge
-                    .emit_application_ready(vec![
-                        "chat".to_string(),
-                        "streaming".to_string(),
-                        "model-management".to_string(),
-                        "session-management".to_string(),
-                    ])
+                    .emit_application_ready(subsystems.available_features())
                     .await
                 {
-                    log_warn!("⚠️ [INIT] Failed to emit ready event: {}", e);
+                    warn!(target: "init", "Failed to emit ready event: {}", e);
+                }
+                startup.mark_ready();
+                log_startup_report(&startup.report());
+
+                let unavailable: Vec<Subsystem> = subsystems
+                    .statuses()
+                    .into_iter()
+                    .filter(|status| status.health == SubsystemHealth::Unavailable)
+                    .map(|status| status.subsystem)
+                    .collect();
+                if unavailable.is_empty() {
+                    info!(target: "init", "OpenCode Nexus initialized successfully");
+                } else {
+                    warn!(target: "init", "OpenCode Nexus initialized in degraded mode, unavailable: {:?}", unavailable);
                 }
-
-                log_info!("✅ [INIT] OpenCode Nexus initialized successfully");
             });
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            // Connection management commands
-            connect_to_server,
-            test_server_connection,
-            get_connection_status,
-            get_current_connection,
-            disconnect_from_server,
-            get_saved_connections,
-            save_connection,
-            get_last_used_connection,
-            // Chat/Session management commands
-            list_sessions,
-            create_session,
-            send_message,
-            get_session_messages,
-            subscribe_to_chat_events,
-            delete_session,
-            update_session_title,
-            get_session_stats,
-            // Model configuration commands
-            get_available_models,
-            get_model_preferences,
-            set_model_preferences,
-            set_default_model,
-            // Streaming commands
-            start_message_stream,
-            stop_message_stream,
-            get_active_streams,
-            // Application commands
-            get_application_logs,
-            log_frontend_error,
-            clear_application_logs
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler({
+            let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+                greet,
+                // Connection management commands
+                connect_to_server,
+                test_server_connection,
+                get_connection_status,
+                get_current_connection,
+                disconnect_from_server,
+                get_active_connections,
+                set_primary_connection,
+                get_server_features,
+                get_saved_connections,
+                test_all_saved_connections,
+                save_connection,
+                get_last_used_connection,
+                // Chat/Session management commands
+                list_sessions,
+                create_session,
+                send_message,
+                get_recovered_work,
+                resend_recovered_prompt,
+                discard_recovered_work,
+                get_outbox,
+                retry_outbox,
+                remove_outbox_item,
+                get_session_messages,
+                get_message_count,
+                get_message_range,
+                get_session_file_changes,
+                get_context_usage,
+                get_budget_status,
+                set_session_budget,
+                override_budget,
+                get_session_diagnostics,
+                get_server_config,
+                get_provider_auth_status,
+                authenticate_provider,
+                list_checkpoints,
+                revert_to_checkpoint,
+                list_slash_commands,
+                rate_message,
+                clear_message_rating,
+                list_message_feedback,
+                export_rated_exchanges,
+                run_slash_command,
+                extract_code_blocks,
+                apply_code_block_to_file,
+                list_server_files,
+                read_server_file,
+                search_server_files,
+                list_agents,
+                list_pending_shell_approvals,
+                open_terminal,
+                write_terminal,
+                resize_terminal,
+                close_terminal,
+                list_terminals,
+                respond_to_shell_approval,
+                subscribe_to_chat_events,
+                delete_session,
+                update_session_title,
+                get_session_stats,
+                // Model configuration commands
+                get_available_models,
+                get_model_preferences,
+                set_model_preferences,
+                set_default_model,
+                // Streaming commands
+                start_message_stream,
+                stop_message_stream,
+                get_active_streams,
+                get_app_resource_usage,
+                get_app_status,
+                // Application commands
+                get_application_logs,
+                query_logs,
+                subscribe_to_logs,
+                unsubscribe_from_logs,
+                log_frontend_error,
+                clear_application_logs,
+                export_support_bundle,
+                get_log_level,
+                set_log_level,
+                get_log_targets,
+                get_remote_logging,
+                set_remote_logging,
+                get_error_reporting_settings,
+                set_error_reporting_enabled,
+                set_log_target_level,
+                get_retry_policy,
+                set_retry_policy,
+                get_error_summary,
+                get_connection_health_history,
+                get_connection_uptime_stats,
+                get_subsystem_status,
+                get_startup_report,
+                report_problem,
+                get_profile_encryption_status,
+                enable_profile_encryption,
+                unlock_profile,
+                disable_profile_encryption,
+                get_app_lock_status,
+                report_user_activity,
+                enable_app_lock,
+                disable_app_lock,
+                lock_app,
+                unlock_app,
+                list_secrets,
+                delete_secret,
+                store_connection_secret,
+                get_connection_secret,
+                rotate_connection_credentials,
+                set_connection_certificate_pin,
+                get_connection_profile,
+                update_connection_profile,
+                store_proxy_password,
+                test_proxy_settings,
+                get_server_certificate_fingerprint,
+                get_security_audit_log,
+                set_secret_scanning,
+                scan_outgoing_content,
+                validate_attachment,
+                upload_context_file,
+                transcribe_audio,
+                set_transcription_api_key,
+                scan_project_directory,
+                attach_project_directory,
+                extract_attachment_archive,
+                get_settings,
+                update_settings,
+                check_for_updates,
+                download_update,
+                install_update_and_restart,
+                get_tray_status,
+                get_notification_settings,
+                set_notification_settings,
+                set_focus_mode,
+                open_quick_prompt,
+                get_quick_prompt_settings,
+                set_quick_prompt_shortcut,
+                submit_quick_prompt,
+                list_plugins,
+                reload_plugins,
+                enable_plugin,
+                disable_plugin,
+                invoke_plugin_command,
+                get_locale,
+                set_locale,
+                get_collected_telemetry,
+                set_telemetry_enabled,
+                clear_collected_telemetry,
+                create_backup,
+                export_config_profile,
+                export_connections,
+                import_connections,
+                restore_backup,
+                open_session_window,
+                close_session_window,
+                set_window_session,
+                list_session_windows,
+                get_setup_state,
+                complete_setup,
+                list_workspaces,
+                get_active_workspace,
+                create_workspace,
+                open_workspace,
+                delete_workspace,
+                add_session_to_workspace,
+                remove_session_from_workspace,
+                list_prompts,
+                create_prompt,
+                update_prompt,
+                delete_prompt,
+                render_prompt,
+                send_prompt,
+                export_prompts,
+                import_prompts,
+                import_conversations
+            ];
+            // Refuse commands that expose chat history or secrets while locked
+            move |invoke| {
+                if app_lock::is_protected(invoke.message.command()) && app_lock.is_locked() {
+                    invoke.resolver.reject(CommandError::locked());
+                    return true;
+                }
+                handler(invoke)
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                flush_sessions(app_handle);
+                seal_profile(app_handle);
+            }
+        });
 }
 
 #[cfg(test)]
@@ -1022,11 +7050,12 @@ mod tests {
     #[test]
     fn test_ensure_server_connected_without_connection() {
         // When no connection exists, should return friendly error message
-        let result = ensure_server_connected();
+        let result = connected_server_url(None);
 
         if let Err(error) = result {
+            assert_eq!(error.code, error::ErrorCode::NotConnected);
             assert!(
-                error.contains("connect to an OpenCode server"),
+                error.message.contains("connect to an OpenCode server"),
                 "Error message should be user-friendly: {}",
                 error
             );
@@ -1049,10 +7078,9 @@ mod tests {
         // This tests the logic structure without full Tauri runtime
         // The actual start_message_stream command:
         // 1. Ensures server connection
-        // 2. Creates ChatClient
-        // 3. Sets server URL
-        // 4. Starts event stream
-        // 5. Spawns task to emit events to frontend
+        // 2. Points the shared API client at the server
+        // 3. Starts event stream
+        // 4. Spawns task to emit events to frontend
 
         // We verify the helper functions work correctly
         let config_dir_result = get_config_dir();
@@ -1068,7 +7096,7 @@ mod tests {
         // - start_message_stream
         //
         // This ensures users can't attempt chat operations without server
-        let result = ensure_server_connected();
+        let result = connected_server_url(None);
 
         // Should either succeed (if connection exists) or fail with friendly message
         match result {
@@ -1077,7 +7105,7 @@ mod tests {
             }
             Err(msg) => {
                 assert!(
-                    msg.contains("connect to an OpenCode server"),
+                    msg.message.contains("connect to an OpenCode server"),
                     "Should provide user-friendly error"
                 );
             }
@@ -1143,17 +7171,17 @@ mod tests {
         // Event Bridge Pattern (start_message_stream):
         //
         // Backend (Rust):
-        // 1. ChatClient.start_event_stream() returns broadcast::Receiver
-        // 2. Tokio task spawned to listen for ChatEvent messages
+        // 1. StreamingClient.subscribe() returns broadcast::Receiver
+        // 2. Tokio task spawned to listen for StreamEvent messages
         // 3. Each event emitted to frontend via app_handle.emit("chat-event", event)
         //
         // Frontend (TypeScript):
         // 1. Calls invoke('start_message_stream') once on app start
         // 2. Listens for events with listen('chat-event', callback)
-        // 3. Receives ChatEvent (SessionCreated, MessageReceived, MessageChunk, Error)
+        // 3. Receives StreamEvent (Start, Chunk, Complete, End, Error, ...)
         //
         // Event Flow:
-        // SSE Server → MessageStream → ChatEvent → Tokio Channel → Tauri Emit → Frontend
+        // SSE Server → MessageStream → StreamEvent → Tokio Channel → Tauri Emit → Frontend
         //
         // This pattern enables:
         // - Real-time message streaming from server
@@ -1208,7 +7236,7 @@ mod tests {
     #[test]
     fn test_delete_session_requires_server_connection() {
         // Verify that delete_session would fail without server connection
-        let result = ensure_server_connected();
+        let result = connected_server_url(None);
 
         match result {
             Ok(url) => {
@@ -1216,7 +7244,7 @@ mod tests {
             }
             Err(msg) => {
                 assert!(
-                    msg.contains("connect to an OpenCode server"),
+                    msg.message.contains("connect to an OpenCode server"),
                     "Should provide user-friendly error: {}",
                     msg
                 );
@@ -1297,4 +7325,41 @@ mod tests {
 
         assert!(true, "Model parsing logic documented");
     }
+
+    #[tokio::test]
+    async fn test_connections_survive_commands_while_profile_sealed() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let config_dir = temp_dir.path().to_path_buf();
+        let mut manager = load_connection_manager(config_dir.clone(), None, true)
+            .await
+            .unwrap();
+        manager
+            .save_connection(connection_manager::ServerConnection {
+                name: "Home".to_string(),
+                hostname: "example.com".to_string(),
+                port: 4096,
+                secure: false,
+                last_connected: None,
+                certificate_fingerprint: None,
+                profile: Default::default(),
+            })
+            .await
+            .unwrap();
+        let (key, _) = ProfileKey::create("correct horse").unwrap();
+        let vault = ProfileVault::new(config_dir.clone());
+        vault.seal(&key).unwrap();
+
+        // A connection command arrives before the passphrase is entered
+        assert!(load_connection_manager(config_dir.clone(), None, false)
+            .await
+            .is_err());
+
+        vault.open(&key).unwrap();
+        let manager = load_connection_manager(config_dir, None, true)
+            .await
+            .unwrap();
+        let saved = manager.get_saved_connections();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, "Home");
+    }
 }
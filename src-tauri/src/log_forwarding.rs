@@ -0,0 +1,359 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::log_query::LogEntry;
+use crate::logging::parse_level;
+use crate::settings::{RemoteLogProtocol, RemoteLogSettings};
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Records buffered before new ones are dropped
+const QUEUE_CAPACITY: usize = 2048;
+
+/// Maximum records per push
+const MAX_BATCH: usize = 200;
+
+/// How long a batch may wait for more records before it is sent
+const BATCH_WINDOW: Duration = Duration::from_secs(1);
+
+/// Targets never forwarded: the HTTP stack would otherwise log its own
+/// forwarding requests in a loop
+const EXCLUDED_TARGETS: [&str; 5] = ["hyper", "reqwest", "h2", "rustls", "tokio"];
+
+struct ActiveSink {
+    sender: mpsc::Sender<LogEntry>,
+    max_level: Level,
+}
+
+/// Currently configured sink; `None` while forwarding is disabled
+static SINK: RwLock<Option<ActiveSink>> = RwLock::new(None);
+
+/// Tracing layer that hands events to the remote sink when one is configured
+pub struct RemoteLogLayer;
+
+impl<S: Subscriber> Layer<S> for RemoteLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if EXCLUDED_TARGETS
+            .iter()
+            .any(|excluded| metadata.target().starts_with(excluded))
+        {
+            return;
+        }
+
+        let sink = SINK.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(sink) = sink.as_ref() else {
+            return;
+        };
+        // More verbose levels compare greater in tracing
+        if *metadata.level() > sink.max_level {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let entry = LogEntry {
+            timestamp: Utc::now(),
+            level: metadata.level().as_str().to_lowercase(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        // Drop rather than block the caller when the collector is slow
+        let _ = sink.sender.try_send(entry);
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .insert(field.name().to_string(), serde_json::json!(value));
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.insert(
+                field.name().to_string(),
+                serde_json::json!(format!("{:?}", value)),
+            );
+        }
+    }
+}
+
+/// Check that the endpoint matches the protocol before enabling forwarding
+pub fn validate(settings: &RemoteLogSettings) -> Result<(), String> {
+    parse_level(&settings.level)?;
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let url = url::Url::parse(&settings.endpoint)
+        .map_err(|e| format!("Invalid log endpoint '{}': {}", settings.endpoint, e))?;
+    match settings.protocol {
+        RemoteLogProtocol::Loki if !matches!(url.scheme(), "http" | "https") => {
+            Err("Loki endpoint must use http or https".to_string())
+        }
+        RemoteLogProtocol::Syslog if url.scheme() != "udp" || url.host_str().is_none() => {
+            Err("Syslog endpoint must look like udp://host:514".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Start, replace or stop the forwarding task to match `settings`
+pub fn configure(settings: &RemoteLogSettings) -> Result<(), String> {
+    validate(settings)?;
+
+    let mut sink = SINK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Dropping the old sender ends the previous forwarding task
+    *sink = None;
+
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let max_level = Level::from_str(&parse_level(&settings.level)?)
+        .map_err(|e| format!("Invalid log level: {}", e))?;
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    tauri::async_runtime::spawn(forward(settings.clone(), receiver));
+    *sink = Some(ActiveSink { sender, max_level });
+
+    Ok(())
+}
+
+async fn forward(settings: RemoteLogSettings, mut receiver: mpsc::Receiver<LogEntry>) {
+    let client = reqwest::Client::new();
+    let socket = match settings.protocol {
+        RemoteLogProtocol::Syslog => tokio::net::UdpSocket::bind("0.0.0.0:0").await.ok(),
+        RemoteLogProtocol::Loki => None,
+    };
+    let syslog_target = url::Url::parse(&settings.endpoint)
+        .ok()
+        .and_then(|url| Some(format!("{}:{}", url.host_str()?, url.port().unwrap_or(514))));
+
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + BATCH_WINDOW;
+        while batch.len() < MAX_BATCH {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(entry)) => batch.push(entry),
+                _ => break,
+            }
+        }
+
+        let result = match settings.protocol {
+            RemoteLogProtocol::Loki => client
+                .post(&settings.endpoint)
+                .json(&loki_payload(&batch, &settings.labels))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            RemoteLogProtocol::Syslog => match (&socket, &syslog_target) {
+                (Some(socket), Some(target)) => {
+                    let mut result = Ok(());
+                    for entry in &batch {
+                        if let Err(e) = socket.send_to(syslog_line(entry).as_bytes(), target).await
+                        {
+                            result = Err(e.to_string());
+                            break;
+                        }
+                    }
+                    result
+                }
+                _ => Err("Syslog socket unavailable".to_string()),
+            },
+        };
+
+        // Reported on stderr only: logging here would be forwarded again
+        if let Err(e) = result {
+            eprintln!(
+                "[WARN] Remote log forwarding dropped {} records: {}",
+                batch.len(),
+                e
+            );
+        }
+    }
+}
+
+/// Build a Loki push request with one stream per level
+pub fn loki_payload(entries: &[LogEntry], labels: &BTreeMap<String, String>) -> serde_json::Value {
+    let mut streams: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+    for entry in entries {
+        let line = serde_json::json!({
+            "target": entry.target,
+            "message": entry.message,
+            "fields": entry.fields,
+        });
+        let nanos = entry
+            .timestamp
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+            .to_string();
+        streams
+            .entry(entry.level.clone())
+            .or_default()
+            .push(serde_json::json!([nanos, line.to_string()]));
+    }
+
+    let streams: Vec<serde_json::Value> = streams
+        .into_iter()
+        .map(|(level, values)| {
+            let mut stream = serde_json::Map::new();
+            stream.insert("app".to_string(), serde_json::json!("opencode-nexus"));
+            for (key, value) in labels {
+                stream.insert(key.clone(), serde_json::json!(value));
+            }
+            stream.insert("level".to_string(), serde_json::json!(level));
+            serde_json::json!({ "stream": stream, "values": values })
+        })
+        .collect();
+
+    serde_json::json!({ "streams": streams })
+}
+
+/// Format an entry as an RFC 5424 syslog message (facility user)
+pub fn syslog_line(entry: &LogEntry) -> String {
+    let severity = match entry.level.as_str() {
+        "error" => 3,
+        "warn" => 4,
+        "info" => 6,
+        _ => 7,
+    };
+    let priority = 8 + severity;
+    let fields = if entry.fields.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", serde_json::Value::Object(entry.fields.clone()))
+    };
+
+    format!(
+        "<{}>1 {} - opencode-nexus - {} - {}{}",
+        priority,
+        entry.timestamp.to_rfc3339(),
+        entry.target,
+        entry.message,
+        fields
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: &str, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: Utc::now(),
+            level: level.to_string(),
+            target: "stream".to_string(),
+            message: message.to_string(),
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_validate_endpoint_per_protocol() {
+        let mut settings = RemoteLogSettings::default();
+        assert!(
+            validate(&settings).is_ok(),
+            "disabled sink needs no endpoint"
+        );
+
+        settings.enabled = true;
+        settings.endpoint = "http://loki:3100/loki/api/v1/push".to_string();
+        assert!(validate(&settings).is_ok());
+
+        settings.protocol = RemoteLogProtocol::Syslog;
+        assert!(validate(&settings).is_err());
+        settings.endpoint = "udp://logs.local:514".to_string();
+        assert!(validate(&settings).is_ok());
+
+        settings.level = "loud".to_string();
+        assert!(validate(&settings).is_err());
+    }
+
+    #[test]
+    fn test_loki_payload_groups_by_level() {
+        let mut labels = BTreeMap::new();
+        labels.insert("host".to_string(), "homelab".to_string());
+
+        let payload = loki_payload(
+            &[entry("info", "a"), entry("error", "b"), entry("info", "c")],
+            &labels,
+        );
+        let streams = payload["streams"].as_array().unwrap();
+        assert_eq!(streams.len(), 2);
+
+        let info = streams
+            .iter()
+            .find(|s| s["stream"]["level"] == "info")
+            .unwrap();
+        assert_eq!(info["stream"]["host"], "homelab");
+        assert_eq!(info["stream"]["app"], "opencode-nexus");
+        assert_eq!(info["values"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_syslog_line_format() {
+        let line = syslog_line(&entry("warn", "Slow stream"));
+        assert!(line.starts_with("<12>1 "));
+        assert!(line.ends_with("opencode-nexus - stream - Slow stream"));
+    }
+}
@@ -0,0 +1,376 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::logging::{active_log_path, segment_paths};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// Page size used when the caller does not pass a `limit`
+pub const DEFAULT_QUERY_LIMIT: usize = 200;
+
+/// Upper bound on a single page so one query never ships the whole history
+pub const MAX_QUERY_LIMIT: usize = 1000;
+
+/// Filters accepted by `query_logs`; every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQuery {
+    /// Minimum level (e.g. "warn" returns warnings and errors)
+    pub level: Option<String>,
+    /// Log target, matched exactly or as a `module::` prefix
+    pub module: Option<String>,
+    /// Case-insensitive text searched in the message and fields
+    pub text: Option<String>,
+    /// Only entries at or after this instant
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// A single structured log line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Remaining structured fields (session_id, connection, ...)
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One page of query results, newest entries first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogQueryResult {
+    pub entries: Vec<LogEntry>,
+    pub offset: usize,
+    pub has_more: bool,
+}
+
+impl LogEntry {
+    /// Parse a line written by the JSON tracing layer; other lines are ignored
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(line).ok()?;
+
+        let timestamp = fields
+            .remove("timestamp")?
+            .as_str()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())?
+            .with_timezone(&Utc);
+        let level = fields.remove("level")?.as_str()?.to_lowercase();
+        let target = fields
+            .remove("target")
+            .and_then(|t| t.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let message = fields
+            .remove("message")
+            .and_then(|m| m.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        Some(Self {
+            timestamp,
+            level,
+            target,
+            message,
+            fields,
+        })
+    }
+}
+
+impl LogQuery {
+    fn min_level_rank(&self) -> usize {
+        self.level.as_deref().map(level_rank).unwrap_or(0)
+    }
+
+    /// Whether an entry passes the level, module and text filters
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if level_rank(&entry.level) < self.min_level_rank() {
+            return false;
+        }
+
+        if let Some(module) = &self.module {
+            let module = module.trim();
+            if !module.is_empty()
+                && entry.target != module
+                && !entry.target.starts_with(&format!("{}::", module))
+            {
+                return false;
+            }
+        }
+
+        if let Some(text) = &self.text {
+            let needle = text.to_lowercase();
+            if !needle.is_empty() {
+                let in_message = entry.message.to_lowercase().contains(&needle);
+                let in_fields = entry
+                    .fields
+                    .values()
+                    .any(|v| v.to_string().to_lowercase().contains(&needle));
+                if !in_message && !in_fields {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+fn level_rank(level: &str) -> usize {
+    match level.to_lowercase().as_str() {
+        "trace" => 0,
+        "debug" => 1,
+        "info" => 2,
+        "warn" | "warning" => 3,
+        "error" => 4,
+        _ => 0,
+    }
+}
+
+/// Run a query over the active log and its compressed segments, newest first.
+/// Files are scanned in order of age and the scan stops as soon as the page is
+/// full or entries fall before `since`.
+pub fn query_logs(log_dir: &Path, query: &LogQuery) -> io::Result<LogQueryResult> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_QUERY_LIMIT)
+        .clamp(1, MAX_QUERY_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+
+    let mut files = vec![active_log_path(log_dir)];
+    files.extend(segment_paths(log_dir));
+
+    let mut skipped = 0;
+    let mut entries = Vec::with_capacity(limit);
+
+    for path in files {
+        if !path.exists() {
+            continue;
+        }
+
+        for entry in read_entries(&path)?.into_iter().rev() {
+            if let Some(since) = query.since {
+                if entry.timestamp < since {
+                    return Ok(LogQueryResult {
+                        entries,
+                        offset,
+                        has_more: false,
+                    });
+                }
+            }
+
+            if !query.matches(&entry) {
+                continue;
+            }
+
+            if skipped < offset {
+                skipped += 1;
+                continue;
+            }
+
+            if entries.len() == limit {
+                return Ok(LogQueryResult {
+                    entries,
+                    offset,
+                    has_more: true,
+                });
+            }
+            entries.push(entry);
+        }
+    }
+
+    Ok(LogQueryResult {
+        entries,
+        offset,
+        has_more: false,
+    })
+}
+
+/// Parse every structured entry in a log file, decompressing segments
+fn read_entries(path: &Path) -> io::Result<Vec<LogEntry>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn BufRead> = if path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        if let Some(entry) = LogEntry::parse(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::{append_line, LogRotationConfig};
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn write_entry(dir: &Path, ts: DateTime<Utc>, level: &str, target: &str, message: &str) {
+        let line = serde_json::json!({
+            "timestamp": ts.to_rfc3339(),
+            "level": level,
+            "target": target,
+            "message": message,
+            "session_id": "s-1",
+        });
+        append_line(dir, &format!("{}\n", line), &LogRotationConfig::default()).unwrap();
+    }
+
+    fn create_test_logs() -> (TempDir, DateTime<Utc>) {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let start = Utc::now() - Duration::minutes(10);
+        write_entry(temp.path(), start, "INFO", "connection", "Connected");
+        write_entry(
+            temp.path(),
+            start + Duration::minutes(1),
+            "WARN",
+            "stream",
+            "Slow stream",
+        );
+        write_entry(
+            temp.path(),
+            start + Duration::minutes(2),
+            "ERROR",
+            "chat",
+            "Send failed",
+        );
+        write_entry(
+            temp.path(),
+            start + Duration::minutes(3),
+            "DEBUG",
+            "stream",
+            "Chunk received",
+        );
+        (temp, start)
+    }
+
+    #[test]
+    fn test_query_returns_newest_first() {
+        let (temp, _) = create_test_logs();
+        let result = query_logs(temp.path(), &LogQuery::default()).unwrap();
+        let messages: Vec<_> = result.entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["Chunk received", "Send failed", "Slow stream", "Connected"]
+        );
+        assert!(!result.has_more);
+    }
+
+    #[test]
+    fn test_query_filters_by_level_module_and_text() {
+        let (temp, _) = create_test_logs();
+
+        let warn_and_up = LogQuery {
+            level: Some("warn".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query_logs(temp.path(), &warn_and_up).unwrap().entries.len(),
+            2
+        );
+
+        let stream_only = LogQuery {
+            module: Some("stream".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query_logs(temp.path(), &stream_only).unwrap().entries.len(),
+            2
+        );
+
+        let text = LogQuery {
+            text: Some("FAILED".to_string()),
+            ..Default::default()
+        };
+        let result = query_logs(temp.path(), &text).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].target, "chat");
+        assert_eq!(result.entries[0].fields["session_id"], "s-1");
+    }
+
+    #[test]
+    fn test_query_since_and_pagination() {
+        let (temp, start) = create_test_logs();
+
+        let since = LogQuery {
+            since: Some(start + Duration::seconds(90)),
+            ..Default::default()
+        };
+        assert_eq!(query_logs(temp.path(), &since).unwrap().entries.len(), 2);
+
+        let first_page = LogQuery {
+            limit: Some(3),
+            ..Default::default()
+        };
+        let page = query_logs(temp.path(), &first_page).unwrap();
+        assert_eq!(page.entries.len(), 3);
+        assert!(page.has_more);
+
+        let second_page = LogQuery {
+            limit: Some(3),
+            offset: Some(3),
+            ..Default::default()
+        };
+        let page = query_logs(temp.path(), &second_page).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].message, "Connected");
+        assert!(!page.has_more);
+    }
+
+    #[test]
+    fn test_query_reads_compressed_segments() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let config = LogRotationConfig {
+            max_file_bytes: 150,
+            ..Default::default()
+        };
+        let start = Utc::now();
+        for i in 0..10 {
+            let line = serde_json::json!({
+                "timestamp": (start + Duration::seconds(i)).to_rfc3339(),
+                "level": "INFO",
+                "target": "chat",
+                "message": format!("entry {}", i),
+            });
+            append_line(temp.path(), &format!("{}\n", line), &config).unwrap();
+        }
+        assert!(!segment_paths(temp.path()).is_empty());
+
+        let result = query_logs(temp.path(), &LogQuery::default()).unwrap();
+        assert_eq!(result.entries.first().unwrap().message, "entry 9");
+        assert!(result.entries.len() > 1);
+    }
+
+    #[test]
+    fn test_parse_skips_plain_text_lines() {
+        assert!(LogEntry::parse("[2024-01-01] [INFO] legacy line").is_none());
+    }
+}
@@ -0,0 +1,215 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::log_query::{LogEntry, LogQuery};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+/// Tauri event carrying newly written log entries
+pub const LOGS_APPENDED_EVENT: &str = "logs-appended";
+
+/// Lines written within this window are delivered in one event
+pub const LOG_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Payload of a `logs-appended` event for one subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogsAppended {
+    pub subscription_id: String,
+    pub entries: Vec<LogEntry>,
+}
+
+/// Tracks live log subscriptions and forwards matching entries to the frontend
+#[derive(Clone, Default)]
+pub struct LogStreamer {
+    subscriptions: Arc<RwLock<HashMap<String, LogQuery>>>,
+}
+
+impl LogStreamer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a filter and return its subscription id. Paging fields of the
+    /// filter are ignored.
+    pub fn subscribe(&self, filter: LogQuery) -> String {
+        let subscription_id = uuid::Uuid::new_v4().to_string();
+        self.write_subscriptions()
+            .insert(subscription_id.clone(), filter);
+        subscription_id
+    }
+
+    /// Remove a subscription, returning whether it existed
+    pub fn unsubscribe(&self, subscription_id: &str) -> bool {
+        self.write_subscriptions().remove(subscription_id).is_some()
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.read_subscriptions().len()
+    }
+
+    /// Split a batch of entries into one payload per subscription that has
+    /// at least one match
+    pub fn batches_for(&self, entries: &[LogEntry]) -> Vec<LogsAppended> {
+        self.read_subscriptions()
+            .iter()
+            .filter_map(|(subscription_id, filter)| {
+                let matching: Vec<LogEntry> = entries
+                    .iter()
+                    .filter(|entry| filter.matches(entry))
+                    .cloned()
+                    .collect();
+                if matching.is_empty() {
+                    None
+                } else {
+                    Some(LogsAppended {
+                        subscription_id: subscription_id.clone(),
+                        entries: matching,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Forward log lines to subscribers until the channel closes. Nothing is
+    /// logged from this loop since that would feed back into the channel.
+    pub fn start(&self, app_handle: AppHandle, mut receiver: broadcast::Receiver<String>) {
+        let streamer = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let first = match receiver.recv().await {
+                    Ok(chunk) => chunk,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+
+                tokio::time::sleep(LOG_DEBOUNCE).await;
+
+                let mut chunks = vec![first];
+                loop {
+                    match receiver.try_recv() {
+                        Ok(chunk) => chunks.push(chunk),
+                        Err(TryRecvError::Lagged(_)) => continue,
+                        Err(_) => break,
+                    }
+                }
+
+                if streamer.subscription_count() == 0 {
+                    continue;
+                }
+
+                let entries: Vec<LogEntry> = chunks
+                    .iter()
+                    .flat_map(|chunk| chunk.lines())
+                    .filter_map(LogEntry::parse)
+                    .collect();
+
+                for batch in streamer.batches_for(&entries) {
+                    let _ = app_handle.emit(LOGS_APPENDED_EVENT, &batch);
+                }
+            }
+        });
+    }
+
+    fn read_subscriptions(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, LogQuery>> {
+        self.subscriptions
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write_subscriptions(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, LogQuery>> {
+        self.subscriptions
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: &str, target: &str, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: chrono::Utc::now(),
+            level: level.to_string(),
+            target: target.to_string(),
+            message: message.to_string(),
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_and_unsubscribe() {
+        let streamer = LogStreamer::new();
+        let id = streamer.subscribe(LogQuery::default());
+        assert_eq!(streamer.subscription_count(), 1);
+
+        assert!(streamer.unsubscribe(&id));
+        assert!(!streamer.unsubscribe(&id));
+        assert_eq!(streamer.subscription_count(), 0);
+    }
+
+    #[test]
+    fn test_batches_are_filtered_per_subscription() {
+        let streamer = LogStreamer::new();
+        let errors_only = streamer.subscribe(LogQuery {
+            level: Some("error".to_string()),
+            ..Default::default()
+        });
+        let everything = streamer.subscribe(LogQuery::default());
+
+        let entries = vec![
+            entry("info", "chat", "Sent"),
+            entry("error", "stream", "Stream failed"),
+        ];
+        let batches = streamer.batches_for(&entries);
+        assert_eq!(batches.len(), 2);
+
+        let errors = batches
+            .iter()
+            .find(|b| b.subscription_id == errors_only)
+            .unwrap();
+        assert_eq!(errors.entries.len(), 1);
+        assert_eq!(errors.entries[0].message, "Stream failed");
+
+        let all = batches
+            .iter()
+            .find(|b| b.subscription_id == everything)
+            .unwrap();
+        assert_eq!(all.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_no_batch_without_matches() {
+        let streamer = LogStreamer::new();
+        streamer.subscribe(LogQuery {
+            module: Some("connection".to_string()),
+            ..Default::default()
+        });
+        assert!(streamer
+            .batches_for(&[entry("info", "chat", "Sent")])
+            .is_empty());
+    }
+}
@@ -0,0 +1,547 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+/// Name of the active application log file inside the config directory
+pub const LOG_FILE_NAME: &str = "application.log";
+
+/// Number of lines returned by `get_application_logs` when no limit is given
+pub const DEFAULT_TAIL_LINES: usize = 1000;
+
+/// Filter used when `RUST_LOG` is not set
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Serializes writes so two threads never rotate the same file at once
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Levels accepted by `set_log_level`
+pub const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Handle used to swap the active filter after the subscriber is installed
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Fan-out of every line written to the application log
+static LINE_SENDER: OnceLock<broadcast::Sender<String>> = OnceLock::new();
+
+fn line_sender() -> &'static broadcast::Sender<String> {
+    LINE_SENDER.get_or_init(|| broadcast::channel(1024).0)
+}
+
+/// Receive each chunk of JSON lines as it is written to the application log
+pub fn subscribe_lines() -> broadcast::Receiver<String> {
+    line_sender().subscribe()
+}
+
+/// Log chunks not yet read by every live log viewer
+pub fn line_backlog() -> usize {
+    line_sender().len()
+}
+
+/// Size and age limits for the application log
+#[derive(Debug, Clone)]
+pub struct LogRotationConfig {
+    /// Rotate the active file once it would grow past this many bytes
+    pub max_file_bytes: u64,
+    /// Number of gzip-compressed segments kept next to the active file
+    pub max_segments: usize,
+    /// Compressed segments older than this are deleted
+    pub max_age: Duration,
+}
+
+impl Default for LogRotationConfig {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 5 * 1024 * 1024,
+            max_segments: 5,
+            max_age: Duration::from_secs(14 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// Path of the active log file
+pub fn active_log_path(log_dir: &Path) -> PathBuf {
+    log_dir.join(LOG_FILE_NAME)
+}
+
+/// Path of the compressed segment with the given index (1 = most recent)
+pub fn segment_path(log_dir: &Path, index: usize) -> PathBuf {
+    log_dir.join(format!("{}.{}.gz", LOG_FILE_NAME, index))
+}
+
+/// Install the global tracing subscriber: human-readable output on the
+/// console and one JSON object per line in the rotating application log.
+/// `RUST_LOG` takes precedence over the persisted `directives`.
+pub fn init(log_dir: PathBuf, directives: &str) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(directives))
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let console_layer = fmt::layer().with_target(true);
+    let file_layer = fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_current_span(false)
+        .with_span_list(false)
+        .with_writer(RotatingFileWriter::new(
+            log_dir,
+            LogRotationConfig::default(),
+        ));
+
+    // A subscriber may already be installed (tests, repeated `run` calls)
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(console_layer)
+        .with(file_layer)
+        .with(crate::log_forwarding::RemoteLogLayer)
+        .try_init();
+
+    if installed.is_ok() {
+        let _ = FILTER_HANDLE.set(handle);
+    }
+}
+
+/// Normalize and validate a log level name
+pub fn parse_level(level: &str) -> Result<String, String> {
+    let normalized = level.trim().to_lowercase();
+    if LOG_LEVELS.contains(&normalized.as_str()) {
+        Ok(normalized)
+    } else {
+        Err(format!(
+            "Invalid log level '{}'. Expected one of: {}",
+            level,
+            LOG_LEVELS.join(", ")
+        ))
+    }
+}
+
+/// Validate a log target name such as `stream` or `connection`
+pub fn parse_target(target: &str) -> Result<String, String> {
+    let target = target.trim();
+    let valid = !target.is_empty()
+        && target
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+    if valid {
+        Ok(target.to_string())
+    } else {
+        Err(format!("Invalid log target '{}'", target))
+    }
+}
+
+/// Build an `EnvFilter` directive string from a global level and per-target
+/// overrides, e.g. `info,stream=debug,health=warn`
+pub fn build_directives(level: &str, targets: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut directives = vec![parse_level(level)?];
+    for (target, target_level) in targets {
+        directives.push(format!(
+            "{}={}",
+            parse_target(target)?,
+            parse_level(target_level)?
+        ));
+    }
+    Ok(directives.join(","))
+}
+
+/// Replace the active filter without restarting the application
+pub fn set_filter(level: &str, targets: &BTreeMap<String, String>) -> Result<(), String> {
+    let directives = build_directives(level, targets)?;
+    let filter = EnvFilter::try_new(&directives)
+        .map_err(|e| format!("Invalid log filter '{}': {}", directives, e))?;
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "Logging has not been initialized".to_string())?;
+
+    handle
+        .reload(filter)
+        .map_err(|e| format!("Failed to update log level: {}", e))
+}
+
+/// Writer handed to the JSON tracing layer; every formatted event is appended
+/// to the application log through the rotation logic
+#[derive(Debug, Clone)]
+pub struct RotatingFileWriter {
+    log_dir: PathBuf,
+    config: LogRotationConfig,
+}
+
+impl RotatingFileWriter {
+    pub fn new(log_dir: PathBuf, config: LogRotationConfig) -> Self {
+        Self { log_dir, config }
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        append_bytes(&self.log_dir, buf, &self.config)?;
+
+        let sender = line_sender();
+        if sender.receiver_count() > 0 {
+            let _ = sender.send(String::from_utf8_lossy(buf).into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFileWriter {
+    type Writer = RotatingFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Append a single line to the active log, rotating first if it is full
+pub fn append_line(log_dir: &Path, line: &str, config: &LogRotationConfig) -> io::Result<()> {
+    append_bytes(log_dir, line.as_bytes(), config)
+}
+
+fn append_bytes(log_dir: &Path, bytes: &[u8], config: &LogRotationConfig) -> io::Result<()> {
+    let _guard = WRITE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    fs::create_dir_all(log_dir)?;
+    let log_path = active_log_path(log_dir);
+
+    if let Ok(metadata) = fs::metadata(&log_path) {
+        if metadata.len() > 0 && metadata.len() + bytes.len() as u64 > config.max_file_bytes {
+            rotate(log_dir, config)?;
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+    file.write_all(bytes)?;
+    file.flush()
+}
+
+/// Compress the active log into segment 1, shifting older segments up and
+/// dropping any beyond `max_segments` or older than `max_age`
+pub fn rotate(log_dir: &Path, config: &LogRotationConfig) -> io::Result<()> {
+    let log_path = active_log_path(log_dir);
+    if !log_path.exists() {
+        return Ok(());
+    }
+
+    if config.max_segments == 0 {
+        return fs::remove_file(&log_path);
+    }
+
+    let oldest = segment_path(log_dir, config.max_segments);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..config.max_segments).rev() {
+        let from = segment_path(log_dir, index);
+        if from.exists() {
+            fs::rename(&from, segment_path(log_dir, index + 1))?;
+        }
+    }
+
+    let mut source = File::open(&log_path)?;
+    let target = File::create(segment_path(log_dir, 1))?;
+    let mut encoder = GzEncoder::new(target, Compression::default());
+    io::copy(&mut source, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(&log_path)?;
+
+    prune_expired_segments(log_dir, config)
+}
+
+/// Delete compressed segments whose modification time exceeds `max_age`
+pub fn prune_expired_segments(log_dir: &Path, config: &LogRotationConfig) -> io::Result<()> {
+    let now = SystemTime::now();
+    for path in segment_paths(log_dir) {
+        let expired = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map(|modified| now.duration_since(modified).unwrap_or_default() > config.max_age)
+            .unwrap_or(false);
+        if expired {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Existing compressed segments, most recent first
+pub fn segment_paths(log_dir: &Path) -> Vec<PathBuf> {
+    let mut segments: Vec<(usize, PathBuf)> = fs::read_dir(log_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let index = name
+                .strip_prefix(LOG_FILE_NAME)?
+                .strip_prefix('.')?
+                .strip_suffix(".gz")?
+                .parse::<usize>()
+                .ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    segments.sort_by_key(|(index, _)| *index);
+    segments.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Read up to `lines` lines of a plain-text file, oldest first, ending
+/// `offset` lines before its end, without loading the whole file into
+/// memory. Passing the number of lines already shown as `offset` pages
+/// further back.
+pub fn read_tail(path: &Path, lines: usize, offset: usize) -> io::Result<Vec<String>> {
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+    let wanted = lines.saturating_add(offset);
+
+    let mut file = File::open(path)?;
+    let file_len = file.seek(SeekFrom::End(0))?;
+    let mut position = file_len;
+    let mut buffer: Vec<u8> = Vec::new();
+    const CHUNK_SIZE: u64 = 8 * 1024;
+
+    // Walk backwards until we have seen enough line breaks (one extra so the
+    // first returned line is complete)
+    while position > 0 && bytecount_newlines(&buffer) <= wanted {
+        let read_size = CHUNK_SIZE.min(position);
+        position -= read_size;
+        file.seek(SeekFrom::Start(position))?;
+        let mut chunk = vec![0u8; read_size as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buffer);
+    let mut all: Vec<&str> = text.lines().collect();
+    if position > 0 && !all.is_empty() {
+        // The first line was cut in the middle by the chunk boundary
+        all.remove(0);
+    }
+    let end = all.len().saturating_sub(offset);
+    let start = end.saturating_sub(lines);
+    Ok(all[start..end]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Remove the active log and all compressed segments
+pub fn clear_logs(log_dir: &Path) -> io::Result<()> {
+    let _guard = WRITE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let log_path = active_log_path(log_dir);
+    if log_path.exists() {
+        fs::remove_file(&log_path)?;
+    }
+    for segment in segment_paths(log_dir) {
+        fs::remove_file(segment)?;
+    }
+    Ok(())
+}
+
+fn bytecount_newlines(buffer: &[u8]) -> usize {
+    buffer.iter().filter(|b| **b == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::{BufRead, BufReader};
+    use tempfile::TempDir;
+
+    fn small_config() -> LogRotationConfig {
+        LogRotationConfig {
+            max_file_bytes: 64,
+            max_segments: 2,
+            max_age: Duration::from_secs(60 * 60),
+        }
+    }
+
+    #[test]
+    fn test_append_creates_log_file() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        append_line(temp.path(), "hello\n", &small_config()).expect("Should append");
+
+        let content = fs::read_to_string(active_log_path(temp.path())).unwrap();
+        assert_eq!(content, "hello\n");
+    }
+
+    #[test]
+    fn test_rotation_compresses_old_segment() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let config = small_config();
+
+        for i in 0..10 {
+            append_line(temp.path(), &format!("line number {}\n", i), &config).unwrap();
+        }
+
+        let segments = segment_paths(temp.path());
+        assert!(!segments.is_empty(), "Should have rotated at least once");
+        assert!(segments.len() <= config.max_segments);
+
+        let reader = BufReader::new(GzDecoder::new(File::open(&segments[0]).unwrap()));
+        let archived: Vec<String> = reader.lines().collect::<Result<_, _>>().unwrap();
+        assert!(archived.iter().all(|l| l.starts_with("line number")));
+
+        let active_len = fs::metadata(active_log_path(temp.path())).unwrap().len();
+        assert!(active_len <= config.max_file_bytes);
+    }
+
+    #[test]
+    fn test_rotation_respects_segment_limit() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let config = small_config();
+
+        for i in 0..100 {
+            append_line(temp.path(), &format!("entry {}\n", i), &config).unwrap();
+        }
+
+        assert_eq!(segment_paths(temp.path()).len(), config.max_segments);
+        assert!(!segment_path(temp.path(), config.max_segments + 1).exists());
+    }
+
+    #[test]
+    fn test_prune_expired_segments() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let mut config = small_config();
+        append_line(temp.path(), "old entry\n", &config).unwrap();
+        rotate(temp.path(), &config).unwrap();
+        assert_eq!(segment_paths(temp.path()).len(), 1);
+
+        config.max_age = Duration::ZERO;
+        std::thread::sleep(Duration::from_millis(10));
+        prune_expired_segments(temp.path(), &config).unwrap();
+        assert!(segment_paths(temp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_read_tail_returns_last_lines() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let path = temp.path().join("tail.log");
+        let content: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, content).unwrap();
+
+        let tail = read_tail(&path, 3, 0).expect("Should read tail");
+        assert_eq!(tail, vec!["line 4997", "line 4998", "line 4999"]);
+
+        let everything = read_tail(&path, 10_000, 0).expect("Should read whole file");
+        assert_eq!(everything.len(), 5000);
+        assert_eq!(everything[0], "line 0");
+    }
+
+    #[test]
+    fn test_read_tail_walks_back_from_offset() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let path = temp.path().join("tail.log");
+        let content: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, content).unwrap();
+
+        let page = read_tail(&path, 2, 3).expect("Should read page");
+        assert_eq!(page, vec!["line 4995", "line 4996"]);
+
+        let oldest = read_tail(&path, 10, 4995).expect("Should read first page");
+        assert_eq!(
+            oldest,
+            vec!["line 0", "line 1", "line 2", "line 3", "line 4"]
+        );
+
+        assert!(read_tail(&path, 10, 5000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_layer_writes_structured_lines() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let writer = RotatingFileWriter::new(temp.path().to_path_buf(), small_config());
+        let subscriber = tracing_subscriber::registry()
+            .with(fmt::layer().json().flatten_event(true).with_writer(writer));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "chat", session_id = "abc", "Sending message");
+        });
+
+        let lines = read_tail(&active_log_path(temp.path()), 1, 0).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(entry["level"], "INFO");
+        assert_eq!(entry["target"], "chat");
+        assert_eq!(entry["session_id"], "abc");
+        assert_eq!(entry["message"], "Sending message");
+    }
+
+    #[test]
+    fn test_clear_logs_removes_segments() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let config = small_config();
+        for i in 0..20 {
+            append_line(temp.path(), &format!("entry {}\n", i), &config).unwrap();
+        }
+
+        clear_logs(temp.path()).expect("Should clear logs");
+        assert!(!active_log_path(temp.path()).exists());
+        assert!(segment_paths(temp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_level_normalizes_and_rejects() {
+        assert_eq!(parse_level(" DEBUG ").unwrap(), "debug");
+        assert_eq!(parse_level("warn").unwrap(), "warn");
+        assert!(parse_level("verbose").is_err());
+        assert!(parse_level("").is_err());
+    }
+
+    #[test]
+    fn test_build_directives_with_targets() {
+        let mut targets = BTreeMap::new();
+        targets.insert("stream".to_string(), "DEBUG".to_string());
+        targets.insert("health".to_string(), "warn".to_string());
+
+        let directives = build_directives("info", &targets).unwrap();
+        assert_eq!(directives, "info,health=warn,stream=debug");
+        assert!(EnvFilter::try_new(&directives).is_ok());
+
+        targets.insert("bad target".to_string(), "info".to_string());
+        assert!(build_directives("info", &targets).is_err());
+    }
+}
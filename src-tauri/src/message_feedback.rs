@@ -0,0 +1,388 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Good/bad ratings on assistant replies, kept locally so users can build
+//! a corpus of answers worth keeping or avoiding.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Most characters a feedback comment may have
+const MAX_COMMENT_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rating {
+    Good,
+    Bad,
+}
+
+/// The user's rating of one reply
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessageFeedback {
+    pub session_id: String,
+    pub message_id: String,
+    pub rating: Rating,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub rated_at: DateTime<Utc>,
+    /// Whether the server accepted a copy
+    #[serde(default)]
+    pub forwarded: bool,
+}
+
+/// A rated reply with the prompt that produced it, one line of an export
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RatedExchange {
+    pub session_id: String,
+    pub message_id: String,
+    pub rating: Rating,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub prompt: String,
+    pub response: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub rated_at: DateTime<Utc>,
+}
+
+/// On-disk shape of `message_feedback.json`, keyed by message id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FeedbackFile {
+    #[serde(default)]
+    feedback: HashMap<String, MessageFeedback>,
+}
+
+/// Concatenated text parts of a `{ info, parts }` message
+pub fn message_text(message: &serde_json::Value) -> String {
+    message
+        .get("parts")
+        .and_then(|parts| parts.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|part| part.get("type").and_then(|kind| kind.as_str()) == Some("text"))
+        .filter_map(|part| part.get("text").and_then(|text| text.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Pair each rated reply in a session's `{ info, parts }` messages with
+/// the user message before it. Ratings whose message is gone are dropped.
+pub fn rated_exchanges(
+    feedback: &[MessageFeedback],
+    messages: &[serde_json::Value],
+) -> Vec<RatedExchange> {
+    let id_of = |message: &serde_json::Value| {
+        message
+            .pointer("/info/id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string)
+    };
+    feedback
+        .iter()
+        .filter_map(|rating| {
+            let index = messages
+                .iter()
+                .position(|message| id_of(message).as_deref() == Some(&rating.message_id))?;
+            let reply = &messages[index];
+            let prompt = messages[..index]
+                .iter()
+                .rev()
+                .find(|message| {
+                    message.pointer("/info/role").and_then(|role| role.as_str()) == Some("user")
+                })
+                .map(message_text)
+                .unwrap_or_default();
+            let model = reply
+                .pointer("/info/modelID")
+                .and_then(|model| model.as_str())
+                .map(|model| {
+                    match reply
+                        .pointer("/info/providerID")
+                        .and_then(|provider| provider.as_str())
+                    {
+                        Some(provider) => format!("{}/{}", provider, model),
+                        None => model.to_string(),
+                    }
+                });
+            Some(RatedExchange {
+                session_id: rating.session_id.clone(),
+                message_id: rating.message_id.clone(),
+                rating: rating.rating,
+                comment: rating.comment.clone(),
+                prompt,
+                response: message_text(reply),
+                model,
+                rated_at: rating.rated_at,
+            })
+        })
+        .collect()
+}
+
+/// Write exchanges to `path` as JSON lines
+pub fn write_exchanges(path: &Path, exchanges: &[RatedExchange]) -> Result<(), AppError> {
+    let fs_error = |e: std::io::Error| AppError::FileSystemError {
+        path: path.to_string_lossy().to_string(),
+        message: "Failed to write feedback export".to_string(),
+        details: e.to_string(),
+    };
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path).map_err(fs_error)?);
+    for exchange in exchanges {
+        let line = serde_json::to_string(exchange).map_err(|e| AppError::DataError {
+            message: "Failed to serialize feedback".to_string(),
+            details: e.to_string(),
+        })?;
+        writeln!(file, "{}", line).map_err(fs_error)?;
+    }
+    file.flush().map_err(fs_error)
+}
+
+/// Persistent message ratings
+#[derive(Clone)]
+pub struct FeedbackStore {
+    config_dir: PathBuf,
+    data: Arc<Mutex<FeedbackFile>>,
+}
+
+impl FeedbackStore {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            data: Arc::new(Mutex::new(FeedbackFile::default())),
+        }
+    }
+
+    fn get_feedback_file_path(&self) -> PathBuf {
+        self.config_dir.join("message_feedback.json")
+    }
+
+    fn lock_data(&self) -> MutexGuard<'_, FeedbackFile> {
+        self.data.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Feedback store lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let feedback_file = self.get_feedback_file_path();
+        if !feedback_file.exists() {
+            return Ok(());
+        }
+
+        let feedback_json =
+            std::fs::read_to_string(&feedback_file).map_err(|e| AppError::FileSystemError {
+                path: feedback_file.to_string_lossy().to_string(),
+                message: "Failed to read feedback file".to_string(),
+                details: e.to_string(),
+            })?;
+        let loaded: FeedbackFile =
+            serde_json::from_str(&feedback_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse feedback file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        *self.lock_data() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, data: &FeedbackFile) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let feedback_json = serde_json::to_string_pretty(data)?;
+        std::fs::write(self.get_feedback_file_path(), feedback_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_feedback_file_path().to_string_lossy().to_string(),
+                message: "Failed to write feedback file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Rate a message, replacing any earlier rating of it
+    pub fn rate(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        rating: Rating,
+        comment: Option<String>,
+    ) -> Result<MessageFeedback, Box<dyn std::error::Error>> {
+        if session_id.trim().is_empty() || message_id.trim().is_empty() {
+            return Err(AppError::ValidationError {
+                field: "message_id".to_string(),
+                message: "Session and message IDs are required".to_string(),
+            }
+            .into());
+        }
+        let comment = comment
+            .map(|comment| comment.trim().to_string())
+            .filter(|comment| !comment.is_empty());
+        if comment
+            .as_ref()
+            .is_some_and(|comment| comment.chars().count() > MAX_COMMENT_CHARS)
+        {
+            return Err(AppError::ValidationError {
+                field: "comment".to_string(),
+                message: format!("Comments are limited to {} characters", MAX_COMMENT_CHARS),
+            }
+            .into());
+        }
+
+        let feedback = MessageFeedback {
+            session_id: session_id.to_string(),
+            message_id: message_id.to_string(),
+            rating,
+            comment,
+            rated_at: Utc::now(),
+            forwarded: false,
+        };
+        self.modify(|data| {
+            data.feedback
+                .insert(feedback.message_id.clone(), feedback.clone());
+        })?;
+        Ok(feedback)
+    }
+
+    /// Record that the server accepted a copy of the rating
+    pub fn mark_forwarded(&self, message_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.modify(|data| {
+            if let Some(feedback) = data.feedback.get_mut(message_id) {
+                feedback.forwarded = true;
+            }
+        })
+    }
+
+    /// Remove a rating; returns whether there was one
+    pub fn remove(&self, message_id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        self.modify(|data| data.feedback.remove(message_id).is_some())
+    }
+
+    /// Ratings, optionally for one session, oldest first
+    pub fn list(&self, session_id: Option<&str>) -> Vec<MessageFeedback> {
+        let mut feedback: Vec<_> = self
+            .lock_data()
+            .feedback
+            .values()
+            .filter(|feedback| session_id.is_none_or(|id| feedback.session_id == id))
+            .cloned()
+            .collect();
+        feedback.sort_by_key(|feedback| feedback.rated_at);
+        feedback
+    }
+
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut FeedbackFile) -> T,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        let mut updated = data.clone();
+        let result = f(&mut updated);
+        self.save(&updated)?;
+        *data = updated;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rate_and_persist() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FeedbackStore::new(temp_dir.path().to_path_buf());
+        store
+            .rate("s1", "m2", Rating::Bad, Some("  wrong  ".to_string()))
+            .unwrap();
+        let rerated = store
+            .rate("s1", "m2", Rating::Good, Some(" ".to_string()))
+            .unwrap();
+        assert_eq!(rerated.comment, None);
+        store.rate("s2", "m9", Rating::Bad, None).unwrap();
+        store.mark_forwarded("m9").unwrap();
+        assert!(store.rate("s1", "", Rating::Good, None).is_err());
+        assert!(store
+            .rate(
+                "s1",
+                "m3",
+                Rating::Good,
+                Some("x".repeat(MAX_COMMENT_CHARS + 1))
+            )
+            .is_err());
+
+        let reloaded = FeedbackStore::new(temp_dir.path().to_path_buf());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.list(None).len(), 2);
+        let session = reloaded.list(Some("s1"));
+        assert_eq!(session.len(), 1);
+        assert_eq!(session[0].rating, Rating::Good);
+        assert!(reloaded.list(Some("s2"))[0].forwarded);
+
+        assert!(reloaded.remove("m2").unwrap());
+        assert!(!reloaded.remove("m2").unwrap());
+    }
+
+    #[test]
+    fn test_rated_exchanges() {
+        let text = |text: &str| serde_json::json!([{ "type": "text", "text": text }]);
+        let messages = vec![
+            serde_json::json!({ "info": { "id": "m1", "role": "user" }, "parts": text("Fix the bug") }),
+            serde_json::json!({
+                "info": { "id": "m2", "role": "assistant", "providerID": "anthropic", "modelID": "claude" },
+                "parts": [
+                    { "type": "tool", "tool": "edit" },
+                    { "type": "text", "text": "Done." },
+                ],
+            }),
+        ];
+        let rating = |message_id: &str| MessageFeedback {
+            session_id: "s1".to_string(),
+            message_id: message_id.to_string(),
+            rating: Rating::Good,
+            comment: None,
+            rated_at: Utc::now(),
+            forwarded: false,
+        };
+
+        let exchanges = rated_exchanges(&[rating("m2"), rating("gone")], &messages);
+        assert_eq!(exchanges.len(), 1);
+        assert_eq!(exchanges[0].prompt, "Fix the bug");
+        assert_eq!(exchanges[0].response, "Done.");
+        assert_eq!(exchanges[0].model.as_deref(), Some("anthropic/claude"));
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("feedback.jsonl");
+        write_exchanges(&path, &exchanges).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let line: RatedExchange = serde_json::from_str(written.lines().next().unwrap()).unwrap();
+        assert_eq!(line, exchanges[0]);
+    }
+}
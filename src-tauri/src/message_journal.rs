@@ -0,0 +1,443 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Append-only storage for session messages. Each session has a JSONL file
+//! of records, so adding a message writes one line, and a file cut short by
+//! a crash loses only its last record. Journals are compacted back to one
+//! record per message when they accumulate superseded or unreadable records.
+//!
+//! Records larger than [`COMPRESS_ABOVE`], typically messages carrying
+//! pasted code or tool output, are stored gzipped and base64-encoded on a
+//! line of their own, and decompressed as they are parsed on replay.
+//! Journals written before compression was added remain readable as-is.
+
+use crate::error::AppError;
+use crate::session_manager::ChatMessage;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Directory under the config dir holding one journal per session
+pub const JOURNAL_DIR: &str = "sessions";
+
+pub const JOURNAL_EXTENSION: &str = "jsonl";
+
+/// Superseded records a journal may hold before it is compacted
+const COMPACT_AFTER: usize = 64;
+
+/// Serialized records at least this long are stored compressed
+pub const COMPRESS_ABOVE: usize = 4 * 1024;
+
+/// Start of a compressed record's line; the rest is base64 up to the closing `"}`
+const COMPRESSED_PREFIX: &str = "{\"gzip\":\"";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum MessageRecord {
+    Append {
+        message: ChatMessage,
+    },
+    /// Replaces the earlier message with the same id
+    Update {
+        message: ChatMessage,
+    },
+}
+
+/// A session's messages replayed from its journal
+#[derive(Debug)]
+pub struct Replay {
+    pub messages: Vec<ChatMessage>,
+    /// Records that were superseded or unreadable; compacting drops them
+    pub wasted: usize,
+}
+
+pub struct MessageJournal {
+    dir: PathBuf,
+    /// Superseded records written per session since it was last compacted
+    superseded: Mutex<HashMap<String, usize>>,
+}
+
+/// Whether `name`, relative to the config dir, is a session journal
+pub fn is_journal_entry(name: &str) -> bool {
+    name.strip_prefix(JOURNAL_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .and_then(|file| file.strip_suffix(&format!(".{}", JOURNAL_EXTENSION)))
+        .is_some_and(|stem| !stem.is_empty() && !stem.contains(['/', '\\']) && stem != "..")
+}
+
+impl MessageJournal {
+    pub fn new(config_dir: &Path) -> Self {
+        Self {
+            dir: config_dir.join(JOURNAL_DIR),
+            superseded: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Session ids come from the server, so they are encoded to stay a
+    /// single file name
+    pub fn get_journal_file_path(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{}.{}",
+            urlencoding::encode(session_id),
+            JOURNAL_EXTENSION
+        ))
+    }
+
+    fn lock_superseded(&self) -> MutexGuard<'_, HashMap<String, usize>> {
+        match self.superseded.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                eprintln!("[WARN] Message journal mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    fn write_error(&self, path: &Path, e: impl ToString) -> AppError {
+        AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: "Failed to write message journal".to_string(),
+            details: e.to_string(),
+        }
+    }
+
+    fn append_records(&self, session_id: &str, records: &[MessageRecord]) -> Result<(), AppError> {
+        let path = self.get_journal_file_path(session_id);
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&encode_record(record)?);
+            lines.push('\n');
+        }
+
+        std::fs::create_dir_all(&self.dir).map_err(|e| self.write_error(&self.dir, e))?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(|e| self.write_error(&path, e))
+    }
+
+    /// Add new messages to the end of a session's journal
+    pub fn append(&self, session_id: &str, messages: &[ChatMessage]) -> Result<(), AppError> {
+        let records: Vec<MessageRecord> = messages
+            .iter()
+            .map(|message| MessageRecord::Append {
+                message: message.clone(),
+            })
+            .collect();
+        self.append_records(session_id, &records)
+    }
+
+    /// Record changes to messages already in the journal. Returns `true`
+    /// once enough records are superseded that the caller should compact.
+    pub fn update(&self, session_id: &str, messages: &[ChatMessage]) -> Result<bool, AppError> {
+        let records: Vec<MessageRecord> = messages
+            .iter()
+            .map(|message| MessageRecord::Update {
+                message: message.clone(),
+            })
+            .collect();
+        self.append_records(session_id, &records)?;
+
+        let mut superseded = self.lock_superseded();
+        let count = superseded.entry(session_id.to_string()).or_insert(0);
+        *count += messages.len();
+        Ok(*count >= COMPACT_AFTER)
+    }
+
+    /// Replay a session's journal. Unreadable lines, such as one cut short
+    /// by a crash, are skipped and counted as wasted.
+    pub fn load(&self, session_id: &str) -> Result<Replay, AppError> {
+        let path = self.get_journal_file_path(session_id);
+        if !path.exists() {
+            return Ok(Replay {
+                messages: Vec::new(),
+                wasted: 0,
+            });
+        }
+        let read_error = |e: std::io::Error| AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: "Failed to read message journal".to_string(),
+            details: e.to_string(),
+        };
+        // Read a record at a time rather than holding the whole file
+        let reader = BufReader::new(File::open(&path).map_err(read_error)?);
+
+        let mut messages: Vec<ChatMessage> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut wasted = 0;
+        for line in reader.split(b'\n') {
+            let line = line.map_err(read_error)?;
+            // A record cut short mid-character is unreadable like any other
+            let Ok(line) = std::str::from_utf8(&line) else {
+                wasted += 1;
+                continue;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match decode_record(line) {
+                Ok(MessageRecord::Append { message }) => {
+                    positions.insert(message.id.clone(), messages.len());
+                    messages.push(message);
+                }
+                Ok(MessageRecord::Update { message }) => {
+                    wasted += 1;
+                    match positions.get(&message.id) {
+                        Some(&index) => messages[index] = message,
+                        None => {
+                            positions.insert(message.id.clone(), messages.len());
+                            messages.push(message);
+                        }
+                    }
+                }
+                Err(_) => wasted += 1,
+            }
+        }
+        self.lock_superseded()
+            .insert(session_id.to_string(), wasted);
+        Ok(Replay { messages, wasted })
+    }
+
+    /// Rewrite a session's journal as one record per message
+    pub fn compact(&self, session_id: &str, messages: &[ChatMessage]) -> Result<(), AppError> {
+        let path = self.get_journal_file_path(session_id);
+        let mut contents = String::new();
+        for message in messages {
+            let record = MessageRecord::Append {
+                message: message.clone(),
+            };
+            contents.push_str(&encode_record(&record)?);
+            contents.push('\n');
+        }
+
+        // Write beside the journal and rename so a crash never loses it
+        std::fs::create_dir_all(&self.dir).map_err(|e| self.write_error(&self.dir, e))?;
+        let temp_path = path.with_extension("jsonl.tmp");
+        std::fs::write(&temp_path, contents)
+            .and_then(|()| std::fs::rename(&temp_path, &path))
+            .map_err(|e| self.write_error(&path, e))?;
+        self.lock_superseded().remove(session_id);
+        Ok(())
+    }
+
+    /// Delete a session's journal
+    pub fn remove(&self, session_id: &str) -> Result<(), AppError> {
+        self.lock_superseded().remove(session_id);
+        let path = self.get_journal_file_path(session_id);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(self.write_error(&path, e)),
+        }
+    }
+
+    /// Size of every journal on disk
+    pub fn total_bytes(&self) -> u64 {
+        std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+}
+
+/// Serialize a record as one journal line, compressing it if it is large
+fn encode_record(record: &MessageRecord) -> Result<String, AppError> {
+    let serialize_error = |e: &dyn std::fmt::Display| AppError::ParseError {
+        message: "Failed to serialize message".to_string(),
+        details: Some(e.to_string()),
+    };
+    let line = serde_json::to_string(record).map_err(|e| serialize_error(&e))?;
+    if line.len() < COMPRESS_ABOVE {
+        return Ok(line);
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(line.as_bytes())
+        .map_err(|e| serialize_error(&e))?;
+    let compressed = encoder.finish().map_err(|e| serialize_error(&e))?;
+    let encoded = format!("{}{}\"}}", COMPRESSED_PREFIX, BASE64.encode(compressed));
+    // Text that doesn't compress, such as already-encoded images, stays plain
+    Ok(if encoded.len() < line.len() {
+        encoded
+    } else {
+        line
+    })
+}
+
+/// Parse one journal line, streaming compressed records through the
+/// decoder rather than inflating them into a string first
+fn decode_record(line: &str) -> Result<MessageRecord, serde_json::Error> {
+    match line
+        .strip_prefix(COMPRESSED_PREFIX)
+        .and_then(|rest| rest.strip_suffix("\"}"))
+    {
+        Some(encoded) => {
+            let decoded = base64::read::DecoderReader::new(encoded.as_bytes(), &BASE64);
+            serde_json::from_reader(GzDecoder::new(decoded))
+        }
+        None => serde_json::from_str(line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_manager::{MessagePart, MessageRole};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn message(id: &str, text: &str) -> ChatMessage {
+        ChatMessage {
+            id: id.to_string(),
+            role: MessageRole::User,
+            content: text.to_string(),
+            timestamp: Utc::now(),
+            model: None,
+            metadata: None,
+            parts: vec![MessagePart::text(text)],
+        }
+    }
+
+    #[test]
+    fn test_append_update_and_compact() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+
+        journal
+            .append("ses_1", &[message("m1", "one"), message("m2", "two")])
+            .unwrap();
+        assert!(!journal.update("ses_1", &[message("m1", "edited")]).unwrap());
+
+        let replay = journal.load("ses_1").unwrap();
+        assert_eq!(replay.wasted, 1);
+        let contents: Vec<_> = replay.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["edited", "two"]);
+
+        journal.compact("ses_1", &replay.messages).unwrap();
+        let lines = std::fs::read_to_string(journal.get_journal_file_path("ses_1")).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert_eq!(journal.load("ses_1").unwrap().wasted, 0);
+    }
+
+    #[test]
+    fn test_truncated_record_loses_only_itself() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+        journal
+            .append("ses_1", &[message("m1", "one"), message("m2", "two")])
+            .unwrap();
+
+        let path = journal.get_journal_file_path("ses_1");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &contents[..contents.len() - 10]).unwrap();
+
+        let replay = journal.load("ses_1").unwrap();
+        assert_eq!(replay.messages.len(), 1);
+        assert_eq!(replay.messages[0].id, "m1");
+        assert_eq!(replay.wasted, 1);
+    }
+
+    #[test]
+    fn test_updates_ask_for_compaction() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+        journal.append("ses_1", &[message("m1", "one")]).unwrap();
+        let edits: Vec<_> = (0..COMPACT_AFTER).map(|_| message("m1", "again")).collect();
+        assert!(journal.update("ses_1", &edits).unwrap());
+    }
+
+    #[test]
+    fn test_session_ids_stay_in_the_journal_dir() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+        let path = journal.get_journal_file_path("../escape");
+        assert_eq!(path.parent().unwrap(), temp.path().join(JOURNAL_DIR));
+
+        assert!(is_journal_entry("sessions/ses_1.jsonl"));
+        assert!(!is_journal_entry("sessions/a/b.jsonl"));
+        assert!(!is_journal_entry("sessions/.jsonl"));
+        assert!(!is_journal_entry("chat_sessions.json"));
+    }
+
+    #[test]
+    fn test_large_messages_are_compressed() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+        let pasted = "fn main() { println!(\"hello\"); }\n".repeat(500);
+        journal
+            .append("ses_1", &[message("m1", "short"), message("m2", &pasted)])
+            .unwrap();
+
+        let contents = std::fs::read_to_string(journal.get_journal_file_path("ses_1")).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert!(!lines[0].starts_with(COMPRESSED_PREFIX));
+        assert!(lines[1].starts_with(COMPRESSED_PREFIX));
+        assert!(contents.len() < pasted.len() / 4);
+
+        let replay = journal.load("ses_1").unwrap();
+        assert_eq!(replay.wasted, 0);
+        assert_eq!(replay.messages[1].content, pasted);
+    }
+
+    #[test]
+    fn test_truncated_compressed_record_is_skipped() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+        let pasted = "let x = 1;\n".repeat(1000);
+        journal
+            .append("ses_1", &[message("m1", "one"), message("m2", &pasted)])
+            .unwrap();
+
+        let path = journal.get_journal_file_path("ses_1");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &contents[..contents.len() - 20]).unwrap();
+
+        let replay = journal.load("ses_1").unwrap();
+        assert_eq!(replay.messages.len(), 1);
+        assert_eq!(replay.wasted, 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let temp = TempDir::new().unwrap();
+        let journal = MessageJournal::new(temp.path());
+        journal.append("ses_1", &[message("m1", "one")]).unwrap();
+        assert!(journal.total_bytes() > 0);
+        journal.remove("ses_1").unwrap();
+        journal.remove("ses_1").unwrap();
+        assert!(journal.load("ses_1").unwrap().messages.is_empty());
+    }
+}
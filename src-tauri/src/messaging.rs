@@ -0,0 +1,408 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The one path chat commands take to the server. Sessions and messages
+//! live on the OpenCode server; the local `SessionManager` keeps a copy of
+//! what passes through, for counts, previews and reading while offline.
+
+use crate::api_client::ApiClient;
+use crate::connection_manager::connection_id_for_url;
+use crate::error::{retry_with_backoff, AppError, RetryConfig};
+use crate::session_manager::{
+    ChatMessage, ChatSession, MessagePart, MessageRole, SessionManager, SessionSummary,
+};
+use crate::settings::RetryOperation;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// The local session store, empty until startup has loaded it
+pub type SessionStore = Arc<Mutex<Option<SessionManager>>>;
+
+/// What the chat commands ask of the messaging pipeline
+pub trait MessagingService {
+    /// Summaries of the server's sessions, with counts and previews from
+    /// the local copy, since the server's listing has none
+    async fn list_sessions(&self) -> Result<Vec<SessionSummary>, Box<dyn Error>>;
+
+    async fn create_session(&self, title: Option<String>) -> Result<ChatSession, Box<dyn Error>>;
+
+    /// Send a prompt and wait for the reply
+    async fn send_message(
+        &self,
+        session_id: &str,
+        content: &str,
+    ) -> Result<ChatMessage, Box<dyn Error>>;
+
+    async fn get_session_messages(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<ChatMessage>, Box<dyn Error>>;
+
+    async fn rename_session(&self, session_id: &str, title: &str) -> Result<(), Box<dyn Error>>;
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), Box<dyn Error>>;
+}
+
+/// Messaging through the shared API client, mirrored into the local store
+pub struct Messaging {
+    api_client: Arc<ApiClient>,
+    store: SessionStore,
+}
+
+impl Messaging {
+    pub fn new(api_client: Arc<ApiClient>, store: SessionStore) -> Self {
+        Self { api_client, store }
+    }
+
+    /// Connection the client talks to, which sessions are tagged with
+    async fn connection_id(&self) -> Option<String> {
+        connection_id_for_url(&self.api_client.get_server_url().await?)
+    }
+}
+
+fn into_app_error(error: Box<dyn Error>) -> AppError {
+    match error.downcast::<AppError>() {
+        Ok(error) => *error,
+        Err(other) => AppError::Other {
+            message: other.to_string(),
+        },
+    }
+}
+
+fn parse_error(what: &str, value: &serde_json::Value) -> AppError {
+    AppError::ParseError {
+        message: format!("Failed to parse {}", what),
+        details: Some(value.to_string()),
+    }
+}
+
+impl MessagingService for Messaging {
+    async fn list_sessions(&self) -> Result<Vec<SessionSummary>, Box<dyn Error>> {
+        let listed = self.api_client.list_sessions().await?;
+        let connection_id = self.connection_id().await;
+        let local: HashMap<String, SessionSummary> = match self.store.lock().await.as_ref() {
+            Some(store) => store
+                .list_session_summaries()
+                .await
+                .into_iter()
+                .map(|summary| (summary.id.clone(), summary))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        Ok(listed
+            .iter()
+            .filter_map(ChatSession::from_server)
+            .map(|session| {
+                let mut summary = SessionSummary::from(&session);
+                summary.connection_id = connection_id.clone();
+                if let Some(cached) = local.get(&session.id) {
+                    summary.updated_at = summary.updated_at.max(cached.updated_at);
+                    summary.message_count = cached.message_count;
+                    summary.user_message_count = cached.user_message_count;
+                    summary.last_message_preview = cached.last_message_preview.clone();
+                    summary.last_message_at = cached.last_message_at;
+                }
+                summary
+            })
+            .collect())
+    }
+
+    async fn create_session(&self, title: Option<String>) -> Result<ChatSession, Box<dyn Error>> {
+        let created = self.api_client.create_session(title.as_deref()).await?;
+        let mut session =
+            ChatSession::from_server(&created).ok_or_else(|| parse_error("session", &created))?;
+        session.connection_id = self.connection_id().await;
+
+        if let Some(store) = self.store.lock().await.as_ref() {
+            if let Err(e) = store.import_session(session.clone()).await {
+                tracing::warn!(target: "chat", session_id = %session.id, "Failed to store new session: {}", e);
+            }
+        }
+        Ok(session)
+    }
+
+    async fn send_message(
+        &self,
+        session_id: &str,
+        content: &str,
+    ) -> Result<ChatMessage, Box<dyn Error>> {
+        let prompt = ChatMessage {
+            id: Uuid::new_v4().to_string(),
+            role: MessageRole::User,
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            model: None,
+            metadata: None,
+            parts: vec![MessagePart::text(content)],
+        };
+        let response = retry_with_backoff(
+            || async {
+                self.api_client
+                    .send_session_message(session_id, content)
+                    .await
+                    .map_err(into_app_error)
+            },
+            RetryConfig::for_operation(RetryOperation::MessageSend),
+        )
+        .await?;
+        let reply =
+            ChatMessage::from_server(&response).ok_or_else(|| parse_error("reply", &response))?;
+
+        if let Some(store) = self.store.lock().await.as_ref() {
+            if let Err(e) = store
+                .append_messages(session_id, &[prompt, reply.clone()])
+                .await
+            {
+                tracing::warn!(target: "chat", session_id = %session_id, "Failed to store messages: {}", e);
+            }
+        }
+        Ok(reply)
+    }
+
+    async fn get_session_messages(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<ChatMessage>, Box<dyn Error>> {
+        let fetched = self
+            .api_client
+            .get_session_message_parts(session_id)
+            .await
+            .map_err(into_app_error);
+        let guard = self.store.lock().await;
+        let store = guard.as_ref();
+
+        match fetched {
+            Ok(raw) => {
+                let messages: Vec<ChatMessage> =
+                    raw.iter().filter_map(ChatMessage::from_server).collect();
+                if let Some(store) = store {
+                    if let Err(e) = store.replace_messages(session_id, messages.clone()).await {
+                        tracing::warn!(target: "chat", session_id = %session_id, "Failed to store messages: {}", e);
+                    }
+                }
+                Ok(messages)
+            }
+            // Unreachable, a stored copy is better than nothing
+            Err(error @ (AppError::NetworkError { .. } | AppError::ConnectionError { .. })) => {
+                match store {
+                    Some(store) if store.message_count(session_id).await.is_some() => {
+                        tracing::info!(target: "chat", session_id = %session_id, "Server unreachable, showing stored messages");
+                        store.get_session_messages(session_id).await
+                    }
+                    _ => Err(error.into()),
+                }
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn rename_session(&self, session_id: &str, title: &str) -> Result<(), Box<dyn Error>> {
+        self.api_client.rename_session(session_id, title).await?;
+        if let Some(store) = self.store.lock().await.as_ref() {
+            if store.message_count(session_id).await.is_some() {
+                store
+                    .update_session_title(session_id, title.to_string())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<(), Box<dyn Error>> {
+        self.api_client.delete_session(session_id).await?;
+        if let Some(store) = self.store.lock().await.as_ref() {
+            if store.message_count(session_id).await.is_some() {
+                store.delete_session(session_id).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer `METHOD /path` requests with canned JSON, 404 otherwise
+    async fn fake_server(routes: Vec<(&'static str, serde_json::Value)>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&request).to_string();
+                let route = head.split(" HTTP/").next().unwrap_or_default().to_string();
+                let body = routes
+                    .iter()
+                    .find(|(key, _)| *key == route)
+                    .map(|(_, body)| body.to_string());
+                let response = match body {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    async fn messaging(url: &str) -> (Messaging, SessionStore, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let api_client = Arc::new(ApiClient::new().unwrap());
+        api_client.set_server_url(url.to_string()).await.unwrap();
+        let store: SessionStore = Arc::new(Mutex::new(Some(SessionManager::new(
+            api_client.clone(),
+            temp.path().to_path_buf(),
+        ))));
+        (Messaging::new(api_client, store.clone()), store, temp)
+    }
+
+    fn reply(id: &str, text: &str) -> serde_json::Value {
+        json!({
+            "info": { "id": id, "role": "assistant", "modelID": "claude", "time": { "created": 1_700_000_005_000i64 } },
+            "parts": [{ "type": "step-start" }, { "type": "text", "text": text }]
+        })
+    }
+
+    #[tokio::test]
+    async fn sessions_created_here_are_mirrored_locally() {
+        let url = fake_server(vec![
+            (
+                "POST /session",
+                json!({ "id": "ses_1", "title": "Plan", "time": { "created": 1_700_000_000_000i64, "updated": 1_700_000_000_000i64 } }),
+            ),
+            ("POST /session/ses_1/message", reply("msg_2", "Sure")),
+            (
+                "GET /session",
+                json!([
+                    { "id": "ses_1", "title": "Plan", "time": { "created": 1_700_000_000_000i64 } },
+                    { "id": "ses_2", "title": "", "time": { "created": 1_600_000_000_000i64 } }
+                ]),
+            ),
+        ])
+        .await;
+        let (messaging, _store, _temp) = messaging(&url).await;
+
+        let session = messaging
+            .create_session(Some("Plan".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(session.id, "ses_1");
+        assert_eq!(session.created_at.timestamp(), 1_700_000_000);
+        let connection_id = connection_id_for_url(&url);
+        assert!(connection_id.is_some());
+        assert_eq!(session.connection_id, connection_id);
+
+        let reply = messaging
+            .send_message("ses_1", "Help me plan")
+            .await
+            .unwrap();
+        assert_eq!(reply.id, "msg_2");
+        assert_eq!(reply.role, MessageRole::Assistant);
+        assert_eq!(reply.content, "Sure");
+        assert_eq!(reply.model.as_deref(), Some("claude"));
+
+        let sessions = messaging.list_sessions().await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].message_count, 2);
+        assert_eq!(sessions[0].user_message_count, 1);
+        assert_eq!(sessions[0].last_message_preview.as_deref(), Some("Sure"));
+        assert_eq!(sessions[1].message_count, 0);
+        assert_eq!(sessions[1].title, None);
+        assert!(sessions.iter().all(|s| s.connection_id == connection_id));
+    }
+
+    #[tokio::test]
+    async fn fetched_messages_replace_the_stored_copy() {
+        let url = fake_server(vec![(
+            "GET /session/ses_1/message",
+            json!([
+                { "info": { "id": "msg_1", "role": "user" }, "parts": [{ "type": "text", "text": "Hi" }] },
+                reply("msg_2", "Hello"),
+                { "info": { "id": "msg_3", "role": "narrator" }, "parts": [] }
+            ]),
+        )])
+        .await;
+        let (messaging, store, _temp) = messaging(&url).await;
+        let session = ChatSession::from_server(&json!({ "id": "ses_1" })).unwrap();
+        let guard = store.lock().await;
+        guard
+            .as_ref()
+            .unwrap()
+            .import_session(session)
+            .await
+            .unwrap();
+        drop(guard);
+
+        let messages = messaging.get_session_messages("ses_1").await.unwrap();
+        let ids: Vec<_> = messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["msg_1", "msg_2"]);
+        let guard = store.lock().await;
+        assert_eq!(
+            guard.as_ref().unwrap().message_count("ses_1").await,
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn stored_messages_are_shown_while_offline() {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let (messaging, store, _temp) = messaging(&url).await;
+
+        let mut session = ChatSession::from_server(&json!({ "id": "ses_1" })).unwrap();
+        session.messages = vec![ChatMessage::from_server(&reply("msg_1", "Cached")).unwrap()];
+        let guard = store.lock().await;
+        guard
+            .as_ref()
+            .unwrap()
+            .import_session(session)
+            .await
+            .unwrap();
+        drop(guard);
+
+        let messages = messaging.get_session_messages("ses_1").await.unwrap();
+        assert_eq!(messages[0].content, "Cached");
+        assert!(messaging.get_session_messages("ses_2").await.is_err());
+    }
+}
@@ -0,0 +1,440 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Prometheus metrics for the app itself. When enabled they are served on
+//! `127.0.0.1` so a homelab scraper can chart Nexus next to everything else;
+//! nothing is reachable from other machines.
+
+use crate::error::ErrorCode;
+use crate::resource_usage::ResourceUsage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::Ipv4Addr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+/// Port used until the user picks another
+pub const DEFAULT_PORT: u16 = 9464;
+
+/// Upper bounds, in seconds, of the stream duration histogram buckets
+const STREAM_BUCKETS: [f64; 8] = [1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// Longest request head read from a scraper
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// How long a scraper gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether and where the metrics endpoint listens
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+struct Counters {
+    /// Requests to the OpenCode server by HTTP method
+    requests: BTreeMap<String, u64>,
+    /// Errors by stable error code
+    errors: BTreeMap<String, u64>,
+    /// Cumulative counts per `STREAM_BUCKETS` bound
+    stream_buckets: [u64; STREAM_BUCKETS.len()],
+    stream_count: u64,
+    stream_seconds: f64,
+}
+
+static COUNTERS: Mutex<Counters> = Mutex::new(Counters {
+    requests: BTreeMap::new(),
+    errors: BTreeMap::new(),
+    stream_buckets: [0; STREAM_BUCKETS.len()],
+    stream_count: 0,
+    stream_seconds: 0.0,
+});
+
+/// Kept between scrapes, since CPU use is measured since the last refresh
+static SYSTEM: Mutex<Option<sysinfo::System>> = Mutex::new(None);
+
+/// The running endpoint; dropping it stops the server
+struct ActiveServer {
+    settings: MetricsSettings,
+    _shutdown: oneshot::Sender<()>,
+}
+
+static SERVER: Mutex<Option<ActiveServer>> = Mutex::new(None);
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Count a request sent to the OpenCode server
+pub fn record_request(method: &reqwest::Method) {
+    *lock(&COUNTERS)
+        .requests
+        .entry(method.as_str().to_string())
+        .or_insert(0) += 1;
+}
+
+/// Count an error occurrence
+pub fn record_error(code: ErrorCode) {
+    let code = serde_json::to_value(code)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", code));
+    *lock(&COUNTERS).errors.entry(code).or_insert(0) += 1;
+}
+
+/// Record how long a response stream ran
+pub fn record_stream(duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let mut counters = lock(&COUNTERS);
+    for (bound, count) in STREAM_BUCKETS
+        .iter()
+        .zip(counters.stream_buckets.iter_mut())
+    {
+        if seconds <= *bound {
+            *count += 1;
+        }
+    }
+    counters.stream_count += 1;
+    counters.stream_seconds += seconds;
+}
+
+/// CPU use of this process since the previous call, in percent of one core
+fn process_cpu_percent() -> Option<f32> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = lock(&SYSTEM);
+    let system = system.get_or_insert_with(sysinfo::System::new);
+    system.refresh_process(pid);
+    Some(system.process(pid)?.cpu_usage())
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Current metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let usage = ResourceUsage::collect();
+    let mut out = String::new();
+
+    let counters = lock(&COUNTERS);
+
+    header(
+        &mut out,
+        "nexus_api_requests_total",
+        "counter",
+        "Requests sent to the OpenCode server",
+    );
+    for (method, count) in &counters.requests {
+        let _ = writeln!(
+            out,
+            "nexus_api_requests_total{{method=\"{}\"}} {}",
+            method, count
+        );
+    }
+
+    header(
+        &mut out,
+        "nexus_errors_total",
+        "counter",
+        "Errors by error code",
+    );
+    for (code, count) in &counters.errors {
+        let _ = writeln!(out, "nexus_errors_total{{code=\"{}\"}} {}", code, count);
+    }
+
+    header(
+        &mut out,
+        "nexus_stream_duration_seconds",
+        "histogram",
+        "How long response streams ran",
+    );
+    for (bound, count) in STREAM_BUCKETS.iter().zip(counters.stream_buckets) {
+        let _ = writeln!(
+            out,
+            "nexus_stream_duration_seconds_bucket{{le=\"{}\"}} {}",
+            bound, count
+        );
+    }
+    let _ = writeln!(
+        out,
+        "nexus_stream_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+        counters.stream_count
+    );
+    let _ = writeln!(
+        out,
+        "nexus_stream_duration_seconds_sum {}",
+        counters.stream_seconds
+    );
+    let _ = writeln!(
+        out,
+        "nexus_stream_duration_seconds_count {}",
+        counters.stream_count
+    );
+    drop(counters);
+
+    header(
+        &mut out,
+        "nexus_open_streams",
+        "gauge",
+        "Response streams being forwarded to the UI",
+    );
+    let _ = writeln!(out, "nexus_open_streams {}", usage.open_streams);
+    header(
+        &mut out,
+        "nexus_background_tasks",
+        "gauge",
+        "Background loops currently running",
+    );
+    let _ = writeln!(out, "nexus_background_tasks {}", usage.background_tasks);
+    header(
+        &mut out,
+        "nexus_uptime_seconds",
+        "gauge",
+        "Seconds since the app started",
+    );
+    let _ = writeln!(out, "nexus_uptime_seconds {}", usage.uptime_secs);
+
+    if let Some(memory) = usage.memory {
+        header(
+            &mut out,
+            "process_resident_memory_bytes",
+            "gauge",
+            "Resident memory size in bytes",
+        );
+        let _ = writeln!(out, "process_resident_memory_bytes {}", memory.rss_bytes);
+        header(
+            &mut out,
+            "process_virtual_memory_bytes",
+            "gauge",
+            "Virtual memory size in bytes",
+        );
+        let _ = writeln!(out, "process_virtual_memory_bytes {}", memory.virtual_bytes);
+    }
+    if let Some(cpu) = process_cpu_percent() {
+        header(
+            &mut out,
+            "nexus_process_cpu_usage_percent",
+            "gauge",
+            "CPU use since the previous scrape, in percent of one core",
+        );
+        let _ = writeln!(out, "nexus_process_cpu_usage_percent {}", cpu);
+    }
+    out
+}
+
+pub fn validate(settings: &MetricsSettings) -> Result<(), String> {
+    if settings.enabled && settings.port == 0 {
+        return Err("Metrics port must be between 1 and 65535".to_string());
+    }
+    Ok(())
+}
+
+/// Start, move or stop the endpoint to match `settings`
+pub fn configure(settings: &MetricsSettings) -> Result<(), String> {
+    validate(settings)?;
+
+    let mut server = lock(&SERVER);
+    if server
+        .as_ref()
+        .is_some_and(|active| active.settings == *settings)
+    {
+        return Ok(());
+    }
+    *server = None;
+
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    // Bind here rather than in the task so a taken port is reported to
+    // whoever changed the setting
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port))
+        .and_then(|listener| {
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        })
+        .map_err(|e| format!("Cannot listen on port {}: {}", settings.port, e))?;
+    let (shutdown, stopped) = oneshot::channel();
+    tauri::async_runtime::spawn(async move {
+        match TcpListener::from_std(listener) {
+            Ok(listener) => serve(listener, stopped).await,
+            Err(e) => tracing::warn!(target: "metrics", "Metrics endpoint failed: {}", e),
+        }
+    });
+    tracing::info!(target: "metrics", port = settings.port, "Serving metrics on localhost");
+    *server = Some(ActiveServer {
+        settings: settings.clone(),
+        _shutdown: shutdown,
+    });
+    Ok(())
+}
+
+/// Answer scrapes until `stopped` resolves, which happens when its sender
+/// is dropped
+async fn serve(listener: TcpListener, mut stopped: oneshot::Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = &mut stopped => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(respond(stream));
+                }
+                Err(e) => tracing::debug!(target: "metrics", "Metrics accept failed: {}", e),
+            },
+        }
+    }
+}
+
+async fn respond(mut stream: tokio::net::TcpStream) {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read_head = async {
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST_BYTES {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => head.extend_from_slice(&buf[..n]),
+            }
+        }
+    };
+    if tokio::time::timeout(READ_TIMEOUT, read_head).await.is_err() {
+        return;
+    }
+
+    let request_line = head.split(|b| *b == b'\n').next().unwrap_or_default();
+    let request_line = String::from_utf8_lossy(request_line);
+    let mut words = request_line.split_whitespace();
+    let (status, body) = match (words.next(), words.next()) {
+        (Some("GET"), Some(path)) if path.split('?').next() == Some("/metrics") => {
+            ("200 OK", render())
+        }
+        (Some("GET"), Some(_)) => ("404 Not Found", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "Only GET is supported\n".to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        record_stream(Duration::from_millis(500));
+        record_stream(Duration::from_secs(45));
+        record_stream(Duration::from_secs(3600));
+
+        let text = render();
+        let value = |line: &str| -> u64 {
+            text.lines()
+                .find_map(|l| l.strip_prefix(line))
+                .and_then(|rest| rest.trim().parse().ok())
+                .unwrap_or_else(|| panic!("{} missing from\n{}", line, text))
+        };
+        // Other tests may record streams too, so compare bucket to bucket
+        let total = value("nexus_stream_duration_seconds_count ");
+        assert!(value("nexus_stream_duration_seconds_bucket{le=\"1\"} ") >= 1);
+        assert!(
+            value("nexus_stream_duration_seconds_bucket{le=\"60\"} ")
+                > value("nexus_stream_duration_seconds_bucket{le=\"30\"} ")
+        );
+        assert!(value("nexus_stream_duration_seconds_bucket{le=\"600\"} ") < total);
+        assert_eq!(
+            value("nexus_stream_duration_seconds_bucket{le=\"+Inf\"} "),
+            total
+        );
+    }
+
+    #[test]
+    fn counts_requests_and_errors_by_label() {
+        record_request(&reqwest::Method::DELETE);
+        record_error(ErrorCode::Validation);
+
+        let text = render();
+        assert!(text.contains("nexus_api_requests_total{method=\"DELETE\"}"));
+        assert!(text.contains("nexus_errors_total{code=\"VALIDATION\"}"));
+        assert!(text.contains("# TYPE nexus_errors_total counter"));
+        assert!(text.contains("process_resident_memory_bytes "));
+    }
+
+    #[test]
+    fn rejects_port_zero() {
+        let settings = MetricsSettings {
+            enabled: true,
+            port: 0,
+        };
+        assert!(validate(&settings).is_err());
+        assert!(validate(&MetricsSettings::default()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn serves_metrics_and_nothing_else() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown, stopped) = oneshot::channel();
+        let server = tokio::spawn(serve(listener, stopped));
+
+        let get = |path: &'static str| async move {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let metrics = get("/metrics").await;
+        assert!(metrics.starts_with("HTTP/1.1 200 OK"));
+        assert!(metrics.contains("nexus_uptime_seconds"));
+        assert!(get("/").await.starts_with("HTTP/1.1 404"));
+
+        drop(shutdown);
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,412 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Versioned upgrades for the JSON files kept in the config directory.
+//!
+//! Data files carry no version of their own, so the version each one was
+//! last written at is recorded in `schema_versions.json`. Files missing
+//! from that record predate versioning and count as version 0. Settings
+//! have their own `schema_version` and are migrated by `SettingsManager`,
+//! which also folds in the old `model_preferences.json`.
+
+use crate::error::AppError;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Record of the schema version each data file is stored at
+pub const VERSIONS_FILE: &str = "schema_versions.json";
+
+/// Upgrades a document from version `from` to `from + 1`
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    pub apply: fn(Value) -> Result<Value, String>,
+}
+
+/// A persisted file and the steps that bring it to `version`
+pub struct DataFile {
+    pub name: &'static str,
+    pub version: u32,
+    pub migrations: &'static [Migration],
+}
+
+/// Every data file that is migrated at startup
+pub const DATA_FILES: &[DataFile] = &[
+    DataFile {
+        name: "chat_sessions.json",
+        version: 2,
+        migrations: &[
+            Migration {
+                from: 0,
+                description: "key sessions by id",
+                apply: sessions_by_id,
+            },
+            Migration {
+                from: 1,
+                description: "split message content into parts",
+                apply: messages_with_parts,
+            },
+        ],
+    },
+    DataFile {
+        name: "server_connections.json",
+        version: 1,
+        migrations: &[Migration {
+            from: 0,
+            description: "store connections as a list",
+            apply: connections_as_list,
+        }],
+    },
+];
+
+/// A file that was upgraded
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MigrationReport {
+    pub file: String,
+    pub from: u32,
+    pub to: u32,
+    /// Copy of the file as it was before migrating
+    pub backup: PathBuf,
+}
+
+/// Bring every file in `DATA_FILES` up to date. Each file is backed up
+/// before it is rewritten, and a failed file is left untouched without
+/// stopping the others.
+pub fn run(config_dir: &Path) -> (Vec<MigrationReport>, Vec<AppError>) {
+    run_files(config_dir, DATA_FILES)
+}
+
+fn run_files(config_dir: &Path, files: &[DataFile]) -> (Vec<MigrationReport>, Vec<AppError>) {
+    let mut reports = Vec::new();
+    let mut errors = Vec::new();
+    let mut versions = match load_versions(config_dir) {
+        Ok(versions) => versions,
+        Err(e) => return (reports, vec![e]),
+    };
+    let before = versions.clone();
+
+    for file in files {
+        match migrate_file(config_dir, file, &mut versions) {
+            Ok(Some(report)) => reports.push(report),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if versions != before {
+        if let Err(e) = save_versions(config_dir, &versions) {
+            errors.push(e);
+        }
+    }
+    (reports, errors)
+}
+
+fn migrate_file(
+    config_dir: &Path,
+    file: &DataFile,
+    versions: &mut BTreeMap<String, u32>,
+) -> Result<Option<MigrationReport>, AppError> {
+    let path = config_dir.join(file.name);
+    let stored = versions.get(file.name).copied().unwrap_or(0);
+    // A newer build wrote this file; leave it for that build to read
+    if stored >= file.version {
+        return Ok(None);
+    }
+    if !path.exists() {
+        // Whatever creates the file will write the current format
+        versions.insert(file.name.to_string(), file.version);
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| AppError::FileSystemError {
+        path: path.display().to_string(),
+        message: "Failed to read data file for migration".to_string(),
+        details: e.to_string(),
+    })?;
+    let mut document: Value =
+        serde_json::from_str(&contents).map_err(|e| AppError::ParseError {
+            message: format!("Failed to parse {} for migration", file.name),
+            details: Some(e.to_string()),
+        })?;
+    for version in stored..file.version {
+        let Some(step) = file.migrations.iter().find(|step| step.from == version) else {
+            continue;
+        };
+        document = (step.apply)(document).map_err(|e| AppError::DataError {
+            message: format!(
+                "Failed to migrate {} from version {} ({})",
+                file.name, version, step.description
+            ),
+            details: e,
+        })?;
+    }
+
+    let backup = config_dir.join(format!("{}.v{}.bak", file.name, stored));
+    std::fs::copy(&path, &backup).map_err(|e| AppError::FileSystemError {
+        path: backup.display().to_string(),
+        message: "Failed to back up data file before migration".to_string(),
+        details: e.to_string(),
+    })?;
+    let migrated = serde_json::to_string_pretty(&document).map_err(|e| AppError::ParseError {
+        message: format!("Failed to serialize migrated {}", file.name),
+        details: Some(e.to_string()),
+    })?;
+    write_atomically(&path, migrated.as_bytes())?;
+
+    versions.insert(file.name.to_string(), file.version);
+    Ok(Some(MigrationReport {
+        file: file.name.to_string(),
+        from: stored,
+        to: file.version,
+        backup,
+    }))
+}
+
+fn load_versions(config_dir: &Path) -> Result<BTreeMap<String, u32>, AppError> {
+    let path = config_dir.join(VERSIONS_FILE);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = std::fs::read_to_string(&path).map_err(|e| AppError::FileSystemError {
+        path: path.display().to_string(),
+        message: "Failed to read schema versions".to_string(),
+        details: e.to_string(),
+    })?;
+    serde_json::from_str(&contents).map_err(|e| AppError::ParseError {
+        message: "Failed to parse schema versions".to_string(),
+        details: Some(e.to_string()),
+    })
+}
+
+fn save_versions(config_dir: &Path, versions: &BTreeMap<String, u32>) -> Result<(), AppError> {
+    std::fs::create_dir_all(config_dir).map_err(|e| AppError::FileSystemError {
+        path: config_dir.display().to_string(),
+        message: "Failed to create config directory".to_string(),
+        details: e.to_string(),
+    })?;
+    let contents = serde_json::to_string_pretty(versions).map_err(|e| AppError::ParseError {
+        message: "Failed to serialize schema versions".to_string(),
+        details: Some(e.to_string()),
+    })?;
+    write_atomically(&config_dir.join(VERSIONS_FILE), contents.as_bytes())
+}
+
+/// Write through a temporary file so a crash never leaves half a document
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), AppError> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    std::fs::write(&temp, contents)
+        .and_then(|_| std::fs::rename(&temp, path))
+        .map_err(|e| AppError::FileSystemError {
+            path: path.display().to_string(),
+            message: "Failed to write migrated file".to_string(),
+            details: e.to_string(),
+        })
+}
+
+/// `ChatClient` wrote sessions as a list; `SessionManager` reads a map
+/// keyed by session id
+fn sessions_by_id(document: Value) -> Result<Value, String> {
+    match document {
+        Value::Array(sessions) => sessions
+            .into_iter()
+            .map(|session| {
+                let id = session
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .ok_or("session without an id")?
+                    .to_string();
+                Ok((id, session))
+            })
+            .collect::<Result<serde_json::Map<_, _>, String>>()
+            .map(Value::Object),
+        Value::Object(_) => Ok(document),
+        _ => Err("expected a list or map of sessions".to_string()),
+    }
+}
+
+/// Messages stored before parts existed get their text as a single part
+fn messages_with_parts(mut document: Value) -> Result<Value, String> {
+    let sessions = document
+        .as_object_mut()
+        .ok_or("expected a map of sessions")?;
+    for session in sessions.values_mut() {
+        let Some(messages) = session.get_mut("messages").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for message in messages.iter_mut().filter_map(Value::as_object_mut) {
+            if message.contains_key("parts") {
+                continue;
+            }
+            let parts = match message.get("content").and_then(Value::as_str) {
+                Some(content) if !content.is_empty() => {
+                    serde_json::json!([{ "type": "text", "text": content }])
+                }
+                _ => serde_json::json!([]),
+            };
+            message.insert("parts".to_string(), parts);
+        }
+    }
+    Ok(document)
+}
+
+/// Connections keyed by name become a list, with the key filling in a
+/// missing `name`
+fn connections_as_list(document: Value) -> Result<Value, String> {
+    match document {
+        Value::Object(connections) => Ok(Value::Array(
+            connections
+                .into_iter()
+                .map(|(name, mut connection)| {
+                    if let Value::Object(fields) = &mut connection {
+                        fields.entry("name").or_insert(Value::String(name));
+                    }
+                    connection
+                })
+                .collect(),
+        )),
+        Value::Array(_) => Ok(document),
+        _ => Err("expected a list or map of connections".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn read_json(path: &Path) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_unversioned_files_are_migrated_and_backed_up() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        std::fs::write(
+            dir.join("chat_sessions.json"),
+            r#"[{"id":"ses_1","title":"One"}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("server_connections.json"),
+            r#"{"home":{"hostname":"nas","port":4096}}"#,
+        )
+        .unwrap();
+
+        let (reports, errors) = run(dir);
+        assert!(errors.is_empty());
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            read_json(&dir.join("chat_sessions.json"))["ses_1"]["title"],
+            "One"
+        );
+        assert_eq!(
+            read_json(&dir.join("server_connections.json"))[0]["name"],
+            "home"
+        );
+        assert!(dir.join("chat_sessions.json.v0.bak").exists());
+        assert_eq!(read_json(&dir.join(VERSIONS_FILE))["chat_sessions.json"], 2);
+
+        // Already current: nothing to do the second time
+        let (reports, errors) = run(dir);
+        assert!(reports.is_empty() && errors.is_empty());
+    }
+
+    #[test]
+    fn test_current_format_passes_through() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let connections = r#"[{"name":"home","hostname":"nas","port":4096}]"#;
+        std::fs::write(dir.join("server_connections.json"), connections).unwrap();
+
+        let (_, errors) = run(dir);
+        assert!(errors.is_empty());
+        assert_eq!(
+            read_json(&dir.join("server_connections.json")),
+            serde_json::from_str::<Value>(connections).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_file_untouched() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("chat_sessions.json"), r#"[{"title":"no id"}]"#).unwrap();
+
+        let (reports, errors) = run(dir);
+        assert!(reports.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("chat_sessions.json")).unwrap(),
+            r#"[{"title":"no id"}]"#
+        );
+        assert!(read_json(&dir.join(VERSIONS_FILE))
+            .get("chat_sessions.json")
+            .is_none());
+    }
+
+    #[test]
+    fn test_messages_gain_parts() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join(VERSIONS_FILE), r#"{"chat_sessions.json":1}"#).unwrap();
+        std::fs::write(
+            dir.join("chat_sessions.json"),
+            r#"{"ses_1":{"id":"ses_1","messages":[
+                {"id":"m1","content":"Hello"},
+                {"id":"m2","content":""},
+                {"id":"m3","content":"Kept","parts":[{"type":"reasoning","text":"Kept"}]}
+            ]},"ses_2":{"id":"ses_2"}}"#,
+        )
+        .unwrap();
+
+        let (reports, errors) = run(dir);
+        assert!(errors.is_empty());
+        assert_eq!(reports[0].from, 1);
+        let messages = &read_json(&dir.join("chat_sessions.json"))["ses_1"]["messages"];
+        assert_eq!(
+            messages[0]["parts"],
+            serde_json::json!([{ "type": "text", "text": "Hello" }])
+        );
+        assert_eq!(messages[1]["parts"], serde_json::json!([]));
+        assert_eq!(messages[2]["parts"][0]["type"], "reasoning");
+    }
+
+    #[test]
+    fn test_newer_versions_are_left_alone() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join(VERSIONS_FILE), r#"{"chat_sessions.json":7}"#).unwrap();
+        std::fs::write(dir.join("chat_sessions.json"), "[]").unwrap();
+
+        let (reports, errors) = run_files(dir, &DATA_FILES[..1]);
+        assert!(reports.is_empty() && errors.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("chat_sessions.json")).unwrap(),
+            "[]"
+        );
+    }
+}
@@ -97,6 +97,7 @@ pub struct ModelSettings {
 
 /// User's model preferences
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ModelPreferences {
     pub default_provider: Option<String>,
     pub default_model: Option<String>,
@@ -140,21 +141,25 @@ impl ModelManager {
     pub async fn load_providers(&self) -> Result<(), Box<dyn std::error::Error>> {
         let providers_file = self.get_providers_file_path();
 
-        if !providers_file.exists() {
-            // Create default configuration
-            self.create_default_providers().await?;
-            return Ok(());
-        }
-
-        let providers_json =
-            std::fs::read_to_string(&providers_file).map_err(|e| AppError::FileSystemError {
-                path: providers_file.to_string_lossy().to_string(),
-                message: "Failed to read providers file".to_string(),
-                details: e.to_string(),
-            })?;
+        let providers_json = match tokio::fs::read(&providers_file).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // Create default configuration
+                self.create_default_providers().await?;
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(AppError::FileSystemError {
+                    path: providers_file.to_string_lossy().to_string(),
+                    message: "Failed to read providers file".to_string(),
+                    details: e.to_string(),
+                }
+                .into())
+            }
+        };
 
         let loaded_providers: HashMap<String, ProviderConfig> =
-            serde_json::from_str(&providers_json).map_err(|e| AppError::ParseError {
+            serde_json::from_slice(&providers_json).map_err(|e| AppError::ParseError {
                 message: "Failed to parse providers file".to_string(),
                 details: Some(e.to_string()),
             })?;
@@ -173,14 +178,15 @@ impl ModelManager {
                 message: "Failed to serialize providers".to_string(),
                 details: Some(e.to_string()),
             })?;
+        drop(providers);
 
-        std::fs::write(self.get_providers_file_path(), providers_json).map_err(|e| {
-            AppError::FileSystemError {
+        tokio::fs::write(self.get_providers_file_path(), providers_json)
+            .await
+            .map_err(|e| AppError::FileSystemError {
                 path: self.get_providers_file_path().to_string_lossy().to_string(),
                 message: "Failed to write providers file".to_string(),
                 details: e.to_string(),
-            }
-        })?;
+            })?;
 
         Ok(())
     }
@@ -0,0 +1,258 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use crate::i18n::t;
+use crate::settings::{NotificationSettings, QuietHours};
+use chrono::NaiveTime;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Longest response excerpt shown in a notification body
+const MAX_SNIPPET_CHARS: usize = 140;
+
+/// Events that can raise an OS notification
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationKind {
+    /// A streamed response finished
+    ResponseCompleted,
+    /// A streamed response failed
+    ResponseFailed,
+}
+
+/// Title and body of an OS notification
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Format of the quiet hours bounds
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Whether `kind` should be shown at local time `now`. Notifications are
+/// only raised while the user is looking elsewhere; a focused window
+/// already shows the result.
+pub fn should_notify(
+    settings: &NotificationSettings,
+    kind: NotificationKind,
+    window_focused: bool,
+    now: NaiveTime,
+) -> bool {
+    if window_focused || settings.focus_mode || is_quiet_time(&settings.quiet_hours, now) {
+        return false;
+    }
+    match kind {
+        NotificationKind::ResponseCompleted => settings.response_completed,
+        NotificationKind::ResponseFailed => settings.response_failed,
+    }
+}
+
+/// Whether `now` falls inside the quiet hours window. Unparseable bounds
+/// are rejected by `validate`, so they never silence anything here.
+pub fn is_quiet_time(quiet_hours: &QuietHours, now: NaiveTime) -> bool {
+    if !quiet_hours.enabled {
+        return false;
+    }
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(&quiet_hours.start, TIME_FORMAT),
+        NaiveTime::parse_from_str(&quiet_hours.end, TIME_FORMAT),
+    ) else {
+        return false;
+    };
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Reject quiet hours that are not `HH:MM` times
+pub fn validate(settings: &NotificationSettings) -> Result<(), String> {
+    for (field, value) in [
+        ("start", &settings.quiet_hours.start),
+        ("end", &settings.quiet_hours.end),
+    ] {
+        NaiveTime::parse_from_str(value, TIME_FORMAT)
+            .map_err(|_| format!("Quiet hours {} must be HH:MM, got '{}'", field, value))?;
+    }
+    Ok(())
+}
+
+/// First part of `text` on one line, shortened to fit a notification
+pub fn snippet(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= MAX_SNIPPET_CHARS {
+        return collapsed;
+    }
+    let shortened: String = collapsed.chars().take(MAX_SNIPPET_CHARS - 1).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+/// Build the notification for a session's response or error text
+pub fn compose(
+    settings: &NotificationSettings,
+    kind: NotificationKind,
+    session_title: Option<&str>,
+    text: &str,
+) -> Notification {
+    let untitled = t("notification.untitled_session", &[]);
+    let session = session_title
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or(&untitled);
+    let (title_key, fallback_key) = match kind {
+        NotificationKind::ResponseCompleted => (
+            "notification.response_completed",
+            "notification.response_completed.body",
+        ),
+        NotificationKind::ResponseFailed => (
+            "notification.response_failed",
+            "notification.response_failed.body",
+        ),
+    };
+
+    let body = if settings.include_snippet && !text.trim().is_empty() {
+        snippet(text)
+    } else {
+        t(fallback_key, &[])
+    };
+    Notification {
+        title: t(title_key, &[("session", session)]),
+        body,
+    }
+}
+
+/// Hand a notification to the OS through the notification plugin
+pub fn show(app_handle: &AppHandle, notification: &Notification) -> Result<(), AppError> {
+    app_handle
+        .notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show()
+        .map_err(|e| AppError::IoError {
+            message: "Failed to show notification".to_string(),
+            details: Some(e.to_string()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, TIME_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_only_notifies_when_unfocused_and_enabled() {
+        let mut settings = NotificationSettings::default();
+        let noon = at("12:00");
+        assert!(should_notify(
+            &settings,
+            NotificationKind::ResponseCompleted,
+            false,
+            noon
+        ));
+        assert!(!should_notify(
+            &settings,
+            NotificationKind::ResponseCompleted,
+            true,
+            noon
+        ));
+
+        settings.response_failed = false;
+        assert!(!should_notify(
+            &settings,
+            NotificationKind::ResponseFailed,
+            false,
+            noon
+        ));
+
+        settings.focus_mode = true;
+        assert!(!should_notify(
+            &settings,
+            NotificationKind::ResponseCompleted,
+            false,
+            noon
+        ));
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_past_midnight() {
+        let mut quiet_hours = QuietHours::default();
+        assert!(!is_quiet_time(&quiet_hours, at("03:00")));
+
+        quiet_hours.enabled = true;
+        assert!(is_quiet_time(&quiet_hours, at("03:00")));
+        assert!(is_quiet_time(&quiet_hours, at("22:00")));
+        assert!(!is_quiet_time(&quiet_hours, at("07:00")));
+        assert!(!is_quiet_time(&quiet_hours, at("12:00")));
+
+        quiet_hours.start = "13:00".to_string();
+        quiet_hours.end = "14:00".to_string();
+        assert!(is_quiet_time(&quiet_hours, at("13:30")));
+        assert!(!is_quiet_time(&quiet_hours, at("03:00")));
+    }
+
+    #[test]
+    fn test_validate_rejects_malformed_times() {
+        let mut settings = NotificationSettings::default();
+        assert!(validate(&settings).is_ok());
+
+        settings.quiet_hours.end = "7am".to_string();
+        assert!(validate(&settings).is_err());
+        settings.quiet_hours.end = "25:00".to_string();
+        assert!(validate(&settings).is_err());
+    }
+
+    #[test]
+    fn test_snippet_collapses_and_shortens() {
+        assert_eq!(
+            snippet("  Done.\n\nAll   tests pass "),
+            "Done. All tests pass"
+        );
+
+        let long = snippet(&"word ".repeat(100));
+        assert_eq!(long.chars().count(), MAX_SNIPPET_CHARS);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_compose_respects_snippet_setting() {
+        let mut settings = NotificationSettings::default();
+        let notification = compose(
+            &settings,
+            NotificationKind::ResponseCompleted,
+            Some("Refactor parser"),
+            "Here is the plan",
+        );
+        assert_eq!(notification.title, "Response ready: Refactor parser");
+        assert_eq!(notification.body, "Here is the plan");
+
+        settings.include_snippet = false;
+        let notification = compose(&settings, NotificationKind::ResponseFailed, None, "boom");
+        assert_eq!(notification.title, "Response failed: Untitled session");
+        assert!(!notification.body.contains("boom"));
+    }
+}
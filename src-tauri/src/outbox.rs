@@ -0,0 +1,344 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::api_client::ModelConfig;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Tauri event emitted whenever an outbox item changes state
+pub const OUTBOX_EVENT: &str = "outbox-event";
+
+/// How a queued prompt should be delivered
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxDelivery {
+    /// Plain `send_message`
+    Message,
+    /// `start_message_stream`
+    Stream,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    Pending,
+    Sending,
+    Delivered,
+    Failed,
+}
+
+/// A prompt that could not be sent because the server was unreachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxItem {
+    pub id: String,
+    pub session_id: String,
+    pub content: String,
+    pub delivery: OutboxDelivery,
+    #[serde(default)]
+    pub model_config: Option<ModelConfig>,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// Payload of `outbox-event`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    pub item: OutboxItem,
+    /// Stream id when a queued stream was started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_id: Option<String>,
+}
+
+/// Persistent queue of prompts waiting for the server to come back
+#[derive(Clone)]
+pub struct Outbox {
+    config_dir: PathBuf,
+    items: Arc<Mutex<Vec<OutboxItem>>>,
+    flushing: Arc<AtomicBool>,
+}
+
+impl Outbox {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            items: Arc::new(Mutex::new(Vec::new())),
+            flushing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn get_outbox_file_path(&self) -> PathBuf {
+        self.config_dir.join("outbox.json")
+    }
+
+    /// Load queued items; anything left `Sending` by a crash becomes `Pending`
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let outbox_file = self.get_outbox_file_path();
+        if !outbox_file.exists() {
+            return Ok(());
+        }
+
+        let outbox_json =
+            std::fs::read_to_string(&outbox_file).map_err(|e| AppError::FileSystemError {
+                path: outbox_file.to_string_lossy().to_string(),
+                message: "Failed to read outbox file".to_string(),
+                details: e.to_string(),
+            })?;
+        let mut loaded: Vec<OutboxItem> =
+            serde_json::from_str(&outbox_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse outbox file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+        for item in &mut loaded {
+            if item.status == OutboxStatus::Sending {
+                item.status = OutboxStatus::Pending;
+            }
+        }
+
+        *self.lock_items() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, items: &[OutboxItem]) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let outbox_json = serde_json::to_string_pretty(items)?;
+        std::fs::write(self.get_outbox_file_path(), outbox_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_outbox_file_path().to_string_lossy().to_string(),
+                message: "Failed to write outbox file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Queue a prompt and persist it immediately
+    pub fn enqueue(
+        &self,
+        session_id: &str,
+        content: &str,
+        delivery: OutboxDelivery,
+        model_config: Option<ModelConfig>,
+    ) -> Result<OutboxItem, Box<dyn std::error::Error>> {
+        let item = OutboxItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            content: content.to_string(),
+            delivery,
+            model_config,
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            created_at: Utc::now(),
+            last_error: None,
+        };
+
+        let mut items = self.lock_items();
+        items.push(item.clone());
+        self.save(&items)?;
+        Ok(item)
+    }
+
+    pub fn list(&self) -> Vec<OutboxItem> {
+        self.lock_items().clone()
+    }
+
+    /// Items waiting for delivery, oldest first
+    pub fn pending(&self) -> Vec<OutboxItem> {
+        self.lock_items()
+            .iter()
+            .filter(|item| item.status == OutboxStatus::Pending)
+            .cloned()
+            .collect()
+    }
+
+    /// Mark an item as in flight and count the attempt
+    pub fn mark_sending(&self, id: &str) -> Result<Option<OutboxItem>, Box<dyn std::error::Error>> {
+        self.update(id, |item| {
+            item.status = OutboxStatus::Sending;
+            item.attempts += 1;
+        })
+    }
+
+    /// Put an item back in the queue after a transient failure
+    pub fn mark_pending(
+        &self,
+        id: &str,
+        error: &str,
+    ) -> Result<Option<OutboxItem>, Box<dyn std::error::Error>> {
+        self.update(id, |item| {
+            item.status = OutboxStatus::Pending;
+            item.last_error = Some(error.to_string());
+        })
+    }
+
+    /// Stop retrying an item after a permanent failure
+    pub fn mark_failed(
+        &self,
+        id: &str,
+        error: &str,
+    ) -> Result<Option<OutboxItem>, Box<dyn std::error::Error>> {
+        self.update(id, |item| {
+            item.status = OutboxStatus::Failed;
+            item.last_error = Some(error.to_string());
+        })
+    }
+
+    /// Drop a delivered item from the queue, returning it marked `Delivered`
+    pub fn mark_delivered(
+        &self,
+        id: &str,
+    ) -> Result<Option<OutboxItem>, Box<dyn std::error::Error>> {
+        let removed = self.remove(id)?;
+        Ok(removed.map(|mut item| {
+            item.status = OutboxStatus::Delivered;
+            item
+        }))
+    }
+
+    /// Remove an item regardless of status
+    pub fn remove(&self, id: &str) -> Result<Option<OutboxItem>, Box<dyn std::error::Error>> {
+        let mut items = self.lock_items();
+        let position = items.iter().position(|item| item.id == id);
+        let removed = position.map(|index| items.remove(index));
+        if removed.is_some() {
+            self.save(&items)?;
+        }
+        Ok(removed)
+    }
+
+    /// Claim the flush slot; returns false if a flush is already running
+    pub fn try_begin_flush(&self) -> bool {
+        !self.flushing.swap(true, Ordering::SeqCst)
+    }
+
+    pub fn end_flush(&self) {
+        self.flushing.store(false, Ordering::SeqCst);
+    }
+
+    fn update<F>(
+        &self,
+        id: &str,
+        change: F,
+    ) -> Result<Option<OutboxItem>, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut OutboxItem),
+    {
+        let mut items = self.lock_items();
+        let Some(item) = items.iter_mut().find(|item| item.id == id) else {
+            return Ok(None);
+        };
+        change(item);
+        let updated = item.clone();
+        self.save(&items)?;
+        Ok(Some(updated))
+    }
+
+    fn lock_items(&self) -> std::sync::MutexGuard<'_, Vec<OutboxItem>> {
+        match self.items.lock() {
+            Ok(items) => items,
+            Err(poisoned) => {
+                eprintln!("[ERROR] Outbox: items mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_outbox() -> (Outbox, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let outbox = Outbox::new(temp_dir.path().to_path_buf());
+        (outbox, temp_dir)
+    }
+
+    #[test]
+    fn test_enqueue_persists_items() {
+        let (outbox, temp) = create_test_outbox();
+        let item = outbox
+            .enqueue("session-1", "Hello", OutboxDelivery::Message, None)
+            .expect("Should enqueue");
+        assert_eq!(item.status, OutboxStatus::Pending);
+
+        let reloaded = Outbox::new(temp.path().to_path_buf());
+        reloaded.load().expect("Should load outbox");
+        assert_eq!(reloaded.pending().len(), 1);
+        assert_eq!(reloaded.pending()[0].content, "Hello");
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let (outbox, _temp) = create_test_outbox();
+        let item = outbox
+            .enqueue("session-1", "Hello", OutboxDelivery::Stream, None)
+            .unwrap();
+
+        let sending = outbox.mark_sending(&item.id).unwrap().unwrap();
+        assert_eq!(sending.status, OutboxStatus::Sending);
+        assert_eq!(sending.attempts, 1);
+        assert!(outbox.pending().is_empty());
+
+        let retry = outbox.mark_pending(&item.id, "timeout").unwrap().unwrap();
+        assert_eq!(retry.last_error.as_deref(), Some("timeout"));
+        assert_eq!(outbox.pending().len(), 1);
+
+        let delivered = outbox.mark_delivered(&item.id).unwrap().unwrap();
+        assert_eq!(delivered.status, OutboxStatus::Delivered);
+        assert!(outbox.list().is_empty());
+    }
+
+    #[test]
+    fn test_load_resets_interrupted_sends() {
+        let (outbox, temp) = create_test_outbox();
+        let item = outbox
+            .enqueue("session-1", "Hello", OutboxDelivery::Message, None)
+            .unwrap();
+        outbox.mark_sending(&item.id).unwrap();
+
+        let reloaded = Outbox::new(temp.path().to_path_buf());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.pending().len(), 1);
+    }
+
+    #[test]
+    fn test_single_flush_at_a_time() {
+        let (outbox, _temp) = create_test_outbox();
+        assert!(outbox.try_begin_flush());
+        assert!(!outbox.try_begin_flush());
+        outbox.end_flush();
+        assert!(outbox.try_begin_flush());
+    }
+}
@@ -0,0 +1,136 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Paging for commands that return long lists, so a single `invoke` never
+//! serializes a multi-megabyte array. Callers pass the `offset` of the next
+//! page until `has_more` is false.
+
+use serde::{Deserialize, Serialize};
+
+/// Page size used when the caller does not pass a `limit`
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+
+/// Upper bound on a single page
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PageRequest {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// One page of a list, in the list's own order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub offset: usize,
+    /// Length of the whole list
+    pub total: usize,
+    pub has_more: bool,
+}
+
+impl PageRequest {
+    fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// Cut the requested page out of `items`. Only that page is kept, so the
+/// rest of the list is never serialized.
+pub fn paginate<T>(items: impl IntoIterator<Item = T>, request: Option<PageRequest>) -> Page<T> {
+    let request = request.unwrap_or_default();
+    let offset = request.offset.unwrap_or(0);
+    let limit = request.limit();
+
+    let mut total = 0;
+    let mut page = Vec::new();
+    for item in items {
+        if total >= offset && page.len() < limit {
+            page.push(item);
+        }
+        total += 1;
+    }
+    Page {
+        has_more: offset.saturating_add(page.len()) < total,
+        items: page,
+        offset,
+        total,
+    }
+}
+
+impl<T> Page<T> {
+    /// Convert each item, keeping the page's position in the list
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            offset: self.offset,
+            total: self.total,
+            has_more: self.has_more,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(limit: usize, offset: usize) -> Option<PageRequest> {
+        Some(PageRequest {
+            limit: Some(limit),
+            offset: Some(offset),
+        })
+    }
+
+    #[test]
+    fn test_pages_walk_the_list() {
+        let first = paginate(0..10, request(4, 0));
+        assert_eq!(first.items, vec![0, 1, 2, 3]);
+        assert_eq!(first.total, 10);
+        assert!(first.has_more);
+
+        let last = paginate(0..10, request(4, 8));
+        assert_eq!(last.items, vec![8, 9]);
+        assert!(!last.has_more);
+
+        let past_end = paginate(0..10, request(4, 20));
+        assert!(past_end.items.is_empty());
+        assert!(!past_end.has_more);
+    }
+
+    #[test]
+    fn test_limit_defaults_and_bounds() {
+        assert_eq!(paginate(0..5000, None).items.len(), DEFAULT_PAGE_SIZE);
+        assert_eq!(
+            paginate(0..5000, request(usize::MAX, 0)).items.len(),
+            MAX_PAGE_SIZE
+        );
+        assert_eq!(paginate(0..5000, request(0, 0)).items.len(), 1);
+    }
+
+    #[test]
+    fn test_map_keeps_position() {
+        let page = paginate(0..10, request(2, 4)).map(|n| n.to_string());
+        assert_eq!(page.items, vec!["4", "5"]);
+        assert_eq!((page.offset, page.total, page.has_more), (4, 10, true));
+    }
+}
@@ -0,0 +1,727 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Host for user-installed plugins. Each plugin lives in its own directory
+//! under `<config>/plugins` with a `plugin.json` manifest naming an
+//! executable, the commands it offers and the events it wants. The host
+//! starts the executable once per call, writes one JSON request to stdin
+//! and reads one JSON response from stdout, so a misbehaving plugin never
+//! outlives the call that started it.
+
+use crate::error::AppError;
+use crate::settings::{PluginGrant, PluginSettings};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+/// Directory under the config dir holding one subdirectory per plugin
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// Manifest file expected in each plugin directory
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+/// How long a plugin may run before it is killed
+const INVOCATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest response read from a plugin's stdout
+const MAX_OUTPUT_BYTES: u64 = 1024 * 1024;
+
+/// Data a plugin has to be granted before the host shares it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPermission {
+    /// Session ids and titles
+    ReadSessions,
+    /// Prompt and response content
+    ReadMessages,
+}
+
+/// App events a plugin can subscribe to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginEvent {
+    ResponseCompleted,
+    ResponseFailed,
+    SessionCreated,
+    SessionDeleted,
+}
+
+impl PluginEvent {
+    /// Permission needed to receive the event's payload
+    pub fn required_permission(self) -> PluginPermission {
+        match self {
+            PluginEvent::ResponseCompleted | PluginEvent::ResponseFailed => {
+                PluginPermission::ReadMessages
+            }
+            PluginEvent::SessionCreated | PluginEvent::SessionDeleted => {
+                PluginPermission::ReadSessions
+            }
+        }
+    }
+}
+
+/// Command a plugin exposes to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginCommandSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Contents of `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginManifest {
+    /// Lowercase letters, digits, `-` and `_`
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Program and arguments. A program found in the plugin directory is
+    /// run from there, otherwise it is looked up on `PATH`.
+    pub exec: Vec<String>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommandSpec>,
+    #[serde(default)]
+    pub events: Vec<PluginEvent>,
+    #[serde(default)]
+    pub permissions: Vec<PluginPermission>,
+}
+
+impl PluginManifest {
+    /// Check the fields the host relies on
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_identifier(&self.id) {
+            return Err(format!("Invalid plugin id '{}'", self.id));
+        }
+        if self.name.trim().is_empty() {
+            return Err("Plugin name is empty".to_string());
+        }
+        let program = self.exec.first().ok_or("exec must name a program")?;
+        if program.trim().is_empty() {
+            return Err("exec must name a program".to_string());
+        }
+        let mut names = BTreeSet::new();
+        for command in &self.commands {
+            if !is_identifier(&command.name) {
+                return Err(format!("Invalid command name '{}'", command.name));
+            }
+            if !names.insert(command.name.as_str()) {
+                return Err(format!("Command '{}' is declared twice", command.name));
+            }
+        }
+        for event in &self.events {
+            if !self.permissions.contains(&event.required_permission()) {
+                return Err(format!(
+                    "Event {:?} needs the {:?} permission",
+                    event,
+                    event.required_permission()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_identifier(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 64
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Plugin as shown in the plugin manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// Directory name, used as the id when the manifest could not be read
+    pub id: String,
+    pub manifest: Option<PluginManifest>,
+    pub enabled: bool,
+    pub granted: Vec<PluginPermission>,
+    /// Requested permissions the user has not granted yet; the frontend
+    /// prompts for these before enabling
+    pub pending_permissions: Vec<PluginPermission>,
+    /// Why the plugin could not be loaded
+    pub error: Option<String>,
+}
+
+/// What the host writes to a plugin's stdin
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginRequest {
+    Command {
+        command: String,
+        args: serde_json::Value,
+    },
+    Event {
+        event: PluginEvent,
+        payload: serde_json::Value,
+    },
+}
+
+/// What a plugin writes to stdout
+#[derive(Debug, Clone, Deserialize)]
+struct PluginResponse {
+    #[serde(default)]
+    result: serde_json::Value,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct DiscoveredPlugin {
+    id: String,
+    dir: PathBuf,
+    manifest: Result<PluginManifest, String>,
+}
+
+/// Discovers plugins and runs them on request
+#[derive(Clone)]
+pub struct PluginHost {
+    plugins_dir: PathBuf,
+    plugins: Arc<RwLock<Vec<DiscoveredPlugin>>>,
+}
+
+impl PluginHost {
+    pub fn new(config_dir: &Path) -> Self {
+        Self {
+            plugins_dir: config_dir.join(PLUGINS_DIR),
+            plugins: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub fn plugins_dir(&self) -> &Path {
+        &self.plugins_dir
+    }
+
+    /// Re-read every manifest. Plugins whose manifest is missing or invalid
+    /// are kept with their error so the user can see why they are inactive.
+    pub fn reload(&self) -> Result<(), AppError> {
+        let mut discovered = Vec::new();
+        if self.plugins_dir.is_dir() {
+            let entries =
+                std::fs::read_dir(&self.plugins_dir).map_err(|e| AppError::FileSystemError {
+                    path: self.plugins_dir.display().to_string(),
+                    message: "Failed to read plugins directory".to_string(),
+                    details: e.to_string(),
+                })?;
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                if !dir.is_dir() {
+                    continue;
+                }
+                let id = entry.file_name().to_string_lossy().to_string();
+                let manifest = read_manifest(&dir).and_then(|manifest| {
+                    if manifest.id != id {
+                        return Err(format!(
+                            "Manifest id '{}' does not match directory '{}'",
+                            manifest.id, id
+                        ));
+                    }
+                    Ok(manifest)
+                });
+                discovered.push(DiscoveredPlugin { id, dir, manifest });
+            }
+        }
+        discovered.sort_by(|a, b| a.id.cmp(&b.id));
+
+        match self.plugins.write() {
+            Ok(mut plugins) => *plugins = discovered,
+            Err(poisoned) => {
+                eprintln!("[ERROR] PluginHost reload: plugins lock poisoned, recovering...");
+                *poisoned.into_inner() = discovered;
+            }
+        }
+        Ok(())
+    }
+
+    fn discovered(&self) -> Vec<DiscoveredPlugin> {
+        match self.plugins.read() {
+            Ok(plugins) => plugins.clone(),
+            Err(poisoned) => {
+                eprintln!("[ERROR] PluginHost: plugins lock poisoned, recovering...");
+                poisoned.into_inner().clone()
+            }
+        }
+    }
+
+    /// Manifest of a loaded plugin
+    pub fn manifest(&self, plugin_id: &str) -> Option<PluginManifest> {
+        self.discovered()
+            .into_iter()
+            .find(|plugin| plugin.id == plugin_id)
+            .and_then(|plugin| plugin.manifest.ok())
+    }
+
+    /// Every discovered plugin with its grant state
+    pub fn list(&self, settings: &PluginSettings) -> Vec<PluginInfo> {
+        self.discovered()
+            .into_iter()
+            .map(|plugin| {
+                let grant = settings.grants.get(&plugin.id).cloned().unwrap_or_default();
+                let (manifest, error) = match plugin.manifest {
+                    Ok(manifest) => (Some(manifest), None),
+                    Err(error) => (None, Some(error)),
+                };
+                let pending_permissions = manifest
+                    .as_ref()
+                    .map(|manifest| pending_permissions(manifest, &grant))
+                    .unwrap_or_default();
+                PluginInfo {
+                    id: plugin.id,
+                    enabled: grant.enabled && manifest.is_some(),
+                    granted: grant.permissions,
+                    pending_permissions,
+                    manifest,
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// Run one of a plugin's declared commands and return its result
+    pub async fn invoke_command(
+        &self,
+        settings: &PluginSettings,
+        plugin_id: &str,
+        command: &str,
+        args: serde_json::Value,
+    ) -> Result<serde_json::Value, AppError> {
+        let plugin = self.enabled_plugin(settings, plugin_id)?;
+        let manifest = plugin
+            .manifest
+            .as_ref()
+            .map_err(|e| AppError::Other { message: e.clone() })?;
+        if !manifest.commands.iter().any(|spec| spec.name == command) {
+            return Err(AppError::ValidationError {
+                field: "command".to_string(),
+                message: format!("Plugin '{}' has no command '{}'", plugin_id, command),
+            });
+        }
+        run_plugin(
+            &plugin.dir,
+            manifest,
+            &PluginRequest::Command {
+                command: command.to_string(),
+                args,
+            },
+        )
+        .await
+    }
+
+    /// Enabled plugins that subscribed to `event` and were granted the
+    /// permission its payload needs
+    fn subscribers(&self, settings: &PluginSettings, event: PluginEvent) -> Vec<DiscoveredPlugin> {
+        self.discovered()
+            .into_iter()
+            .filter(|plugin| {
+                let Ok(manifest) = &plugin.manifest else {
+                    return false;
+                };
+                let Some(grant) = settings.grants.get(&plugin.id) else {
+                    return false;
+                };
+                grant.enabled
+                    && manifest.events.contains(&event)
+                    && grant.permissions.contains(&event.required_permission())
+            })
+            .collect()
+    }
+
+    /// Deliver an event to each subscribed plugin. Failures are returned per
+    /// plugin so the caller can log them without stopping the others.
+    pub async fn dispatch(
+        &self,
+        settings: &PluginSettings,
+        event: PluginEvent,
+        payload: serde_json::Value,
+    ) -> Vec<(String, AppError)> {
+        let mut failures = Vec::new();
+        for plugin in self.subscribers(settings, event) {
+            let Ok(manifest) = &plugin.manifest else {
+                continue;
+            };
+            let request = PluginRequest::Event {
+                event,
+                payload: payload.clone(),
+            };
+            if let Err(e) = run_plugin(&plugin.dir, manifest, &request).await {
+                failures.push((plugin.id.clone(), e));
+            }
+        }
+        failures
+    }
+
+    fn enabled_plugin(
+        &self,
+        settings: &PluginSettings,
+        plugin_id: &str,
+    ) -> Result<DiscoveredPlugin, AppError> {
+        let plugin = self
+            .discovered()
+            .into_iter()
+            .find(|plugin| plugin.id == plugin_id)
+            .ok_or_else(|| AppError::ValidationError {
+                field: "plugin_id".to_string(),
+                message: format!("Plugin '{}' is not installed", plugin_id),
+            })?;
+        let enabled = settings
+            .grants
+            .get(plugin_id)
+            .is_some_and(|grant| grant.enabled);
+        if !enabled {
+            return Err(AppError::ValidationError {
+                field: "plugin_id".to_string(),
+                message: format!("Plugin '{}' is disabled", plugin_id),
+            });
+        }
+        Ok(plugin)
+    }
+}
+
+/// Requested permissions missing from `grant`
+pub fn pending_permissions(
+    manifest: &PluginManifest,
+    grant: &PluginGrant,
+) -> Vec<PluginPermission> {
+    let requested: BTreeSet<_> = manifest.permissions.iter().copied().collect();
+    requested
+        .into_iter()
+        .filter(|permission| !grant.permissions.contains(permission))
+        .collect()
+}
+
+fn read_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let path = dir.join(MANIFEST_FILE);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", MANIFEST_FILE, e))?;
+    manifest.validate()?;
+    Ok(manifest)
+}
+
+/// Program to start: a file inside the plugin directory, or a bare name
+/// looked up on `PATH`. Paths escaping the plugin directory are refused.
+fn resolve_program(dir: &Path, program: &str) -> Result<PathBuf, String> {
+    let local = dir.join(program);
+    if local.exists() {
+        let canonical_dir = dir.canonicalize().map_err(|e| e.to_string())?;
+        let canonical = local.canonicalize().map_err(|e| e.to_string())?;
+        if !canonical.starts_with(&canonical_dir) {
+            return Err(format!("'{}' is outside the plugin directory", program));
+        }
+        return Ok(canonical);
+    }
+    if program.contains(['/', '\\']) {
+        return Err(format!(
+            "'{}' does not exist in the plugin directory",
+            program
+        ));
+    }
+    Ok(PathBuf::from(program))
+}
+
+async fn run_plugin(
+    dir: &Path,
+    manifest: &PluginManifest,
+    request: &PluginRequest,
+) -> Result<serde_json::Value, AppError> {
+    let plugin_error = |message: String| AppError::Other {
+        message: format!("Plugin '{}': {}", manifest.id, message),
+    };
+    let program = resolve_program(dir, &manifest.exec[0]).map_err(plugin_error)?;
+    let input = serde_json::to_vec(request).map_err(|e| plugin_error(e.to_string()))?;
+
+    // Plugins get a clean environment so tokens in ours are not inherited
+    let mut command = Command::new(program);
+    command
+        .args(&manifest.exec[1..])
+        .current_dir(dir)
+        .env_clear()
+        .envs(std::env::vars_os().filter(|(key, _)| {
+            [
+                "PATH",
+                "HOME",
+                "USERPROFILE",
+                "SYSTEMROOT",
+                "TEMP",
+                "TMP",
+                "LANG",
+            ]
+            .iter()
+            .any(|allowed| key.eq_ignore_ascii_case(allowed))
+        }))
+        .env("OPENCODE_NEXUS_PLUGIN_ID", &manifest.id)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| plugin_error(format!("failed to start: {}", e)))?;
+
+    let exchange = async {
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&input).await?;
+            stdin.write_all(b"\n").await?;
+        }
+        let mut output = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            stdout
+                .take(MAX_OUTPUT_BYTES)
+                .read_to_end(&mut output)
+                .await?;
+        }
+        let status = child.wait().await?;
+        Ok::<_, std::io::Error>((status, output))
+    };
+
+    let (status, output) = tokio::time::timeout(INVOCATION_TIMEOUT, exchange)
+        .await
+        .map_err(|_| AppError::TimeoutError {
+            operation: format!("plugin '{}'", manifest.id),
+            timeout_secs: INVOCATION_TIMEOUT.as_secs(),
+        })?
+        .map_err(|e| plugin_error(e.to_string()))?;
+
+    if !status.success() {
+        return Err(plugin_error(format!("exited with {}", status)));
+    }
+    parse_response(&output).map_err(plugin_error)
+}
+
+fn parse_response(output: &[u8]) -> Result<serde_json::Value, String> {
+    let text = String::from_utf8_lossy(output);
+    if text.trim().is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    let response: PluginResponse =
+        serde_json::from_str(text.trim()).map_err(|e| format!("invalid response: {}", e))?;
+    match response.error {
+        Some(error) => Err(error),
+        None => Ok(response.result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    fn manifest() -> PluginManifest {
+        PluginManifest {
+            id: "wiki-sync".to_string(),
+            name: "Wiki sync".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            exec: vec!["run.sh".to_string()],
+            commands: vec![PluginCommandSpec {
+                name: "publish".to_string(),
+                description: String::new(),
+            }],
+            events: vec![PluginEvent::ResponseCompleted],
+            permissions: vec![PluginPermission::ReadMessages],
+        }
+    }
+
+    fn install(temp: &TempDir, manifest: &PluginManifest) -> PathBuf {
+        let dir = temp.path().join(PLUGINS_DIR).join(&manifest.id);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_string(manifest).unwrap(),
+        )
+        .unwrap();
+        dir
+    }
+
+    fn granted(id: &str, permissions: Vec<PluginPermission>) -> PluginSettings {
+        let mut grants = BTreeMap::new();
+        grants.insert(
+            id.to_string(),
+            PluginGrant {
+                enabled: true,
+                permissions,
+            },
+        );
+        PluginSettings { grants }
+    }
+
+    #[test]
+    fn test_validate_manifest() {
+        assert!(manifest().validate().is_ok());
+
+        let mut bad_id = manifest();
+        bad_id.id = "Wiki Sync".to_string();
+        assert!(bad_id.validate().is_err());
+
+        let mut no_exec = manifest();
+        no_exec.exec.clear();
+        assert!(no_exec.validate().is_err());
+
+        let mut missing_permission = manifest();
+        missing_permission.permissions.clear();
+        assert!(missing_permission.validate().is_err());
+
+        let mut duplicate = manifest();
+        duplicate.commands.push(duplicate.commands[0].clone());
+        assert!(duplicate.validate().is_err());
+    }
+
+    #[test]
+    fn test_reload_lists_valid_and_broken_plugins() {
+        let temp = TempDir::new().unwrap();
+        install(&temp, &manifest());
+        let broken = temp.path().join(PLUGINS_DIR).join("broken");
+        std::fs::create_dir_all(&broken).unwrap();
+        std::fs::write(broken.join(MANIFEST_FILE), "{").unwrap();
+
+        let host = PluginHost::new(temp.path());
+        host.reload().unwrap();
+        let plugins = host.list(&PluginSettings::default());
+
+        assert_eq!(plugins.len(), 2);
+        assert_eq!(plugins[0].id, "broken");
+        assert!(plugins[0].error.is_some());
+        assert_eq!(plugins[1].id, "wiki-sync");
+        assert!(!plugins[1].enabled);
+        assert_eq!(
+            plugins[1].pending_permissions,
+            vec![PluginPermission::ReadMessages]
+        );
+    }
+
+    #[test]
+    fn test_subscribers_need_grant_and_permission() {
+        let temp = TempDir::new().unwrap();
+        install(&temp, &manifest());
+        let host = PluginHost::new(temp.path());
+        host.reload().unwrap();
+
+        let event = PluginEvent::ResponseCompleted;
+        assert!(host
+            .subscribers(&PluginSettings::default(), event)
+            .is_empty());
+        assert!(host
+            .subscribers(&granted("wiki-sync", Vec::new()), event)
+            .is_empty());
+        assert_eq!(
+            host.subscribers(
+                &granted("wiki-sync", vec![PluginPermission::ReadMessages]),
+                event
+            )
+            .len(),
+            1
+        );
+        assert!(host
+            .subscribers(
+                &granted("wiki-sync", vec![PluginPermission::ReadMessages]),
+                PluginEvent::SessionCreated
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_resolve_program_stays_in_plugin_dir() {
+        let temp = TempDir::new().unwrap();
+        let dir = install(&temp, &manifest());
+        std::fs::write(dir.join("run.sh"), "").unwrap();
+
+        assert!(resolve_program(&dir, "run.sh").is_ok());
+        assert_eq!(
+            resolve_program(&dir, "node").unwrap(),
+            PathBuf::from("node")
+        );
+        assert!(resolve_program(&dir, "../../escape.sh").is_err());
+        std::fs::write(temp.path().join("outside.sh"), "").unwrap();
+        assert!(resolve_program(&dir, "../../outside.sh").is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(parse_response(b"").unwrap(), serde_json::Value::Null);
+        assert_eq!(
+            parse_response(br#"{"result": {"url": "https://wiki"}}"#).unwrap(),
+            serde_json::json!({"url": "https://wiki"})
+        );
+        assert_eq!(
+            parse_response(br#"{"error": "wiki unreachable"}"#).unwrap_err(),
+            "wiki unreachable"
+        );
+        assert!(parse_response(b"not json").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_invoke_command_round_trip() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let dir = install(&temp, &manifest());
+        let script = dir.join("run.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\nread request\necho \"{\\\"result\\\": $request}\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let host = PluginHost::new(temp.path());
+        host.reload().unwrap();
+        let settings = granted("wiki-sync", vec![PluginPermission::ReadMessages]);
+
+        let result = host
+            .invoke_command(
+                &settings,
+                "wiki-sync",
+                "publish",
+                serde_json::json!({"page": 1}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["type"], "command");
+        assert_eq!(result["args"]["page"], 1);
+
+        assert!(host
+            .invoke_command(&settings, "wiki-sync", "unknown", serde_json::Value::Null)
+            .await
+            .is_err());
+        assert!(host
+            .invoke_command(
+                &PluginSettings::default(),
+                "wiki-sync",
+                "publish",
+                serde_json::Value::Null
+            )
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,290 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Data fetched in the background as soon as a connection is established,
+//! so the first model picker, session list and command completion after
+//! connecting don't wait on the server. Each fetch reports its progress to
+//! the frontend as it finishes.
+
+use crate::session_manager::SessionSummary;
+use crate::slash_commands::ServerCommand;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Event carrying a [`PrefetchProgress`]
+pub const PREFETCH_EVENT: &str = "prefetch-progress";
+
+/// How long prefetched data is served before commands fetch it again
+const PREFETCH_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefetchTarget {
+    Models,
+    Sessions,
+    /// The commands the server offers
+    Capabilities,
+}
+
+impl PrefetchTarget {
+    pub const ALL: [PrefetchTarget; 3] = [Self::Models, Self::Sessions, Self::Capabilities];
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PrefetchStatus {
+    Done { count: usize },
+    Failed { error: String },
+}
+
+/// One fetch finishing, out of the `total` started for a connection
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefetchProgress {
+    pub server_url: String,
+    pub target: PrefetchTarget,
+    #[serde(flatten)]
+    pub status: PrefetchStatus,
+    pub completed: usize,
+    pub total: usize,
+}
+
+struct Fetched<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+impl<T> Fetched<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            fetched_at: Instant::now(),
+        }
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.fetched_at.elapsed() < PREFETCH_TTL
+    }
+}
+
+#[derive(Default)]
+struct Prefetched {
+    server_url: Option<String>,
+    completed: usize,
+    models: Option<Fetched<Vec<serde_json::Value>>>,
+    sessions: Option<Fetched<Vec<SessionSummary>>>,
+    capabilities: Option<Fetched<Vec<ServerCommand>>>,
+}
+
+/// Results of the latest prefetch, for the server connected to last
+#[derive(Clone, Default)]
+pub struct PrefetchCache {
+    inner: Arc<Mutex<Prefetched>>,
+}
+
+impl PrefetchCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Prefetched> {
+        self.inner.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Prefetch cache lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    /// Forget what was fetched from the previous server
+    pub fn begin(&self, server_url: &str) {
+        *self.lock() = Prefetched {
+            server_url: Some(server_url.to_string()),
+            ..Prefetched::default()
+        };
+    }
+
+    pub fn clear(&self) {
+        *self.lock() = Prefetched::default();
+    }
+
+    /// Record a finished fetch and describe it for the progress event.
+    /// Returns `None` if the connection changed while it was running.
+    fn finish(
+        &self,
+        server_url: &str,
+        target: PrefetchTarget,
+        status: PrefetchStatus,
+        store: impl FnOnce(&mut Prefetched),
+    ) -> Option<PrefetchProgress> {
+        let mut prefetched = self.lock();
+        if prefetched.server_url.as_deref() != Some(server_url) {
+            return None;
+        }
+        store(&mut prefetched);
+        prefetched.completed += 1;
+        Some(PrefetchProgress {
+            server_url: server_url.to_string(),
+            target,
+            status,
+            completed: prefetched.completed,
+            total: PrefetchTarget::ALL.len(),
+        })
+    }
+
+    /// Store the outcome of fetching the model list
+    pub fn finish_models(
+        &self,
+        server_url: &str,
+        result: Result<Vec<serde_json::Value>, String>,
+    ) -> Option<PrefetchProgress> {
+        let (status, value) = outcome(result);
+        self.finish(server_url, PrefetchTarget::Models, status, |p| {
+            p.models = value.map(Fetched::new)
+        })
+    }
+
+    /// Store the outcome of fetching the session list
+    pub fn finish_sessions(
+        &self,
+        server_url: &str,
+        result: Result<Vec<SessionSummary>, String>,
+    ) -> Option<PrefetchProgress> {
+        let (status, value) = outcome(result);
+        self.finish(server_url, PrefetchTarget::Sessions, status, |p| {
+            p.sessions = value.map(Fetched::new)
+        })
+    }
+
+    /// Store the outcome of fetching the server's commands
+    pub fn finish_capabilities(
+        &self,
+        server_url: &str,
+        result: Result<Vec<ServerCommand>, String>,
+    ) -> Option<PrefetchProgress> {
+        let (status, value) = outcome(result);
+        self.finish(server_url, PrefetchTarget::Capabilities, status, |p| {
+            p.capabilities = value.map(Fetched::new)
+        })
+    }
+
+    pub fn models(&self) -> Option<Vec<serde_json::Value>> {
+        let prefetched = self.lock();
+        prefetched
+            .models
+            .as_ref()
+            .filter(|fetched| fetched.is_fresh())
+            .map(|fetched| fetched.value.clone())
+    }
+
+    /// The prefetched session list, handed out once since sessions change
+    /// as soon as the user starts chatting
+    pub fn take_sessions(&self) -> Option<Vec<SessionSummary>> {
+        self.lock()
+            .sessions
+            .take()
+            .filter(|fetched| fetched.is_fresh())
+            .map(|fetched| fetched.value)
+    }
+
+    /// Drop the prefetched session list once sessions are added or removed
+    pub fn forget_sessions(&self) {
+        self.lock().sessions = None;
+    }
+
+    pub fn capabilities(&self) -> Option<Vec<ServerCommand>> {
+        let prefetched = self.lock();
+        prefetched
+            .capabilities
+            .as_ref()
+            .filter(|fetched| fetched.is_fresh())
+            .map(|fetched| fetched.value.clone())
+    }
+}
+
+fn outcome<T>(result: Result<Vec<T>, String>) -> (PrefetchStatus, Option<Vec<T>>) {
+    match result {
+        Ok(value) => (PrefetchStatus::Done { count: value.len() }, Some(value)),
+        Err(error) => (PrefetchStatus::Failed { error }, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER: &str = "http://localhost:4096";
+
+    fn command(name: &str) -> ServerCommand {
+        ServerCommand {
+            name: name.to_string(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_progress_counts_finished_fetches() {
+        let cache = PrefetchCache::new();
+        cache.begin(SERVER);
+
+        let first = cache
+            .finish_capabilities(SERVER, Ok(vec![command("init")]))
+            .unwrap();
+        assert_eq!(first.target, PrefetchTarget::Capabilities);
+        assert_eq!(first.status, PrefetchStatus::Done { count: 1 });
+        assert_eq!((first.completed, first.total), (1, 3));
+
+        let second = cache
+            .finish_models(SERVER, Err("timed out".to_string()))
+            .unwrap();
+        assert_eq!(second.completed, 2);
+        let json = serde_json::to_value(&second).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["error"], "timed out");
+        assert_eq!(json["target"], "models");
+
+        assert_eq!(cache.capabilities().unwrap(), vec![command("init")]);
+        assert!(cache.models().is_none());
+    }
+
+    #[test]
+    fn test_sessions_are_handed_out_once() {
+        let cache = PrefetchCache::new();
+        cache.begin(SERVER);
+        cache.finish_sessions(SERVER, Ok(Vec::new())).unwrap();
+        assert!(cache.take_sessions().is_some());
+        assert!(cache.take_sessions().is_none());
+    }
+
+    #[test]
+    fn test_results_for_an_old_connection_are_dropped() {
+        let cache = PrefetchCache::new();
+        cache.begin(SERVER);
+        cache.begin("http://other:4096");
+        assert!(cache
+            .finish_capabilities(SERVER, Ok(vec![command("init")]))
+            .is_none());
+        assert!(cache.capabilities().is_none());
+
+        cache.clear();
+        assert!(cache
+            .finish_capabilities("http://other:4096", Ok(Vec::new()))
+            .is_none());
+    }
+}
@@ -0,0 +1,297 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use crate::error_stats::ErrorSummary;
+use crate::log_query::{self, LogEntry, LogQuery};
+use crate::subsystems::{SubsystemHealth, SubsystemStatus};
+use crate::support_bundle::{redact_text, redact_value, system_info, SystemInfo};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where pre-filled issue links point
+pub const ISSUES_URL: &str = "https://github.com/opencode-nexus/opencode-nexus/issues/new";
+
+/// Events kept in a report
+pub const RECENT_EVENT_LIMIT: usize = 50;
+
+/// Error log entries kept in a report
+pub const RECENT_ERROR_LIMIT: usize = 20;
+
+/// Errors listed in the issue body; the full list stays in the report
+const ISSUE_ERROR_LIMIT: usize = 10;
+
+/// Browsers and GitHub reject very long URLs
+const MAX_ISSUE_URL_LEN: usize = 8000;
+
+/// A user's problem description with the context needed to reproduce it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemReport {
+    pub id: String,
+    pub description: String,
+    pub system: SystemInfo,
+    /// Connection status, current connection and server health
+    pub connection: serde_json::Value,
+    pub subsystems: Vec<SubsystemStatus>,
+    /// Most recent application events, oldest first
+    pub recent_events: Vec<serde_json::Value>,
+    /// Most recent error log entries, newest first
+    pub recent_errors: Vec<LogEntry>,
+    #[serde(default)]
+    pub error_summary: Option<ErrorSummary>,
+    /// Pre-filled GitHub issue link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_url: Option<String>,
+}
+
+impl ProblemReport {
+    /// Start a report for `description` with system details filled in
+    pub fn new(description: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            description: description.trim().to_string(),
+            system: system_info(),
+            connection: serde_json::Value::Null,
+            subsystems: Vec::new(),
+            recent_events: Vec::new(),
+            recent_errors: Vec::new(),
+            error_summary: None,
+            issue_url: None,
+        }
+    }
+
+    /// Trim to the report limits and mask credentials everywhere
+    pub fn redacted(mut self) -> Self {
+        self.description = redact_text(&self.description);
+        redact_value(&mut self.connection);
+
+        let skip = self.recent_events.len().saturating_sub(RECENT_EVENT_LIMIT);
+        self.recent_events.drain(..skip);
+        self.recent_events.iter_mut().for_each(redact_value);
+
+        self.recent_errors.truncate(RECENT_ERROR_LIMIT);
+        for entry in &mut self.recent_errors {
+            entry.message = redact_text(&entry.message);
+            let mut fields = serde_json::Value::Object(std::mem::take(&mut entry.fields));
+            redact_value(&mut fields);
+            if let serde_json::Value::Object(fields) = fields {
+                entry.fields = fields;
+            }
+        }
+        self
+    }
+
+    /// Markdown body for a GitHub issue
+    pub fn issue_body(&self) -> String {
+        let mut body = format!("## Description\n\n{}\n\n", self.description);
+
+        body.push_str("## Environment\n\n");
+        body.push_str(&format!("- App version: {}\n", self.system.app_version));
+        body.push_str(&format!(
+            "- OS: {} {} ({})\n",
+            self.system.os,
+            self.system.os_version.as_deref().unwrap_or_default(),
+            self.system.arch
+        ));
+        if let Some(status) = self.connection.get("connection_status") {
+            let status = status
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| status.to_string());
+            body.push_str(&format!("- Connection: {}\n", status));
+        }
+        body.push_str(&format!("- Report id: {}\n\n", self.id));
+
+        let unhealthy: Vec<&SubsystemStatus> = self
+            .subsystems
+            .iter()
+            .filter(|status| {
+                matches!(
+                    status.health,
+                    SubsystemHealth::Degraded | SubsystemHealth::Unavailable
+                )
+            })
+            .collect();
+        if !unhealthy.is_empty() {
+            body.push_str("## Subsystems\n\n");
+            for status in unhealthy {
+                body.push_str(&format!(
+                    "- {:?}: {:?} ({})\n",
+                    status.subsystem,
+                    status.health,
+                    status.error.as_deref().unwrap_or_default()
+                ));
+            }
+            body.push('\n');
+        }
+
+        if let Some(summary) = self.error_summary.as_ref().filter(|s| s.total > 0) {
+            body.push_str("## Error summary\n\n");
+            for entry in &summary.entries {
+                body.push_str(&format!(
+                    "- {} x {:?} in {}\n",
+                    entry.count, entry.code, entry.module
+                ));
+            }
+            body.push('\n');
+        }
+
+        if !self.recent_errors.is_empty() {
+            body.push_str("## Recent errors\n\n");
+            for entry in self.recent_errors.iter().take(ISSUE_ERROR_LIMIT) {
+                body.push_str(&format!(
+                    "- `{}` {} {}: {}\n",
+                    entry.timestamp.to_rfc3339(),
+                    entry.level,
+                    entry.target,
+                    entry.message
+                ));
+            }
+            body.push('\n');
+        }
+
+        body
+    }
+
+    /// Issue title taken from the first line of the description
+    pub fn issue_title(&self) -> String {
+        let first_line = self.description.lines().next().unwrap_or_default();
+        let mut title: String = first_line.chars().take(80).collect();
+        if first_line.chars().count() > 80 {
+            title.push_str("...");
+        }
+        format!("Problem report: {}", title)
+    }
+
+    /// Pre-filled new-issue link, shortening the body to fit URL limits
+    pub fn build_issue_url(&self) -> String {
+        let prefix = format!(
+            "{}?labels=bug&title={}&body=",
+            ISSUES_URL,
+            urlencoding::encode(&self.issue_title())
+        );
+
+        let mut body = self.issue_body();
+        loop {
+            let encoded = urlencoding::encode(&body);
+            if prefix.len() + encoded.len() <= MAX_ISSUE_URL_LEN || body.is_empty() {
+                return format!("{}{}", prefix, encoded);
+            }
+            let keep = body.chars().count() * 9 / 10;
+            body = body.chars().take(keep).collect();
+        }
+    }
+
+    /// Save the report as `problem_reports/<id>.json` under the config directory
+    pub fn save(&self, config_dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let reports_dir = config_dir.join("problem_reports");
+        std::fs::create_dir_all(&reports_dir).map_err(|e| AppError::FileSystemError {
+            path: reports_dir.to_string_lossy().to_string(),
+            message: "Failed to create problem reports directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let path = reports_dir.join(format!("{}.json", self.id));
+        let report_json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, report_json).map_err(|e| AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: "Failed to write problem report".to_string(),
+            details: e.to_string(),
+        })?;
+        Ok(path)
+    }
+}
+
+/// Newest error log entries; an unreadable log yields an empty list
+pub fn recent_errors(log_dir: &Path) -> Vec<LogEntry> {
+    let query = LogQuery {
+        level: Some("error".to_string()),
+        limit: Some(RECENT_ERROR_LIMIT),
+        ..LogQuery::default()
+    };
+    log_query::query_logs(log_dir, &query)
+        .map(|result| result.entries)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_report() -> ProblemReport {
+        let mut report = ProblemReport::new("Streaming stops after the first chunk\nSteps: ...");
+        report.connection = serde_json::json!({
+            "connection_status": "Connected",
+            "api_key": "sk-secret",
+        });
+        report.recent_events = (0..60).map(|i| serde_json::json!({ "index": i })).collect();
+        report.recent_errors = vec![LogEntry {
+            timestamp: chrono::Utc::now(),
+            level: "ERROR".to_string(),
+            target: "stream".to_string(),
+            message: "Request failed with Bearer abc.def".to_string(),
+            fields: serde_json::Map::new(),
+        }];
+        report
+    }
+
+    #[test]
+    fn test_redacted_trims_and_masks() {
+        let report = create_test_report().redacted();
+
+        assert_eq!(report.recent_events.len(), RECENT_EVENT_LIMIT);
+        assert_eq!(report.recent_events[0]["index"], 10);
+        assert_eq!(report.connection["api_key"], "[REDACTED]");
+        assert!(!report.recent_errors[0].message.contains("abc.def"));
+    }
+
+    #[test]
+    fn test_issue_url_is_prefilled() {
+        let report = create_test_report().redacted();
+        let url = report.build_issue_url();
+
+        assert!(url.starts_with(ISSUES_URL));
+        assert!(url.contains("title=Problem%20report%3A%20Streaming%20stops"));
+        assert!(url.contains(&report.id));
+        assert!(!url.contains("sk-secret"));
+        assert!(url.contains("Connection%3A%20Connected%0A"));
+    }
+
+    #[test]
+    fn test_save_writes_report_json() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let report = create_test_report().redacted();
+
+        let path = report.save(temp_dir.path()).expect("Should save report");
+        let saved: ProblemReport =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(saved.id, report.id);
+    }
+
+    #[test]
+    fn test_issue_url_respects_length_limit() {
+        let mut report = ProblemReport::new(&"x".repeat(20_000));
+        report.connection = serde_json::json!({});
+        let url = report.build_issue_url();
+        assert!(url.len() <= MAX_ISSUE_URL_LEN);
+    }
+}
@@ -0,0 +1,554 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use crate::logging::LOG_FILE_NAME;
+use crate::settings::EncryptedProfileSettings;
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use zeroize::Zeroizing;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Encrypted container holding the profile while the app is closed
+pub const VAULT_FILE_NAME: &str = "profile.vault";
+
+/// Marks a vault whose files are restored and not sealed again yet. Still
+/// there at startup means the last session ended without resealing.
+const OPEN_MARKER_FILE_NAME: &str = "profile.vault.open";
+
+/// Shortest passphrase accepted when enabling encryption
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+const VAULT_MAGIC: &[u8; 4] = b"ONXV";
+const VAULT_AAD: &[u8] = b"opencode-nexus-profile-v1";
+const VERIFIER_PLAINTEXT: &[u8] = b"opencode-nexus-profile";
+const KEY_LEN: usize = 32;
+
+/// Files that stay readable so the app can start and ask for the passphrase
+const UNENCRYPTED_FILES: [&str; 3] = ["settings.json", VAULT_FILE_NAME, OPEN_MARKER_FILE_NAME];
+
+/// AES-256-GCM key derived from the profile passphrase
+pub struct ProfileKey {
+    bytes: Zeroizing<[u8; KEY_LEN]>,
+}
+
+impl ProfileKey {
+    fn derive_with(
+        passphrase: &str,
+        salt: &[u8],
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    ) -> Result<Self, AppError> {
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(KEY_LEN)).map_err(|e| {
+            AppError::ValidationError {
+                field: "encrypted_profile".to_string(),
+                message: format!("Invalid key derivation parameters: {}", e),
+            }
+        })?;
+        let argon2 =
+            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+        let mut bytes = Zeroizing::new([0u8; KEY_LEN]);
+        argon2
+            .hash_password_into(passphrase.as_bytes(), salt, bytes.as_mut())
+            .map_err(|e| AppError::Other {
+                message: format!("Failed to derive profile key: {}", e),
+            })?;
+        Ok(Self { bytes })
+    }
+
+    /// Derive the key for an existing profile, rejecting a wrong passphrase
+    pub fn unlock(passphrase: &str, settings: &EncryptedProfileSettings) -> Result<Self, AppError> {
+        let key = Self::derive_with(
+            passphrase,
+            &settings.salt,
+            settings.m_cost,
+            settings.t_cost,
+            settings.p_cost,
+        )?;
+        match key.decrypt(&settings.verifier) {
+            Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => Ok(key),
+            _ => Err(AppError::AuthError {
+                message: "Incorrect passphrase".to_string(),
+                details: "Profile key verification failed".to_string(),
+            }),
+        }
+    }
+
+    /// Create a key for a newly encrypted profile with default Argon2id costs
+    pub fn create(passphrase: &str) -> Result<(Self, EncryptedProfileSettings), AppError> {
+        let defaults = argon2::Params::default();
+        Self::create_with(
+            passphrase,
+            defaults.m_cost(),
+            defaults.t_cost(),
+            defaults.p_cost(),
+        )
+    }
+
+    fn create_with(
+        passphrase: &str,
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    ) -> Result<(Self, EncryptedProfileSettings), AppError> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(AppError::ValidationError {
+                field: "passphrase".to_string(),
+                message: format!("must be at least {} characters", MIN_PASSPHRASE_LEN),
+            });
+        }
+
+        let mut salt = vec![0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        let key = Self::derive_with(passphrase, &salt, m_cost, t_cost, p_cost)?;
+        let verifier = key.encrypt(VERIFIER_PLAINTEXT)?;
+
+        let settings = EncryptedProfileSettings {
+            salt,
+            m_cost,
+            t_cost,
+            p_cost,
+            verifier,
+        };
+        Ok((key, settings))
+    }
+
+    /// Wrap raw key material, such as the secrets file machine key
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AppError> {
+        let bytes: [u8; KEY_LEN] = bytes.try_into().map_err(|_| AppError::DataError {
+            message: "Invalid key length".to_string(),
+            details: format!("expected {} bytes, got {}", KEY_LEN, bytes.len()),
+        })?;
+        Ok(Self {
+            bytes: Zeroizing::new(bytes),
+        })
+    }
+
+    fn cipher(&self) -> Result<LessSafeKey, AppError> {
+        UnboundKey::new(&AES_256_GCM, self.bytes.as_ref())
+            .map(LessSafeKey::new)
+            .map_err(|_| AppError::Other {
+                message: "Invalid profile key".to_string(),
+            })
+    }
+
+    /// Encrypt to `nonce || ciphertext || tag`
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, AppError> {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
+
+        let mut in_out = plaintext.to_vec();
+        self.cipher()?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::from(VAULT_AAD),
+                &mut in_out,
+            )
+            .map_err(|_| AppError::Other {
+                message: "Failed to encrypt profile data".to_string(),
+            })?;
+
+        let mut sealed = nonce_bytes.to_vec();
+        sealed.extend_from_slice(&in_out);
+        Ok(sealed)
+    }
+
+    /// Decrypt data produced by `encrypt`
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, AppError> {
+        let invalid = || AppError::DataError {
+            message: "Failed to decrypt profile data".to_string(),
+            details: "Wrong key or corrupted data".to_string(),
+        };
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+
+        let (nonce_bytes, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce_bytes).map_err(|_| invalid())?;
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .cipher()?
+            .open_in_place(nonce, Aad::from(VAULT_AAD), &mut in_out)
+            .map_err(|_| invalid())?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// Packs the config directory into `profile.vault` and back
+pub struct ProfileVault {
+    config_dir: PathBuf,
+}
+
+impl ProfileVault {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self { config_dir }
+    }
+
+    fn get_vault_file_path(&self) -> PathBuf {
+        self.config_dir.join(VAULT_FILE_NAME)
+    }
+
+    fn get_open_marker_path(&self) -> PathBuf {
+        self.config_dir.join(OPEN_MARKER_FILE_NAME)
+    }
+
+    /// Whether sealed data is waiting to be opened
+    pub fn is_sealed(&self) -> bool {
+        self.get_vault_file_path().exists() && !self.is_open()
+    }
+
+    /// Whether the vault was opened and never sealed again, e.g. because the
+    /// app crashed. The plaintext files are then newer than the vault.
+    pub fn is_open(&self) -> bool {
+        self.get_open_marker_path().exists()
+    }
+
+    /// Delete the vault once its files are restored for good
+    pub fn remove(&self) -> std::io::Result<()> {
+        for path in [self.get_vault_file_path(), self.get_open_marker_path()] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Profile files relative to the config directory
+    fn protected_files(&self) -> Vec<PathBuf> {
+        fn walk(dir: &Path, base: &Path, files: &mut Vec<PathBuf>) {
+            for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    walk(&path, base, files);
+                } else if let Ok(relative) = path.strip_prefix(base) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        walk(&self.config_dir, &self.config_dir, &mut files);
+        let temp_path = Path::new(VAULT_FILE_NAME).with_extension("vault.tmp");
+        files.retain(|file| {
+            *file != temp_path
+                && !UNENCRYPTED_FILES
+                    .iter()
+                    .any(|unencrypted| file == Path::new(unencrypted))
+        });
+        files.sort();
+        files
+    }
+
+    /// Encrypt every profile file into the vault and remove the plaintext.
+    /// The active log is truncated rather than removed because the logger
+    /// keeps it open. Returns the number of files sealed.
+    pub fn seal(&self, key: &ProfileKey) -> Result<usize, Box<dyn std::error::Error>> {
+        let files = self.protected_files();
+
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        for relative in &files {
+            let path = self.config_dir.join(relative);
+            let contents = std::fs::read(&path)?;
+            let options = SimpleFileOptions::default();
+            // Keep modes so private keys stay private and plugins executable
+            #[cfg(unix)]
+            let options = {
+                use std::os::unix::fs::PermissionsExt;
+                options.unix_permissions(std::fs::metadata(&path)?.permissions().mode())
+            };
+            archive.start_file(relative.to_string_lossy().replace('\\', "/"), options)?;
+            archive.write_all(&contents)?;
+        }
+        let archive = archive.finish()?.into_inner();
+
+        let mut vault = VAULT_MAGIC.to_vec();
+        vault.extend(key.encrypt(&archive)?);
+
+        // Write then rename so a crash never leaves a truncated vault
+        let temp_path = self.get_vault_file_path().with_extension("vault.tmp");
+        std::fs::write(&temp_path, vault).map_err(|e| AppError::FileSystemError {
+            path: temp_path.to_string_lossy().to_string(),
+            message: "Failed to write profile vault".to_string(),
+            details: e.to_string(),
+        })?;
+        std::fs::rename(&temp_path, self.get_vault_file_path())?;
+
+        for relative in &files {
+            let path = self.config_dir.join(relative);
+            let result = if relative == Path::new(LOG_FILE_NAME) {
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|file| file.set_len(0))
+            } else {
+                std::fs::remove_file(&path)
+            };
+            if let Err(e) = result {
+                warn!(target: "init", "Failed to remove {} after sealing: {}", path.display(), e);
+            }
+        }
+        Self::remove_empty_dirs(&self.config_dir);
+        if let Err(e) = std::fs::remove_file(self.get_open_marker_path()) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(target: "init", "Failed to clear the open profile marker: {}", e);
+            }
+        }
+
+        Ok(files.len())
+    }
+
+    fn remove_empty_dirs(dir: &Path) {
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::remove_empty_dirs(&path);
+                let _ = std::fs::remove_dir(&path);
+            }
+        }
+    }
+
+    /// Write a restored file with the mode it was sealed with, or owner-only
+    /// when the vault has none. The file is never readable by others before
+    /// its mode is set.
+    fn write_restored(path: &Path, contents: &[u8], mode: Option<u32>) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = mode.map_or(0o600, |mode| mode & 0o777);
+            file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        file.write_all(contents)
+    }
+
+    /// Decrypt the vault back into the config directory. The vault stays
+    /// until the next `seal` replaces it, so a crash never leaves the profile
+    /// without an encrypted copy. The sealed log is appended to anything
+    /// logged before the unlock. Returns the number of files restored.
+    pub fn open(&self, key: &ProfileKey) -> Result<usize, Box<dyn std::error::Error>> {
+        let vault_path = self.get_vault_file_path();
+        let vault = std::fs::read(&vault_path).map_err(|e| AppError::FileSystemError {
+            path: vault_path.to_string_lossy().to_string(),
+            message: "Failed to read profile vault".to_string(),
+            details: e.to_string(),
+        })?;
+        let sealed =
+            vault
+                .strip_prefix(VAULT_MAGIC.as_slice())
+                .ok_or_else(|| AppError::DataError {
+                    message: "Not a profile vault".to_string(),
+                    details: vault_path.to_string_lossy().to_string(),
+                })?;
+
+        let archive = key.decrypt(sealed)?;
+        let mut archive = ZipArchive::new(Cursor::new(archive))?;
+        let mut restored = 0;
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            let Some(relative) = entry.enclosed_name() else {
+                continue;
+            };
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+
+            let path = self.config_dir.join(&relative);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            if relative == Path::new(LOG_FILE_NAME) && path.exists() {
+                std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(&path)?
+                    .write_all(&contents)?;
+            } else {
+                Self::write_restored(&path, &contents, entry.unix_mode())?;
+            }
+            restored += 1;
+        }
+
+        std::fs::write(self.get_open_marker_path(), b"")?;
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Cheap Argon2 costs so tests stay fast
+    fn create_test_key(passphrase: &str) -> (ProfileKey, EncryptedProfileSettings) {
+        ProfileKey::create_with(passphrase, 64, 1, 1).expect("Should create key")
+    }
+
+    #[test]
+    fn test_unlock_rejects_wrong_passphrase() {
+        let (_key, settings) = create_test_key("correct horse");
+
+        assert!(ProfileKey::unlock("correct horse", &settings).is_ok());
+        assert!(matches!(
+            ProfileKey::unlock("wrong horse", &settings),
+            Err(AppError::AuthError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_create_rejects_short_passphrase() {
+        assert!(matches!(
+            ProfileKey::create_with("short", 64, 1, 1),
+            Err(AppError::ValidationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_seal_and_open_round_trip() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("server_connections.json"), r#"{"home":{}}"#).unwrap();
+        std::fs::write(dir.join("settings.json"), "{}").unwrap();
+        std::fs::create_dir_all(dir.join("problem_reports")).unwrap();
+        std::fs::write(dir.join("problem_reports/a.json"), "{}").unwrap();
+        std::fs::write(dir.join(LOG_FILE_NAME), "old line\n").unwrap();
+
+        let (key, _settings) = create_test_key("correct horse");
+        let vault = ProfileVault::new(dir.to_path_buf());
+        assert_eq!(vault.seal(&key).expect("Should seal"), 3);
+
+        assert!(vault.is_sealed());
+        assert!(!dir.join("server_connections.json").exists());
+        assert!(!dir.join("problem_reports").exists());
+        assert!(dir.join("settings.json").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap(),
+            ""
+        );
+        let raw = std::fs::read(dir.join(VAULT_FILE_NAME)).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("home"));
+
+        std::fs::write(dir.join(LOG_FILE_NAME), "new line\n").unwrap();
+        assert_eq!(vault.open(&key).expect("Should open"), 3);
+
+        assert!(!vault.is_sealed());
+        assert!(vault.is_open());
+        assert!(dir.join(VAULT_FILE_NAME).exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("server_connections.json")).unwrap(),
+            r#"{"home":{}}"#
+        );
+        assert!(dir.join("problem_reports/a.json").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap(),
+            "new line\nold line\n"
+        );
+    }
+
+    #[test]
+    fn test_unsealed_session_is_detected_and_resealed() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(dir.join("outbox.json"), "[]").unwrap();
+
+        let (key, _) = create_test_key("correct horse");
+        let vault = ProfileVault::new(dir.to_path_buf());
+        vault.seal(&key).unwrap();
+        vault.open(&key).unwrap();
+
+        // The app dies before sealing on exit
+        std::fs::write(dir.join("outbox.json"), "[1]").unwrap();
+        let restarted = ProfileVault::new(dir.to_path_buf());
+        assert!(restarted.is_open());
+        assert!(!restarted.is_sealed());
+
+        assert_eq!(restarted.seal(&key).unwrap(), 1);
+        assert!(!restarted.is_open());
+        assert!(restarted.is_sealed());
+        assert!(!dir.join("outbox.json").exists());
+        restarted.open(&key).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("outbox.json")).unwrap(),
+            "[1]"
+        );
+
+        restarted.remove().unwrap();
+        assert!(!restarted.is_open());
+        assert!(!dir.join(VAULT_FILE_NAME).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_restores_file_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let dir = temp_dir.path();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        std::fs::write(dir.join("secrets.key"), "key").unwrap();
+        std::fs::set_permissions(
+            dir.join("secrets.key"),
+            std::fs::Permissions::from_mode(0o600),
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("plugins/lint")).unwrap();
+        std::fs::write(dir.join("plugins/lint/run.sh"), "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(
+            dir.join("plugins/lint/run.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let (key, _) = create_test_key("correct horse");
+        let vault = ProfileVault::new(dir.to_path_buf());
+        vault.seal(&key).unwrap();
+        vault.open(&key).unwrap();
+
+        assert_eq!(mode(&dir.join("secrets.key")), 0o600);
+        assert_eq!(mode(&dir.join("plugins/lint/run.sh")), 0o755);
+    }
+
+    #[test]
+    fn test_open_with_wrong_key_keeps_vault() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(temp_dir.path().join("outbox.json"), "[]").unwrap();
+
+        let (key, _) = create_test_key("correct horse");
+        let (other_key, _) = create_test_key("another passphrase");
+        let vault = ProfileVault::new(temp_dir.path().to_path_buf());
+        vault.seal(&key).unwrap();
+
+        assert!(vault.open(&other_key).is_err());
+        assert!(vault.is_sealed());
+    }
+}
@@ -0,0 +1,357 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Scanning a project directory before it is attached to a session: what
+//! would be visible to the agent once `.gitignore` rules are applied, and
+//! whether the project is large enough to be worth a warning.
+
+use crate::error::AppError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Files at least this big are listed in the scan
+pub const LARGE_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Projects above this many included bytes get a size warning
+pub const LARGE_PROJECT_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Projects above this many included files get a size warning
+pub const MANY_FILES: usize = 50_000;
+
+/// The scan stops counting after this many entries
+const MAX_SCANNED_ENTRIES: usize = 500_000;
+
+/// Largest files reported individually
+const MAX_LARGE_FILES: usize = 20;
+
+/// Always excluded, whatever `.gitignore` says
+const ALWAYS_EXCLUDED: &[&str] = &[".git/"];
+
+/// One line of a `.gitignore`, scoped to the directory it was found in
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory the rule applies under, relative to the project root
+    base: PathBuf,
+    pattern: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Gitignore-style rules: the last matching rule decides
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Add the lines of a `.gitignore` found in `base`
+    pub fn add(&mut self, base: &Path, text: &str) {
+        for line in text.lines() {
+            if let Some(rule) = parse_rule(base, line) {
+                self.rules.push(rule);
+            }
+        }
+    }
+
+    /// Whether `path`, relative to the project root, is excluded
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&rule.base) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if rule.pattern.is_match(&relative) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_rule(base: &Path, line: &str) -> Option<IgnoreRule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    if line.trim_start_matches('/').is_empty() {
+        return None;
+    }
+
+    Some(IgnoreRule {
+        base: base.to_path_buf(),
+        pattern: glob_regex(line)?,
+        negated,
+        dir_only,
+    })
+}
+
+/// Compile a gitignore-style glob. A slash anywhere but the end anchors it
+/// to the root; otherwise it matches at any depth.
+pub fn glob_regex(glob: &str) -> Option<Regex> {
+    let anchored = glob.contains('/');
+    let glob = glob.trim_start_matches('/');
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+/// A file large enough to call out
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LargeFile {
+    /// Relative to the project root
+    pub path: String,
+    pub size: u64,
+}
+
+/// What attaching a directory would expose to the agent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectScan {
+    pub root: PathBuf,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    /// Files and directories skipped by ignore rules
+    pub excluded_count: usize,
+    /// Biggest included files over `LARGE_FILE_BYTES`, largest first
+    pub large_files: Vec<LargeFile>,
+    /// The scan hit its entry limit, so the counts are a lower bound
+    pub truncated: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Walk `root`, honoring `.gitignore` files at every level plus `extra`
+/// patterns. Symlinks are not followed.
+pub fn scan(root: &Path, extra: &[String]) -> Result<ProjectScan, AppError> {
+    let root = root
+        .canonicalize()
+        .ok()
+        .filter(|path| path.is_dir())
+        .ok_or_else(|| AppError::ValidationError {
+            field: "directory".to_string(),
+            message: format!("{} is not a directory", root.display()),
+        })?;
+
+    let mut rules = IgnoreRules::default();
+    rules.add(Path::new(""), &ALWAYS_EXCLUDED.join("\n"));
+    rules.add(Path::new(""), &extra.join("\n"));
+
+    let mut result = ProjectScan {
+        root: root.clone(),
+        file_count: 0,
+        total_bytes: 0,
+        excluded_count: 0,
+        large_files: Vec::new(),
+        truncated: false,
+        warnings: Vec::new(),
+    };
+    let mut scanned = 0usize;
+    visit(&root, Path::new(""), &rules, &mut result, &mut scanned)?;
+
+    result
+        .large_files
+        .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    result.large_files.truncate(MAX_LARGE_FILES);
+    result.warnings = warnings(&result);
+    Ok(result)
+}
+
+fn visit(
+    root: &Path,
+    relative: &Path,
+    inherited: &IgnoreRules,
+    result: &mut ProjectScan,
+    scanned: &mut usize,
+) -> Result<(), AppError> {
+    let directory = root.join(relative);
+    let mut rules = inherited.clone();
+    if let Ok(gitignore) = std::fs::read_to_string(directory.join(".gitignore")) {
+        rules.add(relative, &gitignore);
+    }
+
+    let entries = std::fs::read_dir(&directory).map_err(|e| AppError::FileSystemError {
+        path: directory.display().to_string(),
+        message: "Failed to read project directory".to_string(),
+        details: e.to_string(),
+    })?;
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if *scanned >= MAX_SCANNED_ENTRIES {
+            result.truncated = true;
+            return Ok(());
+        }
+        *scanned += 1;
+
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() {
+            continue;
+        }
+        let path = relative.join(entry.file_name());
+        if rules.is_ignored(&path, file_type.is_dir()) {
+            result.excluded_count += 1;
+            continue;
+        }
+
+        if file_type.is_dir() {
+            // Unreadable subdirectories are skipped rather than failing the scan
+            let _ = visit(root, &path, &rules, result, scanned);
+        } else if file_type.is_file() {
+            let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            result.file_count += 1;
+            result.total_bytes += size;
+            if size >= LARGE_FILE_BYTES {
+                result.large_files.push(LargeFile {
+                    path: path.to_string_lossy().replace('\\', "/"),
+                    size,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn warnings(scan: &ProjectScan) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if scan.truncated {
+        warnings.push(format!(
+            "Stopped after {} entries; the project is larger than shown",
+            MAX_SCANNED_ENTRIES
+        ));
+    }
+    if scan.file_count > MANY_FILES {
+        warnings.push(format!(
+            "{} files will be visible to the agent; consider excluding generated or vendored directories",
+            scan.file_count
+        ));
+    }
+    if scan.total_bytes > LARGE_PROJECT_BYTES {
+        warnings.push(format!(
+            "{} MiB of files will be visible to the agent",
+            scan.total_bytes / (1024 * 1024)
+        ));
+    }
+    if !scan.large_files.is_empty() {
+        warnings.push(format!(
+            "{} files are larger than {} MiB",
+            scan.large_files.len(),
+            LARGE_FILE_BYTES / (1024 * 1024)
+        ));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rules(text: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.add(Path::new(""), text);
+        rules
+    }
+
+    #[test]
+    fn test_gitignore_patterns() {
+        let rules = rules("# build output\ntarget/\n*.log\n!keep.log\n/dist\ndocs/**/*.tmp\n");
+        assert!(rules.is_ignored(Path::new("target"), true));
+        assert!(!rules.is_ignored(Path::new("target"), false));
+        assert!(rules.is_ignored(Path::new("crates/a/target"), true));
+        assert!(rules.is_ignored(Path::new("logs/app.log"), false));
+        assert!(!rules.is_ignored(Path::new("keep.log"), false));
+        assert!(rules.is_ignored(Path::new("dist"), true));
+        assert!(!rules.is_ignored(Path::new("web/dist"), true));
+        assert!(rules.is_ignored(Path::new("docs/a/b/c.tmp"), false));
+        assert!(!rules.is_ignored(Path::new("src/main.rs"), false));
+    }
+
+    #[test]
+    fn test_nested_gitignore_applies_below_its_directory() {
+        let mut rules = IgnoreRules::default();
+        rules.add(Path::new("web"), "/build\n");
+        assert!(rules.is_ignored(Path::new("web/build"), true));
+        assert!(!rules.is_ignored(Path::new("build"), true));
+    }
+
+    #[test]
+    fn test_scan_counts_included_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("node_modules/dep/index.js"), "x").unwrap();
+        std::fs::write(
+            root.join("src/big.bin"),
+            vec![0u8; LARGE_FILE_BYTES as usize],
+        )
+        .unwrap();
+        std::fs::write(root.join("notes.secret"), "x").unwrap();
+
+        let scan = scan(root, &["*.secret".to_string()]).unwrap();
+        assert_eq!(scan.file_count, 3); // .gitignore, main.rs, big.bin
+        assert_eq!(scan.excluded_count, 3); // .git, node_modules, notes.secret
+        assert_eq!(scan.large_files[0].path, "src/big.bin");
+        assert_eq!(scan.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_rejects_missing_directory() {
+        let temp = TempDir::new().unwrap();
+        assert!(scan(&temp.path().join("missing"), &[]).is_err());
+    }
+}
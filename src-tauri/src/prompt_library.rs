@@ -0,0 +1,517 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Reusable prompts with `{{variable}}` placeholders, filled in when sent.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Version written to prompt library exports
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// A saved prompt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Prompt {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Placeholders in `content`, in order of first use
+    #[serde(default)]
+    pub variables: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Fields the user edits when creating or updating a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptInput {
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// File written by `export`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PromptExport {
+    format_version: u32,
+    exported_at: DateTime<Utc>,
+    prompts: Vec<Prompt>,
+}
+
+/// What `import` changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PromptImportSummary {
+    pub added: usize,
+    /// Existing prompts replaced by a newer imported copy
+    pub updated: usize,
+    /// Imported prompts that were not newer than the library's copy
+    pub skipped: usize,
+}
+
+/// On-disk shape of `prompts.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PromptFile {
+    #[serde(default)]
+    prompts: Vec<Prompt>,
+}
+
+/// `{{ name }}` placeholders in `content`, in order of first use
+pub fn template_variables(content: &str) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    for_each_placeholder(content, |name| {
+        if !variables.iter().any(|variable| variable == name) {
+            variables.push(name.to_string());
+        }
+    });
+    variables
+}
+
+fn for_each_placeholder(content: &str, mut f: impl FnMut(&str)) {
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if is_variable_name(name) {
+            f(name);
+        }
+        rest = &after[end + 2..];
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Fill in `content`'s placeholders. Every variable needs a value; text
+/// between braces that is not a variable name is left alone.
+pub fn render(content: &str, values: &HashMap<String, String>) -> Result<String, AppError> {
+    let missing: Vec<_> = template_variables(content)
+        .into_iter()
+        .filter(|name| !values.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::ValidationError {
+            field: "variables".to_string(),
+            message: format!("Missing values for: {}", missing.join(", ")),
+        });
+    }
+
+    let mut rendered = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match values.get(after[..end].trim()) {
+            Some(value) if is_variable_name(after[..end].trim()) => rendered.push_str(value),
+            _ => rendered.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+impl PromptInput {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::ValidationError {
+                field: "name".to_string(),
+                message: "Prompt name cannot be empty".to_string(),
+            });
+        }
+        if self.content.trim().is_empty() {
+            return Err(AppError::ValidationError {
+                field: "content".to_string(),
+                message: "Prompt content cannot be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Persistent prompt library
+#[derive(Clone)]
+pub struct PromptLibrary {
+    config_dir: PathBuf,
+    data: Arc<Mutex<PromptFile>>,
+}
+
+impl PromptLibrary {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            data: Arc::new(Mutex::new(PromptFile::default())),
+        }
+    }
+
+    fn get_prompts_file_path(&self) -> PathBuf {
+        self.config_dir.join("prompts.json")
+    }
+
+    fn lock_data(&self) -> MutexGuard<'_, PromptFile> {
+        self.data.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Prompt library lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let prompts_file = self.get_prompts_file_path();
+        if !prompts_file.exists() {
+            return Ok(());
+        }
+
+        let prompts_json =
+            std::fs::read_to_string(&prompts_file).map_err(|e| AppError::FileSystemError {
+                path: prompts_file.to_string_lossy().to_string(),
+                message: "Failed to read prompt library".to_string(),
+                details: e.to_string(),
+            })?;
+        let loaded: PromptFile =
+            serde_json::from_str(&prompts_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse prompt library".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        *self.lock_data() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, data: &PromptFile) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let prompts_json = serde_json::to_string_pretty(data)?;
+        std::fs::write(self.get_prompts_file_path(), prompts_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_prompts_file_path().to_string_lossy().to_string(),
+                message: "Failed to write prompt library".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Apply `f` to the prompt list and persist the result
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut PromptFile) -> Result<T, AppError>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        let mut updated = data.clone();
+        let result = f(&mut updated)?;
+        self.save(&updated)?;
+        *data = updated;
+        Ok(result)
+    }
+
+    /// Prompts sorted by name, optionally only those with `tag`
+    pub fn list(&self, tag: Option<&str>) -> Vec<Prompt> {
+        let tag = tag.map(|tag| tag.trim().to_lowercase());
+        let mut prompts: Vec<_> = self
+            .lock_data()
+            .prompts
+            .iter()
+            .filter(|prompt| tag.as_ref().is_none_or(|tag| prompt.tags.contains(tag)))
+            .cloned()
+            .collect();
+        prompts.sort_by_key(|prompt| prompt.name.to_lowercase());
+        prompts
+    }
+
+    pub fn get(&self, prompt_id: &str) -> Option<Prompt> {
+        self.lock_data()
+            .prompts
+            .iter()
+            .find(|prompt| prompt.id == prompt_id)
+            .cloned()
+    }
+
+    pub fn create(&self, input: PromptInput) -> Result<Prompt, Box<dyn std::error::Error>> {
+        input.validate()?;
+        let now = Utc::now();
+        let prompt = Prompt {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: input.name.trim().to_string(),
+            variables: template_variables(&input.content),
+            content: input.content,
+            tags: normalize_tags(input.tags),
+            created_at: now,
+            updated_at: now,
+        };
+        self.modify(|data| {
+            ensure_unique_name(data, &prompt.name, None)?;
+            data.prompts.push(prompt.clone());
+            Ok(())
+        })?;
+        Ok(prompt)
+    }
+
+    pub fn update(
+        &self,
+        prompt_id: &str,
+        input: PromptInput,
+    ) -> Result<Prompt, Box<dyn std::error::Error>> {
+        input.validate()?;
+        self.modify(|data| {
+            ensure_unique_name(data, input.name.trim(), Some(prompt_id))?;
+            let prompt = find_mut(data, prompt_id)?;
+            prompt.name = input.name.trim().to_string();
+            prompt.variables = template_variables(&input.content);
+            prompt.content = input.content;
+            prompt.tags = normalize_tags(input.tags);
+            prompt.updated_at = Utc::now();
+            Ok(prompt.clone())
+        })
+    }
+
+    pub fn delete(&self, prompt_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.modify(|data| {
+            find_mut(data, prompt_id)?;
+            data.prompts.retain(|prompt| prompt.id != prompt_id);
+            Ok(())
+        })
+    }
+
+    /// Write every prompt to `path`; returns how many were written
+    pub fn export(&self, path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let export = PromptExport {
+            format_version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            prompts: self.list(None),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&export)?).map_err(|e| {
+            AppError::FileSystemError {
+                path: path.to_string_lossy().to_string(),
+                message: "Failed to write prompt export".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(export.prompts.len())
+    }
+
+    /// Merge an export into the library. Prompts are matched by id, and a
+    /// newer copy replaces the library's; a new prompt whose name is taken
+    /// gets an `(imported)` suffix.
+    pub fn import(&self, path: &Path) -> Result<PromptImportSummary, Box<dyn std::error::Error>> {
+        let export_json = std::fs::read_to_string(path).map_err(|e| AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: "Failed to read prompt export".to_string(),
+            details: e.to_string(),
+        })?;
+        let export: PromptExport =
+            serde_json::from_str(&export_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse prompt export".to_string(),
+                details: Some(e.to_string()),
+            })?;
+        if export.format_version > EXPORT_FORMAT_VERSION {
+            return Err(AppError::ValidationError {
+                field: "format_version".to_string(),
+                message: format!(
+                    "Prompt export version {} is newer than this app supports",
+                    export.format_version
+                ),
+            }
+            .into());
+        }
+
+        self.modify(|data| {
+            let mut summary = PromptImportSummary::default();
+            for mut prompt in export.prompts {
+                if prompt.name.trim().is_empty() || prompt.content.trim().is_empty() {
+                    summary.skipped += 1;
+                    continue;
+                }
+                prompt.variables = template_variables(&prompt.content);
+                prompt.tags = normalize_tags(prompt.tags);
+                match data.prompts.iter_mut().find(|p| p.id == prompt.id) {
+                    Some(existing) if prompt.updated_at > existing.updated_at => {
+                        *existing = prompt;
+                        summary.updated += 1;
+                    }
+                    Some(_) => summary.skipped += 1,
+                    None => {
+                        if ensure_unique_name(data, &prompt.name, None).is_err() {
+                            prompt.name = format!("{} (imported)", prompt.name);
+                        }
+                        data.prompts.push(prompt);
+                        summary.added += 1;
+                    }
+                }
+            }
+            Ok(summary)
+        })
+    }
+}
+
+fn ensure_unique_name(
+    data: &PromptFile,
+    name: &str,
+    except_id: Option<&str>,
+) -> Result<(), AppError> {
+    let taken = data.prompts.iter().any(|prompt| {
+        Some(prompt.id.as_str()) != except_id && prompt.name.eq_ignore_ascii_case(name)
+    });
+    if taken {
+        return Err(AppError::ValidationError {
+            field: "name".to_string(),
+            message: format!("A prompt named '{}' already exists", name),
+        });
+    }
+    Ok(())
+}
+
+fn find_mut<'a>(data: &'a mut PromptFile, prompt_id: &str) -> Result<&'a mut Prompt, AppError> {
+    data.prompts
+        .iter_mut()
+        .find(|prompt| prompt.id == prompt_id)
+        .ok_or_else(|| AppError::ValidationError {
+            field: "prompt_id".to_string(),
+            message: format!("Prompt not found: {}", prompt_id),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn library() -> (TempDir, PromptLibrary) {
+        let temp_dir = TempDir::new().unwrap();
+        let library = PromptLibrary::new(temp_dir.path().join("config"));
+        (temp_dir, library)
+    }
+
+    fn input(name: &str, content: &str) -> PromptInput {
+        PromptInput {
+            name: name.to_string(),
+            content: content.to_string(),
+            tags: vec![" Review ".to_string(), "review".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_render_variables() {
+        let content = "Review {{ file }} for {{focus}}; keep {{file}} intact. {{not a var}}";
+        assert_eq!(template_variables(content), vec!["file", "focus"]);
+
+        let mut values = HashMap::new();
+        values.insert("file".to_string(), "main.rs".to_string());
+        assert!(render(content, &values).is_err());
+
+        values.insert("focus".to_string(), "bugs".to_string());
+        assert_eq!(
+            render(content, &values).unwrap(),
+            "Review main.rs for bugs; keep main.rs intact. {{not a var}}"
+        );
+        assert_eq!(
+            render("unclosed {{file", &values).unwrap(),
+            "unclosed {{file"
+        );
+    }
+
+    #[test]
+    fn test_crud() {
+        let (_temp_dir, library) = library();
+        let prompt = library.create(input("Review", "Check {{file}}")).unwrap();
+        assert_eq!(prompt.tags, vec!["review"]);
+        assert_eq!(prompt.variables, vec!["file"]);
+        assert!(library.create(input("review", "Other")).is_err());
+        assert!(library.create(input(" ", "Other")).is_err());
+
+        let other = library.create(input("Explain", "Explain this")).unwrap();
+        let updated = library
+            .update(&other.id, input("Explain code", "Explain {{symbol}}"))
+            .unwrap();
+        assert_eq!(updated.variables, vec!["symbol"]);
+        assert!(library.update(&other.id, input("Review", "x")).is_err());
+
+        assert_eq!(library.list(Some("REVIEW")).len(), 2);
+        assert_eq!(library.list(Some("missing")).len(), 0);
+
+        library.delete(&prompt.id).unwrap();
+        assert!(library.get(&prompt.id).is_none());
+        assert!(library.delete(&prompt.id).is_err());
+
+        let reloaded = PromptLibrary::new(library.config_dir.clone());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.list(None), library.list(None));
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let (temp_dir, library) = library();
+        let prompt = library.create(input("Review", "Check {{file}}")).unwrap();
+        let export_path = temp_dir.path().join("prompts-export.json");
+        assert_eq!(library.export(&export_path).unwrap(), 1);
+
+        // Same library: nothing newer
+        let summary = library.import(&export_path).unwrap();
+        assert_eq!(summary.skipped, 1);
+
+        let (_other_dir, other) = self::library();
+        other.create(input("Review", "Something else")).unwrap();
+        let summary = other.import(&export_path).unwrap();
+        assert_eq!(summary.added, 1);
+        assert_eq!(other.get(&prompt.id).unwrap().name, "Review (imported)");
+
+        library
+            .update(&prompt.id, input("Review", "Check {{file}} twice"))
+            .unwrap();
+        library.export(&export_path).unwrap();
+        let summary = other.import(&export_path).unwrap();
+        assert_eq!(summary.updated, 1);
+        assert_eq!(
+            other.get(&prompt.id).unwrap().content,
+            "Check {{file}} twice"
+        );
+    }
+}
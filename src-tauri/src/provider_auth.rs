@@ -0,0 +1,232 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Which model providers the server holds credentials for, and handing it
+//! new ones, so a "no credentials" error can be fixed from the app.
+
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProviderAuthStatus {
+    pub provider_id: String,
+    pub name: String,
+    /// The server has credentials for the provider, from its auth store,
+    /// config or environment
+    pub configured: bool,
+    /// Environment variables the server reads the provider's key from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+}
+
+/// Credentials in the shape the server's auth endpoint stores them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProviderCredentials {
+    Api {
+        key: String,
+    },
+    Oauth {
+        refresh: String,
+        access: String,
+        /// Expiry of `access`, in milliseconds since the epoch
+        expires: i64,
+    },
+}
+
+impl ProviderCredentials {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let missing = match self {
+            ProviderCredentials::Api { key } => key.trim().is_empty().then_some("key"),
+            ProviderCredentials::Oauth {
+                refresh, access, ..
+            } => {
+                if refresh.trim().is_empty() {
+                    Some("refresh")
+                } else if access.trim().is_empty() {
+                    Some("access")
+                } else {
+                    None
+                }
+            }
+        };
+        match missing {
+            Some(field) => Err(AppError::ValidationError {
+                field: format!("credentials.{}", field),
+                message: "Credentials are incomplete".to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Provider ids are path segments of the auth endpoint
+pub fn validate_provider_id(provider_id: &str) -> Result<(), AppError> {
+    let valid = !provider_id.is_empty()
+        && provider_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError {
+            field: "provider_id".to_string(),
+            message: format!("Invalid provider id: {}", provider_id),
+        })
+    }
+}
+
+/// Status of every provider the server knows. `catalog` is the server's
+/// `provider` response (`{ all, connected }`); servers without it only
+/// report usable providers through `config/providers`, given as `usable`.
+pub fn statuses(catalog: Option<&Value>, usable: &Value) -> Vec<ProviderAuthStatus> {
+    let usable_ids: HashSet<&str> = usable
+        .get("providers")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|provider| provider.get("id").and_then(Value::as_str))
+        .collect();
+    let connected: HashSet<&str> = catalog
+        .and_then(|catalog| catalog.get("connected"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .chain(usable_ids.iter().copied())
+        .collect();
+
+    let mut statuses: Vec<ProviderAuthStatus> = match catalog
+        .and_then(|catalog| catalog.get("all"))
+        .and_then(Value::as_array)
+    {
+        Some(all) => all
+            .iter()
+            .filter_map(|provider| {
+                let id = provider.get("id").and_then(Value::as_str)?;
+                Some(ProviderAuthStatus {
+                    provider_id: id.to_string(),
+                    name: provider
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or(id)
+                        .to_string(),
+                    configured: connected.contains(id),
+                    env: provider
+                        .get("env")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect(),
+                })
+            })
+            .collect(),
+        None => connected
+            .iter()
+            .map(|id| ProviderAuthStatus {
+                provider_id: id.to_string(),
+                name: id.to_string(),
+                configured: true,
+                env: Vec::new(),
+            })
+            .collect(),
+    };
+    // Missing credentials first, since those need attention
+    statuses.sort_by(|a, b| {
+        (a.configured, a.name.to_lowercase()).cmp(&(b.configured, b.name.to_lowercase()))
+    });
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_statuses_from_catalog() {
+        let catalog = json!({
+            "all": [
+                { "id": "anthropic", "name": "Anthropic", "env": ["ANTHROPIC_API_KEY"] },
+                { "id": "openai", "name": "OpenAI", "env": ["OPENAI_API_KEY"] },
+                { "id": "ollama" }
+            ],
+            "connected": ["openai"]
+        });
+        let usable = json!({ "providers": [{ "id": "ollama", "models": {} }] });
+
+        let statuses = statuses(Some(&catalog), &usable);
+        let summary: Vec<_> = statuses
+            .iter()
+            .map(|status| (status.provider_id.as_str(), status.configured))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("anthropic", false), ("ollama", true), ("openai", true)]
+        );
+        assert_eq!(statuses[0].env, vec!["ANTHROPIC_API_KEY"]);
+        assert_eq!(statuses[1].name, "ollama");
+    }
+
+    #[test]
+    fn test_statuses_without_catalog() {
+        let usable = json!({ "providers": [{ "id": "anthropic" }] });
+        let statuses = statuses(None, &usable);
+        assert_eq!(statuses.len(), 1);
+        assert!(statuses[0].configured);
+    }
+
+    #[test]
+    fn test_credentials_serialize_for_the_server() {
+        let credentials = ProviderCredentials::Api {
+            key: "sk-test".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&credentials).unwrap(),
+            json!({ "type": "api", "key": "sk-test" })
+        );
+        assert!(credentials.validate().is_ok());
+        assert!(ProviderCredentials::Api {
+            key: " ".to_string()
+        }
+        .validate()
+        .is_err());
+        assert!(ProviderCredentials::Oauth {
+            refresh: "r".to_string(),
+            access: String::new(),
+            expires: 0,
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_provider_id() {
+        assert!(validate_provider_id("amazon-bedrock").is_ok());
+        assert!(validate_provider_id("").is_err());
+        assert!(validate_provider_id("../config").is_err());
+    }
+}
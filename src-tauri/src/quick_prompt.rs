@@ -0,0 +1,201 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#[cfg(desktop)]
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+#[cfg(desktop)]
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+#[cfg(desktop)]
+use tracing::warn;
+
+/// Label of the floating prompt window
+pub const QUICK_PROMPT_WINDOW: &str = "quick-prompt";
+
+/// Shortcut offered until the user picks another one
+pub const DEFAULT_SHORTCUT: &str = "CmdOrCtrl+Shift+Space";
+
+/// Title of the session quick prompts are sent to
+pub const SCRATCHPAD_TITLE: &str = "Scratchpad";
+
+const MODIFIERS: &[&str] = &[
+    "CmdOrCtrl",
+    "CommandOrControl",
+    "Cmd",
+    "Command",
+    "Super",
+    "Ctrl",
+    "Control",
+    "Alt",
+    "Option",
+    "Shift",
+];
+
+const NAMED_KEYS: &[&str] = &[
+    "Space",
+    "Enter",
+    "Tab",
+    "Backspace",
+    "Escape",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Insert",
+    "Delete",
+];
+
+/// Check an accelerator such as `ctrl+shift+space` and return it in its
+/// canonical form, `Ctrl+Shift+Space`. At least one modifier is required so
+/// the shortcut cannot swallow ordinary typing in other apps.
+pub fn normalize_shortcut(input: &str) -> Result<String, String> {
+    let parts: Vec<&str> = input.split('+').map(str::trim).collect();
+    let Some((key, modifiers)) = parts.split_last() else {
+        return Err("Shortcut cannot be empty".to_string());
+    };
+    if modifiers.is_empty() {
+        return Err("Shortcut needs at least one modifier, e.g. Ctrl+Shift+Space".to_string());
+    }
+
+    let mut normalized = Vec::with_capacity(parts.len());
+    for modifier in modifiers {
+        let canonical = MODIFIERS
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(modifier))
+            .ok_or_else(|| format!("Unknown modifier '{}'", modifier))?;
+        if normalized.contains(canonical) {
+            return Err(format!("Modifier '{}' is repeated", canonical));
+        }
+        normalized.push(*canonical);
+    }
+
+    let is_character = key.len() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric());
+    let is_function_key = key.is_ascii()
+        && (2..=3).contains(&key.len())
+        && key[..1].eq_ignore_ascii_case("f")
+        && key[1..].parse::<u8>().is_ok_and(|n| (1..=24).contains(&n));
+    let key = if let Some(named) = NAMED_KEYS.iter().find(|k| k.eq_ignore_ascii_case(key)) {
+        named.to_string()
+    } else if is_character || is_function_key {
+        key.to_ascii_uppercase()
+    } else {
+        return Err(format!("Unknown key '{}'", key));
+    };
+
+    Ok(format!("{}+{}", normalized.join("+"), key))
+}
+
+/// Show the floating prompt window, creating it on first use
+#[cfg(desktop)]
+pub fn open_window(app_handle: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app_handle.get_webview_window(QUICK_PROMPT_WINDOW) {
+        window.show()?;
+        return window.set_focus();
+    }
+
+    WebviewWindowBuilder::new(
+        app_handle,
+        QUICK_PROMPT_WINDOW,
+        WebviewUrl::App("quick-prompt".into()),
+    )
+    .title("Quick prompt")
+    .inner_size(560.0, 120.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build()?;
+    Ok(())
+}
+
+/// Register `shortcut` with the OS to open the prompt window, then release
+/// `previous`. When the new shortcut can't be registered, for instance
+/// because another app holds it, the previous one stays active.
+#[cfg(desktop)]
+pub fn register_shortcut(
+    app_handle: &AppHandle,
+    previous: Option<&str>,
+    shortcut: &str,
+) -> Result<(), String> {
+    if previous == Some(shortcut) {
+        return Ok(());
+    }
+    let global_shortcut = app_handle.global_shortcut();
+    global_shortcut
+        .on_shortcut(shortcut, |app_handle, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = open_window(app_handle) {
+                    warn!(target: "tray", "Failed to open quick prompt: {}", e);
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to register shortcut '{}': {}", shortcut, e))?;
+
+    if let Some(previous) = previous {
+        if let Err(e) = global_shortcut.unregister(previous) {
+            warn!(target: "tray", "Failed to release shortcut '{}': {}", previous, e);
+        }
+    }
+    Ok(())
+}
+
+/// Hide the floating prompt window if it is open
+#[cfg(desktop)]
+pub fn hide_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(QUICK_PROMPT_WINDOW) {
+        let _ = window.hide();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_shortcut() {
+        assert_eq!(
+            normalize_shortcut("ctrl+shift+space").unwrap(),
+            "Ctrl+Shift+Space"
+        );
+        assert_eq!(normalize_shortcut("cmdorctrl + k").unwrap(), "CmdOrCtrl+K");
+        assert_eq!(normalize_shortcut("Alt+f12").unwrap(), "Alt+F12");
+        assert_eq!(
+            normalize_shortcut(DEFAULT_SHORTCUT).unwrap(),
+            DEFAULT_SHORTCUT
+        );
+    }
+
+    #[test]
+    fn test_normalize_shortcut_rejects_invalid() {
+        assert!(normalize_shortcut("").is_err());
+        assert!(normalize_shortcut("Space").is_err());
+        assert!(normalize_shortcut("Ctrl+Ctrl+K").is_err());
+        assert!(normalize_shortcut("Hyper+K").is_err());
+        assert!(normalize_shortcut("Ctrl+F25").is_err());
+        assert!(normalize_shortcut("Ctrl+").is_err());
+    }
+}
@@ -0,0 +1,389 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const JOURNAL_FILE_NAME: &str = "recovery_journal.jsonl";
+
+/// One line of the recovery journal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalRecord {
+    /// A prompt is about to be sent
+    PendingSend {
+        id: String,
+        session_id: String,
+        content: String,
+        created_at: DateTime<Utc>,
+    },
+    /// The prompt reached the server (or failed in front of the user)
+    SendFinished { id: String },
+    /// A streamed delta for an assistant message
+    StreamChunk {
+        session_id: String,
+        message_id: String,
+        content: String,
+        index: usize,
+    },
+    /// The streamed message was completed, errored or ended
+    StreamFinished {
+        session_id: String,
+        message_id: String,
+    },
+}
+
+/// A prompt that was queued but never confirmed as sent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingPrompt {
+    pub id: String,
+    pub session_id: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Assistant content streamed before the app stopped
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PartialMessage {
+    pub session_id: String,
+    pub message_id: String,
+    pub content: String,
+}
+
+/// Work recovered from the journal on startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RecoveredWork {
+    pub pending_prompts: Vec<PendingPrompt>,
+    pub partial_messages: Vec<PartialMessage>,
+}
+
+impl RecoveredWork {
+    pub fn is_empty(&self) -> bool {
+        self.pending_prompts.is_empty() && self.partial_messages.is_empty()
+    }
+}
+
+/// Append-only journal of in-flight sends and stream chunks, replayed on the
+/// next launch to restore work lost to a crash
+#[derive(Clone)]
+pub struct RecoveryJournal {
+    config_dir: PathBuf,
+    write_lock: Arc<Mutex<()>>,
+    recovered: Arc<Mutex<RecoveredWork>>,
+}
+
+impl RecoveryJournal {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            write_lock: Arc::new(Mutex::new(())),
+            recovered: Arc::new(Mutex::new(RecoveredWork::default())),
+        }
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.config_dir.join(JOURNAL_FILE_NAME)
+    }
+
+    /// Append a record; failures are returned but never block the caller's work
+    pub fn record(&self, record: &JournalRecord) -> io::Result<()> {
+        let line = serde_json::to_string(record)?;
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        fs::create_dir_all(&self.config_dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.journal_path())?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Replay the journal into unfinished prompts and partial messages
+    pub fn replay(&self) -> io::Result<RecoveredWork> {
+        let path = self.journal_path();
+        if !path.exists() {
+            return Ok(RecoveredWork::default());
+        }
+
+        let mut prompts: BTreeMap<String, PendingPrompt> = BTreeMap::new();
+        let mut chunks: BTreeMap<(String, String), Vec<(usize, String)>> = BTreeMap::new();
+
+        for line in BufReader::new(fs::File::open(&path)?).lines() {
+            // A crash can leave a truncated last line; skip anything unreadable
+            let Ok(record) = serde_json::from_str::<JournalRecord>(&line?) else {
+                continue;
+            };
+            match record {
+                JournalRecord::PendingSend {
+                    id,
+                    session_id,
+                    content,
+                    created_at,
+                } => {
+                    prompts.insert(
+                        id.clone(),
+                        PendingPrompt {
+                            id,
+                            session_id,
+                            content,
+                            created_at,
+                        },
+                    );
+                }
+                JournalRecord::SendFinished { id } => {
+                    prompts.remove(&id);
+                }
+                JournalRecord::StreamChunk {
+                    session_id,
+                    message_id,
+                    content,
+                    index,
+                } => {
+                    chunks
+                        .entry((session_id, message_id))
+                        .or_default()
+                        .push((index, content));
+                }
+                JournalRecord::StreamFinished {
+                    session_id,
+                    message_id,
+                } => {
+                    chunks.remove(&(session_id, message_id));
+                }
+            }
+        }
+
+        let mut pending_prompts: Vec<PendingPrompt> = prompts.into_values().collect();
+        pending_prompts.sort_by_key(|prompt| prompt.created_at);
+
+        let partial_messages = chunks
+            .into_iter()
+            .map(|((session_id, message_id), mut parts)| {
+                parts.sort_by_key(|(index, _)| *index);
+                PartialMessage {
+                    session_id,
+                    message_id,
+                    content: parts.into_iter().map(|(_, content)| content).collect(),
+                }
+            })
+            .collect();
+
+        Ok(RecoveredWork {
+            pending_prompts,
+            partial_messages,
+        })
+    }
+
+    /// Replay the journal at startup, keep the result for the frontend and
+    /// rewrite the journal so it only holds what is still unresolved
+    pub fn recover(&self) -> io::Result<RecoveredWork> {
+        let recovered = self.replay()?;
+        self.rewrite(&recovered)?;
+        *self.lock_recovered() = recovered.clone();
+        Ok(recovered)
+    }
+
+    /// Work found at startup that the user has not resolved yet
+    pub fn recovered(&self) -> RecoveredWork {
+        self.lock_recovered().clone()
+    }
+
+    /// Remove a recovered prompt (resent or discarded)
+    pub fn resolve_prompt(&self, id: &str) -> io::Result<Option<PendingPrompt>> {
+        let prompt = {
+            let mut recovered = self.lock_recovered();
+            let position = recovered.pending_prompts.iter().position(|p| p.id == id);
+            position.map(|index| recovered.pending_prompts.remove(index))
+        };
+        if prompt.is_some() {
+            self.record(&JournalRecord::SendFinished { id: id.to_string() })?;
+        }
+        Ok(prompt)
+    }
+
+    /// Forget all recovered work
+    pub fn clear_recovered(&self) -> io::Result<()> {
+        let recovered = std::mem::take(&mut *self.lock_recovered());
+        for prompt in recovered.pending_prompts {
+            self.record(&JournalRecord::SendFinished { id: prompt.id })?;
+        }
+        for message in recovered.partial_messages {
+            self.record(&JournalRecord::StreamFinished {
+                session_id: message.session_id,
+                message_id: message.message_id,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn rewrite(&self, recovered: &RecoveredWork) -> io::Result<()> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut lines = Vec::new();
+        for prompt in &recovered.pending_prompts {
+            lines.push(serde_json::to_string(&JournalRecord::PendingSend {
+                id: prompt.id.clone(),
+                session_id: prompt.session_id.clone(),
+                content: prompt.content.clone(),
+                created_at: prompt.created_at,
+            })?);
+        }
+        for message in &recovered.partial_messages {
+            lines.push(serde_json::to_string(&JournalRecord::StreamChunk {
+                session_id: message.session_id.clone(),
+                message_id: message.message_id.clone(),
+                content: message.content.clone(),
+                index: 0,
+            })?);
+        }
+
+        if lines.is_empty() {
+            let path = self.journal_path();
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.config_dir)?;
+        let temp_path = self.journal_path().with_extension("jsonl.tmp");
+        fs::write(&temp_path, lines.join("\n") + "\n")?;
+        fs::rename(temp_path, self.journal_path())
+    }
+
+    fn lock_recovered(&self) -> std::sync::MutexGuard<'_, RecoveredWork> {
+        self.recovered
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_journal() -> (RecoveryJournal, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let journal = RecoveryJournal::new(temp_dir.path().to_path_buf());
+        (journal, temp_dir)
+    }
+
+    fn pending(id: &str) -> JournalRecord {
+        JournalRecord::PendingSend {
+            id: id.to_string(),
+            session_id: "session-1".to_string(),
+            content: format!("prompt {}", id),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn chunk(message_id: &str, index: usize, content: &str) -> JournalRecord {
+        JournalRecord::StreamChunk {
+            session_id: "session-1".to_string(),
+            message_id: message_id.to_string(),
+            content: content.to_string(),
+            index,
+        }
+    }
+
+    #[test]
+    fn test_empty_journal_recovers_nothing() {
+        let (journal, _temp) = create_test_journal();
+        assert!(journal.recover().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replay_keeps_only_unfinished_work() {
+        let (journal, _temp) = create_test_journal();
+        journal.record(&pending("a")).unwrap();
+        journal.record(&pending("b")).unwrap();
+        journal
+            .record(&JournalRecord::SendFinished {
+                id: "a".to_string(),
+            })
+            .unwrap();
+        journal.record(&chunk("m1", 1, " world")).unwrap();
+        journal.record(&chunk("m1", 0, "Hello")).unwrap();
+        journal.record(&chunk("m2", 0, "Done")).unwrap();
+        journal
+            .record(&JournalRecord::StreamFinished {
+                session_id: "session-1".to_string(),
+                message_id: "m2".to_string(),
+            })
+            .unwrap();
+
+        let recovered = journal.replay().unwrap();
+        assert_eq!(recovered.pending_prompts.len(), 1);
+        assert_eq!(recovered.pending_prompts[0].id, "b");
+        assert_eq!(recovered.partial_messages.len(), 1);
+        assert_eq!(recovered.partial_messages[0].content, "Hello world");
+    }
+
+    #[test]
+    fn test_recover_compacts_and_survives_truncated_line() {
+        let (journal, temp) = create_test_journal();
+        journal.record(&pending("a")).unwrap();
+        journal.record(&chunk("m1", 0, "Partial")).unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(temp.path().join(JOURNAL_FILE_NAME))
+            .unwrap();
+        write!(file, "{{\"kind\":\"stream_ch").unwrap();
+
+        let recovered = journal.recover().unwrap();
+        assert_eq!(recovered.pending_prompts.len(), 1);
+        assert_eq!(recovered.partial_messages[0].content, "Partial");
+
+        // The rewritten journal replays to the same state
+        assert_eq!(journal.replay().unwrap(), recovered);
+    }
+
+    #[test]
+    fn test_resolve_and_clear_recovered() {
+        let (journal, _temp) = create_test_journal();
+        journal.record(&pending("a")).unwrap();
+        journal.record(&pending("b")).unwrap();
+        journal.record(&chunk("m1", 0, "Partial")).unwrap();
+        journal.recover().unwrap();
+
+        let prompt = journal.resolve_prompt("a").unwrap();
+        assert_eq!(prompt.unwrap().content, "prompt a");
+        assert!(journal.resolve_prompt("a").unwrap().is_none());
+        assert_eq!(journal.replay().unwrap().pending_prompts.len(), 1);
+
+        journal.clear_recovered().unwrap();
+        assert!(journal.recovered().is_empty());
+        assert!(journal.replay().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The app's own resource use, for telling a leak apart from a busy
+//! machine on long-running installs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+static OPEN_STREAMS: AtomicUsize = AtomicUsize::new(0);
+static BACKGROUND_TASKS: AtomicUsize = AtomicUsize::new(0);
+static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
+/// Kinds of long-lived work that are counted while they run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Activity {
+    /// A task forwarding a response stream to the UI
+    Stream,
+    /// A background loop such as outbox delivery or tray updates
+    BackgroundTask,
+}
+
+impl Activity {
+    fn counter(self) -> &'static AtomicUsize {
+        match self {
+            Activity::Stream => &OPEN_STREAMS,
+            Activity::BackgroundTask => &BACKGROUND_TASKS,
+        }
+    }
+}
+
+/// Counts an activity as running until dropped
+pub struct ActivityGuard {
+    activity: Activity,
+    started: Instant,
+}
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        self.activity.counter().fetch_sub(1, Ordering::Relaxed);
+        if self.activity == Activity::Stream {
+            crate::metrics::record_stream(self.started.elapsed());
+        }
+    }
+}
+
+pub fn track(activity: Activity) -> ActivityGuard {
+    activity.counter().fetch_add(1, Ordering::Relaxed);
+    ActivityGuard {
+        activity,
+        started: Instant::now(),
+    }
+}
+
+/// Run `future`, counting it as `activity` until it finishes or is dropped
+pub async fn tracked<F: Future>(activity: Activity, future: F) -> F::Output {
+    let _guard = track(activity);
+    future.await
+}
+
+/// Response streams currently being forwarded to the UI
+pub fn open_streams() -> usize {
+    OPEN_STREAMS.load(Ordering::Relaxed)
+}
+
+/// Start the uptime clock; later calls keep the first instant
+pub fn mark_started() {
+    STARTED_AT.get_or_init(Instant::now);
+}
+
+/// Memory of this process in bytes, from the OS
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ProcessMemory {
+    pub rss_bytes: u64,
+    pub virtual_bytes: u64,
+}
+
+pub fn process_memory() -> Option<ProcessMemory> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = sysinfo::System::new();
+    system.refresh_process(pid);
+    let process = system.process(pid)?;
+    Some(ProcessMemory {
+        rss_bytes: process.memory(),
+        virtual_bytes: process.virtual_memory(),
+    })
+}
+
+/// Snapshot returned by `get_app_resource_usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// `None` where the platform does not report it
+    pub memory: Option<ProcessMemory>,
+    pub uptime_secs: u64,
+    pub open_streams: usize,
+    pub background_tasks: usize,
+    /// Messages queued in each broadcast channel that some receiver has
+    /// not read yet; a steadily growing number means a stalled listener
+    pub channel_backlog: BTreeMap<String, usize>,
+    /// Size of `chat_sessions.json`
+    pub session_cache_bytes: u64,
+    pub recent_sessions: usize,
+    pub session_windows: usize,
+}
+
+impl ResourceUsage {
+    /// Fill in the process-wide counters; the rest comes from app state
+    pub fn collect() -> Self {
+        Self {
+            memory: process_memory(),
+            uptime_secs: STARTED_AT
+                .get()
+                .map_or(0, |started| started.elapsed().as_secs()),
+            open_streams: open_streams(),
+            background_tasks: BACKGROUND_TASKS.load(Ordering::Relaxed),
+            channel_backlog: BTreeMap::new(),
+            session_cache_bytes: 0,
+            recent_sessions: 0,
+            session_windows: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tracked_counts_while_running() {
+        let before = OPEN_STREAMS.load(Ordering::Relaxed);
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(tracked(Activity::Stream, async move {
+            let _ = started_tx.send(());
+            let _ = finish_rx.await;
+        }));
+
+        started_rx.await.unwrap();
+        assert!(OPEN_STREAMS.load(Ordering::Relaxed) > before);
+        finish_tx.send(()).unwrap();
+        task.await.unwrap();
+        assert_eq!(OPEN_STREAMS.load(Ordering::Relaxed), before);
+    }
+
+    #[test]
+    fn test_guard_releases_on_drop() {
+        let before = BACKGROUND_TASKS.load(Ordering::Relaxed);
+        let guard = track(Activity::BackgroundTask);
+        assert!(BACKGROUND_TASKS.load(Ordering::Relaxed) > before);
+        drop(guard);
+        assert_eq!(BACKGROUND_TASKS.load(Ordering::Relaxed), before);
+    }
+
+    #[test]
+    fn test_reports_own_memory() {
+        let memory = process_memory().expect("current process is visible");
+        assert!(memory.rss_bytes > 0);
+    }
+}
@@ -0,0 +1,913 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::audit_log::{self, AuditAction};
+use crate::error::AppError;
+use crate::profile_vault::ProfileKey;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use tracing::warn;
+use zeroize::Zeroizing;
+
+/// Encrypted fallback store used when no OS keychain is available
+pub const SECRETS_FILE_NAME: &str = "secrets.vault";
+
+/// Random machine key for `secrets.vault`. It sits next to the vault, so
+/// the encryption only keeps values out of plain sight (backups, grep,
+/// casual browsing); anyone who can read the config directory can read
+/// both files. Use the keychain, or an encrypted profile, for real
+/// protection at rest.
+const SECRETS_KEY_FILE_NAME: &str = "secrets.key";
+
+/// Environment variables with this prefix override stored secrets
+pub const SECRET_ENV_PREFIX: &str = "OPENCODE_NEXUS_SECRET_";
+
+/// Service name secrets are filed under in the OS keychain
+const KEYCHAIN_SERVICE: &str = "opencode-nexus";
+
+const KEY_LEN: usize = 32;
+
+/// Where a secret value lives
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    Keychain,
+    EncryptedFile,
+    Environment,
+}
+
+/// Secret name for a saved connection's API key
+pub fn connection_api_key(connection_id: &str) -> String {
+    format!("connection/{}/api_key", connection_id)
+}
+
+/// Connection whose API key `name` is, if it is one
+pub fn connection_of_api_key(name: &str) -> Option<&str> {
+    name.strip_prefix("connection/")?.strip_suffix("/api_key")
+}
+
+/// Methods the connect page offered while connection ids were
+/// `{method}-{hostname}`
+const LEGACY_CONNECTION_METHODS: &[&str] = &["localhost", "tunnel", "proxy"];
+
+/// Ids a connection to `hostname` had before ids became `host:port`
+pub fn legacy_connection_ids(hostname: &str) -> Vec<String> {
+    LEGACY_CONNECTION_METHODS
+        .iter()
+        .map(|method| format!("{}-{}", method, hostname))
+        .collect()
+}
+
+/// Secret name for the password of a proxy, `scope` being a connection id
+/// or `proxy::GLOBAL_SCOPE`
+pub fn proxy_password(scope: &str) -> String {
+    format!("proxy/{}/password", scope)
+}
+
+/// Proxy scope whose password `name` is, if it is one
+pub fn proxy_of_password(name: &str) -> Option<&str> {
+    name.strip_prefix("proxy/")?.strip_suffix("/password")
+}
+
+/// Secret name for an AI provider credential
+pub fn provider_api_key(provider_id: &str) -> String {
+    format!("provider/{}/api_key", provider_id)
+}
+
+/// Grace window used when rotation does not specify one
+pub const DEFAULT_ROTATION_GRACE_MINUTES: i64 = 30;
+
+/// Secret name for a signing key
+pub fn signing_key(key_id: &str) -> String {
+    format!("signing/{}/key", key_id)
+}
+
+/// Name the replaced value is stored under during a rotation grace window
+fn previous_name(name: &str) -> String {
+    format!("{}#previous", name)
+}
+
+/// Random 256-bit key, hex encoded
+pub fn generate_secret() -> String {
+    let mut bytes = Zeroizing::new([0u8; KEY_LEN]);
+    rand::rngs::OsRng.fill_bytes(bytes.as_mut());
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Environment variable that overrides a secret, e.g.
+/// `connection/home/api_key` -> `OPENCODE_NEXUS_SECRET_CONNECTION_HOME_API_KEY`
+pub fn env_var_name(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", SECRET_ENV_PREFIX, suffix)
+}
+
+/// Overview of the secret store for the settings UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretStoreStatus {
+    /// Backend new secrets are written to
+    pub backend: SecretBackend,
+    pub secrets: Vec<SecretInfo>,
+}
+
+/// Secret metadata safe to show in the UI; never carries the value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SecretInfo {
+    pub name: String,
+    pub backend: SecretBackend,
+    pub updated_at: DateTime<Utc>,
+    /// End of the grace window for the value this one replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_valid_until: Option<DateTime<Utc>>,
+}
+
+/// Outcome of rotating a secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretRotation {
+    pub name: String,
+    pub backend: SecretBackend,
+    /// When the replaced value stops being accepted, if there was one
+    pub previous_valid_until: Option<DateTime<Utc>>,
+    /// The new key when it was generated, so it can be configured on the
+    /// server; `None` when the caller supplied it
+    pub generated_value: Option<String>,
+}
+
+/// A replaced value kept during its rotation grace window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PreviousSecret {
+    backend: SecretBackend,
+    valid_until: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretsFile {
+    /// Index of every stored secret, including those held in the keychain
+    entries: BTreeMap<String, SecretInfo>,
+    /// Values for entries whose backend is the encrypted file
+    values: BTreeMap<String, String>,
+    /// Rotated-out values still inside their grace window
+    #[serde(default)]
+    previous: BTreeMap<String, PreviousSecret>,
+}
+
+/// OS credential store reached through the platform command-line tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keychain {
+    /// macOS Keychain via `security`
+    MacOs,
+    /// libsecret / Secret Service via `secret-tool`
+    SecretService,
+}
+
+/// Keychain found on this machine, probed once per process
+static KEYCHAIN: OnceLock<Option<Keychain>> = OnceLock::new();
+
+impl Keychain {
+    fn detect() -> Option<Self> {
+        *KEYCHAIN.get_or_init(Self::probe)
+    }
+
+    fn probe() -> Option<Self> {
+        let available = |program: &str| {
+            Command::new(program)
+                .arg("--help")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok()
+        };
+        if cfg!(target_os = "macos") && available("security") {
+            Some(Keychain::MacOs)
+        } else if cfg!(target_os = "linux") && available("secret-tool") {
+            Some(Keychain::SecretService)
+        } else {
+            None
+        }
+    }
+
+    fn error(action: &str, details: impl std::fmt::Display) -> AppError {
+        AppError::IoError {
+            message: format!("Keychain {} failed", action),
+            details: Some(details.to_string()),
+        }
+    }
+
+    /// Quote an argument for `security -i`, which reads commands from stdin
+    /// so the secret never appears in the process list
+    fn quote(value: &str) -> String {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    fn run_with_stdin(mut command: Command, input: &str, action: &str) -> Result<(), AppError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Self::error(action, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| Self::error(action, e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| Self::error(action, e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(Self::error(
+                action,
+                String::from_utf8_lossy(&output.stderr).trim(),
+            ))
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), AppError> {
+        match self {
+            Keychain::MacOs => {
+                let mut command = Command::new("security");
+                command.arg("-i");
+                let input = Zeroizing::new(format!(
+                    "add-generic-password -U -s {} -a {} -w {}\n",
+                    Self::quote(KEYCHAIN_SERVICE),
+                    Self::quote(name),
+                    Self::quote(value)
+                ));
+                Self::run_with_stdin(command, &input, "store")
+            }
+            Keychain::SecretService => {
+                let mut command = Command::new("secret-tool");
+                command.args([
+                    "store",
+                    &format!("--label=OpenCode Nexus: {}", name),
+                    "service",
+                    KEYCHAIN_SERVICE,
+                    "account",
+                    name,
+                ]);
+                Self::run_with_stdin(command, value, "store")
+            }
+        }
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>, AppError> {
+        let output = match self {
+            Keychain::MacOs => Command::new("security")
+                .args(["find-generic-password", "-s", KEYCHAIN_SERVICE])
+                .args(["-a", name, "-w"])
+                .output(),
+            Keychain::SecretService => Command::new("secret-tool")
+                .args(["lookup", "service", KEYCHAIN_SERVICE, "account", name])
+                .output(),
+        }
+        .map_err(|e| Self::error("lookup", e))?;
+
+        // Both tools exit non-zero when the item does not exist
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let value = String::from_utf8(output.stdout).map_err(|e| Self::error("lookup", e))?;
+        let value = value.strip_suffix('\n').unwrap_or(&value).to_string();
+        Ok(Some(value))
+    }
+
+    fn delete(&self, name: &str) -> Result<(), AppError> {
+        let status = match self {
+            Keychain::MacOs => Command::new("security")
+                .args([
+                    "delete-generic-password",
+                    "-s",
+                    KEYCHAIN_SERVICE,
+                    "-a",
+                    name,
+                ])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status(),
+            Keychain::SecretService => Command::new("secret-tool")
+                .args(["clear", "service", KEYCHAIN_SERVICE, "account", name])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status(),
+        };
+        status.map(|_| ()).map_err(|e| Self::error("delete", e))
+    }
+}
+
+/// Single entry point for credentials. Lookups check environment variables,
+/// then the OS keychain, then the encrypted file; writes go to the keychain
+/// when one is available and fall back to the encrypted file otherwise.
+/// The file fallback is obfuscation rather than encryption, see
+/// `SECRETS_KEY_FILE_NAME`. Keychain calls wait on a child process, so
+/// async callers should run the store on the blocking pool.
+pub struct SecretStore {
+    config_dir: PathBuf,
+    keychain: Option<Keychain>,
+}
+
+impl SecretStore {
+    pub fn new(config_dir: &Path) -> Self {
+        Self {
+            config_dir: config_dir.to_path_buf(),
+            keychain: Keychain::detect(),
+        }
+    }
+
+    fn get_secrets_file_path(&self) -> PathBuf {
+        self.config_dir.join(SECRETS_FILE_NAME)
+    }
+
+    fn get_key_file_path(&self) -> PathBuf {
+        self.config_dir.join(SECRETS_KEY_FILE_NAME)
+    }
+
+    /// Backend new secrets are written to
+    pub fn preferred_backend(&self) -> SecretBackend {
+        if self.keychain.is_some() {
+            SecretBackend::Keychain
+        } else {
+            SecretBackend::EncryptedFile
+        }
+    }
+
+    fn fs_error(path: &Path, message: &str, e: impl std::fmt::Display) -> AppError {
+        AppError::FileSystemError {
+            path: path.to_string_lossy().to_string(),
+            message: message.to_string(),
+            details: e.to_string(),
+        }
+    }
+
+    /// Load the machine key, creating it on first use
+    fn machine_key(&self) -> Result<ProfileKey, AppError> {
+        let path = self.get_key_file_path();
+        if path.exists() {
+            let bytes = Zeroizing::new(
+                std::fs::read(&path)
+                    .map_err(|e| Self::fs_error(&path, "Failed to read secrets key", e))?,
+            );
+            return ProfileKey::from_bytes(&bytes);
+        }
+
+        let mut bytes = Zeroizing::new([0u8; KEY_LEN]);
+        rand::rngs::OsRng.fill_bytes(bytes.as_mut());
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| {
+            Self::fs_error(&self.config_dir, "Failed to create config directory", e)
+        })?;
+        Self::write_private(&path, bytes.as_ref())
+            .map_err(|e| Self::fs_error(&path, "Failed to write secrets key", e))?;
+        ProfileKey::from_bytes(bytes.as_ref())
+    }
+
+    /// Write a file readable only by the current user
+    fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(path)?.write_all(contents)
+    }
+
+    fn load_file(&self) -> Result<SecretsFile, AppError> {
+        let path = self.get_secrets_file_path();
+        if !path.exists() {
+            return Ok(SecretsFile::default());
+        }
+        let sealed =
+            std::fs::read(&path).map_err(|e| Self::fs_error(&path, "Failed to read secrets", e))?;
+        let plaintext = Zeroizing::new(self.machine_key()?.decrypt(&sealed)?);
+        serde_json::from_slice(&plaintext).map_err(|e| AppError::ParseError {
+            message: "Failed to parse secrets file".to_string(),
+            details: Some(e.to_string()),
+        })
+    }
+
+    fn save_file(&self, file: &SecretsFile) -> Result<(), AppError> {
+        let plaintext =
+            Zeroizing::new(serde_json::to_vec(file).map_err(|e| AppError::ParseError {
+                message: "Failed to serialize secrets".to_string(),
+                details: Some(e.to_string()),
+            })?);
+        let sealed = self.machine_key()?.encrypt(&plaintext)?;
+
+        let path = self.get_secrets_file_path();
+        let temp_path = path.with_extension("vault.tmp");
+        Self::write_private(&temp_path, &sealed)
+            .and_then(|_| std::fs::rename(&temp_path, &path))
+            .map_err(|e| Self::fs_error(&path, "Failed to write secrets", e))
+    }
+
+    /// Write a value to the keychain, falling back to the encrypted file
+    fn put_value(&self, file: &mut SecretsFile, name: &str, value: &str) -> SecretBackend {
+        match self.keychain.map(|keychain| keychain.set(name, value)) {
+            Some(Ok(())) => {
+                file.values.remove(name);
+                SecretBackend::Keychain
+            }
+            Some(Err(e)) => {
+                warn!(target: "secrets", secret = %name, "Keychain unavailable, using encrypted file: {}", e);
+                file.values.insert(name.to_string(), value.to_string());
+                SecretBackend::EncryptedFile
+            }
+            None => {
+                file.values.insert(name.to_string(), value.to_string());
+                SecretBackend::EncryptedFile
+            }
+        }
+    }
+
+    fn fetch_value(
+        &self,
+        file: &SecretsFile,
+        name: &str,
+        backend: SecretBackend,
+    ) -> Result<Option<String>, AppError> {
+        match backend {
+            SecretBackend::Keychain => match self.keychain {
+                Some(keychain) => keychain.get(name),
+                None => Ok(None),
+            },
+            SecretBackend::EncryptedFile => Ok(file.values.get(name).cloned()),
+            SecretBackend::Environment => Ok(None),
+        }
+    }
+
+    fn remove_value(&self, file: &mut SecretsFile, name: &str) {
+        file.values.remove(name);
+        if let Some(keychain) = self.keychain {
+            if let Err(e) = keychain.delete(name) {
+                warn!(target: "secrets", secret = %name, "Failed to remove keychain item: {}", e);
+            }
+        }
+    }
+
+    fn remove_previous(&self, file: &mut SecretsFile, name: &str) -> bool {
+        let existed = file.previous.remove(name).is_some();
+        if existed {
+            self.remove_value(file, &previous_name(name));
+        }
+        existed
+    }
+
+    /// Store a secret, returning the backend that now holds it
+    pub fn set(&self, name: &str, value: &str) -> Result<SecretBackend, AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::ValidationError {
+                field: "name".to_string(),
+                message: "Secret name cannot be empty".to_string(),
+            });
+        }
+
+        let mut file = self.load_file()?;
+        let backend = self.put_value(&mut file, name, value);
+        file.entries.insert(
+            name.to_string(),
+            SecretInfo {
+                name: name.to_string(),
+                backend,
+                updated_at: Utc::now(),
+                previous_valid_until: None,
+            },
+        );
+        self.save_file(&file)?;
+        audit_log::record(
+            AuditAction::CredentialStored,
+            name,
+            Some(format!("{:?}", backend)),
+        );
+        Ok(backend)
+    }
+
+    /// Look up a secret and the backend it came from
+    pub fn get_with_backend(
+        &self,
+        name: &str,
+    ) -> Result<Option<(String, SecretBackend)>, AppError> {
+        let found = match std::env::var(env_var_name(name)) {
+            Ok(value) if !value.is_empty() => Some((value, SecretBackend::Environment)),
+            _ => {
+                let file = self.load_file()?;
+                match file.entries.get(name).map(|info| info.backend) {
+                    Some(backend) => self
+                        .fetch_value(&file, name, backend)?
+                        .map(|value| (value, backend)),
+                    None => None,
+                }
+            }
+        };
+        if let Some((_, backend)) = &found {
+            audit_log::record(
+                AuditAction::CredentialUsed,
+                name,
+                Some(format!("{:?}", backend)),
+            );
+        }
+        Ok(found)
+    }
+
+    /// Look up a secret value
+    pub fn get(&self, name: &str) -> Result<Option<String>, AppError> {
+        Ok(self.get_with_backend(name)?.map(|(value, _)| value))
+    }
+
+    /// Values to accept for a secret: the current one first, then the
+    /// replaced one while its rotation grace window lasts. An expired
+    /// previous value is removed on the way.
+    pub fn get_accepted(&self, name: &str) -> Result<Vec<String>, AppError> {
+        let mut accepted: Vec<String> = self.get(name)?.into_iter().collect();
+
+        let mut file = self.load_file()?;
+        let Some(previous) = file.previous.get(name).cloned() else {
+            return Ok(accepted);
+        };
+        if Utc::now() < previous.valid_until {
+            if let Some(value) = self.fetch_value(&file, &previous_name(name), previous.backend)? {
+                accepted.push(value);
+            }
+        } else {
+            self.remove_previous(&mut file, name);
+            self.save_file(&file)?;
+        }
+        Ok(accepted)
+    }
+
+    /// Replace a secret with `new_value`, or a generated key when none is
+    /// given. The old value stays accepted for `grace` so requests already
+    /// using it keep working.
+    pub fn rotate(
+        &self,
+        name: &str,
+        new_value: Option<String>,
+        grace: chrono::Duration,
+    ) -> Result<SecretRotation, AppError> {
+        let variable = env_var_name(name);
+        if std::env::var_os(&variable).is_some() {
+            return Err(AppError::ValidationError {
+                field: "name".to_string(),
+                message: format!("Secret is managed by the {} environment variable", variable),
+            });
+        }
+        let generated = new_value.is_none();
+        let new_value = match new_value {
+            Some(value) if value.trim().is_empty() => {
+                return Err(AppError::ValidationError {
+                    field: "new_key".to_string(),
+                    message: "New key cannot be empty".to_string(),
+                })
+            }
+            Some(value) => value,
+            None => generate_secret(),
+        };
+
+        let mut file = self.load_file()?;
+        let now = Utc::now();
+        let current = match file.entries.get(name).map(|info| info.backend) {
+            Some(backend) => self.fetch_value(&file, name, backend)?,
+            None => None,
+        };
+
+        let previous_valid_until = match current {
+            Some(current) if current != new_value => {
+                self.remove_previous(&mut file, name);
+                let backend = self.put_value(&mut file, &previous_name(name), &current);
+                let valid_until = now + grace;
+                file.previous.insert(
+                    name.to_string(),
+                    PreviousSecret {
+                        backend,
+                        valid_until,
+                    },
+                );
+                Some(valid_until)
+            }
+            _ => None,
+        };
+
+        let backend = self.put_value(&mut file, name, &new_value);
+        file.entries.insert(
+            name.to_string(),
+            SecretInfo {
+                name: name.to_string(),
+                backend,
+                updated_at: now,
+                previous_valid_until: None,
+            },
+        );
+        self.save_file(&file)?;
+        audit_log::record(
+            AuditAction::CredentialRotated,
+            name,
+            previous_valid_until.map(|until| format!("previous key valid until {}", until)),
+        );
+
+        Ok(SecretRotation {
+            name: name.to_string(),
+            backend,
+            previous_valid_until,
+            generated_value: generated.then_some(new_value),
+        })
+    }
+
+    /// Remove a secret, and any value it replaced, from every backend.
+    /// Environment overrides are left alone. Returns whether anything was
+    /// stored.
+    pub fn delete(&self, name: &str) -> Result<bool, AppError> {
+        let mut file = self.load_file()?;
+        let existed = file.entries.remove(name).is_some();
+        self.remove_value(&mut file, name);
+        let had_previous = self.remove_previous(&mut file, name);
+        if existed || had_previous {
+            self.save_file(&file)?;
+            audit_log::record(AuditAction::CredentialDeleted, name, None);
+        }
+        Ok(existed)
+    }
+
+    /// Move a connection's API key stored under one of `legacy_ids` to
+    /// `connection_id`, unless it already has one. Returns whether a key was
+    /// moved.
+    pub fn migrate_connection_api_key(
+        &self,
+        connection_id: &str,
+        legacy_ids: &[String],
+    ) -> Result<bool, AppError> {
+        let name = connection_api_key(connection_id);
+        let mut file = self.load_file()?;
+        if file.entries.contains_key(&name) {
+            return Ok(false);
+        }
+
+        for legacy_id in legacy_ids {
+            let legacy_name = connection_api_key(legacy_id);
+            let Some(backend) = file.entries.get(&legacy_name).map(|info| info.backend) else {
+                continue;
+            };
+            let Some(value) = self.fetch_value(&file, &legacy_name, backend)? else {
+                continue;
+            };
+
+            let backend = self.put_value(&mut file, &name, &value);
+            file.entries.insert(
+                name.clone(),
+                SecretInfo {
+                    name: name.clone(),
+                    backend,
+                    updated_at: Utc::now(),
+                    previous_valid_until: None,
+                },
+            );
+            file.entries.remove(&legacy_name);
+            self.remove_value(&mut file, &legacy_name);
+            self.remove_previous(&mut file, &legacy_name);
+            self.save_file(&file)?;
+            audit_log::record(
+                AuditAction::CredentialStored,
+                &name,
+                Some(format!("migrated from {}", legacy_name)),
+            );
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Metadata for every stored secret plus active environment overrides
+    pub fn list(&self) -> Result<Vec<SecretInfo>, AppError> {
+        let file = self.load_file()?;
+        let now = Utc::now();
+        let mut secrets: Vec<SecretInfo> = file.entries.into_values().collect();
+        for info in secrets.iter_mut() {
+            if std::env::var_os(env_var_name(&info.name)).is_some() {
+                info.backend = SecretBackend::Environment;
+            }
+            info.previous_valid_until = file
+                .previous
+                .get(&info.name)
+                .map(|previous| previous.valid_until)
+                .filter(|valid_until| *valid_until > now);
+        }
+        Ok(secrets)
+    }
+
+    pub fn status(&self) -> Result<SecretStoreStatus, AppError> {
+        Ok(SecretStoreStatus {
+            backend: self.preferred_backend(),
+            secrets: self.list()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_store() -> (SecretStore, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SecretStore {
+            config_dir: temp_dir.path().to_path_buf(),
+            keychain: None,
+        };
+        (store, temp_dir)
+    }
+
+    #[test]
+    fn test_connection_of_api_key() {
+        let name = connection_api_key("example.com:4096");
+        assert_eq!(connection_of_api_key(&name), Some("example.com:4096"));
+        assert_eq!(connection_of_api_key(&provider_api_key("openai")), None);
+    }
+
+    #[test]
+    fn test_migrate_legacy_connection_api_key() {
+        let (store, _temp_dir) = create_test_store();
+        let legacy_ids = legacy_connection_ids("example.com");
+        store
+            .set(&connection_api_key("tunnel-example.com"), "sk-legacy")
+            .unwrap();
+
+        assert!(store
+            .migrate_connection_api_key("example.com:443", &legacy_ids)
+            .unwrap());
+        assert_eq!(
+            store.get(&connection_api_key("example.com:443")).unwrap(),
+            Some("sk-legacy".to_string())
+        );
+        assert_eq!(
+            store
+                .get(&connection_api_key("tunnel-example.com"))
+                .unwrap(),
+            None
+        );
+
+        // A key already under the new id is never replaced
+        store
+            .set(&connection_api_key("proxy-example.com"), "sk-other")
+            .unwrap();
+        assert!(!store
+            .migrate_connection_api_key("example.com:443", &legacy_ids)
+            .unwrap());
+        assert_eq!(
+            store.get(&connection_api_key("example.com:443")).unwrap(),
+            Some("sk-legacy".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_get_delete_encrypted_file() {
+        let (store, temp_dir) = create_test_store();
+        let name = connection_api_key("home");
+
+        assert_eq!(store.get(&name).unwrap(), None);
+        let backend = store.set(&name, "sk-test-123").unwrap();
+        assert_eq!(backend, SecretBackend::EncryptedFile);
+        assert_eq!(store.get(&name).unwrap(), Some("sk-test-123".to_string()));
+
+        // The value must not be stored in plaintext
+        let raw = std::fs::read(temp_dir.path().join(SECRETS_FILE_NAME)).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("sk-test-123"));
+
+        assert!(store.delete(&name).unwrap());
+        assert_eq!(store.get(&name).unwrap(), None);
+        assert!(!store.delete(&name).unwrap());
+    }
+
+    #[test]
+    fn test_list_omits_values() {
+        let (store, _temp_dir) = create_test_store();
+        store.set(&provider_api_key("anthropic"), "key-a").unwrap();
+        store.set(&signing_key("default"), "key-b").unwrap();
+
+        let secrets = store.list().unwrap();
+        let names: Vec<&str> = secrets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["provider/anthropic/api_key", "signing/default/key"]
+        );
+        let json = serde_json::to_string(&secrets).unwrap();
+        assert!(!json.contains("key-a"));
+    }
+
+    #[test]
+    fn test_environment_overrides_stored_value() {
+        let (store, _temp_dir) = create_test_store();
+        let name = signing_key("env-override-test");
+        store.set(&name, "stored").unwrap();
+
+        let var = env_var_name(&name);
+        assert_eq!(var, "OPENCODE_NEXUS_SECRET_SIGNING_ENV_OVERRIDE_TEST_KEY");
+        std::env::set_var(&var, "from-env");
+        let result = store.get_with_backend(&name).unwrap();
+        std::env::remove_var(&var);
+
+        assert_eq!(
+            result,
+            Some(("from-env".to_string(), SecretBackend::Environment))
+        );
+        assert_eq!(store.get(&name).unwrap(), Some("stored".to_string()));
+    }
+
+    #[test]
+    fn test_secrets_survive_reopen_and_reject_foreign_key() {
+        let (store, temp_dir) = create_test_store();
+        store.set("custom", "value").unwrap();
+
+        let reopened = SecretStore {
+            config_dir: temp_dir.path().to_path_buf(),
+            keychain: None,
+        };
+        assert_eq!(reopened.get("custom").unwrap(), Some("value".to_string()));
+
+        std::fs::write(temp_dir.path().join(SECRETS_KEY_FILE_NAME), [7u8; KEY_LEN]).unwrap();
+        assert!(reopened.get("custom").is_err());
+    }
+
+    #[test]
+    fn test_rotate_keeps_previous_during_grace() {
+        let (store, _temp_dir) = create_test_store();
+        let name = connection_api_key("rotate");
+        store.set(&name, "old-key").unwrap();
+
+        let rotation = store
+            .rotate(
+                &name,
+                Some("new-key".to_string()),
+                chrono::Duration::minutes(5),
+            )
+            .unwrap();
+        assert!(rotation.previous_valid_until.is_some());
+        assert!(rotation.generated_value.is_none());
+        assert_eq!(store.get(&name).unwrap(), Some("new-key".to_string()));
+        assert_eq!(
+            store.get_accepted(&name).unwrap(),
+            vec!["new-key".to_string(), "old-key".to_string()]
+        );
+        assert!(store.list().unwrap()[0].previous_valid_until.is_some());
+
+        assert!(store.delete(&name).unwrap());
+        assert!(store.get_accepted(&name).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rotate_drops_previous_after_grace() {
+        let (store, _temp_dir) = create_test_store();
+        let name = connection_api_key("expired");
+        store.set(&name, "old-key").unwrap();
+
+        let rotation = store.rotate(&name, None, chrono::Duration::zero()).unwrap();
+        let generated = rotation.generated_value.unwrap();
+        assert_eq!(generated.len(), KEY_LEN * 2);
+
+        assert_eq!(store.get_accepted(&name).unwrap(), vec![generated]);
+        assert!(store.load_file().unwrap().previous.is_empty());
+    }
+
+    #[test]
+    fn test_rotate_rejects_empty_key() {
+        let (store, _temp_dir) = create_test_store();
+        let result = store.rotate("custom", Some(" ".to_string()), chrono::Duration::zero());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rejects_empty_name() {
+        let (store, _temp_dir) = create_test_store();
+        assert!(store.set("  ", "value").is_err());
+    }
+}
@@ -0,0 +1,246 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The connected server's resolved configuration, reduced to the parts that
+//! explain behaviour, and compared with what the app expects of it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpServer {
+    pub name: String,
+    /// `local` or `remote`
+    pub kind: String,
+    /// The command line for local servers, the URL for remote ones
+    pub target: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerConfig {
+    /// `provider/model` used when a prompt names none
+    pub default_model: Option<String>,
+    pub small_model: Option<String>,
+    /// Tool permission (`edit`, `bash`, `webfetch`, ...) to its policy,
+    /// with per-pattern bash rules flattened to `bash:<pattern>`
+    pub permissions: BTreeMap<String, String>,
+    pub mcp_servers: Vec<McpServer>,
+    /// `manual`, `auto` or `disabled`
+    pub share: Option<String>,
+    /// The config as the server sent it
+    pub raw: Value,
+}
+
+/// A setting whose server value differs from the expected one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfigDifference {
+    /// Dotted path into the server config, e.g. `permission.bash`
+    pub path: String,
+    pub expected: Value,
+    /// `null` when the server doesn't set it
+    pub actual: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerConfigReport {
+    pub server_url: String,
+    pub config: ServerConfig,
+    pub differences: Vec<ConfigDifference>,
+}
+
+fn string_at(raw: &Value, key: &str) -> Option<String> {
+    raw.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn policy(value: &Value) -> String {
+    match value {
+        Value::String(policy) => policy.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl ServerConfig {
+    pub fn parse(raw: Value) -> Self {
+        let mut permissions = BTreeMap::new();
+        if let Some(rules) = raw.get("permission").and_then(Value::as_object) {
+            for (tool, rule) in rules {
+                match rule.as_object() {
+                    Some(patterns) => {
+                        for (pattern, action) in patterns {
+                            permissions.insert(format!("{}:{}", tool, pattern), policy(action));
+                        }
+                    }
+                    None => {
+                        permissions.insert(tool.clone(), policy(rule));
+                    }
+                }
+            }
+        }
+
+        let mut mcp_servers: Vec<McpServer> = raw
+            .get("mcp")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(name, server)| {
+                let target = match server.get("command") {
+                    Some(Value::Array(parts)) => parts
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    _ => server
+                        .get("url")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                };
+                McpServer {
+                    name: name.clone(),
+                    kind: server
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .unwrap_or("local")
+                        .to_string(),
+                    target,
+                    enabled: server
+                        .get("enabled")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(true),
+                }
+            })
+            .collect();
+        mcp_servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            default_model: string_at(&raw, "model"),
+            small_model: string_at(&raw, "small_model"),
+            permissions,
+            mcp_servers,
+            share: string_at(&raw, "share"),
+            raw,
+        }
+    }
+}
+
+/// Compare every leaf of `expected`, a partial config in the server's own
+/// shape, with the server's value at the same path
+pub fn differences(expected: &Value, actual: &Value) -> Vec<ConfigDifference> {
+    let mut found = Vec::new();
+    collect_differences("", expected, Some(actual), &mut found);
+    found
+}
+
+fn collect_differences(
+    path: &str,
+    expected: &Value,
+    actual: Option<&Value>,
+    found: &mut Vec<ConfigDifference>,
+) {
+    match expected {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_differences(&child, value, actual.and_then(|a| a.get(key)), found);
+            }
+        }
+        leaf => {
+            if actual != Some(leaf) {
+                found.push(ConfigDifference {
+                    path: path.to_string(),
+                    expected: leaf.clone(),
+                    actual: actual.cloned().unwrap_or(Value::Null),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!({
+            "model": "anthropic/claude-sonnet-4",
+            "share": "manual",
+            "permission": {
+                "edit": "ask",
+                "bash": { "git push": "deny", "*": "allow" }
+            },
+            "mcp": {
+                "docs": { "type": "remote", "url": "https://example.com/mcp" },
+                "fs": { "type": "local", "command": ["npx", "mcp-fs"], "enabled": false }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_server_config() {
+        let config = ServerConfig::parse(sample());
+        assert_eq!(
+            config.default_model.as_deref(),
+            Some("anthropic/claude-sonnet-4")
+        );
+        assert_eq!(config.small_model, None);
+        assert_eq!(config.share.as_deref(), Some("manual"));
+        assert_eq!(config.permissions["edit"], "ask");
+        assert_eq!(config.permissions["bash:git push"], "deny");
+        assert_eq!(config.mcp_servers.len(), 2);
+        assert_eq!(config.mcp_servers[0].target, "https://example.com/mcp");
+        assert_eq!(config.mcp_servers[1].target, "npx mcp-fs");
+        assert!(!config.mcp_servers[1].enabled);
+    }
+
+    #[test]
+    fn test_differences() {
+        let expected = json!({
+            "model": "anthropic/claude-sonnet-4",
+            "share": "disabled",
+            "permission": { "edit": "ask", "webfetch": "deny" }
+        });
+        let found = differences(&expected, &sample());
+        assert_eq!(
+            found,
+            vec![
+                ConfigDifference {
+                    path: "permission.webfetch".to_string(),
+                    expected: json!("deny"),
+                    actual: Value::Null,
+                },
+                ConfigDifference {
+                    path: "share".to_string(),
+                    expected: json!("disabled"),
+                    actual: json!("manual"),
+                },
+            ]
+        );
+        assert!(differences(&json!({}), &sample()).is_empty());
+    }
+}
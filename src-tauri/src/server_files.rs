@@ -0,0 +1,464 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Browsing the server's workspace: directory listings and file contents
+//! from its file endpoints, cached briefly and capped in size, and file
+//! name and text search over its find endpoints.
+
+use crate::error::AppError;
+use crate::project_context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// How long a listing or file is served from the cache
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Listings and files remembered at once
+const MAX_CACHED_ENTRIES: usize = 200;
+
+/// Largest part of a file returned by `read_server_file`
+pub const MAX_FILE_BYTES: usize = 512 * 1024;
+
+/// Tauri event with a `FileSearchBatch` of results
+pub const FILE_SEARCH_EVENT: &str = "file-search-results";
+
+/// Hits sent per `FILE_SEARCH_EVENT`
+pub const SEARCH_BATCH_SIZE: usize = 100;
+
+/// Hits kept from one search
+pub const MAX_SEARCH_HITS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileNodeKind {
+    File,
+    Directory,
+}
+
+/// An entry of a server directory listing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileNode {
+    pub name: String,
+    /// Relative to the server's workspace
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: FileNodeKind,
+    /// Matched by the workspace's ignore rules
+    #[serde(default)]
+    pub ignored: bool,
+}
+
+/// A file read from the server's workspace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerFile {
+    pub path: String,
+    /// Empty for binary files
+    pub content: String,
+    /// Uncommitted changes to the file, as a unified diff
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Size of the content the server sent, in bytes
+    pub size: usize,
+    /// `content` was cut at `MAX_FILE_BYTES`
+    pub truncated: bool,
+    pub binary: bool,
+}
+
+/// Check a workspace-relative path, returning it without `./` prefixes.
+/// The workspace root is `""`.
+pub fn normalize_path(path: &str) -> Result<String, AppError> {
+    let mut parts = Vec::new();
+    for component in Path::new(path.trim()).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => {
+                return Err(AppError::ValidationError {
+                    field: "path".to_string(),
+                    message: format!("Path must stay inside the workspace: {}", path),
+                })
+            }
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Parse a listing, directories first and then by name
+pub fn parse_listing(listing: Value) -> Result<Vec<FileNode>, AppError> {
+    let mut nodes: Vec<FileNode> =
+        serde_json::from_value(listing).map_err(|e| AppError::ParseError {
+            message: "Failed to parse file listing".to_string(),
+            details: Some(e.to_string()),
+        })?;
+    nodes.sort_by(|a, b| {
+        (a.kind != FileNodeKind::Directory, a.name.to_lowercase())
+            .cmp(&(b.kind != FileNodeKind::Directory, b.name.to_lowercase()))
+    });
+    Ok(nodes)
+}
+
+/// Build a `ServerFile` from the server's content response, dropping binary
+/// content and truncating large files
+pub fn parse_content(path: &str, response: &Value) -> ServerFile {
+    let content = response
+        .get("content")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let diff = response
+        .get("diff")
+        .and_then(Value::as_str)
+        .filter(|diff| !diff.is_empty())
+        .map(str::to_string);
+    let size = content.len();
+    let binary = content.contains('\0');
+
+    let (content, truncated) = if binary {
+        (String::new(), false)
+    } else if size > MAX_FILE_BYTES {
+        let mut end = MAX_FILE_BYTES;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        (content[..end].to_string(), true)
+    } else {
+        (content.to_string(), false)
+    };
+
+    ServerFile {
+        path: path.to_string(),
+        content,
+        diff,
+        size,
+        truncated,
+        binary,
+    }
+}
+
+/// A file name match, or a line matching a text search
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchHit {
+    /// Relative to the server's workspace
+    pub path: String,
+    /// How the composer refers to the file, e.g. `@src/lib.rs`
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+}
+
+impl SearchHit {
+    fn new(path: &str, line_number: Option<u64>, line: Option<String>) -> Self {
+        let path = path.trim_start_matches("./").to_string();
+        Self {
+            reference: format!("@{}", path),
+            path,
+            line_number,
+            line,
+        }
+    }
+}
+
+/// Part of the results of one `search_server_files` call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileSearchBatch {
+    pub search_id: String,
+    pub hits: Vec<SearchHit>,
+    /// Set on the last batch
+    pub done: bool,
+}
+
+/// Hits from `find/file`, a plain list of paths
+pub fn parse_file_hits(response: &Value) -> Vec<SearchHit> {
+    response
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|path| SearchHit::new(path, None, None))
+        .collect()
+}
+
+/// Hits from `find`, ripgrep's JSON matches
+pub fn parse_text_hits(response: &Value) -> Vec<SearchHit> {
+    response
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|hit| {
+            let path = hit.pointer("/path/text").and_then(Value::as_str)?;
+            let line = hit
+                .pointer("/lines/text")
+                .and_then(Value::as_str)
+                .map(|line| line.trim_end().to_string());
+            Some(SearchHit::new(
+                path,
+                hit.get("line_number").and_then(Value::as_u64),
+                line,
+            ))
+        })
+        .collect()
+}
+
+/// Keep hits whose path matches a gitignore-style glob, and cap the count.
+/// Returns whether any hits were dropped by the cap.
+pub fn filter_hits(hits: &mut Vec<SearchHit>, glob: Option<&str>) -> Result<bool, AppError> {
+    if let Some(glob) = glob.map(str::trim).filter(|glob| !glob.is_empty()) {
+        let pattern =
+            project_context::glob_regex(glob).ok_or_else(|| AppError::ValidationError {
+                field: "glob".to_string(),
+                message: format!("Invalid glob: {}", glob),
+            })?;
+        hits.retain(|hit| pattern.is_match(&hit.path));
+    }
+    let truncated = hits.len() > MAX_SEARCH_HITS;
+    hits.truncate(MAX_SEARCH_HITS);
+    Ok(truncated)
+}
+
+#[derive(Clone)]
+enum CachedValue {
+    Listing(Vec<FileNode>),
+    File(ServerFile),
+}
+
+struct CacheEntry {
+    value: CachedValue,
+    fetched_at: Instant,
+}
+
+/// Recent listings and files, keyed by server and path
+#[derive(Clone, Default)]
+pub struct ServerFileCache {
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+impl ServerFileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<String, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Server file cache lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<CachedValue> {
+        let mut entries = self.lock_entries();
+        match entries.get(key) {
+            Some(entry) if entry.fetched_at.elapsed() < CACHE_TTL => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn put(&self, key: String, value: CachedValue) {
+        let mut entries = self.lock_entries();
+        entries.retain(|_, entry| entry.fetched_at.elapsed() < CACHE_TTL);
+        if entries.len() >= MAX_CACHED_ENTRIES {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.fetched_at)
+                .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    pub fn listing(&self, server_url: &str, path: &str) -> Option<Vec<FileNode>> {
+        match self.get(&format!("list:{}:{}", server_url, path))? {
+            CachedValue::Listing(nodes) => Some(nodes),
+            CachedValue::File(_) => None,
+        }
+    }
+
+    pub fn store_listing(&self, server_url: &str, path: &str, nodes: Vec<FileNode>) {
+        self.put(
+            format!("list:{}:{}", server_url, path),
+            CachedValue::Listing(nodes),
+        );
+    }
+
+    pub fn file(&self, server_url: &str, path: &str) -> Option<ServerFile> {
+        match self.get(&format!("read:{}:{}", server_url, path))? {
+            CachedValue::File(file) => Some(file),
+            CachedValue::Listing(_) => None,
+        }
+    }
+
+    pub fn store_file(&self, server_url: &str, file: ServerFile) {
+        self.put(
+            format!("read:{}:{}", server_url, file.path),
+            CachedValue::File(file),
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("").unwrap(), "");
+        assert_eq!(normalize_path("./src/./lib.rs").unwrap(), "src/lib.rs");
+        assert_eq!(normalize_path(" src/ ").unwrap(), "src");
+        assert!(normalize_path("../secrets").is_err());
+        assert!(normalize_path("src/../../etc").is_err());
+        assert!(normalize_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_parse_listing_sorts_directories_first() {
+        let nodes = parse_listing(json!([
+            { "name": "b.rs", "path": "b.rs", "absolute": "/w/b.rs", "type": "file", "ignored": false },
+            { "name": "src", "path": "src", "type": "directory" },
+            { "name": "A.md", "path": "A.md", "type": "file" },
+        ]))
+        .unwrap();
+        let names: Vec<_> = nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, vec!["src", "A.md", "b.rs"]);
+        assert_eq!(nodes[2].absolute.as_deref(), Some("/w/b.rs"));
+
+        assert!(parse_listing(json!({ "error": "nope" })).is_err());
+    }
+
+    #[test]
+    fn test_parse_content() {
+        let file = parse_content(
+            "src/lib.rs",
+            &json!({ "type": "raw", "content": "fn main() {}", "diff": "" }),
+        );
+        assert_eq!(file.content, "fn main() {}");
+        assert_eq!(file.size, 12);
+        assert!(file.diff.is_none());
+        assert!(!file.truncated && !file.binary);
+
+        let binary = parse_content("logo.png", &json!({ "content": "PNG\u{0}\u{1}" }));
+        assert!(binary.binary);
+        assert!(binary.content.is_empty());
+
+        let large = "é".repeat(MAX_FILE_BYTES);
+        let truncated = parse_content("big.txt", &json!({ "content": large }));
+        assert!(truncated.truncated);
+        assert!(truncated.content.len() <= MAX_FILE_BYTES);
+        assert_eq!(truncated.size, MAX_FILE_BYTES * 2);
+    }
+
+    #[test]
+    fn test_parse_search_hits() {
+        let files = parse_file_hits(&json!(["src/lib.rs", "./README.md"]));
+        assert_eq!(files[1].path, "README.md");
+        assert_eq!(files[1].reference, "@README.md");
+        assert!(files[0].line_number.is_none());
+
+        let matches = parse_text_hits(&json!([
+            {
+                "path": { "text": "src/lib.rs" },
+                "lines": { "text": "fn run() {\n" },
+                "line_number": 12,
+                "absolute_offset": 200,
+                "submatches": []
+            },
+            { "lines": { "text": "no path" } }
+        ]));
+        assert_eq!(
+            matches,
+            vec![SearchHit {
+                path: "src/lib.rs".to_string(),
+                reference: "@src/lib.rs".to_string(),
+                line_number: Some(12),
+                line: Some("fn run() {".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_filter_hits() {
+        let mut hits = parse_file_hits(&json!(["src/lib.rs", "docs/guide.md", "build.rs"]));
+        assert!(!filter_hits(&mut hits, Some("*.rs")).unwrap());
+        let paths: Vec<_> = hits.iter().map(|hit| hit.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "build.rs"]);
+
+        filter_hits(&mut hits, Some("src/**")).unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let mut many: Vec<_> = (0..MAX_SEARCH_HITS + 1)
+            .map(|i| SearchHit::new(&format!("f{}", i), None, None))
+            .collect();
+        assert!(filter_hits(&mut many, None).unwrap());
+        assert_eq!(many.len(), MAX_SEARCH_HITS);
+    }
+
+    #[test]
+    fn test_cache_keys_by_server_and_kind() {
+        let cache = ServerFileCache::new();
+        let file = parse_content("README.md", &json!({ "content": "# Hi" }));
+        cache.store_file("http://a", file.clone());
+        cache.store_listing("http://a", "README.md", Vec::new());
+
+        assert_eq!(cache.file("http://a", "README.md"), Some(file));
+        assert!(cache.file("http://b", "README.md").is_none());
+        assert_eq!(cache.listing("http://a", "README.md"), Some(Vec::new()));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let cache = ServerFileCache::new();
+        for i in 0..MAX_CACHED_ENTRIES + 5 {
+            cache.store_listing("http://a", &format!("dir{}", i), Vec::new());
+        }
+        assert_eq!(cache.len(), MAX_CACHED_ENTRIES);
+        assert!(cache
+            .listing("http://a", &format!("dir{}", MAX_CACHED_ENTRIES + 4))
+            .is_some());
+    }
+}
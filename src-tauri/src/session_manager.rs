@@ -22,14 +22,19 @@
 
 use crate::api_client::{ApiClient, ModelConfig};
 use crate::error::{AppError, RetryConfig};
+use crate::message_journal::MessageJournal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use tokio::sync::RwLock;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
 use uuid::Uuid;
 
+/// How long changes collect before the sessions file is rewritten
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
 /// Message role in a chat session
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MessageRole {
@@ -39,17 +44,150 @@ pub enum MessageRole {
     Tool,
 }
 
+/// One piece of a message. Tool calls, attachments and reasoning have no
+/// place in flat text, so messages carry a list of parts alongside it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text {
+        text: String,
+    },
+    /// The model's thinking, shown collapsed
+    Reasoning {
+        text: String,
+    },
+    ToolCall {
+        call_id: String,
+        tool: String,
+        #[serde(default)]
+        input: serde_json::Value,
+    },
+    ToolResult {
+        call_id: String,
+        #[serde(default)]
+        output: String,
+        #[serde(default)]
+        is_error: bool,
+    },
+    File {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+        mime_type: String,
+        url: String,
+    },
+    Image {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+        mime_type: String,
+        url: String,
+    },
+}
+
+impl MessagePart {
+    pub fn text(text: impl Into<String>) -> Self {
+        MessagePart::Text { text: text.into() }
+    }
+
+    /// Convert one of the server's message parts. A finished tool part
+    /// becomes a call and its result; bookkeeping parts such as step
+    /// markers and snapshots are dropped.
+    pub fn from_server(part: &serde_json::Value) -> Vec<MessagePart> {
+        let text = |key: &str| {
+            part.get(key)
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        };
+        match part.get("type").and_then(|kind| kind.as_str()) {
+            Some("text") => text("text").map(MessagePart::text).into_iter().collect(),
+            Some("reasoning") => text("text")
+                .map(|text| MessagePart::Reasoning { text })
+                .into_iter()
+                .collect(),
+            Some("tool") => {
+                let call_id = text("callID").or_else(|| text("id")).unwrap_or_default();
+                let state = part.get("state");
+                let mut parts = vec![MessagePart::ToolCall {
+                    call_id: call_id.clone(),
+                    tool: text("tool").unwrap_or_default(),
+                    input: state
+                        .and_then(|state| state.get("input"))
+                        .cloned()
+                        .unwrap_or_default(),
+                }];
+                let state_text = |key: &str| {
+                    state
+                        .and_then(|state| state.get(key))
+                        .and_then(|value| value.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                match state
+                    .and_then(|state| state.get("status"))
+                    .and_then(|status| status.as_str())
+                {
+                    Some("completed") => parts.push(MessagePart::ToolResult {
+                        call_id,
+                        output: state_text("output"),
+                        is_error: false,
+                    }),
+                    Some("error") => parts.push(MessagePart::ToolResult {
+                        call_id,
+                        output: state_text("error"),
+                        is_error: true,
+                    }),
+                    _ => {}
+                }
+                parts
+            }
+            Some("file") => {
+                let mime_type = text("mime").unwrap_or_else(|| "application/octet-stream".into());
+                let filename = text("filename");
+                let url = text("url").unwrap_or_default();
+                vec![if mime_type.starts_with("image/") {
+                    MessagePart::Image {
+                        filename,
+                        mime_type,
+                        url,
+                    }
+                } else {
+                    MessagePart::File {
+                        filename,
+                        mime_type,
+                        url,
+                    }
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The text parts of a message joined together, for its flat `content`
+pub fn text_content(parts: &[MessagePart]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            MessagePart::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// A single message in a chat session
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ChatMessage {
     pub id: String,
     pub role: MessageRole,
+    /// Text of the message; the same as the text `parts` joined together
     pub content: String,
     pub timestamp: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    pub parts: Vec<MessagePart>,
 }
 
 /// A chat session containing messages and metadata
@@ -59,11 +197,154 @@ pub struct ChatSession {
     pub title: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default)]
     pub messages: Vec<ChatMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_config: Option<ModelConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// Connection the session lives on, for sessions from a server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+}
+
+/// Time at `pointer` in a server object, sent as milliseconds since the epoch
+fn server_time(value: &serde_json::Value, pointer: &str) -> Option<DateTime<Utc>> {
+    value
+        .pointer(pointer)
+        .and_then(|time| time.as_i64())
+        .and_then(DateTime::from_timestamp_millis)
+}
+
+impl ChatMessage {
+    /// Convert one of the server's `{ info, parts }` messages
+    pub fn from_server(message: &serde_json::Value) -> Option<Self> {
+        let info = message.get("info")?;
+        let role = match info.get("role")?.as_str()? {
+            "user" => MessageRole::User,
+            "assistant" => MessageRole::Assistant,
+            "system" => MessageRole::System,
+            "tool" => MessageRole::Tool,
+            _ => return None,
+        };
+        let parts: Vec<MessagePart> = message
+            .get("parts")
+            .and_then(|parts| parts.as_array())
+            .into_iter()
+            .flatten()
+            .flat_map(MessagePart::from_server)
+            .collect();
+        Some(Self {
+            id: info.get("id")?.as_str()?.to_string(),
+            role,
+            content: text_content(&parts),
+            timestamp: server_time(info, "/time/created").unwrap_or_else(Utc::now),
+            model: info
+                .get("modelID")
+                .and_then(|model| model.as_str())
+                .map(str::to_string),
+            metadata: None,
+            parts,
+        })
+    }
+}
+
+impl ChatSession {
+    /// Convert a session as the server lists it. The listing carries no
+    /// messages; those come from the session's message endpoint.
+    pub fn from_server(session: &serde_json::Value) -> Option<Self> {
+        let created_at = server_time(session, "/time/created").unwrap_or_else(Utc::now);
+        Some(Self {
+            id: session.get("id")?.as_str()?.to_string(),
+            title: session
+                .get("title")
+                .and_then(|title| title.as_str())
+                .filter(|title| !title.is_empty())
+                .map(str::to_string),
+            created_at,
+            updated_at: server_time(session, "/time/updated").unwrap_or(created_at),
+            messages: Vec::new(),
+            model_config: None,
+            metadata: None,
+            connection_id: None,
+        })
+    }
+}
+
+/// A session as kept in the sessions file; its messages are in its journal
+#[derive(Serialize)]
+struct SessionHeader<'a> {
+    id: &'a str,
+    title: Option<&'a str>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model_config: Option<&'a ModelConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<&'a HashMap<String, serde_json::Value>>,
+}
+
+impl<'a> From<&'a ChatSession> for SessionHeader<'a> {
+    fn from(session: &'a ChatSession) -> Self {
+        Self {
+            id: &session.id,
+            title: session.title.as_deref(),
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            model_config: session.model_config.as_ref(),
+            metadata: session.metadata.as_ref(),
+        }
+    }
+}
+
+/// Characters of the last message shown in a session list
+const PREVIEW_CHARS: usize = 120;
+
+/// What the session list shows, without the session's messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub title: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub message_count: usize,
+    pub user_message_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message_preview: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+}
+
+impl From<&ChatSession> for SessionSummary {
+    fn from(session: &ChatSession) -> Self {
+        let last = session.messages.last();
+        Self {
+            id: session.id.clone(),
+            title: session.title.clone(),
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            message_count: session.messages.len(),
+            user_message_count: session
+                .messages
+                .iter()
+                .filter(|message| matches!(message.role, MessageRole::User))
+                .count(),
+            last_message_preview: last.map(|message| preview(&message.content)),
+            last_message_at: last.map(|message| message.timestamp),
+            connection_id: session.connection_id.clone(),
+        }
+    }
+}
+
+/// The start of `text` on one line, cut at a character boundary
+pub(crate) fn preview(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match flat.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}...", &flat[..end]),
+        None => flat,
+    }
 }
 
 /// Request to create a new session
@@ -95,22 +376,157 @@ pub struct SendMessageResponse {
     pub session_id: Option<String>,
 }
 
+#[derive(Default)]
+struct SaveQueue {
+    /// Sessions changed or removed since the last save
+    dirty: HashSet<String>,
+    /// Each session's JSON as last written, so a save only serializes the
+    /// sessions that changed
+    serialized: BTreeMap<String, String>,
+    /// A delayed save is already pending
+    scheduled: bool,
+}
+
+/// Writes the sessions file from the cached JSON of unchanged sessions and
+/// fresh JSON for dirty ones
+#[derive(Clone)]
+struct SessionWriter {
+    path: PathBuf,
+    sessions: Arc<RwLock<HashMap<String, ChatSession>>>,
+    queue: Arc<Mutex<SaveQueue>>,
+}
+
+impl SessionWriter {
+    /// Write pending changes, returning how many sessions were re-serialized
+    async fn flush(&self) -> Result<usize, AppError> {
+        let mut queue = self.queue.lock().await;
+        queue.scheduled = false;
+        if queue.dirty.is_empty() {
+            return Ok(0);
+        }
+        let dirty = std::mem::take(&mut queue.dirty);
+
+        let sessions = self.sessions.read().await;
+        for session_id in &dirty {
+            match sessions.get(session_id) {
+                Some(session) => {
+                    let json =
+                        serde_json::to_string(&SessionHeader::from(session)).map_err(|e| {
+                            AppError::ParseError {
+                                message: "Failed to serialize sessions".to_string(),
+                                details: Some(e.to_string()),
+                            }
+                        })?;
+                    queue.serialized.insert(session_id.clone(), json);
+                }
+                None => {
+                    queue.serialized.remove(session_id);
+                }
+            }
+        }
+        drop(sessions);
+
+        let mut contents = String::from("{");
+        for (index, (session_id, json)) in queue.serialized.iter().enumerate() {
+            if index > 0 {
+                contents.push(',');
+            }
+            contents.push_str(&serde_json::Value::from(session_id.as_str()).to_string());
+            contents.push(':');
+            contents.push_str(json);
+        }
+        contents.push('}');
+
+        // Write beside the file and rename so a crash never leaves half a file
+        let temp_path = self.path.with_extension("json.tmp");
+        let written = match tokio::fs::write(&temp_path, contents).await {
+            Ok(()) => tokio::fs::rename(&temp_path, &self.path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            // Retry these sessions with the next save
+            queue.dirty.extend(dirty);
+            return Err(AppError::FileSystemError {
+                path: self.path.to_string_lossy().to_string(),
+                message: "Failed to write sessions file".to_string(),
+                details: e.to_string(),
+            });
+        }
+        Ok(dirty.len())
+    }
+}
+
+/// Journal records that take a session from its stored messages to new ones
+#[derive(Debug, PartialEq)]
+enum JournalSync {
+    Unchanged,
+    /// New messages after the stored ones, and stored ones that changed
+    Patch {
+        appended: Vec<ChatMessage>,
+        updated: Vec<ChatMessage>,
+    },
+    /// Messages were removed or reordered, which records can't express, so
+    /// the journal is rewritten with these
+    Rewrite(Vec<ChatMessage>),
+}
+
+impl JournalSync {
+    fn between(stored: &[ChatMessage], messages: &[ChatMessage]) -> Self {
+        if messages.len() < stored.len()
+            || stored
+                .iter()
+                .zip(messages)
+                .any(|(old, new)| old.id != new.id)
+        {
+            return JournalSync::Rewrite(messages.to_vec());
+        }
+        let updated: Vec<ChatMessage> = stored
+            .iter()
+            .zip(messages)
+            .filter(|(old, new)| old != new)
+            .map(|(_, new)| new.clone())
+            .collect();
+        let appended = messages[stored.len()..].to_vec();
+        if updated.is_empty() && appended.is_empty() {
+            JournalSync::Unchanged
+        } else {
+            JournalSync::Patch { appended, updated }
+        }
+    }
+}
+
 /// Session manager for handling chat sessions
 pub struct SessionManager {
     api_client: Arc<ApiClient>,
     config_dir: PathBuf,
     sessions: Arc<RwLock<HashMap<String, ChatSession>>>,
     current_session_id: Arc<RwLock<Option<String>>>,
+    writer: SessionWriter,
+    save_delay: Duration,
+    journal: Arc<MessageJournal>,
+    /// Taken while the sessions lock is still held and kept until the
+    /// journal write finishes, so writes reach disk in the order they were
+    /// made in memory without holding the sessions lock over file I/O
+    journal_order: Arc<Mutex<()>>,
 }
 
 impl SessionManager {
     /// Create a new session manager
     pub fn new(api_client: Arc<ApiClient>, config_dir: PathBuf) -> Self {
+        let sessions = Arc::new(RwLock::new(HashMap::new()));
         Self {
             api_client,
+            writer: SessionWriter {
+                path: config_dir.join("chat_sessions.json"),
+                sessions: sessions.clone(),
+                queue: Arc::new(Mutex::new(SaveQueue::default())),
+            },
+            journal: Arc::new(MessageJournal::new(&config_dir)),
+            journal_order: Arc::new(Mutex::new(())),
             config_dir,
-            sessions: Arc::new(RwLock::new(HashMap::new())),
+            sessions,
             current_session_id: Arc::new(RwLock::new(None)),
+            save_delay: SAVE_DELAY,
         }
     }
 
@@ -119,50 +535,123 @@ impl SessionManager {
         self.config_dir.join("chat_sessions.json")
     }
 
+    /// Queue a changed or removed session for the next save, which runs
+    /// once changes have stopped arriving for `save_delay`
+    async fn mark_dirty(&self, session_id: &str) {
+        let mut queue = self.writer.queue.lock().await;
+        queue.dirty.insert(session_id.to_string());
+        if queue.scheduled {
+            return;
+        }
+        queue.scheduled = true;
+
+        let writer = self.writer.clone();
+        let delay = self.save_delay;
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Err(e) = writer.flush().await {
+                tracing::warn!(target: "chat", "Failed to save sessions: {}", e);
+            }
+        });
+    }
+
+    /// Claim the next slot in journal write order; call before releasing
+    /// the sessions lock
+    async fn journal_turn(&self) -> OwnedMutexGuard<()> {
+        self.journal_order.clone().lock_owned().await
+    }
+
+    /// Run journal I/O on the blocking pool, holding `turn` until it is done
+    async fn write_journal<F>(&self, turn: OwnedMutexGuard<()>, write: F) -> Result<(), AppError>
+    where
+        F: FnOnce(&MessageJournal) -> Result<(), AppError> + Send + 'static,
+    {
+        let journal = self.journal.clone();
+        tokio::task::spawn_blocking(move || {
+            let _turn = turn;
+            write(&journal)
+        })
+        .await
+        .map_err(|e| AppError::IoError {
+            message: "Failed to write message journal".to_string(),
+            details: Some(e.to_string()),
+        })?
+    }
+
     /// Load sessions from disk
     pub async fn load_sessions(&self) -> Result<(), Box<dyn std::error::Error>> {
         let sessions_file = self.get_sessions_file_path();
 
-        if !sessions_file.exists() {
-            return Ok(());
-        }
-
-        let sessions_json =
-            std::fs::read_to_string(&sessions_file).map_err(|e| AppError::FileSystemError {
-                path: sessions_file.to_string_lossy().to_string(),
-                message: "Failed to read sessions file".to_string(),
-                details: e.to_string(),
-            })?;
+        let sessions_json = match tokio::fs::read(&sessions_file).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(AppError::FileSystemError {
+                    path: sessions_file.to_string_lossy().to_string(),
+                    message: "Failed to read sessions file".to_string(),
+                    details: e.to_string(),
+                }
+                .into())
+            }
+        };
 
-        let loaded_sessions: HashMap<String, ChatSession> = serde_json::from_str(&sessions_json)
-            .map_err(|e| AppError::ParseError {
+        let mut loaded_sessions: HashMap<String, ChatSession> =
+            serde_json::from_slice(&sessions_json).map_err(|e| AppError::ParseError {
                 message: "Failed to parse sessions file".to_string(),
                 details: Some(e.to_string()),
             })?;
 
+        // Replaying every journal is the bulk of the disk work, so it runs
+        // off the async runtime
+        let journal = self.journal.clone();
+        let (loaded_sessions, moved) = tokio::task::spawn_blocking(move || {
+            // Sessions saved before journals existed carry their messages
+            // inline; those move into a journal and the file is rewritten
+            let mut moved = Vec::new();
+            for (session_id, session) in loaded_sessions.iter_mut() {
+                if !session.messages.is_empty() {
+                    journal.compact(session_id, &session.messages)?;
+                    moved.push(session_id.clone());
+                    continue;
+                }
+                let replay = journal.load(session_id)?;
+                if replay.wasted > 0 {
+                    journal.compact(session_id, &replay.messages)?;
+                }
+                session.messages = replay.messages;
+            }
+            Ok::<_, AppError>((loaded_sessions, moved))
+        })
+        .await
+        .map_err(|e| AppError::IoError {
+            message: "Failed to load message journals".to_string(),
+            details: Some(e.to_string()),
+        })??;
+
+        let mut queue = self.writer.queue.lock().await;
+        queue.dirty.clear();
+        queue.serialized = loaded_sessions
+            .iter()
+            .filter_map(|(session_id, session)| {
+                serde_json::to_string(&SessionHeader::from(session))
+                    .ok()
+                    .map(|json| (session_id.clone(), json))
+            })
+            .collect();
+        drop(queue);
         let mut sessions = self.sessions.write().await;
         *sessions = loaded_sessions;
+        drop(sessions);
 
+        for session_id in moved {
+            self.mark_dirty(&session_id).await;
+        }
         Ok(())
     }
 
-    /// Save sessions to disk
+    /// Write pending changes to disk now rather than after the save delay
     pub async fn save_sessions(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let sessions = self.sessions.read().await;
-        let sessions_json =
-            serde_json::to_string_pretty(&*sessions).map_err(|e| AppError::ParseError {
-                message: "Failed to serialize sessions".to_string(),
-                details: Some(e.to_string()),
-            })?;
-
-        std::fs::write(self.get_sessions_file_path(), sessions_json).map_err(|e| {
-            AppError::FileSystemError {
-                path: self.get_sessions_file_path().to_string_lossy().to_string(),
-                message: "Failed to write sessions file".to_string(),
-                details: e.to_string(),
-            }
-        })?;
-
+        self.writer.flush().await?;
         Ok(())
     }
 
@@ -177,6 +666,15 @@ impl SessionManager {
         Ok(session_list)
     }
 
+    /// List all sessions without their messages, most recent first
+    pub async fn list_session_summaries(&self) -> Vec<SessionSummary> {
+        let sessions = self.sessions.read().await;
+        let mut summaries: Vec<SessionSummary> =
+            sessions.values().map(SessionSummary::from).collect();
+        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries
+    }
+
     /// Get a session by ID
     pub async fn get_session(
         &self,
@@ -186,14 +684,47 @@ impl SessionManager {
         Ok(sessions.get(session_id).cloned())
     }
 
-    /// Create a new session
+    /// Number of messages in a session, or `None` if it isn't stored locally
+    pub async fn message_count(&self, session_id: &str) -> Option<usize> {
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(session_id)
+            .map(|session| session.messages.len())
+    }
+
+    /// Up to `count` messages of a session starting at `start`, cloning only
+    /// those. `None` if the session isn't stored locally.
+    pub async fn message_range(
+        &self,
+        session_id: &str,
+        start: usize,
+        count: usize,
+    ) -> Option<Vec<ChatMessage>> {
+        let sessions = self.sessions.read().await;
+        sessions.get(session_id).map(|session| {
+            session
+                .messages
+                .iter()
+                .skip(start)
+                .take(count)
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Create a new session. The connection profile supplies the model and
+    /// system prompt when the request leaves them out.
     pub async fn create_session(
         &self,
-        request: CreateSessionRequest,
+        mut request: CreateSessionRequest,
     ) -> Result<ChatSession, Box<dyn std::error::Error>> {
         let session_id = Uuid::new_v4().to_string();
         let now = Utc::now();
 
+        let profile = self.api_client.profile().await;
+        request.model_config = request.model_config.or(profile.default_model);
+        request.system_prompt = request.system_prompt.or(profile.system_prompt);
+
         // Generate title from system prompt or use provided title
         let title = request.title.or_else(|| {
             request.system_prompt.as_ref().map(|prompt| {
@@ -220,6 +751,7 @@ impl SessionManager {
                 );
                 meta
             }),
+            connection_id: None,
         };
 
         // Store session locally
@@ -227,12 +759,107 @@ impl SessionManager {
         sessions.insert(session_id.clone(), session.clone());
         drop(sessions);
 
-        // Save to disk
-        self.save_sessions().await?;
+        self.mark_dirty(&session_id).await;
 
         Ok(session)
     }
 
+    /// Store a session created elsewhere, on the server or in another app's
+    /// export. Returns `false`, changing nothing, when its id is taken.
+    pub async fn import_session(
+        &self,
+        session: ChatSession,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut sessions = self.sessions.write().await;
+        if sessions.contains_key(&session.id) {
+            return Ok(false);
+        }
+        let session_id = session.id.clone();
+        let messages = session.messages.clone();
+        sessions.insert(session_id.clone(), session);
+        let turn = self.journal_turn().await;
+        drop(sessions);
+
+        let id = session_id.clone();
+        self.write_journal(turn, move |journal| journal.append(&id, &messages))
+            .await?;
+
+        self.mark_dirty(&session_id).await;
+        Ok(true)
+    }
+
+    /// Add messages exchanged with the server to a stored session. Returns
+    /// `false` when the session is not stored locally.
+    pub async fn append_messages(
+        &self,
+        session_id: &str,
+        messages: &[ChatMessage],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Ok(false);
+        };
+        session.messages.extend_from_slice(messages);
+        if let Some(last) = messages.last() {
+            session.updated_at = session.updated_at.max(last.timestamp);
+        }
+        let turn = self.journal_turn().await;
+        drop(sessions);
+
+        let id = session_id.to_string();
+        let messages = messages.to_vec();
+        self.write_journal(turn, move |journal| journal.append(&id, &messages))
+            .await?;
+
+        self.mark_dirty(session_id).await;
+        Ok(true)
+    }
+
+    /// Replace a stored session's messages with the server's copy. Returns
+    /// `false` when the session is not stored locally.
+    pub async fn replace_messages(
+        &self,
+        session_id: &str,
+        messages: Vec<ChatMessage>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Ok(false);
+        };
+        // Only what differs from the journaled copy is written, so fetching
+        // an unchanged session touches nothing on disk
+        let sync = JournalSync::between(&session.messages, &messages);
+        if sync == JournalSync::Unchanged {
+            return Ok(true);
+        }
+        if let Some(last) = messages.last() {
+            session.updated_at = session.updated_at.max(last.timestamp);
+        }
+        session.messages = messages;
+        let turn = self.journal_turn().await;
+        drop(sessions);
+
+        let id = session_id.to_string();
+        self.write_journal(turn, move |journal| match sync {
+            JournalSync::Patch { appended, updated } => {
+                let compact = !updated.is_empty() && journal.update(&id, &updated)?;
+                if !appended.is_empty() {
+                    journal.append(&id, &appended)?;
+                }
+                if compact {
+                    journal.compact(&id, &journal.load(&id)?.messages)?;
+                }
+                Ok(())
+            }
+            JournalSync::Rewrite(messages) => journal.compact(&id, &messages),
+            JournalSync::Unchanged => Ok(()),
+        })
+        .await?;
+
+        self.mark_dirty(session_id).await;
+        Ok(true)
+    }
+
     /// Send a message in a session
     pub async fn send_message(
         &self,
@@ -257,6 +884,7 @@ impl SessionManager {
             timestamp: now,
             model: request.model_config.as_ref().map(|m| m.model_id.clone()),
             metadata: None,
+            parts: vec![MessagePart::text(request.content.clone())],
         };
 
         // Add user message to session
@@ -265,10 +893,12 @@ impl SessionManager {
 
         // For now, create a simple assistant response
         // In a real implementation, this would call the OpenCode server
+        let reply = format!("Received your message: {}", request.content);
         let assistant_message = ChatMessage {
             id: Uuid::new_v4().to_string(),
             role: MessageRole::Assistant,
-            content: format!("Received your message: {}", request.content),
+            parts: vec![MessagePart::text(reply.clone())],
+            content: reply,
             timestamp: now + chrono::Duration::milliseconds(100),
             model: request.model_config.as_ref().map(|m| m.model_id.clone()),
             metadata: None,
@@ -288,10 +918,14 @@ impl SessionManager {
             session.title = Some(content_preview);
         }
 
+        let turn = self.journal_turn().await;
         drop(sessions);
 
-        // Save to disk
-        self.save_sessions().await?;
+        let id = session_id.to_string();
+        let messages = [user_message, assistant_message.clone()];
+        self.write_journal(turn, move |journal| journal.append(&id, &messages))
+            .await?;
+        self.mark_dirty(session_id).await;
 
         Ok(assistant_message)
     }
@@ -331,11 +965,14 @@ impl SessionManager {
             *current_session = None;
         }
 
+        let turn = self.journal_turn().await;
         drop(sessions);
         drop(current_session);
 
-        // Save to disk
-        self.save_sessions().await?;
+        let id = session_id.to_string();
+        self.write_journal(turn, move |journal| journal.remove(&id))
+            .await?;
+        self.mark_dirty(session_id).await;
 
         Ok(())
     }
@@ -379,8 +1016,7 @@ impl SessionManager {
             session.updated_at = Utc::now();
             drop(sessions);
 
-            // Save to disk
-            self.save_sessions().await?;
+            self.mark_dirty(session_id).await;
             Ok(())
         } else {
             Err(AppError::SessionError {
@@ -391,6 +1027,53 @@ impl SessionManager {
         }
     }
 
+    /// Mirror a server-side revert in local history: messages from `since`
+    /// on are flagged with the checkpoint they were reverted to, and `marker`
+    /// is appended. Returns `false` when the session is not cached locally.
+    pub async fn record_revert(
+        &self,
+        session_id: &str,
+        checkpoint_id: &str,
+        since: DateTime<Utc>,
+        marker: ChatMessage,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Ok(false);
+        };
+
+        let mut flagged = Vec::new();
+        for message in session
+            .messages
+            .iter_mut()
+            .filter(|message| message.timestamp >= since)
+        {
+            message.metadata.get_or_insert_with(HashMap::new).insert(
+                "reverted_to".to_string(),
+                serde_json::Value::from(checkpoint_id),
+            );
+            flagged.push(message.clone());
+        }
+        session.updated_at = marker.timestamp;
+        session.messages.push(marker.clone());
+        let turn = self.journal_turn().await;
+        drop(sessions);
+
+        let id = session_id.to_string();
+        self.write_journal(turn, move |journal| {
+            let compact = journal.update(&id, &flagged)?;
+            journal.append(&id, &[marker])?;
+            if compact {
+                journal.compact(&id, &journal.load(&id)?.messages)?;
+            }
+            Ok(())
+        })
+        .await?;
+
+        self.mark_dirty(session_id).await;
+        Ok(true)
+    }
+
     /// Get session statistics
     pub async fn get_session_stats(
         &self,
@@ -506,6 +1189,60 @@ mod tests {
         assert!(response.content.contains("Hello, world!"));
     }
 
+    #[tokio::test]
+    async fn test_record_revert() {
+        let (manager, _temp) = create_test_session_manager();
+        let session = manager
+            .create_session(CreateSessionRequest {
+                title: None,
+                model_config: None,
+                system_prompt: None,
+            })
+            .await
+            .expect("Should create session");
+        manager
+            .send_message(
+                &session.id,
+                SendMessageRequest {
+                    content: "Try something".to_string(),
+                    model_config: None,
+                    stream: None,
+                },
+            )
+            .await
+            .expect("Should send message");
+
+        let marker = ChatMessage {
+            id: "marker".to_string(),
+            role: MessageRole::System,
+            content: "Reverted".to_string(),
+            timestamp: Utc::now() + chrono::Duration::seconds(1),
+            model: None,
+            metadata: None,
+            parts: vec![MessagePart::text("Reverted")],
+        };
+        let since = Utc::now() - chrono::Duration::seconds(60);
+        let recorded = manager
+            .record_revert(&session.id, "msg_1", since, marker.clone())
+            .await
+            .expect("Should record revert");
+        assert!(recorded);
+
+        let messages = manager.get_session_messages(&session.id).await.unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0].metadata.as_ref().unwrap()["reverted_to"],
+            "msg_1"
+        );
+        assert_eq!(messages[2].id, "marker");
+
+        let unknown = manager
+            .record_revert("missing", "msg_1", since, marker)
+            .await
+            .unwrap();
+        assert!(!unknown);
+    }
+
     #[tokio::test]
     async fn test_session_listing() {
         let (manager, _temp) = create_test_session_manager();
@@ -527,6 +1264,74 @@ mod tests {
         assert_eq!(sessions.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_message_range() {
+        let (manager, _temp) = create_test_session_manager();
+        let session = manager.create_session(test_request("Range")).await.unwrap();
+        for i in 0..3 {
+            manager
+                .send_message(
+                    &session.id,
+                    SendMessageRequest {
+                        content: format!("message {}", i),
+                        model_config: None,
+                        stream: None,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(manager.message_count(&session.id).await, Some(6));
+        let range = manager.message_range(&session.id, 2, 2).await.unwrap();
+        assert_eq!(range.len(), 2);
+        assert_eq!(range[0].content, "message 1");
+        assert!(manager
+            .message_range(&session.id, 5, 10)
+            .await
+            .is_some_and(|range| range.len() == 1));
+        assert!(manager
+            .message_range(&session.id, 10, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(manager.message_count("missing").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_session_summaries() {
+        let (manager, _temp) = create_test_session_manager();
+        let session = manager
+            .create_session(test_request("Summary"))
+            .await
+            .unwrap();
+        let long = format!("first line\n{}", "x".repeat(200));
+        manager
+            .send_message(
+                &session.id,
+                SendMessageRequest {
+                    content: long,
+                    model_config: None,
+                    stream: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        let summaries = manager.list_session_summaries().await;
+        assert_eq!(summaries.len(), 1);
+        let summary = &summaries[0];
+        assert_eq!(summary.title.as_deref(), Some("Summary"));
+        assert_eq!(summary.message_count, 2);
+        assert_eq!(summary.user_message_count, 1);
+        let preview = summary.last_message_preview.as_deref().unwrap();
+        assert!(preview.starts_with("Received your message: first line x"));
+        assert_eq!(preview.chars().count(), PREVIEW_CHARS + 3);
+
+        let json = serde_json::to_value(summary).unwrap();
+        assert!(json.get("messages").is_none());
+    }
+
     #[tokio::test]
     async fn test_session_deletion() {
         let (manager, _temp) = create_test_session_manager();
@@ -563,6 +1368,206 @@ mod tests {
         assert!(found.is_none());
     }
 
+    fn test_request(title: &str) -> CreateSessionRequest {
+        CreateSessionRequest {
+            title: Some(title.to_string()),
+            model_config: None,
+            system_prompt: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_saves_are_debounced() {
+        let (mut manager, temp) = create_test_session_manager();
+        manager.save_delay = Duration::from_millis(50);
+        let sessions_file = temp.path().join("chat_sessions.json");
+
+        let session = manager.create_session(test_request("First")).await.unwrap();
+        manager
+            .update_session_title(&session.id, "Renamed".to_string())
+            .await
+            .unwrap();
+        assert!(!sessions_file.exists());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let reloaded = SessionManager::new(
+            Arc::new(ApiClient::new().unwrap()),
+            temp.path().to_path_buf(),
+        );
+        reloaded.load_sessions().await.unwrap();
+        let saved = reloaded.get_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(saved.title.as_deref(), Some("Renamed"));
+    }
+
+    #[tokio::test]
+    async fn test_save_serializes_only_changed_sessions() {
+        let (manager, temp) = create_test_session_manager();
+        let kept = manager.create_session(test_request("Kept")).await.unwrap();
+        let changed = manager
+            .create_session(test_request("Changed"))
+            .await
+            .unwrap();
+        assert_eq!(manager.writer.flush().await.unwrap(), 2);
+        assert_eq!(manager.writer.flush().await.unwrap(), 0);
+
+        manager
+            .update_session_title(&changed.id, "Changed again".to_string())
+            .await
+            .unwrap();
+        assert_eq!(manager.writer.flush().await.unwrap(), 1);
+
+        manager.delete_session(&kept.id).await.unwrap();
+        manager.save_sessions().await.unwrap();
+        let saved: HashMap<String, ChatSession> = serde_json::from_str(
+            &std::fs::read_to_string(temp.path().join("chat_sessions.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[&changed.id].title.as_deref(), Some("Changed again"));
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_journaled() {
+        let (manager, temp) = create_test_session_manager();
+        let session = manager.create_session(test_request("Chat")).await.unwrap();
+        manager
+            .send_message(
+                &session.id,
+                SendMessageRequest {
+                    content: "Hello".to_string(),
+                    model_config: None,
+                    stream: None,
+                },
+            )
+            .await
+            .unwrap();
+        manager.save_sessions().await.unwrap();
+
+        let saved: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp.path().join("chat_sessions.json")).unwrap(),
+        )
+        .unwrap();
+        assert!(saved[&session.id].get("messages").is_none());
+
+        let reloaded = SessionManager::new(
+            Arc::new(ApiClient::new().unwrap()),
+            temp.path().to_path_buf(),
+        );
+        reloaded.load_sessions().await.unwrap();
+        let messages = reloaded.get_session_messages(&session.id).await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_replace_messages_journals_only_changes() {
+        let (manager, temp) = create_test_session_manager();
+        let session = manager.create_session(test_request("Chat")).await.unwrap();
+        let message = |id: &str, text: &str| ChatMessage {
+            id: id.to_string(),
+            role: MessageRole::User,
+            content: text.to_string(),
+            timestamp: Utc::now(),
+            model: None,
+            metadata: None,
+            parts: vec![MessagePart::text(text)],
+        };
+        let journal_lines = || {
+            std::fs::read_to_string(manager.journal.get_journal_file_path(&session.id))
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        let first = vec![message("m1", "one"), message("m2", "two")];
+        manager
+            .replace_messages(&session.id, first.clone())
+            .await
+            .unwrap();
+        assert_eq!(journal_lines(), 2);
+
+        // Fetching the same history again writes nothing
+        manager
+            .replace_messages(&session.id, first.clone())
+            .await
+            .unwrap();
+        assert_eq!(journal_lines(), 2);
+
+        // An edit and a new message add one record each
+        let second = vec![
+            message("m1", "edited"),
+            first[1].clone(),
+            message("m3", "three"),
+        ];
+        manager
+            .replace_messages(&session.id, second.clone())
+            .await
+            .unwrap();
+        assert_eq!(journal_lines(), 4);
+
+        // Dropping a message needs a rewrite
+        manager
+            .replace_messages(&session.id, second[1..].to_vec())
+            .await
+            .unwrap();
+        assert_eq!(journal_lines(), 2);
+
+        let reloaded = SessionManager::new(
+            Arc::new(ApiClient::new().unwrap()),
+            temp.path().to_path_buf(),
+        );
+        manager.save_sessions().await.unwrap();
+        reloaded.load_sessions().await.unwrap();
+        let contents: Vec<String> = reloaded
+            .get_session_messages(&session.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|message| message.content)
+            .collect();
+        assert_eq!(contents, vec!["two", "three"]);
+    }
+
+    #[tokio::test]
+    async fn test_inline_messages_move_to_journal() {
+        let (manager, temp) = create_test_session_manager();
+        std::fs::write(
+            temp.path().join("chat_sessions.json"),
+            serde_json::json!({
+                "ses_1": {
+                    "id": "ses_1",
+                    "title": "Old",
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "updated_at": "2025-01-01T00:00:00Z",
+                    "messages": [{
+                        "id": "m1",
+                        "role": "User",
+                        "content": "Hi",
+                        "timestamp": "2025-01-01T00:00:00Z",
+                        "parts": [{ "type": "text", "text": "Hi" }]
+                    }]
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        manager.load_sessions().await.unwrap();
+        assert_eq!(
+            manager.get_session_messages("ses_1").await.unwrap().len(),
+            1
+        );
+        assert!(temp.path().join("sessions/ses_1.jsonl").exists());
+
+        manager.save_sessions().await.unwrap();
+        let saved: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp.path().join("chat_sessions.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved["ses_1"]["title"], "Old");
+        assert!(saved["ses_1"].get("messages").is_none());
+    }
+
     #[tokio::test]
     async fn test_current_session_management() {
         let (manager, _temp) = create_test_session_manager();
@@ -665,6 +1670,7 @@ mod tests {
             timestamp: Utc::now(),
             model: Some("gpt-4".to_string()),
             metadata: None,
+            parts: vec![MessagePart::text("Hello")],
         };
 
         assert_eq!(message.id, "test-id");
@@ -672,4 +1678,61 @@ mod tests {
         assert_eq!(message.content, "Hello");
         assert_eq!(message.model, Some("gpt-4".to_string()));
     }
+
+    #[test]
+    fn test_message_parts_from_server() {
+        let parts: Vec<MessagePart> = [
+            serde_json::json!({ "type": "step-start" }),
+            serde_json::json!({ "type": "reasoning", "text": "Thinking" }),
+            serde_json::json!({
+                "type": "tool",
+                "callID": "call_1",
+                "tool": "bash",
+                "state": { "status": "completed", "input": { "command": "ls" }, "output": "a.txt" },
+            }),
+            serde_json::json!({
+                "type": "tool",
+                "callID": "call_2",
+                "tool": "read",
+                "state": { "status": "error", "input": {}, "error": "not found" },
+            }),
+            serde_json::json!({ "type": "file", "mime": "image/png", "url": "data:image/png;base64,AA" }),
+            serde_json::json!({ "type": "text", "text": "Done" }),
+        ]
+        .iter()
+        .flat_map(MessagePart::from_server)
+        .collect();
+
+        assert_eq!(parts.len(), 7);
+        assert_eq!(
+            parts[2],
+            MessagePart::ToolResult {
+                call_id: "call_1".to_string(),
+                output: "a.txt".to_string(),
+                is_error: false,
+            }
+        );
+        assert!(matches!(
+            &parts[4],
+            MessagePart::ToolResult { is_error: true, .. }
+        ));
+        assert!(matches!(&parts[5], MessagePart::Image { .. }));
+        assert_eq!(text_content(&parts), "Done");
+
+        let json = serde_json::to_value(&parts[1]).unwrap();
+        assert_eq!(json["type"], "tool_call");
+        assert_eq!(
+            serde_json::from_value::<MessagePart>(json).unwrap(),
+            parts[1]
+        );
+    }
+
+    #[test]
+    fn test_message_without_parts_deserializes() {
+        let message: ChatMessage = serde_json::from_str(
+            r#"{"id":"m1","role":"User","content":"Hi","timestamp":"2025-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert!(message.parts.is_empty());
+    }
 }
@@ -0,0 +1,179 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Tracks which session each window shows so session-scoped events can be
+//! delivered only to the windows displaying that session.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+#[cfg(desktop)]
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// Prefix of windows opened for a single session
+pub const SESSION_WINDOW_PREFIX: &str = "session-";
+
+/// Window showing a session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionWindow {
+    pub label: String,
+    pub session_id: String,
+}
+
+/// Label for a session's own window. Window labels only allow a limited
+/// character set, so anything else in the id is replaced.
+pub fn window_label(session_id: &str) -> String {
+    let sanitized: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", SESSION_WINDOW_PREFIX, sanitized)
+}
+
+/// Window label → displayed session
+#[derive(Clone, Default)]
+pub struct SessionWindows {
+    assignments: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl SessionWindows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        match self.assignments.lock() {
+            Ok(assignments) => assignments,
+            Err(poisoned) => {
+                eprintln!("[ERROR] SessionWindows: assignments mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Record that `label` now shows `session_id`
+    pub fn assign(&self, label: &str, session_id: &str) {
+        self.lock()
+            .insert(label.to_string(), session_id.to_string());
+    }
+
+    /// Forget a window, e.g. once it is closed. Returns the session it showed.
+    pub fn release(&self, label: &str) -> Option<String> {
+        self.lock().remove(label)
+    }
+
+    /// Forget every window showing a deleted session
+    pub fn release_session(&self, session_id: &str) {
+        self.lock().retain(|_, shown| shown != session_id);
+    }
+
+    pub fn session_for(&self, label: &str) -> Option<String> {
+        self.lock().get(label).cloned()
+    }
+
+    /// Labels of the windows displaying `session_id`
+    pub fn windows_showing(&self, session_id: &str) -> Vec<String> {
+        self.lock()
+            .iter()
+            .filter(|(_, shown)| shown.as_str() == session_id)
+            .map(|(label, _)| label.clone())
+            .collect()
+    }
+
+    pub fn list(&self) -> Vec<SessionWindow> {
+        self.lock()
+            .iter()
+            .map(|(label, session_id)| SessionWindow {
+                label: label.clone(),
+                session_id: session_id.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Open a window for `session_id`, or focus it if it is already open.
+/// Returns the window label.
+#[cfg(desktop)]
+pub fn open_window(
+    app_handle: &AppHandle,
+    windows: &SessionWindows,
+    session_id: &str,
+    title: &str,
+) -> tauri::Result<String> {
+    let label = window_label(session_id);
+    if let Some(window) = app_handle.get_webview_window(&label) {
+        window.show()?;
+        window.unminimize()?;
+        window.set_focus()?;
+        return Ok(label);
+    }
+
+    let url = format!("chat?session={}", urlencoding::encode(session_id));
+    WebviewWindowBuilder::new(app_handle, &label, WebviewUrl::App(url.into()))
+        .title(title)
+        .inner_size(900.0, 700.0)
+        .min_inner_size(480.0, 360.0)
+        .focused(true)
+        .build()?;
+    windows.assign(&label, session_id);
+    Ok(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_label_is_sanitized() {
+        assert_eq!(window_label("ses_abc-1"), "session-ses_abc-1");
+        assert_eq!(window_label("a/b c"), "session-a_b_c");
+    }
+
+    #[test]
+    fn test_routes_follow_assignments() {
+        let windows = SessionWindows::new();
+        windows.assign("main", "ses_1");
+        windows.assign("session-ses_2", "ses_2");
+        windows.assign("session-ses_1", "ses_1");
+
+        assert_eq!(
+            windows.windows_showing("ses_1"),
+            vec!["main".to_string(), "session-ses_1".to_string()]
+        );
+        assert!(windows.windows_showing("ses_3").is_empty());
+
+        windows.assign("main", "ses_2");
+        assert_eq!(windows.windows_showing("ses_1"), vec!["session-ses_1"]);
+
+        assert_eq!(windows.release("session-ses_1").as_deref(), Some("ses_1"));
+        assert!(windows.windows_showing("ses_1").is_empty());
+
+        windows.release_session("ses_2");
+        assert!(windows.list().is_empty());
+    }
+}
@@ -0,0 +1,899 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::audit_log::{self, AuditAction};
+use crate::error::{AppError, RetryConfig};
+use crate::model_manager::ModelPreferences;
+use crate::updater::UpdateChannel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Current layout of `settings.json`; older files are migrated on load
+pub const SETTINGS_SCHEMA_VERSION: u32 = 1;
+
+/// Frontend event carrying the (redacted) settings after every change
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Standalone preference files folded into `settings.json` by schema 1
+const LEGACY_MODEL_PREFERENCES_FILE: &str = "model_preferences.json";
+
+/// Application-wide settings persisted in `settings.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Files written before versioning have no field and count as 0
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Language for backend-generated messages, e.g. `en` or `es`
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Spending limits and alerts
+    #[serde(default)]
+    pub budgets: crate::budgets::BudgetSettings,
+    #[serde(default)]
+    pub logging: LoggingSettings,
+    /// Localhost Prometheus endpoint
+    #[serde(default)]
+    pub metrics: crate::metrics::MetricsSettings,
+    /// Default model and per-model overrides
+    #[serde(default)]
+    pub models: ModelPreferences,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// Enabled plugins and the permissions granted to them
+    #[serde(default)]
+    pub plugins: PluginSettings,
+    #[serde(default)]
+    pub privacy: PrivacySettings,
+    /// Proxy for reaching OpenCode servers
+    #[serde(default)]
+    pub proxy: crate::proxy::ProxySettings,
+    #[serde(default)]
+    pub quick_prompt: QuickPromptSettings,
+    #[serde(default)]
+    pub retry: RetrySettings,
+    #[serde(default)]
+    pub security: SecuritySettings,
+    /// First-run wizard progress
+    #[serde(default)]
+    pub setup: crate::setup::SetupSettings,
+    /// Speech to text for the prompt box
+    #[serde(default)]
+    pub transcription: crate::transcription::TranscriptionSettings,
+    #[serde(default)]
+    pub updates: UpdateSettings,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            schema_version: SETTINGS_SCHEMA_VERSION,
+            locale: default_locale(),
+            budgets: crate::budgets::BudgetSettings::default(),
+            logging: LoggingSettings::default(),
+            metrics: crate::metrics::MetricsSettings::default(),
+            models: ModelPreferences::default(),
+            notifications: NotificationSettings::default(),
+            plugins: PluginSettings::default(),
+            privacy: PrivacySettings::default(),
+            proxy: crate::proxy::ProxySettings::default(),
+            quick_prompt: QuickPromptSettings::default(),
+            retry: RetrySettings::default(),
+            security: SecuritySettings::default(),
+            setup: crate::setup::SetupSettings::default(),
+            transcription: crate::transcription::TranscriptionSettings::default(),
+            updates: UpdateSettings::default(),
+        }
+    }
+}
+
+fn default_locale() -> String {
+    crate::i18n::DEFAULT_LOCALE.to_string()
+}
+
+impl AppSettings {
+    /// Copy safe to hand to the frontend: PIN hashes and key material are
+    /// blanked, while the sections stay present so the UI can tell that
+    /// the app lock or profile encryption is enabled
+    pub fn redacted(&self) -> Self {
+        let mut settings = self.clone();
+        if let Some(app_lock) = settings.security.app_lock.as_mut() {
+            app_lock.pin_hash.clear();
+        }
+        if let Some(profile) = settings.security.encrypted_profile.as_mut() {
+            profile.salt.clear();
+            profile.verifier.clear();
+        }
+        settings
+    }
+
+    /// Take the user-editable sections from `incoming`. The schema version,
+    /// security and plugin sections are kept, since those are only changed
+    /// through the PIN, encryption and plugin permission commands.
+    pub fn merge_user_settings(&self, incoming: AppSettings) -> Self {
+        Self {
+            schema_version: self.schema_version,
+            plugins: self.plugins.clone(),
+            security: self.security.clone(),
+            ..incoming
+        }
+    }
+}
+
+/// Logging verbosity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingSettings {
+    /// Global level: trace, debug, info, warn or error
+    pub level: String,
+    /// Per-target overrides, e.g. `stream = "debug"`, `health = "warn"`
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+    /// Optional forwarding to an external log collector
+    #[serde(default)]
+    pub remote: RemoteLogSettings,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            targets: BTreeMap::new(),
+            remote: RemoteLogSettings::default(),
+        }
+    }
+}
+
+/// Wire format used by the remote log sink
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteLogProtocol {
+    /// Grafana Loki push API (`http(s)://host:3100/loki/api/v1/push`)
+    #[default]
+    Loki,
+    /// RFC 5424 syslog over UDP (`udp://host:514`)
+    Syslog,
+}
+
+/// Remote log forwarding, disabled by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteLogSettings {
+    pub enabled: bool,
+    pub protocol: RemoteLogProtocol,
+    pub endpoint: String,
+    /// Minimum level forwarded
+    pub level: String,
+    /// Extra Loki stream labels, e.g. `host = "homelab"`
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl Default for RemoteLogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protocol: RemoteLogProtocol::Loki,
+            endpoint: String::new(),
+            level: "info".to_string(),
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+/// OS notifications raised while the window is in the background
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub response_completed: bool,
+    pub response_failed: bool,
+    /// Show the start of the response or error; off keeps content off the
+    /// lock screen
+    pub include_snippet: bool,
+    /// Do not disturb: hold back every notification until turned off
+    pub focus_mode: bool,
+    pub quiet_hours: QuietHours,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            response_completed: true,
+            response_failed: true,
+            include_snippet: true,
+            focus_mode: false,
+            quiet_hours: QuietHours::default(),
+        }
+    }
+}
+
+/// Daily window, in local `HH:MM` time, in which notifications are
+/// suppressed. `start` after `end` wraps past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    pub start: String,
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        }
+    }
+}
+
+/// Plugins the user has enabled, keyed by plugin id
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PluginSettings {
+    pub grants: BTreeMap<String, PluginGrant>,
+}
+
+/// Whether a plugin runs and which of its requested permissions it holds
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PluginGrant {
+    pub enabled: bool,
+    pub permissions: Vec<crate::plugins::PluginPermission>,
+}
+
+/// Floating prompt window that sends to a scratchpad session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QuickPromptSettings {
+    /// Accelerator such as `CmdOrCtrl+Shift+Space`
+    pub shortcut: String,
+    /// Session quick prompts go to; created on first use
+    pub scratchpad_session_id: Option<String>,
+}
+
+impl Default for QuickPromptSettings {
+    fn default() -> Self {
+        Self {
+            shortcut: crate::quick_prompt::DEFAULT_SHORTCUT.to_string(),
+            scratchpad_session_id: None,
+        }
+    }
+}
+
+/// Crash and error reporting consent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacySettings {
+    /// `None` until the user has answered the first-run prompt
+    #[serde(default)]
+    pub error_reporting_enabled: Option<bool>,
+    /// Attach personally identifiable data (IP address, etc.) to reports
+    #[serde(default)]
+    pub error_reporting_pii: bool,
+    /// What to do when an outgoing prompt looks like it contains secrets
+    #[serde(default)]
+    pub secret_scanning: SecretScanMode,
+    /// Upload anonymous feature usage counts; off unless the user opts in
+    #[serde(default)]
+    pub telemetry_enabled: bool,
+}
+
+/// Pre-send secret scanning behaviour
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretScanMode {
+    #[default]
+    Off,
+    /// Refuse until the user confirms sending anyway
+    Confirm,
+    /// Always refuse
+    Block,
+}
+
+/// Backoff parameters for a class of network operations
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay_ms: 1000,
+            max_delay_ms: 30000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Largest `max_attempts` accepted from the frontend
+    pub const MAX_ATTEMPTS: u32 = 10;
+
+    /// Check that the policy is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 || self.max_attempts > Self::MAX_ATTEMPTS {
+            return Err(format!(
+                "max_attempts must be between 1 and {}",
+                Self::MAX_ATTEMPTS
+            ));
+        }
+        if self.base_delay_ms > self.max_delay_ms {
+            return Err("base_delay_ms must not exceed max_delay_ms".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err("jitter must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl From<&RetryPolicy> for RetryConfig {
+    fn from(policy: &RetryPolicy) -> Self {
+        Self {
+            max_retries: policy.max_attempts.saturating_sub(1),
+            initial_delay_ms: policy.base_delay_ms,
+            max_delay_ms: policy.max_delay_ms,
+            backoff_multiplier: 2.0,
+            jitter: policy.jitter,
+        }
+    }
+}
+
+/// Network operations that may override the global retry policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOperation {
+    ConnectionTest,
+    MessageSend,
+}
+
+/// Global retry policy with optional per-operation overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RetrySettings {
+    #[serde(default)]
+    pub global: RetryPolicy,
+    #[serde(default)]
+    pub connection_test: Option<RetryPolicy>,
+    #[serde(default)]
+    pub message_send: Option<RetryPolicy>,
+}
+
+impl RetrySettings {
+    /// Policy in effect for an operation
+    pub fn policy_for(&self, operation: RetryOperation) -> &RetryPolicy {
+        let policy = match operation {
+            RetryOperation::ConnectionTest => self.connection_test.as_ref(),
+            RetryOperation::MessageSend => self.message_send.as_ref(),
+        };
+        policy.unwrap_or(&self.global)
+    }
+
+    /// Check the global policy and every override
+    pub fn validate(&self) -> Result<(), String> {
+        self.global
+            .validate()
+            .map_err(|e| format!("global: {}", e))?;
+        if let Some(policy) = &self.connection_test {
+            policy
+                .validate()
+                .map_err(|e| format!("connection_test: {}", e))?;
+        }
+        if let Some(policy) = &self.message_send {
+            policy
+                .validate()
+                .map_err(|e| format!("message_send: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Application update checks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpdateSettings {
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    /// Look for a new version each time the app starts
+    #[serde(default = "default_check_on_startup")]
+    pub check_on_startup: bool,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::default(),
+            check_on_startup: default_check_on_startup(),
+        }
+    }
+}
+
+fn default_check_on_startup() -> bool {
+    true
+}
+
+/// Local data protection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecuritySettings {
+    /// Set when the profile is stored in an encrypted container
+    #[serde(default)]
+    pub encrypted_profile: Option<EncryptedProfileSettings>,
+    /// Set when the app locks itself after a period of inactivity
+    #[serde(default)]
+    pub app_lock: Option<AppLockSettings>,
+}
+
+/// Idle lock configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppLockSettings {
+    pub idle_timeout_minutes: u32,
+    /// Argon2 PHC string of the PIN or passphrase
+    pub pin_hash: String,
+}
+
+/// Argon2id parameters for the profile key. The verifier is a known value
+/// encrypted with the key, used to reject a wrong passphrase up front.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedProfileSettings {
+    pub salt: Vec<u8>,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub verifier: Vec<u8>,
+}
+
+/// Loads, caches and persists `AppSettings`
+pub struct SettingsManager {
+    config_dir: PathBuf,
+    settings: Arc<RwLock<AppSettings>>,
+    change_sender: broadcast::Sender<AppSettings>,
+}
+
+impl SettingsManager {
+    /// Create a settings manager with default values
+    pub fn new(config_dir: PathBuf) -> Self {
+        let (change_sender, _) = broadcast::channel(16);
+        Self {
+            config_dir,
+            settings: Arc::new(RwLock::new(AppSettings::default())),
+            change_sender,
+        }
+    }
+
+    /// Get settings file path
+    fn get_settings_file_path(&self) -> PathBuf {
+        self.config_dir.join("settings.json")
+    }
+
+    /// Load settings from disk, keeping defaults if the file does not exist.
+    /// Files from an older schema are migrated and written back.
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings_file = self.get_settings_file_path();
+
+        if !settings_file.exists() {
+            // A fresh install may still have preference files from before
+            // settings.json existed
+            if self.config_dir.join(LEGACY_MODEL_PREFERENCES_FILE).exists() {
+                return self.load_value(serde_json::json!({}));
+            }
+            return Ok(());
+        }
+
+        let settings_json =
+            std::fs::read_to_string(&settings_file).map_err(|e| AppError::FileSystemError {
+                path: settings_file.to_string_lossy().to_string(),
+                message: "Failed to read settings file".to_string(),
+                details: e.to_string(),
+            })?;
+
+        let value: serde_json::Value =
+            serde_json::from_str(&settings_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse settings file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        self.load_value(value)
+    }
+
+    /// Migrate a parsed settings document and make it current
+    fn load_value(&self, mut value: serde_json::Value) -> Result<(), Box<dyn std::error::Error>> {
+        let migration = migrate(&mut value, &self.config_dir)?;
+
+        let loaded: AppSettings =
+            serde_json::from_value(value).map_err(|e| AppError::ParseError {
+                message: "Failed to parse settings file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        self.replace(loaded);
+
+        if let Some(retired) = migration {
+            self.save()?;
+            // Only retire the old files once their contents are safely saved
+            for path in retired {
+                let mut migrated = path.clone().into_os_string();
+                migrated.push(".migrated");
+                if let Err(e) = std::fs::rename(&path, migrated) {
+                    eprintln!(
+                        "[WARN] Failed to retire migrated settings file {}: {}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Swap in a freshly loaded settings value
+    fn replace(&self, loaded: AppSettings) {
+        let mut settings = match self.settings.write() {
+            Ok(settings) => settings,
+            Err(poisoned) => {
+                eprintln!("[ERROR] SettingsManager load: settings RwLock poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        };
+        *settings = loaded;
+    }
+
+    /// Save settings to disk
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings_json =
+            serde_json::to_string_pretty(&self.get()).map_err(|e| AppError::ParseError {
+                message: "Failed to serialize settings".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        std::fs::write(self.get_settings_file_path(), settings_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_settings_file_path().to_string_lossy().to_string(),
+                message: "Failed to write settings file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// Get a snapshot of the current settings
+    pub fn get(&self) -> AppSettings {
+        match self.settings.read() {
+            Ok(settings) => settings.clone(),
+            Err(poisoned) => {
+                eprintln!("[ERROR] SettingsManager get: settings RwLock poisoned, recovering...");
+                poisoned.into_inner().clone()
+            }
+        }
+    }
+
+    /// Apply a change to the settings and persist the result
+    pub fn update<F>(&self, change: F) -> Result<AppSettings, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut AppSettings),
+    {
+        let (previous, updated) = {
+            let mut settings = match self.settings.write() {
+                Ok(settings) => settings,
+                Err(poisoned) => {
+                    eprintln!(
+                        "[ERROR] SettingsManager update: settings RwLock poisoned, recovering..."
+                    );
+                    poisoned.into_inner()
+                }
+            };
+            let previous = settings.clone();
+            change(&mut settings);
+            (previous, settings.clone())
+        };
+
+        self.save()?;
+        let changed = changed_sections(&previous, &updated);
+        if !changed.is_empty() {
+            audit_log::record(
+                AuditAction::SettingsChanged,
+                "settings",
+                Some(changed.join(", ")),
+            );
+            // No receivers just means nobody is listening yet
+            let _ = self.change_sender.send(updated.clone());
+        }
+        Ok(updated)
+    }
+
+    /// Subscribe to settings after each change that altered them
+    pub fn subscribe(&self) -> broadcast::Receiver<AppSettings> {
+        self.change_sender.subscribe()
+    }
+}
+
+/// Bring a settings document up to `SETTINGS_SCHEMA_VERSION`. Returns the
+/// legacy files folded in along the way, or `None` if the document was
+/// already current. Documents from a newer version are left alone.
+fn migrate(
+    value: &mut serde_json::Value,
+    config_dir: &Path,
+) -> Result<Option<Vec<PathBuf>>, AppError> {
+    let Some(document) = value.as_object_mut() else {
+        return Err(AppError::ParseError {
+            message: "Settings file is not a JSON object".to_string(),
+            details: None,
+        });
+    };
+    let version = document
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let mut retired = Vec::new();
+
+    if version < 1 {
+        // 0 -> 1: model preferences move from their own file into `models`
+        let legacy = config_dir.join(LEGACY_MODEL_PREFERENCES_FILE);
+        if legacy.exists() {
+            let contents =
+                std::fs::read_to_string(&legacy).map_err(|e| AppError::FileSystemError {
+                    path: legacy.to_string_lossy().to_string(),
+                    message: "Failed to read legacy model preferences".to_string(),
+                    details: e.to_string(),
+                })?;
+            let preferences: ModelPreferences =
+                serde_json::from_str(&contents).map_err(|e| AppError::ParseError {
+                    message: "Failed to parse legacy model preferences".to_string(),
+                    details: Some(e.to_string()),
+                })?;
+            document.insert(
+                "models".to_string(),
+                serde_json::to_value(preferences).unwrap_or_default(),
+            );
+            retired.push(legacy);
+        }
+    }
+
+    if version < u64::from(SETTINGS_SCHEMA_VERSION) {
+        document.insert(
+            "schema_version".to_string(),
+            serde_json::Value::from(SETTINGS_SCHEMA_VERSION),
+        );
+        return Ok(Some(retired));
+    }
+
+    Ok(None)
+}
+
+/// Top-level settings sections that differ, for the audit log. Only names
+/// are reported so values such as PIN hashes never leave the settings file.
+fn changed_sections(previous: &AppSettings, updated: &AppSettings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(previous)), Ok(serde_json::Value::Object(updated))) = (
+        serde_json::to_value(previous),
+        serde_json::to_value(updated),
+    ) else {
+        return Vec::new();
+    };
+    updated
+        .iter()
+        .filter(|(section, value)| previous.get(*section) != Some(*value))
+        .map(|(section, _)| section.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_settings_manager() -> (SettingsManager, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let manager = SettingsManager::new(temp_dir.path().to_path_buf());
+        (manager, temp_dir)
+    }
+
+    #[test]
+    fn test_defaults_without_file() {
+        let (manager, _temp) = create_test_settings_manager();
+        manager.load().expect("Should load defaults");
+        assert_eq!(manager.get().logging.level, "info");
+    }
+
+    #[test]
+    fn test_update_persists_settings() {
+        let (manager, temp) = create_test_settings_manager();
+        manager
+            .update(|settings| settings.logging.level = "debug".to_string())
+            .expect("Should update settings");
+
+        let reloaded = SettingsManager::new(temp.path().to_path_buf());
+        reloaded.load().expect("Should load saved settings");
+        assert_eq!(reloaded.get().logging.level, "debug");
+    }
+
+    #[test]
+    fn test_changed_sections() {
+        let previous = AppSettings::default();
+        let mut updated = previous.clone();
+        assert!(changed_sections(&previous, &updated).is_empty());
+
+        updated.logging.level = "debug".to_string();
+        updated.locale = "fr".to_string();
+        assert_eq!(
+            changed_sections(&previous, &updated),
+            vec!["locale", "logging"]
+        );
+    }
+
+    #[test]
+    fn test_missing_sections_use_defaults() {
+        let (manager, temp) = create_test_settings_manager();
+        std::fs::write(temp.path().join("settings.json"), "{}").unwrap();
+
+        manager.load().expect("Should load partial settings");
+        assert_eq!(manager.get().locale, "en");
+        assert_eq!(manager.get().logging.level, "info");
+        assert!(manager.get().logging.targets.is_empty());
+        assert!(!manager.get().logging.remote.enabled);
+        assert_eq!(manager.get().privacy.error_reporting_enabled, None);
+        assert!(!manager.get().privacy.error_reporting_pii);
+        assert_eq!(manager.get().retry, RetrySettings::default());
+    }
+
+    #[test]
+    fn test_migrates_legacy_model_preferences() {
+        let (manager, temp) = create_test_settings_manager();
+        std::fs::write(temp.path().join("settings.json"), r#"{"locale":"de"}"#).unwrap();
+        std::fs::write(
+            temp.path().join(LEGACY_MODEL_PREFERENCES_FILE),
+            r#"{"default_provider":"anthropic","default_model":"claude","custom_settings":{}}"#,
+        )
+        .unwrap();
+
+        manager.load().expect("Should migrate settings");
+        let settings = manager.get();
+        assert_eq!(settings.schema_version, SETTINGS_SCHEMA_VERSION);
+        assert_eq!(settings.locale, "de");
+        assert_eq!(settings.models.default_model.as_deref(), Some("claude"));
+        assert!(!temp.path().join(LEGACY_MODEL_PREFERENCES_FILE).exists());
+        assert!(temp.path().join("model_preferences.json.migrated").exists());
+
+        let reloaded = SettingsManager::new(temp.path().to_path_buf());
+        reloaded.load().expect("Should load migrated settings");
+        assert_eq!(
+            reloaded.get().models.default_provider.as_deref(),
+            Some("anthropic")
+        );
+    }
+
+    #[test]
+    fn test_newer_schema_is_not_downgraded() {
+        let (manager, temp) = create_test_settings_manager();
+        std::fs::write(
+            temp.path().join("settings.json"),
+            r#"{"schema_version":99,"locale":"fr"}"#,
+        )
+        .unwrap();
+
+        manager.load().expect("Should load newer settings");
+        assert_eq!(manager.get().schema_version, 99);
+        assert_eq!(manager.get().locale, "fr");
+    }
+
+    #[test]
+    fn test_update_notifies_subscribers() {
+        let (manager, _temp) = create_test_settings_manager();
+        let mut receiver = manager.subscribe();
+
+        manager
+            .update(|settings| settings.locale = "es".to_string())
+            .expect("Should update settings");
+        assert_eq!(receiver.try_recv().expect("Should notify").locale, "es");
+
+        // Saving identical settings is not a change
+        manager.update(|_| {}).expect("Should update settings");
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_redacted_and_merge_keep_security() {
+        let mut current = AppSettings::default();
+        current.security.app_lock = Some(AppLockSettings {
+            idle_timeout_minutes: 5,
+            pin_hash: "$argon2id$secret".to_string(),
+        });
+
+        let redacted = current.redacted();
+        assert_eq!(redacted.security.app_lock.as_ref().unwrap().pin_hash, "");
+        assert_eq!(
+            redacted
+                .security
+                .app_lock
+                .as_ref()
+                .unwrap()
+                .idle_timeout_minutes,
+            5
+        );
+
+        let mut incoming = redacted;
+        incoming.locale = "de".to_string();
+        incoming.security.app_lock = None;
+        incoming.plugins.grants.insert(
+            "wiki-sync".to_string(),
+            PluginGrant {
+                enabled: true,
+                permissions: vec![crate::plugins::PluginPermission::ReadMessages],
+            },
+        );
+        let merged = current.merge_user_settings(incoming);
+        assert_eq!(merged.locale, "de");
+        assert_eq!(merged.security.app_lock, current.security.app_lock);
+        assert!(merged.plugins.grants.is_empty());
+    }
+
+    #[test]
+    fn test_retry_overrides() {
+        let settings = RetrySettings {
+            message_send: Some(RetryPolicy {
+                max_attempts: 1,
+                ..RetryPolicy::default()
+            }),
+            ..RetrySettings::default()
+        };
+
+        assert_eq!(
+            settings.policy_for(RetryOperation::ConnectionTest),
+            &settings.global
+        );
+        let config = RetryConfig::from(settings.policy_for(RetryOperation::MessageSend));
+        assert_eq!(config.max_retries, 0);
+    }
+
+    #[test]
+    fn test_retry_policy_validation() {
+        assert!(RetrySettings::default().validate().is_ok());
+
+        let invalid = RetrySettings {
+            connection_test: Some(RetryPolicy {
+                jitter: 1.5,
+                ..RetryPolicy::default()
+            }),
+            ..RetrySettings::default()
+        };
+        assert!(invalid
+            .validate()
+            .unwrap_err()
+            .starts_with("connection_test"));
+
+        let zero_attempts = RetryPolicy {
+            max_attempts: 0,
+            ..RetryPolicy::default()
+        };
+        assert!(zero_attempts.validate().is_err());
+    }
+}
@@ -0,0 +1,234 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! First-run connection wizard. The app no longer starts servers or
+//! tunnels itself, so setup detects an OpenCode server already running on
+//! this machine and otherwise guides the user to a remote or tunnelled one.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Ports probed for a local `opencode serve`
+pub const LOCAL_SERVER_PORTS: [u16; 2] = [4096, 4097];
+
+/// How long each local probe may take
+const PROBE_TIMEOUT: Duration = Duration::from_millis(750);
+
+/// Command suggested when no local server was found
+pub const LOCAL_SERVER_COMMAND: &str = "opencode serve --port 4096";
+
+/// How the user chose to connect
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupMethod {
+    /// A server on this machine
+    Local,
+    /// A server reached directly over the network
+    Remote,
+    /// A server behind a tunnel such as Cloudflare Tunnel or Tailscale Funnel
+    Tunnel,
+}
+
+/// Wizard progress stored in settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SetupSettings {
+    pub completed: bool,
+    pub method: Option<SetupMethod>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// OpenCode server answering on this machine
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DetectedServer {
+    pub url: String,
+    pub port: u16,
+}
+
+/// Choice offered on the first setup screen
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SetupOption {
+    pub method: SetupMethod,
+    /// Whether the option can be used right away
+    pub available: bool,
+    /// Server to prefill, when one is known
+    pub suggested_url: Option<String>,
+    /// Guidance shown under the option, e.g. how to start a local server
+    pub hint: Option<String>,
+}
+
+/// Everything the wizard needs to render
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupState {
+    /// Show the wizard: setup was never completed and nothing is saved
+    pub needs_setup: bool,
+    pub completed: bool,
+    pub method: Option<SetupMethod>,
+    pub has_saved_connections: bool,
+    pub local_servers: Vec<DetectedServer>,
+    pub options: Vec<SetupOption>,
+}
+
+impl SetupState {
+    /// Combine stored progress with what was detected
+    pub fn new(
+        settings: &SetupSettings,
+        has_saved_connections: bool,
+        local_servers: Vec<DetectedServer>,
+    ) -> Self {
+        let options = vec![
+            SetupOption {
+                method: SetupMethod::Local,
+                available: !local_servers.is_empty(),
+                suggested_url: local_servers.first().map(|server| server.url.clone()),
+                hint: local_servers
+                    .is_empty()
+                    .then(|| format!("Start a server with `{}`", LOCAL_SERVER_COMMAND)),
+            },
+            SetupOption {
+                method: SetupMethod::Remote,
+                available: true,
+                suggested_url: None,
+                hint: Some("Enter the address of an OpenCode server on your network".to_string()),
+            },
+            SetupOption {
+                method: SetupMethod::Tunnel,
+                available: true,
+                suggested_url: None,
+                hint: Some(
+                    "Enter the public HTTPS address of your Cloudflare Tunnel or Tailscale Funnel"
+                        .to_string(),
+                ),
+            },
+        ];
+
+        Self {
+            needs_setup: !settings.completed && !has_saved_connections,
+            completed: settings.completed,
+            method: settings.method,
+            has_saved_connections,
+            local_servers,
+            options,
+        }
+    }
+}
+
+/// Whether `url` is acceptable for `method`. Tunnels are public, so they
+/// must use HTTPS; local servers must be on a loopback address.
+pub fn validate_server_url(method: SetupMethod, url: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("Invalid server URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Server URL must start with http:// or https://".to_string());
+    }
+    let host = parsed.host_str().ok_or("Server URL has no host")?;
+    match method {
+        SetupMethod::Local if !matches!(host, "localhost" | "127.0.0.1" | "::1" | "[::1]") => {
+            Err("A local server must use localhost or 127.0.0.1".to_string())
+        }
+        SetupMethod::Tunnel if parsed.scheme() != "https" => {
+            Err("Tunnel addresses must use https://".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Probe the usual ports for a running OpenCode server
+pub async fn detect_local_servers() -> Vec<DetectedServer> {
+    let Ok(client) = reqwest::Client::builder().timeout(PROBE_TIMEOUT).build() else {
+        return Vec::new();
+    };
+    let probes = LOCAL_SERVER_PORTS.iter().map(|port| {
+        let client = client.clone();
+        async move {
+            let url = format!("http://127.0.0.1:{}", port);
+            let response = client.get(format!("{}/session", url)).send().await.ok()?;
+            response
+                .status()
+                .is_success()
+                .then_some(DetectedServer { url, port: *port })
+        }
+    });
+    futures_util::future::join_all(probes)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh_install_needs_setup() {
+        let state = SetupState::new(&SetupSettings::default(), false, Vec::new());
+        assert!(state.needs_setup);
+        let local = &state.options[0];
+        assert_eq!(local.method, SetupMethod::Local);
+        assert!(!local.available);
+        assert!(local
+            .hint
+            .as_deref()
+            .unwrap()
+            .contains(LOCAL_SERVER_COMMAND));
+    }
+
+    #[test]
+    fn test_detected_server_is_suggested() {
+        let detected = vec![DetectedServer {
+            url: "http://127.0.0.1:4096".to_string(),
+            port: 4096,
+        }];
+        let state = SetupState::new(&SetupSettings::default(), false, detected);
+        let local = &state.options[0];
+        assert!(local.available);
+        assert_eq!(
+            local.suggested_url.as_deref(),
+            Some("http://127.0.0.1:4096")
+        );
+        assert!(local.hint.is_none());
+    }
+
+    #[test]
+    fn test_existing_users_skip_setup() {
+        let state = SetupState::new(&SetupSettings::default(), true, Vec::new());
+        assert!(!state.needs_setup);
+
+        let completed = SetupSettings {
+            completed: true,
+            method: Some(SetupMethod::Remote),
+            completed_at: Some(Utc::now()),
+        };
+        assert!(!SetupState::new(&completed, false, Vec::new()).needs_setup);
+    }
+
+    #[test]
+    fn test_validate_server_url() {
+        assert!(validate_server_url(SetupMethod::Local, "http://localhost:4096").is_ok());
+        assert!(validate_server_url(SetupMethod::Local, "http://192.168.1.5:4096").is_err());
+        assert!(validate_server_url(SetupMethod::Remote, "http://192.168.1.5:4096").is_ok());
+        assert!(validate_server_url(SetupMethod::Tunnel, "http://nexus.example.com").is_err());
+        assert!(validate_server_url(SetupMethod::Tunnel, "https://nexus.example.com").is_ok());
+        assert!(validate_server_url(SetupMethod::Remote, "ftp://host").is_err());
+    }
+}
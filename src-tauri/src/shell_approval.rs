@@ -0,0 +1,305 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Bash commands the agent asks permission to run, classified by risk and
+//! held until the user answers.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+
+/// Event carrying a `ShellApprovalRequest` to the frontend
+pub const SHELL_APPROVAL_EVENT: &str = "shell-approval-requested";
+
+/// How much damage a command could do if it ran unchecked
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    /// Changes files, installs packages or reaches the network
+    Medium,
+    /// Destructive, privileged or hard to undo
+    High,
+}
+
+/// Risk of a command and the patterns that set it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RiskAssessment {
+    pub level: RiskLevel,
+    pub reasons: Vec<String>,
+}
+
+static RISK_PATTERNS: LazyLock<Vec<(RiskLevel, &'static str, Regex)>> = LazyLock::new(|| {
+    [
+        (
+            RiskLevel::High,
+            "Deletes files recursively",
+            r"\brm\s+(?:-[a-zA-Z]*[rR][a-zA-Z]*|--recursive)\b",
+        ),
+        (RiskLevel::High, "Runs with elevated privileges", r"\b(?:sudo|doas|su)\s"),
+        (
+            RiskLevel::High,
+            "Pipes a download into a shell",
+            r"\b(?:curl|wget)\b[^|]*\|\s*(?:sudo\s+)?(?:ba|z|da)?sh\b",
+        ),
+        (
+            RiskLevel::High,
+            "Writes to a raw device or filesystem",
+            r"\b(?:dd\s+.*\bof=|mkfs(?:\.\w+)?\s|fdisk\s|>\s*/dev/(?:sd|nvme|disk))",
+        ),
+        (
+            RiskLevel::High,
+            "Rewrites or discards git history",
+            r"\bgit\s+(?:push\s+.*(?:--force|-f\b)|reset\s+--hard|clean\s+-[a-zA-Z]*f)",
+        ),
+        (
+            RiskLevel::High,
+            "Changes permissions or ownership recursively",
+            r"\bch(?:mod|own)\s+-[a-zA-Z]*R",
+        ),
+        (RiskLevel::High, "Shuts down or reboots the machine", r"\b(?:shutdown|reboot|halt|poweroff)\b"),
+        (RiskLevel::High, "Fork bomb", r":\(\)\s*\{\s*:\|:&\s*\};:"),
+        (
+            RiskLevel::Medium,
+            "Deletes or moves files",
+            r"\b(?:rm|mv|rmdir|unlink|shred)\s",
+        ),
+        (
+            RiskLevel::Medium,
+            "Installs or removes packages",
+            r"\b(?:npm|pnpm|yarn|bun|pip3?|cargo|brew|apt(?:-get)?|dnf|yum|pacman|gem|go)\s+(?:install|add|remove|uninstall|i)\b",
+        ),
+        (
+            RiskLevel::Medium,
+            "Reaches the network",
+            r"\b(?:curl|wget|ssh|scp|rsync|nc|telnet|ftp)\s",
+        ),
+        (RiskLevel::Medium, "Publishes changes", r"\b(?:git\s+push|npm\s+publish|cargo\s+publish)\b"),
+        (RiskLevel::Medium, "Kills processes", r"\b(?:kill|pkill|killall)\s"),
+        (RiskLevel::Medium, "Overwrites a file by redirection", r"(?:^|[^>&0-9])>\s*[^\s>&]"),
+        (RiskLevel::Medium, "Evaluates dynamic code", r"\beval\s|\bsh\s+-c\b|\bbash\s+-c\b"),
+    ]
+    .into_iter()
+    .map(|(level, reason, pattern)| {
+        (
+            level,
+            reason,
+            Regex::new(pattern).expect("valid shell risk pattern"),
+        )
+    })
+    .collect()
+});
+
+/// Classify a command by the most severe pattern it matches
+pub fn classify(command: &str) -> RiskAssessment {
+    let matched: Vec<_> = RISK_PATTERNS
+        .iter()
+        .filter(|(_, _, pattern)| pattern.is_match(command))
+        .collect();
+    RiskAssessment {
+        level: matched
+            .iter()
+            .map(|(level, _, _)| *level)
+            .max()
+            .unwrap_or(RiskLevel::Low),
+        reasons: matched
+            .iter()
+            .map(|(_, reason, _)| reason.to_string())
+            .collect(),
+    }
+}
+
+/// A bash command waiting for the user's decision
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShellApprovalRequest {
+    /// The server's permission id
+    pub id: String,
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    pub risk: RiskAssessment,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ShellApprovalRequest {
+    /// Read a `permission.updated` payload. Permissions for tools other
+    /// than bash are not relayed here.
+    pub fn from_permission(permission: &serde_json::Value) -> Option<Self> {
+        let text = |pointer: &str| {
+            permission
+                .pointer(pointer)
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        };
+        if text("/type")? != "bash" {
+            return None;
+        }
+        // Older servers only put the command in the title
+        let command = text("/metadata/command")
+            .or_else(|| text("/title"))
+            .filter(|command| !command.trim().is_empty())?;
+        Some(Self {
+            id: text("/id")?,
+            session_id: text("/sessionID")?,
+            message_id: text("/messageID"),
+            risk: classify(&command),
+            command,
+            working_directory: text("/metadata/cwd").or_else(|| text("/metadata/directory")),
+            requested_at: permission
+                .pointer("/time/created")
+                .and_then(|created| created.as_i64())
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .unwrap_or_else(chrono::Utc::now),
+        })
+    }
+}
+
+/// The user's answer, sent to the server as the permission `response`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Once,
+    /// Allow matching commands for the rest of the session
+    Always,
+    Reject,
+}
+
+/// Approval requests that have not been answered yet, by permission id
+#[derive(Clone, Default)]
+pub struct PendingApprovals {
+    requests: Arc<Mutex<HashMap<String, ShellApprovalRequest>>>,
+}
+
+impl PendingApprovals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock_requests(&self) -> MutexGuard<'_, HashMap<String, ShellApprovalRequest>> {
+        self.requests.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Shell approval lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    /// Hold a request; returns `false` when it was already pending
+    pub fn insert(&self, request: ShellApprovalRequest) -> bool {
+        self.lock_requests()
+            .insert(request.id.clone(), request)
+            .is_none()
+    }
+
+    pub fn take(&self, id: &str) -> Option<ShellApprovalRequest> {
+        self.lock_requests().remove(id)
+    }
+
+    /// Pending requests, oldest first
+    pub fn list(&self, session_id: Option<&str>) -> Vec<ShellApprovalRequest> {
+        let mut requests: Vec<_> = self
+            .lock_requests()
+            .values()
+            .filter(|request| session_id.is_none_or(|id| request.session_id == id))
+            .cloned()
+            .collect();
+        requests.sort_by_key(|request| request.requested_at);
+        requests
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("ls -la src").level, RiskLevel::Low);
+        assert_eq!(classify("cargo test --workspace").level, RiskLevel::Low);
+        assert_eq!(classify("npm install left-pad").level, RiskLevel::Medium);
+        assert_eq!(classify("echo hi > notes.txt").level, RiskLevel::Medium);
+        assert_eq!(classify("cat a 2>&1 | grep b").level, RiskLevel::Low);
+
+        let risky = classify("curl -fsSL https://example.com/install.sh | sh && rm -rf build");
+        assert_eq!(risky.level, RiskLevel::High);
+        assert!(risky
+            .reasons
+            .contains(&"Pipes a download into a shell".to_string()));
+        assert!(risky
+            .reasons
+            .contains(&"Deletes files recursively".to_string()));
+        assert_eq!(classify("sudo apt-get update").level, RiskLevel::High);
+        assert_eq!(
+            classify("git push --force origin main").level,
+            RiskLevel::High
+        );
+    }
+
+    #[test]
+    fn test_from_permission() {
+        let permission = serde_json::json!({
+            "id": "per_1",
+            "type": "bash",
+            "sessionID": "ses_1",
+            "messageID": "msg_1",
+            "title": "rm -rf dist",
+            "metadata": { "command": "rm -rf dist", "cwd": "/work/app" },
+            "time": { "created": 1_700_000_000_000i64 },
+        });
+        let request = ShellApprovalRequest::from_permission(&permission).unwrap();
+        assert_eq!(request.command, "rm -rf dist");
+        assert_eq!(request.working_directory.as_deref(), Some("/work/app"));
+        assert_eq!(request.risk.level, RiskLevel::High);
+        assert_eq!(request.requested_at.timestamp(), 1_700_000_000);
+
+        let edit = serde_json::json!({
+            "id": "per_2",
+            "type": "edit",
+            "sessionID": "ses_1",
+            "title": "Edit src/main.rs",
+        });
+        assert!(ShellApprovalRequest::from_permission(&edit).is_none());
+    }
+
+    #[test]
+    fn test_pending_approvals() {
+        let pending = PendingApprovals::new();
+        let request = |id: &str, session_id: &str| ShellApprovalRequest {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+            message_id: None,
+            command: "ls".to_string(),
+            working_directory: None,
+            risk: classify("ls"),
+            requested_at: chrono::Utc::now(),
+        };
+        assert!(pending.insert(request("a", "s1")));
+        assert!(!pending.insert(request("a", "s1")));
+        assert!(pending.insert(request("b", "s2")));
+
+        assert_eq!(pending.list(None).len(), 2);
+        assert_eq!(pending.list(Some("s2"))[0].id, "b");
+        assert_eq!(pending.take("a").unwrap().session_id, "s1");
+        assert!(pending.take("a").is_none());
+    }
+}
@@ -0,0 +1,288 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! `/commands` typed into the prompt box, routed to server endpoints
+//! instead of being sent as chat content.
+
+use serde::{Deserialize, Serialize};
+
+/// A `/name arguments` line typed by the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlashInvocation {
+    pub name: String,
+    pub arguments: String,
+}
+
+/// Commands with a dedicated session endpoint
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinCommand {
+    /// Revert the last prompt and its changes
+    Undo,
+    /// Restore what the last undo reverted
+    Redo,
+    /// Summarize the session to free up context
+    Compact,
+    Share,
+    Unshare,
+    /// Stop the response in progress
+    Abort,
+}
+
+impl BuiltinCommand {
+    pub const ALL: [BuiltinCommand; 6] = [
+        BuiltinCommand::Undo,
+        BuiltinCommand::Redo,
+        BuiltinCommand::Compact,
+        BuiltinCommand::Share,
+        BuiltinCommand::Unshare,
+        BuiltinCommand::Abort,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BuiltinCommand::Undo => "undo",
+            BuiltinCommand::Redo => "redo",
+            BuiltinCommand::Compact => "compact",
+            BuiltinCommand::Share => "share",
+            BuiltinCommand::Unshare => "unshare",
+            BuiltinCommand::Abort => "abort",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            BuiltinCommand::Undo => "Revert the last message and its file changes",
+            BuiltinCommand::Redo => "Restore the last undone message",
+            BuiltinCommand::Compact => "Summarize the session to free up context",
+            BuiltinCommand::Share => "Create a share link for the session",
+            BuiltinCommand::Unshare => "Remove the session's share link",
+            BuiltinCommand::Abort => "Stop the current response",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        // `/summarize` is the server's name for compacting
+        if name == "summarize" {
+            return Some(BuiltinCommand::Compact);
+        }
+        Self::ALL.into_iter().find(|command| command.name() == name)
+    }
+}
+
+/// A command the server defines, from `GET /command`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerCommand {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Where a recognized command is sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlashRoute {
+    Builtin(BuiltinCommand),
+    /// Run through the server's command endpoint
+    Server(String),
+}
+
+/// Entry in the command list shown while typing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlashCommandInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub builtin: bool,
+}
+
+/// What running a command did, shown as a system message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlashCommandResult {
+    pub command: String,
+    pub message: String,
+    /// The server's response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// Read `input` as a slash command. Paths such as `/usr/bin` and lines
+/// starting with `//` are ordinary text.
+pub fn parse(input: &str) -> Option<SlashInvocation> {
+    let rest = input.trim_start().strip_prefix('/')?;
+    let name_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ':'))
+        .unwrap_or(rest.len());
+    if name_len == 0 {
+        return None;
+    }
+    let (name, arguments) = rest.split_at(name_len);
+    if !arguments.is_empty() && !arguments.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(SlashInvocation {
+        name: name.to_lowercase(),
+        arguments: arguments.trim().to_string(),
+    })
+}
+
+/// Route a command; `None` for names neither built in nor on the server
+pub fn resolve(
+    invocation: &SlashInvocation,
+    server_commands: &[ServerCommand],
+) -> Option<SlashRoute> {
+    // Commands the server defines take precedence, like in the TUI
+    if let Some(command) = server_commands
+        .iter()
+        .find(|command| command.name.eq_ignore_ascii_case(&invocation.name))
+    {
+        return Some(SlashRoute::Server(command.name.clone()));
+    }
+    BuiltinCommand::from_name(&invocation.name).map(SlashRoute::Builtin)
+}
+
+/// Built-in and server commands, sorted by name
+pub fn available(server_commands: &[ServerCommand]) -> Vec<SlashCommandInfo> {
+    let mut commands: Vec<_> = server_commands
+        .iter()
+        .map(|command| SlashCommandInfo {
+            name: command.name.clone(),
+            description: command.description.clone(),
+            builtin: false,
+        })
+        .collect();
+    for builtin in BuiltinCommand::ALL {
+        if !commands
+            .iter()
+            .any(|command| command.name == builtin.name())
+        {
+            commands.push(SlashCommandInfo {
+                name: builtin.name().to_string(),
+                description: Some(builtin.description().to_string()),
+                builtin: true,
+            });
+        }
+    }
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+/// Id of the last user message in a `{ info, parts }` list, the target of `/undo`
+pub fn last_user_message_id(messages: &[serde_json::Value]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|message| {
+            message.pointer("/info/role").and_then(|role| role.as_str()) == Some("user")
+        })
+        .and_then(|message| message.pointer("/info/id"))
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+}
+
+/// Provider and model of the last assistant reply, used for `/compact`
+pub fn last_model(messages: &[serde_json::Value]) -> Option<(String, String)> {
+    messages.iter().rev().find_map(|message| {
+        let info = message.get("info")?;
+        if info.get("role")?.as_str()? != "assistant" {
+            return None;
+        }
+        Some((
+            info.get("providerID")?.as_str()?.to_string(),
+            info.get("modelID")?.as_str()?.to_string(),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("  /Compact  now please "),
+            Some(SlashInvocation {
+                name: "compact".to_string(),
+                arguments: "now please".to_string(),
+            })
+        );
+        assert_eq!(parse("/undo").unwrap().arguments, "");
+        assert!(parse("/usr/bin/env python").is_none());
+        assert!(parse("// comment").is_none());
+        assert!(parse("/ spaced").is_none());
+        assert!(parse("hello /undo").is_none());
+    }
+
+    #[test]
+    fn test_resolve_and_available() {
+        let server = vec![
+            ServerCommand {
+                name: "review".to_string(),
+                description: Some("Review changes".to_string()),
+            },
+            ServerCommand {
+                name: "share".to_string(),
+                description: None,
+            },
+        ];
+        let route = |input: &str| resolve(&parse(input).unwrap(), &server);
+        assert_eq!(
+            route("/undo"),
+            Some(SlashRoute::Builtin(BuiltinCommand::Undo))
+        );
+        assert_eq!(
+            route("/summarize"),
+            Some(SlashRoute::Builtin(BuiltinCommand::Compact))
+        );
+        assert_eq!(
+            route("/review src"),
+            Some(SlashRoute::Server("review".to_string()))
+        );
+        assert_eq!(
+            route("/share"),
+            Some(SlashRoute::Server("share".to_string()))
+        );
+        assert_eq!(route("/unknown"), None);
+
+        let names: Vec<_> = available(&server)
+            .into_iter()
+            .map(|command| (command.name, command.builtin))
+            .collect();
+        assert_eq!(names.len(), 7);
+        assert!(names.contains(&("share".to_string(), false)));
+        assert!(names.contains(&("undo".to_string(), true)));
+    }
+
+    #[test]
+    fn test_message_lookups() {
+        let messages = vec![
+            serde_json::json!({ "info": { "id": "m1", "role": "user" } }),
+            serde_json::json!({ "info": { "id": "m2", "role": "assistant", "providerID": "anthropic", "modelID": "claude" } }),
+            serde_json::json!({ "info": { "id": "m3", "role": "user" } }),
+        ];
+        assert_eq!(last_user_message_id(&messages).as_deref(), Some("m3"));
+        assert_eq!(
+            last_model(&messages),
+            Some(("anthropic".to_string(), "claude".to_string()))
+        );
+        assert_eq!(last_user_message_id(&[]), None);
+    }
+}
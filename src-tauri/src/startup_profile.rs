@@ -0,0 +1,269 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Timing for each startup step, so slow launches can be traced to the
+//! step responsible. Steps that can finish in the background have a
+//! budget: once it runs out, startup stops waiting and the step completes
+//! on its own, keeping the window responsive on slow disks or networks.
+
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupStep {
+    /// Recovery journal, outbox and other profile stores
+    LocalStores,
+    /// Saved connections
+    Connections,
+    /// Reconnecting to the last server
+    ConnectionRestore,
+    Sessions,
+    Models,
+}
+
+impl StartupStep {
+    /// How long startup waits for the step before leaving it to finish in
+    /// the background
+    pub fn budget(&self) -> Duration {
+        Duration::from_millis(match self {
+            StartupStep::LocalStores => 150,
+            StartupStep::Connections => 100,
+            StartupStep::ConnectionRestore => 300,
+            StartupStep::Sessions => 300,
+            StartupStep::Models => 200,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepTiming {
+    pub step: StartupStep,
+    /// When the step began, from process start
+    pub started_ms: u64,
+    /// Unset while a deferred step is still running
+    pub duration_ms: Option<u64>,
+    pub budget_ms: u64,
+    /// Startup stopped waiting for the step when its budget ran out
+    pub deferred: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupReport {
+    /// When the app reported itself ready, from process start
+    pub ready_ms: Option<u64>,
+    pub steps: Vec<StepTiming>,
+    pub over_budget: Vec<StartupStep>,
+}
+
+#[derive(Default)]
+struct Timings {
+    steps: Vec<StepTiming>,
+    ready: Option<Duration>,
+}
+
+#[derive(Clone)]
+pub struct StartupProfiler {
+    started: Instant,
+    timings: Arc<Mutex<Timings>>,
+}
+
+impl Default for StartupProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+impl StartupProfiler {
+    /// Start the clock; call as early in startup as possible
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            timings: Arc::new(Mutex::new(Timings::default())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Timings> {
+        self.timings.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Startup profiler lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    fn begin(&self, step: StartupStep) -> usize {
+        let mut timings = self.lock();
+        timings.steps.push(StepTiming {
+            step,
+            started_ms: millis(self.started.elapsed()),
+            duration_ms: None,
+            budget_ms: millis(step.budget()),
+            deferred: false,
+        });
+        timings.steps.len() - 1
+    }
+
+    fn finish(&self, index: usize, began: Instant) {
+        let elapsed = began.elapsed();
+        let mut timings = self.lock();
+        let timing = &mut timings.steps[index];
+        timing.duration_ms = Some(millis(elapsed));
+        if timing.deferred {
+            info!(target: "init", step = ?timing.step, duration_ms = millis(elapsed), "Deferred startup step finished");
+        }
+    }
+
+    /// Time a step that startup always waits for
+    pub fn time<T>(&self, step: StartupStep, work: impl FnOnce() -> T) -> T {
+        let index = self.begin(step);
+        let began = Instant::now();
+        let result = work();
+        self.finish(index, began);
+        result
+    }
+
+    /// Time an async step that startup always waits for
+    pub async fn time_async<T>(&self, step: StartupStep, work: impl Future<Output = T>) -> T {
+        let index = self.begin(step);
+        let began = Instant::now();
+        let result = work.await;
+        self.finish(index, began);
+        result
+    }
+
+    /// Run a step, waiting at most its budget. A step that overruns keeps
+    /// running as a background task, so it must publish its own results.
+    pub async fn run_within_budget<F>(&self, step: StartupStep, work: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let index = self.begin(step);
+        let began = Instant::now();
+        let profiler = self.clone();
+        let task = tokio::spawn(async move {
+            work.await;
+            profiler.finish(index, began);
+        });
+
+        if tokio::time::timeout(step.budget(), task).await.is_err() {
+            warn!(target: "init", ?step, budget_ms = millis(step.budget()), "Startup step over budget, finishing in the background");
+            self.lock().steps[index].deferred = true;
+        }
+    }
+
+    /// Note that the app is ready for use
+    pub fn mark_ready(&self) {
+        let elapsed = self.started.elapsed();
+        self.lock().ready = Some(elapsed);
+    }
+
+    pub fn report(&self) -> StartupReport {
+        let timings = self.lock();
+        let over_budget = timings
+            .steps
+            .iter()
+            .filter(|timing| {
+                timing.deferred
+                    || timing
+                        .duration_ms
+                        .is_some_and(|duration| duration > timing.budget_ms)
+            })
+            .map(|timing| timing.step)
+            .collect();
+        StartupReport {
+            ready_ms: timings.ready.map(millis),
+            steps: timings.steps.clone(),
+            over_budget,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_steps_within_budget_are_waited_for() {
+        let profiler = StartupProfiler::new();
+        let done = Arc::new(Mutex::new(false));
+        let flag = done.clone();
+        profiler
+            .run_within_budget(StartupStep::Models, async move {
+                *flag.lock().unwrap() = true;
+            })
+            .await;
+        assert!(*done.lock().unwrap());
+
+        let report = profiler.report();
+        assert_eq!(report.steps.len(), 1);
+        assert!(!report.steps[0].deferred);
+        assert!(report.steps[0].duration_ms.is_some());
+        assert!(report.over_budget.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_slow_steps_are_deferred() {
+        let profiler = StartupProfiler::new();
+        let slow = StartupStep::Connections.budget() * 3;
+        profiler
+            .run_within_budget(StartupStep::Connections, tokio::time::sleep(slow))
+            .await;
+        profiler.mark_ready();
+
+        let report = profiler.report();
+        assert!(report.steps[0].deferred);
+        assert_eq!(report.steps[0].duration_ms, None);
+        assert_eq!(report.over_budget, vec![StartupStep::Connections]);
+        assert!(report.ready_ms.is_some());
+
+        tokio::time::sleep(slow).await;
+        assert!(profiler.report().steps[0].duration_ms.is_some());
+    }
+
+    #[test]
+    fn test_time_records_blocking_steps() {
+        let profiler = StartupProfiler::new();
+        let value = profiler.time(StartupStep::LocalStores, || 7);
+        assert_eq!(value, 7);
+        let report = profiler.report();
+        assert_eq!(report.steps[0].step, StartupStep::LocalStores);
+        assert!(!report.steps[0].deferred);
+    }
+
+    #[tokio::test]
+    async fn test_time_async_waits_past_the_budget() {
+        let profiler = StartupProfiler::new();
+        let slow = StartupStep::Connections.budget() * 2;
+        profiler
+            .time_async(StartupStep::Connections, tokio::time::sleep(slow))
+            .await;
+        let report = profiler.report();
+        assert!(!report.steps[0].deferred);
+        assert!(report.steps[0].duration_ms.is_some());
+        assert_eq!(report.over_budget, vec![StartupStep::Connections]);
+    }
+}
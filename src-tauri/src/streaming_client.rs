@@ -21,8 +21,9 @@
 // SOFTWARE.
 
 use crate::api_client::ApiClient;
+use crate::certificate_pinning;
 use crate::error::AppError;
-use crate::session_manager::MessageRole;
+use crate::session_manager::{text_content, MessagePart, MessageRole};
 use futures_util::{Stream, StreamExt};
 use reqwest_eventsource::{Event, EventSource};
 use serde::{Deserialize, Serialize};
@@ -55,6 +56,9 @@ pub enum StreamEvent {
         message_id: String,
         final_content: String,
         metadata: Option<serde_json::Value>,
+        /// Structured message, including tool calls and reasoning
+        #[serde(default)]
+        parts: Vec<MessagePart>,
     },
     /// Error occurred during streaming
     Error {
@@ -68,6 +72,33 @@ pub enum StreamEvent {
         session_id: String,
         message_id: String,
     },
+    /// The agent is waiting for permission to use a tool
+    PermissionRequested {
+        session_id: String,
+        permission: serde_json::Value,
+    },
+}
+
+/// Parts of a finished message: the server's own when the final event
+/// carries them, otherwise the streamed reasoning and text
+fn completed_parts(
+    final_event: &serde_json::Value,
+    reasoning: &str,
+    content: &str,
+) -> Vec<MessagePart> {
+    if let Some(parts) = final_event.get("parts").and_then(|parts| parts.as_array()) {
+        return parts.iter().flat_map(MessagePart::from_server).collect();
+    }
+    let mut parts = Vec::new();
+    if !reasoning.is_empty() {
+        parts.push(MessagePart::Reasoning {
+            text: reasoning.to_string(),
+        });
+    }
+    if !content.is_empty() {
+        parts.push(MessagePart::text(content));
+    }
+    parts
 }
 
 /// Request to start a streaming session
@@ -145,6 +176,19 @@ impl StreamingClient {
         })
     }
 
+    /// Client for another server that shares this client's stream registry,
+    /// so its streams can be listed and stopped here. Events go to a channel
+    /// of its own.
+    pub fn with_api_client(&self, api_client: Arc<ApiClient>) -> Self {
+        let (event_sender, _) = broadcast::channel(self.config.buffer_size);
+        Self {
+            api_client,
+            config: self.config.clone(),
+            event_sender,
+            active_streams: self.active_streams.clone(),
+        }
+    }
+
     /// Subscribe to stream events
     pub fn subscribe(&self) -> broadcast::Receiver<StreamEvent> {
         self.event_sender.subscribe()
@@ -153,8 +197,13 @@ impl StreamingClient {
     /// Start a streaming message session
     pub async fn start_stream(
         &self,
-        request: StreamRequest,
+        mut request: StreamRequest,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        // Fall back to the connection profile's model and system prompt
+        let profile = self.api_client.profile().await;
+        request.model_config = request.model_config.or(profile.default_model);
+        request.system_prompt = request.system_prompt.or(profile.system_prompt);
+
         let stream_id = uuid::Uuid::new_v4().to_string();
         let session_id = request.session_id.clone();
         let message_id = uuid::Uuid::new_v4().to_string();
@@ -291,12 +340,13 @@ impl StreamingClient {
         let stream_url = format!("{}/session/{}/stream", server_url, request.session_id);
 
         // Create HTTP client and request
-        let client = reqwest::Client::new();
+        let client = certificate_pinning::shared_client()?;
         let req = client
             .post(&stream_url)
             .header("Content-Type", "application/json")
             .header("Accept", "text/event-stream")
             .body(serde_json::to_string(request)?);
+        let req = api_client.authorize(req).await;
 
         // Create EventSource connection
         let mut event_source = EventSource::new(req).map_err(|e| AppError::ConnectionError {
@@ -305,6 +355,7 @@ impl StreamingClient {
         })?;
 
         let mut chunk_index = 0;
+        let mut reasoning = String::new();
 
         // Process events with timeout
         loop {
@@ -329,6 +380,26 @@ impl StreamingClient {
                             if let Ok(chunk_data) =
                                 serde_json::from_str::<serde_json::Value>(&message.data)
                             {
+                                // Permission prompts arrive as bus events
+                                if chunk_data.get("type").and_then(|v| v.as_str())
+                                    == Some("permission.updated")
+                                {
+                                    if let Some(permission) = chunk_data.get("properties") {
+                                        let _ =
+                                            event_sender.send(StreamEvent::PermissionRequested {
+                                                session_id: session_id.to_string(),
+                                                permission: permission.clone(),
+                                            });
+                                    }
+                                    continue;
+                                }
+
+                                if let Some(thought) =
+                                    chunk_data.get("reasoning").and_then(|v| v.as_str())
+                                {
+                                    reasoning.push_str(thought);
+                                }
+
                                 if let Some(content) =
                                     chunk_data.get("content").and_then(|v| v.as_str())
                                 {
@@ -350,11 +421,22 @@ impl StreamingClient {
                                 {
                                     if done {
                                         // Send completion event
+                                        let parts = completed_parts(
+                                            &chunk_data,
+                                            &reasoning,
+                                            accumulated_content,
+                                        );
+                                        let final_content = if accumulated_content.is_empty() {
+                                            text_content(&parts)
+                                        } else {
+                                            accumulated_content.clone()
+                                        };
                                         let complete_event = StreamEvent::Complete {
                                             session_id: session_id.to_string(),
                                             message_id: message_id.to_string(),
-                                            final_content: accumulated_content.clone(),
+                                            final_content,
                                             metadata: chunk_data.get("metadata").cloned(),
+                                            parts,
                                         };
                                         let _ = event_sender.send(complete_event);
                                         break;
@@ -454,6 +536,44 @@ mod tests {
         assert!(active.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_with_api_client_shares_streams() {
+        let (client, _temp) = create_test_streaming_client();
+        let other = client.with_api_client(Arc::new(ApiClient::new().unwrap()));
+        other
+            .active_streams
+            .write()
+            .await
+            .insert("stream-1".to_string(), tokio::spawn(async {}));
+
+        assert_eq!(client.get_active_streams().await, vec!["stream-1"]);
+        client.stop_stream("stream-1").await.unwrap();
+        assert!(other.get_active_streams().await.is_empty());
+    }
+
+    #[test]
+    fn test_completed_parts() {
+        let parts = completed_parts(&serde_json::json!({ "done": true }), "Hmm", "Answer");
+        assert_eq!(
+            parts,
+            vec![
+                MessagePart::Reasoning {
+                    text: "Hmm".to_string()
+                },
+                MessagePart::text("Answer"),
+            ]
+        );
+
+        let server = serde_json::json!({
+            "done": true,
+            "parts": [{ "type": "text", "text": "From server" }],
+        });
+        assert_eq!(
+            completed_parts(&server, "", "ignored"),
+            vec![MessagePart::text("From server")]
+        );
+    }
+
     #[test]
     fn test_stream_event_serialization() {
         let event = StreamEvent::Chunk {
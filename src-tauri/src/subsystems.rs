@@ -0,0 +1,283 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Tauri event emitted whenever a subsystem changes state
+pub const SUBSYSTEM_STATUS_EVENT: &str = "subsystem-status-changed";
+
+/// Tauri event emitted as startup progresses, so the UI can show which
+/// features are still loading
+pub const INIT_PROGRESS_EVENT: &str = "init-progress";
+
+/// Parts of the backend that initialize independently at startup
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Settings,
+    ApiClient,
+    EventBridge,
+    Connection,
+    Sessions,
+    Models,
+    Streaming,
+    RecoveryJournal,
+    Outbox,
+}
+
+impl Subsystem {
+    /// Every subsystem, in initialization order
+    pub const ALL: [Subsystem; 9] = [
+        Subsystem::Settings,
+        Subsystem::ApiClient,
+        Subsystem::EventBridge,
+        Subsystem::Connection,
+        Subsystem::Sessions,
+        Subsystem::Models,
+        Subsystem::Streaming,
+        Subsystem::RecoveryJournal,
+        Subsystem::Outbox,
+    ];
+
+    /// Feature name reported in the `application-ready` event
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Subsystem::Settings => "settings",
+            Subsystem::ApiClient => "api",
+            Subsystem::EventBridge => "events",
+            Subsystem::Connection => "connection",
+            Subsystem::Sessions => "session-management",
+            Subsystem::Models => "model-management",
+            Subsystem::Streaming => "streaming",
+            Subsystem::RecoveryJournal => "crash-recovery",
+            Subsystem::Outbox => "offline-queue",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubsystemHealth {
+    /// Not initialized yet
+    Pending,
+    Available,
+    /// Working with reduced functionality, e.g. saved data failed to load
+    Degraded,
+    /// Failed to initialize; dependent features are disabled
+    Unavailable,
+}
+
+/// Current state of one subsystem
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubsystemStatus {
+    pub subsystem: Subsystem,
+    pub health: SubsystemHealth,
+    /// Why the subsystem is degraded or unavailable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Payload of `init-progress`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InitProgress {
+    /// Subsystems no longer pending, whatever their health
+    pub ready: usize,
+    pub total: usize,
+    pub pending: Vec<Subsystem>,
+    /// The change that triggered this report
+    pub latest: SubsystemStatus,
+}
+
+/// Tracks which subsystems initialized so the UI can explain missing features
+#[derive(Clone)]
+pub struct SubsystemRegistry {
+    statuses: Arc<RwLock<BTreeMap<Subsystem, SubsystemStatus>>>,
+}
+
+impl Default for SubsystemRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubsystemRegistry {
+    /// Create a registry with every subsystem pending
+    pub fn new() -> Self {
+        let now = Utc::now();
+        let statuses = Subsystem::ALL
+            .iter()
+            .map(|subsystem| {
+                (
+                    *subsystem,
+                    SubsystemStatus {
+                        subsystem: *subsystem,
+                        health: SubsystemHealth::Pending,
+                        error: None,
+                        updated_at: now,
+                    },
+                )
+            })
+            .collect();
+        Self {
+            statuses: Arc::new(RwLock::new(statuses)),
+        }
+    }
+
+    fn set(
+        &self,
+        subsystem: Subsystem,
+        health: SubsystemHealth,
+        error: Option<String>,
+    ) -> SubsystemStatus {
+        let status = SubsystemStatus {
+            subsystem,
+            health,
+            error,
+            updated_at: Utc::now(),
+        };
+        let mut statuses = match self.statuses.write() {
+            Ok(statuses) => statuses,
+            Err(poisoned) => {
+                eprintln!("[ERROR] SubsystemRegistry: statuses RwLock poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        };
+        statuses.insert(subsystem, status.clone());
+        status
+    }
+
+    pub fn mark_available(&self, subsystem: Subsystem) -> SubsystemStatus {
+        self.set(subsystem, SubsystemHealth::Available, None)
+    }
+
+    pub fn mark_degraded(&self, subsystem: Subsystem, error: impl ToString) -> SubsystemStatus {
+        self.set(
+            subsystem,
+            SubsystemHealth::Degraded,
+            Some(error.to_string()),
+        )
+    }
+
+    pub fn mark_unavailable(&self, subsystem: Subsystem, error: impl ToString) -> SubsystemStatus {
+        self.set(
+            subsystem,
+            SubsystemHealth::Unavailable,
+            Some(error.to_string()),
+        )
+    }
+
+    /// Status of every subsystem, in initialization order
+    pub fn statuses(&self) -> Vec<SubsystemStatus> {
+        match self.statuses.read() {
+            Ok(statuses) => statuses.values().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().values().cloned().collect(),
+        }
+    }
+
+    pub fn status(&self, subsystem: Subsystem) -> Option<SubsystemStatus> {
+        self.statuses()
+            .into_iter()
+            .find(|status| status.subsystem == subsystem)
+    }
+
+    /// Startup progress after `latest` changed
+    pub fn progress(&self, latest: SubsystemStatus) -> InitProgress {
+        let statuses = self.statuses();
+        let pending: Vec<Subsystem> = statuses
+            .iter()
+            .filter(|status| status.health == SubsystemHealth::Pending)
+            .map(|status| status.subsystem)
+            .collect();
+        InitProgress {
+            ready: statuses.len() - pending.len(),
+            total: statuses.len(),
+            pending,
+            latest,
+        }
+    }
+
+    /// Features backed by subsystems that are available or degraded
+    pub fn available_features(&self) -> Vec<String> {
+        self.statuses()
+            .into_iter()
+            .filter(|status| {
+                matches!(
+                    status.health,
+                    SubsystemHealth::Available | SubsystemHealth::Degraded
+                )
+            })
+            .map(|status| status.subsystem.feature().to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_registry_is_pending() {
+        let registry = SubsystemRegistry::new();
+        let statuses = registry.statuses();
+        assert_eq!(statuses.len(), Subsystem::ALL.len());
+        assert!(statuses
+            .iter()
+            .all(|status| status.health == SubsystemHealth::Pending));
+        assert!(registry.available_features().is_empty());
+    }
+
+    #[test]
+    fn test_failures_are_recorded_per_subsystem() {
+        let registry = SubsystemRegistry::new();
+        registry.mark_available(Subsystem::Connection);
+        registry.mark_degraded(Subsystem::Models, "Failed to load providers");
+        registry.mark_unavailable(Subsystem::Streaming, "Failed to create streaming client");
+
+        let streaming = registry.status(Subsystem::Streaming).unwrap();
+        assert_eq!(streaming.health, SubsystemHealth::Unavailable);
+        assert_eq!(
+            streaming.error.as_deref(),
+            Some("Failed to create streaming client")
+        );
+        assert_eq!(
+            registry.available_features(),
+            vec!["connection".to_string(), "model-management".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_progress_counts_settled_subsystems() {
+        let registry = SubsystemRegistry::new();
+        registry.mark_available(Subsystem::Settings);
+        let latest = registry.mark_unavailable(Subsystem::ApiClient, "no TLS backend");
+
+        let progress = registry.progress(latest);
+        assert_eq!(progress.ready, 2);
+        assert_eq!(progress.total, Subsystem::ALL.len());
+        assert!(!progress.pending.contains(&Subsystem::ApiClient));
+        assert_eq!(progress.latest.subsystem, Subsystem::ApiClient);
+    }
+}
@@ -0,0 +1,328 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use crate::logging::{active_log_path, segment_paths, LOG_FILE_NAME};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Replacement for any value that looks like a credential
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments whose values are always stripped from the bundle
+const SENSITIVE_KEYS: [&str; 7] = [
+    "api_key",
+    "apikey",
+    "token",
+    "secret",
+    "password",
+    "authorization",
+    "cookie",
+];
+
+/// Config files copied into the bundle after redaction
+const CONFIG_FILES: [&str; 3] = [
+    "server_connections.json",
+    "settings.json",
+    "model_preferences.json",
+];
+
+/// Host and build details included in every bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub os_version: Option<String>,
+    pub kernel_version: Option<String>,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Data gathered from running components before the bundle is written
+#[derive(Debug, Clone, Default)]
+pub struct SupportBundleInput {
+    pub recent_events: Vec<serde_json::Value>,
+    pub server_diagnostics: serde_json::Value,
+}
+
+/// Collect version and operating system details
+pub fn system_info() -> SystemInfo {
+    SystemInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        os_version: sysinfo::System::long_os_version(),
+        kernel_version: sysinfo::System::kernel_version(),
+        generated_at: chrono::Utc::now(),
+    }
+}
+
+/// Default file name for a bundle created now
+pub fn bundle_file_name() -> String {
+    format!(
+        "opencode-nexus-support-{}.zip",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEYS.iter().any(|fragment| key.contains(fragment))
+}
+
+/// Recursively replace the values of credential-like keys
+pub fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, inner) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *inner = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(inner);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        serde_json::Value::String(text) => *text = redact_text(text),
+        _ => {}
+    }
+}
+
+/// Mask `Bearer <token>` and `key=value` / `key: value` pairs in free text
+pub fn redact_text(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut mask_next = false;
+
+    for token in text.split_inclusive(char::is_whitespace) {
+        let word = token.trim_end();
+        let trailing = &token[word.len()..];
+
+        if word.eq_ignore_ascii_case("bearer") {
+            mask_next = true;
+            redacted.push_str(token);
+            continue;
+        }
+
+        if mask_next && !word.is_empty() {
+            redacted.push_str(REDACTED);
+            redacted.push_str(trailing);
+            mask_next = false;
+            continue;
+        }
+
+        match word.find(['=', ':']) {
+            Some(split) if is_sensitive_key(&word[..split]) => {
+                let separator = &word[split..split + 1];
+                let value = &word[split + 1..];
+                redacted.push_str(&word[..split]);
+                redacted.push_str(separator);
+                if value.is_empty() {
+                    // `key: value` with the value in the next token
+                    mask_next = true;
+                } else {
+                    redacted.push_str(REDACTED);
+                }
+                redacted.push_str(trailing);
+            }
+            _ => redacted.push_str(token),
+        }
+    }
+
+    redacted
+}
+
+/// Redact one log line, keeping JSON lines valid JSON
+pub fn redact_line(line: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(mut value) if value.is_object() => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        _ => redact_text(line),
+    }
+}
+
+/// Write a zip bundle with system info, redacted config, logs, recent events
+/// and server diagnostics to `output`
+pub fn write_bundle(
+    config_dir: &Path,
+    input: &SupportBundleInput,
+    output: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| AppError::FileSystemError {
+            path: parent.to_string_lossy().to_string(),
+            message: "Failed to create bundle directory".to_string(),
+            details: e.to_string(),
+        })?;
+    }
+
+    let file = File::create(output).map_err(|e| AppError::FileSystemError {
+        path: output.to_string_lossy().to_string(),
+        message: "Failed to create support bundle".to_string(),
+        details: e.to_string(),
+    })?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("system.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&system_info())?.as_bytes())?;
+
+    for name in CONFIG_FILES {
+        let path = config_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let redacted = match serde_json::from_str::<serde_json::Value>(&contents) {
+            Ok(mut value) => {
+                redact_value(&mut value);
+                serde_json::to_string_pretty(&value)?
+            }
+            Err(_) => redact_text(&contents),
+        };
+        zip.start_file(format!("config/{}", name), options)?;
+        zip.write_all(redacted.as_bytes())?;
+    }
+
+    let mut recent_events = serde_json::Value::Array(input.recent_events.clone());
+    redact_value(&mut recent_events);
+    zip.start_file("events.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&recent_events)?.as_bytes())?;
+
+    let mut diagnostics = input.server_diagnostics.clone();
+    redact_value(&mut diagnostics);
+    zip.start_file("diagnostics.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&diagnostics)?.as_bytes())?;
+
+    let active = active_log_path(config_dir);
+    if active.exists() {
+        zip.start_file(format!("logs/{}", LOG_FILE_NAME), options)?;
+        write_redacted_log(&mut zip, BufReader::new(File::open(&active)?))?;
+    }
+    for segment in segment_paths(config_dir) {
+        let name = segment
+            .file_name()
+            .map(|n| n.to_string_lossy().trim_end_matches(".gz").to_string())
+            .unwrap_or_default();
+        zip.start_file(format!("logs/{}", name), options)?;
+        let reader = BufReader::new(GzDecoder::new(File::open(&segment)?));
+        write_redacted_log(&mut zip, reader)?;
+    }
+
+    zip.finish()?;
+    Ok(output.to_path_buf())
+}
+
+fn write_redacted_log<W: Write, R: Read>(
+    writer: &mut W,
+    reader: BufReader<R>,
+) -> std::io::Result<()> {
+    for line in reader.lines() {
+        writeln!(writer, "{}", redact_line(&line?))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::{append_line, LogRotationConfig};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_redact_value_strips_secrets() {
+        let mut value = serde_json::json!({
+            "name": "home",
+            "api_key": "sk-123",
+            "nested": [{ "auth_token": "abc", "hostname": "localhost" }],
+        });
+        redact_value(&mut value);
+
+        assert_eq!(value["name"], "home");
+        assert_eq!(value["api_key"], REDACTED);
+        assert_eq!(value["nested"][0]["auth_token"], REDACTED);
+        assert_eq!(value["nested"][0]["hostname"], "localhost");
+    }
+
+    #[test]
+    fn test_redact_text_masks_inline_credentials() {
+        assert_eq!(
+            redact_text("Authorization: Bearer abc.def failed"),
+            format!("Authorization: Bearer {} failed", REDACTED)
+        );
+        assert_eq!(
+            redact_text("retry with api_key=sk-1 now"),
+            format!("retry with api_key={} now", REDACTED)
+        );
+        assert_eq!(redact_text("plain message"), "plain message");
+    }
+
+    #[test]
+    fn test_write_bundle_contains_redacted_files() {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        std::fs::write(
+            temp.path().join("server_connections.json"),
+            r#"{"home":{"hostname":"localhost","api_key":"sk-secret"}}"#,
+        )
+        .unwrap();
+        append_line(
+            temp.path(),
+            "{\"level\":\"INFO\",\"message\":\"Using Bearer sk-secret\"}\n",
+            &LogRotationConfig::default(),
+        )
+        .unwrap();
+
+        let output = temp.path().join("out").join(bundle_file_name());
+        let input = SupportBundleInput {
+            recent_events: vec![serde_json::json!({ "event_type": "Connection" })],
+            server_diagnostics: serde_json::json!({ "connected": false }),
+        };
+        write_bundle(temp.path(), &input, &output).expect("Should write bundle");
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        for name in [
+            "system.json",
+            "config/server_connections.json",
+            "events.json",
+            "diagnostics.json",
+            "logs/application.log",
+        ] {
+            assert!(archive.by_name(name).is_ok(), "missing {}", name);
+        }
+
+        let mut contents = String::new();
+        for i in 0..archive.len() {
+            archive
+                .by_index(i)
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+        }
+        assert!(!contents.contains("sk-secret"));
+    }
+}
@@ -0,0 +1,341 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Opt-in usage telemetry. Only counts of a fixed set of features are kept,
+//! never content, ids, paths or server addresses. Counts are stored locally
+//! so users can inspect them with `get_collected_telemetry`, and are
+//! uploaded only when telemetry is enabled and the build names an endpoint.
+
+use crate::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::warn;
+
+/// Collector baked in at build time; builds without it never upload
+const TELEMETRY_ENDPOINT: Option<&str> = option_env!("OPENCODE_NEXUS_TELEMETRY_URL");
+
+/// Minimum time between uploads
+const UPLOAD_INTERVAL_HOURS: i64 = 24;
+
+/// Counter store used by `record`, installed once at startup
+static RECORDER: OnceLock<Telemetry> = OnceLock::new();
+
+/// Features whose use is counted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    MessageSent,
+    SessionCreated,
+    SessionDeleted,
+    QuickPrompt,
+    AttachmentValidated,
+    ContextFileUploaded,
+    AudioTranscribed,
+    PluginCommand,
+    UpdateCheck,
+    SupportBundleExported,
+    LocaleChanged,
+}
+
+/// Exactly what an upload contains
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TelemetryReport {
+    pub app_version: String,
+    /// `linux`, `macos` or `windows`
+    pub os: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub counters: BTreeMap<Feature, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TelemetryData {
+    period_start: DateTime<Utc>,
+    #[serde(default)]
+    counters: BTreeMap<Feature, u64>,
+    #[serde(default)]
+    last_upload: Option<DateTime<Utc>>,
+}
+
+impl Default for TelemetryData {
+    fn default() -> Self {
+        Self {
+            period_start: Utc::now(),
+            counters: BTreeMap::new(),
+            last_upload: None,
+        }
+    }
+}
+
+/// Whether this build can upload at all
+pub fn is_configured() -> bool {
+    TELEMETRY_ENDPOINT.is_some_and(|endpoint| !endpoint.is_empty())
+}
+
+/// Feature counters persisted in `telemetry.json`
+#[derive(Clone)]
+pub struct Telemetry {
+    config_dir: PathBuf,
+    data: Arc<Mutex<TelemetryData>>,
+}
+
+impl Telemetry {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            data: Arc::new(Mutex::new(TelemetryData::default())),
+        }
+    }
+
+    fn get_telemetry_file_path(&self) -> PathBuf {
+        self.config_dir.join("telemetry.json")
+    }
+
+    fn lock_data(&self) -> std::sync::MutexGuard<'_, TelemetryData> {
+        match self.data.lock() {
+            Ok(data) => data,
+            Err(poisoned) => {
+                eprintln!("[ERROR] Telemetry: data mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let telemetry_file = self.get_telemetry_file_path();
+        if !telemetry_file.exists() {
+            return Ok(());
+        }
+
+        let telemetry_json =
+            std::fs::read_to_string(&telemetry_file).map_err(|e| AppError::FileSystemError {
+                path: telemetry_file.to_string_lossy().to_string(),
+                message: "Failed to read telemetry file".to_string(),
+                details: e.to_string(),
+            })?;
+        let loaded: TelemetryData =
+            serde_json::from_str(&telemetry_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse telemetry file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        *self.lock_data() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, data: &TelemetryData) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let telemetry_json = serde_json::to_string(data)?;
+        std::fs::write(self.get_telemetry_file_path(), telemetry_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self.get_telemetry_file_path().to_string_lossy().to_string(),
+                message: "Failed to write telemetry file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Count one use of `feature`
+    pub fn increment(&self, feature: Feature) -> Result<(), Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        *data.counters.entry(feature).or_insert(0) += 1;
+        self.save(&data)
+    }
+
+    /// Report covering everything counted since the last upload
+    pub fn collected(&self) -> TelemetryReport {
+        let data = self.lock_data();
+        TelemetryReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            period_start: data.period_start,
+            period_end: Utc::now(),
+            counters: data.counters.clone(),
+        }
+    }
+
+    /// Drop collected counts and start a new period
+    pub fn clear(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        data.counters.clear();
+        data.period_start = Utc::now();
+        self.save(&data)
+    }
+
+    /// Whether an upload is due at `now`
+    fn upload_due(&self, now: DateTime<Utc>) -> bool {
+        let data = self.lock_data();
+        !data.counters.is_empty()
+            && data
+                .last_upload
+                .is_none_or(|last| now - last >= Duration::hours(UPLOAD_INTERVAL_HOURS))
+    }
+
+    /// Send the collected report and start a new period. Counts recorded
+    /// while the upload was in flight are kept for the next one.
+    pub async fn upload_if_due(&self) -> Result<bool, AppError> {
+        let Some(endpoint) = TELEMETRY_ENDPOINT.filter(|endpoint| !endpoint.is_empty()) else {
+            return Ok(false);
+        };
+        if !self.upload_due(Utc::now()) {
+            return Ok(false);
+        }
+
+        let report = self.collected();
+        let response = reqwest::Client::new()
+            .post(endpoint)
+            .json(&report)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| AppError::NetworkError {
+                message: "Failed to upload telemetry".to_string(),
+                details: e.to_string(),
+                retry_after: None,
+            })?;
+        if !response.status().is_success() {
+            return Err(AppError::ServerError {
+                status_code: response.status().as_u16(),
+                message: "Telemetry upload was rejected".to_string(),
+                details: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        self.mark_uploaded(&report).map_err(|e| AppError::IoError {
+            message: "Failed to save telemetry".to_string(),
+            details: Some(e.to_string()),
+        })?;
+        Ok(true)
+    }
+
+    fn mark_uploaded(&self, report: &TelemetryReport) -> Result<(), Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        for (feature, sent) in &report.counters {
+            if let Some(count) = data.counters.get_mut(feature) {
+                *count = count.saturating_sub(*sent);
+            }
+        }
+        data.counters.retain(|_, count| *count > 0);
+        data.period_start = report.period_end;
+        data.last_upload = Some(report.period_end);
+        self.save(&data)
+    }
+}
+
+/// Install the process-wide store used by `record`
+pub fn install(telemetry: Telemetry) {
+    let _ = RECORDER.set(telemetry);
+}
+
+/// Installed store, if any
+pub fn recorder() -> Option<&'static Telemetry> {
+    RECORDER.get()
+}
+
+/// Count a feature use; a no-op until `install` has been called
+pub fn record(feature: Feature) {
+    if let Some(telemetry) = RECORDER.get() {
+        if let Err(e) = telemetry.increment(feature) {
+            warn!(target: "metrics", "Failed to record telemetry: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_telemetry() -> (Telemetry, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let telemetry = Telemetry::new(temp_dir.path().to_path_buf());
+        (telemetry, temp_dir)
+    }
+
+    #[test]
+    fn test_counts_persist() {
+        let (telemetry, temp) = create_test_telemetry();
+        telemetry.increment(Feature::MessageSent).unwrap();
+        telemetry.increment(Feature::MessageSent).unwrap();
+        telemetry.increment(Feature::QuickPrompt).unwrap();
+
+        let reloaded = Telemetry::new(temp.path().to_path_buf());
+        reloaded.load().unwrap();
+        let report = reloaded.collected();
+        assert_eq!(report.counters[&Feature::MessageSent], 2);
+        assert_eq!(report.counters[&Feature::QuickPrompt], 1);
+        assert_eq!(report.os, std::env::consts::OS);
+    }
+
+    #[test]
+    fn test_report_contains_only_counts() {
+        let (telemetry, _temp) = create_test_telemetry();
+        telemetry.increment(Feature::SessionCreated).unwrap();
+        let json = serde_json::to_value(telemetry.collected()).unwrap();
+        let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(
+            keys,
+            vec![
+                "app_version",
+                "counters",
+                "os",
+                "period_end",
+                "period_start"
+            ]
+        );
+        assert_eq!(json["counters"]["session_created"], 1);
+    }
+
+    #[test]
+    fn test_mark_uploaded_keeps_newer_counts() {
+        let (telemetry, _temp) = create_test_telemetry();
+        telemetry.increment(Feature::MessageSent).unwrap();
+        let report = telemetry.collected();
+        telemetry.increment(Feature::MessageSent).unwrap();
+
+        telemetry.mark_uploaded(&report).unwrap();
+        let after = telemetry.collected();
+        assert_eq!(after.counters[&Feature::MessageSent], 1);
+        assert_eq!(after.period_start, report.period_end);
+        assert!(!telemetry.upload_due(Utc::now()));
+        assert!(telemetry.upload_due(Utc::now() + Duration::hours(UPLOAD_INTERVAL_HOURS)));
+    }
+
+    #[test]
+    fn test_nothing_due_without_counts() {
+        let (telemetry, _temp) = create_test_telemetry();
+        assert!(!telemetry.upload_due(Utc::now()));
+        telemetry.increment(Feature::UpdateCheck).unwrap();
+        assert!(telemetry.upload_due(Utc::now()));
+        telemetry.clear().unwrap();
+        assert!(telemetry.collected().counters.is_empty());
+    }
+}
@@ -0,0 +1,431 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Shells opened on a pseudo-terminal in a workspace directory, with output
+//! streamed to the frontend, so interactive and full-screen programs behave
+//! as they do in any terminal emulator. Shells run on this machine; remote
+//! servers are refused until they can be reached over SSH.
+
+use crate::error::AppError;
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Tauri event with a `TerminalEvent`
+pub const TERMINAL_EVENT: &str = "terminal-event";
+
+/// Terminals open at once
+pub const MAX_TERMINALS: usize = 8;
+
+/// Bytes read from the terminal per output event
+const READ_CHUNK_BYTES: usize = 4096;
+
+/// Size used until the frontend reports its own
+const DEFAULT_ROWS: u16 = 24;
+const DEFAULT_COLS: u16 = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TerminalEvent {
+    Output {
+        terminal_id: String,
+        data: String,
+    },
+    Exited {
+        terminal_id: String,
+        /// `None` when the shell was killed
+        code: Option<i32>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TerminalInfo {
+    pub id: String,
+    pub shell: String,
+    pub cwd: PathBuf,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+struct TerminalHandle {
+    info: TerminalInfo,
+    master: Box<dyn MasterPty + Send>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    killed: Arc<AtomicBool>,
+}
+
+/// The user's login shell, or the platform default
+pub fn default_shell() -> String {
+    if cfg!(windows) {
+        std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
+    } else {
+        std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty())
+            .unwrap_or_else(|| "/bin/sh".to_string())
+    }
+}
+
+/// Whether `server_url` points at this machine, where a local shell reaches
+/// the server's files
+pub fn is_local_server(server_url: &str) -> bool {
+    let Ok(url) = url::Url::parse(server_url) else {
+        return false;
+    };
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn terminal_size(rows: u16, cols: u16) -> Result<PtySize, AppError> {
+    if rows == 0 || cols == 0 {
+        return Err(AppError::ValidationError {
+            field: "size".to_string(),
+            message: format!("Terminal size must be positive, got {}x{}", cols, rows),
+        });
+    }
+    Ok(PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    })
+}
+
+fn pty_error(message: &str, error: impl std::fmt::Display) -> AppError {
+    AppError::IoError {
+        message: message.to_string(),
+        details: Some(error.to_string()),
+    }
+}
+
+/// Open terminals by id. Clones share the same terminals.
+#[derive(Clone, Default)]
+pub struct Terminals {
+    open: Arc<AsyncMutex<HashMap<String, TerminalHandle>>>,
+}
+
+impl Terminals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start `shell` in `cwd` on a new pseudo-terminal, `rows` by `cols`
+    /// when given. `on_event` receives its output as it arrives and, last,
+    /// its exit.
+    pub async fn open<F>(
+        &self,
+        cwd: &Path,
+        shell: &str,
+        size: Option<(u16, u16)>,
+        on_event: F,
+    ) -> Result<TerminalInfo, AppError>
+    where
+        F: Fn(TerminalEvent) + Send + Sync + 'static,
+    {
+        if !cwd.is_dir() {
+            return Err(AppError::ValidationError {
+                field: "cwd".to_string(),
+                message: format!("Not a directory on this machine: {}", cwd.display()),
+            });
+        }
+        let (rows, cols) = size.unwrap_or((DEFAULT_ROWS, DEFAULT_COLS));
+        let size = terminal_size(rows, cols)?;
+        let mut open = self.open.lock().await;
+        if open.len() >= MAX_TERMINALS {
+            return Err(AppError::ValidationError {
+                field: "terminal".to_string(),
+                message: format!("At most {} terminals can be open", MAX_TERMINALS),
+            });
+        }
+
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(|e| pty_error("Failed to open a pseudo-terminal", e))?;
+        let mut command = CommandBuilder::new(shell);
+        command.cwd(cwd);
+        command.env("TERM", "xterm-256color");
+        let mut child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(|e| pty_error(&format!("Failed to start {}", shell), e))?;
+        // Only the shell may hold the slave, so reads end when it exits
+        drop(pair.slave);
+        let attach_error = |e| pty_error(&format!("Failed to attach to {}", shell), e);
+        let reader = pair.master.try_clone_reader().map_err(attach_error)?;
+        let writer = pair.master.take_writer().map_err(attach_error)?;
+        let killer = child.clone_killer();
+
+        let info = TerminalInfo {
+            id: uuid::Uuid::new_v4().to_string(),
+            shell: shell.to_string(),
+            cwd: cwd.to_path_buf(),
+            started_at: chrono::Utc::now(),
+        };
+        let on_event = Arc::new(on_event);
+        let output_events = on_event.clone();
+        let terminal_id = info.id.clone();
+        let output = std::thread::spawn(move || forward_output(reader, terminal_id, output_events));
+
+        let killed = Arc::new(AtomicBool::new(false));
+        let was_killed = killed.clone();
+        let terminals = self.open.clone();
+        let terminal_id = info.id.clone();
+        std::thread::spawn(move || {
+            let status = child.wait();
+            // Closing the master ends reads on platforms that keep the
+            // terminal readable after the shell exits
+            terminals.blocking_lock().remove(&terminal_id);
+            // Deliver the last output before the exit
+            let _ = output.join();
+            let code = status
+                .ok()
+                .filter(|_| !was_killed.load(Ordering::SeqCst))
+                .map(|status| status.exit_code() as i32);
+            on_event(TerminalEvent::Exited { terminal_id, code });
+        });
+
+        open.insert(
+            info.id.clone(),
+            TerminalHandle {
+                info: info.clone(),
+                master: pair.master,
+                writer: Arc::new(Mutex::new(writer)),
+                killer,
+                killed,
+            },
+        );
+        Ok(info)
+    }
+
+    /// Send input to a terminal, e.g. typed keys or a pasted command
+    pub async fn write(&self, terminal_id: &str, data: &str) -> Result<(), AppError> {
+        let writer = self
+            .open
+            .lock()
+            .await
+            .get(terminal_id)
+            .map(|handle| handle.writer.clone())
+            .ok_or_else(|| not_found(terminal_id))?;
+        let data = data.as_bytes().to_vec();
+        // Writes block while the program isn't reading its input
+        tokio::task::spawn_blocking(move || {
+            let mut writer = writer
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            writer.write_all(&data).and_then(|()| writer.flush())
+        })
+        .await
+        .map_err(|e| pty_error("Failed to write to terminal", e))?
+        .map_err(|e| pty_error("Failed to write to terminal", e))
+    }
+
+    /// Tell a terminal's programs the frontend now shows `rows` by `cols`
+    pub async fn resize(&self, terminal_id: &str, rows: u16, cols: u16) -> Result<(), AppError> {
+        let size = terminal_size(rows, cols)?;
+        let open = self.open.lock().await;
+        let handle = open
+            .get(terminal_id)
+            .ok_or_else(|| not_found(terminal_id))?;
+        handle
+            .master
+            .resize(size)
+            .map_err(|e| pty_error("Failed to resize terminal", e))
+    }
+
+    /// Kill a terminal's shell; its exit is still reported
+    pub async fn close(&self, terminal_id: &str) -> Result<(), AppError> {
+        let mut handle = self
+            .open
+            .lock()
+            .await
+            .remove(terminal_id)
+            .ok_or_else(|| not_found(terminal_id))?;
+        handle.killed.store(true, Ordering::SeqCst);
+        handle
+            .killer
+            .kill()
+            .map_err(|e| pty_error("Failed to close terminal", e))
+    }
+
+    pub async fn list(&self) -> Vec<TerminalInfo> {
+        let mut terminals: Vec<TerminalInfo> = self
+            .open
+            .lock()
+            .await
+            .values()
+            .map(|handle| handle.info.clone())
+            .collect();
+        terminals.sort_by_key(|terminal| terminal.started_at);
+        terminals
+    }
+}
+
+fn not_found(terminal_id: &str) -> AppError {
+    AppError::ValidationError {
+        field: "terminal_id".to_string(),
+        message: format!("No open terminal {}", terminal_id),
+    }
+}
+
+fn forward_output<F>(mut reader: Box<dyn Read + Send>, terminal_id: String, on_event: Arc<F>)
+where
+    F: Fn(TerminalEvent) + Send + Sync + 'static,
+{
+    let mut buffer = vec![0u8; READ_CHUNK_BYTES];
+    // Bytes of a UTF-8 character split across reads
+    let mut pending = Vec::new();
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        pending.extend_from_slice(&buffer[..read]);
+        let complete = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        let data = String::from_utf8_lossy(&pending[..complete]).to_string();
+        pending.drain(..complete);
+        if !data.is_empty() {
+            on_event(TerminalEvent::Output {
+                terminal_id: terminal_id.clone(),
+                data,
+            });
+        }
+    }
+    if !pending.is_empty() {
+        on_event(TerminalEvent::Output {
+            terminal_id,
+            data: String::from_utf8_lossy(&pending).to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::sync::mpsc;
+
+    async fn collect_until_exit(
+        mut events: mpsc::UnboundedReceiver<TerminalEvent>,
+    ) -> (String, Option<i32>) {
+        let mut output = String::new();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(10), events.recv())
+                .await
+                .expect("Terminal should exit")
+                .expect("Events should arrive");
+            match event {
+                TerminalEvent::Output { data, .. } => output.push_str(&data),
+                TerminalEvent::Exited { code, .. } => return (output, code),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_runs_commands_in_cwd_on_a_tty() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("marker.txt"), "").unwrap();
+        let terminals = Terminals::new();
+        let (sender, events) = mpsc::unbounded_channel();
+
+        let info = terminals
+            .open(temp.path(), "/bin/sh", Some((30, 100)), move |event| {
+                let _ = sender.send(event);
+            })
+            .await
+            .expect("Should open a terminal");
+        assert_eq!(terminals.list().await.len(), 1);
+
+        // The terminal echoes input, so only the commands' output has the
+        // evaluated sums
+        terminals
+            .write(
+                &info.id,
+                "ls; [ -t 0 ] && echo tty-$((1+1)); stty size; exit 3\n",
+            )
+            .await
+            .unwrap();
+        let (output, code) = collect_until_exit(events).await;
+        assert!(output.contains("marker.txt"));
+        assert!(output.contains("tty-2"));
+        assert!(output.contains("30 100"));
+        assert_eq!(code, Some(3));
+        assert!(terminals.list().await.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_close_kills_the_shell() {
+        let temp = TempDir::new().unwrap();
+        let terminals = Terminals::new();
+        let (sender, events) = mpsc::unbounded_channel();
+        let info = terminals
+            .open(temp.path(), "/bin/sh", None, move |event| {
+                let _ = sender.send(event);
+            })
+            .await
+            .unwrap();
+
+        terminals.close(&info.id).await.unwrap();
+        let (_, code) = collect_until_exit(events).await;
+        assert_eq!(code, None);
+        assert!(terminals.write(&info.id, "echo\n").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_directory() {
+        let terminals = Terminals::new();
+        let result = terminals
+            .open(
+                Path::new("/definitely/not/here"),
+                &default_shell(),
+                None,
+                |_| {},
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_local_server() {
+        assert!(is_local_server("http://localhost:4096"));
+        assert!(is_local_server("http://127.0.0.1:4096"));
+        assert!(is_local_server("http://[::1]:4096"));
+        assert!(!is_local_server("https://opencode.example.com"));
+        assert!(!is_local_server("http://192.168.1.20:4096"));
+        assert!(!is_local_server("not a url"));
+    }
+}
@@ -0,0 +1,446 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Speech to text for the prompt box, through an OpenAI-compatible
+//! `audio/transcriptions` endpoint.
+
+use crate::certificate_pinning;
+use crate::error::AppError;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Tauri event with `TranscriptionProgress` as text comes in
+pub const TRANSCRIPTION_EVENT: &str = "transcription-progress";
+
+/// Secret holding the key for a custom transcription endpoint
+pub const TRANSCRIPTION_API_KEY: &str = "transcription/api_key";
+
+/// Upload limit of the Whisper API
+pub const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+/// Path on the connected server used when no endpoint is configured
+const SERVER_TRANSCRIPTION_PATH: &str = "audio/transcriptions";
+
+/// Where and how recorded audio is transcribed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    /// Whisper-compatible URL; unset uses the connected server
+    pub endpoint: Option<String>,
+    pub model: String,
+    /// ISO-639-1 hint such as `en`; unset lets the model detect it
+    pub language: Option<String>,
+    /// Ask for partial transcripts as they are produced. Only some
+    /// models support this, so it is off by default.
+    pub stream: bool,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            model: "whisper-1".to_string(),
+            language: None,
+            stream: false,
+        }
+    }
+}
+
+impl TranscriptionSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(endpoint) = &self.endpoint {
+            let valid = url::Url::parse(endpoint)
+                .map(|url| matches!(url.scheme(), "http" | "https"))
+                .unwrap_or(false);
+            if !valid {
+                return Err(AppError::ValidationError {
+                    field: "transcription.endpoint".to_string(),
+                    message: "Transcription endpoint must be an http(s) URL".to_string(),
+                });
+            }
+        }
+        if self.model.trim().is_empty() {
+            return Err(AppError::ValidationError {
+                field: "transcription.model".to_string(),
+                message: "Transcription model is required".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The configured endpoint, or the server's own
+    pub fn endpoint_for(&self, server_url: Option<&str>) -> Option<String> {
+        self.endpoint.clone().or_else(|| {
+            server_url.map(|url| {
+                format!(
+                    "{}/{}",
+                    url.trim_end_matches('/'),
+                    SERVER_TRANSCRIPTION_PATH
+                )
+            })
+        })
+    }
+}
+
+/// Recorded audio, either a file the recorder saved or raw bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AudioSource {
+    Path {
+        path: String,
+    },
+    Bytes {
+        data: Vec<u8>,
+        /// Used for the format; defaults to `recording.webm`
+        #[serde(default)]
+        file_name: Option<String>,
+    },
+}
+
+/// Audio ready to upload
+#[derive(Debug, Clone)]
+pub struct AudioClip {
+    pub file_name: String,
+    pub mime: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Partial or final transcript of one `transcribe_audio` call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptionProgress {
+    pub transcription_id: String,
+    /// Everything transcribed so far
+    pub text: String,
+    pub done: bool,
+}
+
+fn audio_mime(file_name: &str) -> Option<&'static str> {
+    let extension = Path::new(file_name)
+        .extension()?
+        .to_string_lossy()
+        .to_lowercase();
+    Some(match extension.as_str() {
+        "wav" => "audio/wav",
+        "mp3" | "mpga" | "mpeg" => "audio/mpeg",
+        "m4a" | "mp4" => "audio/mp4",
+        "webm" => "audio/webm",
+        "ogg" | "oga" => "audio/ogg",
+        "flac" => "audio/flac",
+        _ => return None,
+    })
+}
+
+impl AudioClip {
+    pub fn load(source: AudioSource) -> Result<Self, AppError> {
+        let (file_name, data) = match source {
+            AudioSource::Path { path } => {
+                let path = Path::new(&path);
+                let metadata = std::fs::metadata(path).map_err(|e| AppError::FileSystemError {
+                    path: path.to_string_lossy().to_string(),
+                    message: "Failed to read recording".to_string(),
+                    details: e.to_string(),
+                })?;
+                if metadata.len() > MAX_AUDIO_BYTES as u64 {
+                    return Err(too_large());
+                }
+                let data = std::fs::read(path).map_err(|e| AppError::FileSystemError {
+                    path: path.to_string_lossy().to_string(),
+                    message: "Failed to read recording".to_string(),
+                    details: e.to_string(),
+                })?;
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                (file_name, data)
+            }
+            AudioSource::Bytes { data, file_name } => (
+                file_name.unwrap_or_else(|| "recording.webm".to_string()),
+                data,
+            ),
+        };
+
+        if data.is_empty() {
+            return Err(AppError::ValidationError {
+                field: "audio".to_string(),
+                message: "Recording is empty".to_string(),
+            });
+        }
+        if data.len() > MAX_AUDIO_BYTES {
+            return Err(too_large());
+        }
+        let mime = audio_mime(&file_name).ok_or_else(|| AppError::ValidationError {
+            field: "audio".to_string(),
+            message: format!("Unsupported audio format: {}", file_name),
+        })?;
+        Ok(Self {
+            file_name,
+            mime,
+            data,
+        })
+    }
+}
+
+fn too_large() -> AppError {
+    AppError::ValidationError {
+        field: "audio".to_string(),
+        message: format!(
+            "Recordings are limited to {} MB",
+            MAX_AUDIO_BYTES / (1024 * 1024)
+        ),
+    }
+}
+
+/// `multipart/form-data` body with text `fields` followed by the audio file
+fn multipart_body(boundary: &str, fields: &[(&str, String)], clip: &AudioClip) -> Vec<u8> {
+    let mut body = Vec::with_capacity(clip.data.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            clip.file_name.replace('"', ""),
+            clip.mime
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(&clip.data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Apply one server-sent event line to the transcript. Returns `true` once
+/// the final text has arrived.
+fn apply_stream_line(line: &str, text: &mut String) -> bool {
+    let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+        return false;
+    };
+    if data == "[DONE]" {
+        return true;
+    }
+    let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+        return false;
+    };
+    match event.get("type").and_then(|kind| kind.as_str()) {
+        Some("transcript.text.delta") => {
+            if let Some(delta) = event.get("delta").and_then(|delta| delta.as_str()) {
+                text.push_str(delta);
+            }
+            false
+        }
+        Some("transcript.text.done") => {
+            if let Some(final_text) = event.get("text").and_then(|text| text.as_str()) {
+                *text = final_text.to_string();
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Send `clip` to `endpoint` and return its text, calling `on_partial`
+/// with the transcript so far whenever it grows
+pub async fn transcribe<F>(
+    endpoint: &str,
+    api_key: Option<&str>,
+    settings: &TranscriptionSettings,
+    clip: &AudioClip,
+    mut on_partial: F,
+) -> Result<String, AppError>
+where
+    F: FnMut(&str),
+{
+    let boundary = format!("nexus-{}", uuid::Uuid::new_v4().simple());
+    let mut fields = vec![
+        ("model", settings.model.clone()),
+        ("response_format", "json".to_string()),
+    ];
+    if let Some(language) = &settings.language {
+        fields.push(("language", language.clone()));
+    }
+    if settings.stream {
+        fields.push(("stream", "true".to_string()));
+    }
+
+    let client = certificate_pinning::client_builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .build()?;
+    let mut request = client
+        .post(endpoint)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .body(multipart_body(&boundary, &fields, clip));
+    if let Some(api_key) = api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(AppError::ServerError {
+            status_code: response.status().as_u16(),
+            message: format!("Transcription failed with status: {}", response.status()),
+            details: response.text().await.unwrap_or_default(),
+        });
+    }
+
+    let is_event_stream = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    if !is_event_stream {
+        let body: serde_json::Value = response.json().await.map_err(|e| AppError::ParseError {
+            message: "Failed to parse transcription".to_string(),
+            details: Some(e.to_string()),
+        })?;
+        let text = body
+            .get("text")
+            .and_then(|text| text.as_str())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        on_partial(&text);
+        return Ok(text);
+    }
+
+    let mut text = String::new();
+    let mut pending = String::new();
+    let mut stream = response.bytes_stream();
+    'read: while let Some(chunk) = stream.next().await {
+        pending.push_str(&String::from_utf8_lossy(&chunk?));
+        while let Some(end) = pending.find('\n') {
+            let line: String = pending.drain(..=end).collect();
+            let before = text.len();
+            let done = apply_stream_line(line.trim_end(), &mut text);
+            if text.len() != before || done {
+                on_partial(&text);
+            }
+            if done {
+                break 'read;
+            }
+        }
+    }
+    Ok(text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_validates_audio() {
+        let clip = AudioClip::load(AudioSource::Bytes {
+            data: vec![1, 2, 3],
+            file_name: None,
+        })
+        .unwrap();
+        assert_eq!(clip.mime, "audio/webm");
+
+        let empty = AudioClip::load(AudioSource::Bytes {
+            data: Vec::new(),
+            file_name: None,
+        });
+        assert!(empty.is_err());
+
+        let unsupported = AudioClip::load(AudioSource::Bytes {
+            data: vec![1],
+            file_name: Some("notes.txt".to_string()),
+        });
+        assert!(unsupported.is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.M4A");
+        std::fs::write(&path, b"audio").unwrap();
+        let clip = AudioClip::load(AudioSource::Path {
+            path: path.to_string_lossy().to_string(),
+        })
+        .unwrap();
+        assert_eq!(clip.file_name, "memo.M4A");
+        assert_eq!(clip.mime, "audio/mp4");
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let clip = AudioClip {
+            file_name: "a.wav".to_string(),
+            mime: "audio/wav",
+            data: b"RIFF".to_vec(),
+        };
+        let body = multipart_body("b", &[("model", "whisper-1".to_string())], &clip);
+        let body = String::from_utf8(body).unwrap();
+        assert_eq!(
+            body,
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\n\
+             Content-Type: audio/wav\r\n\r\nRIFF\r\n--b--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_stream_line() {
+        let mut text = String::new();
+        assert!(!apply_stream_line(
+            r#"data: {"type":"transcript.text.delta","delta":"Hello"}"#,
+            &mut text
+        ));
+        assert!(!apply_stream_line(
+            r#"data: {"type":"transcript.text.delta","delta":" wor"}"#,
+            &mut text
+        ));
+        assert!(!apply_stream_line("event: ping", &mut text));
+        assert_eq!(text, "Hello wor");
+        assert!(apply_stream_line(
+            r#"data: {"type":"transcript.text.done","text":"Hello world"}"#,
+            &mut text
+        ));
+        assert_eq!(text, "Hello world");
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings = TranscriptionSettings::default();
+        assert!(settings.validate().is_ok());
+        assert_eq!(
+            settings
+                .endpoint_for(Some("http://localhost:4096/"))
+                .as_deref(),
+            Some("http://localhost:4096/audio/transcriptions")
+        );
+        assert_eq!(settings.endpoint_for(None), None);
+
+        let custom = TranscriptionSettings {
+            endpoint: Some("ftp://example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(custom.validate().is_err());
+    }
+}
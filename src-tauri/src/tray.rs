@@ -0,0 +1,357 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::connection_manager::ConnectionStatus;
+use crate::i18n::t;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+#[cfg(desktop)]
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+#[cfg(desktop)]
+use tauri::tray::TrayIconBuilder;
+#[cfg(desktop)]
+use tauri::{AppHandle, Wry};
+
+/// Id of the app's single tray icon
+pub const TRAY_ID: &str = "main";
+
+/// Tauri event with the id of a session picked from the tray
+pub const TRAY_OPEN_SESSION_EVENT: &str = "tray-open-session";
+
+/// Sessions listed under "Recent sessions"
+const MAX_RECENT_SESSIONS: usize = 5;
+
+/// Longest session title shown before it is shortened
+const MAX_TITLE_CHARS: usize = 40;
+
+const MENU_STATUS: &str = "status";
+const MENU_TOGGLE_CONNECTION: &str = "toggle-connection";
+const MENU_QUICK_PROMPT: &str = "quick-prompt";
+const MENU_RECENT_SESSIONS: &str = "recent-sessions";
+const MENU_NO_SESSIONS: &str = "no-sessions";
+const MENU_SHOW: &str = "show";
+const MENU_QUIT: &str = "quit";
+const SESSION_ITEM_PREFIX: &str = "session:";
+
+/// A session shortcut in the tray menu
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraySession {
+    pub id: String,
+    pub title: Option<String>,
+}
+
+/// What the tray currently shows
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrayStatus {
+    pub connection_status: ConnectionStatus,
+    /// Name of the current server connection, if any
+    pub server: Option<String>,
+    pub recent_sessions: Vec<TraySession>,
+}
+
+/// Something the user picked from the tray menu
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayAction {
+    /// Disconnect when connected, otherwise restore the last connection
+    ToggleConnection,
+    QuickPrompt,
+    OpenSession(String),
+    ShowWindow,
+    Quit,
+}
+
+impl TrayAction {
+    /// Map a menu item id back to its action
+    pub fn from_menu_id(id: &str) -> Option<Self> {
+        match id {
+            MENU_TOGGLE_CONNECTION => Some(TrayAction::ToggleConnection),
+            MENU_QUICK_PROMPT => Some(TrayAction::QuickPrompt),
+            MENU_SHOW => Some(TrayAction::ShowWindow),
+            MENU_QUIT => Some(TrayAction::Quit),
+            _ => id
+                .strip_prefix(SESSION_ITEM_PREFIX)
+                .filter(|session_id| !session_id.is_empty())
+                .map(|session_id| TrayAction::OpenSession(session_id.to_string())),
+        }
+    }
+}
+
+/// Most recently used sessions, newest first
+#[derive(Clone, Default)]
+pub struct RecentSessions {
+    sessions: Arc<Mutex<Vec<TraySession>>>,
+}
+
+impl RecentSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<TraySession>> {
+        match self.sessions.lock() {
+            Ok(sessions) => sessions,
+            Err(poisoned) => {
+                eprintln!("[ERROR] RecentSessions: mutex poisoned, recovering...");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Move a session to the top of the list
+    pub fn touch(&self, session: TraySession) {
+        let mut sessions = self.lock();
+        sessions.retain(|existing| existing.id != session.id);
+        sessions.insert(0, session);
+        sessions.truncate(MAX_RECENT_SESSIONS);
+    }
+
+    /// Replace the list with the first sessions of a fresh listing
+    pub fn replace(&self, listed: Vec<TraySession>) {
+        let mut sessions = self.lock();
+        *sessions = listed;
+        sessions.truncate(MAX_RECENT_SESSIONS);
+    }
+
+    pub fn remove(&self, session_id: &str) {
+        self.lock().retain(|session| session.id != session_id);
+    }
+
+    pub fn list(&self) -> Vec<TraySession> {
+        self.lock().clone()
+    }
+}
+
+/// First line of the menu, e.g. "Connected to homelab"
+pub fn status_label(status: ConnectionStatus, server: Option<&str>) -> String {
+    match (status, server) {
+        (ConnectionStatus::Connected, Some(server)) => {
+            t("tray.status.connected", &[("server", server)])
+        }
+        (ConnectionStatus::Connected, None) => t("tray.status.connected", &[("server", "server")]),
+        (ConnectionStatus::Connecting, _) => t("tray.status.connecting", &[]),
+        (ConnectionStatus::Disconnected, _) => t("tray.status.disconnected", &[]),
+        (ConnectionStatus::Error, _) => t("tray.status.error", &[]),
+    }
+}
+
+/// Menu label for a session, shortened to fit the menu
+pub fn session_label(session: &TraySession) -> String {
+    let title = session
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| t("tray.untitled_session", &[]));
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title;
+    }
+    let shortened: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+#[cfg(desktop)]
+fn build_menu(app_handle: &AppHandle, status: &TrayStatus) -> tauri::Result<Menu<Wry>> {
+    let status_item = MenuItem::with_id(
+        app_handle,
+        MENU_STATUS,
+        status_label(status.connection_status, status.server.as_deref()),
+        false,
+        None::<&str>,
+    )?;
+    let toggle_label = if status.connection_status == ConnectionStatus::Connected {
+        t("tray.disconnect", &[])
+    } else {
+        t("tray.reconnect", &[])
+    };
+    let toggle_item = MenuItem::with_id(
+        app_handle,
+        MENU_TOGGLE_CONNECTION,
+        toggle_label,
+        status.connection_status != ConnectionStatus::Connecting,
+        None::<&str>,
+    )?;
+    let quick_prompt_item = MenuItem::with_id(
+        app_handle,
+        MENU_QUICK_PROMPT,
+        t("tray.quick_prompt", &[]),
+        true,
+        None::<&str>,
+    )?;
+
+    let mut session_items = Vec::new();
+    for session in &status.recent_sessions {
+        session_items.push(MenuItem::with_id(
+            app_handle,
+            format!("{}{}", SESSION_ITEM_PREFIX, session.id),
+            session_label(session),
+            true,
+            None::<&str>,
+        )?);
+    }
+    if session_items.is_empty() {
+        session_items.push(MenuItem::with_id(
+            app_handle,
+            MENU_NO_SESSIONS,
+            t("tray.no_sessions", &[]),
+            false,
+            None::<&str>,
+        )?);
+    }
+    let session_refs: Vec<&dyn IsMenuItem<Wry>> = session_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let sessions_menu = Submenu::with_id_and_items(
+        app_handle,
+        MENU_RECENT_SESSIONS,
+        t("tray.recent_sessions", &[]),
+        true,
+        &session_refs,
+    )?;
+
+    let show_item = MenuItem::with_id(
+        app_handle,
+        MENU_SHOW,
+        t("tray.show", &[]),
+        true,
+        None::<&str>,
+    )?;
+    let quit_item = MenuItem::with_id(
+        app_handle,
+        MENU_QUIT,
+        t("tray.quit", &[]),
+        true,
+        None::<&str>,
+    )?;
+
+    Menu::with_items(
+        app_handle,
+        &[
+            &status_item,
+            &toggle_item,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &quick_prompt_item,
+            &sessions_menu,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &show_item,
+            &quit_item,
+        ],
+    )
+}
+
+/// Create the tray icon; `on_action` runs for every menu pick
+#[cfg(desktop)]
+pub fn create<F>(app_handle: &AppHandle, status: &TrayStatus, on_action: F) -> tauri::Result<()>
+where
+    F: Fn(&AppHandle, TrayAction) + Send + Sync + 'static,
+{
+    let menu = build_menu(app_handle, status)?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(status_label(
+            status.connection_status,
+            status.server.as_deref(),
+        ))
+        .show_menu_on_left_click(true)
+        .on_menu_event(move |app_handle, event| {
+            if let Some(action) = TrayAction::from_menu_id(event.id.as_ref()) {
+                on_action(app_handle, action);
+            }
+        });
+    if let Some(icon) = app_handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app_handle)?;
+    Ok(())
+}
+
+/// Rebuild the menu and tooltip of an existing tray icon
+#[cfg(desktop)]
+pub fn update(app_handle: &AppHandle, status: &TrayStatus) -> tauri::Result<()> {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    tray.set_menu(Some(build_menu(app_handle, status)?))?;
+    tray.set_tooltip(Some(status_label(
+        status.connection_status,
+        status.server.as_deref(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_session(id: &str) -> TraySession {
+        TraySession {
+            id: id.to_string(),
+            title: Some(format!("Session {}", id)),
+        }
+    }
+
+    #[test]
+    fn test_menu_ids_map_to_actions() {
+        assert_eq!(
+            TrayAction::from_menu_id(MENU_TOGGLE_CONNECTION),
+            Some(TrayAction::ToggleConnection)
+        );
+        assert_eq!(
+            TrayAction::from_menu_id("session:ses_123"),
+            Some(TrayAction::OpenSession("ses_123".to_string()))
+        );
+        assert_eq!(TrayAction::from_menu_id("session:"), None);
+        assert_eq!(TrayAction::from_menu_id(MENU_STATUS), None);
+    }
+
+    #[test]
+    fn test_recent_sessions_are_capped_and_deduplicated() {
+        let recent = RecentSessions::new();
+        for id in ["a", "b", "c", "d", "e", "f"] {
+            recent.touch(create_test_session(id));
+        }
+        recent.touch(create_test_session("d"));
+
+        let ids: Vec<String> = recent.list().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec!["d", "f", "e", "c", "b"]);
+
+        recent.remove("f");
+        assert_eq!(recent.list().len(), 4);
+    }
+
+    #[test]
+    fn test_session_label_shortens_long_titles() {
+        let long = TraySession {
+            id: "a".to_string(),
+            title: Some("x".repeat(60)),
+        };
+        let label = session_label(&long);
+        assert_eq!(label.chars().count(), MAX_TITLE_CHARS);
+        assert!(label.ends_with('…'));
+
+        let untitled = TraySession {
+            id: "b".to_string(),
+            title: Some("  ".to_string()),
+        };
+        assert_eq!(session_label(&untitled), "Untitled session");
+    }
+}
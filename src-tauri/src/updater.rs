@@ -0,0 +1,275 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+#[cfg(desktop)]
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+
+/// Tauri event with `UpdateProgress` while an update downloads
+pub const UPDATE_PROGRESS_EVENT: &str = "update-download-progress";
+
+/// Tauri event with `UpdateInfo` when a startup check finds a new version
+pub const UPDATE_AVAILABLE_EVENT: &str = "update-available";
+
+/// Manifest for the stable channel, published with every release
+const STABLE_MANIFEST_URL: &str =
+    "https://github.com/ferg-cod3s/opencode-nexus/releases/latest/download/latest.json";
+
+/// Manifest for the beta channel, re-pointed by each pre-release
+const BETA_MANIFEST_URL: &str =
+    "https://github.com/ferg-cod3s/opencode-nexus/releases/download/beta/latest.json";
+
+/// Minisign public key (the base64 `.pub` file) that release bundles are
+/// signed with. Set by the release pipeline; builds without it cannot update.
+const UPDATER_PUBLIC_KEY: Option<&str> = option_env!("OPENCODE_NEXUS_UPDATER_PUBKEY");
+
+/// Release channel to follow
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases as well as stable releases
+    Beta,
+}
+
+impl UpdateChannel {
+    fn manifest_url(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => STABLE_MANIFEST_URL,
+            UpdateChannel::Beta => BETA_MANIFEST_URL,
+        }
+    }
+}
+
+/// A newer version available for this platform
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+}
+
+/// Download progress; `total` is unknown when the server sends no length
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+/// A verified bundle waiting for "restart to update"
+#[derive(Clone)]
+pub struct DownloadedUpdate {
+    pub info: UpdateInfo,
+    #[cfg(desktop)]
+    update: Update,
+    #[cfg(desktop)]
+    bytes: Vec<u8>,
+}
+
+/// Whether this build can install updates at all
+pub fn is_configured() -> bool {
+    cfg!(desktop) && UPDATER_PUBLIC_KEY.is_some()
+}
+
+/// Whether `candidate` should replace `current`. Pre-release versions are
+/// only offered on the beta channel.
+pub fn offers(
+    current: &semver::Version,
+    candidate: &semver::Version,
+    channel: UpdateChannel,
+) -> bool {
+    candidate > current && (candidate.pre.is_empty() || channel == UpdateChannel::Beta)
+}
+
+/// Updater for the channel's manifest, checking signatures against the key
+/// this build was made with
+#[cfg(desktop)]
+fn updater(app_handle: &AppHandle, channel: UpdateChannel) -> Result<Updater, AppError> {
+    let public_key = UPDATER_PUBLIC_KEY.ok_or_else(|| AppError::ValidationError {
+        field: "updater".to_string(),
+        message: "This build has no update signing key".to_string(),
+    })?;
+    let endpoint = url::Url::parse(channel.manifest_url()).map_err(|e| AppError::ParseError {
+        message: "Invalid update manifest URL".to_string(),
+        details: Some(e.to_string()),
+    })?;
+
+    app_handle
+        .updater_builder()
+        .pubkey(public_key)
+        .endpoints(vec![endpoint])
+        .and_then(|builder| {
+            builder
+                .version_comparator(move |current, release| {
+                    offers(&current, &release.version, channel)
+                })
+                .build()
+        })
+        .map_err(updater_error)
+}
+
+#[cfg(desktop)]
+fn update_info(update: &Update, channel: UpdateChannel) -> UpdateInfo {
+    UpdateInfo {
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        channel,
+        notes: update.body.clone(),
+        pub_date: update
+            .raw_json
+            .get("pub_date")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string),
+    }
+}
+
+#[cfg(desktop)]
+fn updater_error(error: tauri_plugin_updater::Error) -> AppError {
+    AppError::Other {
+        message: format!("Update failed: {}", error),
+    }
+}
+
+/// Fetch the channel manifest and return an update for this platform
+#[cfg(desktop)]
+pub async fn check(
+    app_handle: &AppHandle,
+    channel: UpdateChannel,
+) -> Result<Option<UpdateInfo>, AppError> {
+    let update = updater(app_handle, channel)?
+        .check()
+        .await
+        .map_err(updater_error)?;
+    Ok(update.map(|update| update_info(&update, channel)))
+}
+
+/// Download the bundle for `info`, reporting progress. The updater rejects
+/// bundles whose signature doesn't match before handing them back.
+#[cfg(desktop)]
+pub async fn download<F>(
+    app_handle: &AppHandle,
+    info: &UpdateInfo,
+    mut on_progress: F,
+) -> Result<DownloadedUpdate, AppError>
+where
+    F: FnMut(UpdateProgress),
+{
+    let update = updater(app_handle, info.channel)?
+        .check()
+        .await
+        .map_err(updater_error)?
+        .filter(|update| update.version == info.version)
+        .ok_or_else(|| AppError::ValidationError {
+            field: "updater".to_string(),
+            message: format!("Version {} is no longer offered", info.version),
+        })?;
+
+    let mut downloaded = 0;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                on_progress(UpdateProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(updater_error)?;
+
+    Ok(DownloadedUpdate {
+        info: update_info(&update, info.channel),
+        update,
+        bytes,
+    })
+}
+
+/// Put a downloaded bundle in place of the running app. On Windows the
+/// installer takes over and the app exits before this returns.
+#[cfg(desktop)]
+pub fn install(update: &DownloadedUpdate) -> Result<(), AppError> {
+    update.update.install(&update.bytes).map_err(updater_error)
+}
+
+#[cfg(mobile)]
+fn unavailable() -> AppError {
+    AppError::ValidationError {
+        field: "updater".to_string(),
+        message: "Updates are delivered through the app store on this platform".to_string(),
+    }
+}
+
+#[cfg(mobile)]
+pub async fn check(
+    _app_handle: &AppHandle,
+    _channel: UpdateChannel,
+) -> Result<Option<UpdateInfo>, AppError> {
+    Err(unavailable())
+}
+
+#[cfg(mobile)]
+pub async fn download<F>(
+    _app_handle: &AppHandle,
+    _info: &UpdateInfo,
+    _on_progress: F,
+) -> Result<DownloadedUpdate, AppError>
+where
+    F: FnMut(UpdateProgress),
+{
+    Err(unavailable())
+}
+
+#[cfg(mobile)]
+pub fn install(_update: &DownloadedUpdate) -> Result<(), AppError> {
+    Err(unavailable())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> semver::Version {
+        semver::Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn test_offers_newer_versions() {
+        let current = version("0.1.30");
+
+        assert!(offers(&current, &version("0.2.0"), UpdateChannel::Stable));
+        assert!(!offers(&current, &version("0.1.30"), UpdateChannel::Stable));
+        assert!(!offers(&current, &version("0.1.29"), UpdateChannel::Beta));
+    }
+
+    #[test]
+    fn test_prereleases_only_on_beta() {
+        let current = version("0.1.30");
+        let beta = version("0.2.0-beta.1");
+
+        assert!(!offers(&current, &beta, UpdateChannel::Stable));
+        assert!(offers(&current, &beta, UpdateChannel::Beta));
+    }
+}
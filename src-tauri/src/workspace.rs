@@ -0,0 +1,387 @@
+// MIT License
+//
+// Copyright (c) 2025 OpenCode Nexus Contributors
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A local codebase and the sessions that belong to it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    /// Canonical path of the project directory
+    pub directory: PathBuf,
+    /// Server dedicated to this workspace; the active connection when `None`
+    #[serde(default)]
+    pub server_url: Option<String>,
+    #[serde(default)]
+    pub session_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_opened_at: Option<DateTime<Utc>>,
+}
+
+/// On-disk shape of `workspaces.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WorkspaceFile {
+    #[serde(default)]
+    workspaces: Vec<Workspace>,
+    #[serde(default)]
+    active_workspace_id: Option<String>,
+}
+
+/// Persistent list of workspaces and the one currently open
+#[derive(Clone)]
+pub struct WorkspaceStore {
+    config_dir: PathBuf,
+    data: Arc<Mutex<WorkspaceFile>>,
+}
+
+impl WorkspaceStore {
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            data: Arc::new(Mutex::new(WorkspaceFile::default())),
+        }
+    }
+
+    fn get_workspaces_file_path(&self) -> PathBuf {
+        self.config_dir.join("workspaces.json")
+    }
+
+    fn lock_data(&self) -> MutexGuard<'_, WorkspaceFile> {
+        self.data.lock().unwrap_or_else(|poisoned| {
+            eprintln!("Workspace store lock was poisoned, recovering");
+            poisoned.into_inner()
+        })
+    }
+
+    pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let workspaces_file = self.get_workspaces_file_path();
+        if !workspaces_file.exists() {
+            return Ok(());
+        }
+
+        let workspaces_json =
+            std::fs::read_to_string(&workspaces_file).map_err(|e| AppError::FileSystemError {
+                path: workspaces_file.to_string_lossy().to_string(),
+                message: "Failed to read workspaces file".to_string(),
+                details: e.to_string(),
+            })?;
+        let loaded: WorkspaceFile =
+            serde_json::from_str(&workspaces_json).map_err(|e| AppError::ParseError {
+                message: "Failed to parse workspaces file".to_string(),
+                details: Some(e.to_string()),
+            })?;
+
+        *self.lock_data() = loaded;
+        Ok(())
+    }
+
+    fn save(&self, data: &WorkspaceFile) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(&self.config_dir).map_err(|e| AppError::FileSystemError {
+            path: self.config_dir.to_string_lossy().to_string(),
+            message: "Failed to create config directory".to_string(),
+            details: e.to_string(),
+        })?;
+
+        let workspaces_json = serde_json::to_string_pretty(data)?;
+        std::fs::write(self.get_workspaces_file_path(), workspaces_json).map_err(|e| {
+            AppError::FileSystemError {
+                path: self
+                    .get_workspaces_file_path()
+                    .to_string_lossy()
+                    .to_string(),
+                message: "Failed to write workspaces file".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Apply `f` to the workspace list and persist the result
+    fn modify<T>(
+        &self,
+        f: impl FnOnce(&mut WorkspaceFile) -> Result<T, AppError>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let mut data = self.lock_data();
+        let mut updated = data.clone();
+        let result = f(&mut updated)?;
+        self.save(&updated)?;
+        *data = updated;
+        Ok(result)
+    }
+
+    /// Workspaces, most recently opened first
+    pub fn list(&self) -> Vec<Workspace> {
+        let mut workspaces = self.lock_data().workspaces.clone();
+        workspaces.sort_by(|a, b| {
+            b.last_opened_at
+                .unwrap_or(b.created_at)
+                .cmp(&a.last_opened_at.unwrap_or(a.created_at))
+        });
+        workspaces
+    }
+
+    pub fn get(&self, workspace_id: &str) -> Option<Workspace> {
+        self.lock_data()
+            .workspaces
+            .iter()
+            .find(|workspace| workspace.id == workspace_id)
+            .cloned()
+    }
+
+    /// Workspace registered for `directory`, which must be canonical
+    pub fn find_by_directory(&self, directory: &Path) -> Option<Workspace> {
+        self.lock_data()
+            .workspaces
+            .iter()
+            .find(|workspace| workspace.directory == directory)
+            .cloned()
+    }
+
+    pub fn active(&self) -> Option<Workspace> {
+        let data = self.lock_data();
+        let active_id = data.active_workspace_id.as_deref()?;
+        data.workspaces
+            .iter()
+            .find(|workspace| workspace.id == active_id)
+            .cloned()
+    }
+
+    /// Register `directory` as a workspace; each directory may only be added once
+    pub fn create(
+        &self,
+        name: &str,
+        directory: &Path,
+        server_url: Option<String>,
+    ) -> Result<Workspace, Box<dyn std::error::Error>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::ValidationError {
+                field: "name".to_string(),
+                message: "Workspace name cannot be empty".to_string(),
+            }
+            .into());
+        }
+        let directory = directory
+            .canonicalize()
+            .ok()
+            .filter(|path| path.is_dir())
+            .ok_or_else(|| AppError::ValidationError {
+                field: "directory".to_string(),
+                message: format!("{} is not a directory", directory.display()),
+            })?;
+        let server_url = server_url
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty());
+
+        let workspace = Workspace {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            directory,
+            server_url,
+            session_ids: Vec::new(),
+            created_at: Utc::now(),
+            last_opened_at: None,
+        };
+        self.modify(|data| {
+            if let Some(existing) = data
+                .workspaces
+                .iter()
+                .find(|existing| existing.directory == workspace.directory)
+            {
+                return Err(AppError::ValidationError {
+                    field: "directory".to_string(),
+                    message: format!(
+                        "{} is already the workspace '{}'",
+                        workspace.directory.display(),
+                        existing.name
+                    ),
+                });
+            }
+            data.workspaces.push(workspace.clone());
+            Ok(())
+        })?;
+        Ok(workspace)
+    }
+
+    /// Make `workspace_id` the active workspace
+    pub fn open(&self, workspace_id: &str) -> Result<Workspace, Box<dyn std::error::Error>> {
+        self.modify(|data| {
+            let workspace = find_mut(data, workspace_id)?;
+            workspace.last_opened_at = Some(Utc::now());
+            let workspace = workspace.clone();
+            data.active_workspace_id = Some(workspace.id.clone());
+            Ok(workspace)
+        })
+    }
+
+    /// Forget a workspace; its sessions stay on the server
+    pub fn delete(&self, workspace_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.modify(|data| {
+            find_mut(data, workspace_id)?;
+            data.workspaces
+                .retain(|workspace| workspace.id != workspace_id);
+            if data.active_workspace_id.as_deref() == Some(workspace_id) {
+                data.active_workspace_id = None;
+            }
+            Ok(())
+        })
+    }
+
+    /// Move `session_id` into `workspace_id`; a session belongs to at most one workspace
+    pub fn add_session(
+        &self,
+        workspace_id: &str,
+        session_id: &str,
+    ) -> Result<Workspace, Box<dyn std::error::Error>> {
+        self.modify(|data| {
+            find_mut(data, workspace_id)?;
+            for workspace in &mut data.workspaces {
+                workspace.session_ids.retain(|id| id != session_id);
+            }
+            let workspace = find_mut(data, workspace_id)?;
+            workspace.session_ids.push(session_id.to_string());
+            Ok(workspace.clone())
+        })
+    }
+
+    /// Detach `session_id` from whichever workspace holds it
+    pub fn remove_session(&self, session_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.workspace_for_session(session_id).is_none() {
+            return Ok(());
+        }
+        self.modify(|data| {
+            for workspace in &mut data.workspaces {
+                workspace.session_ids.retain(|id| id != session_id);
+            }
+            Ok(())
+        })
+    }
+
+    pub fn workspace_for_session(&self, session_id: &str) -> Option<Workspace> {
+        self.lock_data()
+            .workspaces
+            .iter()
+            .find(|workspace| workspace.session_ids.iter().any(|id| id == session_id))
+            .cloned()
+    }
+}
+
+fn find_mut<'a>(
+    data: &'a mut WorkspaceFile,
+    workspace_id: &str,
+) -> Result<&'a mut Workspace, AppError> {
+    data.workspaces
+        .iter_mut()
+        .find(|workspace| workspace.id == workspace_id)
+        .ok_or_else(|| AppError::ValidationError {
+            field: "workspace_id".to_string(),
+            message: format!("Workspace not found: {}", workspace_id),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store() -> (TempDir, WorkspaceStore) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = WorkspaceStore::new(temp_dir.path().join("config"));
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_create_requires_existing_unique_directory() {
+        let (temp_dir, store) = store();
+        let project = temp_dir.path().join("project");
+        assert!(store.create("Project", &project, None).is_err());
+
+        std::fs::create_dir(&project).unwrap();
+        let workspace = store
+            .create(" Project ", &project, Some("http://localhost:4096/".into()))
+            .unwrap();
+        assert_eq!(workspace.name, "Project");
+        assert_eq!(workspace.directory, project.canonicalize().unwrap());
+        assert_eq!(
+            workspace.server_url.as_deref(),
+            Some("http://localhost:4096")
+        );
+        assert_eq!(
+            store.find_by_directory(&workspace.directory).unwrap().id,
+            workspace.id
+        );
+
+        assert!(store.create("Again", &project.join("."), None).is_err());
+        assert!(store.create("  ", temp_dir.path(), None).is_err());
+        assert_eq!(store.list().len(), 1);
+    }
+
+    #[test]
+    fn test_sessions_belong_to_one_workspace() {
+        let (temp_dir, store) = store();
+        let a = store.create("A", temp_dir.path(), None).unwrap();
+        std::fs::create_dir(temp_dir.path().join("b")).unwrap();
+        let b = store.create("B", &temp_dir.path().join("b"), None).unwrap();
+
+        store.add_session(&a.id, "ses_1").unwrap();
+        store.add_session(&b.id, "ses_1").unwrap();
+        assert!(store.get(&a.id).unwrap().session_ids.is_empty());
+        assert_eq!(store.workspace_for_session("ses_1").unwrap().id, b.id);
+
+        store.remove_session("ses_1").unwrap();
+        assert!(store.workspace_for_session("ses_1").is_none());
+        assert!(store.add_session("missing", "ses_2").is_err());
+    }
+
+    #[test]
+    fn test_open_and_delete_track_active_workspace() {
+        let (temp_dir, store) = store();
+        let workspace = store.create("A", temp_dir.path(), None).unwrap();
+        assert!(store.active().is_none());
+
+        let opened = store.open(&workspace.id).unwrap();
+        assert!(opened.last_opened_at.is_some());
+        assert_eq!(store.active().unwrap().id, workspace.id);
+
+        store.delete(&workspace.id).unwrap();
+        assert!(store.active().is_none());
+        assert!(store.open(&workspace.id).is_err());
+    }
+
+    #[test]
+    fn test_workspaces_persist() {
+        let (temp_dir, store) = store();
+        let workspace = store.create("A", temp_dir.path(), None).unwrap();
+        store.add_session(&workspace.id, "ses_1").unwrap();
+        store.open(&workspace.id).unwrap();
+
+        let reloaded = WorkspaceStore::new(temp_dir.path().join("config"));
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.active().unwrap().session_ids, vec!["ses_1"]);
+    }
+}